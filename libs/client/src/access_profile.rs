@@ -0,0 +1,48 @@
+use crate::{ClientError, KanidmClient};
+use kanidm_proto::v1::{AccessCheckResponse, AccessProfile, Entry};
+
+impl KanidmClient {
+    pub async fn idm_access_profile_list(&self) -> Result<Vec<Entry>, ClientError> {
+        self.perform_get_request("/v1/access_profile").await
+    }
+
+    pub async fn idm_access_profile_get(&self, id: &str) -> Result<Option<Entry>, ClientError> {
+        self.perform_get_request(format!("/v1/access_profile/{}", id).as_str())
+            .await
+    }
+
+    pub async fn idm_access_profile_create(
+        &self,
+        profile: AccessProfile,
+    ) -> Result<(), ClientError> {
+        self.perform_post_request("/v1/access_profile", profile)
+            .await
+    }
+
+    pub async fn idm_access_profile_update(
+        &self,
+        id: &str,
+        profile: AccessProfile,
+    ) -> Result<(), ClientError> {
+        self.perform_patch_request(format!("/v1/access_profile/{}", id).as_str(), profile)
+            .await
+    }
+
+    pub async fn idm_access_profile_delete(&self, id: &str) -> Result<(), ClientError> {
+        self.perform_delete_request(format!("/v1/access_profile/{}", id).as_str())
+            .await
+    }
+
+    /// Evaluate the access that `subject` is granted to `target` by the current access
+    /// control profiles.
+    pub async fn idm_access_profile_check(
+        &self,
+        subject: &str,
+        target: &str,
+    ) -> Result<AccessCheckResponse, ClientError> {
+        self.perform_get_request(
+            format!("/v1/access_profile/_check/{}/{}", subject, target).as_str(),
+        )
+        .await
+    }
+}
@@ -0,0 +1,47 @@
+use crate::{ClientError, KanidmClient};
+use kanidm_proto::v1::{AccessRequest, AccessRequestCreate};
+use uuid::Uuid;
+
+impl KanidmClient {
+    /// List the access requests made by this account, and those that it may approve.
+    pub async fn idm_access_request_list(&self) -> Result<Vec<AccessRequest>, ClientError> {
+        self.perform_get_request("/v1/access_request").await
+    }
+
+    pub async fn idm_access_request_get(&self, id: &str) -> Result<AccessRequest, ClientError> {
+        self.perform_get_request(format!("/v1/access_request/{}", id).as_str())
+            .await
+    }
+
+    /// Request time limited membership of a group. If no duration is provided, the maximum
+    /// duration permitted by the group is requested.
+    pub async fn idm_access_request_create(
+        &self,
+        group: &str,
+        reason: Option<&str>,
+        duration: Option<u32>,
+    ) -> Result<Uuid, ClientError> {
+        let request = AccessRequestCreate {
+            group: group.to_string(),
+            reason: reason.map(str::to_string),
+            duration,
+        };
+        self.perform_post_request("/v1/access_request", request)
+            .await
+    }
+
+    pub async fn idm_access_request_approve(&self, id: &str) -> Result<(), ClientError> {
+        self.perform_post_request(format!("/v1/access_request/{}/_approve", id).as_str(), ())
+            .await
+    }
+
+    pub async fn idm_access_request_deny(&self, id: &str) -> Result<(), ClientError> {
+        self.perform_post_request(format!("/v1/access_request/{}/_deny", id).as_str(), ())
+            .await
+    }
+
+    pub async fn idm_access_request_withdraw(&self, id: &str) -> Result<(), ClientError> {
+        self.perform_post_request(format!("/v1/access_request/{}/_withdraw", id).as_str(), ())
+            .await
+    }
+}
@@ -38,12 +38,15 @@ use webauthn_rs_proto::{
     PublicKeyCredential, RegisterPublicKeyCredential, RequestChallengeResponse,
 };
 
+mod access_profile;
+mod access_request;
 mod oauth;
 mod person;
 mod scim;
 mod service_account;
 mod sync_account;
 mod system;
+mod webhook;
 
 pub const KOPID: &str = "X-KANIDM-OPID";
 pub const KSESSIONID: &str = "X-KANIDM-AUTH-SESSION-ID";
@@ -1451,6 +1454,35 @@ impl KanidmClient {
         self.perform_post_request("/v1/group", new_group).await
     }
 
+    /// Create a dynamic group, whose members are all entries that match `filter`.
+    pub async fn idm_group_create_dynamic(
+        &self,
+        name: &str,
+        filter: &Filter,
+    ) -> Result<(), ClientError> {
+        let filter = serde_json::to_string(filter).map_err(ClientError::JsonEncode)?;
+        let mut new_group = Entry {
+            attrs: BTreeMap::new(),
+        };
+        new_group
+            .attrs
+            .insert("name".to_string(), vec![name.to_string()]);
+        new_group
+            .attrs
+            .insert("dyngroup_filter".to_string(), vec![filter]);
+        self.perform_post_request("/v1/group/_dynamic", new_group)
+            .await
+    }
+
+    /// List the entries that would be members of a dynamic group using `filter`.
+    pub async fn idm_group_dynamic_preview(
+        &self,
+        filter: &Filter,
+    ) -> Result<Vec<String>, ClientError> {
+        self.perform_post_request("/v1/group/_dynamic/_preview", filter)
+            .await
+    }
+
     pub async fn idm_group_set_members(
         &self,
         id: &str,
@@ -1471,6 +1503,37 @@ impl KanidmClient {
             .await
     }
 
+    /// Set the period in which a member of the group is considered to be a member. Either
+    /// time is an rfc3339 timestamp. The validity should be set before the member is added
+    /// to the group, so that the membership is never in effect outside of this period.
+    pub async fn idm_group_set_member_validity(
+        &self,
+        id: &str,
+        member: &str,
+        not_before: Option<&str>,
+        not_after: Option<&str>,
+    ) -> Result<(), ClientError> {
+        let m = vec![format!(
+            "{},{},{}",
+            member,
+            not_before.unwrap_or_default(),
+            not_after.unwrap_or_default()
+        )];
+        self.perform_post_request(
+            format!("/v1/group/{}/_attr/member_validity", id).as_str(),
+            m,
+        )
+        .await
+    }
+
+    pub async fn idm_group_get_member_validity(
+        &self,
+        id: &str,
+    ) -> Result<Option<Vec<String>>, ClientError> {
+        self.perform_get_request(format!("/v1/group/{}/_attr/member_validity", id).as_str())
+            .await
+    }
+
     pub async fn idm_group_remove_members(
         &self,
         group: &str,
@@ -1498,6 +1561,60 @@ impl KanidmClient {
             .await
     }
 
+    pub async fn idm_group_set_entry_managed_by(
+        &self,
+        id: &str,
+        entry_managed_by: &str,
+    ) -> Result<(), ClientError> {
+        let m = vec![entry_managed_by.to_string()];
+        self.perform_put_request(
+            format!("/v1/group/{}/_attr/entry_managed_by", id).as_str(),
+            m,
+        )
+        .await
+    }
+
+    pub async fn idm_group_purge_entry_managed_by(&self, id: &str) -> Result<(), ClientError> {
+        self.perform_delete_request(format!("/v1/group/{}/_attr/entry_managed_by", id).as_str())
+            .await
+    }
+
+    pub async fn idm_group_set_access_request_approvers(
+        &self,
+        id: &str,
+        approvers: &[&str],
+    ) -> Result<(), ClientError> {
+        let m: Vec<_> = approvers.iter().map(|v| (*v).to_string()).collect();
+        self.perform_put_request(
+            format!("/v1/group/{}/_attr/access_request_approvers", id).as_str(),
+            m,
+        )
+        .await
+    }
+
+    pub async fn idm_group_purge_access_request_approvers(
+        &self,
+        id: &str,
+    ) -> Result<(), ClientError> {
+        self.perform_delete_request(
+            format!("/v1/group/{}/_attr/access_request_approvers", id).as_str(),
+        )
+        .await
+    }
+
+    pub async fn idm_group_set_access_request_max_duration(
+        &self,
+        id: &str,
+        seconds: u32,
+    ) -> Result<(), ClientError> {
+        let m = vec![seconds.to_string()];
+        self.perform_put_request(
+            format!("/v1/group/{}/_attr/access_request_max_duration", id).as_str(),
+            m,
+        )
+        .await
+    }
+
     pub async fn idm_group_unix_extend(
         &self,
         id: &str,
@@ -1791,11 +1908,188 @@ impl KanidmClient {
         self.perform_get_request("/v1/schema/classtype").await
     }
 
+    pub async fn idm_schema_attributetype_create(
+        &self,
+        name: &str,
+        description: &str,
+        syntax: &str,
+        multivalue: bool,
+        unique: bool,
+        unique_scope: Option<&str>,
+        index: Vec<String>,
+    ) -> Result<(), ClientError> {
+        let mut new_attr = Entry {
+            attrs: BTreeMap::new(),
+        };
+        new_attr
+            .attrs
+            .insert("attributename".to_string(), vec![name.to_string()]);
+        new_attr
+            .attrs
+            .insert("description".to_string(), vec![description.to_string()]);
+        new_attr
+            .attrs
+            .insert("syntax".to_string(), vec![syntax.to_string()]);
+        new_attr
+            .attrs
+            .insert("multivalue".to_string(), vec![multivalue.to_string()]);
+        new_attr
+            .attrs
+            .insert("unique".to_string(), vec![unique.to_string()]);
+        if let Some(unique_scope) = unique_scope {
+            new_attr
+                .attrs
+                .insert("unique_scope".to_string(), vec![unique_scope.to_string()]);
+        }
+        if !index.is_empty() {
+            new_attr.attrs.insert("index".to_string(), index);
+        }
+        self.perform_post_request("/v1/schema/attributetype", new_attr)
+            .await
+    }
+
+    pub async fn idm_schema_attributetype_update(
+        &self,
+        id: &str,
+        description: Option<&str>,
+        multivalue: Option<bool>,
+        unique: Option<bool>,
+        unique_scope: Option<&str>,
+        index: Option<Vec<String>>,
+    ) -> Result<(), ClientError> {
+        let mut update_attr = Entry {
+            attrs: BTreeMap::new(),
+        };
+        if let Some(description) = description {
+            update_attr
+                .attrs
+                .insert("description".to_string(), vec![description.to_string()]);
+        }
+        if let Some(multivalue) = multivalue {
+            update_attr
+                .attrs
+                .insert("multivalue".to_string(), vec![multivalue.to_string()]);
+        }
+        if let Some(unique) = unique {
+            update_attr
+                .attrs
+                .insert("unique".to_string(), vec![unique.to_string()]);
+        }
+        // An empty scope makes the attribute unique throughout the database again.
+        if let Some(unique_scope) = unique_scope {
+            let values = if unique_scope.is_empty() {
+                Vec::new()
+            } else {
+                vec![unique_scope.to_string()]
+            };
+            update_attr.attrs.insert("unique_scope".to_string(), values);
+        }
+        if let Some(index) = index {
+            update_attr.attrs.insert("index".to_string(), index);
+        }
+        self.perform_patch_request(
+            format!("/v1/schema/attributetype/{}", id).as_str(),
+            update_attr,
+        )
+        .await
+    }
+
+    pub async fn idm_schema_attributetype_deprecate(
+        &self,
+        id: &str,
+        deprecated: bool,
+    ) -> Result<(), ClientError> {
+        let mut update_attr = Entry {
+            attrs: BTreeMap::new(),
+        };
+        update_attr
+            .attrs
+            .insert("deprecated".to_string(), vec![deprecated.to_string()]);
+        self.perform_patch_request(
+            format!("/v1/schema/attributetype/{}", id).as_str(),
+            update_attr,
+        )
+        .await
+    }
+
     pub async fn idm_schema_classtype_get(&self, id: &str) -> Result<Option<Entry>, ClientError> {
         self.perform_get_request(format!("/v1/schema/classtype/{}", id).as_str())
             .await
     }
 
+    pub async fn idm_schema_classtype_create(
+        &self,
+        name: &str,
+        description: &str,
+        may: Vec<String>,
+        must: Vec<String>,
+    ) -> Result<(), ClientError> {
+        let mut new_class = Entry {
+            attrs: BTreeMap::new(),
+        };
+        new_class
+            .attrs
+            .insert("classname".to_string(), vec![name.to_string()]);
+        new_class
+            .attrs
+            .insert("description".to_string(), vec![description.to_string()]);
+        if !may.is_empty() {
+            new_class.attrs.insert("may".to_string(), may);
+        }
+        if !must.is_empty() {
+            new_class.attrs.insert("must".to_string(), must);
+        }
+        self.perform_post_request("/v1/schema/classtype", new_class)
+            .await
+    }
+
+    /// Update a class. The may and must lists replace the current values when provided.
+    pub async fn idm_schema_classtype_update(
+        &self,
+        id: &str,
+        description: Option<&str>,
+        may: Option<Vec<String>>,
+        must: Option<Vec<String>>,
+    ) -> Result<(), ClientError> {
+        let mut update_class = Entry {
+            attrs: BTreeMap::new(),
+        };
+        if let Some(description) = description {
+            update_class
+                .attrs
+                .insert("description".to_string(), vec![description.to_string()]);
+        }
+        if let Some(may) = may {
+            update_class.attrs.insert("may".to_string(), may);
+        }
+        if let Some(must) = must {
+            update_class.attrs.insert("must".to_string(), must);
+        }
+        self.perform_patch_request(
+            format!("/v1/schema/classtype/{}", id).as_str(),
+            update_class,
+        )
+        .await
+    }
+
+    pub async fn idm_schema_classtype_deprecate(
+        &self,
+        id: &str,
+        deprecated: bool,
+    ) -> Result<(), ClientError> {
+        let mut update_class = Entry {
+            attrs: BTreeMap::new(),
+        };
+        update_class
+            .attrs
+            .insert("deprecated".to_string(), vec![deprecated.to_string()]);
+        self.perform_patch_request(
+            format!("/v1/schema/classtype/{}", id).as_str(),
+            update_class,
+        )
+        .await
+    }
+
     // ==== recycle bin
     pub async fn recycle_bin_list(&self) -> Result<Vec<Entry>, ClientError> {
         self.perform_get_request("/v1/recycle_bin").await
@@ -1810,4 +2104,17 @@ impl KanidmClient {
         self.perform_post_request(format!("/v1/recycle_bin/{}/_revive", id).as_str(), ())
             .await
     }
+
+    pub async fn recycle_bin_revive_without_members(&self, id: &str) -> Result<(), ClientError> {
+        self.perform_post_request(
+            format!("/v1/recycle_bin/{}/_revive?with_members=false", id).as_str(),
+            (),
+        )
+        .await
+    }
+
+    pub async fn recycle_bin_purge(&self, id: &str) -> Result<(), ClientError> {
+        self.perform_post_request(format!("/v1/recycle_bin/{}/_purge", id).as_str(), ())
+            .await
+    }
 }
@@ -244,4 +244,152 @@ impl KanidmClient {
         self.perform_patch_request(format!("/v1/oauth2/{}", id).as_str(), update_oauth2_rs)
             .await
     }
+
+    pub async fn idm_oauth2_rs_require_pushed_authorisation(
+        &self,
+        id: &str,
+    ) -> Result<(), ClientError> {
+        let mut update_oauth2_rs = Entry {
+            attrs: BTreeMap::new(),
+        };
+        update_oauth2_rs.attrs.insert(
+            "oauth2_require_pushed_authorisation".to_string(),
+            vec!["true".to_string()],
+        );
+        self.perform_patch_request(format!("/v1/oauth2/{}", id).as_str(), update_oauth2_rs)
+            .await
+    }
+
+    pub async fn idm_oauth2_rs_allow_direct_authorisation(
+        &self,
+        id: &str,
+    ) -> Result<(), ClientError> {
+        let mut update_oauth2_rs = Entry {
+            attrs: BTreeMap::new(),
+        };
+        update_oauth2_rs.attrs.insert(
+            "oauth2_require_pushed_authorisation".to_string(),
+            vec!["false".to_string()],
+        );
+        self.perform_patch_request(format!("/v1/oauth2/{}", id).as_str(), update_oauth2_rs)
+            .await
+    }
+
+    pub async fn idm_oauth2_rs_disable_consent_prompt(&self, id: &str) -> Result<(), ClientError> {
+        let mut update_oauth2_rs = Entry {
+            attrs: BTreeMap::new(),
+        };
+        update_oauth2_rs.attrs.insert(
+            "oauth2_consent_prompt_disable".to_string(),
+            vec!["true".to_string()],
+        );
+        self.perform_patch_request(format!("/v1/oauth2/{}", id).as_str(), update_oauth2_rs)
+            .await
+    }
+
+    pub async fn idm_oauth2_rs_enable_consent_prompt(&self, id: &str) -> Result<(), ClientError> {
+        let mut update_oauth2_rs = Entry {
+            attrs: BTreeMap::new(),
+        };
+        update_oauth2_rs.attrs.insert(
+            "oauth2_consent_prompt_disable".to_string(),
+            vec!["false".to_string()],
+        );
+        self.perform_patch_request(format!("/v1/oauth2/{}", id).as_str(), update_oauth2_rs)
+            .await
+    }
+
+    pub async fn idm_oauth2_rs_add_token_exchange_source(
+        &self,
+        id: &str,
+        source: &str,
+    ) -> Result<(), ClientError> {
+        self.perform_post_request(
+            format!("/v1/oauth2/{}/_token_exchange_from/{}", id, source).as_str(),
+            (),
+        )
+        .await
+    }
+
+    pub async fn idm_oauth2_rs_delete_token_exchange_source(
+        &self,
+        id: &str,
+        source: &str,
+    ) -> Result<(), ClientError> {
+        self.perform_delete_request(
+            format!("/v1/oauth2/{}/_token_exchange_from/{}", id, source).as_str(),
+        )
+        .await
+    }
+
+    pub async fn idm_oauth2_rs_set_backchannel_logout_uri(
+        &self,
+        id: &str,
+        url: &str,
+    ) -> Result<(), ClientError> {
+        let mut update_oauth2_rs = Entry {
+            attrs: BTreeMap::new(),
+        };
+        update_oauth2_rs.attrs.insert(
+            "oauth2_rs_backchannel_logout_uri".to_string(),
+            vec![url.to_string()],
+        );
+        self.perform_patch_request(format!("/v1/oauth2/{}", id).as_str(), update_oauth2_rs)
+            .await
+    }
+
+    pub async fn idm_oauth2_rs_clear_backchannel_logout_uri(
+        &self,
+        id: &str,
+    ) -> Result<(), ClientError> {
+        let mut update_oauth2_rs = Entry {
+            attrs: BTreeMap::new(),
+        };
+        update_oauth2_rs
+            .attrs
+            .insert("oauth2_rs_backchannel_logout_uri".to_string(), Vec::new());
+        self.perform_patch_request(format!("/v1/oauth2/{}", id).as_str(), update_oauth2_rs)
+            .await
+    }
+
+    /// Set the json web key set this resource server signs client assertions with. Once
+    /// set, the resource server may no longer authenticate with its basic secret.
+    pub async fn idm_oauth2_rs_set_jwks(&self, id: &str, jwks: &str) -> Result<(), ClientError> {
+        let mut update_oauth2_rs = Entry {
+            attrs: BTreeMap::new(),
+        };
+        update_oauth2_rs
+            .attrs
+            .insert("oauth2_rs_jwks".to_string(), vec![jwks.to_string()]);
+        self.perform_patch_request(format!("/v1/oauth2/{}", id).as_str(), update_oauth2_rs)
+            .await
+    }
+
+    pub async fn idm_oauth2_rs_clear_jwks(&self, id: &str) -> Result<(), ClientError> {
+        let mut update_oauth2_rs = Entry {
+            attrs: BTreeMap::new(),
+        };
+        update_oauth2_rs
+            .attrs
+            .insert("oauth2_rs_jwks".to_string(), Vec::new());
+        self.perform_patch_request(format!("/v1/oauth2/{}", id).as_str(), update_oauth2_rs)
+            .await
+    }
+
+    /// Replace the thumbprints of the tls client certificates this resource server may
+    /// authenticate with.
+    pub async fn idm_oauth2_rs_set_tls_client_certs(
+        &self,
+        id: &str,
+        thumbprints: Vec<String>,
+    ) -> Result<(), ClientError> {
+        let mut update_oauth2_rs = Entry {
+            attrs: BTreeMap::new(),
+        };
+        update_oauth2_rs
+            .attrs
+            .insert("oauth2_rs_tls_client_cert".to_string(), thumbprints);
+        self.perform_patch_request(format!("/v1/oauth2/{}", id).as_str(), update_oauth2_rs)
+            .await
+    }
 }
@@ -1,7 +1,11 @@
 use std::collections::BTreeMap;
 
+use kanidm_proto::internal::{
+    CredentialResetEmailRequest, HelpdeskCredentialResetRequest, IdentifyUserRequest,
+    IdentifyUserResponse, Oauth2Grant,
+};
 use kanidm_proto::v1::{
-    AccountUnixExtend, CredentialStatus, Entry, SingleStringRequest, UatStatus,
+    AccountUnixExtend, CUIntentToken, CredentialStatus, Entry, SingleStringRequest, UatStatus,
 };
 use uuid::Uuid;
 
@@ -258,4 +262,69 @@ impl KanidmClient {
         )
         .await
     }
+
+    pub async fn idm_account_destroy_user_auth_token_all(
+        &self,
+        id: &str,
+    ) -> Result<(), ClientError> {
+        self.perform_delete_request(format!("/v1/account/{}/_user_auth_token", id).as_str())
+            .await
+    }
+
+    pub async fn idm_account_list_oauth2_grant(
+        &self,
+        id: &str,
+    ) -> Result<Vec<Oauth2Grant>, ClientError> {
+        self.perform_get_request(format!("/v1/account/{}/_oauth2_grant", id).as_str())
+            .await
+    }
+
+    pub async fn idm_account_revoke_oauth2_grant(
+        &self,
+        id: &str,
+        rs_name: &str,
+    ) -> Result<(), ClientError> {
+        self.perform_delete_request(
+            format!("/v1/account/{}/_oauth2_grant/{}", id, rs_name).as_str(),
+        )
+        .await
+    }
+
+    pub async fn idm_person_identify_user(
+        &self,
+        id: &str,
+        request: IdentifyUserRequest,
+    ) -> Result<IdentifyUserResponse, ClientError> {
+        self.perform_post_request(
+            format!("/v1/person/{}/_identify_user", id).as_str(),
+            request,
+        )
+        .await
+    }
+
+    pub async fn idm_person_credential_helpdesk_reset(
+        &self,
+        id: &str,
+        other_totp: u32,
+        ttl: Option<u64>,
+    ) -> Result<CUIntentToken, ClientError> {
+        self.perform_post_request(
+            format!("/v1/person/{}/_credential/_helpdesk_reset", id).as_str(),
+            HelpdeskCredentialResetRequest { other_totp, ttl },
+        )
+        .await
+    }
+
+    /// Mail a credential reset link to the primary mail address of a person.
+    pub async fn idm_person_credential_reset_email(
+        &self,
+        id: &str,
+        ttl: Option<u64>,
+    ) -> Result<(), ClientError> {
+        self.perform_post_request(
+            format!("/v1/person/{}/_credential/_email_reset", id).as_str(),
+            CredentialResetEmailRequest { ttl },
+        )
+        .await
+    }
 }
@@ -115,6 +115,23 @@ impl KanidmClient {
             .await
     }
 
+    pub async fn idm_service_account_set_entry_managed_by(
+        &self,
+        id: &str,
+        entry_managed_by: &str,
+    ) -> Result<(), ClientError> {
+        self.idm_service_account_set_attr(id, "entry_managed_by", &[entry_managed_by])
+            .await
+    }
+
+    pub async fn idm_service_account_purge_entry_managed_by(
+        &self,
+        id: &str,
+    ) -> Result<(), ClientError> {
+        self.idm_service_account_purge_attr(id, "entry_managed_by")
+            .await
+    }
+
     pub async fn idm_service_account_post_ssh_pubkey(
         &self,
         id: &str,
@@ -140,6 +157,75 @@ impl KanidmClient {
         .await
     }
 
+    pub async fn idm_service_account_oauth2_registrar_add_domain(
+        &self,
+        id: &str,
+        domain: &str,
+    ) -> Result<(), ClientError> {
+        self.idm_service_account_add_attr(id, "class", &["oauth2_registrar"])
+            .await?;
+        self.idm_service_account_add_attr(id, "oauth2_registrar_domain", &[domain])
+            .await
+    }
+
+    pub async fn idm_service_account_oauth2_registrar_remove_domain(
+        &self,
+        id: &str,
+        domain: &str,
+    ) -> Result<(), ClientError> {
+        let domains: Vec<String> = self
+            .idm_service_account_get_attr(id, "oauth2_registrar_domain")
+            .await?
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|d| !d.eq_ignore_ascii_case(domain))
+            .collect();
+
+        if domains.is_empty() {
+            self.idm_service_account_purge_attr(id, "oauth2_registrar_domain")
+                .await
+        } else {
+            let domains: Vec<&str> = domains.iter().map(String::as_str).collect();
+            self.idm_service_account_set_attr(id, "oauth2_registrar_domain", &domains)
+                .await
+        }
+    }
+
+    pub async fn idm_service_account_oauth2_registrar_update_scope_map(
+        &self,
+        id: &str,
+        group: &str,
+        scopes: Vec<&str>,
+    ) -> Result<(), ClientError> {
+        self.idm_service_account_add_attr(id, "class", &["oauth2_registrar"])
+            .await?;
+        let scopes: Vec<String> = scopes.into_iter().map(str::to_string).collect();
+        self.perform_post_request(
+            format!(
+                "/v1/service_account/{}/_oauth2_registrar_scopemap/{}",
+                id, group
+            )
+            .as_str(),
+            scopes,
+        )
+        .await
+    }
+
+    pub async fn idm_service_account_oauth2_registrar_delete_scope_map(
+        &self,
+        id: &str,
+        group: &str,
+    ) -> Result<(), ClientError> {
+        self.perform_delete_request(
+            format!(
+                "/v1/service_account/{}/_oauth2_registrar_scopemap/{}",
+                id, group
+            )
+            .as_str(),
+        )
+        .await
+    }
+
     pub async fn idm_service_account_unix_extend(
         &self,
         id: &str,
@@ -23,4 +23,65 @@ impl KanidmClient {
         self.perform_delete_request_with_body("/v1/system/_attr/badlist_password", list)
             .await
     }
+
+    pub async fn system_password_history_length_get(&self) -> Result<Option<u32>, ClientError> {
+        let list: Option<Vec<String>> = self
+            .perform_get_request("/v1/system/_attr/password_history_length")
+            .await?;
+        Ok(list
+            .and_then(|mut l| l.pop())
+            .and_then(|v| v.parse::<u32>().ok()))
+    }
+
+    pub async fn system_password_history_length_set(&self, length: u32) -> Result<(), ClientError> {
+        self.perform_put_request(
+            "/v1/system/_attr/password_history_length",
+            vec![length.to_string()],
+        )
+        .await
+    }
+
+    pub async fn system_recycle_bin_retention_get(&self) -> Result<Vec<String>, ClientError> {
+        let list: Option<Vec<String>> = self
+            .perform_get_request("/v1/system/_attr/recycle_bin_retention")
+            .await?;
+        Ok(list.unwrap_or_default())
+    }
+
+    /// Set how many seconds recycled entries of this class are retained, replacing any
+    /// existing retention for the class.
+    pub async fn system_recycle_bin_retention_set(
+        &self,
+        class: &str,
+        seconds: u64,
+    ) -> Result<(), ClientError> {
+        let prefix = format!("{}:", class.to_lowercase());
+        let mut list: Vec<String> = self
+            .system_recycle_bin_retention_get()
+            .await?
+            .into_iter()
+            .filter(|v| !v.starts_with(&prefix))
+            .collect();
+        list.push(format!("{}{}", prefix, seconds));
+        self.perform_put_request("/v1/system/_attr/recycle_bin_retention", list)
+            .await
+    }
+
+    pub async fn system_recycle_bin_retention_remove(
+        &self,
+        class: &str,
+    ) -> Result<(), ClientError> {
+        let prefix = format!("{}:", class.to_lowercase());
+        let list: Vec<String> = self
+            .system_recycle_bin_retention_get()
+            .await?
+            .into_iter()
+            .filter(|v| v.starts_with(&prefix))
+            .collect();
+        if list.is_empty() {
+            return Ok(());
+        }
+        self.perform_delete_request_with_body("/v1/system/_attr/recycle_bin_retention", list)
+            .await
+    }
 }
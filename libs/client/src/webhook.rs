@@ -0,0 +1,99 @@
+use crate::{ClientError, KanidmClient};
+use kanidm_proto::v1::{Entry, WebhookDeadLetter, WebhookEventType};
+
+impl KanidmClient {
+    pub async fn idm_webhook_list(&self) -> Result<Vec<Entry>, ClientError> {
+        self.perform_get_request("/v1/webhook").await
+    }
+
+    pub async fn idm_webhook_get(&self, id: &str) -> Result<Option<Entry>, ClientError> {
+        self.perform_get_request(format!("/v1/webhook/{}", id).as_str())
+            .await
+    }
+
+    /// Create a webhook that posts the listed events to `url`. If set, only changes to
+    /// entries matching the json `filter` are sent.
+    pub async fn idm_webhook_create(
+        &self,
+        name: &str,
+        url: &str,
+        events: &[WebhookEventType],
+        filter: Option<&str>,
+    ) -> Result<(), ClientError> {
+        let mut new_webhook = Entry::default();
+        new_webhook
+            .attrs
+            .insert("name".to_string(), vec![name.to_string()]);
+        new_webhook
+            .attrs
+            .insert("webhook_url".to_string(), vec![url.to_string()]);
+        new_webhook.attrs.insert(
+            "webhook_event".to_string(),
+            events.iter().map(|ev| ev.to_string()).collect(),
+        );
+        if let Some(filter) = filter {
+            new_webhook
+                .attrs
+                .insert("webhook_filter".to_string(), vec![filter.to_string()]);
+        }
+        self.perform_post_request("/v1/webhook", new_webhook).await
+    }
+
+    pub async fn idm_webhook_set_url(&self, id: &str, url: &str) -> Result<(), ClientError> {
+        self.perform_put_request(
+            format!("/v1/webhook/{}/_attr/webhook_url", id).as_str(),
+            vec![url.to_string()],
+        )
+        .await
+    }
+
+    pub async fn idm_webhook_delete(&self, id: &str) -> Result<(), ClientError> {
+        self.perform_delete_request(format!("/v1/webhook/{}", id).as_str())
+            .await
+    }
+
+    /// The secret used to sign the events sent to this webhook.
+    pub async fn idm_webhook_get_secret(&self, id: &str) -> Result<Option<String>, ClientError> {
+        self.perform_get_request(format!("/v1/webhook/{}/_secret", id).as_str())
+            .await
+    }
+
+    /// Replace the secret of this webhook with a newly generated one.
+    pub async fn idm_webhook_reset_secret(&self, id: &str) -> Result<(), ClientError> {
+        self.perform_delete_request(format!("/v1/webhook/{}/_attr/webhook_secret", id).as_str())
+            .await
+    }
+
+    /// The events that could not be delivered to this webhook.
+    pub async fn idm_webhook_dead_letters(
+        &self,
+        id: &str,
+    ) -> Result<Vec<WebhookDeadLetter>, ClientError> {
+        let values: Option<Vec<String>> = self
+            .perform_get_request(format!("/v1/webhook/{}/_attr/webhook_dead_letter", id).as_str())
+            .await?;
+        values
+            .unwrap_or_default()
+            .iter()
+            .map(|v| serde_json::from_str(v).map_err(ClientError::JsonEncode))
+            .collect()
+    }
+
+    /// Discard the events that could not be delivered to this webhook.
+    pub async fn idm_webhook_dead_letters_clear(&self, id: &str) -> Result<(), ClientError> {
+        self.perform_delete_request(
+            format!("/v1/webhook/{}/_attr/webhook_dead_letter", id).as_str(),
+        )
+        .await
+    }
+
+    /// Attempt to deliver the events that could not be delivered to this webhook again,
+    /// returning how many were queued.
+    pub async fn idm_webhook_dead_letters_retry(&self, id: &str) -> Result<usize, ClientError> {
+        self.perform_post_request(
+            format!("/v1/webhook/{}/_dead_letter/_retry", id).as_str(),
+            (),
+        )
+        .await
+    }
+}
@@ -0,0 +1,264 @@
+//! Verification of crypt(3) style password hashes. These are only supported so that
+//! hashes can be imported from other systems such as OpenLDAP or /etc/shadow, and are
+//! upgraded to our own kdf on the next successful authentication.
+//!
+//! The hashes themselves are computed by the pwhash and bcrypt crates, this module only
+//! handles parsing them into the parts we store.
+
+use base64::engine::{DecodePaddingMode, GeneralPurpose, GeneralPurposeConfig};
+use base64::{alphabet, Engine};
+use pwhash::{sha256_crypt, sha512_crypt, HashSetup};
+use tracing::error;
+
+const SHA_CRYPT_DEFAULT_ROUNDS: u32 = 5000;
+const SHA_CRYPT_MIN_ROUNDS: u32 = 1000;
+// Imported hashes are verified on every authentication attempt until they are upgraded,
+// so the work they can demand is bounded well below what the formats allow.
+const SHA_CRYPT_MAX_ROUNDS: u32 = 1_000_000;
+const SHA_CRYPT_MAX_SALT_LEN: usize = 16;
+
+const BCRYPT_MIN_COST: u32 = 4;
+const BCRYPT_MAX_COST: u32 = 14;
+const BCRYPT_SALT_LEN: usize = 16;
+const BCRYPT_HASH_LEN: usize = 23;
+
+const CRYPT_ALPHABET: &[u8; 64] =
+    b"./0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+
+// The order that sha-crypt encodes the digest bytes in. Each group of three bytes
+// becomes four characters, except the last group.
+const SHA256_CRYPT_ORDER: [(usize, usize, usize); 10] = [
+    (0, 10, 20),
+    (21, 1, 11),
+    (12, 22, 2),
+    (3, 13, 23),
+    (24, 4, 14),
+    (15, 25, 5),
+    (6, 16, 26),
+    (27, 7, 17),
+    (18, 28, 8),
+    (9, 19, 29),
+];
+
+const SHA512_CRYPT_ORDER: [(usize, usize, usize); 21] = [
+    (0, 21, 42),
+    (22, 43, 1),
+    (44, 2, 23),
+    (3, 24, 45),
+    (25, 46, 4),
+    (47, 5, 26),
+    (6, 27, 48),
+    (28, 49, 7),
+    (50, 8, 29),
+    (9, 30, 51),
+    (31, 52, 10),
+    (53, 11, 32),
+    (12, 33, 54),
+    (34, 55, 13),
+    (56, 14, 35),
+    (15, 36, 57),
+    (37, 58, 16),
+    (59, 17, 38),
+    (18, 39, 60),
+    (40, 61, 19),
+    (62, 20, 41),
+];
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum ShaCrypt {
+    Sha256,
+    Sha512,
+}
+
+impl ShaCrypt {
+    fn prefix(self) -> &'static str {
+        match self {
+            ShaCrypt::Sha256 => "$5$",
+            ShaCrypt::Sha512 => "$6$",
+        }
+    }
+
+    fn digest_len(self) -> usize {
+        match self {
+            ShaCrypt::Sha256 => 32,
+            ShaCrypt::Sha512 => 64,
+        }
+    }
+
+    fn order(self) -> &'static [(usize, usize, usize)] {
+        match self {
+            ShaCrypt::Sha256 => &SHA256_CRYPT_ORDER,
+            ShaCrypt::Sha512 => &SHA512_CRYPT_ORDER,
+        }
+    }
+
+    // The final group is only one or two bytes.
+    fn final_group(self) -> (Option<usize>, usize, usize) {
+        match self {
+            ShaCrypt::Sha256 => (Some(31), 30, 3),
+            ShaCrypt::Sha512 => (None, 63, 2),
+        }
+    }
+}
+
+/// Parse the content of a sha-crypt hash after the `$5$` or `$6$` identifier, in the
+/// form `[rounds=N$]salt$hash`. Returns the rounds, salt and digest.
+pub(crate) fn sha_crypt_parse(kind: ShaCrypt, value: &str) -> Option<(u32, Vec<u8>, Vec<u8>)> {
+    let (rounds, value) = match value.strip_prefix("rounds=") {
+        Some(v) => {
+            let (rounds, rest) = v.split_once('$')?;
+            let rounds = rounds.parse::<u32>().ok()?.max(SHA_CRYPT_MIN_ROUNDS);
+            if rounds > SHA_CRYPT_MAX_ROUNDS {
+                error!(%rounds, "sha-crypt rounds exceed the import limit of {}", SHA_CRYPT_MAX_ROUNDS);
+                return None;
+            }
+            (rounds, rest)
+        }
+        None => (SHA_CRYPT_DEFAULT_ROUNDS, value),
+    };
+
+    let (salt, hash) = value.split_once('$')?;
+    if salt.len() > SHA_CRYPT_MAX_SALT_LEN {
+        error!("sha-crypt salt is too long");
+        return None;
+    }
+    if !salt.bytes().all(|c| CRYPT_ALPHABET.contains(&c)) {
+        error!("sha-crypt salt contains invalid characters");
+        return None;
+    }
+
+    let digest = sha_crypt_decode(kind, hash)?;
+    Some((rounds, salt.as_bytes().to_vec(), digest))
+}
+
+fn sha_crypt_decode(kind: ShaCrypt, hash: &str) -> Option<Vec<u8>> {
+    let mut chars = hash.bytes().map(|c| {
+        CRYPT_ALPHABET
+            .iter()
+            .position(|a| *a == c)
+            .map(|v| v as u32)
+    });
+
+    let mut read_group = |n: usize| -> Option<u32> {
+        let mut w = 0;
+        for i in 0..n {
+            w |= chars.next()?? << (6 * i);
+        }
+        Some(w)
+    };
+
+    let mut digest = vec![0; kind.digest_len()];
+    for (a, b, c) in kind.order() {
+        let w = read_group(4)?;
+        digest[*a] = (w >> 16) as u8;
+        digest[*b] = (w >> 8) as u8;
+        digest[*c] = w as u8;
+    }
+
+    let (a, b, n) = kind.final_group();
+    let w = read_group(n)?;
+    if let Some(a) = a {
+        digest[a] = (w >> 8) as u8;
+    }
+    digest[b] = w as u8;
+
+    if chars.next().is_some() {
+        error!("sha-crypt hash is too long");
+        return None;
+    }
+
+    Some(digest)
+}
+
+/// Produce the sha-crypt digest of this cleartext.
+pub(crate) fn sha_crypt(
+    kind: ShaCrypt,
+    cleartext: &[u8],
+    salt: &[u8],
+    rounds: u32,
+) -> Option<Vec<u8>> {
+    let setup = HashSetup {
+        salt: Some(std::str::from_utf8(salt).ok()?),
+        rounds: Some(rounds),
+    };
+    // sha256-crypt is deprecated for new hashes, which is fine as we only verify them.
+    #[allow(deprecated)]
+    let hashed = match kind {
+        ShaCrypt::Sha256 => sha256_crypt::hash_with(setup, cleartext),
+        ShaCrypt::Sha512 => sha512_crypt::hash_with(setup, cleartext),
+    }
+    .map_err(|e| {
+        error!(?e, "unable to compute sha-crypt hash");
+    })
+    .ok()?;
+
+    hashed
+        .strip_prefix(kind.prefix())
+        .and_then(|value| sha_crypt_parse(kind, value))
+        .map(|(_, _, digest)| digest)
+}
+
+fn bcrypt_base64() -> GeneralPurpose {
+    GeneralPurpose::new(
+        &alphabet::BCRYPT,
+        GeneralPurposeConfig::new()
+            .with_encode_padding(false)
+            .with_decode_padding_mode(DecodePaddingMode::RequireNone)
+            .with_decode_allow_trailing_bits(true),
+    )
+}
+
+/// Parse a bcrypt hash in the form `$2b$cost$saltandhash`. Returns the cost, salt
+/// and hash.
+pub(crate) fn bcrypt_parse(value: &str) -> Option<(u32, Vec<u8>, Vec<u8>)> {
+    let mut parts = value.split('$');
+    // Leading empty element.
+    parts.next().filter(|p| p.is_empty())?;
+
+    match parts.next()? {
+        // 2a and 2y only differ from 2b in the handling of very long passwords
+        // in some buggy implementations.
+        "2a" | "2b" | "2y" => {}
+        v => {
+            error!(version = %v, "unsupported bcrypt version");
+            return None;
+        }
+    }
+
+    let cost = parts.next()?.parse::<u32>().ok()?;
+    if !(BCRYPT_MIN_COST..=BCRYPT_MAX_COST).contains(&cost) {
+        error!(
+            %cost,
+            "bcrypt cost must be between {} and {}", BCRYPT_MIN_COST, BCRYPT_MAX_COST
+        );
+        return None;
+    }
+
+    let salt_hash = parts.next()?;
+    if parts.next().is_some() || salt_hash.len() != 53 || !salt_hash.is_ascii() {
+        return None;
+    }
+    let (salt, hash) = salt_hash.split_at(22);
+
+    let engine = bcrypt_base64();
+    let salt = engine.decode(salt).ok()?;
+    let hash = engine.decode(hash).ok()?;
+    if salt.len() != BCRYPT_SALT_LEN || hash.len() != BCRYPT_HASH_LEN {
+        return None;
+    }
+
+    Some((cost, salt, hash))
+}
+
+/// Produce the bcrypt hash of this cleartext.
+pub(crate) fn bcrypt(cleartext: &[u8], cost: u32, salt: &[u8]) -> Option<Vec<u8>> {
+    let salt: [u8; BCRYPT_SALT_LEN] = salt.try_into().ok()?;
+    let hashed = bcrypt::hash_with_salt(cleartext, cost, salt)
+        .map_err(|e| {
+            error!(?e, "unable to compute bcrypt hash");
+        })
+        .ok()?
+        .to_string();
+
+    bcrypt_parse(&hashed).map(|(_, _, hash)| hash)
+}
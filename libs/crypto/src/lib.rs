@@ -28,6 +28,10 @@ use openssl::nid::Nid;
 use openssl::pkcs5::pbkdf2_hmac;
 use openssl::sha::Sha512;
 
+mod crypt;
+
+use crate::crypt::ShaCrypt;
+
 #[cfg(feature = "tpm")]
 pub use tss_esapi::{handles::ObjectHandle as TpmHandle, Context as TpmContext, Error as TpmError};
 #[cfg(not(feature = "tpm"))]
@@ -73,6 +77,7 @@ pub enum CryptoError {
     Argon2,
     Argon2Version,
     Argon2Parameters,
+    Crypt,
 }
 
 #[allow(clippy::from_over_into)]
@@ -113,6 +118,9 @@ pub enum DbPasswordV1 {
     PBKDF2_SHA512(usize, Vec<u8>, Vec<u8>),
     SSHA512(Vec<u8>, Vec<u8>),
     NT_MD4(Vec<u8>),
+    CRYPT_SHA256(u32, Vec<u8>, Vec<u8>),
+    CRYPT_SHA512(u32, Vec<u8>, Vec<u8>),
+    BCRYPT(u32, Vec<u8>, Vec<u8>),
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
@@ -156,6 +164,21 @@ pub enum ReplPasswordV1 {
     NT_MD4 {
         hash: Base64UrlSafeData,
     },
+    CRYPT_SHA256 {
+        rounds: u32,
+        salt: Base64UrlSafeData,
+        hash: Base64UrlSafeData,
+    },
+    CRYPT_SHA512 {
+        rounds: u32,
+        salt: Base64UrlSafeData,
+        hash: Base64UrlSafeData,
+    },
+    BCRYPT {
+        cost: u32,
+        salt: Base64UrlSafeData,
+        hash: Base64UrlSafeData,
+    },
 }
 
 impl fmt::Debug for DbPasswordV1 {
@@ -168,6 +191,9 @@ impl fmt::Debug for DbPasswordV1 {
             DbPasswordV1::PBKDF2_SHA512(_, _, _) => write!(f, "PBKDF2_SHA512"),
             DbPasswordV1::SSHA512(_, _) => write!(f, "SSHA512"),
             DbPasswordV1::NT_MD4(_) => write!(f, "NT_MD4"),
+            DbPasswordV1::CRYPT_SHA256(_, _, _) => write!(f, "CRYPT_SHA256"),
+            DbPasswordV1::CRYPT_SHA512(_, _, _) => write!(f, "CRYPT_SHA512"),
+            DbPasswordV1::BCRYPT(_, _, _) => write!(f, "BCRYPT"),
         }
     }
 }
@@ -346,6 +372,7 @@ impl CryptoPolicy {
 // pbkdf2 in openssl because it doesn't have the same limits.
 #[derive(Clone, Debug, PartialEq)]
 #[allow(non_camel_case_types)]
+#[allow(clippy::upper_case_acronyms)]
 enum Kdf {
     TPM_ARGON2ID {
         m_cost: u32,
@@ -376,6 +403,12 @@ enum Kdf {
     SSHA512(Vec<u8>, Vec<u8>),
     //     hash
     NT_MD4(Vec<u8>),
+    //          rounds, salt,    hash
+    CRYPT_SHA256(u32, Vec<u8>, Vec<u8>),
+    //          rounds, salt,    hash
+    CRYPT_SHA512(u32, Vec<u8>, Vec<u8>),
+    //    cost, salt,    hash
+    BCRYPT(u32, Vec<u8>, Vec<u8>),
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -423,6 +456,15 @@ impl TryFrom<DbPasswordV1> for Password {
             DbPasswordV1::NT_MD4(h) => Ok(Password {
                 material: Kdf::NT_MD4(h),
             }),
+            DbPasswordV1::CRYPT_SHA256(r, s, h) => Ok(Password {
+                material: Kdf::CRYPT_SHA256(r, s, h),
+            }),
+            DbPasswordV1::CRYPT_SHA512(r, s, h) => Ok(Password {
+                material: Kdf::CRYPT_SHA512(r, s, h),
+            }),
+            DbPasswordV1::BCRYPT(c, s, h) => Ok(Password {
+                material: Kdf::BCRYPT(c, s, h),
+            }),
         }
     }
 }
@@ -481,6 +523,15 @@ impl TryFrom<&ReplPasswordV1> for Password {
             ReplPasswordV1::NT_MD4 { hash } => Ok(Password {
                 material: Kdf::NT_MD4(hash.0.clone()),
             }),
+            ReplPasswordV1::CRYPT_SHA256 { rounds, salt, hash } => Ok(Password {
+                material: Kdf::CRYPT_SHA256(*rounds, salt.0.clone(), hash.0.clone()),
+            }),
+            ReplPasswordV1::CRYPT_SHA512 { rounds, salt, hash } => Ok(Password {
+                material: Kdf::CRYPT_SHA512(*rounds, salt.0.clone(), hash.0.clone()),
+            }),
+            ReplPasswordV1::BCRYPT { cost, salt, hash } => Ok(Password {
+                material: Kdf::BCRYPT(*cost, salt.0.clone(), hash.0.clone()),
+            }),
         }
     }
 }
@@ -539,6 +590,38 @@ impl TryFrom<&str> for Password {
             }
         }
 
+        // Test crypt(3) formats, which OpenLDAP prefixes with {CRYPT}
+        let crypt_value = value.strip_prefix("{CRYPT}").unwrap_or(value);
+
+        if let Some(sha256_crypt) = crypt_value.strip_prefix("$5$") {
+            let (r, s, h) =
+                crypt::sha_crypt_parse(ShaCrypt::Sha256, sha256_crypt).ok_or_else(|| {
+                    error!("Invalid sha256-crypt hash");
+                })?;
+            return Ok(Password {
+                material: Kdf::CRYPT_SHA256(r, s, h),
+            });
+        }
+
+        if let Some(sha512_crypt) = crypt_value.strip_prefix("$6$") {
+            let (r, s, h) =
+                crypt::sha_crypt_parse(ShaCrypt::Sha512, sha512_crypt).ok_or_else(|| {
+                    error!("Invalid sha512-crypt hash");
+                })?;
+            return Ok(Password {
+                material: Kdf::CRYPT_SHA512(r, s, h),
+            });
+        }
+
+        if crypt_value.starts_with("$2") {
+            let (c, s, h) = crypt::bcrypt_parse(crypt_value).ok_or_else(|| {
+                error!("Invalid bcrypt hash");
+            })?;
+            return Ok(Password {
+                material: Kdf::BCRYPT(c, s, h),
+            });
+        }
+
         if value.starts_with("ipaNTHash: ") {
             let nt_md4 = match value.split_once(' ') {
                 Some((_, v)) => v,
@@ -1032,6 +1115,19 @@ impl Password {
                     })
                     .map(|chal_key| chal_key.as_ref() == key)
             }
+            (Kdf::CRYPT_SHA256(rounds, salt, key), _) => {
+                crypt::sha_crypt(ShaCrypt::Sha256, cleartext.as_bytes(), salt, *rounds)
+                    .ok_or(CryptoError::Crypt)
+                    .map(|chal_key| &chal_key == key)
+            }
+            (Kdf::CRYPT_SHA512(rounds, salt, key), _) => {
+                crypt::sha_crypt(ShaCrypt::Sha512, cleartext.as_bytes(), salt, *rounds)
+                    .ok_or(CryptoError::Crypt)
+                    .map(|chal_key| &chal_key == key)
+            }
+            (Kdf::BCRYPT(cost, salt, key), _) => crypt::bcrypt(cleartext.as_bytes(), *cost, salt)
+                .ok_or(CryptoError::Crypt)
+                .map(|chal_key| &chal_key == key),
         }
     }
 
@@ -1078,6 +1174,15 @@ impl Password {
             }
             Kdf::SSHA512(salt, hash) => DbPasswordV1::SSHA512(salt.clone(), hash.clone()),
             Kdf::NT_MD4(hash) => DbPasswordV1::NT_MD4(hash.clone()),
+            Kdf::CRYPT_SHA256(rounds, salt, hash) => {
+                DbPasswordV1::CRYPT_SHA256(*rounds, salt.clone(), hash.clone())
+            }
+            Kdf::CRYPT_SHA512(rounds, salt, hash) => {
+                DbPasswordV1::CRYPT_SHA512(*rounds, salt.clone(), hash.clone())
+            }
+            Kdf::BCRYPT(cost, salt, hash) => {
+                DbPasswordV1::BCRYPT(*cost, salt.clone(), hash.clone())
+            }
         }
     }
 
@@ -1135,6 +1240,21 @@ impl Password {
             Kdf::NT_MD4(hash) => ReplPasswordV1::NT_MD4 {
                 hash: hash.clone().into(),
             },
+            Kdf::CRYPT_SHA256(rounds, salt, hash) => ReplPasswordV1::CRYPT_SHA256 {
+                rounds: *rounds,
+                salt: salt.clone().into(),
+                hash: hash.clone().into(),
+            },
+            Kdf::CRYPT_SHA512(rounds, salt, hash) => ReplPasswordV1::CRYPT_SHA512 {
+                rounds: *rounds,
+                salt: salt.clone().into(),
+                hash: hash.clone().into(),
+            },
+            Kdf::BCRYPT(cost, salt, hash) => ReplPasswordV1::BCRYPT {
+                cost: *cost,
+                salt: salt.clone().into(),
+                hash: hash.clone().into(),
+            },
         }
     }
 
@@ -1165,7 +1285,10 @@ impl Password {
             | Kdf::PBKDF2_SHA512(_, _, _)
             | Kdf::PBKDF2_SHA1(_, _, _)
             | Kdf::SSHA512(_, _)
-            | Kdf::NT_MD4(_) => true,
+            | Kdf::NT_MD4(_)
+            | Kdf::CRYPT_SHA256(_, _, _)
+            | Kdf::CRYPT_SHA512(_, _, _)
+            | Kdf::BCRYPT(_, _, _) => true,
         }
     }
 }
@@ -1311,6 +1434,96 @@ mod tests {
         assert!(r.verify(password).unwrap_or(false));
     }
 
+    #[test]
+    fn test_password_from_crypt_sha256() {
+        sketching::test_init();
+        let im_pw = "$5$ScpuJoWVmKOG2LM3$Z//BECY7zt5nK/ByDRn944O8cneqicoRtTtc2ATOhU9";
+        let password = "eicieY7ahchaoCh0eeTa";
+        let r = Password::try_from(im_pw).expect("Failed to parse");
+        assert!(r.requires_upgrade());
+        assert!(r.verify(password).unwrap_or(false));
+        assert!(!r.verify("password").unwrap_or(true));
+    }
+
+    #[test]
+    fn test_password_from_crypt_sha512() {
+        sketching::test_init();
+        let im_pw = "$6$rounds=10000$bbEsEhCZ8GNs7nE2$kp4VVMeUuqlx86C7dmJTmdbJ3GXJR47WtUbJ7CS6P9hvpilSr/nCcYeJ4DeUzhrTtEWDuNm40aD7ouH0H3GXw.";
+        let password = "eicieY7ahchaoCh0eeTa";
+        let r = Password::try_from(im_pw).expect("Failed to parse");
+        assert!(r.requires_upgrade());
+        assert!(r.verify(password).unwrap_or(false));
+        assert!(!r.verify("password").unwrap_or(true));
+
+        // The wrong number of rounds must not verify.
+        let im_pw = "$6$rounds=5000$bbEsEhCZ8GNs7nE2$kp4VVMeUuqlx86C7dmJTmdbJ3GXJR47WtUbJ7CS6P9hvpilSr/nCcYeJ4DeUzhrTtEWDuNm40aD7ouH0H3GXw.";
+        let r = Password::try_from(im_pw).expect("Failed to parse");
+        assert!(!r.verify(password).unwrap_or(true));
+
+        // Truncated hashes are rejected.
+        assert!(Password::try_from("$6$bbEsEhCZ8GNs7nE2$kp4VVMeUuqlx86C7dmJTmdbJ3GXJR47").is_err());
+
+        // Rounds beyond the import limit would make every authentication attempt expensive.
+        let im_pw = "$6$rounds=999999999$bbEsEhCZ8GNs7nE2$kp4VVMeUuqlx86C7dmJTmdbJ3GXJR47WtUbJ7CS6P9hvpilSr/nCcYeJ4DeUzhrTtEWDuNm40aD7ouH0H3GXw.";
+        assert!(Password::try_from(im_pw).is_err());
+    }
+
+    #[test]
+    fn test_password_from_openldap_crypt() {
+        sketching::test_init();
+        let im_pw = "{CRYPT}$6$toolongsaltstrin$26G5OvFY/rYqUle7WGZY89Vs1ezAqIxvpc.eLACycbH17hIZb03cl8gbLnGKh1wzxcZl3NMO9nX2M4uZso9y/0";
+        let password = "eicieY7ahchaoCh0eeTa";
+        let r = Password::try_from(im_pw).expect("Failed to parse");
+        assert!(r.requires_upgrade());
+        assert!(r.verify(password).unwrap_or(false));
+
+        // md5-crypt and des-crypt are not supported.
+        assert!(Password::try_from("{CRYPT}$1$saltsalt$N0V3ljpVvF7fD1FNzt1sS.").is_err());
+        assert!(Password::try_from("{CRYPT}abJnggxhB/yWI").is_err());
+    }
+
+    #[test]
+    fn test_password_from_bcrypt() {
+        sketching::test_init();
+        let password = "eicieY7ahchaoCh0eeTa";
+
+        let im_pw = "$2b$06$5bn7ty8h0XBsWlhJvgq1lOhsVRGRiJ2llYuoihQ1gQxoPhVsnvUou";
+        let r = Password::try_from(im_pw).expect("Failed to parse");
+        assert!(r.requires_upgrade());
+        assert!(r.verify(password).unwrap_or(false));
+        assert!(!r.verify("password").unwrap_or(true));
+
+        let im_pw = "{CRYPT}$2y$05$2ZTwGzLo7bWDN9aibfhTOe1PGuar65xBnNp9OXpK8Jr4oTlsjAE7G";
+        let r = Password::try_from(im_pw).expect("Failed to parse");
+        assert!(r.verify(password).unwrap_or(false));
+
+        // From the openwall crypt_blowfish test vectors.
+        let im_pw = "$2a$05$CCCCCCCCCCCCCCCCCCCCC.E5YPO9kmyuRGyh0XouQYb4YMJKvyOeW";
+        let r = Password::try_from(im_pw).expect("Failed to parse");
+        assert!(r.verify("U*U").unwrap_or(false));
+
+        // bcrypt only considers the first 72 bytes.
+        let im_pw = "$2b$04$abcdefghijklmnopqrstuubzadhGtS2zEF.gu0yd0opP6cVzb.e0i";
+        let r = Password::try_from(im_pw).expect("Failed to parse");
+        assert!(r.verify(&"x".repeat(72)).unwrap_or(false));
+        assert!(r.verify(&"x".repeat(100)).unwrap_or(false));
+        assert!(!r.verify(&"x".repeat(71)).unwrap_or(true));
+
+        // Invalid versions and costs are rejected.
+        assert!(
+            Password::try_from("$2x$05$CCCCCCCCCCCCCCCCCCCCC.E5YPO9kmyuRGyh0XouQYb4YMJKvyOeW")
+                .is_err()
+        );
+        assert!(
+            Password::try_from("$2b$03$CCCCCCCCCCCCCCCCCCCCC.E5YPO9kmyuRGyh0XouQYb4YMJKvyOeW")
+                .is_err()
+        );
+        assert!(
+            Password::try_from("$2b$31$CCCCCCCCCCCCCCCCCCCCC.E5YPO9kmyuRGyh0XouQYb4YMJKvyOeW")
+                .is_err()
+        );
+    }
+
     /*
      * wbrown - 20221104 - I tried to programmatically enable the legacy provider, but
      * it consistently "did nothing at all", meaning we have to rely on users to enable
@@ -1,5 +1,7 @@
 use crate::v1::ApiTokenPurpose;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fmt;
 use url::Url;
 use uuid::Uuid;
 
@@ -16,6 +18,40 @@ pub enum AppLink {
     },
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone)]
+/// An oauth2 application that a user has consented to, or holds sessions with. This
+/// is used in the UI so that a user can review and revoke the access they have granted.
+pub struct Oauth2Grant {
+    pub name: String,
+    pub display_name: String,
+    /// The scopes the user consented to. Empty if no consent is recorded.
+    pub scopes: BTreeSet<String>,
+    /// The number of live oauth2 sessions the application holds for the user.
+    pub session_count: usize,
+    #[serde(with = "time::serde::timestamp::option")]
+    pub last_issued_at: Option<time::OffsetDateTime>,
+}
+
+impl fmt::Display for Oauth2Grant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "name: {}", self.name)?;
+        writeln!(f, "display_name: {}", self.display_name)?;
+        if self.scopes.is_empty() {
+            writeln!(f, "consented_scopes: -")?;
+        } else {
+            let scopes: Vec<&str> = self.scopes.iter().map(String::as_str).collect();
+            writeln!(f, "consented_scopes: {}", scopes.join(" "))?;
+        }
+        writeln!(f, "active_sessions: {}", self.session_count)?;
+        if let Some(last_issued_at) = self.last_issued_at {
+            writeln!(f, "last_issued_at: {}", last_issued_at)?;
+        } else {
+            writeln!(f, "last_issued_at: -")?;
+        }
+        Ok(())
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "lowercase")]
 pub struct ScimSyncToken {
@@ -26,3 +62,39 @@ pub struct ScimSyncToken {
     #[serde(default)]
     pub purpose: ApiTokenPurpose,
 }
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+/// A step in verifying the identity of another person, such as when a user contacts
+/// the helpdesk. Each party reads their code to the other, who submits it to the
+/// server to confirm they are talking to who they think they are.
+pub enum IdentifyUserRequest {
+    /// Request the code that we must read to the other person.
+    Start,
+    /// Submit the code that the other person read to us.
+    SubmitCode { other_totp: u32 },
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub enum IdentifyUserResponse {
+    /// The code to read to the other person, valid for `step` seconds.
+    ProvideCode { step: u32, totp: u32 },
+    /// The other person's code was correct. If they need to verify us in turn, this
+    /// is the code to read back to them.
+    Success { step: u32, totp: u32 },
+    /// The other person's code was incorrect or has expired.
+    CodeFailure,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+/// Request that the helpdesk issues a credential reset token for a person, once the
+/// person has read their identity verification code to the helpdesk.
+pub struct HelpdeskCredentialResetRequest {
+    pub other_totp: u32,
+    pub ttl: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+/// Request that a credential reset link is mailed to the primary mail address of a person.
+pub struct CredentialResetEmailRequest {
+    pub ttl: Option<u64>,
+}
@@ -13,6 +13,7 @@ pub mod internal;
 pub mod messages;
 pub mod oauth2;
 pub mod scim_v1;
+pub mod scim_v2;
 pub mod v1;
 
 pub use webauthn_rs_proto as webauthn;
@@ -53,6 +53,101 @@ pub struct AuthorisationRequestOidc {
     pub acr: Option<String>,
 }
 
+/// The prefix of the request_uri that is returned from a pushed authorisation request.
+pub const OAUTH2_PAR_REQUEST_URI_PREFIX: &str = "urn:ietf:params:oauth:request_uri:";
+
+/// An authorisation request may be sent as url parameters, or by reference to a pushed
+/// authorisation request or a signed request object. The variants are tried in order,
+/// so a request that has a request_uri or request is never treated as direct parameters.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum AuthorisationRequestRef {
+    // https://datatracker.ietf.org/doc/html/rfc9126#section-4
+    Pushed {
+        client_id: String,
+        request_uri: String,
+    },
+    // https://datatracker.ietf.org/doc/html/rfc9101#section-5.1
+    Signed {
+        client_id: String,
+        request: String,
+    },
+    Direct(Box<AuthorisationRequest>),
+}
+
+impl From<AuthorisationRequest> for AuthorisationRequestRef {
+    fn from(auth_req: AuthorisationRequest) -> Self {
+        AuthorisationRequestRef::Direct(Box::new(auth_req))
+    }
+}
+
+/// A pushed authorisation request, sent by the client directly to us before redirecting
+/// the user agent. The authorisation parameters are either sent in the form, or in a
+/// signed request object.
+/// <https://datatracker.ietf.org/doc/html/rfc9126#section-2.1>
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PushedAuthorisationRequest {
+    pub client_id: String,
+    pub client_secret: Option<String>,
+    pub client_assertion_type: Option<String>,
+    pub client_assertion: Option<String>,
+    pub request: Option<String>,
+    #[serde(flatten)]
+    pub params: BTreeMap<String, String>,
+}
+
+/// <https://datatracker.ietf.org/doc/html/rfc9126#section-2.2>
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PushedAuthorisationResponse {
+    pub request_uri: String,
+    pub expires_in: u64,
+}
+
+/// The metadata of a client that a registrar asks to be registered, or to replace the
+/// metadata of a client it registered earlier.
+/// <https://datatracker.ietf.org/doc/html/rfc7591#section-2>
+#[serde_as]
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ClientRegistrationRequest {
+    /// Only present when updating a client, and must match the client being updated.
+    pub client_id: Option<String>,
+    #[serde(default)]
+    pub redirect_uris: Vec<Url>,
+    pub client_name: Option<String>,
+    pub token_endpoint_auth_method: Option<String>,
+    #[serde(default)]
+    pub grant_types: Vec<String>,
+    #[serde(default)]
+    pub response_types: Vec<String>,
+    #[serde_as(as = "Option<StringWithSeparator::<SpaceSeparator, String>>")]
+    pub scope: Option<BTreeSet<String>>,
+    pub jwks: Option<serde_json::Value>,
+}
+
+/// The metadata of a registered client. As the registrar authenticates with its own token to
+/// manage the client, no registration access token is issued.
+/// <https://datatracker.ietf.org/doc/html/rfc7591#section-3.2.1>
+#[serde_as]
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ClientRegistrationResponse {
+    pub client_id: String,
+    pub client_secret: Option<String>,
+    pub client_id_issued_at: Option<i64>,
+    pub client_secret_expires_at: Option<i64>,
+    pub registration_client_uri: Url,
+    /// Redirects are permitted anywhere within the origin of the client, so this is the origin.
+    pub redirect_uris: Vec<Url>,
+    pub client_name: String,
+    pub token_endpoint_auth_method: String,
+    pub grant_types: Vec<String>,
+    pub response_types: Vec<String>,
+    #[serde_as(as = "StringWithSeparator::<SpaceSeparator, String>")]
+    pub scope: BTreeSet<String>,
+}
+
 /// When we request to authorise, it can either prompt us for consent,
 /// or it can immediately be granted due the past grant.
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -90,8 +185,28 @@ pub enum GrantTypeReq {
         #[serde_as(as = "Option<StringWithSeparator::<SpaceSeparator, String>>")]
         scope: Option<BTreeSet<String>>,
     },
+    /// <https://datatracker.ietf.org/doc/html/rfc8693#section-2.1>
+    #[serde(rename = "urn:ietf:params:oauth:grant-type:token-exchange")]
+    TokenExchange {
+        // An access token that was issued to the requesting client.
+        subject_token: String,
+        subject_token_type: String,
+        requested_token_type: Option<String>,
+        // The name of the resource server the token is to be used with.
+        audience: String,
+        #[serde_as(as = "Option<StringWithSeparator::<SpaceSeparator, String>>")]
+        scope: Option<BTreeSet<String>>,
+    },
 }
 
+/// The only token type that can be exchanged, or issued by an exchange.
+pub const OAUTH2_TOKEN_TYPE_ACCESS_TOKEN: &str = "urn:ietf:params:oauth:token-type:access_token";
+
+/// The client_assertion_type of a private_key_jwt client authentication.
+/// <https://datatracker.ietf.org/doc/html/rfc7523#section-2.2>
+pub const OAUTH2_CLIENT_ASSERTION_TYPE_JWT_BEARER: &str =
+    "urn:ietf:params:oauth:client-assertion-type:jwt-bearer";
+
 #[skip_serializing_none]
 #[derive(Serialize, Deserialize, Debug)]
 pub struct AccessTokenRequest {
@@ -101,6 +216,9 @@ pub struct AccessTokenRequest {
     //  authorization server as described in Section 3.2.1.
     pub client_id: Option<String>,
     pub client_secret: Option<String>,
+    // Used in place of a secret by clients that authenticate with private_key_jwt.
+    pub client_assertion_type: Option<String>,
+    pub client_assertion: Option<String>,
 }
 
 impl From<GrantTypeReq> for AccessTokenRequest {
@@ -109,6 +227,8 @@ impl From<GrantTypeReq> for AccessTokenRequest {
             grant_type: req,
             client_id: None,
             client_secret: None,
+            client_assertion_type: None,
+            client_assertion: None,
         }
     }
 }
@@ -120,6 +240,10 @@ pub struct TokenRevokeRequest {
     /// Generally not needed. See:
     /// <https://datatracker.ietf.org/doc/html/rfc7009#section-4.1.2>
     pub token_type_hint: Option<String>,
+    // Client authentication for clients that do not use basic auth.
+    pub client_id: Option<String>,
+    pub client_assertion_type: Option<String>,
+    pub client_assertion: Option<String>,
 }
 
 // The corresponding Response to a revoke request is empty body with 200.
@@ -139,6 +263,8 @@ pub struct AccessTokenResponse {
     pub scope: Option<String>,
     /// Oidc puts the token here.
     pub id_token: Option<String>,
+    /// The type of the issued token, only present for token exchanges.
+    pub issued_token_type: Option<String>,
 }
 
 #[skip_serializing_none]
@@ -148,6 +274,10 @@ pub struct AccessTokenIntrospectRequest {
     /// Generally not needed. See:
     /// <https://datatracker.ietf.org/doc/html/rfc7009#section-4.1.2>
     pub token_type_hint: Option<String>,
+    // Client authentication for clients that do not use basic auth.
+    pub client_id: Option<String>,
+    pub client_assertion_type: Option<String>,
+    pub client_assertion: Option<String>,
 }
 
 #[skip_serializing_none]
@@ -165,6 +295,17 @@ pub struct AccessTokenIntrospectResponse {
     pub aud: Option<String>,
     pub iss: Option<String>,
     pub jti: Option<String>,
+    pub cnf: Option<Oauth2Confirmation>,
+}
+
+/// The key an access token is bound to. Resource servers must check the client presenting
+/// the token holds this key.
+/// <https://datatracker.ietf.org/doc/html/rfc8705#section-3.1>
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Oauth2Confirmation {
+    /// The base64url encoded sha256 thumbprint of the tls client certificate.
+    #[serde(rename = "x5t#S256")]
+    pub x5t_s256: String,
 }
 
 impl AccessTokenIntrospectResponse {
@@ -182,10 +323,33 @@ impl AccessTokenIntrospectResponse {
             aud: None,
             iss: None,
             jti: None,
+            cnf: None,
         }
     }
 }
 
+/// An RP-initiated logout request, sent by the user agent to the end_session_endpoint.
+/// <https://openid.net/specs/openid-connect-rpinitiated-1_0.html#RPLogout>
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct EndSessionRequest {
+    pub id_token_hint: Option<String>,
+    pub client_id: Option<String>,
+    pub post_logout_redirect_uri: Option<Url>,
+    pub state: Option<String>,
+}
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct EndSessionResponse {
+    /// If the users session with us was ended by this request. When the id_token_hint
+    /// does not identify the current user, the user must confirm the logout themself.
+    pub logged_out: bool,
+    /// Where to send the user agent once they are logged out, including the state
+    /// of the request.
+    pub redirect_uri: Option<Url>,
+}
+
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
 pub enum ResponseType {
@@ -211,6 +375,8 @@ pub enum GrantType {
     #[serde(rename = "authorization_code")]
     AuthorisationCode,
     Implicit,
+    #[serde(rename = "urn:ietf:params:oauth:grant-type:token-exchange")]
+    TokenExchange,
 }
 
 fn grant_types_supported_default() -> Vec<GrantType> {
@@ -239,6 +405,8 @@ pub enum TokenEndpointAuthMethod {
     ClientSecretBasic,
     ClientSecretJwt,
     PrivateKeyJwt,
+    // https://datatracker.ietf.org/doc/html/rfc8705#section-2.1.1
+    TlsClientAuth,
 }
 
 fn token_endpoint_auth_methods_supported_default() -> Vec<TokenEndpointAuthMethod> {
@@ -293,6 +461,17 @@ pub struct OidcDiscoveryResponse {
     pub userinfo_endpoint: Option<Url>,
     pub jwks_uri: Url,
     pub registration_endpoint: Option<Url>,
+    // https://openid.net/specs/openid-connect-rpinitiated-1_0.html#OPMetadata
+    pub end_session_endpoint: Option<Url>,
+    // https://datatracker.ietf.org/doc/html/rfc9126#section-5
+    pub pushed_authorization_request_endpoint: Option<Url>,
+    #[serde(default)]
+    pub require_pushed_authorization_requests: bool,
+    // https://openid.net/specs/openid-connect-backchannel-1_0.html#BCSupport
+    #[serde(default)]
+    pub backchannel_logout_supported: bool,
+    #[serde(default)]
+    pub backchannel_logout_session_supported: bool,
     pub scopes_supported: Option<Vec<String>>,
     // https://datatracker.ietf.org/doc/html/rfc6749#section-3.1.1
     pub response_types_supported: Vec<ResponseType>,
@@ -318,6 +497,9 @@ pub struct OidcDiscoveryResponse {
     #[serde(default = "token_endpoint_auth_methods_supported_default")]
     pub token_endpoint_auth_methods_supported: Vec<TokenEndpointAuthMethod>,
     pub token_endpoint_auth_signing_alg_values_supported: Option<Vec<String>>,
+    // https://datatracker.ietf.org/doc/html/rfc8705#section-3.3
+    #[serde(default)]
+    pub tls_client_certificate_bound_access_tokens: bool,
     // https://openid.net/specs/openid-connect-core-1_0.html#AuthRequest
     pub display_values_supported: Option<Vec<DisplayValue>>,
     // Default to normal.
@@ -0,0 +1,159 @@
+//! Types for the standard SCIM 2.0 provisioning endpoints of RFC 7643 and RFC 7644, as
+//! used by external systems (such as HR platforms) to manage users and groups.
+
+use serde::{Deserialize, Serialize};
+use serde_with::skip_serializing_none;
+use uuid::Uuid;
+
+pub const SCIM_V2_CONTENT_TYPE: &str = "application/scim+json";
+
+pub const SCIM_V2_SCHEMA_USER: &str = "urn:ietf:params:scim:schemas:core:2.0:User";
+pub const SCIM_V2_SCHEMA_GROUP: &str = "urn:ietf:params:scim:schemas:core:2.0:Group";
+pub const SCIM_V2_SCHEMA_SERVICE_PROVIDER_CONFIG: &str =
+    "urn:ietf:params:scim:schemas:core:2.0:ServiceProviderConfig";
+pub const SCIM_V2_SCHEMA_RESOURCE_TYPE: &str = "urn:ietf:params:scim:schemas:core:2.0:ResourceType";
+pub const SCIM_V2_SCHEMA_SCHEMA: &str = "urn:ietf:params:scim:schemas:core:2.0:Schema";
+pub const SCIM_V2_MESSAGE_LIST_RESPONSE: &str =
+    "urn:ietf:params:scim:api:messages:2.0:ListResponse";
+pub const SCIM_V2_MESSAGE_PATCH_OP: &str = "urn:ietf:params:scim:api:messages:2.0:PatchOp";
+pub const SCIM_V2_MESSAGE_ERROR: &str = "urn:ietf:params:scim:api:messages:2.0:Error";
+
+/// The largest page of resources that is returned from a list request.
+pub const SCIM_V2_MAX_RESULTS: usize = 1000;
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ScimV2Meta {
+    pub resource_type: String,
+    pub location: Option<String>,
+}
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ScimV2Name {
+    /// The full name of the user. This is the legal name of a person in Kanidm.
+    pub formatted: Option<String>,
+    pub given_name: Option<String>,
+    pub family_name: Option<String>,
+}
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct ScimV2Email {
+    pub value: String,
+    #[serde(default)]
+    pub primary: bool,
+    #[serde(rename = "type")]
+    pub kind: Option<String>,
+}
+
+/// A reference to another resource, such as the member of a group.
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ScimV2Reference {
+    pub value: Uuid,
+    pub display: Option<String>,
+    #[serde(rename = "$ref")]
+    pub reference: Option<String>,
+}
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ScimV2User {
+    #[serde(default)]
+    pub schemas: Vec<String>,
+    pub id: Option<Uuid>,
+    /// Accepted for compatibility with provisioning clients, but not stored.
+    pub external_id: Option<String>,
+    pub user_name: String,
+    pub display_name: Option<String>,
+    pub name: Option<ScimV2Name>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub emails: Vec<ScimV2Email>,
+    /// If the account may authenticate. Inactive accounts have an expiry in the past.
+    pub active: Option<bool>,
+    /// The groups the user is a member of. This is read only.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub groups: Vec<ScimV2Reference>,
+    pub meta: Option<ScimV2Meta>,
+}
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ScimV2Group {
+    #[serde(default)]
+    pub schemas: Vec<String>,
+    pub id: Option<Uuid>,
+    /// Accepted for compatibility with provisioning clients, but not stored.
+    pub external_id: Option<String>,
+    pub display_name: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub members: Vec<ScimV2Reference>,
+    pub meta: Option<ScimV2Meta>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum ScimV2Resource {
+    User(ScimV2User),
+    Group(ScimV2Group),
+}
+
+impl ScimV2Resource {
+    pub fn id(&self) -> Option<Uuid> {
+        match self {
+            ScimV2Resource::User(u) => u.id,
+            ScimV2Resource::Group(g) => g.id,
+        }
+    }
+
+    pub fn meta_mut(&mut self) -> &mut Option<ScimV2Meta> {
+        match self {
+            ScimV2Resource::User(u) => &mut u.meta,
+            ScimV2Resource::Group(g) => &mut g.meta,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ScimV2ListResponse<T> {
+    pub schemas: Vec<String>,
+    pub total_results: usize,
+    pub start_index: usize,
+    pub items_per_page: usize,
+    #[serde(rename = "Resources")]
+    pub resources: Vec<T>,
+}
+
+/// A single operation of a PATCH request. `op` is one of `add`, `remove` or `replace`,
+/// compared case insensitively as some clients capitalise it.
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ScimV2PatchOp {
+    pub op: String,
+    pub path: Option<String>,
+    pub value: Option<serde_json::Value>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ScimV2PatchRequest {
+    #[serde(default)]
+    pub schemas: Vec<String>,
+    #[serde(rename = "Operations")]
+    pub operations: Vec<ScimV2PatchOp>,
+}
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ScimV2Error {
+    pub schemas: Vec<String>,
+    pub status: String,
+    pub scim_type: Option<String>,
+    pub detail: Option<String>,
+}
@@ -32,6 +32,9 @@ pub enum SchemaError {
     EmptyFilter,
     Corrupted,
     PhantomAttribute(String),
+    DeprecatedAttribute(String),
+    DeprecatedClass(String),
+    InUse(String),
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
@@ -104,6 +107,7 @@ pub enum PasswordFeedback {
     // Custom
     TooShort(usize),
     BadListed,
+    PreviouslyUsed,
 }
 
 /// Human-readable PasswordFeedback result.
@@ -134,6 +138,10 @@ impl fmt::Display for PasswordFeedback {
                 f,
                 "This password has been compromised or otherwise blocked and can not be used."
             ),
+            PasswordFeedback::PreviouslyUsed => write!(
+                f,
+                "This password has been used previously and can not be used again."
+            ),
             PasswordFeedback::CapitalizationDoesntHelpVeryMuch => {
                 write!(f, "Capitalization doesn't help very much.")
             }
@@ -255,6 +263,12 @@ pub enum OperationError {
     ReplDomainLevelUnsatisfiable,
     ReplDomainUuidMismatch,
     TransactionAlreadyCommitted,
+    BackupChecksumMismatch,
+    BackupKeyRequired,
+    BackupChainInvalid,
+    BackupIncrementalUnavailable,
+    ScimInvalidFilter(String),
+    ScimInvalidPath(String),
 }
 
 impl PartialEq for OperationError {
@@ -356,6 +370,12 @@ pub struct UatStatus {
     #[serde(with = "time::serde::timestamp")]
     pub issued_at: time::OffsetDateTime,
     pub purpose: UatPurposeStatus,
+    /// A description of the session, including where it was established from.
+    #[serde(default)]
+    pub label: String,
+    /// The credential that was used to establish this session, if it still exists.
+    #[serde(default)]
+    pub credential: Option<String>,
 }
 
 impl fmt::Display for UatStatus {
@@ -368,6 +388,12 @@ impl fmt::Display for UatStatus {
             writeln!(f, "expiry: -")?;
         }
         writeln!(f, "issued_at: {}", self.issued_at)?;
+        writeln!(f, "label: {}", self.label)?;
+        writeln!(
+            f,
+            "credential: {}",
+            self.credential.as_deref().unwrap_or("-")
+        )?;
         match &self.purpose {
             UatPurposeStatus::ReadOnly => writeln!(f, "purpose: read only")?,
             UatPurposeStatus::ReadWrite => writeln!(f, "purpose: read write")?,
@@ -1196,6 +1222,256 @@ impl SingleStringRequest {
         SingleStringRequest { value: s }
     }
 }
+
+/// The definition of an access control profile. When creating a profile, the name,
+/// receiver group and targetscope must be provided. When updating a profile, only the
+/// fields that are provided are changed. Providing an empty list of attributes removes
+/// that right from the profile.
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Default)]
+pub struct AccessProfile {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub enabled: Option<bool>,
+    /// The name or uuid of the group that this profile applies to.
+    pub receiver_group: Option<String>,
+    /// The set of entries that this profile grants rights over.
+    pub targetscope: Option<Filter>,
+    pub search_attrs: Option<Vec<String>>,
+    pub modify_present_attrs: Option<Vec<String>>,
+    pub modify_removed_attrs: Option<Vec<String>>,
+    pub modify_classes: Option<Vec<String>>,
+    pub create_attrs: Option<Vec<String>>,
+    pub create_classes: Option<Vec<String>>,
+    pub delete: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum AccessCheckAttrs {
+    /// Access to all attributes is granted.
+    Grant,
+    /// No access is granted.
+    Denied,
+    /// Access to only these attributes is granted.
+    Allow(Vec<String>),
+}
+
+impl fmt::Display for AccessCheckAttrs {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AccessCheckAttrs::Grant => write!(f, "all attributes"),
+            AccessCheckAttrs::Denied => write!(f, "denied"),
+            AccessCheckAttrs::Allow(attrs) if attrs.is_empty() => write!(f, "denied"),
+            AccessCheckAttrs::Allow(attrs) => write!(f, "{}", attrs.join(", ")),
+        }
+    }
+}
+
+/// The access that an identity is granted to a target entry.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct AccessCheckResponse {
+    pub subject: Uuid,
+    pub target: Uuid,
+    pub search: AccessCheckAttrs,
+    pub modify_present: AccessCheckAttrs,
+    pub modify_removed: AccessCheckAttrs,
+    pub modify_class: AccessCheckAttrs,
+    pub delete: bool,
+}
+
+impl fmt::Display for AccessCheckResponse {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "subject: {}", self.subject)?;
+        writeln!(f, "target: {}", self.target)?;
+        writeln!(f, "search: {}", self.search)?;
+        writeln!(f, "modify present: {}", self.modify_present)?;
+        writeln!(f, "modify removed: {}", self.modify_removed)?;
+        writeln!(f, "modify class: {}", self.modify_class)?;
+        writeln!(f, "delete: {}", self.delete)
+    }
+}
+
+/// A request for time limited membership of a group. If no duration is provided, the
+/// maximum duration permitted by the group is requested.
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct AccessRequestCreate {
+    /// The name or uuid of the group that membership is requested of.
+    pub group: String,
+    pub reason: Option<String>,
+    /// The number of seconds that membership is requested for.
+    pub duration: Option<u32>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum AccessRequestState {
+    /// Waiting for an approver to make a decision.
+    Pending,
+    /// Membership was granted to the requester.
+    Approved,
+    Denied,
+    /// The requester cancelled the request before a decision was made.
+    Withdrawn,
+}
+
+impl AccessRequestState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AccessRequestState::Pending => "pending",
+            AccessRequestState::Approved => "approved",
+            AccessRequestState::Denied => "denied",
+            AccessRequestState::Withdrawn => "withdrawn",
+        }
+    }
+}
+
+impl fmt::Display for AccessRequestState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for AccessRequestState {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pending" => Ok(AccessRequestState::Pending),
+            "approved" => Ok(AccessRequestState::Approved),
+            "denied" => Ok(AccessRequestState::Denied),
+            "withdrawn" => Ok(AccessRequestState::Withdrawn),
+            _ => Err(()),
+        }
+    }
+}
+
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct AccessRequest {
+    pub uuid: Uuid,
+    pub state: AccessRequestState,
+    /// The spn of the requesting account. This is absent if the account was deleted.
+    pub requester: Option<String>,
+    /// The spn of the requested group. This is absent if the group was deleted.
+    pub group: Option<String>,
+    pub reason: Option<String>,
+    /// The number of seconds that membership is requested for.
+    pub duration: u32,
+    pub decided_by: Option<String>,
+    /// The time that membership granted by this request ends, in RFC3339 format.
+    pub expiry: Option<String>,
+}
+
+impl fmt::Display for AccessRequest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "uuid: {}", self.uuid)?;
+        writeln!(f, "state: {}", self.state)?;
+        if let Some(requester) = &self.requester {
+            writeln!(f, "requester: {}", requester)?;
+        }
+        if let Some(group) = &self.group {
+            writeln!(f, "group: {}", group)?;
+        }
+        if let Some(reason) = &self.reason {
+            writeln!(f, "reason: {}", reason)?;
+        }
+        writeln!(f, "duration: {}s", self.duration)?;
+        if let Some(decided_by) = &self.decided_by {
+            writeln!(f, "decided by: {}", decided_by)?;
+        }
+        if let Some(expiry) = &self.expiry {
+            writeln!(f, "expiry: {}", expiry)?;
+        }
+        Ok(())
+    }
+}
+
+/// The kinds of directory change that a webhook can subscribe to.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookEventType {
+    AccountCreated,
+    /// The account expiry was set to a time that has already passed.
+    AccountDisabled,
+    AccountDeleted,
+    /// Members were added to or removed from a group.
+    GroupMembership,
+}
+
+impl WebhookEventType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WebhookEventType::AccountCreated => "account_created",
+            WebhookEventType::AccountDisabled => "account_disabled",
+            WebhookEventType::AccountDeleted => "account_deleted",
+            WebhookEventType::GroupMembership => "group_membership",
+        }
+    }
+}
+
+impl fmt::Display for WebhookEventType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for WebhookEventType {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "account_created" => Ok(WebhookEventType::AccountCreated),
+            "account_disabled" => Ok(WebhookEventType::AccountDisabled),
+            "account_deleted" => Ok(WebhookEventType::AccountDeleted),
+            "group_membership" => Ok(WebhookEventType::GroupMembership),
+            _ => Err(()),
+        }
+    }
+}
+
+/// The body that is posted to a webhook. The body is signed with the webhook secret as
+/// a hex encoded HMAC-SHA256 in the `X-Kanidm-Signature` header.
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct WebhookEvent {
+    /// A unique id for this event. Retried deliveries reuse the same id.
+    pub id: Uuid,
+    pub event: WebhookEventType,
+    /// The entry that changed.
+    pub uuid: Uuid,
+    pub spn: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub members_added: Vec<Uuid>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub members_removed: Vec<Uuid>,
+    /// The time of the change, in RFC3339 format.
+    pub time: String,
+}
+
+/// An event that could not be delivered to a webhook after all retries.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct WebhookDeadLetter {
+    pub event: WebhookEvent,
+    pub attempts: u32,
+    pub error: String,
+}
+
+impl fmt::Display for WebhookDeadLetter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "id: {}", self.event.id)?;
+        writeln!(f, "event: {}", self.event.event)?;
+        writeln!(f, "uuid: {}", self.event.uuid)?;
+        if let Some(spn) = &self.event.spn {
+            writeln!(f, "spn: {}", spn)?;
+        }
+        writeln!(f, "time: {}", self.event.time)?;
+        writeln!(f, "attempts: {}", self.attempts)?;
+        writeln!(f, "error: {}", self.error)
+    }
+}
+
 // Use OperationResponse here ...
 
 #[cfg(test)]
@@ -4,32 +4,39 @@ use std::net::IpAddr;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
-use kanidm_proto::internal::AppLink;
+use kanidm_proto::internal::{AppLink, Oauth2Grant};
 use kanidm_proto::v1::{
-    ApiToken, AuthIssueSession, AuthRequest, BackupCodesView, CURequest, CUSessionToken, CUStatus,
-    CredentialStatus, Entry as ProtoEntry, OperationError, RadiusAuthToken, SearchRequest,
-    SearchResponse, UatStatus, UnixGroupToken, UnixUserToken, UserAuthToken, WhoamiResponse,
+    AccessCheckResponse, AccessRequest, ApiToken, AuthIssueSession, AuthRequest, BackupCodesView,
+    CURequest, CUSessionToken, CUStatus, CredentialStatus, Entry as ProtoEntry,
+    Filter as ProtoFilter, OperationError, RadiusAuthToken, SearchRequest, SearchResponse,
+    UatStatus, UnixGroupToken, UnixUserToken, UserAuthToken, WhoamiResponse,
 };
 use ldap3_proto::simple::*;
 use regex::Regex;
 use tracing::{error, info, instrument, trace};
 use uuid::Uuid;
 
+use crate::interval::OnlineBackupChain;
+use kanidmd_lib::be::dbbackup::BackupOptions;
 use kanidmd_lib::be::BackendTransaction;
 use kanidmd_lib::prelude::*;
 use kanidmd_lib::{
     event::{OnlineBackupEvent, SearchEvent, SearchResult, WhoamiResult},
     filter::{Filter, FilterInvalid},
-    idm::account::ListUserAuthTokenEvent,
+    idm::accessprofile::AccessProfileCheckEvent,
+    idm::accessrequest::{AccessRequestGetEvent, AccessRequestListEvent},
+    idm::account::{ListOauth2GrantEvent, ListUserAuthTokenEvent},
     idm::credupdatesession::CredentialUpdateSessionToken,
     idm::event::{
         AuthEvent, AuthResult, CredentialStatusEvent, RadiusAuthTokenEvent, ReadBackupCodeEvent,
         UnixGroupTokenEvent, UnixUserAuthEvent, UnixUserTokenEvent,
     },
+    idm::group::DynGroupPreviewEvent,
     idm::ldap::{LdapBoundToken, LdapResponseState, LdapServer},
     idm::oauth2::{
-        AccessTokenIntrospectRequest, AccessTokenIntrospectResponse, AuthorisationRequest,
-        AuthoriseResponse, JwkKeySet, Oauth2Error, OidcDiscoveryResponse, OidcToken,
+        AccessTokenIntrospectRequest, AccessTokenIntrospectResponse, AuthorisationRequestRef,
+        AuthoriseResponse, ClientAuthInfo, ClientRegistrationResponse, JwkKeySet, Oauth2Error,
+        OidcDiscoveryResponse, OidcToken, PushedAuthorisationRequest, PushedAuthorisationResponse,
     },
     idm::server::{IdmServer, IdmServerTransaction},
     idm::serviceaccount::ListApiTokenEvent,
@@ -202,6 +209,8 @@ impl QueryServerReadV1 {
         msg: OnlineBackupEvent,
         outpath: &str,
         versions: usize,
+        opts: &BackupOptions,
+        chain: &mut OnlineBackupChain,
     ) -> Result<(), OperationError> {
         trace!(eventid = ?msg.eventid, "Begin online backup event");
 
@@ -215,29 +224,57 @@ impl QueryServerReadV1 {
         #[allow(clippy::unwrap_used)]
         let timestamp = now.format(&Rfc3339).unwrap();
         let dest_file = format!("{}/backup-{}.json", outpath, timestamp);
+        let incr_dest_file = format!("{}/backup-{}.incr.json", outpath, timestamp);
 
-        if Path::new(&dest_file).exists() {
-            error!(
-                "Online backup file {} already exists, will not overwrite it.",
-                dest_file
-            );
-            return Err(OperationError::InvalidState);
+        for file in [&dest_file, &incr_dest_file] {
+            if Path::new(file).exists() {
+                error!(
+                    "Online backup file {} already exists, will not overwrite it.",
+                    file
+                );
+                return Err(OperationError::InvalidState);
+            }
         }
 
+        let parent = chain.next_parent().cloned();
+
         // Scope to limit the read txn.
         {
             let mut idms_prox_read = self.idms.proxy_read().await;
-            idms_prox_read
-                .qs_read
-                .get_be_txn()
-                .backup(&dest_file)
-                .map(|()| {
+            let be_txn = idms_prox_read.qs_read.get_be_txn();
+
+            let incremental = match &parent {
+                Some(parent) => match be_txn.backup_incremental(&incr_dest_file, parent, opts) {
+                    Ok(manifest) => Some(manifest),
+                    Err(OperationError::BackupIncrementalUnavailable) => {
+                        warn!("Online incremental backup is not possible, taking a full backup");
+                        None
+                    }
+                    Err(e) => {
+                        error!("Online backup failed to create {}: {:?}", incr_dest_file, e);
+                        return Err(OperationError::InvalidState);
+                    }
+                },
+                None => None,
+            };
+
+            match incremental {
+                Some(manifest) => {
+                    info!(
+                        entries = manifest.entry_count,
+                        "Online incremental backup created {} successfully", incr_dest_file
+                    );
+                    chain.push_incremental(manifest);
+                }
+                None => {
+                    let manifest = be_txn.backup(&dest_file, opts).map_err(|e| {
+                        error!("Online backup failed to create {}: {:?}", dest_file, e);
+                        OperationError::InvalidState
+                    })?;
                     info!("Online backup created {} successfully", dest_file);
-                })
-                .map_err(|e| {
-                    error!("Online backup failed to create {}: {:?}", dest_file, e);
-                    OperationError::InvalidState
-                })?;
+                    chain.reset(manifest);
+                }
+            }
         }
 
         // pattern to find automatically generated backup files
@@ -250,9 +287,18 @@ impl QueryServerReadV1 {
                 OperationError::InvalidState
             },
         )?;
+        let incr_re = Regex::new(r"^backup-\d{4}-\d{2}-\d{2}T\d{2}:\d{2}:\d{2}Z\.incr\.json$")
+            .map_err(|error| {
+                error!(
+                    "Failed to parse regexp for online incremental backup files: {:?}",
+                    error
+                );
+                OperationError::InvalidState
+            })?;
 
         // cleanup of maximum backup versions to keep
         let mut backup_file_list: Vec<PathBuf> = Vec::new();
+        let mut incr_backup_file_list: Vec<PathBuf> = Vec::new();
         // get a list of backup files
         match fs::read_dir(outpath) {
             Ok(rd) => {
@@ -278,6 +324,8 @@ impl QueryServerReadV1 {
                     // check for a online backup file
                     if re.is_match(file_name) {
                         backup_file_list.push(pb.clone());
+                    } else if incr_re.is_match(file_name) {
+                        incr_backup_file_list.push(pb.clone());
                     }
                 }
             }
@@ -303,7 +351,21 @@ impl QueryServerReadV1 {
                 versions,
                 x
             );
+            // Incremental backups older than the oldest full backup we keep can no
+            // longer be restored, so they are removed too. The file names only differ
+            // in their timestamp up to the suffix, so they sort by time.
+            let oldest_kept = backup_file_list
+                .get(x)
+                .and_then(|pb| pb.file_name())
+                .map(|f| f.to_os_string());
             backup_file_list.truncate(x);
+            if let Some(oldest_kept) = oldest_kept {
+                backup_file_list.extend(incr_backup_file_list.into_iter().filter(|pb| {
+                    pb.file_name()
+                        .map(|f| f < oldest_kept.as_os_str())
+                        .unwrap_or(false)
+                }));
+            }
 
             // removing files
             for file in backup_file_list {
@@ -877,6 +939,38 @@ impl QueryServerReadV1 {
         idms_prox_read.account_list_user_auth_tokens(&lte)
     }
 
+    #[instrument(
+        level = "info",
+        skip_all,
+        fields(uuid = ?eventid)
+    )]
+    pub async fn handle_account_oauth2_grant_get(
+        &self,
+        uat: Option<String>,
+        uuid_or_name: String,
+        eventid: Uuid,
+    ) -> Result<Vec<Oauth2Grant>, OperationError> {
+        let ct = duration_from_epoch_now();
+        let mut idms_prox_read = self.idms.proxy_read().await;
+        let ident = idms_prox_read
+            .validate_and_parse_token_to_ident(uat.as_deref(), ct)
+            .map_err(|e| {
+                admin_error!("Invalid identity: {:?}", e);
+                e
+            })?;
+        let target = idms_prox_read
+            .qs_read
+            .name_to_uuid(uuid_or_name.as_str())
+            .map_err(|e| {
+                admin_error!("Error resolving id to target");
+                e
+            })?;
+
+        let lge = ListOauth2GrantEvent { ident, target };
+
+        idms_prox_read.account_list_oauth2_grants(&lge, ct)
+    }
+
     #[instrument(
         level = "info",
         skip_all,
@@ -1232,6 +1326,45 @@ impl QueryServerReadV1 {
         }
     }
 
+    #[instrument(
+        level = "info",
+        skip_all,
+        fields(uuid = ?eventid)
+    )]
+    pub async fn handle_webhook_secret_read(
+        &self,
+        uat: Option<String>,
+        filter: Filter<FilterInvalid>,
+        eventid: Uuid,
+    ) -> Result<Option<String>, OperationError> {
+        let ct = duration_from_epoch_now();
+        let mut idms_prox_read = self.idms.proxy_read().await;
+        let ident = idms_prox_read
+            .validate_and_parse_token_to_ident(uat.as_deref(), ct)
+            .map_err(|e| {
+                admin_error!("Invalid identity: {:?}", e);
+                e
+            })?;
+
+        let srch =
+            SearchEvent::from_internal_message(ident, &filter, None, &mut idms_prox_read.qs_read)
+                .map_err(|e| {
+                admin_error!("Failed to begin webhook secret read: {:?}", e);
+                e
+            })?;
+
+        trace!(?srch, "Begin event");
+
+        // We have to use search_ext to guarantee acs was applied.
+        idms_prox_read.qs_read.search_ext(&srch).map(|mut entries| {
+            entries.pop().and_then(|entry| {
+                entry
+                    .get_ava_single("webhook_secret")
+                    .and_then(|v| v.get_secret_str().map(str::to_string))
+            })
+        })
+    }
+
     #[instrument(
         level = "info",
         skip_all,
@@ -1240,7 +1373,7 @@ impl QueryServerReadV1 {
     pub async fn handle_oauth2_authorise(
         &self,
         uat: Option<String>,
-        auth_req: AuthorisationRequest,
+        auth_req: AuthorisationRequestRef,
         eventid: Uuid,
     ) -> Result<AuthoriseResponse, Oauth2Error> {
         let ct = duration_from_epoch_now();
@@ -1258,9 +1391,48 @@ impl QueryServerReadV1 {
             })?;
 
         // Now we can send to the idm server for authorisation checking.
+        let auth_req = idms_prox_read.resolve_oauth2_authorisation_request(auth_req, ct)?;
         idms_prox_read.check_oauth2_authorisation(&ident, &uat, &auth_req, ct)
     }
 
+    #[instrument(
+        level = "info",
+        skip_all,
+        fields(uuid = ?eventid)
+    )]
+    pub async fn handle_oauth2_pushed_authorisation(
+        &self,
+        client_auth_info: ClientAuthInfo,
+        par_req: PushedAuthorisationRequest,
+        eventid: Uuid,
+    ) -> Result<PushedAuthorisationResponse, Oauth2Error> {
+        let ct = duration_from_epoch_now();
+        let idms_prox_read = self.idms.proxy_read().await;
+        idms_prox_read.check_oauth2_pushed_authorisation(&client_auth_info, &par_req, ct)
+    }
+
+    #[instrument(
+        level = "info",
+        skip_all,
+        fields(uuid = ?eventid)
+    )]
+    pub async fn handle_oauth2_client_registration_get(
+        &self,
+        uat: Option<String>,
+        client_id: String,
+        eventid: Uuid,
+    ) -> Result<ClientRegistrationResponse, Oauth2Error> {
+        let ct = duration_from_epoch_now();
+        let mut idms_prox_read = self.idms.proxy_read().await;
+        let ident = idms_prox_read
+            .validate_and_parse_token_to_ident(uat.as_deref(), ct)
+            .map_err(|e| {
+                admin_error!(err = ?e, "Invalid identity");
+                Oauth2Error::AuthenticationRequired
+            })?;
+        idms_prox_read.oauth2_client_registration_get(&ident, &client_id)
+    }
+
     #[instrument(
         level = "info",
         skip_all,
@@ -1296,14 +1468,14 @@ impl QueryServerReadV1 {
     )]
     pub async fn handle_oauth2_token_introspect(
         &self,
-        client_authz: String,
+        client_auth_info: ClientAuthInfo,
         intr_req: AccessTokenIntrospectRequest,
         eventid: Uuid,
     ) -> Result<AccessTokenIntrospectResponse, Oauth2Error> {
         let ct = duration_from_epoch_now();
         let mut idms_prox_read = self.idms.proxy_read().await;
         // Now we can send to the idm server for introspection checking.
-        idms_prox_read.check_oauth2_token_introspect(&client_authz, &intr_req, ct)
+        idms_prox_read.check_oauth2_token_introspect(&client_auth_info, &intr_req, ct)
     }
 
     #[instrument(
@@ -1315,11 +1487,12 @@ impl QueryServerReadV1 {
         &self,
         client_id: String,
         client_authz: String,
+        client_auth_info: ClientAuthInfo,
         eventid: Uuid,
     ) -> Result<OidcToken, Oauth2Error> {
         let ct = duration_from_epoch_now();
         let mut idms_prox_read = self.idms.proxy_read().await;
-        idms_prox_read.oauth2_openid_userinfo(&client_id, &client_authz, ct)
+        idms_prox_read.oauth2_openid_userinfo(&client_id, &client_authz, &client_auth_info, ct)
     }
 
     #[instrument(
@@ -1373,6 +1546,129 @@ impl QueryServerReadV1 {
         idms_prox_read.list_applinks(&ident)
     }
 
+    #[instrument(
+        level = "info",
+        skip_all,
+        fields(uuid = ?eventid)
+    )]
+    pub async fn handle_access_profile_check(
+        &self,
+        uat: Option<String>,
+        subject: String,
+        target: String,
+        eventid: Uuid,
+    ) -> Result<AccessCheckResponse, OperationError> {
+        let ct = duration_from_epoch_now();
+        let mut idms_prox_read = self.idms.proxy_read().await;
+        let ident = idms_prox_read
+            .validate_and_parse_token_to_ident(uat.as_deref(), ct)
+            .map_err(|e| {
+                admin_error!("Invalid identity: {:?}", e);
+                e
+            })?;
+
+        let subject = idms_prox_read
+            .qs_read
+            .name_to_uuid(subject.as_str())
+            .map_err(|e| {
+                admin_error!(err = ?e, "Error resolving id to subject");
+                e
+            })?;
+        let target = idms_prox_read
+            .qs_read
+            .name_to_uuid(target.as_str())
+            .map_err(|e| {
+                admin_error!(err = ?e, "Error resolving id to target");
+                e
+            })?;
+
+        let ev = AccessProfileCheckEvent {
+            ident,
+            subject,
+            target,
+        };
+        idms_prox_read.access_profile_check(&ev)
+    }
+
+    #[instrument(
+        level = "info",
+        skip_all,
+        fields(uuid = ?eventid)
+    )]
+    pub async fn handle_dyngroup_preview(
+        &self,
+        uat: Option<String>,
+        filter: ProtoFilter,
+        eventid: Uuid,
+    ) -> Result<Vec<String>, OperationError> {
+        let ct = duration_from_epoch_now();
+        let mut idms_prox_read = self.idms.proxy_read().await;
+        let ident = idms_prox_read
+            .validate_and_parse_token_to_ident(uat.as_deref(), ct)
+            .map_err(|e| {
+                admin_error!("Invalid identity: {:?}", e);
+                e
+            })?;
+
+        let ev = DynGroupPreviewEvent { ident, filter };
+        idms_prox_read.dyngroup_preview(&ev)
+    }
+
+    #[instrument(
+        level = "info",
+        skip_all,
+        fields(uuid = ?eventid)
+    )]
+    pub async fn handle_access_request_list(
+        &self,
+        uat: Option<String>,
+        eventid: Uuid,
+    ) -> Result<Vec<AccessRequest>, OperationError> {
+        let ct = duration_from_epoch_now();
+        let mut idms_prox_read = self.idms.proxy_read().await;
+        let ident = idms_prox_read
+            .validate_and_parse_token_to_ident(uat.as_deref(), ct)
+            .map_err(|e| {
+                admin_error!("Invalid identity: {:?}", e);
+                e
+            })?;
+
+        let ev = AccessRequestListEvent { ident };
+        idms_prox_read.access_request_list(&ev)
+    }
+
+    #[instrument(
+        level = "info",
+        skip_all,
+        fields(uuid = ?eventid)
+    )]
+    pub async fn handle_access_request_get(
+        &self,
+        uat: Option<String>,
+        uuid_or_name: String,
+        eventid: Uuid,
+    ) -> Result<AccessRequest, OperationError> {
+        let ct = duration_from_epoch_now();
+        let mut idms_prox_read = self.idms.proxy_read().await;
+        let ident = idms_prox_read
+            .validate_and_parse_token_to_ident(uat.as_deref(), ct)
+            .map_err(|e| {
+                admin_error!("Invalid identity: {:?}", e);
+                e
+            })?;
+
+        let target = idms_prox_read
+            .qs_read
+            .name_to_uuid(uuid_or_name.as_str())
+            .map_err(|e| {
+                admin_error!(err = ?e, "Error resolving id to target");
+                e
+            })?;
+
+        let ev = AccessRequestGetEvent { ident, target };
+        idms_prox_read.access_request_get(&ev)
+    }
+
     #[instrument(
         level = "info",
         skip_all,
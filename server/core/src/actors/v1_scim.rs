@@ -3,10 +3,13 @@ use kanidmd_lib::prelude::*;
 use crate::{QueryServerReadV1, QueryServerWriteV1};
 use kanidmd_lib::idm::scim::{
     GenerateScimSyncTokenEvent, ScimSyncFinaliseEvent, ScimSyncTerminateEvent, ScimSyncUpdateEvent,
+    ScimV2CreateEvent, ScimV2DeleteEvent, ScimV2GetEvent, ScimV2PatchEvent, ScimV2ReplaceEvent,
+    ScimV2ResourceType, ScimV2SearchEvent,
 };
 use kanidmd_lib::idm::server::IdmServerTransaction;
 
 use kanidm_proto::scim_v1::{ScimSyncRequest, ScimSyncState};
+use kanidm_proto::scim_v2::{ScimV2ListResponse, ScimV2PatchRequest, ScimV2Resource};
 
 impl QueryServerWriteV1 {
     #[instrument(
@@ -175,6 +178,131 @@ impl QueryServerWriteV1 {
             .scim_sync_apply(&sse, &changes, ct)
             .and_then(|r| idms_prox_write.commit().map(|_| r))
     }
+
+    #[instrument(
+        level = "info",
+        skip_all,
+        fields(uuid = ?eventid)
+    )]
+    pub async fn handle_scim_v2_create(
+        &self,
+        uat: Option<String>,
+        resource: ScimV2Resource,
+        eventid: Uuid,
+    ) -> Result<ScimV2Resource, OperationError> {
+        let ct = duration_from_epoch_now();
+        let mut idms_prox_write = self.idms.proxy_write(ct).await;
+        let ident = idms_prox_write
+            .validate_and_parse_token_to_ident(uat.as_deref(), ct)
+            .map_err(|e| {
+                admin_error!(err = ?e, "Invalid identity");
+                e
+            })?;
+
+        let ev = ScimV2CreateEvent { ident, resource };
+
+        idms_prox_write
+            .scim_v2_create(&ev)
+            .and_then(|r| idms_prox_write.commit().map(|_| r))
+    }
+
+    #[instrument(
+        level = "info",
+        skip_all,
+        fields(uuid = ?eventid)
+    )]
+    pub async fn handle_scim_v2_replace(
+        &self,
+        uat: Option<String>,
+        target: Uuid,
+        resource: ScimV2Resource,
+        eventid: Uuid,
+    ) -> Result<ScimV2Resource, OperationError> {
+        let ct = duration_from_epoch_now();
+        let mut idms_prox_write = self.idms.proxy_write(ct).await;
+        let ident = idms_prox_write
+            .validate_and_parse_token_to_ident(uat.as_deref(), ct)
+            .map_err(|e| {
+                admin_error!(err = ?e, "Invalid identity");
+                e
+            })?;
+
+        let ev = ScimV2ReplaceEvent {
+            ident,
+            target,
+            resource,
+        };
+
+        idms_prox_write
+            .scim_v2_replace(&ev)
+            .and_then(|r| idms_prox_write.commit().map(|_| r))
+    }
+
+    #[instrument(
+        level = "info",
+        skip_all,
+        fields(uuid = ?eventid)
+    )]
+    pub async fn handle_scim_v2_patch(
+        &self,
+        uat: Option<String>,
+        resource: ScimV2ResourceType,
+        target: Uuid,
+        request: ScimV2PatchRequest,
+        eventid: Uuid,
+    ) -> Result<ScimV2Resource, OperationError> {
+        let ct = duration_from_epoch_now();
+        let mut idms_prox_write = self.idms.proxy_write(ct).await;
+        let ident = idms_prox_write
+            .validate_and_parse_token_to_ident(uat.as_deref(), ct)
+            .map_err(|e| {
+                admin_error!(err = ?e, "Invalid identity");
+                e
+            })?;
+
+        let ev = ScimV2PatchEvent {
+            ident,
+            resource,
+            target,
+            request,
+        };
+
+        idms_prox_write
+            .scim_v2_patch(&ev)
+            .and_then(|r| idms_prox_write.commit().map(|_| r))
+    }
+
+    #[instrument(
+        level = "info",
+        skip_all,
+        fields(uuid = ?eventid)
+    )]
+    pub async fn handle_scim_v2_delete(
+        &self,
+        uat: Option<String>,
+        resource: ScimV2ResourceType,
+        target: Uuid,
+        eventid: Uuid,
+    ) -> Result<(), OperationError> {
+        let ct = duration_from_epoch_now();
+        let mut idms_prox_write = self.idms.proxy_write(ct).await;
+        let ident = idms_prox_write
+            .validate_and_parse_token_to_ident(uat.as_deref(), ct)
+            .map_err(|e| {
+                admin_error!(err = ?e, "Invalid identity");
+                e
+            })?;
+
+        let ev = ScimV2DeleteEvent {
+            ident,
+            resource,
+            target,
+        };
+
+        idms_prox_write
+            .scim_v2_delete(&ev)
+            .and_then(|r| idms_prox_write.commit().map(|_| r))
+    }
 }
 
 impl QueryServerReadV1 {
@@ -195,4 +323,68 @@ impl QueryServerReadV1 {
 
         idms_prox_read.scim_sync_get_state(&ident)
     }
+
+    #[instrument(
+        level = "info",
+        skip_all,
+        fields(uuid = ?eventid)
+    )]
+    pub async fn handle_scim_v2_search(
+        &self,
+        uat: Option<String>,
+        resource: ScimV2ResourceType,
+        filter: Option<String>,
+        start_index: Option<usize>,
+        count: Option<usize>,
+        eventid: Uuid,
+    ) -> Result<ScimV2ListResponse<ScimV2Resource>, OperationError> {
+        let ct = duration_from_epoch_now();
+        let mut idms_prox_read = self.idms.proxy_read().await;
+        let ident = idms_prox_read
+            .validate_and_parse_token_to_ident(uat.as_deref(), ct)
+            .map_err(|e| {
+                admin_error!(err = ?e, "Invalid identity");
+                e
+            })?;
+
+        let ev = ScimV2SearchEvent {
+            ident,
+            resource,
+            filter,
+            start_index,
+            count,
+        };
+
+        idms_prox_read.scim_v2_search(&ev, ct)
+    }
+
+    #[instrument(
+        level = "info",
+        skip_all,
+        fields(uuid = ?eventid)
+    )]
+    pub async fn handle_scim_v2_get(
+        &self,
+        uat: Option<String>,
+        resource: ScimV2ResourceType,
+        target: Uuid,
+        eventid: Uuid,
+    ) -> Result<ScimV2Resource, OperationError> {
+        let ct = duration_from_epoch_now();
+        let mut idms_prox_read = self.idms.proxy_read().await;
+        let ident = idms_prox_read
+            .validate_and_parse_token_to_ident(uat.as_deref(), ct)
+            .map_err(|e| {
+                admin_error!(err = ?e, "Invalid identity");
+                e
+            })?;
+
+        let ev = ScimV2GetEvent {
+            ident,
+            resource,
+            target,
+        };
+
+        idms_prox_read.scim_v2_get(&ev, ct)
+    }
 }
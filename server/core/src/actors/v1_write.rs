@@ -1,10 +1,14 @@
 use std::time::Duration;
 use std::{iter, sync::Arc};
 
+use kanidm_proto::internal::{
+    CredentialResetEmailRequest, HelpdeskCredentialResetRequest, IdentifyUserRequest,
+    IdentifyUserResponse,
+};
 use kanidm_proto::v1::{
-    AccountUnixExtend, CUIntentToken, CUSessionToken, CUStatus, CreateRequest, DeleteRequest,
-    Entry as ProtoEntry, GroupUnixExtend, Modify as ProtoModify, ModifyList as ProtoModifyList,
-    ModifyRequest, OperationError,
+    AccessProfile, AccessRequestCreate, AccountUnixExtend, CUIntentToken, CUSessionToken, CUStatus,
+    CreateRequest, DeleteRequest, Entry as ProtoEntry, GroupUnixExtend, Modify as ProtoModify,
+    ModifyList as ProtoModifyList, ModifyRequest, OperationError,
 };
 use time::OffsetDateTime;
 use tracing::{info, instrument, span, trace, Level};
@@ -12,23 +16,33 @@ use uuid::Uuid;
 
 use kanidmd_lib::{
     event::{
-        CreateEvent, DeleteEvent, ModifyEvent, PurgeRecycledEvent, PurgeTombstoneEvent,
-        ReviveRecycledEvent,
+        CreateEvent, DeleteEvent, ModifyEvent, PurgeMemberValidityEvent, PurgeRecycledEntryEvent,
+        PurgeRecycledEvent, PurgeTombstoneEvent, ReviveRecycledEvent,
     },
     filter::{Filter, FilterInvalid},
-    idm::account::DestroySessionTokenEvent,
+    idm::accessprofile::{AccessProfileCreateEvent, AccessProfileUpdateEvent},
+    idm::accessrequest::{
+        AccessRequestCreateEvent, AccessRequestDecideEvent, AccessRequestWithdrawEvent,
+    },
+    idm::account::{
+        DestroyAllSessionTokensEvent, DestroySessionTokenEvent, RevokeOauth2GrantEvent,
+    },
     idm::credupdatesession::{
         CredentialUpdateIntentToken, CredentialUpdateSessionToken, InitCredentialUpdateEvent,
         InitCredentialUpdateIntentEvent,
     },
     idm::delayed::DelayedAction,
     idm::event::{GeneratePasswordEvent, RegenerateRadiusSecretEvent, UnixPasswordChangeEvent},
+    idm::identityverification::{HelpdeskCredentialResetEvent, IdentifyUserEvent},
+    idm::mail::{CredentialResetEmailEvent, OutboundMessage},
     idm::oauth2::{
-        AccessTokenRequest, AccessTokenResponse, AuthorisePermitSuccess, Oauth2Error,
-        TokenRevokeRequest,
+        AccessTokenRequest, AccessTokenResponse, AuthorisePermitSuccess, ClientAuthInfo,
+        ClientRegistrationRequest, ClientRegistrationResponse, EndSessionRequest,
+        EndSessionResponse, Oauth2Error, TokenRevokeRequest,
     },
-    idm::server::{IdmServer, IdmServerTransaction},
+    idm::server::{IdmServer, IdmServerProxyWriteTransaction, IdmServerTransaction},
     idm::serviceaccount::{DestroyApiTokenEvent, GenerateApiTokenEvent},
+    idm::webhook::{WebhookDelivery, WebhookRetryEvent},
     modify::{Modify, ModifyInvalid, ModifyList},
     utils::duration_from_epoch_now,
     value::{PartialValue, Value},
@@ -348,6 +362,7 @@ impl QueryServerWriteV1 {
         &self,
         uat: Option<String>,
         filter: Filter<FilterInvalid>,
+        restore_memberships: bool,
         eventid: Uuid,
     ) -> Result<(), OperationError> {
         let mut idms_prox_write = self.idms.proxy_write(duration_from_epoch_now()).await;
@@ -358,7 +373,12 @@ impl QueryServerWriteV1 {
                 admin_error!(err = ?e, "Invalid identity");
                 e
             })?;
-        let rev = match ReviveRecycledEvent::from_parts(ident, &filter, &idms_prox_write.qs_write) {
+        let rev = match ReviveRecycledEvent::from_parts(
+            ident,
+            &filter,
+            restore_memberships,
+            &idms_prox_write.qs_write,
+        ) {
             Ok(r) => r,
             Err(e) => {
                 admin_error!(err = ?e, "Failed to begin revive");
@@ -379,12 +399,12 @@ impl QueryServerWriteV1 {
         skip_all,
         fields(uuid = ?eventid)
     )]
-    pub async fn handle_service_account_credential_generate(
+    pub async fn handle_purgerecycledentry(
         &self,
         uat: Option<String>,
-        uuid_or_name: String,
+        filter: Filter<FilterInvalid>,
         eventid: Uuid,
-    ) -> Result<String, OperationError> {
+    ) -> Result<(), OperationError> {
         let ct = duration_from_epoch_now();
         let mut idms_prox_write = self.idms.proxy_write(ct).await;
         let ident = idms_prox_write
@@ -393,30 +413,18 @@ impl QueryServerWriteV1 {
                 admin_error!(err = ?e, "Invalid identity");
                 e
             })?;
-
-        // given the uuid_or_name, determine the target uuid.
-        // We can either do this by trying to parse the name or by creating a filter
-        // to find the entry - there are risks to both TBH ... especially when the uuid
-        // is also an entries name, but that they aren't the same entry.
-
-        let target_uuid = idms_prox_write
-            .qs_write
-            .name_to_uuid(uuid_or_name.as_str())
+        let pe = PurgeRecycledEntryEvent::from_parts(ident, &filter, &idms_prox_write.qs_write)
             .map_err(|e| {
-                admin_error!(err = ?e, "Error resolving id to target");
+                admin_error!(err = ?e, "Failed to begin recycled entry purge");
                 e
             })?;
 
-        let gpe = GeneratePasswordEvent::from_parts(ident, target_uuid).map_err(|e| {
-            admin_error!(
-                err = ?e,
-                "Failed to begin handle_service_account_credential_generate",
-            );
-            e
-        })?;
+        trace!(?pe, "Begin purge recycled entry event");
+
         idms_prox_write
-            .generate_service_account_password(&gpe)
-            .and_then(|r| idms_prox_write.commit().map(|_| r))
+            .qs_write
+            .purge_recycled_entry(&pe)
+            .and_then(|_| idms_prox_write.commit().map(|_| ()))
     }
 
     #[instrument(
@@ -424,15 +432,12 @@ impl QueryServerWriteV1 {
         skip_all,
         fields(uuid = ?eventid)
     )]
-    pub async fn handle_service_account_api_token_generate(
+    pub async fn handle_webhook_dead_letter_retry(
         &self,
         uat: Option<String>,
-        uuid_or_name: String,
-        label: String,
-        expiry: Option<OffsetDateTime>,
-        read_write: bool,
+        webhook_id: String,
         eventid: Uuid,
-    ) -> Result<String, OperationError> {
+    ) -> Result<usize, OperationError> {
         let ct = duration_from_epoch_now();
         let mut idms_prox_write = self.idms.proxy_write(ct).await;
         let ident = idms_prox_write
@@ -441,26 +446,36 @@ impl QueryServerWriteV1 {
                 admin_error!(err = ?e, "Invalid identity");
                 e
             })?;
-
         let target = idms_prox_write
             .qs_write
-            .name_to_uuid(uuid_or_name.as_str())
+            .name_to_uuid(webhook_id.as_str())
             .map_err(|e| {
                 admin_error!(err = ?e, "Error resolving id to target");
                 e
             })?;
 
-        let gte = GenerateApiTokenEvent {
-            ident,
-            target,
-            label,
-            expiry,
-            read_write,
-        };
+        let ev = WebhookRetryEvent { ident, target };
 
         idms_prox_write
-            .service_account_generate_api_token(&gte, ct)
-            .and_then(|r| idms_prox_write.commit().map(|_| r))
+            .webhook_dead_letter_retry(&ev)
+            .and_then(|queued| idms_prox_write.commit().map(|_| queued))
+    }
+
+    /// Store an event that the webhook worker gave up delivering.
+    pub(crate) async fn handle_webhook_dead_letter(
+        &self,
+        delivery: &WebhookDelivery,
+        attempts: u32,
+        error: String,
+    ) {
+        let ct = duration_from_epoch_now();
+        let mut idms_prox_write = self.idms.proxy_write(ct).await;
+        if let Err(res) = idms_prox_write
+            .webhook_dead_letter(delivery, attempts, error)
+            .and_then(|_| idms_prox_write.commit())
+        {
+            admin_error!(?res, webhook = ?delivery.webhook, "Unable to store undelivered webhook event");
+        }
     }
 
     #[instrument(
@@ -468,11 +483,37 @@ impl QueryServerWriteV1 {
         skip_all,
         fields(uuid = ?eventid)
     )]
-    pub async fn handle_service_account_api_token_destroy(
+    pub async fn handle_access_profile_create(
+        &self,
+        uat: Option<String>,
+        profile: AccessProfile,
+        eventid: Uuid,
+    ) -> Result<(), OperationError> {
+        let ct = duration_from_epoch_now();
+        let mut idms_prox_write = self.idms.proxy_write(ct).await;
+        let ident = idms_prox_write
+            .validate_and_parse_token_to_ident(uat.as_deref(), ct)
+            .map_err(|e| {
+                admin_error!(err = ?e, "Invalid identity");
+                e
+            })?;
+
+        let ev = AccessProfileCreateEvent { ident, profile };
+        idms_prox_write
+            .access_profile_create(&ev)
+            .and_then(|_| idms_prox_write.commit())
+    }
+
+    #[instrument(
+        level = "info",
+        skip_all,
+        fields(uuid = ?eventid)
+    )]
+    pub async fn handle_access_profile_update(
         &self,
         uat: Option<String>,
         uuid_or_name: String,
-        token_id: Uuid,
+        profile: AccessProfile,
         eventid: Uuid,
     ) -> Result<(), OperationError> {
         let ct = duration_from_epoch_now();
@@ -492,15 +533,14 @@ impl QueryServerWriteV1 {
                 e
             })?;
 
-        let dte = DestroyApiTokenEvent {
+        let ev = AccessProfileUpdateEvent {
             ident,
             target,
-            token_id,
+            profile,
         };
-
         idms_prox_write
-            .service_account_destroy_api_token(&dte)
-            .and_then(|r| idms_prox_write.commit().map(|_| r))
+            .access_profile_update(&ev)
+            .and_then(|_| idms_prox_write.commit())
     }
 
     #[instrument(
@@ -508,13 +548,12 @@ impl QueryServerWriteV1 {
         skip_all,
         fields(uuid = ?eventid)
     )]
-    pub async fn handle_account_user_auth_token_destroy(
+    pub async fn handle_access_request_create(
         &self,
         uat: Option<String>,
-        uuid_or_name: String,
-        token_id: Uuid,
+        request: AccessRequestCreate,
         eventid: Uuid,
-    ) -> Result<(), OperationError> {
+    ) -> Result<Uuid, OperationError> {
         let ct = duration_from_epoch_now();
         let mut idms_prox_write = self.idms.proxy_write(ct).await;
         let ident = idms_prox_write
@@ -524,23 +563,23 @@ impl QueryServerWriteV1 {
                 e
             })?;
 
-        let target = idms_prox_write
+        let group = idms_prox_write
             .qs_write
-            .name_to_uuid(uuid_or_name.as_str())
+            .name_to_uuid(request.group.as_str())
             .map_err(|e| {
-                admin_error!(err = ?e, "Error resolving id to target");
+                admin_error!(err = ?e, "Error resolving id to group");
                 e
             })?;
 
-        let dte = DestroySessionTokenEvent {
+        let ev = AccessRequestCreateEvent {
             ident,
-            target,
-            token_id,
+            group,
+            reason: request.reason,
+            duration: request.duration,
         };
-
         idms_prox_write
-            .account_destroy_session_token(&dte)
-            .and_then(|r| idms_prox_write.commit().map(|_| r))
+            .access_request_create(&ev, ct)
+            .and_then(|request_uuid| idms_prox_write.commit().map(|_| request_uuid))
     }
 
     #[instrument(
@@ -548,44 +587,38 @@ impl QueryServerWriteV1 {
         skip_all,
         fields(uuid = ?eventid)
     )]
-    pub async fn handle_logout(
+    pub async fn handle_access_request_decide(
         &self,
         uat: Option<String>,
+        uuid_or_name: String,
+        approve: bool,
         eventid: Uuid,
     ) -> Result<(), OperationError> {
         let ct = duration_from_epoch_now();
         let mut idms_prox_write = self.idms.proxy_write(ct).await;
-
-        // We specifically need a uat here to assess the auth type!
-        let (ident, uat) = idms_prox_write
-            .validate_and_parse_uat(uat.as_deref(), ct)
-            .and_then(|uat| {
-                idms_prox_write
-                    .process_uat_to_identity(&uat, ct)
-                    .map(|ident| (ident, uat))
+        let ident = idms_prox_write
+            .validate_and_parse_token_to_ident(uat.as_deref(), ct)
+            .map_err(|e| {
+                admin_error!(err = ?e, "Invalid identity");
+                e
             })?;
 
-        if uat.uuid == UUID_ANONYMOUS {
-            info!("Ignoring request to logout anonymous session - these sessions are not recorded");
-            return Ok(());
-        }
-
-        let target = ident.get_uuid().ok_or_else(|| {
-            admin_error!("Invalid identity - no uuid present");
-            OperationError::InvalidState
-        })?;
-
-        let token_id = ident.get_session_id();
+        let target = idms_prox_write
+            .qs_write
+            .name_to_uuid(uuid_or_name.as_str())
+            .map_err(|e| {
+                admin_error!(err = ?e, "Error resolving id to target");
+                e
+            })?;
 
-        let dte = DestroySessionTokenEvent {
+        let ev = AccessRequestDecideEvent {
             ident,
             target,
-            token_id,
+            approve,
         };
-
         idms_prox_write
-            .account_destroy_session_token(&dte)
-            .and_then(|r| idms_prox_write.commit().map(|_| r))
+            .access_request_decide(&ev, ct)
+            .and_then(|_| idms_prox_write.commit())
     }
 
     #[instrument(
@@ -593,12 +626,12 @@ impl QueryServerWriteV1 {
         skip_all,
         fields(uuid = ?eventid)
     )]
-    pub async fn handle_idmcredentialupdate(
+    pub async fn handle_access_request_withdraw(
         &self,
         uat: Option<String>,
         uuid_or_name: String,
         eventid: Uuid,
-    ) -> Result<(CUSessionToken, CUStatus), OperationError> {
+    ) -> Result<(), OperationError> {
         let ct = duration_from_epoch_now();
         let mut idms_prox_write = self.idms.proxy_write(ct).await;
         let ident = idms_prox_write
@@ -608,7 +641,7 @@ impl QueryServerWriteV1 {
                 e
             })?;
 
-        let target_uuid = idms_prox_write
+        let target = idms_prox_write
             .qs_write
             .name_to_uuid(uuid_or_name.as_str())
             .map_err(|e| {
@@ -616,38 +649,23 @@ impl QueryServerWriteV1 {
                 e
             })?;
 
+        let ev = AccessRequestWithdrawEvent { ident, target };
         idms_prox_write
-            .init_credential_update(&InitCredentialUpdateEvent::new(ident, target_uuid), ct)
-            .and_then(|tok| idms_prox_write.commit().map(|_| tok))
-            .map_err(|e| {
-                admin_error!(
-                    err = ?e,
-                    "Failed to begin init_credential_update",
-                );
-                e
-            })
-            .map(|(tok, sta)| {
-                (
-                    CUSessionToken {
-                        token: tok.token_enc,
-                    },
-                    sta.into(),
-                )
-            })
+            .access_request_withdraw(&ev, ct)
+            .and_then(|_| idms_prox_write.commit())
     }
 
     #[instrument(
         level = "info",
         skip_all,
-        fields(uuid = ?eventid),
+        fields(uuid = ?eventid)
     )]
-    pub async fn handle_idmcredentialupdateintent(
+    pub async fn handle_service_account_credential_generate(
         &self,
         uat: Option<String>,
         uuid_or_name: String,
-        ttl: Option<Duration>,
         eventid: Uuid,
-    ) -> Result<CUIntentToken, OperationError> {
+    ) -> Result<String, OperationError> {
         let ct = duration_from_epoch_now();
         let mut idms_prox_write = self.idms.proxy_write(ct).await;
         let ident = idms_prox_write
@@ -657,6 +675,11 @@ impl QueryServerWriteV1 {
                 e
             })?;
 
+        // given the uuid_or_name, determine the target uuid.
+        // We can either do this by trying to parse the name or by creating a filter
+        // to find the entry - there are risks to both TBH ... especially when the uuid
+        // is also an entries name, but that they aren't the same entry.
+
         let target_uuid = idms_prox_write
             .qs_write
             .name_to_uuid(uuid_or_name.as_str())
@@ -665,22 +688,16 @@ impl QueryServerWriteV1 {
                 e
             })?;
 
+        let gpe = GeneratePasswordEvent::from_parts(ident, target_uuid).map_err(|e| {
+            admin_error!(
+                err = ?e,
+                "Failed to begin handle_service_account_credential_generate",
+            );
+            e
+        })?;
         idms_prox_write
-            .init_credential_update_intent(
-                &InitCredentialUpdateIntentEvent::new(ident, target_uuid, ttl),
-                ct,
-            )
-            .and_then(|tok| idms_prox_write.commit().map(|_| tok))
-            .map_err(|e| {
-                admin_error!(
-                    err = ?e,
-                    "Failed to begin init_credential_update_intent",
-                );
-                e
-            })
-            .map(|tok| CUIntentToken {
-                token: tok.intent_id,
-            })
+            .generate_service_account_password(&gpe)
+            .and_then(|r| idms_prox_write.commit().map(|_| r))
     }
 
     #[instrument(
@@ -688,35 +705,43 @@ impl QueryServerWriteV1 {
         skip_all,
         fields(uuid = ?eventid)
     )]
-    pub async fn handle_idmcredentialexchangeintent(
+    pub async fn handle_service_account_api_token_generate(
         &self,
-        intent_token: CUIntentToken,
+        uat: Option<String>,
+        uuid_or_name: String,
+        label: String,
+        expiry: Option<OffsetDateTime>,
+        read_write: bool,
         eventid: Uuid,
-    ) -> Result<(CUSessionToken, CUStatus), OperationError> {
+    ) -> Result<String, OperationError> {
         let ct = duration_from_epoch_now();
         let mut idms_prox_write = self.idms.proxy_write(ct).await;
-        let intent_token = CredentialUpdateIntentToken {
-            intent_id: intent_token.token,
-        };
-        // TODO: this is throwing a 500 error when a session is already in use, that seems bad?
-        idms_prox_write
-            .exchange_intent_credential_update(intent_token, ct)
-            .and_then(|tok| idms_prox_write.commit().map(|_| tok))
+        let ident = idms_prox_write
+            .validate_and_parse_token_to_ident(uat.as_deref(), ct)
             .map_err(|e| {
-                admin_error!(
-                    err = ?e,
-                    "Failed to begin exchange_intent_credential_update",
-                );
+                admin_error!(err = ?e, "Invalid identity");
                 e
-            })
-            .map(|(tok, sta)| {
-                (
-                    CUSessionToken {
-                        token: tok.token_enc,
-                    },
-                    sta.into(),
-                )
-            })
+            })?;
+
+        let target = idms_prox_write
+            .qs_write
+            .name_to_uuid(uuid_or_name.as_str())
+            .map_err(|e| {
+                admin_error!(err = ?e, "Error resolving id to target");
+                e
+            })?;
+
+        let gte = GenerateApiTokenEvent {
+            ident,
+            target,
+            label,
+            expiry,
+            read_write,
+        };
+
+        idms_prox_write
+            .service_account_generate_api_token(&gte, ct)
+            .and_then(|r| idms_prox_write.commit().map(|_| r))
     }
 
     #[instrument(
@@ -724,7 +749,501 @@ impl QueryServerWriteV1 {
         skip_all,
         fields(uuid = ?eventid)
     )]
-    pub async fn handle_idmcredentialupdatecommit(
+    pub async fn handle_service_account_api_token_destroy(
+        &self,
+        uat: Option<String>,
+        uuid_or_name: String,
+        token_id: Uuid,
+        eventid: Uuid,
+    ) -> Result<(), OperationError> {
+        let ct = duration_from_epoch_now();
+        let mut idms_prox_write = self.idms.proxy_write(ct).await;
+        let ident = idms_prox_write
+            .validate_and_parse_token_to_ident(uat.as_deref(), ct)
+            .map_err(|e| {
+                admin_error!(err = ?e, "Invalid identity");
+                e
+            })?;
+
+        let target = idms_prox_write
+            .qs_write
+            .name_to_uuid(uuid_or_name.as_str())
+            .map_err(|e| {
+                admin_error!(err = ?e, "Error resolving id to target");
+                e
+            })?;
+
+        let dte = DestroyApiTokenEvent {
+            ident,
+            target,
+            token_id,
+        };
+
+        idms_prox_write
+            .service_account_destroy_api_token(&dte)
+            .and_then(|r| idms_prox_write.commit().map(|_| r))
+    }
+
+    #[instrument(
+        level = "info",
+        skip_all,
+        fields(uuid = ?eventid)
+    )]
+    pub async fn handle_account_user_auth_token_destroy(
+        &self,
+        uat: Option<String>,
+        uuid_or_name: String,
+        token_id: Uuid,
+        eventid: Uuid,
+    ) -> Result<(), OperationError> {
+        let ct = duration_from_epoch_now();
+        let mut idms_prox_write = self.idms.proxy_write(ct).await;
+        let ident = idms_prox_write
+            .validate_and_parse_token_to_ident(uat.as_deref(), ct)
+            .map_err(|e| {
+                admin_error!(err = ?e, "Invalid identity");
+                e
+            })?;
+
+        let target = idms_prox_write
+            .qs_write
+            .name_to_uuid(uuid_or_name.as_str())
+            .map_err(|e| {
+                admin_error!(err = ?e, "Error resolving id to target");
+                e
+            })?;
+
+        let dte = DestroySessionTokenEvent {
+            ident,
+            target,
+            token_id,
+        };
+
+        idms_prox_write
+            .account_destroy_session_token(&dte)
+            .and_then(|r| idms_prox_write.commit().map(|_| r))
+    }
+
+    #[instrument(
+        level = "info",
+        skip_all,
+        fields(uuid = ?eventid)
+    )]
+    pub async fn handle_account_user_auth_token_destroy_all(
+        &self,
+        uat: Option<String>,
+        uuid_or_name: String,
+        eventid: Uuid,
+    ) -> Result<(), OperationError> {
+        let ct = duration_from_epoch_now();
+        let mut idms_prox_write = self.idms.proxy_write(ct).await;
+        let ident = idms_prox_write
+            .validate_and_parse_token_to_ident(uat.as_deref(), ct)
+            .map_err(|e| {
+                admin_error!(err = ?e, "Invalid identity");
+                e
+            })?;
+
+        let target = idms_prox_write
+            .qs_write
+            .name_to_uuid(uuid_or_name.as_str())
+            .map_err(|e| {
+                admin_error!(err = ?e, "Error resolving id to target");
+                e
+            })?;
+
+        let dte = DestroyAllSessionTokensEvent { ident, target };
+
+        idms_prox_write
+            .account_destroy_all_session_tokens(&dte)
+            .and_then(|r| idms_prox_write.commit().map(|_| r))
+    }
+
+    #[instrument(
+        level = "info",
+        skip_all,
+        fields(uuid = ?eventid)
+    )]
+    pub async fn handle_account_oauth2_grant_revoke(
+        &self,
+        uat: Option<String>,
+        uuid_or_name: String,
+        rs_name: String,
+        eventid: Uuid,
+    ) -> Result<(), OperationError> {
+        let ct = duration_from_epoch_now();
+        let mut idms_prox_write = self.idms.proxy_write(ct).await;
+        let ident = idms_prox_write
+            .validate_and_parse_token_to_ident(uat.as_deref(), ct)
+            .map_err(|e| {
+                admin_error!(err = ?e, "Invalid identity");
+                e
+            })?;
+
+        let target = idms_prox_write
+            .qs_write
+            .name_to_uuid(uuid_or_name.as_str())
+            .map_err(|e| {
+                admin_error!(err = ?e, "Error resolving id to target");
+                e
+            })?;
+
+        let rge = RevokeOauth2GrantEvent {
+            ident,
+            target,
+            rs_name,
+        };
+
+        idms_prox_write
+            .account_revoke_oauth2_grant(&rge)
+            .and_then(|r| idms_prox_write.commit().map(|_| r))
+    }
+
+    #[instrument(
+        level = "info",
+        skip_all,
+        fields(uuid = ?eventid)
+    )]
+    pub async fn handle_logout(
+        &self,
+        uat: Option<String>,
+        eventid: Uuid,
+    ) -> Result<(), OperationError> {
+        let ct = duration_from_epoch_now();
+        let mut idms_prox_write = self.idms.proxy_write(ct).await;
+
+        // We specifically need a uat here to assess the auth type!
+        let (ident, uat) = idms_prox_write
+            .validate_and_parse_uat(uat.as_deref(), ct)
+            .and_then(|uat| {
+                idms_prox_write
+                    .process_uat_to_identity(&uat, ct)
+                    .map(|ident| (ident, uat))
+            })?;
+
+        if uat.uuid == UUID_ANONYMOUS {
+            info!("Ignoring request to logout anonymous session - these sessions are not recorded");
+            return Ok(());
+        }
+
+        let target = ident.get_uuid().ok_or_else(|| {
+            admin_error!("Invalid identity - no uuid present");
+            OperationError::InvalidState
+        })?;
+
+        let token_id = ident.get_session_id();
+
+        let dte = DestroySessionTokenEvent {
+            ident,
+            target,
+            token_id,
+        };
+
+        idms_prox_write
+            .account_destroy_session_token(&dte)
+            .and_then(|r| idms_prox_write.commit().map(|_| r))
+    }
+
+    #[instrument(
+        level = "info",
+        skip_all,
+        fields(uuid = ?eventid)
+    )]
+    pub async fn handle_idmcredentialupdate(
+        &self,
+        uat: Option<String>,
+        uuid_or_name: String,
+        eventid: Uuid,
+    ) -> Result<(CUSessionToken, CUStatus), OperationError> {
+        let ct = duration_from_epoch_now();
+        let mut idms_prox_write = self.idms.proxy_write(ct).await;
+        let ident = idms_prox_write
+            .validate_and_parse_token_to_ident(uat.as_deref(), ct)
+            .map_err(|e| {
+                admin_error!(err = ?e, "Invalid identity");
+                e
+            })?;
+
+        let target_uuid = idms_prox_write
+            .qs_write
+            .name_to_uuid(uuid_or_name.as_str())
+            .map_err(|e| {
+                admin_error!(err = ?e, "Error resolving id to target");
+                e
+            })?;
+
+        idms_prox_write
+            .init_credential_update(&InitCredentialUpdateEvent::new(ident, target_uuid), ct)
+            .and_then(|tok| idms_prox_write.commit().map(|_| tok))
+            .map_err(|e| {
+                admin_error!(
+                    err = ?e,
+                    "Failed to begin init_credential_update",
+                );
+                e
+            })
+            .map(|(tok, sta)| {
+                (
+                    CUSessionToken {
+                        token: tok.token_enc,
+                    },
+                    sta.into(),
+                )
+            })
+    }
+
+    #[instrument(
+        level = "info",
+        skip_all,
+        fields(uuid = ?eventid),
+    )]
+    pub async fn handle_idmcredentialupdateintent(
+        &self,
+        uat: Option<String>,
+        uuid_or_name: String,
+        ttl: Option<Duration>,
+        eventid: Uuid,
+    ) -> Result<CUIntentToken, OperationError> {
+        let ct = duration_from_epoch_now();
+        let mut idms_prox_write = self.idms.proxy_write(ct).await;
+        let ident = idms_prox_write
+            .validate_and_parse_token_to_ident(uat.as_deref(), ct)
+            .map_err(|e| {
+                admin_error!(err = ?e, "Invalid identity");
+                e
+            })?;
+
+        let target_uuid = idms_prox_write
+            .qs_write
+            .name_to_uuid(uuid_or_name.as_str())
+            .map_err(|e| {
+                admin_error!(err = ?e, "Error resolving id to target");
+                e
+            })?;
+
+        idms_prox_write
+            .init_credential_update_intent(
+                &InitCredentialUpdateIntentEvent::new(ident, target_uuid, ttl),
+                ct,
+            )
+            .and_then(|tok| idms_prox_write.commit().map(|_| tok))
+            .map_err(|e| {
+                admin_error!(
+                    err = ?e,
+                    "Failed to begin init_credential_update_intent",
+                );
+                e
+            })
+            .map(|tok| CUIntentToken {
+                token: tok.intent_id,
+            })
+    }
+
+    #[instrument(
+        level = "info",
+        skip_all,
+        fields(uuid = ?eventid),
+    )]
+    pub async fn handle_identify_user(
+        &self,
+        uat: Option<String>,
+        uuid_or_name: String,
+        request: IdentifyUserRequest,
+        eventid: Uuid,
+    ) -> Result<IdentifyUserResponse, OperationError> {
+        let ct = duration_from_epoch_now();
+        let mut idms_prox_write = self.idms.proxy_write(ct).await;
+        let ident = idms_prox_write
+            .validate_and_parse_token_to_ident(uat.as_deref(), ct)
+            .map_err(|e| {
+                admin_error!(err = ?e, "Invalid identity");
+                e
+            })?;
+
+        let target = idms_prox_write
+            .qs_write
+            .name_to_uuid(uuid_or_name.as_str())
+            .map_err(|e| {
+                admin_error!(err = ?e, "Error resolving id to target");
+                e
+            })?;
+
+        let ev = IdentifyUserEvent {
+            ident,
+            target,
+            request,
+        };
+        idms_prox_write
+            .identify_user(&ev, ct)
+            .and_then(|res| idms_prox_write.commit().map(|_| res))
+    }
+
+    #[instrument(
+        level = "info",
+        skip_all,
+        fields(uuid = ?eventid),
+    )]
+    pub async fn handle_helpdesk_credential_reset(
+        &self,
+        uat: Option<String>,
+        uuid_or_name: String,
+        request: HelpdeskCredentialResetRequest,
+        eventid: Uuid,
+    ) -> Result<CUIntentToken, OperationError> {
+        let ct = duration_from_epoch_now();
+        let mut idms_prox_write = self.idms.proxy_write(ct).await;
+        let ident = idms_prox_write
+            .validate_and_parse_token_to_ident(uat.as_deref(), ct)
+            .map_err(|e| {
+                admin_error!(err = ?e, "Invalid identity");
+                e
+            })?;
+
+        let target = idms_prox_write
+            .qs_write
+            .name_to_uuid(uuid_or_name.as_str())
+            .map_err(|e| {
+                admin_error!(err = ?e, "Error resolving id to target");
+                e
+            })?;
+
+        let ev = HelpdeskCredentialResetEvent {
+            ident,
+            target,
+            other_totp: request.other_totp,
+            max_ttl: request.ttl.map(Duration::from_secs),
+        };
+        idms_prox_write
+            .helpdesk_credential_reset(&ev, ct)
+            .and_then(|tok| idms_prox_write.commit().map(|_| tok))
+            .map_err(|e| {
+                admin_error!(err = ?e, "Failed to issue helpdesk credential reset");
+                e
+            })
+            .map(|tok| CUIntentToken {
+                token: tok.intent_id,
+            })
+    }
+
+    #[instrument(
+        level = "info",
+        skip_all,
+        fields(uuid = ?eventid)
+    )]
+    pub async fn handle_credential_reset_email(
+        &self,
+        uat: Option<String>,
+        uuid_or_name: String,
+        request: CredentialResetEmailRequest,
+        eventid: Uuid,
+    ) -> Result<(), OperationError> {
+        let ct = duration_from_epoch_now();
+        let mut idms_prox_write = self.idms.proxy_write(ct).await;
+        let ident = idms_prox_write
+            .validate_and_parse_token_to_ident(uat.as_deref(), ct)
+            .map_err(|e| {
+                admin_error!(err = ?e, "Invalid identity");
+                e
+            })?;
+
+        let target = idms_prox_write
+            .qs_write
+            .name_to_uuid(uuid_or_name.as_str())
+            .map_err(|e| {
+                admin_error!(err = ?e, "Error resolving id to target");
+                e
+            })?;
+
+        let ev = CredentialResetEmailEvent {
+            ident,
+            target,
+            max_ttl: request.ttl.map(Duration::from_secs),
+        };
+        idms_prox_write
+            .credential_reset_email(&ev, ct)
+            .and_then(|_| idms_prox_write.commit())
+            .map_err(|e| {
+                admin_error!(err = ?e, "Failed to mail credential reset");
+                e
+            })
+    }
+
+    /// The messages that the mail worker should attempt to deliver now.
+    pub(crate) async fn handle_mail_queue_due(&self) -> Vec<OutboundMessage> {
+        let ct = duration_from_epoch_now();
+        let mut idms_prox_write = self.idms.proxy_write(ct).await;
+        idms_prox_write.mail_queue_due(ct).unwrap_or_else(|e| {
+            admin_error!(?e, "Unable to read the mail queue");
+            Vec::new()
+        })
+    }
+
+    /// Remove a message that the mail worker has delivered.
+    pub(crate) async fn handle_mail_delivered(&self, message_uuid: Uuid) {
+        let ct = duration_from_epoch_now();
+        let mut idms_prox_write = self.idms.proxy_write(ct).await;
+        if let Err(res) = idms_prox_write
+            .mail_delivered(message_uuid)
+            .and_then(|_| idms_prox_write.commit())
+        {
+            admin_error!(?res, message = ?message_uuid, "Unable to remove delivered message");
+        }
+    }
+
+    /// Schedule another attempt for a message that the mail worker could not deliver.
+    pub(crate) async fn handle_mail_delivery_failed(&self, message: &OutboundMessage, error: &str) {
+        let ct = duration_from_epoch_now();
+        let mut idms_prox_write = self.idms.proxy_write(ct).await;
+        if let Err(res) = idms_prox_write
+            .mail_delivery_failed(message, error, ct)
+            .and_then(|_| idms_prox_write.commit())
+        {
+            admin_error!(?res, message = ?message.uuid, "Unable to record failed message delivery");
+        }
+    }
+
+    #[instrument(
+        level = "info",
+        skip_all,
+        fields(uuid = ?eventid)
+    )]
+    pub async fn handle_idmcredentialexchangeintent(
+        &self,
+        intent_token: CUIntentToken,
+        eventid: Uuid,
+    ) -> Result<(CUSessionToken, CUStatus), OperationError> {
+        let ct = duration_from_epoch_now();
+        let mut idms_prox_write = self.idms.proxy_write(ct).await;
+        let intent_token = CredentialUpdateIntentToken {
+            intent_id: intent_token.token,
+        };
+        // TODO: this is throwing a 500 error when a session is already in use, that seems bad?
+        idms_prox_write
+            .exchange_intent_credential_update(intent_token, ct)
+            .and_then(|tok| idms_prox_write.commit().map(|_| tok))
+            .map_err(|e| {
+                admin_error!(
+                    err = ?e,
+                    "Failed to begin exchange_intent_credential_update",
+                );
+                e
+            })
+            .map(|(tok, sta)| {
+                (
+                    CUSessionToken {
+                        token: tok.token_enc,
+                    },
+                    sta.into(),
+                )
+            })
+    }
+
+    #[instrument(
+        level = "info",
+        skip_all,
+        fields(uuid = ?eventid)
+    )]
+    pub async fn handle_idmcredentialupdatecommit(
         &self,
         session_token: CUSessionToken,
         eventid: Uuid,
@@ -1136,12 +1655,121 @@ impl QueryServerWriteV1 {
     pub async fn handle_idmaccountunixsetcred(
         &self,
         uat: Option<String>,
-        uuid_or_name: String,
-        cred: String,
+        uuid_or_name: String,
+        cred: String,
+        eventid: Uuid,
+    ) -> Result<(), OperationError> {
+        let ct = duration_from_epoch_now();
+        let mut idms_prox_write = self.idms.proxy_write(ct).await;
+        let ident = idms_prox_write
+            .validate_and_parse_token_to_ident(uat.as_deref(), ct)
+            .map_err(|e| {
+                admin_error!(err = ?e, "Invalid identity");
+                e
+            })?;
+
+        let target_uuid = Uuid::parse_str(uuid_or_name.as_str()).or_else(|_| {
+            idms_prox_write
+                .qs_write
+                .name_to_uuid(uuid_or_name.as_str())
+                .map_err(|e| {
+                    admin_info!("Error resolving as gidnumber continuing ...");
+                    e
+                })
+        })?;
+
+        let upce = UnixPasswordChangeEvent::from_parts(
+            // &idms_prox_write.qs_write,
+            ident,
+            target_uuid,
+            cred,
+        )
+        .map_err(|e| {
+            admin_error!(err = ?e, "Failed to begin UnixPasswordChangeEvent");
+            e
+        })?;
+        idms_prox_write
+            .set_unix_account_password(&upce)
+            .and_then(|_| idms_prox_write.commit())
+            .map(|_| ())
+    }
+
+    #[instrument(
+        level = "info",
+        skip_all,
+        fields(uuid = ?eventid)
+    )]
+    pub async fn handle_oauth2_scopemap_update(
+        &self,
+        uat: Option<String>,
+        group: String,
+        scopes: Vec<String>,
+        filter: Filter<FilterInvalid>,
+        eventid: Uuid,
+    ) -> Result<(), OperationError> {
+        // Because this is from internal, we can generate a real modlist, rather
+        // than relying on the proto ones.
+        let mut idms_prox_write = self.idms.proxy_write(duration_from_epoch_now()).await;
+        let ct = duration_from_epoch_now();
+
+        let ident = idms_prox_write
+            .validate_and_parse_token_to_ident(uat.as_deref(), ct)
+            .map_err(|e| {
+                admin_error!(err = ?e, "Invalid identity");
+                e
+            })?;
+
+        let group_uuid = idms_prox_write
+            .qs_write
+            .name_to_uuid(group.as_str())
+            .map_err(|e| {
+                admin_error!(err = ?e, "Error resolving group name to target");
+                e
+            })?;
+
+        let ml = ModifyList::new_append(
+            "oauth2_rs_scope_map",
+            Value::new_oauthscopemap(group_uuid, scopes.into_iter().collect()).ok_or_else(
+                || OperationError::InvalidAttribute("Invalid Oauth Scope Map syntax".to_string()),
+            )?,
+        );
+
+        let mdf = match ModifyEvent::from_internal_parts(
+            ident,
+            &ml,
+            &filter,
+            &idms_prox_write.qs_write,
+        ) {
+            Ok(m) => m,
+            Err(e) => {
+                admin_error!(err = ?e, "Failed to begin modify");
+                return Err(e);
+            }
+        };
+
+        trace!(?mdf, "Begin modify event");
+
+        idms_prox_write
+            .qs_write
+            .modify(&mdf)
+            .and_then(|_| idms_prox_write.commit().map(|_| ()))
+    }
+
+    #[instrument(
+        level = "info",
+        skip_all,
+        fields(uuid = ?eventid)
+    )]
+    pub async fn handle_oauth2_scopemap_delete(
+        &self,
+        uat: Option<String>,
+        group: String,
+        filter: Filter<FilterInvalid>,
         eventid: Uuid,
     ) -> Result<(), OperationError> {
+        let mut idms_prox_write = self.idms.proxy_write(duration_from_epoch_now()).await;
         let ct = duration_from_epoch_now();
-        let mut idms_prox_write = self.idms.proxy_write(ct).await;
+
         let ident = idms_prox_write
             .validate_and_parse_token_to_ident(uat.as_deref(), ct)
             .map_err(|e| {
@@ -1149,30 +1777,35 @@ impl QueryServerWriteV1 {
                 e
             })?;
 
-        let target_uuid = Uuid::parse_str(uuid_or_name.as_str()).or_else(|_| {
-            idms_prox_write
-                .qs_write
-                .name_to_uuid(uuid_or_name.as_str())
-                .map_err(|e| {
-                    admin_info!("Error resolving as gidnumber continuing ...");
-                    e
-                })
-        })?;
+        let group_uuid = idms_prox_write
+            .qs_write
+            .name_to_uuid(group.as_str())
+            .map_err(|e| {
+                admin_error!(err = ?e, "Error resolving group name to target");
+                e
+            })?;
 
-        let upce = UnixPasswordChangeEvent::from_parts(
-            // &idms_prox_write.qs_write,
+        let ml = ModifyList::new_remove("oauth2_rs_scope_map", PartialValue::Refer(group_uuid));
+
+        let mdf = match ModifyEvent::from_internal_parts(
             ident,
-            target_uuid,
-            cred,
-        )
-        .map_err(|e| {
-            admin_error!(err = ?e, "Failed to begin UnixPasswordChangeEvent");
-            e
-        })?;
+            &ml,
+            &filter,
+            &idms_prox_write.qs_write,
+        ) {
+            Ok(m) => m,
+            Err(e) => {
+                admin_error!(err = ?e, "Failed to begin modify");
+                return Err(e);
+            }
+        };
+
+        trace!(?mdf, "Begin modify event");
+
         idms_prox_write
-            .set_unix_account_password(&upce)
-            .and_then(|_| idms_prox_write.commit())
-            .map(|_| ())
+            .qs_write
+            .modify(&mdf)
+            .and_then(|_| idms_prox_write.commit().map(|_| ()))
     }
 
     #[instrument(
@@ -1180,7 +1813,7 @@ impl QueryServerWriteV1 {
         skip_all,
         fields(uuid = ?eventid)
     )]
-    pub async fn handle_oauth2_scopemap_update(
+    pub async fn handle_oauth2_sup_scopemap_update(
         &self,
         uat: Option<String>,
         group: String,
@@ -1209,7 +1842,7 @@ impl QueryServerWriteV1 {
             })?;
 
         let ml = ModifyList::new_append(
-            "oauth2_rs_scope_map",
+            "oauth2_rs_sup_scope_map",
             Value::new_oauthscopemap(group_uuid, scopes.into_iter().collect()).ok_or_else(
                 || OperationError::InvalidAttribute("Invalid Oauth Scope Map syntax".to_string()),
             )?,
@@ -1241,15 +1874,15 @@ impl QueryServerWriteV1 {
         skip_all,
         fields(uuid = ?eventid)
     )]
-    pub async fn handle_oauth2_scopemap_delete(
+    pub async fn handle_oauth2_sup_scopemap_delete(
         &self,
         uat: Option<String>,
         group: String,
         filter: Filter<FilterInvalid>,
         eventid: Uuid,
     ) -> Result<(), OperationError> {
-        let mut idms_prox_write = self.idms.proxy_write(duration_from_epoch_now()).await;
         let ct = duration_from_epoch_now();
+        let mut idms_prox_write = self.idms.proxy_write(ct).await;
 
         let ident = idms_prox_write
             .validate_and_parse_token_to_ident(uat.as_deref(), ct)
@@ -1266,7 +1899,7 @@ impl QueryServerWriteV1 {
                 e
             })?;
 
-        let ml = ModifyList::new_remove("oauth2_rs_scope_map", PartialValue::Refer(group_uuid));
+        let ml = ModifyList::new_remove("oauth2_rs_sup_scope_map", PartialValue::Refer(group_uuid));
 
         let mdf = match ModifyEvent::from_internal_parts(
             ident,
@@ -1294,7 +1927,7 @@ impl QueryServerWriteV1 {
         skip_all,
         fields(uuid = ?eventid)
     )]
-    pub async fn handle_oauth2_sup_scopemap_update(
+    pub async fn handle_oauth2_registrar_scopemap_update(
         &self,
         uat: Option<String>,
         group: String,
@@ -1323,7 +1956,7 @@ impl QueryServerWriteV1 {
             })?;
 
         let ml = ModifyList::new_append(
-            "oauth2_rs_sup_scope_map",
+            "oauth2_registrar_scope_map",
             Value::new_oauthscopemap(group_uuid, scopes.into_iter().collect()).ok_or_else(
                 || OperationError::InvalidAttribute("Invalid Oauth Scope Map syntax".to_string()),
             )?,
@@ -1355,15 +1988,15 @@ impl QueryServerWriteV1 {
         skip_all,
         fields(uuid = ?eventid)
     )]
-    pub async fn handle_oauth2_sup_scopemap_delete(
+    pub async fn handle_oauth2_registrar_scopemap_delete(
         &self,
         uat: Option<String>,
         group: String,
         filter: Filter<FilterInvalid>,
         eventid: Uuid,
     ) -> Result<(), OperationError> {
+        let mut idms_prox_write = self.idms.proxy_write(duration_from_epoch_now()).await;
         let ct = duration_from_epoch_now();
-        let mut idms_prox_write = self.idms.proxy_write(ct).await;
 
         let ident = idms_prox_write
             .validate_and_parse_token_to_ident(uat.as_deref(), ct)
@@ -1380,7 +2013,107 @@ impl QueryServerWriteV1 {
                 e
             })?;
 
-        let ml = ModifyList::new_remove("oauth2_rs_sup_scope_map", PartialValue::Refer(group_uuid));
+        let ml = ModifyList::new_remove(
+            "oauth2_registrar_scope_map",
+            PartialValue::Refer(group_uuid),
+        );
+
+        let mdf = match ModifyEvent::from_internal_parts(
+            ident,
+            &ml,
+            &filter,
+            &idms_prox_write.qs_write,
+        ) {
+            Ok(m) => m,
+            Err(e) => {
+                admin_error!(err = ?e, "Failed to begin modify");
+                return Err(e);
+            }
+        };
+
+        trace!(?mdf, "Begin modify event");
+
+        idms_prox_write
+            .qs_write
+            .modify(&mdf)
+            .and_then(|_| idms_prox_write.commit().map(|_| ()))
+    }
+
+    #[instrument(
+        level = "info",
+        skip_all,
+        fields(uuid = ?eventid)
+    )]
+    pub async fn handle_oauth2_token_exchange_from_update(
+        &self,
+        uat: Option<String>,
+        source: String,
+        filter: Filter<FilterInvalid>,
+        eventid: Uuid,
+    ) -> Result<(), OperationError> {
+        let mut idms_prox_write = self.idms.proxy_write(duration_from_epoch_now()).await;
+        let ct = duration_from_epoch_now();
+
+        let ident = idms_prox_write
+            .validate_and_parse_token_to_ident(uat.as_deref(), ct)
+            .map_err(|e| {
+                admin_error!(err = ?e, "Invalid identity");
+                e
+            })?;
+
+        let source_uuid = oauth2_rs_name_to_uuid(&mut idms_prox_write, &source)?;
+
+        let ml = ModifyList::new_append("oauth2_rs_token_exchange_from", Value::Refer(source_uuid));
+
+        let mdf = match ModifyEvent::from_internal_parts(
+            ident,
+            &ml,
+            &filter,
+            &idms_prox_write.qs_write,
+        ) {
+            Ok(m) => m,
+            Err(e) => {
+                admin_error!(err = ?e, "Failed to begin modify");
+                return Err(e);
+            }
+        };
+
+        trace!(?mdf, "Begin modify event");
+
+        idms_prox_write
+            .qs_write
+            .modify(&mdf)
+            .and_then(|_| idms_prox_write.commit().map(|_| ()))
+    }
+
+    #[instrument(
+        level = "info",
+        skip_all,
+        fields(uuid = ?eventid)
+    )]
+    pub async fn handle_oauth2_token_exchange_from_delete(
+        &self,
+        uat: Option<String>,
+        source: String,
+        filter: Filter<FilterInvalid>,
+        eventid: Uuid,
+    ) -> Result<(), OperationError> {
+        let mut idms_prox_write = self.idms.proxy_write(duration_from_epoch_now()).await;
+        let ct = duration_from_epoch_now();
+
+        let ident = idms_prox_write
+            .validate_and_parse_token_to_ident(uat.as_deref(), ct)
+            .map_err(|e| {
+                admin_error!(err = ?e, "Invalid identity");
+                e
+            })?;
+
+        let source_uuid = oauth2_rs_name_to_uuid(&mut idms_prox_write, &source)?;
+
+        let ml = ModifyList::new_remove(
+            "oauth2_rs_token_exchange_from",
+            PartialValue::Refer(source_uuid),
+        );
 
         let mdf = match ModifyEvent::from_internal_parts(
             ident,
@@ -1440,15 +2173,14 @@ impl QueryServerWriteV1 {
     )]
     pub async fn handle_oauth2_token_exchange(
         &self,
-        client_authz: Option<String>,
+        client_auth_info: ClientAuthInfo,
         token_req: AccessTokenRequest,
         eventid: Uuid,
     ) -> Result<AccessTokenResponse, Oauth2Error> {
         let ct = duration_from_epoch_now();
         let mut idms_prox_write = self.idms.proxy_write(ct).await;
         // Now we can send to the idm server for authorisation checking.
-        let resp =
-            idms_prox_write.check_oauth2_token_exchange(client_authz.as_deref(), &token_req, ct);
+        let resp = idms_prox_write.check_oauth2_token_exchange(&client_auth_info, &token_req, ct);
 
         match &resp {
             Err(Oauth2Error::InvalidGrant) | Ok(_) => {
@@ -1467,14 +2199,130 @@ impl QueryServerWriteV1 {
     )]
     pub async fn handle_oauth2_token_revoke(
         &self,
-        client_authz: String,
+        client_auth_info: ClientAuthInfo,
         intr_req: TokenRevokeRequest,
         eventid: Uuid,
     ) -> Result<(), Oauth2Error> {
         let ct = duration_from_epoch_now();
         let mut idms_prox_write = self.idms.proxy_write(ct).await;
         idms_prox_write
-            .oauth2_token_revoke(&client_authz, &intr_req, ct)
+            .oauth2_token_revoke(&client_auth_info, &intr_req, ct)
+            .and_then(|()| idms_prox_write.commit().map_err(Oauth2Error::ServerError))
+    }
+
+    #[instrument(
+        level = "info",
+        skip_all,
+        fields(uuid = ?eventid)
+    )]
+    pub async fn handle_oauth2_end_session(
+        &self,
+        uat: Option<String>,
+        end_session_req: EndSessionRequest,
+        eventid: Uuid,
+    ) -> Result<EndSessionResponse, Oauth2Error> {
+        let ct = duration_from_epoch_now();
+        let mut idms_prox_write = self.idms.proxy_write(ct).await;
+
+        // The user may have already logged out, in which case there is no session to end.
+        let ident = idms_prox_write
+            .validate_and_parse_uat(uat.as_deref(), ct)
+            .and_then(|uat| idms_prox_write.process_uat_to_identity(&uat, ct))
+            .ok();
+
+        idms_prox_write
+            .oauth2_end_session(ident.as_ref(), &end_session_req)
+            .and_then(|r| {
+                idms_prox_write
+                    .commit()
+                    .map(|_| r)
+                    .map_err(Oauth2Error::ServerError)
+            })
+    }
+
+    #[instrument(
+        level = "info",
+        skip_all,
+        fields(uuid = ?eventid)
+    )]
+    pub async fn handle_oauth2_client_register(
+        &self,
+        uat: Option<String>,
+        reg_req: ClientRegistrationRequest,
+        eventid: Uuid,
+    ) -> Result<ClientRegistrationResponse, Oauth2Error> {
+        let ct = duration_from_epoch_now();
+        let mut idms_prox_write = self.idms.proxy_write(ct).await;
+        let ident = idms_prox_write
+            .validate_and_parse_token_to_ident(uat.as_deref(), ct)
+            .map_err(|e| {
+                admin_error!(err = ?e, "Invalid identity");
+                Oauth2Error::AuthenticationRequired
+            })?;
+
+        idms_prox_write
+            .oauth2_client_register(&ident, &reg_req, ct)
+            .and_then(|r| {
+                idms_prox_write
+                    .commit()
+                    .map(|_| r)
+                    .map_err(Oauth2Error::ServerError)
+            })
+    }
+
+    #[instrument(
+        level = "info",
+        skip_all,
+        fields(uuid = ?eventid)
+    )]
+    pub async fn handle_oauth2_client_registration_update(
+        &self,
+        uat: Option<String>,
+        client_id: String,
+        reg_req: ClientRegistrationRequest,
+        eventid: Uuid,
+    ) -> Result<ClientRegistrationResponse, Oauth2Error> {
+        let ct = duration_from_epoch_now();
+        let mut idms_prox_write = self.idms.proxy_write(ct).await;
+        let ident = idms_prox_write
+            .validate_and_parse_token_to_ident(uat.as_deref(), ct)
+            .map_err(|e| {
+                admin_error!(err = ?e, "Invalid identity");
+                Oauth2Error::AuthenticationRequired
+            })?;
+
+        idms_prox_write
+            .oauth2_client_registration_update(&ident, &client_id, &reg_req)
+            .and_then(|r| {
+                idms_prox_write
+                    .commit()
+                    .map(|_| r)
+                    .map_err(Oauth2Error::ServerError)
+            })
+    }
+
+    #[instrument(
+        level = "info",
+        skip_all,
+        fields(uuid = ?eventid)
+    )]
+    pub async fn handle_oauth2_client_registration_delete(
+        &self,
+        uat: Option<String>,
+        client_id: String,
+        eventid: Uuid,
+    ) -> Result<(), Oauth2Error> {
+        let ct = duration_from_epoch_now();
+        let mut idms_prox_write = self.idms.proxy_write(ct).await;
+        let ident = idms_prox_write
+            .validate_and_parse_token_to_ident(uat.as_deref(), ct)
+            .map_err(|e| {
+                admin_error!(err = ?e, "Invalid identity");
+                Oauth2Error::AuthenticationRequired
+            })?;
+
+        idms_prox_write
+            .oauth2_client_registration_delete(&ident, &client_id)
             .and_then(|()| idms_prox_write.commit().map_err(Oauth2Error::ServerError))
     }
 
@@ -1514,6 +2362,25 @@ impl QueryServerWriteV1 {
         res.expect("Invalid Server State");
     }
 
+    #[instrument(
+        level = "info",
+        skip_all,
+        fields(uuid = ?msg.eventid)
+    )]
+    pub async fn handle_purgemembervalidityevent(&self, msg: PurgeMemberValidityEvent) {
+        trace!(?msg, "Begin purge member validity event");
+        let mut idms_prox_write = self.idms.proxy_write(duration_from_epoch_now()).await;
+        let res = idms_prox_write
+            .qs_write
+            .purge_member_validity()
+            .and_then(|_| idms_prox_write.commit());
+        // Unlike the purges of tombstones and recycled entries, this is a regular modification
+        // of groups, so a failure here is not a fault in the server's state.
+        if let Err(err) = res {
+            admin_error!(?err, "Purge member validity failed");
+        }
+    }
+
     pub(crate) async fn handle_delayedaction(&self, da: DelayedAction) {
         let eventid = Uuid::new_v4();
         let nspan = span!(Level::INFO, "process_delayed_action", uuid = ?eventid);
@@ -1547,3 +2414,28 @@ impl QueryServerWriteV1 {
         idms_prox_write.commit().map(|()| pw)
     }
 }
+
+/// Resolve an oauth2 resource server by its name, as these are not part of the name index.
+fn oauth2_rs_name_to_uuid(
+    idms_prox_write: &mut IdmServerProxyWriteTransaction,
+    rs_name: &str,
+) -> Result<Uuid, OperationError> {
+    let filter = filter!(f_and!([
+        f_eq("class", PartialValue::new_class("oauth2_resource_server")),
+        f_eq("oauth2_rs_name", PartialValue::new_iname(rs_name))
+    ]));
+
+    idms_prox_write
+        .qs_write
+        .internal_search(filter)
+        .and_then(|entries| {
+            entries
+                .first()
+                .map(|entry| entry.get_uuid())
+                .ok_or(OperationError::NoMatchingEntries)
+        })
+        .map_err(|e| {
+            admin_error!(err = ?e, "Error resolving oauth2 resource server name to target");
+            e
+        })
+}
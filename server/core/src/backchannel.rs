@@ -0,0 +1,105 @@
+//! Delivers oauth2 back-channel logout tokens to resource servers once the transaction
+//! that ended their sessions has committed. A resource server that can't be reached is
+//! retried a few times, after which the logout is dropped - its sessions have already
+//! been revoked, so it will find out the next time it uses a token.
+
+use std::time::Duration;
+
+use kanidmd_lib::idm::oauth2::Oauth2BackchannelLogout;
+use kanidmd_lib::prelude::IdmServerBackchannel;
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+use tokio::time::sleep;
+
+use crate::CoreAction;
+
+/// How many times delivery of a logout token is attempted.
+const BACKCHANNEL_ATTEMPTS: u32 = 3;
+/// How long to wait before the first retry. This doubles after each failed attempt.
+const BACKCHANNEL_RETRY_DELAY: Duration = Duration::from_secs(1);
+const BACKCHANNEL_TIMEOUT: Duration = Duration::from_secs(10);
+
+pub(crate) struct BackchannelLogoutActor;
+
+impl BackchannelLogoutActor {
+    pub fn start(
+        mut idms_backchannel: IdmServerBackchannel,
+        mut rx: broadcast::Receiver<CoreAction>,
+    ) -> Result<JoinHandle<()>, ()> {
+        let client = reqwest::Client::builder()
+            .timeout(BACKCHANNEL_TIMEOUT)
+            .build()
+            .map_err(|e| {
+                error!(?e, "Unable to build back-channel logout http client");
+            })?;
+
+        let handle = tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    Ok(action) = rx.recv() => {
+                        match action {
+                            CoreAction::Shutdown => break,
+                        }
+                    }
+                    logout = idms_backchannel.backchannel_rx().recv() => {
+                        match logout {
+                            Some(logout) => {
+                                tokio::spawn(deliver(client.clone(), logout));
+                            }
+                            None => break,
+                        }
+                    }
+                }
+            }
+            info!("Stopped BackchannelLogoutActor");
+        });
+
+        Ok(handle)
+    }
+}
+
+// https://openid.net/specs/openid-connect-backchannel-1_0.html#BCRequest
+async fn post(client: &reqwest::Client, logout: &Oauth2BackchannelLogout) -> Result<(), String> {
+    let response = client
+        .post(logout.url.clone())
+        .form(&[("logout_token", logout.logout_token.as_str())])
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if response.status().is_success() {
+        Ok(())
+    } else {
+        Err(format!("unexpected response status {}", response.status()))
+    }
+}
+
+#[instrument(level = "debug", skip_all, fields(rs = %logout.rs_name))]
+async fn deliver(client: reqwest::Client, logout: Oauth2BackchannelLogout) {
+    let mut delay = BACKCHANNEL_RETRY_DELAY;
+    let mut attempt = 1;
+    loop {
+        match post(&client, &logout).await {
+            Ok(()) => {
+                debug!(attempt, "Delivered back-channel logout");
+                return;
+            }
+            Err(e) if attempt >= BACKCHANNEL_ATTEMPTS => {
+                error!(
+                    ?e,
+                    attempt, "Unable to deliver back-channel logout, giving up"
+                );
+                return;
+            }
+            Err(e) => {
+                warn!(
+                    ?e,
+                    attempt, "Unable to deliver back-channel logout, will retry"
+                );
+                sleep(delay).await;
+                delay *= 2;
+                attempt += 1;
+            }
+        }
+    }
+}
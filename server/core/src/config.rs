@@ -28,6 +28,14 @@ pub struct OnlineBackup {
     pub schedule: String,
     #[serde(default = "default_online_backup_versions")]
     pub versions: usize,
+    /// The number of incremental backups to take between each full backup.
+    #[serde(default)]
+    pub incrementals: usize,
+    /// Compress the backup content with gzip.
+    #[serde(default)]
+    pub compress: bool,
+    /// Encrypt the backup content with a key derived from the content of this file.
+    pub key_file: Option<String>,
 }
 
 fn default_online_backup_schedule() -> String {
@@ -42,6 +50,38 @@ fn default_online_backup_versions() -> usize {
 pub struct TlsConfiguration {
     pub chain: String,
     pub key: String,
+    // Request certificates from clients, so that oauth2 clients can authenticate with them.
+    #[serde(default)]
+    pub client_auth: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, Eq, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum MailTls {
+    /// Connect to the relay with TLS, usually on port 465.
+    Tls,
+    /// Upgrade the connection to the relay with STARTTLS, usually on port 587.
+    #[default]
+    StartTls,
+    /// Send mail without encryption. Only use this with a relay on the same host.
+    None,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MailConfig {
+    /// The SMTP relay that mail is submitted to.
+    pub smtp_host: String,
+    /// Defaults to the usual port for the TLS mode.
+    pub smtp_port: Option<u16>,
+    #[serde(default)]
+    pub smtp_tls: MailTls,
+    pub smtp_username: Option<String>,
+    /// A file containing the password used to authenticate to the relay.
+    pub smtp_password_file: Option<String>,
+    /// The address that mail is sent from, such as `Kanidm <idm@example.com>`.
+    pub from: String,
+    /// A directory of templates that replace the built in messages.
+    pub template_path: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -56,7 +96,10 @@ pub struct ServerConfig {
     pub db_arc_size: Option<usize>,
     pub tls_chain: Option<String>,
     pub tls_key: Option<String>,
+    pub tls_client_auth: Option<bool>,
     pub online_backup: Option<OnlineBackup>,
+    pub breached_password_list: Option<String>,
+    pub mail: Option<MailConfig>,
     pub domain: String,
     pub origin: String,
     #[serde(default)]
@@ -174,6 +217,8 @@ pub struct Configuration {
     pub tls_config: Option<TlsConfiguration>,
     pub integration_test_config: Option<Box<IntegrationTestConfig>>,
     pub online_backup: Option<OnlineBackup>,
+    pub breached_password_list: Option<String>,
+    pub mail: Option<MailConfig>,
     pub domain: String,
     pub origin: String,
     pub role: ServerRole,
@@ -204,6 +249,14 @@ impl fmt::Display for Configuration {
                 Some(_) => write!(f, "online_backup: enabled, "),
                 None => write!(f, "online_backup: disabled, "),
             })
+            .and_then(|_| match &self.breached_password_list {
+                Some(p) => write!(f, "breached password list: {}, ", p),
+                None => write!(f, "breached password list: disabled, "),
+            })
+            .and_then(|_| match &self.mail {
+                Some(mail) => write!(f, "mail relay: {}, ", mail.smtp_host),
+                None => write!(f, "mail relay: disabled, "),
+            })
             .and_then(|_| write!(f, "role: {}, ", self.role.to_string()))
             .and_then(|_| {
                 write!(
@@ -237,6 +290,8 @@ impl Configuration {
             tls_config: None,
             integration_test_config: None,
             online_backup: None,
+            breached_password_list: None,
+            mail: None,
             domain: "idm.example.com".to_string(),
             origin: "https://idm.example.com".to_string(),
             role: ServerRole::WriteReplica,
@@ -259,15 +314,29 @@ impl Configuration {
                 let path = cfg.path.to_string();
                 let schedule = cfg.schedule.to_string();
                 let versions = cfg.versions;
+                let incrementals = cfg.incrementals;
+                let compress = cfg.compress;
+                let key_file = cfg.key_file.clone();
                 self.online_backup = Some(OnlineBackup {
                     path,
                     schedule,
                     versions,
+                    incrementals,
+                    compress,
+                    key_file,
                 })
             }
         }
     }
 
+    pub fn update_breached_password_list(&mut self, p: &Option<String>) {
+        self.breached_password_list = p.clone();
+    }
+
+    pub fn update_mail(&mut self, mail: &Option<MailConfig>) {
+        self.mail = mail.clone();
+    }
+
     pub fn update_log_level(&mut self, level: &Option<LogLevel>) {
         let level = level.clone();
         self.log_level = level.unwrap_or_default();
@@ -278,9 +347,12 @@ impl Configuration {
         #[cfg(debug_assertions)]
         debug!("update_config_for_server_mode {:?}", sconfig);
         self.update_tls(&sconfig.tls_chain, &sconfig.tls_key);
+        self.update_tls_client_auth(sconfig.tls_client_auth);
         self.update_bind(&sconfig.bindaddress);
         self.update_ldapbind(&sconfig.ldapbindaddress);
         self.update_online_backup(&sconfig.online_backup);
+        self.update_breached_password_list(&sconfig.breached_password_list);
+        self.update_mail(&sconfig.mail);
         self.update_log_level(&sconfig.log_level);
     }
 
@@ -340,7 +412,11 @@ impl Configuration {
             (Some(chainp), Some(keyp)) => {
                 let chain = chainp.to_string();
                 let key = keyp.to_string();
-                self.tls_config = Some(TlsConfiguration { chain, key })
+                self.tls_config = Some(TlsConfiguration {
+                    chain,
+                    key,
+                    client_auth: false,
+                })
             }
             _ => {
                 eprintln!("ERROR: Invalid TLS configuration - must provide chain and key!");
@@ -348,4 +424,10 @@ impl Configuration {
             }
         }
     }
+
+    pub fn update_tls_client_auth(&mut self, t: Option<bool>) {
+        if let Some(tls_config) = self.tls_config.as_mut() {
+            tls_config.client_auth = t.unwrap_or(false);
+        }
+    }
 }
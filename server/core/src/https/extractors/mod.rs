@@ -14,6 +14,11 @@ const X_FORWARDED_FOR: HeaderName = HeaderName::from_static("x-forwarded-for");
 
 pub struct TrustedClientIp(pub IpAddr);
 
+/// The thumbprint of the tls client certificate presented on the connection. This is added
+/// to each request of the connection when tls client auth is enabled.
+#[derive(Debug, Clone)]
+pub struct ClientCertThumbprint(pub String);
+
 #[async_trait]
 impl FromRequestParts<ServerState> for TrustedClientIp {
     type Rejection = (StatusCode, &'static str);
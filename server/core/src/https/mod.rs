@@ -29,8 +29,9 @@ use hyper::Body;
 use javascript::*;
 use kanidm_proto::constants::APPLICATION_JSON;
 use kanidm_proto::v1::OperationError;
+use kanidmd_lib::idm::oauth2::tls_client_cert_thumbprint;
 use kanidmd_lib::status::StatusActor;
-use openssl::ssl::{Ssl, SslAcceptor, SslFiletype, SslMethod};
+use openssl::ssl::{Ssl, SslAcceptor, SslFiletype, SslMethod, SslVerifyMode};
 use sketching::*;
 use tokio_openssl::SslStream;
 
@@ -44,6 +45,7 @@ use std::pin::Pin;
 use std::sync::Arc;
 use std::{net::SocketAddr, str::FromStr};
 use tokio::sync::broadcast;
+use tower::ServiceExt;
 use tower_http::services::ServeDir;
 use tower_http::trace::TraceLayer;
 use uuid::Uuid;
@@ -64,6 +66,8 @@ pub struct ServerState {
     pub js_files: Vec<JavaScriptFile>,
     pub(crate) trust_x_forward_for: bool,
     pub csp_header: HeaderValue,
+    /// The public origin of this server, used to build absolute resource locations.
+    pub(crate) origin: String,
 }
 
 impl ServerState {
@@ -190,6 +194,7 @@ pub async fn create_https_server(
         js_files,
         trust_x_forward_for,
         csp_header: csp_header.finish(),
+        origin: config.origin.clone(),
     };
 
     let static_routes = match config.role {
@@ -325,6 +330,11 @@ async fn server_loop(
             format!("Failed to create TLS listener: {:?}", err),
         )
     })?;
+    if tls_param.client_auth {
+        // Client certificates are optional, and are trusted by the thumbprint registered to
+        // an oauth2 client rather than by a certificate authority.
+        tls_builder.set_verify_callback(SslVerifyMode::PEER, |_, _| true);
+    }
     let acceptor = tls_builder.build();
     let listener = TcpListener::bind(addr).await?;
 
@@ -370,6 +380,18 @@ async fn handle_conn(
                 std::io::Error::from(ErrorKind::Other)
             })?;
 
+            let client_cert = tls_stream
+                .ssl()
+                .peer_certificate()
+                .and_then(|cert| tls_client_cert_thumbprint(&cert).ok())
+                .map(extractors::ClientCertThumbprint);
+            let svc = svc.map_request(move |mut req: http::Request<Body>| {
+                if let Some(client_cert) = &client_cert {
+                    req.extensions_mut().insert(client_cert.clone());
+                }
+                req
+            });
+
             protocol
                 .serve_connection(tls_stream, svc)
                 .await
@@ -1,3 +1,4 @@
+use super::extractors::ClientCertThumbprint;
 use super::middleware::KOpId;
 use super::v1::{json_rest_event_get, json_rest_event_post};
 use super::{to_axum_response, HttpOperationError, ServerState};
@@ -14,11 +15,15 @@ use http::header::{
 use http::{HeaderMap, HeaderValue, StatusCode};
 use hyper::Body;
 use kanidm_proto::constants::APPLICATION_JSON;
-use kanidm_proto::oauth2::{AuthorisationResponse, OidcDiscoveryResponse};
+use kanidm_proto::oauth2::{
+    AuthorisationResponse, EndSessionRequest, EndSessionResponse, OidcDiscoveryResponse,
+};
 use kanidm_proto::v1::Entry as ProtoEntry;
 use kanidmd_lib::idm::oauth2::{
-    AccessTokenIntrospectRequest, AccessTokenRequest, AuthorisationRequest, AuthorisePermitSuccess,
-    AuthoriseResponse, ErrorResponse, Oauth2Error, TokenRevokeRequest,
+    AccessTokenIntrospectRequest, AccessTokenRequest, AuthorisationRequestRef,
+    AuthorisePermitSuccess, AuthoriseResponse, ClientAuthInfo, ClientRegistrationRequest,
+    ClientRegistrationResponse, ErrorResponse, Oauth2Error, PushedAuthorisationRequest,
+    PushedAuthorisationResponse, TokenRevokeRequest,
 };
 use kanidmd_lib::prelude::f_eq;
 use kanidmd_lib::prelude::*;
@@ -209,6 +214,66 @@ pub async fn oauth2_id_sup_scopemap_delete(
     to_axum_response(res)
 }
 
+fn oauth2_registrar_id(id: &str) -> Filter<FilterInvalid> {
+    filter_all!(f_and!([
+        f_eq("class", PartialValue::new_class("service_account")),
+        f_id(id)
+    ]))
+}
+
+pub async fn oauth2_registrar_id_scopemap_post(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    Path((id, group)): Path<(String, String)>,
+    Json(scopes): Json<Vec<String>>,
+) -> Response<Body> {
+    let filter = oauth2_registrar_id(&id);
+    let res = state
+        .qe_w_ref
+        .handle_oauth2_registrar_scopemap_update(kopid.uat, group, scopes, filter, kopid.eventid)
+        .await;
+    to_axum_response(res)
+}
+
+pub async fn oauth2_registrar_id_scopemap_delete(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    Path((id, group)): Path<(String, String)>,
+) -> Response<Body> {
+    let filter = oauth2_registrar_id(&id);
+    let res = state
+        .qe_w_ref
+        .handle_oauth2_registrar_scopemap_delete(kopid.uat, group, filter, kopid.eventid)
+        .await;
+    to_axum_response(res)
+}
+
+pub async fn oauth2_id_token_exchange_from_post(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    Path((rs_name, source)): Path<(String, String)>,
+) -> Response<Body> {
+    let filter = oauth2_id(&rs_name);
+    let res = state
+        .qe_w_ref
+        .handle_oauth2_token_exchange_from_update(kopid.uat, source, filter, kopid.eventid)
+        .await;
+    to_axum_response(res)
+}
+
+pub async fn oauth2_id_token_exchange_from_delete(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    Path((rs_name, source)): Path<(String, String)>,
+) -> Response<Body> {
+    let filter = oauth2_id(&rs_name);
+    let res = state
+        .qe_w_ref
+        .handle_oauth2_token_exchange_from_delete(kopid.uat, source, filter, kopid.eventid)
+        .await;
+    to_axum_response(res)
+}
+
 pub async fn oauth2_id_delete(
     State(state): State<ServerState>,
     Extension(kopid): Extension<KOpId>,
@@ -287,7 +352,7 @@ pub async fn oauth2_id_delete(
 pub async fn oauth2_authorise_post(
     State(state): State<ServerState>,
     Extension(kopid): Extension<KOpId>,
-    Json(auth_req): Json<AuthorisationRequest>,
+    Json(auth_req): Json<AuthorisationRequestRef>,
 ) -> impl IntoResponse {
     let mut res = oauth2_authorise(state, auth_req, kopid)
         .await
@@ -303,7 +368,7 @@ pub async fn oauth2_authorise_post(
 pub async fn oauth2_authorise_get(
     State(state): State<ServerState>,
     Extension(kopid): Extension<KOpId>,
-    Query(auth_req): Query<AuthorisationRequest>,
+    Query(auth_req): Query<AuthorisationRequestRef>,
 ) -> impl IntoResponse {
     // Start the oauth2 authorisation flow to present to the user.
     oauth2_authorise(state, auth_req, kopid).await
@@ -311,7 +376,7 @@ pub async fn oauth2_authorise_get(
 
 async fn oauth2_authorise(
     state: ServerState,
-    auth_req: AuthorisationRequest,
+    auth_req: AuthorisationRequestRef,
     kopid: KOpId,
 ) -> impl IntoResponse {
     let res: Result<AuthoriseResponse, Oauth2Error> = state
@@ -568,10 +633,11 @@ async fn oauth2_authorise_reject(
 }
 
 #[axum_macros::debug_handler]
-#[instrument(skip(state, kopid, headers), level = "DEBUG")]
+#[instrument(skip(state, kopid, headers, client_cert), level = "DEBUG")]
 pub async fn oauth2_token_post(
     State(state): State<ServerState>,
     Extension(kopid): Extension<KOpId>,
+    client_cert: Option<Extension<ClientCertThumbprint>>,
     headers: HeaderMap,
     Form(tok_req): Form<AccessTokenRequest>,
 ) -> Result<Json<kanidm_proto::oauth2::AccessTokenResponse>, HTTPOauth2Error> {
@@ -579,11 +645,14 @@ pub async fn oauth2_token_post(
     // the token to the caller.
 
     // Get the authz header (if present). Not all exchange types require this.
-    let client_authz = headers
-        .get("authorization")
-        .and_then(|hv| hv.to_str().ok())
-        .and_then(|h| h.split(' ').last())
-        .map(str::to_string);
+    let client_auth_info = ClientAuthInfo {
+        basic_authz: headers
+            .get("authorization")
+            .and_then(|hv| hv.to_str().ok())
+            .and_then(|h| h.rsplit(' ').next())
+            .map(str::to_string),
+        client_cert: client_cert.map(|Extension(ClientCertThumbprint(cert))| cert),
+    };
 
     // Do we change the method/path we take here based on the type of requested
     // grant? Should we cease the delayed/async session update here and just opt
@@ -591,7 +660,7 @@ pub async fn oauth2_token_post(
 
     match state
         .qe_w_ref
-        .handle_oauth2_token_exchange(client_authz, tok_req, kopid.eventid)
+        .handle_oauth2_token_exchange(client_auth_info, tok_req, kopid.eventid)
         .await
     {
         Ok(tok_res) => Ok(Json(tok_res)),
@@ -599,6 +668,123 @@ pub async fn oauth2_token_post(
     }
 }
 
+/// A pushed authorisation request, sent directly by the resource server before it redirects
+/// the user agent to the authorisation endpoint with the request_uri we return.
+#[instrument(skip(state, kopid, headers, client_cert), level = "DEBUG")]
+pub async fn oauth2_par_post(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    client_cert: Option<Extension<ClientCertThumbprint>>,
+    headers: HeaderMap,
+    Form(par_req): Form<PushedAuthorisationRequest>,
+) -> Result<(StatusCode, Json<PushedAuthorisationResponse>), HTTPOauth2Error> {
+    let client_auth_info = ClientAuthInfo {
+        basic_authz: headers
+            .get(AUTHORIZATION)
+            .and_then(|hv| hv.to_str().ok())
+            .and_then(|h| h.strip_prefix("Basic "))
+            .map(str::to_string),
+        client_cert: client_cert.map(|Extension(ClientCertThumbprint(cert))| cert),
+    };
+
+    match state
+        .qe_r_ref
+        .handle_oauth2_pushed_authorisation(client_auth_info, par_req, kopid.eventid)
+        .await
+    {
+        Ok(par_res) => Ok((StatusCode::CREATED, Json(par_res))),
+        Err(e) => Err(HTTPOauth2Error(e)),
+    }
+}
+
+/// Errors of the client registration endpoints. A client that doesn't exist, or that the
+/// registrar didn't register, is reported as an invalid token for it.
+/// <https://datatracker.ietf.org/doc/html/rfc7592#section-3>
+pub struct HTTPOauth2RegistrationError(Oauth2Error);
+
+impl IntoResponse for HTTPOauth2RegistrationError {
+    fn into_response(self) -> Response {
+        let HTTPOauth2RegistrationError(error) = self;
+        match error {
+            Oauth2Error::AccessDenied => StatusCode::FORBIDDEN.into_response(),
+            Oauth2Error::InvalidClientId => {
+                HTTPOauth2Error(Oauth2Error::AuthenticationRequired).into_response()
+            }
+            error => HTTPOauth2Error(error).into_response(),
+        }
+    }
+}
+
+/// Dynamic client registration, authenticated by the token of an oauth2 registrar.
+/// <https://datatracker.ietf.org/doc/html/rfc7591#section-3.1>
+pub async fn oauth2_register_post(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    Json(reg_req): Json<ClientRegistrationRequest>,
+) -> Result<(StatusCode, Json<ClientRegistrationResponse>), HTTPOauth2RegistrationError> {
+    state
+        .qe_w_ref
+        .handle_oauth2_client_register(kopid.uat, reg_req, kopid.eventid)
+        .await
+        .map(|reg_res| (StatusCode::CREATED, Json(reg_res)))
+        .map_err(HTTPOauth2RegistrationError)
+}
+
+pub async fn oauth2_register_id_get(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    Path(client_id): Path<String>,
+) -> Result<Json<ClientRegistrationResponse>, HTTPOauth2RegistrationError> {
+    state
+        .qe_r_ref
+        .handle_oauth2_client_registration_get(kopid.uat, client_id, kopid.eventid)
+        .await
+        .map(Json)
+        .map_err(HTTPOauth2RegistrationError)
+}
+
+pub async fn oauth2_register_id_put(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    Path(client_id): Path<String>,
+    Json(reg_req): Json<ClientRegistrationRequest>,
+) -> Result<Json<ClientRegistrationResponse>, HTTPOauth2RegistrationError> {
+    state
+        .qe_w_ref
+        .handle_oauth2_client_registration_update(kopid.uat, client_id, reg_req, kopid.eventid)
+        .await
+        .map(Json)
+        .map_err(HTTPOauth2RegistrationError)
+}
+
+pub async fn oauth2_register_id_delete(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    Path(client_id): Path<String>,
+) -> Result<StatusCode, HTTPOauth2RegistrationError> {
+    state
+        .qe_w_ref
+        .handle_oauth2_client_registration_delete(kopid.uat, client_id, kopid.eventid)
+        .await
+        .map(|()| StatusCode::NO_CONTENT)
+        .map_err(HTTPOauth2RegistrationError)
+}
+
+/// RP-initiated logout. The end_session_endpoint is served by our ui, which then calls this
+/// on behalf of the user agent, as the users session is needed to end it.
+pub async fn oauth2_end_session_post(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    Json(end_session_req): Json<EndSessionRequest>,
+) -> Result<Json<EndSessionResponse>, HTTPOauth2Error> {
+    state
+        .qe_w_ref
+        .handle_oauth2_end_session(kopid.uat, end_session_req, kopid.eventid)
+        .await
+        .map(Json)
+        .map_err(HTTPOauth2Error)
+}
+
 // // For future openid integration
 pub async fn oauth2_openid_discovery_get(
     State(state): State<ServerState>,
@@ -626,6 +812,7 @@ pub async fn oauth2_openid_userinfo_get(
     State(state): State<ServerState>,
     Path(client_id): Path<String>,
     Extension(kopid): Extension<KOpId>,
+    client_cert: Option<Extension<ClientCertThumbprint>>,
 ) -> impl IntoResponse {
     // The token we want to inspect is in the authorisation header.
     let client_token = match kopid.uat {
@@ -636,9 +823,15 @@ pub async fn oauth2_openid_userinfo_get(
         }
     };
 
+    // Certificate bound tokens are checked against the certificate of this connection.
+    let client_auth_info = ClientAuthInfo {
+        basic_authz: None,
+        client_cert: client_cert.map(|Extension(ClientCertThumbprint(cert))| cert),
+    };
+
     let res = state
         .qe_r_ref
-        .handle_oauth2_openid_userinfo(client_id, client_token, kopid.eventid)
+        .handle_oauth2_openid_userinfo(client_id, client_token, client_auth_info, kopid.eventid)
         .await;
 
     match res {
@@ -665,39 +858,29 @@ pub async fn oauth2_openid_publickey_get(
 pub async fn oauth2_token_introspect_post(
     State(state): State<ServerState>,
     Extension(kopid): Extension<KOpId>,
+    client_cert: Option<Extension<ClientCertThumbprint>>,
     headers: HeaderMap,
     Form(intr_req): Form<AccessTokenIntrospectRequest>,
 ) -> impl IntoResponse {
-    let client_authz = match kopid.uat {
-        Some(val) => val,
-        None => {
-            error!("Bearer Authentication Not Provided, trying basic");
-            match headers.get(AUTHORIZATION) {
-                Some(val) => {
-                    // LOL THIS IS HILARIOUSLY TERRIBLE BUT WE PARSE THE RAW OK
-                    #[allow(clippy::unwrap_used)]
-                    val.to_str()
-                        .unwrap()
-                        .strip_prefix("Basic ")
-                        .unwrap()
-                        .to_string()
-                }
-                None => {
-                    #[allow(clippy::unwrap_used)]
-                    return Response::builder()
-                        .status(StatusCode::UNAUTHORIZED)
-                        .header(ACCESS_CONTROL_ALLOW_ORIGIN, "*")
-                        .body(Body::from("Invalid Bearer Authorisation"))
-                        .unwrap();
-                }
-            }
-        }
+    // Clients that authenticate with a certificate or assertion don't send an authorization
+    // header, so we leave it to the idm layer to decide if the client is authenticated.
+    let basic_authz = kopid.uat.or_else(|| {
+        debug!("Bearer Authentication Not Provided, trying basic");
+        headers
+            .get(AUTHORIZATION)
+            .and_then(|hv| hv.to_str().ok())
+            .and_then(|h| h.strip_prefix("Basic "))
+            .map(str::to_string)
+    });
+    let client_auth_info = ClientAuthInfo {
+        basic_authz,
+        client_cert: client_cert.map(|Extension(ClientCertThumbprint(cert))| cert),
     };
     request_trace!("Introspect Request - {:?}", intr_req);
 
     let res = state
         .qe_r_ref
-        .handle_oauth2_token_introspect(client_authz, intr_req, kopid.eventid)
+        .handle_oauth2_token_introspect(client_auth_info, intr_req, kopid.eventid)
         .await;
 
     match res {
@@ -753,27 +936,20 @@ pub async fn oauth2_token_introspect_post(
 pub async fn oauth2_token_revoke_post(
     State(state): State<ServerState>,
     Extension(kopid): Extension<KOpId>,
+    client_cert: Option<Extension<ClientCertThumbprint>>,
     Form(intr_req): Form<TokenRevokeRequest>,
 ) -> impl IntoResponse {
     // TODO: we should handle the session-based auth bit here I think maybe possibly there's no tests
-    let client_authz = match kopid.uat {
-        Some(val) => val,
-        None =>
-        {
-            #[allow(clippy::unwrap_used)]
-            return Response::builder()
-                .status(StatusCode::UNAUTHORIZED)
-                .header(ACCESS_CONTROL_ALLOW_ORIGIN, "*")
-                .body(Body::empty())
-                .unwrap()
-        }
+    let client_auth_info = ClientAuthInfo {
+        basic_authz: kopid.uat,
+        client_cert: client_cert.map(|Extension(ClientCertThumbprint(cert))| cert),
     };
 
     request_trace!("Revoke Request - {:?}", intr_req);
 
     let res = state
         .qe_w_ref
-        .handle_oauth2_token_revoke(client_authz, intr_req, kopid.eventid)
+        .handle_oauth2_token_revoke(client_auth_info, intr_req, kopid.eventid)
         .await;
 
     match res {
@@ -877,6 +1053,21 @@ pub fn oauth2_route_setup(state: ServerState) -> Router<ServerState> {
             post(oauth2_token_introspect_post),
         )
         .route("/oauth2/token/revoke", post(oauth2_token_revoke_post))
+        // ⚠️  ⚠️   WARNING  ⚠️  ⚠️
+        // IF YOU CHANGE THESE VALUES YOU MUST UPDATE OIDC DISCOVERY URLS
+        .route("/oauth2/par", post(oauth2_par_post))
+        // ⚠️  ⚠️   WARNING  ⚠️  ⚠️
+        // IF YOU CHANGE THESE VALUES YOU MUST UPDATE OIDC DISCOVERY URLS
+        .route("/oauth2/end_session", post(oauth2_end_session_post))
+        // ⚠️  ⚠️   WARNING  ⚠️  ⚠️
+        // IF YOU CHANGE THESE VALUES YOU MUST UPDATE OIDC DISCOVERY URLS
+        .route("/oauth2/register", post(oauth2_register_post))
+        .route(
+            "/oauth2/register/:client_id",
+            get(oauth2_register_id_get)
+                .put(oauth2_register_id_put)
+                .delete(oauth2_register_id_delete),
+        )
         .merge(openid_router)
         .with_state(state)
         .layer(from_fn(super::middleware::caching::dont_cache_me))
@@ -11,11 +11,14 @@ use axum_macros::debug_handler;
 use compact_jwt::Jws;
 use http::{HeaderMap, HeaderValue, StatusCode};
 use hyper::Body;
+use kanidm_proto::internal::{
+    CredentialResetEmailRequest, HelpdeskCredentialResetRequest, IdentifyUserRequest,
+};
 use kanidm_proto::v1::{
-    AccountUnixExtend, ApiTokenGenerate, AuthIssueSession, AuthRequest, AuthResponse,
-    AuthState as ProtoAuthState, CUIntentToken, CURequest, CUSessionToken, CreateRequest,
-    DeleteRequest, Entry as ProtoEntry, GroupUnixExtend, ModifyRequest, SearchRequest,
-    SingleStringRequest,
+    AccessProfile, AccessRequestCreate, AccountUnixExtend, ApiTokenGenerate, AuthIssueSession,
+    AuthRequest, AuthResponse, AuthState as ProtoAuthState, CUIntentToken, CURequest,
+    CUSessionToken, CreateRequest, DeleteRequest, Entry as ProtoEntry, Filter as ProtoFilter,
+    GroupUnixExtend, ModifyRequest, SearchRequest, SingleStringRequest,
 };
 
 use kanidmd_lib::idm::event::AuthResult;
@@ -372,6 +375,32 @@ pub async fn schema_attributetype_get_id(
     to_axum_response(res)
 }
 
+pub async fn schema_attributetype_post(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    Json(obj): Json<ProtoEntry>,
+) -> impl IntoResponse {
+    let classes = vec!["object".to_string(), "attributetype".to_string()];
+    json_rest_event_post(state, classes, obj, kopid).await
+}
+
+pub async fn schema_attributetype_patch(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    Path(id): Path<String>,
+    Json(obj): Json<ProtoEntry>,
+) -> impl IntoResponse {
+    let filter = filter_all!(f_and!([
+        f_eq("class", PartialValue::new_class("attributetype")),
+        f_eq("attributename", PartialValue::new_iutf8(id.as_str()))
+    ]));
+    let res = state
+        .qe_w_ref
+        .handle_internalpatch(kopid.uat, filter, obj, kopid.eventid)
+        .await;
+    to_axum_response(res)
+}
+
 pub async fn schema_classtype_get(
     State(state): State<ServerState>,
     Extension(kopid): Extension<KOpId>,
@@ -398,6 +427,32 @@ pub async fn schema_classtype_get_id(
     to_axum_response(res)
 }
 
+pub async fn schema_classtype_post(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    Json(obj): Json<ProtoEntry>,
+) -> impl IntoResponse {
+    let classes = vec!["object".to_string(), "classtype".to_string()];
+    json_rest_event_post(state, classes, obj, kopid).await
+}
+
+pub async fn schema_classtype_patch(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    Path(id): Path<String>,
+    Json(obj): Json<ProtoEntry>,
+) -> impl IntoResponse {
+    let filter = filter_all!(f_and!([
+        f_eq("class", PartialValue::new_class("classtype")),
+        f_eq("classname", PartialValue::new_iutf8(id.as_str()))
+    ]));
+    let res = state
+        .qe_w_ref
+        .handle_internalpatch(kopid.uat, filter, obj, kopid.eventid)
+        .await;
+    to_axum_response(res)
+}
+
 // // == person ==
 pub async fn person_get(
     State(state): State<ServerState>,
@@ -657,6 +712,45 @@ pub async fn account_get_id_credential_update_intent(
     to_axum_response(res)
 }
 
+pub async fn person_id_identify_user_post(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    Path(id): Path<String>,
+    Json(obj): Json<IdentifyUserRequest>,
+) -> impl IntoResponse {
+    let res = state
+        .qe_w_ref
+        .handle_identify_user(kopid.uat, id, obj, kopid.eventid)
+        .await;
+    to_axum_response(res)
+}
+
+pub async fn person_id_credential_helpdesk_reset_post(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    Path(id): Path<String>,
+    Json(obj): Json<HelpdeskCredentialResetRequest>,
+) -> impl IntoResponse {
+    let res = state
+        .qe_w_ref
+        .handle_helpdesk_credential_reset(kopid.uat, id, obj, kopid.eventid)
+        .await;
+    to_axum_response(res)
+}
+
+pub async fn person_id_credential_reset_email_post(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    Path(id): Path<String>,
+    Json(obj): Json<CredentialResetEmailRequest>,
+) -> impl IntoResponse {
+    let res = state
+        .qe_w_ref
+        .handle_credential_reset_email(kopid.uat, id, obj, kopid.eventid)
+        .await;
+    to_axum_response(res)
+}
+
 pub async fn account_get_id_user_auth_token(
     State(state): State<ServerState>,
     Path(id): Path<String>,
@@ -681,6 +775,42 @@ pub async fn account_user_auth_token_delete(
     to_axum_response(res)
 }
 
+pub async fn account_user_auth_token_delete_all(
+    State(state): State<ServerState>,
+    Path(id): Path<String>,
+    Extension(kopid): Extension<KOpId>,
+) -> impl IntoResponse {
+    let res = state
+        .qe_w_ref
+        .handle_account_user_auth_token_destroy_all(kopid.uat, id, kopid.eventid)
+        .await;
+    to_axum_response(res)
+}
+
+pub async fn account_get_id_oauth2_grant(
+    State(state): State<ServerState>,
+    Path(id): Path<String>,
+    Extension(kopid): Extension<KOpId>,
+) -> impl IntoResponse {
+    let res = state
+        .qe_r_ref
+        .handle_account_oauth2_grant_get(kopid.uat, id, kopid.eventid)
+        .await;
+    to_axum_response(res)
+}
+
+pub async fn account_oauth2_grant_delete(
+    State(state): State<ServerState>,
+    Path((id, rs_name)): Path<(String, String)>,
+    Extension(kopid): Extension<KOpId>,
+) -> impl IntoResponse {
+    let res = state
+        .qe_w_ref
+        .handle_account_oauth2_grant_revoke(kopid.uat, id, rs_name, kopid.eventid)
+        .await;
+    to_axum_response(res)
+}
+
 pub async fn credential_update_exchange_intent(
     State(state): State<ServerState>,
     Extension(kopid): Extension<KOpId>,
@@ -980,6 +1110,31 @@ pub async fn group_post(
     json_rest_event_post(state, classes, obj, kopid).await
 }
 
+pub async fn group_dynamic_post(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    Json(obj): Json<ProtoEntry>,
+) -> impl IntoResponse {
+    let classes = vec![
+        "dyngroup".to_string(),
+        "group".to_string(),
+        "object".to_string(),
+    ];
+    json_rest_event_post(state, classes, obj, kopid).await
+}
+
+pub async fn group_dynamic_preview_post(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    Json(filter): Json<ProtoFilter>,
+) -> impl IntoResponse {
+    let res = state
+        .qe_r_ref
+        .handle_dyngroup_preview(kopid.uat, filter, kopid.eventid)
+        .await;
+    to_axum_response(res)
+}
+
 pub async fn group_id_get(
     State(state): State<ServerState>,
     Extension(kopid): Extension<KOpId>,
@@ -1150,6 +1305,24 @@ pub async fn system_post_attr(
     .await
 }
 
+pub async fn system_put_attr(
+    State(state): State<ServerState>,
+    Path(attr): Path<String>,
+    Extension(kopid): Extension<KOpId>,
+    Json(values): Json<Vec<String>>,
+) -> impl IntoResponse {
+    let filter = filter_all!(f_eq("class", PartialValue::new_class("system_config")));
+    json_rest_event_put_attr(
+        state,
+        STR_UUID_SYSTEM_CONFIG.to_string(),
+        attr,
+        filter,
+        values,
+        kopid,
+    )
+    .await
+}
+
 pub async fn system_delete_attr(
     State(state): State<ServerState>,
     Path(attr): Path<String>,
@@ -1198,15 +1371,291 @@ pub async fn recycle_bin_id_get(
     to_axum_response(res)
 }
 
+#[derive(Deserialize, Debug)]
+pub struct ReviveRecycledQuery {
+    #[serde(default = "default_revive_with_members")]
+    with_members: bool,
+}
+
+fn default_revive_with_members() -> bool {
+    true
+}
+
 pub async fn recycle_bin_revive_id_post(
     State(state): State<ServerState>,
     Path(id): Path<String>,
+    Query(query): Query<ReviveRecycledQuery>,
     Extension(kopid): Extension<KOpId>,
 ) -> impl IntoResponse {
     let filter = filter_all!(f_id(id.as_str()));
     let res = state
         .qe_w_ref
-        .handle_reviverecycled(kopid.uat, filter, kopid.eventid)
+        .handle_reviverecycled(kopid.uat, filter, query.with_members, kopid.eventid)
+        .await;
+    to_axum_response(res)
+}
+
+pub async fn recycle_bin_purge_id_post(
+    State(state): State<ServerState>,
+    Path(id): Path<String>,
+    Extension(kopid): Extension<KOpId>,
+) -> impl IntoResponse {
+    let filter = filter_all!(f_id(id.as_str()));
+    let res = state
+        .qe_w_ref
+        .handle_purgerecycledentry(kopid.uat, filter, kopid.eventid)
+        .await;
+    to_axum_response(res)
+}
+
+pub async fn access_profile_get(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+) -> impl IntoResponse {
+    let filter = filter_all!(f_eq(
+        "class",
+        PartialValue::new_class("access_control_profile")
+    ));
+    json_rest_event_get(state, None, filter, kopid).await
+}
+
+pub async fn access_profile_post(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    Json(obj): Json<AccessProfile>,
+) -> impl IntoResponse {
+    let res = state
+        .qe_w_ref
+        .handle_access_profile_create(kopid.uat, obj, kopid.eventid)
+        .await;
+    to_axum_response(res)
+}
+
+pub async fn access_profile_id_get(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let filter = filter_all!(f_eq(
+        "class",
+        PartialValue::new_class("access_control_profile")
+    ));
+    json_rest_event_get_id(state, id, filter, None, kopid).await
+}
+
+pub async fn access_profile_id_patch(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    Path(id): Path<String>,
+    Json(obj): Json<AccessProfile>,
+) -> impl IntoResponse {
+    let res = state
+        .qe_w_ref
+        .handle_access_profile_update(kopid.uat, id, obj, kopid.eventid)
+        .await;
+    to_axum_response(res)
+}
+
+pub async fn access_profile_id_delete(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let filter = filter_all!(f_eq(
+        "class",
+        PartialValue::new_class("access_control_profile")
+    ));
+    json_rest_event_delete_id(state, id, filter, kopid).await
+}
+
+pub async fn access_profile_id_get_attr(
+    State(state): State<ServerState>,
+    Path((id, attr)): Path<(String, String)>,
+    Extension(kopid): Extension<KOpId>,
+) -> impl IntoResponse {
+    let filter = filter_all!(f_eq(
+        "class",
+        PartialValue::new_class("access_control_profile")
+    ));
+    json_rest_event_get_id_attr(state, id, attr, filter, kopid).await
+}
+
+pub async fn access_profile_check_get(
+    State(state): State<ServerState>,
+    Path((subject, target)): Path<(String, String)>,
+    Extension(kopid): Extension<KOpId>,
+) -> impl IntoResponse {
+    let res = state
+        .qe_r_ref
+        .handle_access_profile_check(kopid.uat, subject, target, kopid.eventid)
+        .await;
+    to_axum_response(res)
+}
+
+fn webhook_filter() -> Filter<FilterInvalid> {
+    filter_all!(f_eq("class", PartialValue::new_class("webhook")))
+}
+
+pub async fn webhook_get(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+) -> impl IntoResponse {
+    json_rest_event_get(state, None, webhook_filter(), kopid).await
+}
+
+pub async fn webhook_post(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    Json(obj): Json<ProtoEntry>,
+) -> impl IntoResponse {
+    let classes = vec!["webhook".to_string(), "object".to_string()];
+    json_rest_event_post(state, classes, obj, kopid).await
+}
+
+pub async fn webhook_id_get(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    json_rest_event_get_id(state, id, webhook_filter(), None, kopid).await
+}
+
+pub async fn webhook_id_delete(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    json_rest_event_delete_id(state, id, webhook_filter(), kopid).await
+}
+
+pub async fn webhook_id_get_attr(
+    State(state): State<ServerState>,
+    Path((id, attr)): Path<(String, String)>,
+    Extension(kopid): Extension<KOpId>,
+) -> impl IntoResponse {
+    json_rest_event_get_id_attr(state, id, attr, webhook_filter(), kopid).await
+}
+
+pub async fn webhook_id_put_attr(
+    Path((id, attr)): Path<(String, String)>,
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    Json(values): Json<Vec<String>>,
+) -> impl IntoResponse {
+    json_rest_event_put_id_attr(state, id, attr, webhook_filter(), values, kopid).await
+}
+
+pub async fn webhook_id_post_attr(
+    Path((id, attr)): Path<(String, String)>,
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    Json(values): Json<Vec<String>>,
+) -> impl IntoResponse {
+    json_rest_event_post_id_attr(state, id, attr, webhook_filter(), values, kopid).await
+}
+
+pub async fn webhook_id_delete_attr(
+    Path((id, attr)): Path<(String, String)>,
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    values: Option<Json<Vec<String>>>,
+) -> impl IntoResponse {
+    let values = values.map(|v| v.0);
+    json_rest_event_delete_id_attr(state, id, attr, webhook_filter(), values, kopid).await
+}
+
+pub async fn webhook_id_get_secret(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let filter = Filter::join_parts_and(webhook_filter(), filter_all!(f_id(id.as_str())));
+    let res = state
+        .qe_r_ref
+        .handle_webhook_secret_read(kopid.uat, filter, kopid.eventid)
+        .await;
+    to_axum_response(res)
+}
+
+pub async fn webhook_id_dead_letter_retry_post(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let res = state
+        .qe_w_ref
+        .handle_webhook_dead_letter_retry(kopid.uat, id, kopid.eventid)
+        .await;
+    to_axum_response(res)
+}
+
+pub async fn access_request_get(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+) -> impl IntoResponse {
+    let res = state
+        .qe_r_ref
+        .handle_access_request_list(kopid.uat, kopid.eventid)
+        .await;
+    to_axum_response(res)
+}
+
+pub async fn access_request_post(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    Json(obj): Json<AccessRequestCreate>,
+) -> impl IntoResponse {
+    let res = state
+        .qe_w_ref
+        .handle_access_request_create(kopid.uat, obj, kopid.eventid)
+        .await;
+    to_axum_response(res)
+}
+
+pub async fn access_request_id_get(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let res = state
+        .qe_r_ref
+        .handle_access_request_get(kopid.uat, id, kopid.eventid)
+        .await;
+    to_axum_response(res)
+}
+
+pub async fn access_request_id_approve_post(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let res = state
+        .qe_w_ref
+        .handle_access_request_decide(kopid.uat, id, true, kopid.eventid)
+        .await;
+    to_axum_response(res)
+}
+
+pub async fn access_request_id_deny_post(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let res = state
+        .qe_w_ref
+        .handle_access_request_decide(kopid.uat, id, false, kopid.eventid)
+        .await;
+    to_axum_response(res)
+}
+
+pub async fn access_request_id_withdraw_post(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let res = state
+        .qe_w_ref
+        .handle_access_request_withdraw(kopid.uat, id, kopid.eventid)
         .await;
     to_axum_response(res)
 }
@@ -1384,6 +1833,11 @@ pub fn router(state: ServerState) -> Router<ServerState> {
             post(super::oauth2::oauth2_id_sup_scopemap_post)
                 .delete(super::oauth2::oauth2_id_sup_scopemap_delete),
         )
+        .route(
+            "/v1/oauth2/:rs_name/_token_exchange_from/:source",
+            post(super::oauth2::oauth2_id_token_exchange_from_post)
+                .delete(super::oauth2::oauth2_id_token_exchange_from_delete),
+        )
         .route("/v1/raw/create", post(create))
         .route("/v1/raw/modify", post(v1_modify))
         .route("/v1/raw/delete", post(v1_delete))
@@ -1391,21 +1845,19 @@ pub fn router(state: ServerState) -> Router<ServerState> {
         .route("/v1/schema", get(schema_get))
         .route(
             "/v1/schema/attributetype",
-            get(schema_attributetype_get), // post(|| async { "TODO" })
+            get(schema_attributetype_get).post(schema_attributetype_post),
         )
         .route(
             "/v1/schema/attributetype/:id",
-            get(schema_attributetype_get_id),
+            get(schema_attributetype_get_id).patch(schema_attributetype_patch),
         )
-        // .route("/schema/attributetype/:id", put(|| async { "TODO" }).patch(|| async { "TODO" }))
         .route(
             "/v1/schema/classtype",
-            get(schema_classtype_get), // .post(|| async { "TODO" })
+            get(schema_classtype_get).post(schema_classtype_post),
         )
         .route(
             "/v1/schema/classtype/:id",
-            get(schema_classtype_get_id), //         .put(|| async { "TODO" })
-                                          //         .patch(|| async { "TODO" }),
+            get(schema_classtype_get_id).patch(schema_classtype_patch),
         )
         .route("/v1/self", get(whoami))
         .route("/v1/self/_uat", get(whoami_uat))
@@ -1464,6 +1916,18 @@ pub fn router(state: ServerState) -> Router<ServerState> {
             "/v1/person/:id/_credential/_update_intent",
             get(account_get_id_credential_update_intent),
         )
+        .route(
+            "/v1/person/:id/_credential/_helpdesk_reset",
+            post(person_id_credential_helpdesk_reset_post),
+        )
+        .route(
+            "/v1/person/:id/_credential/_email_reset",
+            post(person_id_credential_reset_email_post),
+        )
+        .route(
+            "/v1/person/:id/_identify_user",
+            post(person_id_identify_user_post),
+        )
         .route(
             "/v1/person/:id/_ssh_pubkeys",
             get(account_get_id_ssh_pubkeys).post(account_post_id_ssh_pubkey),
@@ -1545,6 +2009,11 @@ pub fn router(state: ServerState) -> Router<ServerState> {
             get(account_get_id_ssh_pubkey_tag).delete(account_delete_id_ssh_pubkey_tag),
         )
         .route("/v1/service_account/:id/_unix", post(account_post_id_unix))
+        .route(
+            "/v1/service_account/:id/_oauth2_registrar_scopemap/:group",
+            post(super::oauth2::oauth2_registrar_id_scopemap_post)
+                .delete(super::oauth2::oauth2_registrar_id_scopemap_delete),
+        )
         .route(
             "/v1/account/:id/_unix/_auth",
             post(account_post_id_unix_auth),
@@ -1567,12 +2036,20 @@ pub fn router(state: ServerState) -> Router<ServerState> {
         )
         .route(
             "/v1/account/:id/_user_auth_token",
-            get(account_get_id_user_auth_token),
+            get(account_get_id_user_auth_token).delete(account_user_auth_token_delete_all),
         )
         .route(
             "/v1/account/:id/_user_auth_token/:token_id",
             delete(account_user_auth_token_delete),
         )
+        .route(
+            "/v1/account/:id/_oauth2_grant",
+            get(account_get_id_oauth2_grant),
+        )
+        .route(
+            "/v1/account/:id/_oauth2_grant/:rs_name",
+            delete(account_oauth2_grant_delete),
+        )
         .route(
             "/v1/credential/_exchange_intent",
             post(credential_update_exchange_intent),
@@ -1592,6 +2069,11 @@ pub fn router(state: ServerState) -> Router<ServerState> {
         .route("/v1/group/:id/_unix/_token", get(group_get_id_unix_token))
         .route("/v1/group/:id/_unix", post(group_post_id_unix))
         .route("/v1/group", get(group_get).post(group_post))
+        .route("/v1/group/_dynamic", post(group_dynamic_post))
+        .route(
+            "/v1/group/_dynamic/_preview",
+            post(group_dynamic_preview_post),
+        )
         .route("/v1/group/:id", get(group_id_get).delete(group_id_delete))
         .route(
             "/v1/group/:id/_attr/:attr",
@@ -1605,6 +2087,7 @@ pub fn router(state: ServerState) -> Router<ServerState> {
         .route(
             "/v1/system/_attr/:attr",
             get(system_get_attr)
+                .put(system_put_attr)
                 .post(system_post_attr)
                 .delete(system_delete_attr),
         )
@@ -1614,12 +2097,62 @@ pub fn router(state: ServerState) -> Router<ServerState> {
             "/v1/recycle_bin/:id/_revive",
             post(recycle_bin_revive_id_post),
         )
-        // .route("/v1/access_profile", get(|| async { "TODO" }))
-        // .route("/v1/access_profile/:id", get(|| async { "TODO" }))
-        // .route(
-        //     "/v1/access_profile/:id/_attr/:attr",
-        //     get(|| async { "TODO" }),
-        // )
+        .route(
+            "/v1/recycle_bin/:id/_purge",
+            post(recycle_bin_purge_id_post),
+        )
+        .route(
+            "/v1/access_profile",
+            get(access_profile_get).post(access_profile_post),
+        )
+        .route(
+            "/v1/access_profile/:id",
+            get(access_profile_id_get)
+                .patch(access_profile_id_patch)
+                .delete(access_profile_id_delete),
+        )
+        .route(
+            "/v1/access_profile/:id/_attr/:attr",
+            get(access_profile_id_get_attr),
+        )
+        .route(
+            "/v1/access_profile/_check/:subject/:target",
+            get(access_profile_check_get),
+        )
+        .route("/v1/webhook", get(webhook_get).post(webhook_post))
+        .route(
+            "/v1/webhook/:id",
+            get(webhook_id_get).delete(webhook_id_delete),
+        )
+        .route(
+            "/v1/webhook/:id/_attr/:attr",
+            get(webhook_id_get_attr)
+                .put(webhook_id_put_attr)
+                .post(webhook_id_post_attr)
+                .delete(webhook_id_delete_attr),
+        )
+        .route("/v1/webhook/:id/_secret", get(webhook_id_get_secret))
+        .route(
+            "/v1/webhook/:id/_dead_letter/_retry",
+            post(webhook_id_dead_letter_retry_post),
+        )
+        .route(
+            "/v1/access_request",
+            get(access_request_get).post(access_request_post),
+        )
+        .route("/v1/access_request/:id", get(access_request_id_get))
+        .route(
+            "/v1/access_request/:id/_approve",
+            post(access_request_id_approve_post),
+        )
+        .route(
+            "/v1/access_request/:id/_deny",
+            post(access_request_id_deny_post),
+        )
+        .route(
+            "/v1/access_request/:id/_withdraw",
+            post(access_request_id_withdraw_post),
+        )
         .route("/v1/auth", post(auth))
         .route("/v1/auth/valid", get(auth_valid))
         .route("/v1/logout", get(logout))
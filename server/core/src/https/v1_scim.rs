@@ -1,13 +1,25 @@
 use super::middleware::KOpId;
 use super::{to_axum_response, ServerState};
-use axum::extract::{Path, State};
-use axum::response::IntoResponse;
+use axum::extract::{Path, Query, State};
+use axum::response::{IntoResponse, Response};
 use axum::routing::{get, post};
 use axum::{Extension, Json, Router};
 use axum_auth::AuthBearer;
+use http::header::{CONTENT_TYPE, LOCATION, WWW_AUTHENTICATE};
+use http::{HeaderValue, StatusCode};
+use hyper::Body;
 use kanidm_proto::scim_v1::ScimSyncRequest;
-use kanidm_proto::v1::Entry as ProtoEntry;
+use kanidm_proto::scim_v2::{
+    ScimV2Error, ScimV2Group, ScimV2ListResponse, ScimV2PatchRequest, ScimV2Resource, ScimV2User,
+    SCIM_V2_CONTENT_TYPE, SCIM_V2_MAX_RESULTS, SCIM_V2_MESSAGE_ERROR,
+    SCIM_V2_MESSAGE_LIST_RESPONSE, SCIM_V2_SCHEMA_GROUP, SCIM_V2_SCHEMA_RESOURCE_TYPE,
+    SCIM_V2_SCHEMA_SCHEMA, SCIM_V2_SCHEMA_SERVICE_PROVIDER_CONFIG, SCIM_V2_SCHEMA_USER,
+};
+use kanidm_proto::v1::{Entry as ProtoEntry, PluginError};
+use kanidmd_lib::idm::scim::ScimV2ResourceType;
 use kanidmd_lib::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
 
 use super::v1::{
     json_rest_event_get, json_rest_event_get_id, json_rest_event_get_id_attr, json_rest_event_post,
@@ -184,6 +196,472 @@ async fn scim_sink_get() -> impl IntoResponse {
     </html>"#
 }
 
+// SCIM 2.0 provisioning. These respond with SCIM error bodies rather than our own, since
+// that's what provisioning clients expect to parse.
+
+fn scim_v2_response<T: Serialize>(status: StatusCode, body: &T) -> Response<Body> {
+    #[allow(clippy::expect_used)]
+    match serde_json::to_string(body) {
+        Ok(val) => Response::builder()
+            .status(status)
+            .header(CONTENT_TYPE, SCIM_V2_CONTENT_TYPE)
+            .body(Body::from(val))
+            .expect("Failed to build response!"),
+        Err(err) => {
+            error!(?err, "Failed to serialise response");
+            Response::builder()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .body(Body::empty())
+                .expect("Failed to build response!")
+        }
+    }
+}
+
+fn scim_v2_error(e: &OperationError) -> Response<Body> {
+    debug!("OperationError: {:?}", e);
+    let (status, scim_type) = match e {
+        OperationError::NotAuthenticated | OperationError::SessionExpired => {
+            (StatusCode::UNAUTHORIZED, None)
+        }
+        OperationError::SystemProtectedObject | OperationError::AccessDenied => {
+            (StatusCode::FORBIDDEN, None)
+        }
+        OperationError::NoMatchingEntries => (StatusCode::NOT_FOUND, None),
+        OperationError::ScimInvalidFilter(_) => (StatusCode::BAD_REQUEST, Some("invalidFilter")),
+        OperationError::ScimInvalidPath(_) => (StatusCode::BAD_REQUEST, Some("invalidPath")),
+        OperationError::Plugin(PluginError::AttrUnique(_)) => {
+            (StatusCode::CONFLICT, Some("uniqueness"))
+        }
+        OperationError::InvalidAttribute(_)
+        | OperationError::InvalidValueState
+        | OperationError::SchemaViolation(_)
+        | OperationError::EmptyRequest => (StatusCode::BAD_REQUEST, Some("invalidValue")),
+        _ => (StatusCode::INTERNAL_SERVER_ERROR, None),
+    };
+
+    let mut res = scim_v2_response(
+        status,
+        &ScimV2Error {
+            schemas: vec![SCIM_V2_MESSAGE_ERROR.to_string()],
+            status: status.as_u16().to_string(),
+            scim_type: scim_type.map(str::to_string),
+            detail: Some(format!("{:?}", e)),
+        },
+    );
+    if status == StatusCode::UNAUTHORIZED {
+        res.headers_mut()
+            .insert(WWW_AUTHENTICATE, HeaderValue::from_static("Bearer"));
+    }
+    res
+}
+
+/// Set the absolute locations of a resource and the resources it refers to.
+fn scim_v2_locate(origin: &str, resource: &mut ScimV2Resource) {
+    let base = format!("{}/scim/v2", origin.trim_end_matches('/'));
+    let (endpoint, refs) = match resource {
+        ScimV2Resource::User(u) => (ScimV2ResourceType::User.endpoint(), &mut u.groups),
+        ScimV2Resource::Group(g) => (ScimV2ResourceType::Group.endpoint(), &mut g.members),
+    };
+
+    for r in refs.iter_mut() {
+        r.reference = r
+            .reference
+            .as_ref()
+            .map(|path| format!("{}/{}", base, path));
+    }
+
+    let location = resource
+        .id()
+        .map(|id| format!("{}/{}/{}", base, endpoint, id));
+    if let Some(meta) = resource.meta_mut() {
+        meta.location = location;
+    }
+}
+
+fn scim_v2_resource_response(
+    state: &ServerState,
+    status: StatusCode,
+    res: Result<ScimV2Resource, OperationError>,
+) -> Response<Body> {
+    match res {
+        Ok(mut resource) => {
+            scim_v2_locate(&state.origin, &mut resource);
+            let mut response = scim_v2_response(status, &resource);
+            if let Some(Ok(location)) = resource
+                .meta_mut()
+                .as_ref()
+                .and_then(|m| m.location.as_deref())
+                .map(HeaderValue::from_str)
+            {
+                response.headers_mut().insert(LOCATION, location);
+            }
+            response
+        }
+        Err(e) => scim_v2_error(&e),
+    }
+}
+
+/// Ids that aren't uuids can never match a resource.
+fn scim_v2_id(id: &str) -> Result<Uuid, OperationError> {
+    Uuid::parse_str(id).map_err(|_| OperationError::NoMatchingEntries)
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ScimV2ListQuery {
+    filter: Option<String>,
+    start_index: Option<usize>,
+    count: Option<usize>,
+}
+
+async fn scim_v2_list(
+    state: ServerState,
+    kopid: KOpId,
+    resource: ScimV2ResourceType,
+    query: ScimV2ListQuery,
+) -> Response<Body> {
+    let res = state
+        .qe_r_ref
+        .handle_scim_v2_search(
+            kopid.uat,
+            resource,
+            query.filter,
+            query.start_index,
+            query.count,
+            kopid.eventid,
+        )
+        .await;
+    match res {
+        Ok(mut list) => {
+            list.resources
+                .iter_mut()
+                .for_each(|r| scim_v2_locate(&state.origin, r));
+            scim_v2_response(StatusCode::OK, &list)
+        }
+        Err(e) => scim_v2_error(&e),
+    }
+}
+
+async fn scim_v2_id_get(
+    state: ServerState,
+    kopid: KOpId,
+    resource: ScimV2ResourceType,
+    id: String,
+) -> Response<Body> {
+    let target = match scim_v2_id(&id) {
+        Ok(t) => t,
+        Err(e) => return scim_v2_error(&e),
+    };
+    let res = state
+        .qe_r_ref
+        .handle_scim_v2_get(kopid.uat, resource, target, kopid.eventid)
+        .await;
+    scim_v2_resource_response(&state, StatusCode::OK, res)
+}
+
+async fn scim_v2_id_put(
+    state: ServerState,
+    kopid: KOpId,
+    id: String,
+    resource: ScimV2Resource,
+) -> Response<Body> {
+    let target = match scim_v2_id(&id) {
+        Ok(t) => t,
+        Err(e) => return scim_v2_error(&e),
+    };
+    let res = state
+        .qe_w_ref
+        .handle_scim_v2_replace(kopid.uat, target, resource, kopid.eventid)
+        .await;
+    scim_v2_resource_response(&state, StatusCode::OK, res)
+}
+
+async fn scim_v2_id_patch(
+    state: ServerState,
+    kopid: KOpId,
+    resource: ScimV2ResourceType,
+    id: String,
+    request: ScimV2PatchRequest,
+) -> Response<Body> {
+    let target = match scim_v2_id(&id) {
+        Ok(t) => t,
+        Err(e) => return scim_v2_error(&e),
+    };
+    let res = state
+        .qe_w_ref
+        .handle_scim_v2_patch(kopid.uat, resource, target, request, kopid.eventid)
+        .await;
+    scim_v2_resource_response(&state, StatusCode::OK, res)
+}
+
+async fn scim_v2_id_delete(
+    state: ServerState,
+    kopid: KOpId,
+    resource: ScimV2ResourceType,
+    id: String,
+) -> Response<Body> {
+    let target = match scim_v2_id(&id) {
+        Ok(t) => t,
+        Err(e) => return scim_v2_error(&e),
+    };
+    match state
+        .qe_w_ref
+        .handle_scim_v2_delete(kopid.uat, resource, target, kopid.eventid)
+        .await
+    {
+        #[allow(clippy::expect_used)]
+        Ok(()) => Response::builder()
+            .status(StatusCode::NO_CONTENT)
+            .body(Body::empty())
+            .expect("Failed to build response!"),
+        Err(e) => scim_v2_error(&e),
+    }
+}
+
+async fn scim_v2_users_get(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    Query(query): Query<ScimV2ListQuery>,
+) -> Response<Body> {
+    scim_v2_list(state, kopid, ScimV2ResourceType::User, query).await
+}
+
+async fn scim_v2_users_post(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    Json(user): Json<ScimV2User>,
+) -> Response<Body> {
+    let res = state
+        .qe_w_ref
+        .handle_scim_v2_create(kopid.uat, ScimV2Resource::User(user), kopid.eventid)
+        .await;
+    scim_v2_resource_response(&state, StatusCode::CREATED, res)
+}
+
+async fn scim_v2_users_id_get(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    Path(id): Path<String>,
+) -> Response<Body> {
+    scim_v2_id_get(state, kopid, ScimV2ResourceType::User, id).await
+}
+
+async fn scim_v2_users_id_put(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    Path(id): Path<String>,
+    Json(user): Json<ScimV2User>,
+) -> Response<Body> {
+    scim_v2_id_put(state, kopid, id, ScimV2Resource::User(user)).await
+}
+
+async fn scim_v2_users_id_patch(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    Path(id): Path<String>,
+    Json(request): Json<ScimV2PatchRequest>,
+) -> Response<Body> {
+    scim_v2_id_patch(state, kopid, ScimV2ResourceType::User, id, request).await
+}
+
+async fn scim_v2_users_id_delete(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    Path(id): Path<String>,
+) -> Response<Body> {
+    scim_v2_id_delete(state, kopid, ScimV2ResourceType::User, id).await
+}
+
+async fn scim_v2_groups_get(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    Query(query): Query<ScimV2ListQuery>,
+) -> Response<Body> {
+    scim_v2_list(state, kopid, ScimV2ResourceType::Group, query).await
+}
+
+async fn scim_v2_groups_post(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    Json(group): Json<ScimV2Group>,
+) -> Response<Body> {
+    let res = state
+        .qe_w_ref
+        .handle_scim_v2_create(kopid.uat, ScimV2Resource::Group(group), kopid.eventid)
+        .await;
+    scim_v2_resource_response(&state, StatusCode::CREATED, res)
+}
+
+async fn scim_v2_groups_id_get(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    Path(id): Path<String>,
+) -> Response<Body> {
+    scim_v2_id_get(state, kopid, ScimV2ResourceType::Group, id).await
+}
+
+async fn scim_v2_groups_id_put(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    Path(id): Path<String>,
+    Json(group): Json<ScimV2Group>,
+) -> Response<Body> {
+    scim_v2_id_put(state, kopid, id, ScimV2Resource::Group(group)).await
+}
+
+async fn scim_v2_groups_id_patch(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    Path(id): Path<String>,
+    Json(request): Json<ScimV2PatchRequest>,
+) -> Response<Body> {
+    scim_v2_id_patch(state, kopid, ScimV2ResourceType::Group, id, request).await
+}
+
+async fn scim_v2_groups_id_delete(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    Path(id): Path<String>,
+) -> Response<Body> {
+    scim_v2_id_delete(state, kopid, ScimV2ResourceType::Group, id).await
+}
+
+async fn scim_v2_service_provider_config_get() -> Response<Body> {
+    scim_v2_response(
+        StatusCode::OK,
+        &json!({
+            "schemas": [SCIM_V2_SCHEMA_SERVICE_PROVIDER_CONFIG],
+            "documentationUri": "https://kanidm.github.io/kanidm/stable/",
+            "patch": { "supported": true },
+            "bulk": { "supported": false, "maxOperations": 0, "maxPayloadSize": 0 },
+            "filter": { "supported": true, "maxResults": SCIM_V2_MAX_RESULTS },
+            "changePassword": { "supported": false },
+            "sort": { "supported": false },
+            "etag": { "supported": false },
+            "authenticationSchemes": [{
+                "type": "oauthbearertoken",
+                "name": "Bearer Token",
+                "description": "Authentication with an api token or session token",
+                "primary": true
+            }]
+        }),
+    )
+}
+
+fn scim_v2_list_of(resources: Vec<serde_json::Value>) -> ScimV2ListResponse<serde_json::Value> {
+    ScimV2ListResponse {
+        schemas: vec![SCIM_V2_MESSAGE_LIST_RESPONSE.to_string()],
+        total_results: resources.len(),
+        start_index: 1,
+        items_per_page: resources.len(),
+        resources,
+    }
+}
+
+async fn scim_v2_resource_types_get() -> Response<Body> {
+    let resource_type = |name: &str, endpoint: &str, schema: &str| {
+        json!({
+            "schemas": [SCIM_V2_SCHEMA_RESOURCE_TYPE],
+            "id": name,
+            "name": name,
+            "endpoint": format!("/{}", endpoint),
+            "schema": schema,
+        })
+    };
+
+    scim_v2_response(
+        StatusCode::OK,
+        &scim_v2_list_of(vec![
+            resource_type(
+                "User",
+                ScimV2ResourceType::User.endpoint(),
+                SCIM_V2_SCHEMA_USER,
+            ),
+            resource_type(
+                "Group",
+                ScimV2ResourceType::Group.endpoint(),
+                SCIM_V2_SCHEMA_GROUP,
+            ),
+        ]),
+    )
+}
+
+/// Describe a schema attribute. Only the fields that differ between attributes are
+/// parameters, the rest are the defaults of RFC 7643 section 7.
+fn scim_v2_schema_attr(
+    name: &str,
+    kind: &str,
+    multi_valued: bool,
+    required: bool,
+    mutability: &str,
+) -> serde_json::Value {
+    json!({
+        "name": name,
+        "type": kind,
+        "multiValued": multi_valued,
+        "required": required,
+        "caseExact": false,
+        "mutability": mutability,
+        "returned": "default",
+        "uniqueness": if name == "userName" { "server" } else { "none" },
+    })
+}
+
+async fn scim_v2_schemas_get() -> Response<Body> {
+    let mut emails = scim_v2_schema_attr("emails", "complex", true, false, "readWrite");
+    emails["subAttributes"] = json!([
+        scim_v2_schema_attr("value", "string", false, true, "readWrite"),
+        scim_v2_schema_attr("primary", "boolean", false, false, "readWrite"),
+    ]);
+    let mut name = scim_v2_schema_attr("name", "complex", false, false, "readWrite");
+    name["subAttributes"] = json!([scim_v2_schema_attr(
+        "formatted",
+        "string",
+        false,
+        false,
+        "readWrite"
+    )]);
+    let mut groups = scim_v2_schema_attr("groups", "complex", true, false, "readOnly");
+    groups["subAttributes"] = json!([scim_v2_schema_attr(
+        "value", "string", false, false, "readOnly"
+    )]);
+    let mut members = scim_v2_schema_attr("members", "complex", true, false, "readWrite");
+    members["subAttributes"] = json!([scim_v2_schema_attr(
+        "value",
+        "string",
+        false,
+        false,
+        "immutable"
+    )]);
+
+    let user = json!({
+        "schemas": [SCIM_V2_SCHEMA_SCHEMA],
+        "id": SCIM_V2_SCHEMA_USER,
+        "name": "User",
+        "description": "User Account",
+        "attributes": [
+            scim_v2_schema_attr("userName", "string", false, true, "readWrite"),
+            scim_v2_schema_attr("displayName", "string", false, false, "readWrite"),
+            name,
+            emails,
+            scim_v2_schema_attr("active", "boolean", false, false, "readWrite"),
+            groups,
+        ],
+    });
+    let group = json!({
+        "schemas": [SCIM_V2_SCHEMA_SCHEMA],
+        "id": SCIM_V2_SCHEMA_GROUP,
+        "name": "Group",
+        "description": "Group",
+        "attributes": [
+            scim_v2_schema_attr("displayName", "string", false, true, "readWrite"),
+            members,
+        ],
+    });
+
+    scim_v2_response(StatusCode::OK, &scim_v2_list_of(vec![user, group]))
+}
+
 pub fn scim_route_setup() -> Router<ServerState> {
     Router::new()
         // https://datatracker.ietf.org/doc/html/rfc7644#section-3.2
@@ -256,4 +734,33 @@ pub fn scim_route_setup() -> Router<ServerState> {
         //
         .route("/scim/v1/Sync", post(scim_sync_post).get(scim_sync_get))
         .route("/scim/v1/Sink", get(scim_sink_get))
+        // -- SCIM 2.0 provisioning of users and groups.
+        .route(
+            "/scim/v2/Users",
+            get(scim_v2_users_get).post(scim_v2_users_post),
+        )
+        .route(
+            "/scim/v2/Users/:id",
+            get(scim_v2_users_id_get)
+                .put(scim_v2_users_id_put)
+                .patch(scim_v2_users_id_patch)
+                .delete(scim_v2_users_id_delete),
+        )
+        .route(
+            "/scim/v2/Groups",
+            get(scim_v2_groups_get).post(scim_v2_groups_post),
+        )
+        .route(
+            "/scim/v2/Groups/:id",
+            get(scim_v2_groups_id_get)
+                .put(scim_v2_groups_id_put)
+                .patch(scim_v2_groups_id_patch)
+                .delete(scim_v2_groups_id_delete),
+        )
+        .route(
+            "/scim/v2/ServiceProviderConfig",
+            get(scim_v2_service_provider_config_get),
+        )
+        .route("/scim/v2/ResourceTypes", get(scim_v2_resource_types_get))
+        .route("/scim/v2/Schemas", get(scim_v2_schemas_get))
 }
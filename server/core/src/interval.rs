@@ -16,11 +16,51 @@ use crate::CoreAction;
 
 use crate::actors::v1_read::QueryServerReadV1;
 use crate::actors::v1_write::QueryServerWriteV1;
+use kanidmd_lib::be::dbbackup::{BackupCompression, BackupKey, BackupOptions, DbBackupManifest};
 use kanidmd_lib::constants::PURGE_FREQUENCY;
-use kanidmd_lib::event::{OnlineBackupEvent, PurgeRecycledEvent, PurgeTombstoneEvent};
+use kanidmd_lib::event::{
+    OnlineBackupEvent, PurgeMemberValidityEvent, PurgeRecycledEvent, PurgeTombstoneEvent,
+};
 
 pub(crate) struct IntervalActor;
 
+/// Tracks the chain of online backups, so that incremental backups can be taken between
+/// full backups. This is only held in memory, so the first backup after a restart is
+/// always a full backup.
+pub(crate) struct OnlineBackupChain {
+    /// How many incremental backups to take after each full backup.
+    incrementals: usize,
+    /// The manifest of the latest backup of the chain, and how many incremental backups
+    /// have been taken since the full backup.
+    latest: Option<(DbBackupManifest, usize)>,
+}
+
+impl OnlineBackupChain {
+    pub fn new(incrementals: usize) -> Self {
+        OnlineBackupChain {
+            incrementals,
+            latest: None,
+        }
+    }
+
+    /// The parent of the next backup, if it should be an incremental backup.
+    pub fn next_parent(&self) -> Option<&DbBackupManifest> {
+        match &self.latest {
+            Some((manifest, count)) if *count < self.incrementals => Some(manifest),
+            _ => None,
+        }
+    }
+
+    pub fn push_incremental(&mut self, manifest: DbBackupManifest) {
+        let count = self.latest.as_ref().map(|(_, c)| *c).unwrap_or(0);
+        self.latest = Some((manifest, count + 1));
+    }
+
+    pub fn reset(&mut self, manifest: DbBackupManifest) {
+        self.latest = Some((manifest, 0));
+    }
+}
+
 impl IntervalActor {
     pub fn start(
         server: &'static QueryServerWriteV1,
@@ -43,6 +83,9 @@ impl IntervalActor {
                         server
                             .handle_purgerecycledevent(PurgeRecycledEvent::new())
                             .await;
+                        server
+                            .handle_purgemembervalidityevent(PurgeMemberValidityEvent::new())
+                            .await;
                     }
                 }
             }
@@ -61,6 +104,22 @@ impl IntervalActor {
     ) -> Result<tokio::task::JoinHandle<()>, ()> {
         let outpath = online_backup_config.path.to_owned();
         let versions = online_backup_config.versions;
+        let mut chain = OnlineBackupChain::new(online_backup_config.incrementals);
+        let compression = if online_backup_config.compress {
+            BackupCompression::Gzip
+        } else {
+            BackupCompression::None
+        };
+        let key = match &online_backup_config.key_file {
+            Some(key_file) => Some(BackupKey::from_key_file(key_file).map_err(|e| {
+                error!(?e, "Online backup failed to load key file '{}'", key_file);
+            })?),
+            None => {
+                warn!("Online backups are not encrypted, consider setting a key_file");
+                None
+            }
+        };
+        let backup_opts = BackupOptions { compression, key };
         let crono_expr = online_backup_config.schedule.as_str().to_string();
         let mut crono_expr_values = crono_expr.split_ascii_whitespace().collect::<Vec<&str>>();
         let chrono_expr_uses_standard_syntax = crono_expr_values.len() == 5;
@@ -141,6 +200,8 @@ impl IntervalActor {
                                 OnlineBackupEvent::new(),
                                 outpath.clone().as_str(),
                                 versions,
+                                &backup_opts,
+                                &mut chain,
                             )
                             .await
                         {
@@ -27,18 +27,25 @@ extern crate kanidmd_lib;
 
 pub mod actors;
 pub mod admin;
+mod backchannel;
 pub mod config;
 mod crypto;
 mod https;
 mod interval;
 mod ldaps;
+mod mail;
+mod webhook;
 
 use std::path::Path;
 use std::sync::Arc;
 
 use compact_jwt::JwsSigner;
 use kanidm_proto::v1::OperationError;
+use kanidmd_lib::be::dbbackup::{
+    read_backup_manifest, verify_backup_file, BackupKey, BackupOptions,
+};
 use kanidmd_lib::be::{Backend, BackendConfig, BackendTransaction, FsType};
+use kanidmd_lib::idm::breachlist::BreachList;
 use kanidmd_lib::idm::ldap::LdapServer;
 use kanidmd_lib::prelude::*;
 use kanidmd_lib::schema::Schema;
@@ -52,8 +59,11 @@ use tokio::sync::broadcast;
 use crate::actors::v1_read::QueryServerReadV1;
 use crate::actors::v1_write::QueryServerWriteV1;
 use crate::admin::AdminActor;
+use crate::backchannel::BackchannelLogoutActor;
 use crate::config::{Configuration, ServerRole};
 use crate::interval::IntervalActor;
+use crate::mail::MailActor;
+use crate::webhook::WebhookActor;
 
 // === internal setup helpers
 
@@ -101,7 +111,18 @@ async fn setup_qs_idms(
     be: Backend,
     schema: Schema,
     config: &Configuration,
-) -> Result<(QueryServer, IdmServer, IdmServerDelayed, IdmServerAudit), OperationError> {
+) -> Result<
+    (
+        QueryServer,
+        IdmServer,
+        IdmServerDelayed,
+        IdmServerAudit,
+        IdmServerWebhook,
+        IdmServerBackchannel,
+        IdmServerMail,
+    ),
+    OperationError,
+> {
     // Create a query_server implementation
     let query_server = QueryServer::new(be, schema, config.domain.clone());
 
@@ -117,12 +138,32 @@ async fn setup_qs_idms(
         .initialise_helper(duration_from_epoch_now())
         .await?;
 
+    let breach_list = config
+        .breached_password_list
+        .as_ref()
+        .map(BreachList::open)
+        .transpose()?;
+
     // We generate a SINGLE idms only!
 
-    let (idms, idms_delayed, idms_audit) =
-        IdmServer::new(query_server.clone(), &config.origin).await?;
+    let (idms, idms_delayed, idms_audit, idms_webhook, idms_backchannel, idms_mail) =
+        IdmServer::new(
+            query_server.clone(),
+            &config.origin,
+            breach_list,
+            config.mail.is_some(),
+        )
+        .await?;
 
-    Ok((query_server, idms, idms_delayed, idms_audit))
+    Ok((
+        query_server,
+        idms,
+        idms_delayed,
+        idms_audit,
+        idms_webhook,
+        idms_backchannel,
+        idms_mail,
+    ))
 }
 
 async fn setup_qs(
@@ -238,7 +279,20 @@ pub fn dbscan_get_id2entry_core(config: &Configuration, id: u64) {
     };
 }
 
-pub fn backup_server_core(config: &Configuration, dst_path: &str) {
+pub fn backup_server_core(
+    config: &Configuration,
+    dst_path: &str,
+    parent_path: Option<&str>,
+    opts: &BackupOptions,
+) {
+    let parent = match parent_path.map(read_backup_manifest).transpose() {
+        Ok(p) => p,
+        Err(e) => {
+            error!("Failed to read parent backup: {:?}", e);
+            std::process::exit(1);
+        }
+    };
+
     let schema = match Schema::new() {
         Ok(s) => s,
         Err(e) => {
@@ -256,9 +310,17 @@ pub fn backup_server_core(config: &Configuration, dst_path: &str) {
     };
 
     let mut be_ro_txn = be.read();
-    let r = be_ro_txn.backup(dst_path);
+    let r = match &parent {
+        Some(parent) => be_ro_txn.backup_incremental(dst_path, parent, opts),
+        None => be_ro_txn.backup(dst_path, opts),
+    };
     match r {
-        Ok(_) => info!("Backup success!"),
+        Ok(manifest) => info!(
+            kind = ?manifest.kind,
+            entries = manifest.entry_count,
+            checksum = %manifest.checksum,
+            "Backup success!"
+        ),
         Err(e) => {
             error!("Backup failed: {:?}", e);
             std::process::exit(1);
@@ -267,7 +329,12 @@ pub fn backup_server_core(config: &Configuration, dst_path: &str) {
     // Let the txn abort, even on success.
 }
 
-pub async fn restore_server_core(config: &Configuration, dst_path: &str) {
+pub async fn restore_server_core(
+    config: &Configuration,
+    dst_path: &str,
+    incremental_paths: &[&str],
+    key: Option<&BackupKey>,
+) {
     touch_file_or_quit(config.db_path.as_str());
 
     // First, we provide the in-memory schema so that core attrs are indexed correctly.
@@ -288,7 +355,9 @@ pub async fn restore_server_core(config: &Configuration, dst_path: &str) {
     };
 
     let mut be_wr_txn = be.write();
-    let r = be_wr_txn.restore(dst_path).and_then(|_| be_wr_txn.commit());
+    let r = be_wr_txn
+        .restore(dst_path, incremental_paths, key)
+        .and_then(|_| be_wr_txn.commit());
 
     if r.is_err() {
         error!("Failed to restore database: {:?}", r);
@@ -298,13 +367,14 @@ pub async fn restore_server_core(config: &Configuration, dst_path: &str) {
 
     info!("Attempting to init query server ...");
 
-    let (qs, _idms, _idms_delayed, _idms_audit) = match setup_qs_idms(be, schema, config).await {
-        Ok(t) => t,
-        Err(e) => {
-            error!("Unable to setup query server or idm server -> {:?}", e);
-            return;
-        }
-    };
+    let (qs, _idms, _idms_delayed, _idms_audit, _idms_webhook, _idms_backchannel, _idms_mail) =
+        match setup_qs_idms(be, schema, config).await {
+            Ok(t) => t,
+            Err(e) => {
+                error!("Unable to setup query server or idm server -> {:?}", e);
+                return;
+            }
+        };
     info!("Success!");
 
     info!("Start reindex phase ...");
@@ -323,6 +393,33 @@ pub async fn restore_server_core(config: &Configuration, dst_path: &str) {
     info!("✅ Restore Success!");
 }
 
+pub fn verify_backup_core(src_path: &str, key: Option<&BackupKey>) {
+    match verify_backup_file(src_path, key) {
+        Ok(Some(manifest)) => {
+            info!(
+                format = manifest.format,
+                kind = ?manifest.kind,
+                server_version = %manifest.server_version,
+                domain_uuid = %manifest.db_d_uuid,
+                server_uuid = %manifest.db_s_uuid,
+                entries = manifest.entry_count,
+                compression = ?manifest.compression,
+                encrypted = manifest.encryption.is_some(),
+                checksum = %manifest.checksum,
+                "Backup manifest"
+            );
+            info!("✅ Backup Verified!");
+        }
+        Ok(None) => {
+            warn!("Backup has no manifest and could only be parsed");
+        }
+        Err(e) => {
+            error!("Backup verification failed: {:?}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
 pub async fn reindex_server_core(config: &Configuration) {
     eprintln!("Start Index Phase 1 ...");
     // First, we provide the in-memory schema so that core attrs are indexed correctly.
@@ -355,13 +452,14 @@ pub async fn reindex_server_core(config: &Configuration) {
 
     eprintln!("Attempting to init query server ...");
 
-    let (qs, _idms, _idms_delayed, _idms_audit) = match setup_qs_idms(be, schema, config).await {
-        Ok(t) => t,
-        Err(e) => {
-            error!("Unable to setup query server or idm server -> {:?}", e);
-            return;
-        }
-    };
+    let (qs, _idms, _idms_delayed, _idms_audit, _idms_webhook, _idms_backchannel, _idms_mail) =
+        match setup_qs_idms(be, schema, config).await {
+            Ok(t) => t,
+            Err(e) => {
+                error!("Unable to setup query server or idm server -> {:?}", e);
+                return;
+            }
+        };
     eprintln!("Init Query Server Success!");
 
     eprintln!("Start Index Phase 2 ...");
@@ -688,7 +786,7 @@ pub async fn create_server_core(
         }
     };
     // Start the IDM server.
-    let (_qs, idms, mut idms_delayed, mut idms_audit) =
+    let (_qs, idms, mut idms_delayed, mut idms_audit, idms_webhook, idms_backchannel, idms_mail) =
         match setup_qs_idms(be, schema, &config).await {
             Ok(t) => t,
             Err(e) => {
@@ -798,6 +896,24 @@ pub async fn create_server_core(
         info!("Stopped AuditdActor");
     });
 
+    let webhook_handle =
+        WebhookActor::start(server_write_ref, idms_webhook, broadcast_tx.subscribe())?;
+
+    let backchannel_handle =
+        BackchannelLogoutActor::start(idms_backchannel, broadcast_tx.subscribe())?;
+
+    // Messages are only queued when a relay is configured, so without one there is nothing to deliver.
+    let maybe_mail_handle = match &config.mail {
+        Some(mail_config) => Some(MailActor::start(
+            server_write_ref,
+            idms_mail,
+            mail_config,
+            &config.origin,
+            broadcast_tx.subscribe(),
+        )?),
+        None => None,
+    };
+
     // Setup timed events associated to the write thread
     let interval_handle = IntervalActor::start(server_write_ref, broadcast_tx.subscribe());
     // Setup timed events associated to the read thread
@@ -890,12 +1006,22 @@ pub async fn create_server_core(
         Some(h)
     };
 
-    let mut handles = vec![interval_handle, delayed_handle, auditd_handle];
+    let mut handles = vec![
+        interval_handle,
+        delayed_handle,
+        auditd_handle,
+        webhook_handle,
+        backchannel_handle,
+    ];
 
     if let Some(backup_handle) = maybe_backup_handle {
         handles.push(backup_handle)
     }
 
+    if let Some(mail_handle) = maybe_mail_handle {
+        handles.push(mail_handle)
+    }
+
     if let Some(admin_sock_handle) = maybe_admin_sock_handle {
         handles.push(admin_sock_handle)
     }
@@ -0,0 +1,307 @@
+//! Delivers queued messages, such as credential reset links, to people by mail. Messages are
+//! stored by the server, so the queue is checked when the actor starts, whenever a transaction
+//! that queued a message commits, and periodically for messages that are due a retry.
+
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+use kanidmd_lib::idm::mail::{MessageTemplate, OutboundMessage};
+use kanidmd_lib::prelude::{IdmServerMail, Rfc3339, Url};
+use lettre::message::header::ContentType;
+use lettre::message::Mailbox;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+use tokio::time::interval;
+
+use crate::actors::v1_write::QueryServerWriteV1;
+use crate::config::{MailConfig, MailTls};
+use crate::CoreAction;
+
+/// How often the queue is checked for messages that are due a retry.
+const MAIL_QUEUE_INTERVAL: Duration = Duration::from_secs(60);
+const MAIL_TIMEOUT: Duration = Duration::from_secs(30);
+
+const CREDENTIAL_RESET_FILE: &str = "credential_reset.txt";
+const CREDENTIALS_CHANGED_FILE: &str = "credentials_changed.txt";
+
+const CREDENTIAL_RESET_TEMPLATE: &str = "Subject: Set the credentials of your account
+
+Hello {display_name},
+
+Use this link to set the credentials of your account {name}:
+
+{url}
+
+Alternatively, enter this code at {origin}/ui/reset
+
+{token}
+
+This link can only be used once, and expires at {expiry}.
+If you were not expecting this message, contact your administrator.
+";
+
+const CREDENTIALS_CHANGED_TEMPLATE: &str = "Subject: The credentials of your account were changed
+
+Hello {display_name},
+
+The credentials of your account {name} were changed.
+If you did not make this change, contact your administrator immediately.
+";
+
+/// The subject and body of a message, which may contain placeholders such as `{name}`.
+struct Template {
+    subject: String,
+    body: String,
+}
+
+impl Template {
+    /// Templates start with a subject line, followed by an empty line and the body.
+    fn parse(content: &str) -> Option<Self> {
+        let content = content.replace("\r\n", "\n");
+        let (header, body) = content.split_once("\n\n")?;
+        let subject = header.strip_prefix("Subject:")?.trim();
+        Some(Template {
+            subject: subject.to_string(),
+            body: body.to_string(),
+        })
+    }
+
+    fn load(dir: Option<&Path>, file: &str, default: &str) -> Result<Self, ()> {
+        let path = dir.map(|dir| dir.join(file)).filter(|path| path.exists());
+        let content = match &path {
+            Some(path) => fs::read_to_string(path).map_err(|e| {
+                error!(?e, ?path, "Unable to read mail template");
+            })?,
+            None => default.to_string(),
+        };
+        Template::parse(&content).ok_or_else(|| {
+            error!(
+                ?path,
+                "Invalid mail template, it must start with a 'Subject:' line followed by an empty line"
+            );
+        })
+    }
+
+    fn render(&self, values: &[(&str, &str)]) -> (String, String) {
+        (fill(&self.subject, values), fill(&self.body, values))
+    }
+}
+
+/// Replace the placeholders in a template with their values. This is a single pass so that
+/// values, such as a display name, can't introduce placeholders of their own.
+fn fill(text: &str, values: &[(&str, &str)]) -> String {
+    let mut filled = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('{') {
+        filled.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let placeholder = after.find('}').and_then(|end| {
+            values
+                .iter()
+                .find(|(name, _)| *name == &after[..end])
+                .map(|(_, value)| (end, value))
+        });
+        match placeholder {
+            Some((end, value)) => {
+                filled.push_str(value);
+                rest = &after[end + 1..];
+            }
+            None => {
+                filled.push('{');
+                rest = after;
+            }
+        }
+    }
+    filled.push_str(rest);
+    filled
+}
+
+struct Mailer {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+    origin: Url,
+    credential_reset: Template,
+    credentials_changed: Template,
+}
+
+impl Mailer {
+    fn new(config: &MailConfig, origin: &str) -> Result<Self, ()> {
+        let builder = match config.smtp_tls {
+            MailTls::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(&config.smtp_host),
+            MailTls::StartTls => {
+                AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&config.smtp_host)
+            }
+            MailTls::None => Ok(AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(
+                &config.smtp_host,
+            )),
+        }
+        .map_err(|e| {
+            error!(?e, "Invalid mail relay configuration");
+        })?
+        .timeout(Some(MAIL_TIMEOUT));
+
+        let builder = match config.smtp_port {
+            Some(port) => builder.port(port),
+            None => builder,
+        };
+
+        let builder = match (&config.smtp_username, &config.smtp_password_file) {
+            (Some(username), Some(password_file)) => {
+                let password = fs::read_to_string(password_file).map_err(|e| {
+                    error!(
+                        ?e,
+                        ?password_file,
+                        "Unable to read mail relay password file"
+                    );
+                })?;
+                builder.credentials(Credentials::new(
+                    username.clone(),
+                    password.trim().to_string(),
+                ))
+            }
+            (None, None) => builder,
+            _ => {
+                error!("smtp_username and smtp_password_file must be set together");
+                return Err(());
+            }
+        };
+
+        let from = config.from.parse().map_err(|e| {
+            error!(?e, from = ?config.from, "Invalid mail from address");
+        })?;
+
+        let origin = Url::parse(origin).map_err(|e| {
+            error!(?e, "Invalid origin");
+        })?;
+
+        let template_path = config.template_path.as_deref().map(Path::new);
+        let credential_reset = Template::load(
+            template_path,
+            CREDENTIAL_RESET_FILE,
+            CREDENTIAL_RESET_TEMPLATE,
+        )?;
+        let credentials_changed = Template::load(
+            template_path,
+            CREDENTIALS_CHANGED_FILE,
+            CREDENTIALS_CHANGED_TEMPLATE,
+        )?;
+
+        Ok(Mailer {
+            transport: builder.build(),
+            from,
+            origin,
+            credential_reset,
+            credentials_changed,
+        })
+    }
+
+    fn render(&self, template: &MessageTemplate) -> (String, String) {
+        let origin = self.origin.as_str().trim_end_matches('/');
+        match template {
+            MessageTemplate::CredentialReset {
+                name,
+                display_name,
+                intent_id,
+                expiry,
+            } => {
+                let mut url = self.origin.clone();
+                url.set_path("/ui/reset");
+                url.query_pairs_mut().append_pair("token", intent_id);
+                let expiry = expiry
+                    .format(&Rfc3339)
+                    .unwrap_or_else(|_| expiry.to_string());
+                self.credential_reset.render(&[
+                    ("name", name),
+                    ("display_name", display_name),
+                    ("url", url.as_str()),
+                    ("token", intent_id),
+                    ("expiry", &expiry),
+                    ("origin", origin),
+                ])
+            }
+            MessageTemplate::CredentialsChanged { name, display_name } => {
+                self.credentials_changed.render(&[
+                    ("name", name),
+                    ("display_name", display_name),
+                    ("origin", origin),
+                ])
+            }
+        }
+    }
+
+    async fn send(&self, message: &OutboundMessage) -> Result<(), String> {
+        let (subject, body) = self.render(&message.template);
+        let to: Mailbox = message.to.parse().map_err(|e| format!("{:?}", e))?;
+        let mail = Message::builder()
+            .from(self.from.clone())
+            .to(to)
+            .subject(subject)
+            .header(ContentType::TEXT_PLAIN)
+            .body(body)
+            .map_err(|e| e.to_string())?;
+
+        self.transport
+            .send(mail)
+            .await
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+
+    #[instrument(level = "debug", skip_all)]
+    async fn deliver_due(&self, server: &'static QueryServerWriteV1) {
+        for message in server.handle_mail_queue_due().await {
+            match self.send(&message).await {
+                Ok(()) => {
+                    debug!(message = ?message.uuid, "Delivered message");
+                    server.handle_mail_delivered(message.uuid).await;
+                }
+                Err(e) => {
+                    server.handle_mail_delivery_failed(&message, &e).await;
+                }
+            }
+        }
+    }
+}
+
+pub(crate) struct MailActor;
+
+impl MailActor {
+    pub fn start(
+        server: &'static QueryServerWriteV1,
+        mut idms_mail: IdmServerMail,
+        config: &MailConfig,
+        origin: &str,
+        mut rx: broadcast::Receiver<CoreAction>,
+    ) -> Result<JoinHandle<()>, ()> {
+        let mailer = Mailer::new(config, origin)?;
+
+        let handle = tokio::spawn(async move {
+            // The first tick completes immediately, delivering anything queued before a restart.
+            let mut queue_interval = interval(MAIL_QUEUE_INTERVAL);
+            loop {
+                tokio::select! {
+                    Ok(action) = rx.recv() => {
+                        match action {
+                            CoreAction::Shutdown => break,
+                        }
+                    }
+                    _ = queue_interval.tick() => {
+                        mailer.deliver_due(server).await;
+                    }
+                    queued = idms_mail.mail_rx().recv() => {
+                        match queued {
+                            Some(()) => mailer.deliver_due(server).await,
+                            None => break,
+                        }
+                    }
+                }
+            }
+            info!("Stopped MailActor");
+        });
+
+        Ok(handle)
+    }
+}
@@ -0,0 +1,172 @@
+//! Delivers webhook events to their subscribers once the transaction that caused them has
+//! committed. Each event is posted as JSON, signed with the secret of its webhook. Events
+//! that can't be delivered after a number of attempts are stored on the webhook so that
+//! they can be inspected and retried.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use kanidmd_lib::idm::webhook::WebhookDelivery;
+use kanidmd_lib::prelude::IdmServerWebhook;
+use openssl::hash::MessageDigest;
+use openssl::pkey::PKey;
+use openssl::sign::Signer;
+use tokio::sync::{broadcast, Semaphore};
+use tokio::task::JoinHandle;
+use tokio::time::sleep;
+
+use crate::actors::v1_write::QueryServerWriteV1;
+use crate::CoreAction;
+
+/// How many times delivery of an event is attempted before it is dead lettered.
+const WEBHOOK_ATTEMPTS: u32 = 4;
+/// How long to wait before the first retry. This doubles after each failed attempt.
+const WEBHOOK_RETRY_DELAY: Duration = Duration::from_secs(1);
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+/// How many events may be in the process of being delivered at once. Further events wait
+/// in the queue until a delivery completes.
+const WEBHOOK_MAX_CONCURRENT: usize = 8;
+
+const WEBHOOK_SIGNATURE_HEADER: &str = "X-Kanidm-Signature";
+const WEBHOOK_EVENT_HEADER: &str = "X-Kanidm-Event";
+const WEBHOOK_DELIVERY_HEADER: &str = "X-Kanidm-Delivery";
+
+pub(crate) struct WebhookActor;
+
+impl WebhookActor {
+    pub fn start(
+        server: &'static QueryServerWriteV1,
+        mut idms_webhook: IdmServerWebhook,
+        mut rx: broadcast::Receiver<CoreAction>,
+    ) -> Result<JoinHandle<()>, ()> {
+        let client = reqwest::Client::builder()
+            .timeout(WEBHOOK_TIMEOUT)
+            .build()
+            .map_err(|e| {
+                error!(?e, "Unable to build webhook http client");
+            })?;
+
+        let deliveries = Arc::new(Semaphore::new(WEBHOOK_MAX_CONCURRENT));
+
+        let handle = tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    Ok(action) = rx.recv() => {
+                        match action {
+                            CoreAction::Shutdown => break,
+                        }
+                    }
+                    delivery = idms_webhook.webhook_rx().recv() => {
+                        match delivery {
+                            Some(delivery) => {
+                                // Deliveries are independent, so a slow subscriber doesn't
+                                // hold up the others, but only so many run at once.
+                                let permit = tokio::select! {
+                                    Ok(action) = rx.recv() => {
+                                        match action {
+                                            CoreAction::Shutdown => break,
+                                        }
+                                    }
+                                    permit = deliveries.clone().acquire_owned() => permit,
+                                };
+                                let Ok(permit) = permit else {
+                                    break;
+                                };
+                                let client = client.clone();
+                                tokio::spawn(async move {
+                                    deliver(server, client, delivery).await;
+                                    drop(permit);
+                                });
+                            }
+                            None => break,
+                        }
+                    }
+                }
+            }
+            info!("Stopped WebhookActor");
+        });
+
+        Ok(handle)
+    }
+}
+
+/// The value of the signature header for a request body.
+fn sign(secret: &str, body: &[u8]) -> Result<String, openssl::error::ErrorStack> {
+    let key = PKey::hmac(secret.as_bytes())?;
+    let mut signer = Signer::new(MessageDigest::sha256(), &key)?;
+    signer
+        .sign_oneshot_to_vec(body)
+        .map(|sig| format!("sha256={}", hex::encode(sig)))
+}
+
+async fn post(
+    client: &reqwest::Client,
+    delivery: &WebhookDelivery,
+    body: &[u8],
+    signature: &str,
+) -> Result<(), String> {
+    let response = client
+        .post(delivery.url.clone())
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .header(WEBHOOK_SIGNATURE_HEADER, signature)
+        .header(WEBHOOK_EVENT_HEADER, delivery.event.event.as_str())
+        .header(WEBHOOK_DELIVERY_HEADER, delivery.event.id.to_string())
+        .body(body.to_vec())
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if response.status().is_success() {
+        Ok(())
+    } else {
+        Err(format!("unexpected response status {}", response.status()))
+    }
+}
+
+#[instrument(level = "debug", skip_all, fields(webhook = ?delivery.webhook, event = ?delivery.event.id))]
+async fn deliver(
+    server: &'static QueryServerWriteV1,
+    client: reqwest::Client,
+    delivery: WebhookDelivery,
+) {
+    let prepared = serde_json::to_vec(&delivery.event)
+        .map_err(|e| e.to_string())
+        .and_then(|body| {
+            sign(&delivery.secret, &body)
+                .map(|signature| (body, signature))
+                .map_err(|e| e.to_string())
+        });
+
+    let (body, signature) = match prepared {
+        Ok(p) => p,
+        Err(e) => {
+            error!(?e, "Unable to prepare webhook event");
+            server.handle_webhook_dead_letter(&delivery, 0, e).await;
+            return;
+        }
+    };
+
+    let mut delay = WEBHOOK_RETRY_DELAY;
+    let mut attempt = 1;
+    loop {
+        match post(&client, &delivery, &body, &signature).await {
+            Ok(()) => {
+                debug!(attempt, "Delivered webhook event");
+                return;
+            }
+            Err(e) if attempt >= WEBHOOK_ATTEMPTS => {
+                error!(?e, attempt, "Unable to deliver webhook event, giving up");
+                server
+                    .handle_webhook_dead_letter(&delivery, attempt, e)
+                    .await;
+                return;
+            }
+            Err(e) => {
+                warn!(?e, attempt, "Unable to deliver webhook event, will retry");
+                sleep(delay).await;
+                delay *= 2;
+                attempt += 1;
+            }
+        }
+    }
+}
@@ -19,6 +19,7 @@ use std::str::FromStr;
 // This works on both unix and windows.
 use fs2::FileExt;
 use kanidm_proto::messages::ConsoleOutputMode;
+use kanidm_proto::v1::OperationError;
 #[cfg(target_family = "unix")]
 use std::os::unix::fs::MetadataExt;
 use std::path::PathBuf;
@@ -34,8 +35,9 @@ use kanidmd_core::{
     backup_server_core, cert_generate_core, create_server_core, dbscan_get_id2entry_core,
     dbscan_list_id2entry_core, dbscan_list_index_analysis_core, dbscan_list_index_core,
     dbscan_list_indexes_core, domain_rename_core, reindex_server_core, restore_server_core,
-    vacuum_server_core, verify_server_core,
+    vacuum_server_core, verify_backup_core, verify_server_core,
 };
+use kanidmd_lib::be::dbbackup::{BackupCompression, BackupKey, BackupOptions};
 use sketching::tracing_forest::traits::*;
 use sketching::tracing_forest::util::*;
 use sketching::tracing_forest::{self};
@@ -67,6 +69,9 @@ impl KanidmdOpt {
             KanidmdOpt::Database {
                 commands: DbCommands::Restore(ropt),
             } => &ropt.commonopts,
+            KanidmdOpt::Database {
+                commands: DbCommands::VerifyBackup(vopt),
+            } => &vopt.commonopts,
             KanidmdOpt::RecoverAccount { commonopts, .. } => commonopts,
             KanidmdOpt::DbScan {
                 commands: DbScanOpt::ListIndex(dopt),
@@ -93,6 +98,16 @@ impl KanidmdOpt {
     }
 }
 
+impl BackupKeyOpt {
+    fn backup_key(&self) -> Result<Option<BackupKey>, OperationError> {
+        match (&self.key_file, &self.passphrase) {
+            (Some(key_file), _) => BackupKey::from_key_file(key_file).map(Some),
+            (None, Some(passphrase)) => BackupKey::from_passphrase(passphrase).map(Some),
+            (None, None) => Ok(None),
+        }
+    }
+}
+
 /// Get information on the windows username
 #[cfg(target_family = "windows")]
 fn get_user_details_windows() {
@@ -334,7 +349,10 @@ async fn main() -> ExitCode {
 
             match &opt.commands  {
                 // we aren't going to touch the DB so we can carry on
-                KanidmdOpt::HealthCheck(_) => (),
+                KanidmdOpt::HealthCheck(_)
+                | KanidmdOpt::Database {
+                    commands: DbCommands::VerifyBackup(_),
+                } => (),
                 _ => {
                     // Okay - Lets now create our lock and go.
                     let klock_path = format!("{}.klock" ,sconfig.db_path.as_str());
@@ -501,7 +519,30 @@ async fn main() -> ExitCode {
                             return ExitCode::FAILURE
                         }
                     };
-                    backup_server_core(&config, p);
+                    let key = match bopt.keyopts.backup_key() {
+                        Ok(key) => key,
+                        Err(e) => {
+                            error!("Unable to load backup key: {:?}", e);
+                            return ExitCode::FAILURE
+                        }
+                    };
+                    if key.is_none() {
+                        warn!("This backup will not be encrypted! It contains credentials and private keys, so it must be stored securely.");
+                    }
+                    let compression = if bopt.compress {
+                        BackupCompression::Gzip
+                    } else {
+                        BackupCompression::None
+                    };
+                    let parent = match bopt.incremental_from.as_ref().map(|p| p.to_str()) {
+                        Some(Some(p)) => Some(p),
+                        Some(None) => {
+                            error!("Invalid incremental-from path");
+                            return ExitCode::FAILURE
+                        }
+                        None => None,
+                    };
+                    backup_server_core(&config, p, parent, &BackupOptions { compression, key });
                 }
                 KanidmdOpt::Database {
                     commands: DbCommands::Restore(ropt),
@@ -514,7 +555,41 @@ async fn main() -> ExitCode {
                             return ExitCode::FAILURE
                         }
                     };
-                    restore_server_core(&config, p).await;
+                    let key = match ropt.keyopts.backup_key() {
+                        Ok(key) => key,
+                        Err(e) => {
+                            error!("Unable to load backup key: {:?}", e);
+                            return ExitCode::FAILURE
+                        }
+                    };
+                    let incrementals = match ropt.incrementals.iter().map(|p| p.to_str()).collect::<Option<Vec<_>>>() {
+                        Some(i) => i,
+                        None => {
+                            error!("Invalid incremental path");
+                            return ExitCode::FAILURE
+                        }
+                    };
+                    restore_server_core(&config, p, &incrementals, key.as_ref()).await;
+                }
+                KanidmdOpt::Database {
+                    commands: DbCommands::VerifyBackup(vopt),
+                } => {
+                    info!("Running in backup verification mode ...");
+                    let p = match vopt.path.to_str() {
+                        Some(p) => p,
+                        None => {
+                            error!("Invalid backup path");
+                            return ExitCode::FAILURE
+                        }
+                    };
+                    let key = match vopt.keyopts.backup_key() {
+                        Ok(key) => key,
+                        Err(e) => {
+                            error!("Unable to load backup key: {:?}", e);
+                            return ExitCode::FAILURE
+                        }
+                    };
+                    verify_backup_core(p, key.as_ref());
                 }
                 KanidmdOpt::Database {
                     commands: DbCommands::Verify(_vopt),
@@ -8,11 +8,29 @@ struct CommonOpt {
     output_mode: String,
 }
 
+#[derive(Debug, Args)]
+struct BackupKeyOpt {
+    /// Path to a file containing the key material used to encrypt or decrypt the backup.
+    #[clap(long = "key-file", conflicts_with = "passphrase")]
+    key_file: Option<PathBuf>,
+    /// Passphrase used to encrypt or decrypt the backup.
+    #[clap(long, env = "KANIDM_BACKUP_PASSPHRASE", hide_env_values = true)]
+    passphrase: Option<String>,
+}
+
 #[derive(Debug, Args)]
 struct BackupOpt {
     #[clap(value_parser)]
     /// Output path for the backup content.
     path: PathBuf,
+    /// Compress the backup content.
+    #[clap(long)]
+    compress: bool,
+    /// Take an incremental backup, containing only the changes made since this backup.
+    #[clap(long = "incremental-from")]
+    incremental_from: Option<PathBuf>,
+    #[clap(flatten)]
+    keyopts: BackupKeyOpt,
     #[clap(flatten)]
     commonopts: CommonOpt,
 }
@@ -22,6 +40,23 @@ struct RestoreOpt {
     #[clap(value_parser)]
     /// Restore from this path. Should be created with "backup".
     path: PathBuf,
+    /// Incremental backups to apply after the restore. May be repeated, and must be given
+    /// in the order they were taken.
+    #[clap(long = "incremental")]
+    incrementals: Vec<PathBuf>,
+    #[clap(flatten)]
+    keyopts: BackupKeyOpt,
+    #[clap(flatten)]
+    commonopts: CommonOpt,
+}
+
+#[derive(Debug, Args)]
+struct VerifyBackupOpt {
+    #[clap(value_parser)]
+    /// The backup file to verify. Should be created with "backup".
+    path: PathBuf,
+    #[clap(flatten)]
+    keyopts: BackupKeyOpt,
     #[clap(flatten)]
     commonopts: CommonOpt,
 }
@@ -44,6 +79,9 @@ enum DbCommands {
     #[clap(name = "restore")]
     /// Restore the database content (offline)
     Restore(RestoreOpt),
+    #[clap(name = "verify-backup")]
+    /// Verify the integrity of a backup file without restoring it
+    VerifyBackup(VerifyBackupOpt),
     #[clap(name = "verify")]
     /// Verify database and entity consistency.
     Verify(CommonOpt),
@@ -0,0 +1,582 @@
+//! Backup archives. A backup is the serialised [`DbBackup`] content of the database, wrapped
+//! in an archive that carries a manifest describing the backup. The payload of the archive may
+//! optionally be compressed and/or encrypted. Because backups contain password hashes, private
+//! keys and session data, encryption is strongly encouraged for any backup that leaves the host.
+//!
+//! The manifest always contains a checksum of the stored payload, so that a backup can be
+//! checked for corruption even when the key to decrypt it is not available.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{Read, Write};
+use std::path::Path;
+
+use base64urlsafedata::Base64UrlSafeData;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use openssl::hash::MessageDigest;
+use openssl::pkcs5::pbkdf2_hmac;
+use openssl::rand::rand_bytes;
+use openssl::sha::sha256;
+use openssl::symm::{decrypt_aead, encrypt_aead, Cipher};
+use serde::{Deserialize, Serialize};
+
+use crate::be::dbentry::{DbBackup, DbEntry};
+use crate::prelude::*;
+use crate::repl::proto::ReplCidRange;
+
+/// The current version of the archive format.
+const BACKUP_ARCHIVE_FORMAT: u32 = 1;
+
+const BACKUP_KDF_ITERATIONS: usize = 600_000;
+// The iterations are read from the manifest, which isn't covered by the checksum, so bound
+// them to stop a crafted archive from stalling a restore.
+const BACKUP_KDF_MIN_ITERATIONS: usize = 100_000;
+const BACKUP_KDF_MAX_ITERATIONS: usize = BACKUP_KDF_ITERATIONS * 10;
+const BACKUP_SALT_LEN: usize = 32;
+const BACKUP_KEY_LEN: usize = 32;
+const BACKUP_NONCE_LEN: usize = 12;
+const BACKUP_TAG_LEN: usize = 16;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum BackupCompression {
+    #[default]
+    None,
+    Gzip,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum BackupKeySource {
+    Passphrase,
+    KeyFile,
+}
+
+/// The secret material used to derive the encryption key of a backup.
+pub struct BackupKey {
+    source: BackupKeySource,
+    secret: Vec<u8>,
+}
+
+impl std::fmt::Debug for BackupKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BackupKey")
+            .field("source", &self.source)
+            .finish_non_exhaustive()
+    }
+}
+
+impl BackupKey {
+    pub fn from_passphrase(passphrase: &str) -> Result<Self, OperationError> {
+        if passphrase.is_empty() {
+            admin_error!("backup passphrase must not be empty");
+            return Err(OperationError::InvalidState);
+        }
+        Ok(BackupKey {
+            source: BackupKeySource::Passphrase,
+            secret: passphrase.as_bytes().to_vec(),
+        })
+    }
+
+    pub fn from_key_file<P: AsRef<Path>>(path: P) -> Result<Self, OperationError> {
+        let mut secret = fs::read(path.as_ref()).map_err(|e| {
+            admin_error!(?e, path = ?path.as_ref(), "unable to read backup key file");
+            OperationError::FsError
+        })?;
+
+        // Tolerate a trailing newline from editors or `openssl rand -base64 > file`.
+        while secret.last().map(u8::is_ascii_whitespace).unwrap_or(false) {
+            secret.pop();
+        }
+
+        if secret.len() < BACKUP_KEY_LEN {
+            admin_error!(
+                "backup key file must contain at least {} bytes of key material",
+                BACKUP_KEY_LEN
+            );
+            return Err(OperationError::InvalidState);
+        }
+
+        Ok(BackupKey {
+            source: BackupKeySource::KeyFile,
+            secret,
+        })
+    }
+
+    fn derive(&self, salt: &[u8], iterations: usize) -> Result<Vec<u8>, OperationError> {
+        let mut key = vec![0; BACKUP_KEY_LEN];
+        pbkdf2_hmac(
+            &self.secret,
+            salt,
+            iterations,
+            MessageDigest::sha256(),
+            &mut key,
+        )
+        .map_err(|e| {
+            admin_error!(?e, "unable to derive backup key");
+            OperationError::CryptographyError
+        })?;
+        Ok(key)
+    }
+}
+
+/// Options controlling how a backup archive is written.
+#[derive(Debug, Default)]
+pub struct BackupOptions {
+    pub compression: BackupCompression,
+    pub key: Option<BackupKey>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "lowercase")]
+pub enum BackupKdf {
+    Pbkdf2Sha256 {
+        iterations: usize,
+        salt: Base64UrlSafeData,
+    },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "lowercase")]
+pub enum BackupCipher {
+    Aes256Gcm {
+        nonce: Base64UrlSafeData,
+        tag: Base64UrlSafeData,
+    },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BackupEncryption {
+    pub key_source: BackupKeySource,
+    pub kdf: BackupKdf,
+    pub cipher: BackupCipher,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum BackupKind {
+    /// The backup contains every entry of the database.
+    #[default]
+    Full,
+    /// The backup only contains entries changed since the backup with the checksum
+    /// `parent` was taken. It must be restored on top of its parent.
+    Incremental { parent: String },
+}
+
+/// Describes the content of a backup archive.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DbBackupManifest {
+    pub format: u32,
+    pub server_version: String,
+    pub created: Duration,
+    #[serde(default)]
+    pub kind: BackupKind,
+    pub db_s_uuid: Uuid,
+    pub db_d_uuid: Uuid,
+    /// The replication update vector ranges of the database when this backup was taken.
+    #[serde(default)]
+    pub ruv: BTreeMap<Uuid, ReplCidRange>,
+    pub entry_count: usize,
+    pub compression: BackupCompression,
+    pub encryption: Option<BackupEncryption>,
+    /// Hex encoded sha256 of the payload, as it is stored in the archive.
+    pub checksum: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct DbBackupArchive {
+    pub manifest: DbBackupManifest,
+    pub payload: Base64UrlSafeData,
+}
+
+impl DbBackupArchive {
+    pub fn seal(
+        bak: &DbBackup,
+        created: Duration,
+        kind: BackupKind,
+        ruv: BTreeMap<Uuid, ReplCidRange>,
+        opts: &BackupOptions,
+    ) -> Result<Self, OperationError> {
+        let (db_s_uuid, db_d_uuid, entry_count) = match bak {
+            DbBackup::V2 {
+                db_s_uuid,
+                db_d_uuid,
+                entries,
+                ..
+            } => (*db_s_uuid, *db_d_uuid, entries.len()),
+            DbBackup::V1(_) => {
+                admin_error!("refusing to archive a v1 backup");
+                return Err(OperationError::InvalidState);
+            }
+        };
+
+        let mut payload = serde_json::to_vec(bak).map_err(|e| {
+            admin_error!(?e, "serde error");
+            OperationError::SerdeJsonError
+        })?;
+
+        if opts.compression == BackupCompression::Gzip {
+            let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+            payload = encoder
+                .write_all(&payload)
+                .and_then(|_| encoder.finish())
+                .map_err(|e| {
+                    admin_error!(?e, "unable to compress backup");
+                    OperationError::FsError
+                })?;
+        }
+
+        let encryption = match &opts.key {
+            Some(key) => {
+                let mut salt = vec![0; BACKUP_SALT_LEN];
+                let mut nonce = vec![0; BACKUP_NONCE_LEN];
+                let mut tag = vec![0; BACKUP_TAG_LEN];
+                rand_bytes(&mut salt)
+                    .and_then(|_| rand_bytes(&mut nonce))
+                    .map_err(|e| {
+                        admin_error!(?e, "unable to generate backup salt or nonce");
+                        OperationError::CryptographyError
+                    })?;
+
+                let dk = key.derive(&salt, BACKUP_KDF_ITERATIONS)?;
+
+                // The domain uuid is bound to the ciphertext so that the manifest can't be
+                // swapped to claim the backup belongs to another domain.
+                payload = encrypt_aead(
+                    Cipher::aes_256_gcm(),
+                    &dk,
+                    Some(&nonce),
+                    db_d_uuid.as_bytes(),
+                    &payload,
+                    &mut tag,
+                )
+                .map_err(|e| {
+                    admin_error!(?e, "unable to encrypt backup");
+                    OperationError::CryptographyError
+                })?;
+
+                Some(BackupEncryption {
+                    key_source: key.source,
+                    kdf: BackupKdf::Pbkdf2Sha256 {
+                        iterations: BACKUP_KDF_ITERATIONS,
+                        salt: salt.into(),
+                    },
+                    cipher: BackupCipher::Aes256Gcm {
+                        nonce: nonce.into(),
+                        tag: tag.into(),
+                    },
+                })
+            }
+            None => None,
+        };
+
+        let checksum = hex::encode(sha256(&payload));
+
+        Ok(DbBackupArchive {
+            manifest: DbBackupManifest {
+                format: BACKUP_ARCHIVE_FORMAT,
+                server_version: env!("CARGO_PKG_VERSION").to_string(),
+                created,
+                kind,
+                db_s_uuid,
+                db_d_uuid,
+                ruv,
+                entry_count,
+                compression: opts.compression,
+                encryption,
+                checksum,
+            },
+            payload: payload.into(),
+        })
+    }
+
+    pub fn is_encrypted(&self) -> bool {
+        self.manifest.encryption.is_some()
+    }
+
+    /// Check the stored payload against the manifest checksum. This does not require the
+    /// backup key.
+    pub fn verify_checksum(&self) -> Result<(), OperationError> {
+        if self.manifest.format != BACKUP_ARCHIVE_FORMAT {
+            admin_error!("unsupported backup archive format {}", self.manifest.format);
+            return Err(OperationError::InvalidState);
+        }
+
+        let checksum = hex::encode(sha256(&self.payload.0));
+        if checksum != self.manifest.checksum {
+            admin_error!(
+                expected = %self.manifest.checksum,
+                got = %checksum,
+                "backup checksum mismatch"
+            );
+            return Err(OperationError::BackupChecksumMismatch);
+        }
+        Ok(())
+    }
+
+    /// Verify, decrypt and decompress the archive, returning the backup content.
+    pub fn open(&self, key: Option<&BackupKey>) -> Result<DbBackup, OperationError> {
+        self.verify_checksum()?;
+
+        let mut payload = match &self.manifest.encryption {
+            Some(BackupEncryption {
+                kdf: BackupKdf::Pbkdf2Sha256 { iterations, salt },
+                cipher: BackupCipher::Aes256Gcm { nonce, tag },
+                ..
+            }) => {
+                if !(BACKUP_KDF_MIN_ITERATIONS..=BACKUP_KDF_MAX_ITERATIONS).contains(iterations) {
+                    admin_error!(
+                        %iterations,
+                        "backup kdf iterations must be between {} and {}",
+                        BACKUP_KDF_MIN_ITERATIONS,
+                        BACKUP_KDF_MAX_ITERATIONS
+                    );
+                    return Err(OperationError::InvalidState);
+                }
+                let key = key.ok_or_else(|| {
+                    admin_error!("backup is encrypted, but no key was provided");
+                    OperationError::BackupKeyRequired
+                })?;
+                let dk = key.derive(&salt.0, *iterations)?;
+                decrypt_aead(
+                    Cipher::aes_256_gcm(),
+                    &dk,
+                    Some(&nonce.0),
+                    self.manifest.db_d_uuid.as_bytes(),
+                    &self.payload.0,
+                    &tag.0,
+                )
+                .map_err(|e| {
+                    admin_error!(?e, "unable to decrypt backup - is the key correct?");
+                    OperationError::CryptographyError
+                })?
+            }
+            None => self.payload.0.clone(),
+        };
+
+        if self.manifest.compression == BackupCompression::Gzip {
+            let mut decoded = Vec::new();
+            GzDecoder::new(payload.as_slice())
+                .read_to_end(&mut decoded)
+                .map_err(|e| {
+                    admin_error!(?e, "unable to decompress backup");
+                    OperationError::FsError
+                })?;
+            payload = decoded;
+        }
+
+        let bak: DbBackup = serde_json::from_slice(&payload).map_err(|e| {
+            admin_error!(?e, "serde error");
+            OperationError::SerdeJsonError
+        })?;
+
+        match &bak {
+            DbBackup::V2 {
+                db_s_uuid,
+                db_d_uuid,
+                entries,
+                ..
+            } if *db_s_uuid == self.manifest.db_s_uuid
+                && *db_d_uuid == self.manifest.db_d_uuid
+                && entries.len() == self.manifest.entry_count =>
+            {
+                Ok(bak)
+            }
+            _ => {
+                admin_error!("backup content does not match the manifest");
+                Err(OperationError::InvalidDbState)
+            }
+        }
+    }
+}
+
+/// The content of a backup file on disk. Backups created before archives were introduced
+/// are plain serialised [`DbBackup`] values, and are still accepted for restore.
+pub enum DbBackupFile {
+    Archive(DbBackupArchive),
+    Legacy(DbBackup),
+}
+
+impl DbBackupFile {
+    pub fn read<P: AsRef<Path>>(path: P) -> Result<Self, OperationError> {
+        let content = fs::read(path.as_ref()).map_err(|e| {
+            admin_error!(?e, path = ?path.as_ref(), "fs::read error");
+            OperationError::FsError
+        })?;
+
+        if let Ok(archive) = serde_json::from_slice::<DbBackupArchive>(&content) {
+            return Ok(DbBackupFile::Archive(archive));
+        }
+
+        serde_json::from_slice::<DbBackup>(&content)
+            .map(DbBackupFile::Legacy)
+            .map_err(|e| {
+                admin_error!(?e, "serde error - this is not a kanidm backup file");
+                OperationError::SerdeJsonError
+            })
+    }
+
+    pub fn into_backup(self, key: Option<&BackupKey>) -> Result<DbBackup, OperationError> {
+        match self {
+            DbBackupFile::Archive(archive) => archive.open(key),
+            DbBackupFile::Legacy(bak) => Ok(bak),
+        }
+    }
+}
+
+/// Read the manifest of a backup archive, checking that the payload matches its checksum.
+pub fn read_backup_manifest<P: AsRef<Path>>(path: P) -> Result<DbBackupManifest, OperationError> {
+    match DbBackupFile::read(path)? {
+        DbBackupFile::Archive(archive) => {
+            archive.verify_checksum()?;
+            Ok(archive.manifest)
+        }
+        DbBackupFile::Legacy(_) => {
+            admin_error!("this is a legacy backup without a manifest");
+            Err(OperationError::BackupChainInvalid)
+        }
+    }
+}
+
+/// Read a full backup and apply a chain of incremental backups on top of it, returning the
+/// combined content. Each incremental must name the previous backup of the chain as its parent.
+pub fn read_backup_chain<P: AsRef<Path>>(
+    src_path: P,
+    incrementals: &[P],
+    key: Option<&BackupKey>,
+) -> Result<DbBackup, OperationError> {
+    let (mut parent, dbbak) = match DbBackupFile::read(src_path)? {
+        DbBackupFile::Archive(archive) => {
+            if archive.manifest.kind != BackupKind::Full {
+                admin_error!("the first backup of a chain must be a full backup");
+                return Err(OperationError::BackupChainInvalid);
+            }
+            (Some(archive.manifest.checksum.clone()), archive.open(key)?)
+        }
+        DbBackupFile::Legacy(dbbak) => (None, dbbak),
+    };
+
+    if incrementals.is_empty() {
+        return Ok(dbbak);
+    }
+
+    let (mut db_s_uuid, db_d_uuid, mut db_ts_max, entries) = match dbbak {
+        DbBackup::V2 {
+            db_s_uuid,
+            db_d_uuid,
+            db_ts_max,
+            entries,
+        } => (db_s_uuid, db_d_uuid, db_ts_max, entries),
+        DbBackup::V1(_) => {
+            admin_error!("incremental backups can not be applied to a v1 backup");
+            return Err(OperationError::BackupChainInvalid);
+        }
+    };
+
+    // Keep the order of the full backup, and index by uuid so that changed entries
+    // replace their previous version.
+    let mut entries: Vec<DbEntry> = entries;
+    let mut uuid_index = entries
+        .iter()
+        .enumerate()
+        .map(|(i, dbe)| dbe.get_uuid().map(|u| (u, i)))
+        .collect::<Option<BTreeMap<_, _>>>()
+        .ok_or_else(|| {
+            admin_error!("backup contains an entry without a uuid");
+            OperationError::InvalidDbState
+        })?;
+
+    for incr_path in incrementals {
+        let archive = match DbBackupFile::read(incr_path.as_ref())? {
+            DbBackupFile::Archive(archive) => archive,
+            DbBackupFile::Legacy(_) => {
+                admin_error!(path = ?incr_path.as_ref(), "not an incremental backup");
+                return Err(OperationError::BackupChainInvalid);
+            }
+        };
+
+        match (&archive.manifest.kind, &parent) {
+            (BackupKind::Incremental { parent: expect }, Some(parent)) if expect == parent => {}
+            _ => {
+                admin_error!(path = ?incr_path.as_ref(), "incremental backup does not follow the previous backup of the chain");
+                return Err(OperationError::BackupChainInvalid);
+            }
+        }
+
+        if archive.manifest.db_d_uuid != db_d_uuid {
+            admin_error!(path = ?incr_path.as_ref(), "incremental backup is from a different domain");
+            return Err(OperationError::BackupChainInvalid);
+        }
+
+        let DbBackup::V2 {
+            db_s_uuid: incr_s_uuid,
+            db_ts_max: incr_ts_max,
+            entries: incr_entries,
+            ..
+        } = archive.open(key)?
+        else {
+            return Err(OperationError::InvalidDbState);
+        };
+
+        info!(
+            path = ?incr_path.as_ref(),
+            entries = incr_entries.len(),
+            "Applying incremental backup"
+        );
+
+        for dbe in incr_entries {
+            let uuid = dbe.get_uuid().ok_or_else(|| {
+                admin_error!("backup contains an entry without a uuid");
+                OperationError::InvalidDbState
+            })?;
+            match uuid_index.get(&uuid) {
+                Some(i) => entries[*i] = dbe,
+                None => {
+                    uuid_index.insert(uuid, entries.len());
+                    entries.push(dbe);
+                }
+            }
+        }
+
+        db_s_uuid = incr_s_uuid;
+        db_ts_max = incr_ts_max;
+        parent = Some(archive.manifest.checksum);
+    }
+
+    Ok(DbBackup::V2 {
+        db_s_uuid,
+        db_d_uuid,
+        db_ts_max,
+        entries,
+    })
+}
+
+/// Validate a backup file without restoring it. If the backup is encrypted and no key is
+/// provided, only the checksum of the payload can be validated.
+pub fn verify_backup_file<P: AsRef<Path>>(
+    path: P,
+    key: Option<&BackupKey>,
+) -> Result<Option<DbBackupManifest>, OperationError> {
+    match DbBackupFile::read(path)? {
+        DbBackupFile::Archive(archive) => {
+            if archive.is_encrypted() && key.is_none() {
+                admin_warn!("backup is encrypted and no key was provided, only the checksum will be verified");
+                archive.verify_checksum()?;
+            } else {
+                let bak = archive.open(key)?;
+                if let DbBackup::V2 { entries, .. } = bak {
+                    entries
+                        .into_iter()
+                        .try_for_each(|dbe| dbe.convert_to_v2().map(|_| ()))?;
+                }
+            }
+            Ok(Some(archive.manifest))
+        }
+        DbBackupFile::Legacy(_) => {
+            admin_warn!("this is a legacy backup without a manifest, it can not be verified");
+            Ok(None)
+        }
+    }
+}
@@ -390,6 +390,19 @@ fn from_vec_dbval1(attr_val: NonEmpty<DbValueV1>) -> Result<DbValueSetV2, Operat
 }
 
 impl DbEntry {
+    pub(crate) fn get_uuid(&self) -> Option<Uuid> {
+        match &self.ent {
+            DbEntryVers::V1(dbe) => dbe.attrs.get("uuid").and_then(|vs| match vs.first() {
+                DbValueV1::Uuid(u) => Some(*u),
+                _ => None,
+            }),
+            DbEntryVers::V2(dbe) => dbe.attrs.get("uuid").and_then(|vs| match vs {
+                DbValueSetV2::Uuid(us) => us.first().copied(),
+                _ => None,
+            }),
+        }
+    }
+
     pub(crate) fn convert_to_v2(self) -> Result<Self, OperationError> {
         if let DbEntryVers::V1(dbe) = self.ent {
             dbe.attrs
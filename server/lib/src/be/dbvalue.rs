@@ -364,6 +364,16 @@ pub struct DbValueOauthScopeMapV1 {
     pub data: Vec<String>,
 }
 
+#[derive(Serialize, Deserialize, Debug)]
+pub struct DbValueMemberValidityV1 {
+    #[serde(rename = "u")]
+    pub refer: Uuid,
+    #[serde(rename = "b", default, skip_serializing_if = "Option::is_none")]
+    pub not_before: Option<String>,
+    #[serde(rename = "a", default, skip_serializing_if = "Option::is_none")]
+    pub not_after: Option<String>,
+}
+
 #[derive(Serialize, Deserialize, Debug, Default)]
 pub enum DbValueAccessScopeV1 {
     #[serde(rename = "i")]
@@ -611,6 +621,10 @@ pub enum DbValueSetV2 {
     ApiToken(Vec<DbValueApiToken>),
     #[serde(rename = "SA")]
     AuditLogString(Vec<(Cid, String)>),
+    #[serde(rename = "PH")]
+    PasswordHistory(Vec<(Cid, DbPasswordV1)>),
+    #[serde(rename = "MV")]
+    MemberValidity(Vec<DbValueMemberValidityV1>),
 }
 
 impl DbValueSetV2 {
@@ -654,6 +668,8 @@ impl DbValueSetV2 {
             DbValueSetV2::UiHint(set) => set.len(),
             DbValueSetV2::TotpSecret(set) => set.len(),
             DbValueSetV2::AuditLogString(set) => set.len(),
+            DbValueSetV2::PasswordHistory(set) => set.len(),
+            DbValueSetV2::MemberValidity(set) => set.len(),
         }
     }
 
@@ -19,6 +19,9 @@ use smartstring::alias::String as AttrString;
 use tracing::{trace, trace_span};
 use uuid::Uuid;
 
+use crate::be::dbbackup::{
+    read_backup_chain, BackupKey, BackupKind, BackupOptions, DbBackupArchive, DbBackupManifest,
+};
 use crate::be::dbentry::{DbBackup, DbEntry};
 use crate::entry::Entry;
 use crate::filter::{Filter, FilterPlan, FilterResolved, FilterValidResolved};
@@ -26,11 +29,12 @@ use crate::prelude::*;
 use crate::repl::cid::Cid;
 use crate::repl::proto::ReplCidRange;
 use crate::repl::ruv::{
-    ReplicationUpdateVector, ReplicationUpdateVectorReadTransaction,
+    RangeDiffStatus, ReplicationUpdateVector, ReplicationUpdateVectorReadTransaction,
     ReplicationUpdateVectorTransaction, ReplicationUpdateVectorWriteTransaction,
 };
 use crate::value::{IndexType, Value};
 
+pub mod dbbackup;
 pub mod dbentry;
 pub mod dbvalue;
 mod idl_arc_sqlite;
@@ -846,10 +850,83 @@ pub trait BackendTransaction {
         self.get_ruv().verify(&entries, results);
     }
 
-    fn backup(&mut self, dst_path: &str) -> Result<(), OperationError> {
+    fn backup(
+        &mut self,
+        dst_path: &str,
+        opts: &BackupOptions,
+    ) -> Result<DbBackupManifest, OperationError> {
+        let ruv = self.get_ruv().current_ruv_range()?;
+        self.write_backup(dst_path, IdList::AllIds, BackupKind::Full, ruv, opts)
+    }
+
+    /// Write a backup containing only the entries that have changed since the backup
+    /// described by `parent` was taken. This is determined by comparing the parent's
+    /// RUV to our current RUV, in the same way as a replication consumer would.
+    fn backup_incremental(
+        &mut self,
+        dst_path: &str,
+        parent: &DbBackupManifest,
+        opts: &BackupOptions,
+    ) -> Result<DbBackupManifest, OperationError> {
+        let db_d_uuid = self
+            .get_idlayer()
+            .get_db_d_uuid()
+            .and_then(|u| u.ok_or(OperationError::InvalidDbState))?;
+
+        if parent.db_d_uuid != db_d_uuid {
+            admin_error!("parent backup is from a different domain");
+            return Err(OperationError::BackupChainInvalid);
+        }
+
+        // Without the parent's RUV we can't know what it contains, and an empty
+        // consumer range would only select changes still in our (trimmed) changelog.
+        if parent.ruv.is_empty() {
+            admin_warn!(
+                "parent backup has no replication update vector, a full backup is required"
+            );
+            return Err(OperationError::BackupIncrementalUnavailable);
+        }
+
+        let ruv = self.get_ruv().current_ruv_range()?;
+
+        let ranges = match ReplicationUpdateVector::range_diff(&parent.ruv, &ruv) {
+            RangeDiffStatus::Ok(ranges) => ranges,
+            RangeDiffStatus::Refresh { lag_range } => {
+                admin_warn!(
+                    ?lag_range,
+                    "changelog has been trimmed beyond the parent backup, a full backup is required"
+                );
+                return Err(OperationError::BackupIncrementalUnavailable);
+            }
+            RangeDiffStatus::Unwilling { adv_range }
+            | RangeDiffStatus::Critical { adv_range, .. } => {
+                admin_error!(
+                    ?adv_range,
+                    "parent backup is advanced beyond our current state, a full backup is required"
+                );
+                return Err(OperationError::BackupIncrementalUnavailable);
+            }
+        };
+
+        debug!(?ranges, "these ranges will be backed up");
+
+        let idl = IdList::Indexed(self.get_ruv().range_to_idl(&ranges));
+        let kind = BackupKind::Incremental {
+            parent: parent.checksum.clone(),
+        };
+        self.write_backup(dst_path, idl, kind, ruv, opts)
+    }
+
+    fn write_backup(
+        &mut self,
+        dst_path: &str,
+        idl: IdList,
+        kind: BackupKind,
+        ruv: BTreeMap<Uuid, ReplCidRange>,
+        opts: &BackupOptions,
+    ) -> Result<DbBackupManifest, OperationError> {
         // load all entries into RAM, may need to change this later
         // if the size of the database compared to RAM is an issue
-        let idl = IdList::AllIds;
         let idlayer = self.get_idlayer();
         let raw_entries: Vec<IdRawEntry> = idlayer.get_identry_raw(&idl)?;
 
@@ -880,13 +957,15 @@ pub trait BackendTransaction {
             entries,
         };
 
-        let serialized_entries_str = serde_json::to_string(&bak).map_err(|e| {
+        let archive = DbBackupArchive::seal(&bak, duration_from_epoch_now(), kind, ruv, opts)?;
+
+        let serialized_archive_str = serde_json::to_string(&archive).map_err(|e| {
             admin_error!(?e, "serde error");
             OperationError::SerdeJsonError
         })?;
 
-        fs::write(dst_path, serialized_entries_str)
-            .map(|_| ())
+        fs::write(dst_path, serialized_archive_str)
+            .map(|_| archive.manifest)
             .map_err(|e| {
                 admin_error!(?e, "fs::write error");
                 OperationError::FsError
@@ -1669,28 +1748,27 @@ impl<'a> BackendWriteTransaction<'a> {
         Ok(slope)
     }
 
-    pub fn restore(&mut self, src_path: &str) -> Result<(), OperationError> {
+    /// Restore from the full backup at `src_path`, and then apply the chain of
+    /// incremental backups in order.
+    pub fn restore(
+        &mut self,
+        src_path: &str,
+        incrementals: &[&str],
+        key: Option<&BackupKey>,
+    ) -> Result<(), OperationError> {
         let idlayer = self.get_idlayer();
         // load all entries into RAM, may need to change this later
-        // if the size of the database compared to RAM is an issue
-        let serialized_string = fs::read_to_string(src_path).map_err(|e| {
-            admin_error!("fs::read_to_string {:?}", e);
-            OperationError::FsError
-        })?;
+        // if the size of the database compared to RAM is an issue.
+        //
+        // The archives are fully verified and decoded before we purge anything, so that a
+        // bad key, a broken chain or a corrupted file leaves the current database untouched.
+        let dbbak = read_backup_chain(src_path, incrementals, key)?;
 
         idlayer.danger_purge_id2entry().map_err(|e| {
             admin_error!("purge_id2entry failed {:?}", e);
             e
         })?;
 
-        let dbbak_option: Result<DbBackup, serde_json::Error> =
-            serde_json::from_str(&serialized_string);
-
-        let dbbak = dbbak_option.map_err(|e| {
-            admin_error!("serde_json error {:?}", e);
-            OperationError::SerdeJsonError
-        })?;
-
         let dbentries = match dbbak {
             DbBackup::V1(dbentries) => dbentries,
             DbBackup::V2 {
@@ -1987,6 +2065,10 @@ mod tests {
     use idlset::v2::IDLBitRange;
 
     use super::super::entry::{Entry, EntryInit, EntryNew};
+    use super::dbbackup::{
+        verify_backup_file, BackupCompression, BackupEncryption, BackupKdf, BackupKey, BackupKind,
+        BackupOptions, DbBackupArchive, DbBackupFile,
+    };
     use super::Limits;
     use super::{
         Backend, BackendConfig, BackendTransaction, BackendWriteTransaction, DbBackup, IdList,
@@ -2354,8 +2436,10 @@ mod tests {
                 _ => (),
             }
 
-            be.backup(&db_backup_file_name).expect("Backup failed!");
-            be.restore(&db_backup_file_name).expect("Restore failed!");
+            be.backup(&db_backup_file_name, &BackupOptions::default())
+                .expect("Backup failed!");
+            be.restore(&db_backup_file_name, &[], None)
+                .expect("Restore failed!");
 
             assert!(be.verify().is_empty());
         });
@@ -2409,11 +2493,13 @@ mod tests {
                 _ => (),
             }
 
-            be.backup(&db_backup_file_name).expect("Backup failed!");
+            be.backup(&db_backup_file_name, &BackupOptions::default())
+                .expect("Backup failed!");
 
             // Now here, we need to tamper with the file.
-            let serialized_string = fs::read_to_string(&db_backup_file_name).unwrap();
-            let mut dbbak: DbBackup = serde_json::from_str(&serialized_string).unwrap();
+            let mut dbbak = DbBackupFile::read(&db_backup_file_name)
+                .and_then(|f| f.into_backup(None))
+                .unwrap();
 
             match &mut dbbak {
                 DbBackup::V1(_) => {
@@ -2433,12 +2519,220 @@ mod tests {
             let serialized_entries_str = serde_json::to_string_pretty(&dbbak).unwrap();
             fs::write(&db_backup_file_name, serialized_entries_str).unwrap();
 
-            be.restore(&db_backup_file_name).expect("Restore failed!");
+            be.restore(&db_backup_file_name, &[], None)
+                .expect("Restore failed!");
 
             assert!(be.verify().is_empty());
         });
     }
 
+    #[test]
+    fn test_be_backup_restore_encrypted() {
+        let _ = sketching::test_init();
+        let db_backup_file_name = format!(
+            "{}/.backup3_test.json",
+            option_env!("OUT_DIR").unwrap_or("/tmp")
+        );
+        eprintln!(" ⚠️   {db_backup_file_name}");
+        let _ = fs::remove_file(&db_backup_file_name);
+
+        let be = Backend::new(BackendConfig::new_test("main"), Vec::new(), false)
+            .expect("Failed to setup backend");
+
+        let mut e1: Entry<EntryInit, EntryNew> = Entry::new();
+        e1.add_ava("userid", Value::from("william"));
+        e1.add_ava("uuid", Value::from("db237e8a-0079-4b8c-8a56-593b22aa44d1"));
+
+        // The backup only contains committed content, so commit our entry first.
+        let db_d_uuid = {
+            let mut be_txn = be.write();
+            be_txn.reset_db_s_uuid().unwrap();
+            let db_d_uuid = be_txn.reset_db_d_uuid().unwrap();
+            be_txn.set_db_ts_max(Duration::from_secs(1)).unwrap();
+            let ve1 = e1.clone().into_sealed_new();
+            assert!(be_txn.create(&CID_ZERO, vec![ve1]).is_ok());
+            assert!(be_txn.commit().is_ok());
+            db_d_uuid
+        };
+
+        let opts = BackupOptions {
+            compression: BackupCompression::Gzip,
+            key: Some(BackupKey::from_passphrase("correct horse battery staple").unwrap()),
+        };
+        be.read()
+            .backup(&db_backup_file_name, &opts)
+            .expect("Backup failed!");
+
+        // The credentials must not be readable from the archive.
+        let content = fs::read_to_string(&db_backup_file_name).unwrap();
+        assert!(!content.contains("william"));
+
+        // Without a key we can only check the checksum.
+        let manifest = verify_backup_file(&db_backup_file_name, None)
+            .expect("Verify failed!")
+            .expect("No manifest!");
+        assert_eq!(manifest.entry_count, 1);
+        assert_eq!(manifest.db_d_uuid, db_d_uuid);
+
+        let bad_key = BackupKey::from_passphrase("incorrect").unwrap();
+        assert_eq!(
+            verify_backup_file(&db_backup_file_name, Some(&bad_key)).err(),
+            Some(OperationError::CryptographyError)
+        );
+        assert!(verify_backup_file(&db_backup_file_name, opts.key.as_ref()).is_ok());
+
+        let mut be_txn = be.write();
+        assert_eq!(
+            be_txn.restore(&db_backup_file_name, &[], None),
+            Err(OperationError::BackupKeyRequired)
+        );
+        // A failed restore must not have removed our content.
+        assert!(entry_exists!(be_txn, e1));
+
+        be_txn
+            .restore(&db_backup_file_name, &[], opts.key.as_ref())
+            .expect("Restore failed!");
+        assert!(entry_exists!(be_txn, e1));
+        assert!(be_txn.verify().is_empty());
+        assert!(be_txn.commit().is_ok());
+
+        // The kdf parameters aren't covered by the checksum, so they must be bounded.
+        let mut archive: DbBackupArchive = serde_json::from_str(&content).unwrap();
+        if let Some(BackupEncryption {
+            kdf: BackupKdf::Pbkdf2Sha256 { iterations, .. },
+            ..
+        }) = archive.manifest.encryption.as_mut()
+        {
+            *iterations = usize::MAX;
+        }
+        fs::write(
+            &db_backup_file_name,
+            serde_json::to_string(&archive).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            verify_backup_file(&db_backup_file_name, opts.key.as_ref()).err(),
+            Some(OperationError::InvalidState)
+        );
+
+        // Corrupt the payload, the checksum must catch it.
+        let mut archive: DbBackupArchive = serde_json::from_str(&content).unwrap();
+        archive.payload.0[0] ^= 0xff;
+        fs::write(
+            &db_backup_file_name,
+            serde_json::to_string(&archive).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            verify_backup_file(&db_backup_file_name, None).err(),
+            Some(OperationError::BackupChecksumMismatch)
+        );
+    }
+
+    #[test]
+    fn test_be_backup_restore_incremental() {
+        let _ = sketching::test_init();
+        let out_dir = option_env!("OUT_DIR").unwrap_or("/tmp");
+        let full_file_name = format!("{out_dir}/.backup4_test.json");
+        let incr_file_name = format!("{out_dir}/.backup4_test.incr.json");
+        eprintln!(" ⚠️   {full_file_name}");
+        let _ = fs::remove_file(&full_file_name);
+        let _ = fs::remove_file(&incr_file_name);
+
+        let be = Backend::new(BackendConfig::new_test("main"), Vec::new(), false)
+            .expect("Failed to setup backend");
+
+        let mut e1: Entry<EntryInit, EntryNew> = Entry::new();
+        e1.add_ava("userid", Value::from("william"));
+        e1.add_ava("uuid", Value::from("db237e8a-0079-4b8c-8a56-593b22aa44d1"));
+
+        let mut e2: Entry<EntryInit, EntryNew> = Entry::new();
+        e2.add_ava("userid", Value::from("claire"));
+        e2.add_ava("uuid", Value::from("0c680959-0944-47d6-9dea-53304d124266"));
+
+        {
+            let mut be_txn = be.write();
+            be_txn.reset_db_s_uuid().unwrap();
+            be_txn.reset_db_d_uuid().unwrap();
+            be_txn.set_db_ts_max(Duration::from_secs(1)).unwrap();
+            let ve1 = e1.clone().into_sealed_new();
+            let ve2 = e2.clone().into_sealed_new();
+            assert!(be_txn.create(&CID_ZERO, vec![ve1, ve2]).is_ok());
+            assert!(be_txn.commit().is_ok());
+        }
+
+        let opts = BackupOptions::default();
+        let full = be
+            .read()
+            .backup(&full_file_name, &opts)
+            .expect("Backup failed!");
+        assert_eq!(full.kind, BackupKind::Full);
+        assert_eq!(full.entry_count, 2);
+        assert!(!full.ruv.is_empty());
+
+        // Nothing has changed yet, but we can still take an (empty) incremental.
+        let incr = be
+            .read()
+            .backup_incremental(&incr_file_name, &full, &opts)
+            .expect("Incremental backup failed!");
+        assert_eq!(incr.entry_count, 0);
+
+        // Now change only one entry.
+        let r1_ts = {
+            let mut be_txn = be.write();
+            let r1 = be_txn
+                .search(
+                    &Limits::unlimited(),
+                    &filter_resolved!(f_eq("userid", PartialValue::new_utf8s("william"))),
+                )
+                .expect("Failed to search")
+                .remove(0);
+            let r1_ts = r1.to_tombstone(CID_ONE.clone()).into_sealed_committed();
+            assert!(be_txn.modify(&CID_ONE, &[r1], &[r1_ts.clone()]).is_ok());
+            assert!(be_txn.commit().is_ok());
+            r1_ts
+        };
+
+        let incr = be
+            .read()
+            .backup_incremental(&incr_file_name, &full, &opts)
+            .expect("Incremental backup failed!");
+        assert_eq!(
+            incr.kind,
+            BackupKind::Incremental {
+                parent: full.checksum.clone()
+            }
+        );
+        assert_eq!(incr.entry_count, 1);
+
+        let mut be_txn = be.write();
+        // An incremental can't be restored on its own.
+        assert_eq!(
+            be_txn.restore(&incr_file_name, &[], None),
+            Err(OperationError::BackupChainInvalid)
+        );
+        // Nor out of order.
+        assert_eq!(
+            be_txn.restore(&full_file_name, &[&full_file_name], None),
+            Err(OperationError::BackupChainInvalid)
+        );
+
+        be_txn
+            .restore(&full_file_name, &[], None)
+            .expect("Restore failed!");
+        assert!(entry_attr_pres!(be_txn, e1, "userid"));
+
+        be_txn
+            .restore(&full_file_name, &[&incr_file_name], None)
+            .expect("Restore failed!");
+        // The tombstone from the incremental replaced the live entry.
+        assert!(entry_exists!(be_txn, r1_ts));
+        assert!(!entry_attr_pres!(be_txn, e1, "userid"));
+        assert!(entry_attr_pres!(be_txn, e2, "userid"));
+        assert!(be_txn.verify().is_empty());
+        assert!(be_txn.commit().is_ok());
+    }
+
     #[test]
     fn test_be_sid_generation_and_reset() {
         run_test!(|be: &mut BackendWriteTransaction| {
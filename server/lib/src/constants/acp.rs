@@ -36,11 +36,12 @@ lazy_static! {
         ("class", CLASS_OBJECT.clone()),
         ("class", CLASS_ACCESS_CONTROL_PROFILE.clone()),
         ("class", CLASS_ACCESS_CONTROL_MODIFY.clone()),
+        ("class", CLASS_ACCESS_CONTROL_DELETE.clone()),
         ("name", Value::new_iname("idm_admins_acp_revive")),
         ("uuid", Value::Uuid(UUID_IDM_ADMINS_ACP_REVIVE_V1)),
         (
             "description",
-            Value::new_utf8s("Builtin IDM admin recycle bin revive permission.")
+            Value::new_utf8s("Builtin IDM admin recycle bin revive and purge permission.")
         ),
         ("acp_receiver_group", Value::Refer(UUID_SYSTEM_ADMINS)),
         (
@@ -92,7 +93,12 @@ lazy_static! {
             Value::new_iutf8("user_auth_token_session")
         ),
         ("acp_search_attr", Value::new_iutf8("passkeys")),
-        ("acp_search_attr", Value::new_iutf8("devicekeys"))
+        ("acp_search_attr", Value::new_iutf8("devicekeys")),
+        (
+            "acp_search_attr",
+            Value::new_iutf8("oauth2_consent_scope_map")
+        ),
+        ("acp_search_attr", Value::new_iutf8("oauth2_session"))
     );
 }
 
@@ -163,7 +169,9 @@ lazy_static! {
             )
                 .expect("Invalid JSON filter")
         ),
-        ("acp_modify_removedattr", Value::new_iutf8("user_auth_token_session"))
+        ("acp_modify_removedattr", Value::new_iutf8("user_auth_token_session")),
+        ("acp_modify_removedattr", Value::new_iutf8("oauth2_consent_scope_map")),
+        ("acp_modify_removedattr", Value::new_iutf8("oauth2_session"))
     );
 }
 
@@ -223,10 +231,15 @@ lazy_static! {
         ("acp_search_attr", Value::new_iutf8("class")),
         ("acp_search_attr", Value::new_iutf8("memberof")),
         ("acp_search_attr", Value::new_iutf8("member")),
+        ("acp_search_attr", Value::new_iutf8("dynmember")),
+        ("acp_search_attr", Value::new_iutf8("member_validity")),
         ("acp_search_attr", Value::new_iutf8("uuid")),
         ("acp_search_attr", Value::new_iutf8("gidnumber")),
         ("acp_search_attr", Value::new_iutf8("loginshell")),
-        ("acp_search_attr", Value::new_iutf8("ssh_publickey"))
+        ("acp_search_attr", Value::new_iutf8("ssh_publickey")),
+        ("acp_search_attr", Value::new_iutf8("entry_managed_by")),
+        ("acp_search_attr", Value::new_iutf8("access_request_approvers")),
+        ("acp_search_attr", Value::new_iutf8("access_request_max_duration"))
     );
 }
 
@@ -561,12 +574,28 @@ lazy_static! {
         ("acp_search_attr", Value::new_iutf8("uuid")),
         ("acp_search_attr", Value::new_iutf8("description")),
         ("acp_search_attr", Value::new_iutf8("member")),
+        ("acp_search_attr", Value::new_iutf8("dynmember")),
+        ("acp_search_attr", Value::new_iutf8("dyngroup_filter")),
+        ("acp_search_attr", Value::new_iutf8("member_validity")),
+        ("acp_search_attr", Value::new_iutf8("entry_managed_by")),
+        ("acp_search_attr", Value::new_iutf8("access_request_approvers")),
+        ("acp_search_attr", Value::new_iutf8("access_request_max_duration")),
         ("acp_modify_removedattr", Value::new_iutf8("name")),
         ("acp_modify_removedattr", Value::new_iutf8("description")),
         ("acp_modify_removedattr", Value::new_iutf8("member")),
+        ("acp_modify_removedattr", Value::new_iutf8("dyngroup_filter")),
+        ("acp_modify_removedattr", Value::new_iutf8("member_validity")),
+        ("acp_modify_removedattr", Value::new_iutf8("entry_managed_by")),
+        ("acp_modify_removedattr", Value::new_iutf8("access_request_approvers")),
+        ("acp_modify_removedattr", Value::new_iutf8("access_request_max_duration")),
         ("acp_modify_presentattr", Value::new_iutf8("name")),
         ("acp_modify_presentattr", Value::new_iutf8("description")),
-        ("acp_modify_presentattr", Value::new_iutf8("member"))
+        ("acp_modify_presentattr", Value::new_iutf8("member")),
+        ("acp_modify_presentattr", Value::new_iutf8("dyngroup_filter")),
+        ("acp_modify_presentattr", Value::new_iutf8("member_validity")),
+        ("acp_modify_presentattr", Value::new_iutf8("entry_managed_by")),
+        ("acp_modify_presentattr", Value::new_iutf8("access_request_approvers")),
+        ("acp_modify_presentattr", Value::new_iutf8("access_request_max_duration"))
     );
 }
 
@@ -607,7 +636,10 @@ lazy_static! {
         ("acp_search_attr", Value::new_iutf8("passkeys")),
         ("acp_search_attr", Value::new_iutf8("devicekeys")),
         ("acp_search_attr", Value::new_iutf8("api_token_session")),
-        ("acp_search_attr", Value::new_iutf8("user_auth_token_session"))
+        ("acp_search_attr", Value::new_iutf8("user_auth_token_session")),
+        ("acp_search_attr", Value::new_iutf8("oauth2_consent_scope_map")),
+        ("acp_search_attr", Value::new_iutf8("oauth2_session")),
+        ("acp_search_attr", Value::new_iutf8("entry_managed_by"))
     );
 }
 
@@ -644,6 +676,9 @@ lazy_static! {
         ("acp_modify_removedattr", Value::new_iutf8("devicekeys")),
         ("acp_modify_removedattr", Value::new_iutf8("api_token_session")),
         ("acp_modify_removedattr", Value::new_iutf8("user_auth_token_session")),
+        ("acp_modify_removedattr", Value::new_iutf8("oauth2_consent_scope_map")),
+        ("acp_modify_removedattr", Value::new_iutf8("oauth2_session")),
+        ("acp_modify_removedattr", Value::new_iutf8("entry_managed_by")),
 
         ("acp_modify_presentattr", Value::new_iutf8("name")),
         ("acp_modify_presentattr", Value::new_iutf8("displayname")),
@@ -654,7 +689,8 @@ lazy_static! {
         ("acp_modify_presentattr", Value::new_iutf8("account_valid_from")),
         ("acp_modify_presentattr", Value::new_iutf8("passkeys")),
         ("acp_modify_presentattr", Value::new_iutf8("devicekeys")),
-        ("acp_modify_presentattr", Value::new_iutf8("api_token_session"))
+        ("acp_modify_presentattr", Value::new_iutf8("api_token_session")),
+        ("acp_modify_presentattr", Value::new_iutf8("entry_managed_by"))
     );
 }
 
@@ -692,6 +728,7 @@ lazy_static! {
         ("acp_create_attr", Value::new_iutf8("account_valid_from")),
         ("acp_create_attr", Value::new_iutf8("passkeys")),
         ("acp_create_attr", Value::new_iutf8("devicekeys")),
+        ("acp_create_attr", Value::new_iutf8("entry_managed_by")),
         ("acp_create_class", Value::new_iutf8("object")),
         ("acp_create_class", Value::new_iutf8("account")),
         ("acp_create_class", Value::new_iutf8("service_account"))
@@ -821,7 +858,10 @@ lazy_static! {
         ("acp_search_attr", Value::new_iutf8("passkeys")),
         ("acp_search_attr", Value::new_iutf8("devicekeys")),
         ("acp_search_attr", Value::new_iutf8("api_token_session")),
-        ("acp_search_attr", Value::new_iutf8("user_auth_token_session"))
+        ("acp_search_attr", Value::new_iutf8("user_auth_token_session")),
+        ("acp_search_attr", Value::new_iutf8("oauth2_consent_scope_map")),
+        ("acp_search_attr", Value::new_iutf8("oauth2_session")),
+        ("acp_search_attr", Value::new_iutf8("entry_managed_by"))
     );
 }
 
@@ -857,6 +897,9 @@ lazy_static! {
         ("acp_modify_removedattr", Value::new_iutf8("devicekeys")),
         ("acp_modify_removedattr", Value::new_iutf8("api_token_session")),
         ("acp_modify_removedattr", Value::new_iutf8("user_auth_token_session")),
+        ("acp_modify_removedattr", Value::new_iutf8("oauth2_consent_scope_map")),
+        ("acp_modify_removedattr", Value::new_iutf8("oauth2_session")),
+        ("acp_modify_removedattr", Value::new_iutf8("entry_managed_by")),
 
         ("acp_modify_presentattr", Value::new_iutf8("name")),
         ("acp_modify_presentattr", Value::new_iutf8("displayname")),
@@ -866,7 +909,8 @@ lazy_static! {
         ("acp_modify_presentattr", Value::new_iutf8("account_valid_from")),
         ("acp_modify_presentattr", Value::new_iutf8("passkeys")),
         ("acp_modify_presentattr", Value::new_iutf8("devicekeys")),
-        ("acp_modify_presentattr", Value::new_iutf8("api_token_session"))
+        ("acp_modify_presentattr", Value::new_iutf8("api_token_session")),
+        ("acp_modify_presentattr", Value::new_iutf8("entry_managed_by"))
     );
 }
 
@@ -900,12 +944,28 @@ lazy_static! {
         ("acp_search_attr", Value::new_iutf8("uuid")),
         ("acp_search_attr", Value::new_iutf8("description")),
         ("acp_search_attr", Value::new_iutf8("member")),
+        ("acp_search_attr", Value::new_iutf8("dynmember")),
+        ("acp_search_attr", Value::new_iutf8("dyngroup_filter")),
+        ("acp_search_attr", Value::new_iutf8("member_validity")),
+        ("acp_search_attr", Value::new_iutf8("entry_managed_by")),
+        ("acp_search_attr", Value::new_iutf8("access_request_approvers")),
+        ("acp_search_attr", Value::new_iutf8("access_request_max_duration")),
         ("acp_modify_removedattr", Value::new_iutf8("name")),
         ("acp_modify_removedattr", Value::new_iutf8("description")),
         ("acp_modify_removedattr", Value::new_iutf8("member")),
+        ("acp_modify_removedattr", Value::new_iutf8("dyngroup_filter")),
+        ("acp_modify_removedattr", Value::new_iutf8("member_validity")),
+        ("acp_modify_removedattr", Value::new_iutf8("entry_managed_by")),
+        ("acp_modify_removedattr", Value::new_iutf8("access_request_approvers")),
+        ("acp_modify_removedattr", Value::new_iutf8("access_request_max_duration")),
         ("acp_modify_presentattr", Value::new_iutf8("name")),
         ("acp_modify_presentattr", Value::new_iutf8("description")),
-        ("acp_modify_presentattr", Value::new_iutf8("member"))
+        ("acp_modify_presentattr", Value::new_iutf8("member")),
+        ("acp_modify_presentattr", Value::new_iutf8("dyngroup_filter")),
+        ("acp_modify_presentattr", Value::new_iutf8("member_validity")),
+        ("acp_modify_presentattr", Value::new_iutf8("entry_managed_by")),
+        ("acp_modify_presentattr", Value::new_iutf8("access_request_approvers")),
+        ("acp_modify_presentattr", Value::new_iutf8("access_request_max_duration"))
     );
 }
 
@@ -941,18 +1001,24 @@ lazy_static! {
         ("acp_search_attr", Value::new_iutf8("attributename")),
         ("acp_search_attr", Value::new_iutf8("syntax")),
         ("acp_search_attr", Value::new_iutf8("uuid")),
+        ("acp_search_attr", Value::new_iutf8("deprecated")),
+        ("acp_search_attr", Value::new_iutf8("unique_scope")),
 
         ("acp_modify_removedattr", Value::new_iutf8("description")),
         ("acp_modify_removedattr", Value::new_iutf8("index")),
         ("acp_modify_removedattr", Value::new_iutf8("unique")),
         ("acp_modify_removedattr", Value::new_iutf8("multivalue")),
         ("acp_modify_removedattr", Value::new_iutf8("syntax")),
+        ("acp_modify_removedattr", Value::new_iutf8("deprecated")),
+        ("acp_modify_removedattr", Value::new_iutf8("unique_scope")),
 
         ("acp_modify_presentattr", Value::new_iutf8("description")),
         ("acp_modify_presentattr", Value::new_iutf8("index")),
         ("acp_modify_presentattr", Value::new_iutf8("unique")),
         ("acp_modify_presentattr", Value::new_iutf8("multivalue")),
         ("acp_modify_presentattr", Value::new_iutf8("syntax")),
+        ("acp_modify_presentattr", Value::new_iutf8("deprecated")),
+        ("acp_modify_presentattr", Value::new_iutf8("unique_scope")),
 
         ("acp_create_attr", Value::new_iutf8("class")),
         ("acp_create_attr", Value::new_iutf8("description")),
@@ -962,6 +1028,7 @@ lazy_static! {
         ("acp_create_attr", Value::new_iutf8("attributename")),
         ("acp_create_attr", Value::new_iutf8("syntax")),
         ("acp_create_attr", Value::new_iutf8("uuid")),
+        ("acp_create_attr", Value::new_iutf8("unique_scope")),
 
         ("acp_create_class", Value::new_iutf8("object")),
         ("acp_create_class", Value::new_iutf8("attributetype"))
@@ -995,6 +1062,7 @@ lazy_static! {
         ),
         ("acp_search_attr", Value::new_iutf8("class")),
         ("acp_search_attr", Value::new_iutf8("name")),
+        ("acp_search_attr", Value::new_iutf8("uuid")),
         ("acp_search_attr", Value::new_iutf8("description")),
         ("acp_search_attr", Value::new_iutf8("acp_enable")),
         ("acp_search_attr", Value::new_iutf8("acp_receiver_group")),
@@ -1092,14 +1160,17 @@ lazy_static! {
         ("acp_search_attr", Value::new_iutf8("systemmust")),
         ("acp_search_attr", Value::new_iutf8("must")),
         ("acp_search_attr", Value::new_iutf8("uuid")),
+        ("acp_search_attr", Value::new_iutf8("deprecated")),
         ("acp_modify_removedattr", Value::new_iutf8("class")),
         ("acp_modify_removedattr", Value::new_iutf8("description")),
         ("acp_modify_removedattr", Value::new_iutf8("may")),
         ("acp_modify_removedattr", Value::new_iutf8("must")),
+        ("acp_modify_removedattr", Value::new_iutf8("deprecated")),
         ("acp_modify_presentattr", Value::new_iutf8("name")),
         ("acp_modify_presentattr", Value::new_iutf8("description")),
         ("acp_modify_presentattr", Value::new_iutf8("may")),
         ("acp_modify_presentattr", Value::new_iutf8("must")),
+        ("acp_modify_presentattr", Value::new_iutf8("deprecated")),
         ("acp_create_attr", Value::new_iutf8("class")),
         ("acp_create_attr", Value::new_iutf8("classname")),
         ("acp_create_attr", Value::new_iutf8("description")),
@@ -1140,8 +1211,12 @@ lazy_static! {
         ("acp_create_attr", Value::new_iutf8("name")),
         ("acp_create_attr", Value::new_iutf8("description")),
         ("acp_create_attr", Value::new_iutf8("member")),
+        ("acp_create_attr", Value::new_iutf8("dyngroup_filter")),
+        ("acp_create_attr", Value::new_iutf8("member_validity")),
+        ("acp_create_attr", Value::new_iutf8("entry_managed_by")),
         ("acp_create_class", Value::new_iutf8("object")),
-        ("acp_create_class", Value::new_iutf8("group"))
+        ("acp_create_class", Value::new_iutf8("group")),
+        ("acp_create_class", Value::new_iutf8("dyngroup"))
     );
 }
 
@@ -1178,6 +1253,7 @@ lazy_static! {
         ("acp_create_attr", Value::new_iutf8("account_valid_from")),
         ("acp_create_attr", Value::new_iutf8("passkeys")),
         ("acp_create_attr", Value::new_iutf8("devicekeys")),
+        ("acp_create_attr", Value::new_iutf8("entry_managed_by")),
         ("acp_create_class", Value::new_iutf8("object")),
         ("acp_create_class", Value::new_iutf8("account")),
         ("acp_create_class", Value::new_iutf8("service_account"))
@@ -1211,8 +1287,12 @@ lazy_static! {
         ("acp_create_attr", Value::new_iutf8("name")),
         ("acp_create_attr", Value::new_iutf8("description")),
         ("acp_create_attr", Value::new_iutf8("member")),
+        ("acp_create_attr", Value::new_iutf8("dyngroup_filter")),
+        ("acp_create_attr", Value::new_iutf8("member_validity")),
+        ("acp_create_attr", Value::new_iutf8("entry_managed_by")),
         ("acp_create_class", Value::new_iutf8("object")),
-        ("acp_create_class", Value::new_iutf8("group"))
+        ("acp_create_class", Value::new_iutf8("group")),
+        ("acp_create_class", Value::new_iutf8("dyngroup"))
     );
 }
 
@@ -1249,12 +1329,10 @@ lazy_static! {
         ("acp_search_attr", Value::new_iutf8("domain_uuid")),
         ("acp_search_attr", Value::new_iutf8("es256_private_key_der")),
         ("acp_search_attr", Value::new_iutf8("fernet_private_key_str")),
-        ("acp_search_attr", Value::new_iutf8("cookie_private_key")),
         ("acp_modify_removedattr", Value::new_iutf8("domain_display_name")),
         ("acp_modify_removedattr", Value::new_iutf8("domain_ssid")),
         ("acp_modify_removedattr", Value::new_iutf8("domain_ldap_basedn")),
         ("acp_modify_removedattr", Value::new_iutf8("es256_private_key_der")),
-        ("acp_modify_removedattr", Value::new_iutf8("cookie_private_key")),
         ("acp_modify_removedattr", Value::new_iutf8("fernet_private_key_str")),
         ("acp_modify_presentattr", Value::new_iutf8("domain_display_name")),
         ("acp_modify_presentattr", Value::new_iutf8("domain_ldap_basedn")),
@@ -1290,8 +1368,14 @@ lazy_static! {
         ("acp_search_attr", Value::new_iutf8("uuid")),
         ("acp_search_attr", Value::new_iutf8("description")),
         ("acp_search_attr", Value::new_iutf8("badlist_password")),
+        ("acp_search_attr", Value::new_iutf8("password_history_length")),
+        ("acp_search_attr", Value::new_iutf8("recycle_bin_retention")),
         ("acp_modify_removedattr", Value::new_iutf8("badlist_password")),
-        ("acp_modify_presentattr", Value::new_iutf8("badlist_password"))
+        ("acp_modify_removedattr", Value::new_iutf8("password_history_length")),
+        ("acp_modify_removedattr", Value::new_iutf8("recycle_bin_retention")),
+        ("acp_modify_presentattr", Value::new_iutf8("badlist_password")),
+        ("acp_modify_presentattr", Value::new_iutf8("password_history_length")),
+        ("acp_modify_presentattr", Value::new_iutf8("recycle_bin_retention"))
     );
 }
 
@@ -1493,6 +1577,14 @@ lazy_static! {
         ("acp_search_attr", Value::new_iutf8("rs256_private_key_der")),
         ("acp_search_attr", Value::new_iutf8("oauth2_jwt_legacy_crypto_enable")),
         ("acp_search_attr", Value::new_iutf8("oauth2_prefer_short_username")),
+        ("acp_search_attr", Value::new_iutf8("oauth2_rs_backchannel_logout_uri")),
+        ("acp_search_attr", Value::new_iutf8("oauth2_rs_token_exchange_from")),
+        ("acp_search_attr", Value::new_iutf8("oauth2_rs_jwks")),
+        ("acp_search_attr", Value::new_iutf8("oauth2_rs_tls_client_cert")),
+        ("acp_search_attr", Value::new_iutf8("oauth2_require_pushed_authorisation")),
+        ("acp_search_attr", Value::new_iutf8("oauth2_consent_prompt_disable")),
+        ("acp_search_attr", Value::new_iutf8("oauth2_rs_registered_by")),
+        ("acp_search_attr", Value::new_iutf8("oauth2_rs_redirect_uri")),
 
         ("acp_modify_removedattr", Value::new_iutf8("description")),
         ("acp_modify_removedattr", Value::new_iutf8("displayname")),
@@ -1508,6 +1600,14 @@ lazy_static! {
         ("acp_modify_removedattr", Value::new_iutf8("rs256_private_key_der")),
         ("acp_modify_removedattr", Value::new_iutf8("oauth2_jwt_legacy_crypto_enable")),
         ("acp_modify_removedattr", Value::new_iutf8("oauth2_prefer_short_username")),
+        ("acp_modify_removedattr", Value::new_iutf8("oauth2_rs_backchannel_logout_uri")),
+        ("acp_modify_removedattr", Value::new_iutf8("oauth2_rs_token_exchange_from")),
+        ("acp_modify_removedattr", Value::new_iutf8("oauth2_rs_jwks")),
+        ("acp_modify_removedattr", Value::new_iutf8("oauth2_rs_tls_client_cert")),
+        ("acp_modify_removedattr", Value::new_iutf8("oauth2_require_pushed_authorisation")),
+        ("acp_modify_removedattr", Value::new_iutf8("oauth2_consent_prompt_disable")),
+        ("acp_modify_removedattr", Value::new_iutf8("oauth2_rs_registered_by")),
+        ("acp_modify_removedattr", Value::new_iutf8("oauth2_rs_redirect_uri")),
 
 
         ("acp_modify_presentattr", Value::new_iutf8("description")),
@@ -1520,6 +1620,12 @@ lazy_static! {
         ("acp_modify_presentattr", Value::new_iutf8("oauth2_allow_insecure_client_disable_pkce")),
         ("acp_modify_presentattr", Value::new_iutf8("oauth2_jwt_legacy_crypto_enable")),
         ("acp_modify_presentattr", Value::new_iutf8("oauth2_prefer_short_username")),
+        ("acp_modify_presentattr", Value::new_iutf8("oauth2_rs_backchannel_logout_uri")),
+        ("acp_modify_presentattr", Value::new_iutf8("oauth2_rs_token_exchange_from")),
+        ("acp_modify_presentattr", Value::new_iutf8("oauth2_rs_jwks")),
+        ("acp_modify_presentattr", Value::new_iutf8("oauth2_rs_tls_client_cert")),
+        ("acp_modify_presentattr", Value::new_iutf8("oauth2_require_pushed_authorisation")),
+        ("acp_modify_presentattr", Value::new_iutf8("oauth2_consent_prompt_disable")),
 
         ("acp_create_attr", Value::new_iutf8("class")),
         ("acp_create_attr", Value::new_iutf8("description")),
@@ -1532,6 +1638,12 @@ lazy_static! {
         ("acp_create_attr", Value::new_iutf8("oauth2_allow_insecure_client_disable_pkce")),
         ("acp_create_attr", Value::new_iutf8("oauth2_jwt_legacy_crypto_enable")),
         ("acp_create_attr", Value::new_iutf8("oauth2_prefer_short_username")),
+        ("acp_create_attr", Value::new_iutf8("oauth2_rs_backchannel_logout_uri")),
+        ("acp_create_attr", Value::new_iutf8("oauth2_rs_token_exchange_from")),
+        ("acp_create_attr", Value::new_iutf8("oauth2_rs_jwks")),
+        ("acp_create_attr", Value::new_iutf8("oauth2_rs_tls_client_cert")),
+        ("acp_create_attr", Value::new_iutf8("oauth2_require_pushed_authorisation")),
+        ("acp_create_attr", Value::new_iutf8("oauth2_consent_prompt_disable")),
 
 
         ("acp_create_class", Value::new_iutf8("object")),
@@ -1627,3 +1739,210 @@ lazy_static! {
         ("acp_create_class", Value::new_iutf8("sync_account"))
     );
 }
+
+lazy_static! {
+    pub static ref E_IDM_ACP_GROUP_ENTRY_MANAGER_V1: EntryInitNew = entry_init!(
+        ("class", CLASS_OBJECT.clone()),
+        ("class", CLASS_ACCESS_CONTROL_PROFILE.clone()),
+        ("class", CLASS_ACCESS_CONTROL_ENTRY_MANAGER.clone()),
+        ("class", CLASS_ACCESS_CONTROL_MODIFY.clone()),
+        ("class", CLASS_ACCESS_CONTROL_SEARCH.clone()),
+        ("name", Value::new_iname("idm_acp_group_entry_manager")),
+        ("uuid", Value::Uuid(UUID_IDM_ACP_GROUP_ENTRY_MANAGER_V1)),
+        (
+            "description",
+            Value::new_utf8s("Builtin IDM Control for allowing entry managers to modify the members of the groups they manage")
+        ),
+        (
+            "acp_receiver_group",
+            Value::Refer(UUID_IDM_ALL_ACCOUNTS)
+        ),
+        (
+            "acp_targetscope",
+            Value::new_json_filter_s(
+                "{\"and\": [{\"eq\": [\"class\",\"group\"]}, {\"andnot\": {\"or\": [{\"eq\": [\"memberof\",\"00000000-0000-0000-0000-000000001000\"]}, {\"eq\": [\"class\", \"tombstone\"]}, {\"eq\": [\"class\", \"recycled\"]}]}}]}"
+            )
+                .expect("Invalid JSON filter")
+        ),
+        ("acp_search_attr", Value::new_iutf8("class")),
+        ("acp_search_attr", Value::new_iutf8("name")),
+        ("acp_search_attr", Value::new_iutf8("spn")),
+        ("acp_search_attr", Value::new_iutf8("uuid")),
+        ("acp_search_attr", Value::new_iutf8("description")),
+        ("acp_search_attr", Value::new_iutf8("member")),
+        ("acp_search_attr", Value::new_iutf8("member_validity")),
+        ("acp_search_attr", Value::new_iutf8("entry_managed_by")),
+        ("acp_modify_removedattr", Value::new_iutf8("description")),
+        ("acp_modify_removedattr", Value::new_iutf8("member")),
+        ("acp_modify_removedattr", Value::new_iutf8("member_validity")),
+        ("acp_modify_presentattr", Value::new_iutf8("description")),
+        ("acp_modify_presentattr", Value::new_iutf8("member")),
+        ("acp_modify_presentattr", Value::new_iutf8("member_validity"))
+    );
+}
+
+lazy_static! {
+    pub static ref E_IDM_ACP_SERVICE_ACCOUNT_ENTRY_MANAGER_V1: EntryInitNew = entry_init!(
+        ("class", CLASS_OBJECT.clone()),
+        ("class", CLASS_ACCESS_CONTROL_PROFILE.clone()),
+        ("class", CLASS_ACCESS_CONTROL_ENTRY_MANAGER.clone()),
+        ("class", CLASS_ACCESS_CONTROL_MODIFY.clone()),
+        ("class", CLASS_ACCESS_CONTROL_SEARCH.clone()),
+        ("name", Value::new_iname("idm_acp_service_account_entry_manager")),
+        ("uuid", Value::Uuid(UUID_IDM_ACP_SERVICE_ACCOUNT_ENTRY_MANAGER_V1)),
+        (
+            "description",
+            Value::new_utf8s("Builtin IDM Control for allowing entry managers to modify the service accounts they manage")
+        ),
+        (
+            "acp_receiver_group",
+            Value::Refer(UUID_IDM_ALL_ACCOUNTS)
+        ),
+        (
+            "acp_targetscope",
+            Value::new_json_filter_s(
+                "{\"and\": [{\"eq\": [\"class\",\"service_account\"]}, {\"andnot\": {\"or\": [{\"eq\": [\"memberof\",\"00000000-0000-0000-0000-000000001000\"]}, {\"eq\": [\"class\", \"tombstone\"]}, {\"eq\": [\"class\", \"recycled\"]}]}}]}"
+            )
+                .expect("Invalid JSON filter")
+        ),
+        ("acp_search_attr", Value::new_iutf8("class")),
+        ("acp_search_attr", Value::new_iutf8("name")),
+        ("acp_search_attr", Value::new_iutf8("spn")),
+        ("acp_search_attr", Value::new_iutf8("uuid")),
+        ("acp_search_attr", Value::new_iutf8("displayname")),
+        ("acp_search_attr", Value::new_iutf8("description")),
+        ("acp_search_attr", Value::new_iutf8("ssh_publickey")),
+        ("acp_search_attr", Value::new_iutf8("api_token_session")),
+        ("acp_search_attr", Value::new_iutf8("entry_managed_by")),
+        ("acp_modify_removedattr", Value::new_iutf8("displayname")),
+        ("acp_modify_removedattr", Value::new_iutf8("description")),
+        ("acp_modify_removedattr", Value::new_iutf8("ssh_publickey")),
+        ("acp_modify_removedattr", Value::new_iutf8("api_token_session")),
+        ("acp_modify_presentattr", Value::new_iutf8("displayname")),
+        ("acp_modify_presentattr", Value::new_iutf8("description")),
+        ("acp_modify_presentattr", Value::new_iutf8("ssh_publickey")),
+        ("acp_modify_presentattr", Value::new_iutf8("api_token_session"))
+    );
+}
+
+lazy_static! {
+    pub static ref E_IDM_ACP_WEBHOOK_MANAGE_V1: EntryInitNew = entry_init!(
+        ("class", CLASS_OBJECT.clone()),
+        ("class", CLASS_ACCESS_CONTROL_PROFILE.clone()),
+        ("class", CLASS_ACCESS_CONTROL_CREATE.clone()),
+        ("class", CLASS_ACCESS_CONTROL_DELETE.clone()),
+        ("class", CLASS_ACCESS_CONTROL_MODIFY.clone()),
+        ("class", CLASS_ACCESS_CONTROL_SEARCH.clone()),
+        ("name", Value::new_iname("idm_acp_webhook_manage")),
+        ("uuid", Value::Uuid(UUID_IDM_ACP_WEBHOOK_MANAGE_V1)),
+        (
+            "description",
+            Value::new_utf8s("Builtin IDM Control for managing webhook subscriptions.")
+        ),
+        ("acp_receiver_group", Value::Refer(UUID_SYSTEM_ADMINS)),
+        (
+            "acp_targetscope",
+            Value::new_json_filter_s(
+                "{\"and\": [{\"eq\": [\"class\",\"webhook\"]},{\"andnot\": {\"or\": [{\"eq\": [\"class\", \"tombstone\"]}, {\"eq\": [\"class\", \"recycled\"]}]}}]}"
+            )
+                .expect("Invalid JSON filter")
+        ),
+        ("acp_search_attr", Value::new_iutf8("class")),
+        ("acp_search_attr", Value::new_iutf8("name")),
+        ("acp_search_attr", Value::new_iutf8("uuid")),
+        ("acp_search_attr", Value::new_iutf8("description")),
+        ("acp_search_attr", Value::new_iutf8("webhook_url")),
+        ("acp_search_attr", Value::new_iutf8("webhook_event")),
+        ("acp_search_attr", Value::new_iutf8("webhook_filter")),
+        ("acp_search_attr", Value::new_iutf8("webhook_secret")),
+        ("acp_search_attr", Value::new_iutf8("webhook_dead_letter")),
+        ("acp_modify_removedattr", Value::new_iutf8("name")),
+        ("acp_modify_removedattr", Value::new_iutf8("description")),
+        ("acp_modify_removedattr", Value::new_iutf8("webhook_url")),
+        ("acp_modify_removedattr", Value::new_iutf8("webhook_event")),
+        ("acp_modify_removedattr", Value::new_iutf8("webhook_filter")),
+        ("acp_modify_removedattr", Value::new_iutf8("webhook_secret")),
+        ("acp_modify_removedattr", Value::new_iutf8("webhook_dead_letter")),
+        ("acp_modify_presentattr", Value::new_iutf8("name")),
+        ("acp_modify_presentattr", Value::new_iutf8("description")),
+        ("acp_modify_presentattr", Value::new_iutf8("webhook_url")),
+        ("acp_modify_presentattr", Value::new_iutf8("webhook_event")),
+        ("acp_modify_presentattr", Value::new_iutf8("webhook_filter")),
+        ("acp_modify_presentattr", Value::new_iutf8("webhook_secret")),
+        ("acp_create_attr", Value::new_iutf8("class")),
+        ("acp_create_attr", Value::new_iutf8("name")),
+        ("acp_create_attr", Value::new_iutf8("description")),
+        ("acp_create_attr", Value::new_iutf8("webhook_url")),
+        ("acp_create_attr", Value::new_iutf8("webhook_event")),
+        ("acp_create_attr", Value::new_iutf8("webhook_filter")),
+        ("acp_create_attr", Value::new_iutf8("webhook_secret")),
+        ("acp_create_class", Value::new_iutf8("object")),
+        ("acp_create_class", Value::new_iutf8("webhook"))
+    );
+}
+
+lazy_static! {
+    pub static ref E_IDM_ACP_OAUTH2_REGISTRAR_MANAGE_V1: EntryInitNew = entry_init!(
+        ("class", CLASS_OBJECT.clone()),
+        ("class", CLASS_ACCESS_CONTROL_PROFILE.clone()),
+        ("class", CLASS_ACCESS_CONTROL_MODIFY.clone()),
+        ("class", CLASS_ACCESS_CONTROL_SEARCH.clone()),
+        ("name", Value::new_iname("idm_acp_oauth2_registrar_manage")),
+        ("uuid", Value::Uuid(UUID_IDM_ACP_OAUTH2_REGISTRAR_MANAGE_V1)),
+        (
+            "description",
+            Value::new_utf8s("Builtin IDM Control for granting service accounts the right to dynamically register oauth2 resource servers.")
+        ),
+        (
+            "acp_receiver_group",
+            Value::Refer(UUID_IDM_HP_OAUTH2_MANAGE_PRIV)
+        ),
+        (
+            "acp_targetscope",
+            Value::new_json_filter_s(
+                "{\"and\": [{\"eq\": [\"class\",\"service_account\"]},{\"andnot\": {\"or\": [{\"eq\": [\"class\", \"tombstone\"]}, {\"eq\": [\"class\", \"recycled\"]}]}}]}"
+            )
+                .expect("Invalid JSON filter")
+        ),
+        ("acp_search_attr", Value::new_iutf8("class")),
+        ("acp_search_attr", Value::new_iutf8("name")),
+        ("acp_search_attr", Value::new_iutf8("uuid")),
+        ("acp_search_attr", Value::new_iutf8("oauth2_registrar_domain")),
+        ("acp_search_attr", Value::new_iutf8("oauth2_registrar_scope_map")),
+        ("acp_modify_removedattr", Value::new_iutf8("class")),
+        ("acp_modify_removedattr", Value::new_iutf8("oauth2_registrar_domain")),
+        ("acp_modify_removedattr", Value::new_iutf8("oauth2_registrar_scope_map")),
+        ("acp_modify_presentattr", Value::new_iutf8("class")),
+        ("acp_modify_presentattr", Value::new_iutf8("oauth2_registrar_domain")),
+        ("acp_modify_presentattr", Value::new_iutf8("oauth2_registrar_scope_map")),
+        ("acp_modify_class", Value::new_iutf8("oauth2_registrar"))
+    );
+}
+
+lazy_static! {
+    pub static ref E_IDM_ACP_HELPDESK_V1: EntryInitNew = entry_init!(
+        ("class", CLASS_OBJECT.clone()),
+        ("class", CLASS_ACCESS_CONTROL_PROFILE.clone()),
+        ("class", CLASS_ACCESS_CONTROL_SEARCH.clone()),
+        ("name", Value::new_iname("idm_acp_helpdesk")),
+        ("uuid", Value::Uuid(UUID_IDM_ACP_HELPDESK_V1)),
+        (
+            "description",
+            Value::new_utf8s("Builtin IDM Control for allowing the helpdesk to find the persons they may assist.")
+        ),
+        ("acp_receiver_group", Value::Refer(UUID_IDM_HELPDESK)),
+        (
+            "acp_targetscope",
+            Value::new_json_filter_s(
+                "{\"and\": [{\"eq\": [\"class\",\"person\"]}, {\"andnot\": {\"or\": [{\"eq\": [\"memberof\",\"00000000-0000-0000-0000-000000001000\"]}, {\"eq\": [\"class\", \"tombstone\"]}, {\"eq\": [\"class\", \"recycled\"]}]}}]}"
+            )
+                .expect("Invalid JSON filter")
+        ),
+        ("acp_search_attr", Value::new_iutf8("class")),
+        ("acp_search_attr", Value::new_iutf8("name")),
+        ("acp_search_attr", Value::new_iutf8("spn")),
+        ("acp_search_attr", Value::new_iutf8("uuid")),
+        ("acp_search_attr", Value::new_iutf8("displayname")),
+        ("acp_search_attr", Value::new_iutf8("mail"))
+    );
+}
@@ -467,6 +467,15 @@ pub const JSON_IDM_HP_SYNC_ACCOUNT_MANAGE_PRIV: &str = r#"{
     }
 }"#;
 
+pub const JSON_IDM_HELPDESK_V1: &str = r#"{
+    "attrs": {
+        "class": ["group", "object"],
+        "name": ["idm_helpdesk"],
+        "uuid": ["00000000-0000-0000-0000-000000000040"],
+        "description": ["Builtin IDM Group for helpdesk staff who may reset the credentials of persons once they have verified their identity."]
+    }
+}"#;
+
 // == dyn groups
 
 pub const JSON_IDM_ALL_PERSONS: &str = r#"{
@@ -570,6 +579,7 @@ pub const JSON_IDM_HIGH_PRIVILEGE_V1: &str = r#"{
             "00000000-0000-0000-0000-000000000032",
             "00000000-0000-0000-0000-000000000034",
             "00000000-0000-0000-0000-000000000037",
+            "00000000-0000-0000-0000-000000000040",
             "00000000-0000-0000-0000-000000001000"
         ]
     }
@@ -75,6 +75,12 @@ pub const AUTH_SESSION_TIMEOUT: u64 = 300;
 // 5 minute mfa reg window
 pub const MFAREG_SESSION_TIMEOUT: u64 = 300;
 pub const PW_MIN_LENGTH: usize = 10;
+// Default - the previous 5 passwords of an account may not be reused.
+pub const PW_HISTORY_DEFAULT_LENGTH: u32 = 5;
+// Default - requested group memberships last for 1 hour.
+pub const ACCESS_REQUEST_DEFAULT_DURATION: u32 = 3600;
+// The maximum number of undelivered events that are kept for each webhook.
+pub const WEBHOOK_DEAD_LETTER_MAX: usize = 256;
 
 // Default - sessions last for 1 hour.
 pub const AUTH_SESSION_EXPIRY: u64 = 3600;
@@ -4,7 +4,7 @@
 use crate::constants::uuids::*;
 use crate::constants::values::*;
 use crate::entry::{Entry, EntryInit, EntryInitNew, EntryNew};
-use crate::value::{SyntaxType, Value};
+use crate::value::{IndexType, SyntaxType, Value};
 
 // system supplementary
 pub const JSON_SCHEMA_ATTR_DISPLAYNAME: &str = r#"{
@@ -1492,6 +1492,588 @@ lazy_static! {
         ("syntax", Value::Syntax(SyntaxType::Utf8StringInsensitive)),
         ("uuid", Value::Uuid(UUID_SCHEMA_ATTR_SYNC_YIELD_AUTHORITY))
     );
+
+    pub static ref E_SCHEMA_ATTR_PASSWORD_HISTORY: EntryInitNew = entry_init!(
+        ("class", CLASS_OBJECT.clone()),
+        ("class", CLASS_SYSTEM.clone()),
+        ("class", CLASS_ATTRIBUTETYPE.clone()),
+        (
+            "description",
+            Value::new_utf8s("The hashes of the primary passwords previously set on this account.")
+        ),
+        ("unique", Value::Bool(false)),
+        ("multivalue", Value::Bool(true)),
+        ("attributename", Value::new_iutf8("password_history")),
+        ("syntax", Value::Syntax(SyntaxType::PasswordHistory)),
+        ("uuid", Value::Uuid(UUID_SCHEMA_ATTR_PASSWORD_HISTORY))
+    );
+
+    pub static ref E_SCHEMA_ATTR_UNIX_PASSWORD_HISTORY: EntryInitNew = entry_init!(
+        ("class", CLASS_OBJECT.clone()),
+        ("class", CLASS_SYSTEM.clone()),
+        ("class", CLASS_ATTRIBUTETYPE.clone()),
+        (
+            "description",
+            Value::new_utf8s("The hashes of the unix passwords previously set on this account.")
+        ),
+        ("unique", Value::Bool(false)),
+        ("multivalue", Value::Bool(true)),
+        ("attributename", Value::new_iutf8("unix_password_history")),
+        ("syntax", Value::Syntax(SyntaxType::PasswordHistory)),
+        ("uuid", Value::Uuid(UUID_SCHEMA_ATTR_UNIX_PASSWORD_HISTORY))
+    );
+
+    pub static ref E_SCHEMA_ATTR_PASSWORD_HISTORY_LENGTH: EntryInitNew = entry_init!(
+        ("class", CLASS_OBJECT.clone()),
+        ("class", CLASS_SYSTEM.clone()),
+        ("class", CLASS_ATTRIBUTETYPE.clone()),
+        (
+            "description",
+            Value::new_utf8s("The number of previous passwords that may not be reused. 0 disables password history.")
+        ),
+        ("unique", Value::Bool(false)),
+        ("multivalue", Value::Bool(false)),
+        ("attributename", Value::new_iutf8("password_history_length")),
+        ("syntax", Value::Syntax(SyntaxType::Uint32)),
+        ("uuid", Value::Uuid(UUID_SCHEMA_ATTR_PASSWORD_HISTORY_LENGTH))
+    );
+
+    pub static ref E_SCHEMA_ATTR_ENTRY_MANAGED_BY: EntryInitNew = entry_init!(
+        ("class", CLASS_OBJECT.clone()),
+        ("class", CLASS_SYSTEM.clone()),
+        ("class", CLASS_ATTRIBUTETYPE.clone()),
+        (
+            "description",
+            Value::new_utf8s("The person or group that is delegated the management of this entry.")
+        ),
+        ("index", Value::new_index(IndexType::Equality)),
+        ("unique", Value::Bool(false)),
+        ("multivalue", Value::Bool(false)),
+        ("attributename", Value::new_iutf8("entry_managed_by")),
+        ("syntax", Value::Syntax(SyntaxType::ReferenceUuid)),
+        ("uuid", Value::Uuid(UUID_SCHEMA_ATTR_ENTRY_MANAGED_BY))
+    );
+}
+
+lazy_static! {
+    pub static ref E_SCHEMA_ATTR_MEMBER_VALIDITY: EntryInitNew = entry_init!(
+        ("class", CLASS_OBJECT.clone()),
+        ("class", CLASS_SYSTEM.clone()),
+        ("class", CLASS_ATTRIBUTETYPE.clone()),
+        (
+            "description",
+            Value::new_utf8s(
+                "The period in which a member of this group is considered to be a member."
+            )
+        ),
+        ("index", Value::new_index(IndexType::Equality)),
+        ("unique", Value::Bool(false)),
+        ("multivalue", Value::Bool(true)),
+        ("attributename", Value::new_iutf8("member_validity")),
+        ("syntax", Value::Syntax(SyntaxType::MemberValidity)),
+        ("uuid", Value::Uuid(UUID_SCHEMA_ATTR_MEMBER_VALIDITY))
+    );
+    pub static ref E_SCHEMA_ATTR_ACCESS_REQUEST_APPROVERS: EntryInitNew = entry_init!(
+        ("class", CLASS_OBJECT.clone()),
+        ("class", CLASS_SYSTEM.clone()),
+        ("class", CLASS_ATTRIBUTETYPE.clone()),
+        (
+            "description",
+            Value::new_utf8s(
+                "The groups whose members may approve requests for membership of this group."
+            )
+        ),
+        ("index", Value::new_index(IndexType::Equality)),
+        ("unique", Value::Bool(false)),
+        ("multivalue", Value::Bool(true)),
+        (
+            "attributename",
+            Value::new_iutf8("access_request_approvers")
+        ),
+        ("syntax", Value::Syntax(SyntaxType::ReferenceUuid)),
+        (
+            "uuid",
+            Value::Uuid(UUID_SCHEMA_ATTR_ACCESS_REQUEST_APPROVERS)
+        )
+    );
+    pub static ref E_SCHEMA_ATTR_ACCESS_REQUEST_MAX_DURATION: EntryInitNew =
+        entry_init!(
+        ("class", CLASS_OBJECT.clone()),
+        ("class", CLASS_SYSTEM.clone()),
+        ("class", CLASS_ATTRIBUTETYPE.clone()),
+        (
+            "description",
+            Value::new_utf8s(
+                "The maximum number of seconds that a requested membership of this group may last."
+            )
+        ),
+        ("unique", Value::Bool(false)),
+        ("multivalue", Value::Bool(false)),
+        ("attributename", Value::new_iutf8("access_request_max_duration")),
+        ("syntax", Value::Syntax(SyntaxType::Uint32)),
+        ("uuid", Value::Uuid(UUID_SCHEMA_ATTR_ACCESS_REQUEST_MAX_DURATION))
+    );
+    pub static ref E_SCHEMA_ATTR_ACCESS_REQUEST_REQUESTER: EntryInitNew = entry_init!(
+        ("class", CLASS_OBJECT.clone()),
+        ("class", CLASS_SYSTEM.clone()),
+        ("class", CLASS_ATTRIBUTETYPE.clone()),
+        (
+            "description",
+            Value::new_utf8s("The account that made this access request.")
+        ),
+        ("index", Value::new_index(IndexType::Equality)),
+        ("unique", Value::Bool(false)),
+        ("multivalue", Value::Bool(false)),
+        (
+            "attributename",
+            Value::new_iutf8("access_request_requester")
+        ),
+        ("syntax", Value::Syntax(SyntaxType::ReferenceUuid)),
+        (
+            "uuid",
+            Value::Uuid(UUID_SCHEMA_ATTR_ACCESS_REQUEST_REQUESTER)
+        )
+    );
+    pub static ref E_SCHEMA_ATTR_ACCESS_REQUEST_GROUP: EntryInitNew = entry_init!(
+        ("class", CLASS_OBJECT.clone()),
+        ("class", CLASS_SYSTEM.clone()),
+        ("class", CLASS_ATTRIBUTETYPE.clone()),
+        (
+            "description",
+            Value::new_utf8s("The group that membership is requested of.")
+        ),
+        ("index", Value::new_index(IndexType::Equality)),
+        ("unique", Value::Bool(false)),
+        ("multivalue", Value::Bool(false)),
+        ("attributename", Value::new_iutf8("access_request_group")),
+        ("syntax", Value::Syntax(SyntaxType::ReferenceUuid)),
+        ("uuid", Value::Uuid(UUID_SCHEMA_ATTR_ACCESS_REQUEST_GROUP))
+    );
+    pub static ref E_SCHEMA_ATTR_ACCESS_REQUEST_STATE: EntryInitNew = entry_init!(
+        ("class", CLASS_OBJECT.clone()),
+        ("class", CLASS_SYSTEM.clone()),
+        ("class", CLASS_ATTRIBUTETYPE.clone()),
+        (
+            "description",
+            Value::new_utf8s("The current state of this access request.")
+        ),
+        ("index", Value::new_index(IndexType::Equality)),
+        ("unique", Value::Bool(false)),
+        ("multivalue", Value::Bool(false)),
+        ("attributename", Value::new_iutf8("access_request_state")),
+        ("syntax", Value::Syntax(SyntaxType::Utf8StringInsensitive)),
+        ("uuid", Value::Uuid(UUID_SCHEMA_ATTR_ACCESS_REQUEST_STATE))
+    );
+    pub static ref E_SCHEMA_ATTR_ACCESS_REQUEST_DURATION: EntryInitNew = entry_init!(
+        ("class", CLASS_OBJECT.clone()),
+        ("class", CLASS_SYSTEM.clone()),
+        ("class", CLASS_ATTRIBUTETYPE.clone()),
+        (
+            "description",
+            Value::new_utf8s("The number of seconds that membership is requested for.")
+        ),
+        ("unique", Value::Bool(false)),
+        ("multivalue", Value::Bool(false)),
+        ("attributename", Value::new_iutf8("access_request_duration")),
+        ("syntax", Value::Syntax(SyntaxType::Uint32)),
+        (
+            "uuid",
+            Value::Uuid(UUID_SCHEMA_ATTR_ACCESS_REQUEST_DURATION)
+        )
+    );
+    pub static ref E_SCHEMA_ATTR_ACCESS_REQUEST_DECIDED_BY: EntryInitNew = entry_init!(
+        ("class", CLASS_OBJECT.clone()),
+        ("class", CLASS_SYSTEM.clone()),
+        ("class", CLASS_ATTRIBUTETYPE.clone()),
+        (
+            "description",
+            Value::new_utf8s("The account that approved or denied this access request.")
+        ),
+        ("unique", Value::Bool(false)),
+        ("multivalue", Value::Bool(false)),
+        (
+            "attributename",
+            Value::new_iutf8("access_request_decided_by")
+        ),
+        ("syntax", Value::Syntax(SyntaxType::ReferenceUuid)),
+        (
+            "uuid",
+            Value::Uuid(UUID_SCHEMA_ATTR_ACCESS_REQUEST_DECIDED_BY)
+        )
+    );
+    pub static ref E_SCHEMA_ATTR_ACCESS_REQUEST_EXPIRY: EntryInitNew = entry_init!(
+        ("class", CLASS_OBJECT.clone()),
+        ("class", CLASS_SYSTEM.clone()),
+        ("class", CLASS_ATTRIBUTETYPE.clone()),
+        (
+            "description",
+            Value::new_utf8s("The time at which membership granted by this access request ends.")
+        ),
+        ("unique", Value::Bool(false)),
+        ("multivalue", Value::Bool(false)),
+        ("attributename", Value::new_iutf8("access_request_expiry")),
+        ("syntax", Value::Syntax(SyntaxType::DateTime)),
+        ("uuid", Value::Uuid(UUID_SCHEMA_ATTR_ACCESS_REQUEST_EXPIRY))
+    );
+    pub static ref E_SCHEMA_ATTR_RECYCLE_BIN_RETENTION: EntryInitNew = entry_init!(
+        ("class", CLASS_OBJECT.clone()),
+        ("class", CLASS_SYSTEM.clone()),
+        ("class", CLASS_ATTRIBUTETYPE.clone()),
+        (
+            "description",
+            Value::new_utf8s("The number of seconds that recycled entries of a class are kept, in the form class:seconds.")
+        ),
+        ("unique", Value::Bool(false)),
+        ("multivalue", Value::Bool(true)),
+        ("attributename", Value::new_iutf8("recycle_bin_retention")),
+        ("syntax", Value::Syntax(SyntaxType::Utf8StringInsensitive)),
+        ("uuid", Value::Uuid(UUID_SCHEMA_ATTR_RECYCLE_BIN_RETENTION))
+    );
+    pub static ref E_SCHEMA_ATTR_WEBHOOK_URL: EntryInitNew = entry_init!(
+        ("class", CLASS_OBJECT.clone()),
+        ("class", CLASS_SYSTEM.clone()),
+        ("class", CLASS_ATTRIBUTETYPE.clone()),
+        (
+            "description",
+            Value::new_utf8s("The url that events of this webhook are posted to.")
+        ),
+        ("unique", Value::Bool(false)),
+        ("multivalue", Value::Bool(false)),
+        ("attributename", Value::new_iutf8("webhook_url")),
+        ("syntax", Value::Syntax(SyntaxType::Url)),
+        ("uuid", Value::Uuid(UUID_SCHEMA_ATTR_WEBHOOK_URL))
+    );
+    pub static ref E_SCHEMA_ATTR_OAUTH2_RS_BACKCHANNEL_LOGOUT_URI: EntryInitNew = entry_init!(
+        ("class", CLASS_OBJECT.clone()),
+        ("class", CLASS_SYSTEM.clone()),
+        ("class", CLASS_ATTRIBUTETYPE.clone()),
+        (
+            "description",
+            Value::new_utf8s("The url that oauth2 back-channel logout tokens are posted to when a session of this resource server ends.")
+        ),
+        ("unique", Value::Bool(false)),
+        ("multivalue", Value::Bool(false)),
+        ("attributename", Value::new_iutf8("oauth2_rs_backchannel_logout_uri")),
+        ("syntax", Value::Syntax(SyntaxType::Url)),
+        ("uuid", Value::Uuid(UUID_SCHEMA_ATTR_OAUTH2_RS_BACKCHANNEL_LOGOUT_URI))
+    );
+    pub static ref E_SCHEMA_ATTR_OAUTH2_RS_TOKEN_EXCHANGE_FROM: EntryInitNew = entry_init!(
+        ("class", CLASS_OBJECT.clone()),
+        ("class", CLASS_SYSTEM.clone()),
+        ("class", CLASS_ATTRIBUTETYPE.clone()),
+        (
+            "description",
+            Value::new_utf8s("The oauth2 resource servers that may exchange their access tokens for tokens of this resource server.")
+        ),
+        ("index", Value::new_index(IndexType::Equality)),
+        ("unique", Value::Bool(false)),
+        ("multivalue", Value::Bool(true)),
+        ("attributename", Value::new_iutf8("oauth2_rs_token_exchange_from")),
+        ("syntax", Value::Syntax(SyntaxType::ReferenceUuid)),
+        ("uuid", Value::Uuid(UUID_SCHEMA_ATTR_OAUTH2_RS_TOKEN_EXCHANGE_FROM))
+    );
+    pub static ref E_SCHEMA_ATTR_OAUTH2_RS_JWKS: EntryInitNew = entry_init!(
+        ("class", CLASS_OBJECT.clone()),
+        ("class", CLASS_SYSTEM.clone()),
+        ("class", CLASS_ATTRIBUTETYPE.clone()),
+        (
+            "description",
+            Value::new_utf8s("The json web key set an oauth2 resource server signs its client assertions with.")
+        ),
+        ("unique", Value::Bool(false)),
+        ("multivalue", Value::Bool(false)),
+        ("attributename", Value::new_iutf8("oauth2_rs_jwks")),
+        ("syntax", Value::Syntax(SyntaxType::Utf8String)),
+        ("uuid", Value::Uuid(UUID_SCHEMA_ATTR_OAUTH2_RS_JWKS))
+    );
+    pub static ref E_SCHEMA_ATTR_OAUTH2_RS_TLS_CLIENT_CERT: EntryInitNew = entry_init!(
+        ("class", CLASS_OBJECT.clone()),
+        ("class", CLASS_SYSTEM.clone()),
+        ("class", CLASS_ATTRIBUTETYPE.clone()),
+        (
+            "description",
+            Value::new_utf8s("The sha256 thumbprints of the tls client certificates an oauth2 resource server may authenticate with.")
+        ),
+        ("unique", Value::Bool(false)),
+        ("multivalue", Value::Bool(true)),
+        ("attributename", Value::new_iutf8("oauth2_rs_tls_client_cert")),
+        ("syntax", Value::Syntax(SyntaxType::Utf8String)),
+        ("uuid", Value::Uuid(UUID_SCHEMA_ATTR_OAUTH2_RS_TLS_CLIENT_CERT))
+    );
+    pub static ref E_SCHEMA_ATTR_OAUTH2_REQUIRE_PUSHED_AUTHORISATION: EntryInitNew = entry_init!(
+        ("class", CLASS_OBJECT.clone()),
+        ("class", CLASS_SYSTEM.clone()),
+        ("class", CLASS_ATTRIBUTETYPE.clone()),
+        (
+            "description",
+            Value::new_utf8s("Require that an oauth2 resource server sends its authorisation requests with a pushed authorisation request.")
+        ),
+        ("unique", Value::Bool(false)),
+        ("multivalue", Value::Bool(false)),
+        ("attributename", Value::new_iutf8("oauth2_require_pushed_authorisation")),
+        ("syntax", Value::Syntax(SyntaxType::Boolean)),
+        ("uuid", Value::Uuid(UUID_SCHEMA_ATTR_OAUTH2_REQUIRE_PUSHED_AUTHORISATION))
+    );
+    pub static ref E_SCHEMA_ATTR_OAUTH2_REGISTRAR_DOMAIN: EntryInitNew = entry_init!(
+        ("class", CLASS_OBJECT.clone()),
+        ("class", CLASS_SYSTEM.clone()),
+        ("class", CLASS_ATTRIBUTETYPE.clone()),
+        (
+            "description",
+            Value::new_utf8s("A domain that clients registered by this oauth2 registrar may redirect to.")
+        ),
+        ("unique", Value::Bool(false)),
+        ("multivalue", Value::Bool(true)),
+        ("attributename", Value::new_iutf8("oauth2_registrar_domain")),
+        ("syntax", Value::Syntax(SyntaxType::Utf8StringInsensitive)),
+        ("uuid", Value::Uuid(UUID_SCHEMA_ATTR_OAUTH2_REGISTRAR_DOMAIN))
+    );
+    pub static ref E_SCHEMA_ATTR_OAUTH2_REGISTRAR_SCOPE_MAP: EntryInitNew = entry_init!(
+        ("class", CLASS_OBJECT.clone()),
+        ("class", CLASS_SYSTEM.clone()),
+        ("class", CLASS_ATTRIBUTETYPE.clone()),
+        (
+            "description",
+            Value::new_utf8s("The scope maps that clients registered by this oauth2 registrar may be granted.")
+        ),
+        ("unique", Value::Bool(false)),
+        ("multivalue", Value::Bool(true)),
+        ("attributename", Value::new_iutf8("oauth2_registrar_scope_map")),
+        ("syntax", Value::Syntax(SyntaxType::OauthScopeMap)),
+        ("uuid", Value::Uuid(UUID_SCHEMA_ATTR_OAUTH2_REGISTRAR_SCOPE_MAP))
+    );
+    pub static ref E_SCHEMA_ATTR_OAUTH2_RS_REGISTERED_BY: EntryInitNew = entry_init!(
+        ("class", CLASS_OBJECT.clone()),
+        ("class", CLASS_SYSTEM.clone()),
+        ("class", CLASS_ATTRIBUTETYPE.clone()),
+        (
+            "description",
+            Value::new_utf8s("The oauth2 registrar that dynamically registered this resource server.")
+        ),
+        ("unique", Value::Bool(false)),
+        ("multivalue", Value::Bool(false)),
+        ("attributename", Value::new_iutf8("oauth2_rs_registered_by")),
+        ("syntax", Value::Syntax(SyntaxType::ReferenceUuid)),
+        ("uuid", Value::Uuid(UUID_SCHEMA_ATTR_OAUTH2_RS_REGISTERED_BY))
+    );
+    pub static ref E_SCHEMA_ATTR_OAUTH2_RS_REDIRECT_URI: EntryInitNew = entry_init!(
+        ("class", CLASS_OBJECT.clone()),
+        ("class", CLASS_SYSTEM.clone()),
+        ("class", CLASS_ATTRIBUTETYPE.clone()),
+        (
+            "description",
+            Value::new_utf8s("A redirect uri that was registered by a dynamically registered resource server.")
+        ),
+        ("unique", Value::Bool(false)),
+        ("multivalue", Value::Bool(true)),
+        ("attributename", Value::new_iutf8("oauth2_rs_redirect_uri")),
+        ("syntax", Value::Syntax(SyntaxType::Url)),
+        ("uuid", Value::Uuid(UUID_SCHEMA_ATTR_OAUTH2_RS_REDIRECT_URI))
+    );
+    pub static ref E_SCHEMA_ATTR_OAUTH2_CONSENT_PROMPT_DISABLE: EntryInitNew = entry_init!(
+        ("class", CLASS_OBJECT.clone()),
+        ("class", CLASS_SYSTEM.clone()),
+        ("class", CLASS_ATTRIBUTETYPE.clone()),
+        (
+            "description",
+            Value::new_utf8s("Consider consent to be granted for this first party oauth2 resource server, so that users are not prompted for it.")
+        ),
+        ("unique", Value::Bool(false)),
+        ("multivalue", Value::Bool(false)),
+        ("attributename", Value::new_iutf8("oauth2_consent_prompt_disable")),
+        ("syntax", Value::Syntax(SyntaxType::Boolean)),
+        ("uuid", Value::Uuid(UUID_SCHEMA_ATTR_OAUTH2_CONSENT_PROMPT_DISABLE))
+    );
+    pub static ref E_SCHEMA_ATTR_ID_VERIFICATION_KEY: EntryInitNew = entry_init!(
+        ("class", CLASS_OBJECT.clone()),
+        ("class", CLASS_SYSTEM.clone()),
+        ("class", CLASS_ATTRIBUTETYPE.clone()),
+        (
+            "description",
+            Value::new_utf8s("A secret used to derive the codes that two people exchange to verify each others identity.")
+        ),
+        ("unique", Value::Bool(false)),
+        ("multivalue", Value::Bool(false)),
+        ("attributename", Value::new_iutf8("id_verification_key")),
+        ("syntax", Value::Syntax(SyntaxType::SecretUtf8String)),
+        ("uuid", Value::Uuid(UUID_SCHEMA_ATTR_ID_VERIFICATION_KEY))
+    );
+    pub static ref E_SCHEMA_ATTR_MAIL_DESTINATION: EntryInitNew = entry_init!(
+        ("class", CLASS_OBJECT.clone()),
+        ("class", CLASS_SYSTEM.clone()),
+        ("class", CLASS_ATTRIBUTETYPE.clone()),
+        (
+            "description",
+            Value::new_utf8s("The address that a queued message is delivered to.")
+        ),
+        ("unique", Value::Bool(false)),
+        ("multivalue", Value::Bool(false)),
+        ("attributename", Value::new_iutf8("mail_destination")),
+        ("syntax", Value::Syntax(SyntaxType::Utf8String)),
+        ("uuid", Value::Uuid(UUID_SCHEMA_ATTR_MAIL_DESTINATION))
+    );
+    pub static ref E_SCHEMA_ATTR_MESSAGE_TEMPLATE: EntryInitNew = entry_init!(
+        ("class", CLASS_OBJECT.clone()),
+        ("class", CLASS_SYSTEM.clone()),
+        ("class", CLASS_ATTRIBUTETYPE.clone()),
+        (
+            "description",
+            Value::new_utf8s("The template and values that a queued message is rendered from. This may contain credential reset tokens.")
+        ),
+        ("unique", Value::Bool(false)),
+        ("multivalue", Value::Bool(false)),
+        ("attributename", Value::new_iutf8("message_template")),
+        ("syntax", Value::Syntax(SyntaxType::SecretUtf8String)),
+        ("uuid", Value::Uuid(UUID_SCHEMA_ATTR_MESSAGE_TEMPLATE))
+    );
+    pub static ref E_SCHEMA_ATTR_SEND_AFTER: EntryInitNew = entry_init!(
+        ("class", CLASS_OBJECT.clone()),
+        ("class", CLASS_SYSTEM.clone()),
+        ("class", CLASS_ATTRIBUTETYPE.clone()),
+        (
+            "description",
+            Value::new_utf8s("The time after which delivery of a queued message is next attempted.")
+        ),
+        ("unique", Value::Bool(false)),
+        ("multivalue", Value::Bool(false)),
+        ("attributename", Value::new_iutf8("send_after")),
+        ("syntax", Value::Syntax(SyntaxType::DateTime)),
+        ("uuid", Value::Uuid(UUID_SCHEMA_ATTR_SEND_AFTER))
+    );
+    pub static ref E_SCHEMA_ATTR_DELIVERY_ATTEMPTS: EntryInitNew = entry_init!(
+        ("class", CLASS_OBJECT.clone()),
+        ("class", CLASS_SYSTEM.clone()),
+        ("class", CLASS_ATTRIBUTETYPE.clone()),
+        (
+            "description",
+            Value::new_utf8s("The number of times delivery of a queued message has failed.")
+        ),
+        ("unique", Value::Bool(false)),
+        ("multivalue", Value::Bool(false)),
+        ("attributename", Value::new_iutf8("delivery_attempts")),
+        ("syntax", Value::Syntax(SyntaxType::Uint32)),
+        ("uuid", Value::Uuid(UUID_SCHEMA_ATTR_DELIVERY_ATTEMPTS))
+    );
+    pub static ref E_SCHEMA_ATTR_WEBHOOK_EVENT: EntryInitNew = entry_init!(
+        ("class", CLASS_OBJECT.clone()),
+        ("class", CLASS_SYSTEM.clone()),
+        ("class", CLASS_ATTRIBUTETYPE.clone()),
+        (
+            "description",
+            Value::new_utf8s("The types of directory change that this webhook is notified of.")
+        ),
+        ("unique", Value::Bool(false)),
+        ("multivalue", Value::Bool(true)),
+        ("attributename", Value::new_iutf8("webhook_event")),
+        ("syntax", Value::Syntax(SyntaxType::Utf8StringInsensitive)),
+        ("uuid", Value::Uuid(UUID_SCHEMA_ATTR_WEBHOOK_EVENT))
+    );
+    pub static ref E_SCHEMA_ATTR_WEBHOOK_FILTER: EntryInitNew = entry_init!(
+        ("class", CLASS_OBJECT.clone()),
+        ("class", CLASS_SYSTEM.clone()),
+        ("class", CLASS_ATTRIBUTETYPE.clone()),
+        (
+            "description",
+            Value::new_utf8s("A filter limiting the entries that this webhook is notified of.")
+        ),
+        ("unique", Value::Bool(false)),
+        ("multivalue", Value::Bool(false)),
+        ("attributename", Value::new_iutf8("webhook_filter")),
+        ("syntax", Value::Syntax(SyntaxType::JsonFilter)),
+        ("uuid", Value::Uuid(UUID_SCHEMA_ATTR_WEBHOOK_FILTER))
+    );
+    pub static ref E_SCHEMA_ATTR_WEBHOOK_SECRET: EntryInitNew = entry_init!(
+        ("class", CLASS_OBJECT.clone()),
+        ("class", CLASS_SYSTEM.clone()),
+        ("class", CLASS_ATTRIBUTETYPE.clone()),
+        (
+            "description",
+            Value::new_utf8s("The secret used to sign the events posted to this webhook.")
+        ),
+        ("unique", Value::Bool(false)),
+        ("multivalue", Value::Bool(false)),
+        ("attributename", Value::new_iutf8("webhook_secret")),
+        ("syntax", Value::Syntax(SyntaxType::SecretUtf8String)),
+        ("uuid", Value::Uuid(UUID_SCHEMA_ATTR_WEBHOOK_SECRET))
+    );
+    pub static ref E_SCHEMA_ATTR_WEBHOOK_DEAD_LETTER: EntryInitNew = entry_init!(
+        ("class", CLASS_OBJECT.clone()),
+        ("class", CLASS_SYSTEM.clone()),
+        ("class", CLASS_ATTRIBUTETYPE.clone()),
+        (
+            "description",
+            Value::new_utf8s("Events that could not be delivered to this webhook.")
+        ),
+        ("unique", Value::Bool(false)),
+        ("multivalue", Value::Bool(true)),
+        ("attributename", Value::new_iutf8("webhook_dead_letter")),
+        ("syntax", Value::Syntax(SyntaxType::Utf8String)),
+        ("uuid", Value::Uuid(UUID_SCHEMA_ATTR_WEBHOOK_DEAD_LETTER))
+    );
+    pub static ref E_SCHEMA_CLASS_ACCESS_REQUEST: EntryInitNew = entry_init!(
+        ("class", CLASS_OBJECT.clone()),
+        ("class", CLASS_SYSTEM.clone()),
+        ("class", CLASS_CLASSTYPE.clone()),
+        (
+            "description",
+            Value::new_utf8s("A request for time limited membership of a group")
+        ),
+        ("classname", Value::new_iutf8("access_request")),
+        ("systemmust", Value::new_iutf8("access_request_state")),
+        ("systemmust", Value::new_iutf8("access_request_duration")),
+        ("systemmay", Value::new_iutf8("access_request_requester")),
+        ("systemmay", Value::new_iutf8("access_request_group")),
+        ("systemmay", Value::new_iutf8("access_request_decided_by")),
+        ("systemmay", Value::new_iutf8("access_request_expiry")),
+        ("systemmay", Value::new_iutf8("description")),
+        ("uuid", Value::Uuid(UUID_SCHEMA_CLASS_ACCESS_REQUEST))
+    );
+    pub static ref E_SCHEMA_CLASS_WEBHOOK: EntryInitNew = entry_init!(
+        ("class", CLASS_OBJECT.clone()),
+        ("class", CLASS_SYSTEM.clone()),
+        ("class", CLASS_CLASSTYPE.clone()),
+        (
+            "description",
+            Value::new_utf8s("A subscription that posts directory changes to an external url")
+        ),
+        ("classname", Value::new_iutf8("webhook")),
+        ("systemmust", Value::new_iutf8("name")),
+        ("systemmust", Value::new_iutf8("webhook_url")),
+        ("systemmust", Value::new_iutf8("webhook_event")),
+        ("systemmust", Value::new_iutf8("webhook_secret")),
+        ("systemmay", Value::new_iutf8("webhook_filter")),
+        ("systemmay", Value::new_iutf8("webhook_dead_letter")),
+        ("systemmay", Value::new_iutf8("description")),
+        ("uuid", Value::Uuid(UUID_SCHEMA_CLASS_WEBHOOK))
+    );
+    pub static ref E_SCHEMA_CLASS_OAUTH2_REGISTRAR: EntryInitNew = entry_init!(
+        ("class", CLASS_OBJECT.clone()),
+        ("class", CLASS_SYSTEM.clone()),
+        ("class", CLASS_CLASSTYPE.clone()),
+        (
+            "description",
+            Value::new_utf8s("A service account that may dynamically register oauth2 resource servers")
+        ),
+        ("classname", Value::new_iutf8("oauth2_registrar")),
+        ("systemmay", Value::new_iutf8("oauth2_registrar_domain")),
+        ("systemmay", Value::new_iutf8("oauth2_registrar_scope_map")),
+        ("uuid", Value::Uuid(UUID_SCHEMA_CLASS_OAUTH2_REGISTRAR))
+    );
+    pub static ref E_SCHEMA_CLASS_OUTBOUND_MESSAGE: EntryInitNew = entry_init!(
+        ("class", CLASS_OBJECT.clone()),
+        ("class", CLASS_SYSTEM.clone()),
+        ("class", CLASS_CLASSTYPE.clone()),
+        (
+            "description",
+            Value::new_utf8s("A message that is queued for delivery by mail")
+        ),
+        ("classname", Value::new_iutf8("outbound_message")),
+        ("systemmust", Value::new_iutf8("mail_destination")),
+        ("systemmust", Value::new_iutf8("send_after")),
+        ("systemmay", Value::new_iutf8("message_template")),
+        ("systemmay", Value::new_iutf8("delivery_attempts")),
+        ("uuid", Value::Uuid(UUID_SCHEMA_CLASS_OUTBOUND_MESSAGE))
+    );
 }
 
 // === classes ===
@@ -1515,7 +2097,8 @@ pub const JSON_SCHEMA_CLASS_PERSON: &str = r#"
       ],
       "systemmay": [
         "mail",
-        "legalname"
+        "legalname",
+        "id_verification_key"
       ],
       "systemmust": [
         "displayname",
@@ -1576,8 +2159,12 @@ pub const JSON_SCHEMA_CLASS_GROUP: &str = r#"
       ],
       "systemmay": [
         "member",
+        "member_validity",
         "grant_ui_hint",
-        "description"
+        "description",
+        "entry_managed_by",
+        "access_request_approvers",
+        "access_request_max_duration"
       ],
       "systemmust": [
         "name",
@@ -1651,7 +2238,8 @@ pub const JSON_SCHEMA_CLASS_ACCOUNT: &str = r#"
         "user_auth_token_session",
         "oauth2_session",
         "description",
-        "name_history"
+        "name_history",
+        "password_history"
       ],
       "systemmust": [
         "displayname",
@@ -1690,7 +2278,8 @@ pub const JSON_SCHEMA_CLASS_SERVICE_ACCOUNT: &str = r#"
         "mail",
         "primary_credential",
         "jws_es256_private_key",
-        "api_token_session"
+        "api_token_session",
+        "entry_managed_by"
       ],
       "uuid": [
         "00000000-0000-0000-0000-ffff00000106"
@@ -1825,7 +2414,8 @@ pub const JSON_SCHEMA_CLASS_POSIXACCOUNT: &str = r#"
       ],
       "systemmay": [
         "loginshell",
-        "unix_password"
+        "unix_password",
+        "unix_password_history"
       ],
       "systemmust": [
         "gidnumber"
@@ -1856,7 +2446,9 @@ pub const JSON_SCHEMA_CLASS_SYSTEM_CONFIG: &str = r#"
       ],
       "systemmay": [
         "description",
-        "badlist_password"
+        "badlist_password",
+        "password_history_length",
+        "recycle_bin_retention"
       ],
       "uuid": [
         "00000000-0000-0000-0000-ffff00000060"
@@ -1886,7 +2478,15 @@ pub const JSON_SCHEMA_CLASS_OAUTH2_RS: &str = r#"
         "rs256_private_key_der",
         "oauth2_jwt_legacy_crypto_enable",
         "oauth2_prefer_short_username",
-        "oauth2_rs_origin_landing"
+        "oauth2_rs_origin_landing",
+        "oauth2_rs_backchannel_logout_uri",
+        "oauth2_rs_token_exchange_from",
+        "oauth2_rs_jwks",
+        "oauth2_rs_tls_client_cert",
+        "oauth2_require_pushed_authorisation",
+        "oauth2_rs_registered_by",
+        "oauth2_consent_prompt_disable",
+        "oauth2_rs_redirect_uri"
       ],
       "systemmust": [
         "oauth2_rs_name",
@@ -57,9 +57,10 @@ pub const UUID_IDM_HP_SYNC_ACCOUNT_MANAGE_PRIV: Uuid =
 pub const UUID_IDM_UI_ENABLE_EXPERIMENTAL_FEATURES: Uuid =
     uuid!("00000000-0000-0000-0000-000000000038");
 pub const UUID_IDM_ACCOUNT_MAIL_READ_PRIV: Uuid = uuid!("00000000-0000-0000-0000-000000000039");
+pub const UUID_IDM_HELPDESK: Uuid = uuid!("00000000-0000-0000-0000-000000000040");
 
 //
-pub const _UUID_IDM_HIGH_PRIVILEGE: Uuid = uuid!("00000000-0000-0000-0000-000000001000");
+pub const UUID_IDM_HIGH_PRIVILEGE: Uuid = uuid!("00000000-0000-0000-0000-000000001000");
 
 // Builtin schema
 pub const UUID_SCHEMA_ATTR_CLASS: Uuid = uuid!("00000000-0000-0000-0000-ffff00000000");
@@ -236,6 +237,70 @@ pub const UUID_SCHEMA_ATTR_SYNC_YIELD_AUTHORITY: Uuid =
     uuid!("00000000-0000-0000-0000-ffff00000138");
 pub const UUID_SCHEMA_CLASS_CONFLICT: Uuid = uuid!("00000000-0000-0000-0000-ffff00000139");
 pub const UUID_SCHEMA_ATTR_SOURCE_UUID: Uuid = uuid!("00000000-0000-0000-0000-ffff00000140");
+pub const UUID_SCHEMA_ATTR_PASSWORD_HISTORY: Uuid = uuid!("00000000-0000-0000-0000-ffff00000141");
+pub const UUID_SCHEMA_ATTR_UNIX_PASSWORD_HISTORY: Uuid =
+    uuid!("00000000-0000-0000-0000-ffff00000142");
+pub const UUID_SCHEMA_ATTR_PASSWORD_HISTORY_LENGTH: Uuid =
+    uuid!("00000000-0000-0000-0000-ffff00000143");
+pub const UUID_SCHEMA_ATTR_DEPRECATED: Uuid = uuid!("00000000-0000-0000-0000-ffff00000144");
+pub const UUID_SCHEMA_ATTR_ENTRY_MANAGED_BY: Uuid = uuid!("00000000-0000-0000-0000-ffff00000145");
+pub const UUID_SCHEMA_CLASS_ACCESS_CONTROL_ENTRY_MANAGER: Uuid =
+    uuid!("00000000-0000-0000-0000-ffff00000146");
+pub const UUID_SCHEMA_ATTR_MEMBER_VALIDITY: Uuid = uuid!("00000000-0000-0000-0000-ffff00000147");
+pub const UUID_SCHEMA_ATTR_ACCESS_REQUEST_APPROVERS: Uuid =
+    uuid!("00000000-0000-0000-0000-ffff00000148");
+pub const UUID_SCHEMA_ATTR_ACCESS_REQUEST_MAX_DURATION: Uuid =
+    uuid!("00000000-0000-0000-0000-ffff00000149");
+pub const UUID_SCHEMA_ATTR_ACCESS_REQUEST_REQUESTER: Uuid =
+    uuid!("00000000-0000-0000-0000-ffff00000150");
+pub const UUID_SCHEMA_ATTR_ACCESS_REQUEST_GROUP: Uuid =
+    uuid!("00000000-0000-0000-0000-ffff00000151");
+pub const UUID_SCHEMA_ATTR_ACCESS_REQUEST_STATE: Uuid =
+    uuid!("00000000-0000-0000-0000-ffff00000152");
+pub const UUID_SCHEMA_ATTR_ACCESS_REQUEST_DURATION: Uuid =
+    uuid!("00000000-0000-0000-0000-ffff00000153");
+pub const UUID_SCHEMA_ATTR_ACCESS_REQUEST_DECIDED_BY: Uuid =
+    uuid!("00000000-0000-0000-0000-ffff00000154");
+pub const UUID_SCHEMA_ATTR_ACCESS_REQUEST_EXPIRY: Uuid =
+    uuid!("00000000-0000-0000-0000-ffff00000155");
+pub const UUID_SCHEMA_CLASS_ACCESS_REQUEST: Uuid = uuid!("00000000-0000-0000-0000-ffff00000156");
+pub const UUID_SCHEMA_ATTR_UNIQUE_SCOPE: Uuid = uuid!("00000000-0000-0000-0000-ffff00000157");
+pub const UUID_SCHEMA_ATTR_RECYCLE_BIN_RETENTION: Uuid =
+    uuid!("00000000-0000-0000-0000-ffff00000158");
+pub const UUID_SCHEMA_ATTR_WEBHOOK_URL: Uuid = uuid!("00000000-0000-0000-0000-ffff00000159");
+pub const UUID_SCHEMA_ATTR_WEBHOOK_EVENT: Uuid = uuid!("00000000-0000-0000-0000-ffff00000160");
+pub const UUID_SCHEMA_ATTR_WEBHOOK_FILTER: Uuid = uuid!("00000000-0000-0000-0000-ffff00000161");
+pub const UUID_SCHEMA_ATTR_WEBHOOK_SECRET: Uuid = uuid!("00000000-0000-0000-0000-ffff00000162");
+pub const UUID_SCHEMA_ATTR_WEBHOOK_DEAD_LETTER: Uuid =
+    uuid!("00000000-0000-0000-0000-ffff00000163");
+pub const UUID_SCHEMA_CLASS_WEBHOOK: Uuid = uuid!("00000000-0000-0000-0000-ffff00000164");
+pub const UUID_SCHEMA_ATTR_OAUTH2_RS_BACKCHANNEL_LOGOUT_URI: Uuid =
+    uuid!("00000000-0000-0000-0000-ffff00000165");
+pub const UUID_SCHEMA_ATTR_OAUTH2_RS_TOKEN_EXCHANGE_FROM: Uuid =
+    uuid!("00000000-0000-0000-0000-ffff00000166");
+pub const UUID_SCHEMA_ATTR_OAUTH2_RS_JWKS: Uuid = uuid!("00000000-0000-0000-0000-ffff00000167");
+pub const UUID_SCHEMA_ATTR_OAUTH2_RS_TLS_CLIENT_CERT: Uuid =
+    uuid!("00000000-0000-0000-0000-ffff00000168");
+pub const UUID_SCHEMA_ATTR_OAUTH2_REQUIRE_PUSHED_AUTHORISATION: Uuid =
+    uuid!("00000000-0000-0000-0000-ffff00000169");
+pub const UUID_SCHEMA_ATTR_OAUTH2_REGISTRAR_DOMAIN: Uuid =
+    uuid!("00000000-0000-0000-0000-ffff00000170");
+pub const UUID_SCHEMA_ATTR_OAUTH2_REGISTRAR_SCOPE_MAP: Uuid =
+    uuid!("00000000-0000-0000-0000-ffff00000171");
+pub const UUID_SCHEMA_ATTR_OAUTH2_RS_REGISTERED_BY: Uuid =
+    uuid!("00000000-0000-0000-0000-ffff00000172");
+pub const UUID_SCHEMA_CLASS_OAUTH2_REGISTRAR: Uuid = uuid!("00000000-0000-0000-0000-ffff00000173");
+pub const UUID_SCHEMA_ATTR_OAUTH2_CONSENT_PROMPT_DISABLE: Uuid =
+    uuid!("00000000-0000-0000-0000-ffff00000174");
+pub const UUID_SCHEMA_ATTR_ID_VERIFICATION_KEY: Uuid =
+    uuid!("00000000-0000-0000-0000-ffff00000175");
+pub const UUID_SCHEMA_ATTR_MAIL_DESTINATION: Uuid = uuid!("00000000-0000-0000-0000-ffff00000176");
+pub const UUID_SCHEMA_ATTR_MESSAGE_TEMPLATE: Uuid = uuid!("00000000-0000-0000-0000-ffff00000177");
+pub const UUID_SCHEMA_ATTR_SEND_AFTER: Uuid = uuid!("00000000-0000-0000-0000-ffff00000178");
+pub const UUID_SCHEMA_ATTR_DELIVERY_ATTEMPTS: Uuid = uuid!("00000000-0000-0000-0000-ffff00000179");
+pub const UUID_SCHEMA_CLASS_OUTBOUND_MESSAGE: Uuid = uuid!("00000000-0000-0000-0000-ffff00000180");
+pub const UUID_SCHEMA_ATTR_OAUTH2_RS_REDIRECT_URI: Uuid =
+    uuid!("00000000-0000-0000-0000-ffff00000181");
 
 // System and domain infos
 // I'd like to strongly criticise william of the past for making poor choices about these allocations.
@@ -315,6 +380,13 @@ pub const UUID_IDM_HP_ACP_SYNC_ACCOUNT_MANAGE_PRIV_V1: Uuid =
 pub const UUID_IDM_ACP_ACCOUNT_MAIL_READ_PRIV_V1: Uuid =
     uuid!("00000000-0000-0000-0000-ffffff000045");
 pub const UUID_IDM_ACCOUNT_SELF_ACP_WRITE_V1: Uuid = uuid!("00000000-0000-0000-0000-ffffff000046");
+pub const UUID_IDM_ACP_GROUP_ENTRY_MANAGER_V1: Uuid = uuid!("00000000-0000-0000-0000-ffffff000047");
+pub const UUID_IDM_ACP_SERVICE_ACCOUNT_ENTRY_MANAGER_V1: Uuid =
+    uuid!("00000000-0000-0000-0000-ffffff000048");
+pub const UUID_IDM_ACP_WEBHOOK_MANAGE_V1: Uuid = uuid!("00000000-0000-0000-0000-ffffff000049");
+pub const UUID_IDM_ACP_OAUTH2_REGISTRAR_MANAGE_V1: Uuid =
+    uuid!("00000000-0000-0000-0000-ffffff000050");
+pub const UUID_IDM_ACP_HELPDESK_V1: Uuid = uuid!("00000000-0000-0000-0000-ffffff000051");
 
 // End of system ranges
 pub const UUID_DOES_NOT_EXIST: Uuid = uuid!("00000000-0000-0000-0000-fffffffffffe");
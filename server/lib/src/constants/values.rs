@@ -8,10 +8,13 @@ lazy_static! {
         Url::parse("https://kanidm.github.io/kanidm/master/integrations/oauth2.html")
             .expect("Failed to parse oauth2 service documentation url");
     pub static ref PV_FALSE: PartialValue = PartialValue::new_bool(false);
+    pub static ref PVCLASS_ACCESS_REQUEST: PartialValue = PartialValue::new_class("access_request");
     pub static ref PVCLASS_ACCOUNT: PartialValue = PartialValue::new_class("account");
     pub static ref PVCLASS_ACS: PartialValue = PartialValue::new_class("access_control_search");
     pub static ref PVCLASS_ACC: PartialValue = PartialValue::new_class("access_control_create");
     pub static ref PVCLASS_ACD: PartialValue = PartialValue::new_class("access_control_delete");
+    pub static ref PVCLASS_ACEM: PartialValue =
+        PartialValue::new_class("access_control_entry_manager");
     pub static ref PVCLASS_ACM: PartialValue = PartialValue::new_class("access_control_modify");
     pub static ref PVCLASS_ACP: PartialValue = PartialValue::new_class("access_control_profile");
     pub static ref PVCLASS_ATTRIBUTETYPE: PartialValue = PartialValue::new_class("attributetype");
@@ -27,6 +30,10 @@ lazy_static! {
         PartialValue::new_class("oauth2_resource_server_basic");
     pub static ref PVCLASS_OAUTH2_PUBLIC: PartialValue =
         PartialValue::new_class("oauth2_resource_server_public");
+    pub static ref PVCLASS_OAUTH2_REGISTRAR: PartialValue =
+        PartialValue::new_class("oauth2_registrar");
+    pub static ref PVCLASS_OUTBOUND_MESSAGE: PartialValue =
+        PartialValue::new_class("outbound_message");
     pub static ref PVCLASS_PERSON: PartialValue = PartialValue::new_class("person");
     pub static ref PVCLASS_POSIXACCOUNT: PartialValue = PartialValue::new_class("posixaccount");
     pub static ref PVCLASS_POSIXGROUP: PartialValue = PartialValue::new_class("posixgroup");
@@ -39,12 +46,16 @@ lazy_static! {
     pub static ref PVCLASS_SYSTEM_INFO: PartialValue = PartialValue::new_class("system_info");
     pub static ref PVCLASS_SYSTEM_CONFIG: PartialValue = PartialValue::new_class("system_config");
     pub static ref PVCLASS_TOMBSTONE: PartialValue = PartialValue::new_class("tombstone");
+    pub static ref PVCLASS_WEBHOOK: PartialValue = PartialValue::new_class("webhook");
     pub static ref PVUUID_DOMAIN_INFO: PartialValue = PartialValue::Uuid(UUID_DOMAIN_INFO);
     pub static ref PVUUID_SYSTEM_CONFIG: PartialValue = PartialValue::Uuid(UUID_SYSTEM_CONFIG);
     pub static ref PVUUID_SYSTEM_INFO: PartialValue = PartialValue::Uuid(UUID_SYSTEM_INFO);
+    pub static ref CLASS_ACCESS_REQUEST: Value = Value::new_class("access_request");
     pub static ref CLASS_ACCESS_CONTROL_PROFILE: Value = Value::new_class("access_control_profile");
     pub static ref CLASS_ACCESS_CONTROL_CREATE: Value = Value::new_class("access_control_create");
     pub static ref CLASS_ACCESS_CONTROL_DELETE: Value = Value::new_class("access_control_delete");
+    pub static ref CLASS_ACCESS_CONTROL_ENTRY_MANAGER: Value =
+        Value::new_class("access_control_entry_manager");
     pub static ref CLASS_ACCESS_CONTROL_MODIFY: Value = Value::new_class("access_control_modify");
     pub static ref CLASS_ACCESS_CONTROL_SEARCH: Value = Value::new_class("access_control_search");
     pub static ref CLASS_ACCOUNT: Value = Value::new_class("account");
@@ -56,6 +67,7 @@ lazy_static! {
     pub static ref CLASS_GROUP: Value = Value::new_class("group");
     pub static ref CLASS_MEMBEROF: Value = Value::new_class("memberof");
     pub static ref CLASS_OBJECT: Value = Value::new_class("object");
+    pub static ref CLASS_OUTBOUND_MESSAGE: Value = Value::new_class("outbound_message");
     pub static ref CLASS_PERSON: Value = Value::new_class("person");
     pub static ref CLASS_RECYCLED: Value = Value::new_class("recycled");
     pub static ref CLASS_SERVICE_ACCOUNT: Value = Value::new_class("service_account");
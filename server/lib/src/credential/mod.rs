@@ -32,6 +32,19 @@ pub enum Policy {
 
 pub use kanidm_lib_crypto::Password;
 
+/// Check if the cleartext matches any of these previously used passwords.
+pub(crate) fn password_history_contains<'a>(
+    mut history: impl Iterator<Item = &'a Password>,
+    cleartext: &str,
+) -> bool {
+    history.any(|pw| {
+        pw.verify(cleartext).unwrap_or_else(|e| {
+            error!(crypto_err = ?e);
+            false
+        })
+    })
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct BackupCodes {
     code_set: HashSet<String>,
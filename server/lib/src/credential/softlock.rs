@@ -59,12 +59,14 @@ use std::time::Duration;
 //
 
 const ONEDAY: u64 = 86400;
+const ONEHOUR: u64 = 3600;
 
 #[derive(Debug, Clone)]
 pub enum CredSoftLockPolicy {
     Password,
     Totp(u64),
     Webauthn,
+    IdentityVerification,
     Unrestricted,
 }
 
@@ -112,6 +114,16 @@ impl CredSoftLockPolicy {
                     ct + Duration::from_secs(1),
                 )
             }
+            CredSoftLockPolicy::IdentityVerification => {
+                // Codes are read aloud, so we allow for a few mistakes without a delay. Past
+                // that, the pair is locked until an hour after the last failure.
+                let reset_at = ct + Duration::from_secs(ONEHOUR);
+                if count >= 3 {
+                    LockState::Locked(count, reset_at, reset_at)
+                } else {
+                    LockState::Unlocked(count, reset_at)
+                }
+            }
             CredSoftLockPolicy::Unrestricted => {
                 // No action needed
                 LockState::Init
@@ -198,6 +210,15 @@ impl CredSoftLock {
         std::mem::swap(&mut self.state, &mut next_state);
     }
 
+    /// Has this softlock reset by this point in time, so that it no longer needs to be kept.
+    pub fn is_expired(&self, ct: Duration) -> bool {
+        match self.state {
+            LockState::Init => true,
+            LockState::Locked(_count, reset_at, _unlock_at) => ct > reset_at,
+            LockState::Unlocked(_count, reset_at) => ct > reset_at,
+        }
+    }
+
     #[cfg(test)]
     pub fn is_state_init(&self) -> bool {
         match self.state {
@@ -367,4 +388,24 @@ mod tests {
                 == LockState::Locked(1000, Duration::from_secs(1), Duration::from_secs(1))
         );
     }
+
+    #[test]
+    fn test_credential_softlock_policy_identity_verification() {
+        let policy = CredSoftLockPolicy::IdentityVerification;
+
+        // The first few failures don't delay the next attempt.
+        assert!(
+            policy.failure_next_state(2, Duration::from_secs(10))
+                == LockState::Unlocked(2, Duration::from_secs(10 + ONEHOUR))
+        );
+
+        assert!(
+            policy.failure_next_state(3, Duration::from_secs(10))
+                == LockState::Locked(
+                    3,
+                    Duration::from_secs(10 + ONEHOUR),
+                    Duration::from_secs(10 + ONEHOUR)
+                )
+        );
+    }
 }
@@ -45,7 +45,7 @@ use webauthn_rs::prelude::{DeviceKey as DeviceKeyV4, Passkey as PasskeyV4};
 use crate::be::dbentry::{DbEntry, DbEntryV2, DbEntryVers};
 use crate::be::dbvalue::DbValueSetV2;
 use crate::be::{IdxKey, IdxSlope};
-use crate::credential::Credential;
+use crate::credential::{Credential, Password};
 use crate::filter::{Filter, FilterInvalid, FilterResolved, FilterValidResolved};
 use crate::idm::ldap::ldap_vattr_map;
 use crate::modify::{Modify, ModifyInvalid, ModifyList, ModifyValid};
@@ -56,7 +56,8 @@ use crate::repl::proto::{ReplEntryV1, ReplIncrementalEntryV1};
 
 use crate::schema::{SchemaAttribute, SchemaClass, SchemaTransaction};
 use crate::value::{
-    ApiToken, IndexType, IntentTokenState, Oauth2Session, PartialValue, Session, SyntaxType, Value,
+    ApiToken, IndexType, IntentTokenState, MemberValidity, Oauth2Session, PartialValue, Session,
+    SyntaxType, Value,
 };
 use crate::valueset::{self, ValueSet};
 
@@ -660,6 +661,10 @@ impl<STATE> Entry<EntryIncremental, STATE> {
     pub fn get_uuid(&self) -> Uuid {
         self.valid.uuid
     }
+
+    pub(crate) fn get_changestate(&self) -> &EntryChangeState {
+        &self.valid.ecstate
+    }
 }
 
 impl Entry<EntryIncremental, EntryNew> {
@@ -1059,6 +1064,14 @@ impl Entry<EntryIncremental, EntryCommitted> {
         }
         ne
     }
+
+    /// Move this entry to a conflict state, such as when a plugin determines that
+    /// it can not co-exist with the current content of the database.
+    pub(crate) fn set_conflict(&mut self) {
+        self.add_ava_int("class", Value::new_class("recycled"));
+        self.add_ava_int("class", Value::new_class("conflict"));
+        self.add_ava_int("source_uuid", Value::Uuid(self.valid.uuid));
+    }
 }
 
 impl<STATE> Entry<EntryInvalid, STATE> {
@@ -2459,6 +2472,12 @@ impl<VALID, STATE> Entry<VALID, STATE> {
         self.get_ava_set(attr).and_then(|vs| vs.as_iutf8_iter())
     }
 
+    #[inline(always)]
+    /// If possible, return an iterator over the set of values transformed into a `&str`.
+    pub fn get_ava_iter_utf8(&self, attr: &str) -> Option<impl Iterator<Item = &str>> {
+        self.get_ava_set(attr).and_then(|vs| vs.as_utf8_iter())
+    }
+
     #[inline(always)]
     /// If possible, return an iterator over the set of values transformed into a `Uuid`.
     pub fn get_ava_as_refuuid(&self, attr: &str) -> Option<Box<dyn Iterator<Item = Uuid> + '_>> {
@@ -2533,6 +2552,20 @@ impl<VALID, STATE> Entry<VALID, STATE> {
             .and_then(|vs| vs.to_credential_single())
     }
 
+    #[inline(always)]
+    /// Get the previously used passwords of this account, if any are present.
+    pub fn get_ava_password_history(&self, attr: &str) -> Option<&BTreeMap<Cid, Password>> {
+        self.attrs.get(attr).and_then(|vs| vs.as_password_history())
+    }
+
+    #[inline(always)]
+    /// Get the validity periods of the time bounded members of this group, if any are present.
+    pub fn get_ava_member_validity(&self, attr: &str) -> Option<&BTreeMap<Uuid, MemberValidity>> {
+        self.attrs
+            .get(attr)
+            .and_then(|vs| vs.as_member_validity_map())
+    }
+
     #[inline(always)]
     /// Get the set of passkeys on this account, if any are present.
     pub fn get_ava_passkeys(&self, attr: &str) -> Option<&BTreeMap<Uuid, (String, PasskeyV4)>> {
@@ -3053,6 +3086,9 @@ impl From<&SchemaAttribute> for Entry<EntryInit, EntryNew> {
         attrs.insert(AttrString::from("sync_allowed"), sync_allowed_v);
         attrs.insert(AttrString::from("replicated"), replicated_v);
         attrs.insert(AttrString::from("unique"), unique_v);
+        if let Some(scope) = &s.unique_scope {
+            attrs.insert(AttrString::from("unique_scope"), vs_iutf8![scope.as_str()]);
+        }
         if let Some(vs) = index_v {
             attrs.insert(AttrString::from("index"), vs);
         }
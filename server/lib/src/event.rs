@@ -789,6 +789,27 @@ impl PurgeRecycledEvent {
     }
 }
 
+#[derive(Debug)]
+pub struct PurgeMemberValidityEvent {
+    pub ident: Identity,
+    pub eventid: Uuid,
+}
+
+impl Default for PurgeMemberValidityEvent {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PurgeMemberValidityEvent {
+    pub fn new() -> Self {
+        PurgeMemberValidityEvent {
+            ident: Identity::from_internal(),
+            eventid: Uuid::new_v4(),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct OnlineBackupEvent {
     pub ident: Identity,
@@ -819,19 +840,28 @@ pub struct ReviveRecycledEvent {
     // to be retained, because the filter is the orig filter for this check.
     //
     // It will be duplicated into the modify ident as it exists.
+    //
+    // If the group memberships that were removed when the entry was recycled should
+    // be restored.
+    pub restore_memberships: bool,
 }
 
 impl ReviveRecycledEvent {
     pub fn from_parts(
         ident: Identity,
         filter: &Filter<FilterInvalid>,
+        restore_memberships: bool,
         qs: &QueryServerWriteTransaction,
     ) -> Result<Self, OperationError> {
         let filter = filter
             .validate(qs.get_schema())
             .map(|f| f.into_recycled())
             .map_err(OperationError::SchemaViolation)?;
-        Ok(ReviveRecycledEvent { ident, filter })
+        Ok(ReviveRecycledEvent {
+            ident,
+            filter,
+            restore_memberships,
+        })
     }
 
     /// ⚠️  - Bypass the schema state machine and force the filter to be considered valid.
@@ -844,6 +874,7 @@ impl ReviveRecycledEvent {
         ReviveRecycledEvent {
             ident: Identity::from_impersonate_entry_readwrite(e),
             filter: filter.into_valid(),
+            restore_memberships: true,
         }
     }
 
@@ -852,6 +883,41 @@ impl ReviveRecycledEvent {
         ReviveRecycledEvent {
             ident: Identity::from_internal(),
             filter,
+            restore_memberships: true,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct PurgeRecycledEntryEvent {
+    pub ident: Identity,
+    // This is the filter, as it will be processed.
+    pub filter: Filter<FilterValid>,
+}
+
+impl PurgeRecycledEntryEvent {
+    pub fn from_parts(
+        ident: Identity,
+        filter: &Filter<FilterInvalid>,
+        qs: &QueryServerWriteTransaction,
+    ) -> Result<Self, OperationError> {
+        let filter = filter
+            .validate(qs.get_schema())
+            .map(|f| f.into_recycled())
+            .map_err(OperationError::SchemaViolation)?;
+        Ok(PurgeRecycledEntryEvent { ident, filter })
+    }
+
+    /// ⚠️  - Bypass the schema state machine and force the filter to be considered valid.
+    /// This is a TEST ONLY method and will never be exposed in production.
+    #[cfg(test)]
+    pub fn new_impersonate_entry(
+        e: Arc<Entry<EntrySealed, EntryCommitted>>,
+        filter: Filter<FilterInvalid>,
+    ) -> Self {
+        PurgeRecycledEntryEvent {
+            ident: Identity::from_impersonate_entry_readwrite(e),
+            filter: filter.into_valid(),
         }
     }
 }
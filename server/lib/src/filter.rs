@@ -30,7 +30,7 @@ use crate::prelude::*;
 use crate::schema::SchemaTransaction;
 use crate::value::{IndexType, PartialValue};
 
-const FILTER_DEPTH_MAX: usize = 16;
+pub(crate) const FILTER_DEPTH_MAX: usize = 16;
 
 // Default filter is safe, ignores all hidden types!
 
@@ -374,6 +374,24 @@ impl Filter<FilterValid> {
             },
         }
     }
+
+    /// Constrain this filter to entries where the reference attribute `attr` refers to the
+    /// identity, or to a group that the identity is a member of.
+    pub fn constrain_to_ident_refer(&self, attr: &str, ident: &Identity) -> Self {
+        // The caller must ensure that attr is a reference type in the schema.
+        let refers = ident
+            .get_uuid()
+            .into_iter()
+            .chain(ident.get_memberof().into_iter().flatten().copied())
+            .map(|u| FilterComp::Eq(AttrString::from(attr), PartialValue::Refer(u)))
+            .collect();
+
+        Filter {
+            state: FilterValid {
+                inner: FilterComp::And(vec![self.state.inner.clone(), FilterComp::Or(refers)]),
+            },
+        }
+    }
 }
 
 impl Filter<FilterInvalid> {
@@ -0,0 +1,527 @@
+use kanidm_proto::v1::{AccessCheckAttrs, AccessCheckResponse, AccessProfile};
+
+use crate::idm::server::{IdmServerProxyReadTransaction, IdmServerProxyWriteTransaction};
+use crate::prelude::*;
+use crate::server::access::{Access, AccessControlsTransaction};
+
+pub struct AccessProfileCreateEvent {
+    pub ident: Identity,
+    pub profile: AccessProfile,
+}
+
+pub struct AccessProfileUpdateEvent {
+    pub ident: Identity,
+    pub target: Uuid,
+    pub profile: AccessProfile,
+}
+
+pub struct AccessProfileCheckEvent {
+    pub ident: Identity,
+    // The identity whose access is being evaluated.
+    pub subject: Uuid,
+    pub target: Uuid,
+}
+
+// The rights a profile grants are determined by which access control classes it has, and
+// each of these classes has its own set of attributes.
+const ACP_SEARCH_ATTRS: [&str; 1] = ["acp_search_attr"];
+const ACP_MODIFY_ATTRS: [&str; 3] = [
+    "acp_modify_presentattr",
+    "acp_modify_removedattr",
+    "acp_modify_class",
+];
+const ACP_CREATE_ATTRS: [&str; 2] = ["acp_create_attr", "acp_create_class"];
+
+fn profile_lists(ap: &AccessProfile) -> [(&'static str, Option<&Vec<String>>); 6] {
+    [
+        ("acp_search_attr", ap.search_attrs.as_ref()),
+        ("acp_modify_presentattr", ap.modify_present_attrs.as_ref()),
+        ("acp_modify_removedattr", ap.modify_removed_attrs.as_ref()),
+        ("acp_modify_class", ap.modify_classes.as_ref()),
+        ("acp_create_attr", ap.create_attrs.as_ref()),
+        ("acp_create_class", ap.create_classes.as_ref()),
+    ]
+}
+
+impl From<Access> for AccessCheckAttrs {
+    fn from(a: Access) -> Self {
+        match a {
+            Access::Grant => AccessCheckAttrs::Grant,
+            Access::Denied => AccessCheckAttrs::Denied,
+            Access::Allow(attrs) => {
+                AccessCheckAttrs::Allow(attrs.into_iter().map(|s| s.to_string()).collect())
+            }
+        }
+    }
+}
+
+impl<'a> IdmServerProxyWriteTransaction<'a> {
+    pub fn access_profile_create(
+        &mut self,
+        ev: &AccessProfileCreateEvent,
+    ) -> Result<(), OperationError> {
+        let ap = &ev.profile;
+
+        let (Some(name), Some(receiver), Some(targetscope)) = (
+            ap.name.as_deref(),
+            ap.receiver_group.as_deref(),
+            ap.targetscope.as_ref(),
+        ) else {
+            admin_error!("Access profile requires a name, receiver group and targetscope");
+            return Err(OperationError::InvalidAcpState(
+                "a name, receiver group and targetscope are required".to_string(),
+            ));
+        };
+
+        let receiver = self.qs_write.name_to_uuid(receiver).map_err(|e| {
+            admin_error!(?e, "Unable to resolve receiver group");
+            e
+        })?;
+
+        let mut e = entry_init!(
+            ("class", Value::new_class("access_control_profile")),
+            ("name", Value::new_iname(name)),
+            ("acp_receiver_group", Value::Refer(receiver)),
+            ("acp_targetscope", Value::JsonFilt(targetscope.clone()))
+        );
+
+        if let Some(description) = ap.description.as_deref().filter(|d| !d.is_empty()) {
+            e.add_ava("description", Value::new_utf8s(description));
+        }
+        if let Some(enabled) = ap.enabled {
+            e.add_ava("acp_enable", Value::new_bool(enabled));
+        }
+
+        for (attr, values) in profile_lists(ap) {
+            values
+                .into_iter()
+                .flatten()
+                .for_each(|v| e.add_ava(attr, Value::new_iutf8(v)));
+        }
+
+        let has_any = |attrs: &[&str]| attrs.iter().any(|a| e.attribute_pres(a));
+        let search = has_any(&ACP_SEARCH_ATTRS);
+        let modify = has_any(&ACP_MODIFY_ATTRS);
+        let create = has_any(&ACP_CREATE_ATTRS);
+        let delete = ap.delete.unwrap_or(false);
+
+        if search {
+            e.add_ava("class", Value::new_class("access_control_search"));
+        }
+        if modify {
+            e.add_ava("class", Value::new_class("access_control_modify"));
+        }
+        if create {
+            e.add_ava("class", Value::new_class("access_control_create"));
+        }
+        if delete {
+            e.add_ava("class", Value::new_class("access_control_delete"));
+        }
+
+        let ce = CreateEvent {
+            ident: ev.ident.clone(),
+            entries: vec![e],
+        };
+        self.qs_write.create(&ce)
+    }
+
+    pub fn access_profile_update(
+        &mut self,
+        ev: &AccessProfileUpdateEvent,
+    ) -> Result<(), OperationError> {
+        let ap = &ev.profile;
+
+        // We need the current state of the profile to determine which rights it will
+        // have once updated. Profiles the caller can't see are treated as though they
+        // don't exist, and the modify below is still subject to access controls.
+        let filter = filter!(f_and!([
+            f_eq("uuid", PartialValue::Uuid(ev.target)),
+            f_eq("class", PVCLASS_ACP.clone())
+        ]));
+        let current = self
+            .qs_write
+            .impersonate_search(filter.clone(), filter, &ev.ident)?
+            .pop()
+            .ok_or(OperationError::NoMatchingEntries)?;
+
+        let mut mods = Vec::new();
+
+        if let Some(name) = ap.name.as_deref() {
+            mods.push(Modify::Purged(AttrString::from("name")));
+            mods.push(Modify::Present(
+                AttrString::from("name"),
+                Value::new_iname(name),
+            ));
+        }
+        if let Some(description) = ap.description.as_deref() {
+            mods.push(Modify::Purged(AttrString::from("description")));
+            if !description.is_empty() {
+                mods.push(Modify::Present(
+                    AttrString::from("description"),
+                    Value::new_utf8s(description),
+                ));
+            }
+        }
+        if let Some(enabled) = ap.enabled {
+            mods.push(Modify::Purged(AttrString::from("acp_enable")));
+            mods.push(Modify::Present(
+                AttrString::from("acp_enable"),
+                Value::new_bool(enabled),
+            ));
+        }
+        if let Some(receiver) = ap.receiver_group.as_deref() {
+            let receiver = self.qs_write.name_to_uuid(receiver).map_err(|e| {
+                admin_error!(?e, "Unable to resolve receiver group");
+                e
+            })?;
+            mods.push(Modify::Purged(AttrString::from("acp_receiver_group")));
+            mods.push(Modify::Present(
+                AttrString::from("acp_receiver_group"),
+                Value::Refer(receiver),
+            ));
+        }
+        if let Some(targetscope) = ap.targetscope.as_ref() {
+            mods.push(Modify::Purged(AttrString::from("acp_targetscope")));
+            mods.push(Modify::Present(
+                AttrString::from("acp_targetscope"),
+                Value::JsonFilt(targetscope.clone()),
+            ));
+        }
+
+        let lists = profile_lists(ap);
+        for (attr, values) in lists.iter() {
+            if let Some(values) = values {
+                mods.push(Modify::Purged(AttrString::from(*attr)));
+                values.iter().for_each(|v| {
+                    mods.push(Modify::Present(
+                        AttrString::from(*attr),
+                        Value::new_iutf8(v),
+                    ))
+                });
+            }
+        }
+
+        // A list that was provided replaces the current one, otherwise the current
+        // values are retained.
+        let has_any = |attrs: &[&str]| {
+            attrs.iter().any(|a| {
+                match lists
+                    .iter()
+                    .find(|(attr, _)| attr == a)
+                    .and_then(|(_, v)| *v)
+                {
+                    Some(values) => !values.is_empty(),
+                    None => current.attribute_pres(a),
+                }
+            })
+        };
+
+        let rights = [
+            ("access_control_search", has_any(&ACP_SEARCH_ATTRS)),
+            ("access_control_modify", has_any(&ACP_MODIFY_ATTRS)),
+            ("access_control_create", has_any(&ACP_CREATE_ATTRS)),
+            (
+                "access_control_delete",
+                ap.delete
+                    .unwrap_or_else(|| current.attribute_equality("class", &PVCLASS_ACD)),
+            ),
+        ];
+
+        for (class, granted) in rights {
+            let present = current.attribute_equality("class", &PartialValue::new_class(class));
+            if granted && !present {
+                mods.push(Modify::Present(
+                    AttrString::from("class"),
+                    Value::new_class(class),
+                ));
+            } else if !granted && present {
+                mods.push(Modify::Removed(
+                    AttrString::from("class"),
+                    PartialValue::new_class(class),
+                ));
+            }
+        }
+
+        let modlist = ModifyList::new_list(mods);
+
+        self.qs_write
+            .impersonate_modify(
+                // Filter as executed
+                &filter!(f_eq("uuid", PartialValue::Uuid(ev.target))),
+                // Filter as intended (acp)
+                &filter_all!(f_eq("uuid", PartialValue::Uuid(ev.target))),
+                &modlist,
+                &ev.ident,
+            )
+            .map_err(|e| {
+                admin_error!("Failed to update access profile {:?}", e);
+                e
+            })
+    }
+}
+
+impl<'a> IdmServerProxyReadTransaction<'a> {
+    pub fn access_profile_check(
+        &mut self,
+        ev: &AccessProfileCheckEvent,
+    ) -> Result<AccessCheckResponse, OperationError> {
+        // This reveals the effect of the access profiles, so the caller must be able to
+        // read them.
+        let f_acp = filter!(f_eq("class", PVCLASS_ACP.clone()));
+        let acps = self
+            .qs_read
+            .impersonate_search(f_acp.clone(), f_acp, &ev.ident)?;
+        if acps.is_empty() {
+            security_access!("denied ❌ - access profiles are not visible to the requestor");
+            return Err(OperationError::AccessDenied);
+        }
+
+        // The caller must also be able to see both the subject and target.
+        let mut search_uuid = |uuid: Uuid| {
+            let f_uuid = filter!(f_eq("uuid", PartialValue::Uuid(uuid)));
+            self.qs_read
+                .impersonate_search(f_uuid.clone(), f_uuid, &ev.ident)
+                .and_then(|mut r| r.pop().ok_or(OperationError::NoMatchingEntries))
+        };
+
+        let subject = search_uuid(ev.subject)?;
+        let target = search_uuid(ev.target)?;
+
+        if !subject.attribute_equality("class", &PVCLASS_ACCOUNT) {
+            return Err(OperationError::InvalidAccountState(
+                "Only accounts may be granted access".to_string(),
+            ));
+        }
+
+        let subject_ident = Identity::from_entry_for_access_check(subject);
+
+        let perm = self
+            .qs_read
+            .get_accesscontrols()
+            .effective_permission_check(&subject_ident, None, &[target])?
+            .pop()
+            .ok_or(OperationError::InvalidState)?;
+
+        Ok(AccessCheckResponse {
+            subject: ev.subject,
+            target: perm.target,
+            search: perm.search.into(),
+            modify_present: perm.modify_pres.into(),
+            modify_removed: perm.modify_rem.into(),
+            modify_class: perm.modify_class.into(),
+            delete: perm.delete,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AccessProfileCheckEvent, AccessProfileCreateEvent, AccessProfileUpdateEvent};
+    use crate::prelude::*;
+    use crate::testkit::test_person;
+    use kanidm_proto::v1::{AccessCheckAttrs, AccessProfile, Filter as ProtoFilter};
+
+    const UUID_TEST_GROUP: Uuid = uuid::uuid!("a7d5a5ab-3f1d-4a5a-9f87-2e0f0b9e5f11");
+    const UUID_TEST_PERSON_1: Uuid = uuid::uuid!("0e2d3c8c-52b6-4bbf-b6d0-5e4d3f3f4d01");
+    const UUID_TEST_PERSON_2: Uuid = uuid::uuid!("0e2d3c8c-52b6-4bbf-b6d0-5e4d3f3f4d02");
+
+    async fn setup(idms: &IdmServer) -> Identity {
+        let ct = duration_from_epoch_now();
+        let mut idms_prox_write = idms.proxy_write(ct).await;
+
+        let e_grp = entry_init!(
+            ("class", Value::new_class("object")),
+            ("class", Value::new_class("group")),
+            ("name", Value::new_iname("test_helpdesk")),
+            ("uuid", Value::Uuid(UUID_TEST_GROUP)),
+            ("member", Value::Refer(UUID_TEST_PERSON_1))
+        );
+
+        assert!(idms_prox_write
+            .qs_write
+            .internal_create(vec![
+                test_person("testperson1", UUID_TEST_PERSON_1),
+                test_person("testperson2", UUID_TEST_PERSON_2),
+                e_grp
+            ])
+            .is_ok());
+
+        let admin = idms_prox_write
+            .qs_write
+            .internal_search_uuid(UUID_ADMIN)
+            .expect("failed to find admin");
+        let ident = Identity::from_impersonate_entry_readwrite(admin);
+
+        assert!(idms_prox_write.commit().is_ok());
+        ident
+    }
+
+    #[idm_test]
+    async fn test_idm_access_profile_lifecycle(
+        idms: &IdmServer,
+        _idms_delayed: &mut IdmServerDelayed,
+    ) {
+        let ident = setup(idms).await;
+        let ct = duration_from_epoch_now();
+
+        let mut idms_prox_write = idms.proxy_write(ct).await;
+        let ev = AccessProfileCreateEvent {
+            ident: ident.clone(),
+            profile: AccessProfile {
+                name: Some("test_helpdesk_read".to_string()),
+                receiver_group: Some("test_helpdesk".to_string()),
+                targetscope: Some(ProtoFilter::Eq("class".to_string(), "person".to_string())),
+                search_attrs: Some(vec!["name".to_string(), "displayname".to_string()]),
+                ..Default::default()
+            },
+        };
+        assert!(idms_prox_write.access_profile_create(&ev).is_ok());
+
+        // A profile needs a receiver and targetscope.
+        let ev = AccessProfileCreateEvent {
+            ident: ident.clone(),
+            profile: AccessProfile {
+                name: Some("test_incomplete".to_string()),
+                ..Default::default()
+            },
+        };
+        assert!(matches!(
+            idms_prox_write.access_profile_create(&ev),
+            Err(OperationError::InvalidAcpState(_))
+        ));
+
+        let acp = idms_prox_write
+            .qs_write
+            .internal_search(filter!(f_eq(
+                "name",
+                PartialValue::new_iname("test_helpdesk_read")
+            )))
+            .expect("search failure")
+            .pop()
+            .expect("access profile not created");
+        assert!(acp.attribute_equality("class", &PVCLASS_ACS));
+        assert!(!acp.attribute_equality("class", &PVCLASS_ACM));
+        assert!(idms_prox_write.commit().is_ok());
+
+        // Remove the search right and grant delete and modify instead.
+        let mut idms_prox_write = idms.proxy_write(ct).await;
+        let ev = AccessProfileUpdateEvent {
+            ident: ident.clone(),
+            target: acp.get_uuid(),
+            profile: AccessProfile {
+                description: Some("helpdesk".to_string()),
+                search_attrs: Some(Vec::new()),
+                modify_present_attrs: Some(vec!["displayname".to_string()]),
+                delete: Some(true),
+                ..Default::default()
+            },
+        };
+        assert!(idms_prox_write.access_profile_update(&ev).is_ok());
+
+        let acp = idms_prox_write
+            .qs_write
+            .internal_search_uuid(acp.get_uuid())
+            .expect("access profile not found");
+        assert!(!acp.attribute_equality("class", &PVCLASS_ACS));
+        assert!(!acp.attribute_pres("acp_search_attr"));
+        assert!(acp.attribute_equality("class", &PVCLASS_ACM));
+        assert!(acp.attribute_equality("class", &PVCLASS_ACD));
+        assert!(acp.attribute_equality("description", &PartialValue::new_utf8s("helpdesk")));
+
+        assert!(idms_prox_write.commit().is_ok());
+
+        // Profiles that can't be seen are reported the same as those that don't exist.
+        let mut idms_prox_write = idms.proxy_write(ct).await;
+        let person = idms_prox_write
+            .qs_write
+            .internal_search_uuid(UUID_TEST_PERSON_1)
+            .expect("failed to find person");
+        let person = Identity::from_impersonate_entry_readwrite(person);
+        for (ident, target) in [
+            (person, acp.get_uuid()),
+            (ident.clone(), Uuid::new_v4()),
+            (ident, UUID_TEST_PERSON_1),
+        ] {
+            let ev = AccessProfileUpdateEvent {
+                ident,
+                target,
+                profile: AccessProfile {
+                    description: Some("changed".to_string()),
+                    ..Default::default()
+                },
+            };
+            assert_eq!(
+                idms_prox_write.access_profile_update(&ev),
+                Err(OperationError::NoMatchingEntries)
+            );
+        }
+    }
+
+    #[idm_test]
+    async fn test_idm_access_profile_check(idms: &IdmServer, _idms_delayed: &mut IdmServerDelayed) {
+        let ident = setup(idms).await;
+        let ct = duration_from_epoch_now();
+
+        let mut idms_prox_write = idms.proxy_write(ct).await;
+        let ev = AccessProfileCreateEvent {
+            ident: ident.clone(),
+            profile: AccessProfile {
+                name: Some("test_helpdesk_read".to_string()),
+                receiver_group: Some("test_helpdesk".to_string()),
+                targetscope: Some(ProtoFilter::Eq("class".to_string(), "person".to_string())),
+                search_attrs: Some(vec!["legalname".to_string()]),
+                delete: Some(true),
+                ..Default::default()
+            },
+        };
+        assert!(idms_prox_write.access_profile_create(&ev).is_ok());
+        assert!(idms_prox_write.commit().is_ok());
+
+        let mut idms_prox_read = idms.proxy_read().await;
+
+        let grants_legalname = |access: &AccessCheckAttrs| match access {
+            AccessCheckAttrs::Grant => true,
+            AccessCheckAttrs::Allow(attrs) => attrs.iter().any(|a| a == "legalname"),
+            AccessCheckAttrs::Denied => false,
+        };
+
+        // A member of the group is granted the rights of the profile.
+        let ev = AccessProfileCheckEvent {
+            ident: ident.clone(),
+            subject: UUID_TEST_PERSON_1,
+            target: UUID_TEST_PERSON_2,
+        };
+        let r = idms_prox_read
+            .access_profile_check(&ev)
+            .expect("access check failed");
+        assert!(grants_legalname(&r.search));
+        assert!(r.delete);
+
+        // A non member is not granted these.
+        let ev = AccessProfileCheckEvent {
+            ident,
+            subject: UUID_TEST_PERSON_2,
+            target: UUID_TEST_PERSON_1,
+        };
+        let r = idms_prox_read
+            .access_profile_check(&ev)
+            .expect("access check failed");
+        assert!(!grants_legalname(&r.search));
+        assert!(!r.delete);
+
+        // Someone who can't read access profiles can't perform the check.
+        let person = idms_prox_read
+            .qs_read
+            .internal_search_uuid(UUID_TEST_PERSON_1)
+            .expect("failed to find person");
+        let ev = AccessProfileCheckEvent {
+            ident: Identity::from_impersonate_entry_readonly(person),
+            subject: UUID_TEST_PERSON_1,
+            target: UUID_TEST_PERSON_2,
+        };
+        assert!(matches!(
+            idms_prox_read.access_profile_check(&ev),
+            Err(OperationError::AccessDenied)
+        ));
+    }
+}
@@ -0,0 +1,756 @@
+//! Access requests allow a person to request time limited membership of a group, which
+//! must then be approved or denied by a member of one of the group's approver groups.
+//!
+//! The state machine of a request is:
+//!
+//! ```text
+//! pending -> approved
+//!         -> denied
+//!         -> withdrawn
+//! ```
+//!
+//! Once approved the requester is added to the group with a `member_validity` that ends
+//! after the requested duration, and the membership is revoked by the member validity
+//! purge task.
+
+use std::time::Duration;
+
+use kanidm_proto::v1::{AccessRequest, AccessRequestState};
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+
+use crate::idm::audit::AuditEvent;
+use crate::idm::server::{IdmServerProxyReadTransaction, IdmServerProxyWriteTransaction};
+use crate::prelude::*;
+use crate::value::MemberValidity;
+
+pub struct AccessRequestCreateEvent {
+    pub ident: Identity,
+    pub group: Uuid,
+    pub reason: Option<String>,
+    pub duration: Option<u32>,
+}
+
+pub struct AccessRequestDecideEvent {
+    pub ident: Identity,
+    pub target: Uuid,
+    pub approve: bool,
+}
+
+pub struct AccessRequestWithdrawEvent {
+    pub ident: Identity,
+    pub target: Uuid,
+}
+
+pub struct AccessRequestListEvent {
+    pub ident: Identity,
+}
+
+pub struct AccessRequestGetEvent {
+    pub ident: Identity,
+    pub target: Uuid,
+}
+
+fn access_request_state(
+    entry: &EntrySealedCommitted,
+) -> Result<AccessRequestState, OperationError> {
+    entry
+        .get_ava_single_iutf8("access_request_state")
+        .and_then(|s| s.parse().ok())
+        .ok_or_else(|| {
+            admin_error!(uuid = ?entry.get_uuid(), "Access request has an invalid state");
+            OperationError::InvalidEntryState
+        })
+}
+
+fn set_state_mods(state: AccessRequestState) -> Vec<Modify> {
+    vec![
+        Modify::Purged(AttrString::from("access_request_state")),
+        Modify::Present(
+            AttrString::from("access_request_state"),
+            Value::new_iutf8(state.as_str()),
+        ),
+    ]
+}
+
+impl<'a> IdmServerProxyWriteTransaction<'a> {
+    /// Load a pending access request, along with the requester and the requested group.
+    fn access_request_pending(
+        &mut self,
+        target: Uuid,
+    ) -> Result<(Uuid, Uuid, u32), OperationError> {
+        let request = self.qs_write.internal_search_uuid(target)?;
+        if !request.attribute_equality("class", &PVCLASS_ACCESS_REQUEST) {
+            return Err(OperationError::NoMatchingEntries);
+        }
+
+        if access_request_state(&request)? != AccessRequestState::Pending {
+            admin_error!(%target, "Access request is not pending");
+            return Err(OperationError::InvalidRequestState);
+        }
+
+        // If either of these were deleted, then the request can no longer be acted upon.
+        let requester = request.get_ava_single_refer("access_request_requester");
+        let group = request.get_ava_single_refer("access_request_group");
+        let duration = request.get_ava_single_uint32("access_request_duration");
+
+        match (requester, group, duration) {
+            (Some(requester), Some(group), Some(duration)) => Ok((requester, group, duration)),
+            _ => {
+                admin_error!(%target, "Access request requester or group no longer exists");
+                Err(OperationError::InvalidRequestState)
+            }
+        }
+    }
+
+    pub fn access_request_create(
+        &mut self,
+        ev: &AccessRequestCreateEvent,
+        ct: Duration,
+    ) -> Result<Uuid, OperationError> {
+        let requester = ev
+            .ident
+            .get_user_entry()
+            .filter(|e| e.attribute_equality("class", &PVCLASS_PERSON))
+            .ok_or_else(|| {
+                admin_error!("Only persons may request access");
+                OperationError::InvalidAccountState("Only persons may request access".to_string())
+            })?;
+        let requester_uuid = requester.get_uuid();
+
+        let group = self.qs_write.internal_search_uuid(ev.group)?;
+        if !group.attribute_equality("class", &PVCLASS_GROUP) {
+            return Err(OperationError::NoMatchingEntries);
+        }
+
+        if group
+            .get_ava_refer("access_request_approvers")
+            .map(|approvers| approvers.is_empty())
+            .unwrap_or(true)
+        {
+            admin_error!(group = %ev.group, "Group does not have any access request approvers");
+            return Err(OperationError::InvalidRequestState);
+        }
+
+        let max_duration = group
+            .get_ava_single_uint32("access_request_max_duration")
+            .unwrap_or(ACCESS_REQUEST_DEFAULT_DURATION);
+        let duration = ev.duration.unwrap_or(max_duration);
+        if duration == 0 || duration > max_duration {
+            admin_error!(
+                ?duration,
+                ?max_duration,
+                "Requested duration is not permitted by this group"
+            );
+            return Err(OperationError::InvalidRequestState);
+        }
+
+        // A member that already has a permanent membership has nothing to request. A time
+        // bounded member may request an extension.
+        let is_member = group.attribute_equality("member", &PartialValue::Refer(requester_uuid));
+        let is_bounded = group
+            .get_ava_member_validity("member_validity")
+            .map(|mv| mv.contains_key(&requester_uuid))
+            .unwrap_or(false);
+        if is_member && !is_bounded {
+            admin_error!(group = %ev.group, "Requester is already a member of this group");
+            return Err(OperationError::InvalidRequestState);
+        }
+
+        let f_pending = filter!(f_and!([
+            f_eq("class", PVCLASS_ACCESS_REQUEST.clone()),
+            f_eq(
+                "access_request_requester",
+                PartialValue::Refer(requester_uuid)
+            ),
+            f_eq("access_request_group", PartialValue::Refer(ev.group)),
+            f_eq(
+                "access_request_state",
+                PartialValue::new_iutf8(AccessRequestState::Pending.as_str())
+            )
+        ]));
+        if !self.qs_write.internal_search(f_pending)?.is_empty() {
+            admin_error!(group = %ev.group, "Requester already has a pending request for this group");
+            return Err(OperationError::InvalidRequestState);
+        }
+
+        let request_uuid = Uuid::new_v4();
+        let mut e = entry_init!(
+            ("class", CLASS_OBJECT.clone()),
+            ("class", CLASS_ACCESS_REQUEST.clone()),
+            ("uuid", Value::Uuid(request_uuid)),
+            ("access_request_requester", Value::Refer(requester_uuid)),
+            ("access_request_group", Value::Refer(ev.group)),
+            (
+                "access_request_state",
+                Value::new_iutf8(AccessRequestState::Pending.as_str())
+            ),
+            ("access_request_duration", Value::new_uint32(duration))
+        );
+        if let Some(reason) = ev.reason.as_deref().filter(|r| !r.is_empty()) {
+            e.add_ava("description", Value::new_utf8s(reason));
+        }
+
+        self.qs_write.internal_create(vec![e]).map_err(|e| {
+            admin_error!(?e, "Failed to create access request");
+            e
+        })?;
+
+        security_info!(%request_uuid, requester = %requester_uuid, group = %ev.group, "Access requested");
+        self.audit_pending.push(AuditEvent::AccessRequest {
+            request: request_uuid,
+            requester: requester_uuid,
+            group: ev.group,
+            actor: requester_uuid,
+            state: AccessRequestState::Pending,
+            time: OffsetDateTime::UNIX_EPOCH + ct,
+        });
+
+        Ok(request_uuid)
+    }
+
+    pub fn access_request_decide(
+        &mut self,
+        ev: &AccessRequestDecideEvent,
+        ct: Duration,
+    ) -> Result<(), OperationError> {
+        // Approving a request grants privileges, so the approver must have reauthenticated.
+        if ev.ident.access_scope() != AccessScope::ReadWrite {
+            security_access!("identity access scope is not permitted to decide access requests");
+            return Err(OperationError::AccessDenied);
+        }
+        let approver = ev
+            .ident
+            .get_user_entry()
+            .map(|e| e.get_uuid())
+            .ok_or(OperationError::AccessDenied)?;
+
+        let (requester, group_uuid, duration) = self.access_request_pending(ev.target)?;
+
+        if approver == requester {
+            security_access!("denied ❌ - requesters may not decide their own access requests");
+            return Err(OperationError::AccessDenied);
+        }
+
+        let group = self.qs_write.internal_search_uuid(group_uuid)?;
+        let is_approver = group
+            .get_ava_refer("access_request_approvers")
+            .map(|approvers| approvers.iter().any(|a| ev.ident.is_memberof(*a)))
+            .unwrap_or(false);
+        if !is_approver {
+            security_access!("denied ❌ - identity is not an approver for this group");
+            return Err(OperationError::AccessDenied);
+        }
+
+        let mut mods = if ev.approve {
+            set_state_mods(AccessRequestState::Approved)
+        } else {
+            set_state_mods(AccessRequestState::Denied)
+        };
+        mods.push(Modify::Present(
+            AttrString::from("access_request_decided_by"),
+            Value::Refer(approver),
+        ));
+
+        if ev.approve {
+            // The requester may have been made a permanent member since the request was
+            // made, and approving must not turn that into a time bounded membership.
+            let is_member = group.attribute_equality("member", &PartialValue::Refer(requester));
+            let is_bounded = group
+                .get_ava_member_validity("member_validity")
+                .map(|mv| mv.contains_key(&requester))
+                .unwrap_or(false);
+            if is_member && !is_bounded {
+                admin_error!(group = %group_uuid, "Requester is already a member of this group");
+                return Err(OperationError::InvalidRequestState);
+            }
+
+            let expiry = OffsetDateTime::UNIX_EPOCH + ct + Duration::from_secs(duration as u64);
+            let validity = MemberValidity {
+                not_before: None,
+                not_after: Some(expiry),
+            };
+
+            let group_mods = ModifyList::new_list(vec![
+                Modify::Removed(
+                    AttrString::from("member_validity"),
+                    PartialValue::Refer(requester),
+                ),
+                Modify::Present(
+                    AttrString::from("member_validity"),
+                    Value::MemberValidity(requester, validity),
+                ),
+                Modify::Present(AttrString::from("member"), Value::Refer(requester)),
+            ]);
+            self.qs_write
+                .internal_modify_uuid(group_uuid, &group_mods)
+                .map_err(|e| {
+                    admin_error!(?e, "Failed to grant requested membership");
+                    e
+                })?;
+
+            mods.push(Modify::Present(
+                AttrString::from("access_request_expiry"),
+                Value::DateTime(expiry),
+            ));
+        }
+
+        self.qs_write
+            .internal_modify_uuid(ev.target, &ModifyList::new_list(mods))
+            .map_err(|e| {
+                admin_error!(?e, "Failed to update access request");
+                e
+            })?;
+
+        let state = if ev.approve {
+            AccessRequestState::Approved
+        } else {
+            AccessRequestState::Denied
+        };
+        security_info!(request = %ev.target, %requester, group = %group_uuid, %approver, %state, "Access request decided");
+        self.audit_pending.push(AuditEvent::AccessRequest {
+            request: ev.target,
+            requester,
+            group: group_uuid,
+            actor: approver,
+            state,
+            time: OffsetDateTime::UNIX_EPOCH + ct,
+        });
+
+        Ok(())
+    }
+
+    pub fn access_request_withdraw(
+        &mut self,
+        ev: &AccessRequestWithdrawEvent,
+        ct: Duration,
+    ) -> Result<(), OperationError> {
+        let (requester, group, _) = self.access_request_pending(ev.target)?;
+
+        if ev.ident.get_uuid() != Some(requester) {
+            security_access!("denied ❌ - only the requester may withdraw an access request");
+            return Err(OperationError::AccessDenied);
+        }
+
+        self.qs_write
+            .internal_modify_uuid(
+                ev.target,
+                &ModifyList::new_list(set_state_mods(AccessRequestState::Withdrawn)),
+            )
+            .map_err(|e| {
+                admin_error!(?e, "Failed to update access request");
+                e
+            })?;
+
+        self.audit_pending.push(AuditEvent::AccessRequest {
+            request: ev.target,
+            requester,
+            group,
+            actor: requester,
+            state: AccessRequestState::Withdrawn,
+            time: OffsetDateTime::UNIX_EPOCH + ct,
+        });
+
+        Ok(())
+    }
+}
+
+impl<'a> IdmServerProxyReadTransaction<'a> {
+    /// Requests are visible to their requester, and to the approvers of the requested group.
+    fn access_request_search(
+        &mut self,
+        ident: &Identity,
+        target: Option<Uuid>,
+    ) -> Result<Vec<AccessRequest>, OperationError> {
+        let ident_uuid = ident.get_uuid().ok_or(OperationError::AccessDenied)?;
+
+        let approver_of: Vec<_> = match ident.get_memberof() {
+            Some(memberof) if !memberof.is_empty() => {
+                let f_approver = filter!(f_or(
+                    memberof
+                        .iter()
+                        .map(|g| f_eq("access_request_approvers", PartialValue::Refer(*g)))
+                        .collect()
+                ));
+                self.qs_read
+                    .internal_search(f_approver)?
+                    .iter()
+                    .map(|group| {
+                        f_eq(
+                            "access_request_group",
+                            PartialValue::Refer(group.get_uuid()),
+                        )
+                    })
+                    .collect()
+            }
+            _ => Vec::new(),
+        };
+
+        let mut visible = approver_of;
+        visible.push(f_eq(
+            "access_request_requester",
+            PartialValue::Refer(ident_uuid),
+        ));
+
+        let mut conditions = vec![f_eq("class", PVCLASS_ACCESS_REQUEST.clone()), f_or(visible)];
+        if let Some(target) = target {
+            conditions.push(f_eq("uuid", PartialValue::Uuid(target)));
+        }
+
+        let requests = self.qs_read.internal_search(filter!(f_and(conditions)))?;
+
+        requests
+            .iter()
+            .map(|e| {
+                let mut spn = |attr: &str| -> Result<Option<String>, OperationError> {
+                    match e.get_ava_single_refer(attr) {
+                        Some(uuid) => self
+                            .qs_read
+                            .uuid_to_spn(uuid)
+                            .map(|v| v.map(|v| v.to_proto_string_clone())),
+                        None => Ok(None),
+                    }
+                };
+
+                Ok(AccessRequest {
+                    uuid: e.get_uuid(),
+                    state: access_request_state(e)?,
+                    requester: spn("access_request_requester")?,
+                    group: spn("access_request_group")?,
+                    decided_by: spn("access_request_decided_by")?,
+                    reason: e.get_ava_single_utf8("description").map(str::to_string),
+                    duration: e
+                        .get_ava_single_uint32("access_request_duration")
+                        .unwrap_or_default(),
+                    expiry: e
+                        .get_ava_single_datetime("access_request_expiry")
+                        .and_then(|odt| odt.format(&Rfc3339).ok()),
+                })
+            })
+            .collect()
+    }
+
+    pub fn access_request_list(
+        &mut self,
+        ev: &AccessRequestListEvent,
+    ) -> Result<Vec<AccessRequest>, OperationError> {
+        self.access_request_search(&ev.ident, None)
+    }
+
+    pub fn access_request_get(
+        &mut self,
+        ev: &AccessRequestGetEvent,
+    ) -> Result<AccessRequest, OperationError> {
+        self.access_request_search(&ev.ident, Some(ev.target))?
+            .pop()
+            .ok_or(OperationError::NoMatchingEntries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        AccessRequestCreateEvent, AccessRequestDecideEvent, AccessRequestGetEvent,
+        AccessRequestListEvent, AccessRequestWithdrawEvent,
+    };
+    use crate::idm::audit::AuditEvent;
+    use crate::prelude::*;
+    use crate::testkit::test_person;
+    use kanidm_proto::v1::AccessRequestState;
+
+    const UUID_TEST_REQUESTER: Uuid = uuid::uuid!("5d3f8f30-7a7e-4f7e-9d4e-6b6f1c0b0a01");
+    const UUID_TEST_APPROVER: Uuid = uuid::uuid!("5d3f8f30-7a7e-4f7e-9d4e-6b6f1c0b0a02");
+    const UUID_TEST_APPROVERS: Uuid = uuid::uuid!("5d3f8f30-7a7e-4f7e-9d4e-6b6f1c0b0a03");
+    const UUID_TEST_PRIV_GROUP: Uuid = uuid::uuid!("5d3f8f30-7a7e-4f7e-9d4e-6b6f1c0b0a04");
+
+    async fn setup(idms: &IdmServer, ct: Duration) {
+        let mut idms_prox_write = idms.proxy_write(ct).await;
+
+        let e_approvers = entry_init!(
+            ("class", Value::new_class("object")),
+            ("class", Value::new_class("group")),
+            ("name", Value::new_iname("test_approvers")),
+            ("uuid", Value::Uuid(UUID_TEST_APPROVERS)),
+            ("member", Value::Refer(UUID_TEST_APPROVER))
+        );
+
+        let e_priv = entry_init!(
+            ("class", Value::new_class("object")),
+            ("class", Value::new_class("group")),
+            ("name", Value::new_iname("test_priv")),
+            ("uuid", Value::Uuid(UUID_TEST_PRIV_GROUP)),
+            (
+                "access_request_approvers",
+                Value::Refer(UUID_TEST_APPROVERS)
+            ),
+            ("access_request_max_duration", Value::new_uint32(600))
+        );
+
+        assert!(idms_prox_write
+            .qs_write
+            .internal_create(vec![
+                test_person("test_requester", UUID_TEST_REQUESTER),
+                test_person("test_approver", UUID_TEST_APPROVER),
+                e_approvers,
+                e_priv
+            ])
+            .is_ok());
+        assert!(idms_prox_write.commit().is_ok());
+    }
+
+    async fn ident_rw(idms: &IdmServer, ct: Duration, uuid: Uuid) -> Identity {
+        let mut idms_prox_write = idms.proxy_write(ct).await;
+        let entry = idms_prox_write
+            .qs_write
+            .internal_search_uuid(uuid)
+            .expect("failed to find entry");
+        Identity::from_impersonate_entry_readwrite(entry)
+    }
+
+    fn expect_audit(idms_audit: &mut IdmServerAudit, expect: AccessRequestState) {
+        match idms_audit.audit_rx().try_recv() {
+            Ok(AuditEvent::AccessRequest { state, .. }) => assert_eq!(state, expect),
+            _ => panic!("expected an access request audit event"),
+        }
+    }
+
+    #[idm_test(audit)]
+    async fn test_idm_access_request_approve(
+        idms: &IdmServer,
+        _idms_delayed: &mut IdmServerDelayed,
+        idms_audit: &mut IdmServerAudit,
+    ) {
+        let ct = duration_from_epoch_now();
+        setup(idms, ct).await;
+
+        let requester = ident_rw(idms, ct, UUID_TEST_REQUESTER).await;
+        let approver = ident_rw(idms, ct, UUID_TEST_APPROVER).await;
+
+        let mut idms_prox_write = idms.proxy_write(ct).await;
+
+        // The group limits how long membership may be requested for.
+        let ev = AccessRequestCreateEvent {
+            ident: requester.clone(),
+            group: UUID_TEST_PRIV_GROUP,
+            reason: Some("on call".to_string()),
+            duration: Some(1200),
+        };
+        assert_eq!(
+            idms_prox_write.access_request_create(&ev, ct),
+            Err(OperationError::InvalidRequestState)
+        );
+
+        // A group without approvers can't be requested.
+        let ev = AccessRequestCreateEvent {
+            ident: requester.clone(),
+            group: UUID_TEST_APPROVERS,
+            reason: None,
+            duration: None,
+        };
+        assert_eq!(
+            idms_prox_write.access_request_create(&ev, ct),
+            Err(OperationError::InvalidRequestState)
+        );
+
+        let ev = AccessRequestCreateEvent {
+            ident: requester.clone(),
+            group: UUID_TEST_PRIV_GROUP,
+            reason: Some("on call".to_string()),
+            duration: None,
+        };
+        let request = idms_prox_write
+            .access_request_create(&ev, ct)
+            .expect("failed to create request");
+
+        // Only one request may be pending at a time.
+        assert_eq!(
+            idms_prox_write.access_request_create(&ev, ct),
+            Err(OperationError::InvalidRequestState)
+        );
+        assert!(idms_prox_write.commit().is_ok());
+        expect_audit(idms_audit, AccessRequestState::Pending);
+
+        let mut idms_prox_write = idms.proxy_write(ct).await;
+
+        // Requesters can't approve their own requests, and approvers must have
+        // reauthenticated.
+        let ev = AccessRequestDecideEvent {
+            ident: requester.clone(),
+            target: request,
+            approve: true,
+        };
+        assert_eq!(
+            idms_prox_write.access_request_decide(&ev, ct),
+            Err(OperationError::AccessDenied)
+        );
+
+        let ev = AccessRequestDecideEvent {
+            ident: approver.project_with_scope(AccessScope::ReadOnly),
+            target: request,
+            approve: true,
+        };
+        assert_eq!(
+            idms_prox_write.access_request_decide(&ev, ct),
+            Err(OperationError::AccessDenied)
+        );
+
+        let ev = AccessRequestDecideEvent {
+            ident: approver.clone(),
+            target: request,
+            approve: true,
+        };
+        assert!(idms_prox_write.access_request_decide(&ev, ct).is_ok());
+
+        // A decided request can't be decided again.
+        assert_eq!(
+            idms_prox_write.access_request_decide(&ev, ct),
+            Err(OperationError::InvalidRequestState)
+        );
+
+        // The requester is now a time bounded member.
+        let group = idms_prox_write
+            .qs_write
+            .internal_search_uuid(UUID_TEST_PRIV_GROUP)
+            .expect("failed to find group");
+        let validity = group
+            .get_ava_member_validity("member_validity")
+            .and_then(|mv| mv.get(&UUID_TEST_REQUESTER).copied())
+            .expect("no member validity");
+        assert_eq!(
+            validity.not_after,
+            Some(time::OffsetDateTime::UNIX_EPOCH + ct + Duration::from_secs(600))
+        );
+
+        let entry = idms_prox_write
+            .qs_write
+            .internal_search_uuid(UUID_TEST_REQUESTER)
+            .expect("failed to find requester");
+        assert!(entry.attribute_equality("memberof", &PartialValue::Refer(UUID_TEST_PRIV_GROUP)));
+        assert!(idms_prox_write.commit().is_ok());
+        expect_audit(idms_audit, AccessRequestState::Approved);
+
+        // The request is visible to the requester and approver, but not others.
+        let mut idms_prox_read = idms.proxy_read().await;
+        let ev = AccessRequestGetEvent {
+            ident: requester.clone(),
+            target: request,
+        };
+        let r = idms_prox_read
+            .access_request_get(&ev)
+            .expect("failed to get request");
+        assert_eq!(r.state, AccessRequestState::Approved);
+        assert_eq!(r.duration, 600);
+        assert_eq!(r.reason.as_deref(), Some("on call"));
+        assert!(r.expiry.is_some());
+        assert!(r
+            .decided_by
+            .as_deref()
+            .map(|spn| spn.starts_with("test_approver@"))
+            .unwrap_or(false));
+
+        let ev = AccessRequestListEvent {
+            ident: approver.clone(),
+        };
+        assert_eq!(
+            idms_prox_read.access_request_list(&ev).map(|r| r.len()),
+            Ok(1)
+        );
+
+        let admin = idms_prox_read
+            .qs_read
+            .internal_search_uuid(UUID_ADMIN)
+            .expect("failed to find admin");
+        let ev = AccessRequestListEvent {
+            ident: Identity::from_impersonate_entry_readwrite(admin),
+        };
+        assert_eq!(
+            idms_prox_read.access_request_list(&ev).map(|r| r.len()),
+            Ok(0)
+        );
+    }
+
+    #[idm_test(audit)]
+    async fn test_idm_access_request_deny_and_withdraw(
+        idms: &IdmServer,
+        _idms_delayed: &mut IdmServerDelayed,
+        idms_audit: &mut IdmServerAudit,
+    ) {
+        let ct = duration_from_epoch_now();
+        setup(idms, ct).await;
+
+        let requester = ident_rw(idms, ct, UUID_TEST_REQUESTER).await;
+        let approver = ident_rw(idms, ct, UUID_TEST_APPROVER).await;
+
+        let mut idms_prox_write = idms.proxy_write(ct).await;
+        let ev = AccessRequestCreateEvent {
+            ident: requester.clone(),
+            group: UUID_TEST_PRIV_GROUP,
+            reason: None,
+            duration: Some(60),
+        };
+        let request = idms_prox_write
+            .access_request_create(&ev, ct)
+            .expect("failed to create request");
+
+        let ev = AccessRequestDecideEvent {
+            ident: approver.clone(),
+            target: request,
+            approve: false,
+        };
+        assert!(idms_prox_write.access_request_decide(&ev, ct).is_ok());
+
+        let group = idms_prox_write
+            .qs_write
+            .internal_search_uuid(UUID_TEST_PRIV_GROUP)
+            .expect("failed to find group");
+        assert!(!group.attribute_pres("member"));
+        assert!(idms_prox_write.commit().is_ok());
+        expect_audit(idms_audit, AccessRequestState::Pending);
+        expect_audit(idms_audit, AccessRequestState::Denied);
+
+        // Audit events are only sent when the transaction commits.
+        let mut idms_prox_write = idms.proxy_write(ct).await;
+        let ev = AccessRequestCreateEvent {
+            ident: requester.clone(),
+            group: UUID_TEST_PRIV_GROUP,
+            reason: None,
+            duration: Some(60),
+        };
+        assert!(idms_prox_write.access_request_create(&ev, ct).is_ok());
+        drop(idms_prox_write);
+
+        let mut idms_prox_write = idms.proxy_write(ct).await;
+        let request = idms_prox_write
+            .access_request_create(&ev, ct)
+            .expect("failed to create request");
+
+        // Only the requester may withdraw a request.
+        let ev = AccessRequestWithdrawEvent {
+            ident: approver.clone(),
+            target: request,
+        };
+        assert_eq!(
+            idms_prox_write.access_request_withdraw(&ev, ct),
+            Err(OperationError::AccessDenied)
+        );
+
+        let ev = AccessRequestWithdrawEvent {
+            ident: requester.clone(),
+            target: request,
+        };
+        assert!(idms_prox_write.access_request_withdraw(&ev, ct).is_ok());
+
+        let ev = AccessRequestDecideEvent {
+            ident: approver.clone(),
+            target: request,
+            approve: true,
+        };
+        assert_eq!(
+            idms_prox_write.access_request_decide(&ev, ct),
+            Err(OperationError::InvalidRequestState)
+        );
+        assert!(idms_prox_write.commit().is_ok());
+        expect_audit(idms_audit, AccessRequestState::Pending);
+        expect_audit(idms_audit, AccessRequestState::Withdrawn);
+    }
+}
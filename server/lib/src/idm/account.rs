@@ -1,6 +1,7 @@
 use std::collections::{BTreeMap, BTreeSet};
 use std::time::Duration;
 
+use kanidm_proto::internal::Oauth2Grant;
 use kanidm_proto::v1::{
     BackupCodesView, CredentialStatus, OperationError, UatPurpose, UatStatus, UiHint, UserAuthToken,
 };
@@ -12,7 +13,7 @@ use webauthn_rs::prelude::{
 
 use crate::constants::UUID_ANONYMOUS;
 use crate::credential::softlock::CredSoftLockPolicy;
-use crate::credential::Credential;
+use crate::credential::{Credential, CredentialType, Password};
 use crate::entry::{Entry, EntryCommitted, EntryReduced, EntrySealed};
 use crate::event::SearchEvent;
 use crate::idm::group::Group;
@@ -53,6 +54,11 @@ macro_rules! try_from_entry {
             .get_ava_single_credential("primary_credential")
             .map(|v| v.clone());
 
+        let password_history = $value
+            .get_ava_password_history("password_history")
+            .map(|h| h.values().cloned().collect())
+            .unwrap_or_default();
+
         let passkeys = $value
             .get_ava_passkeys("passkeys")
             .cloned()
@@ -120,6 +126,7 @@ macro_rules! try_from_entry {
             displayname,
             groups,
             primary,
+            password_history,
             passkeys,
             devicekeys,
             valid_from,
@@ -150,6 +157,7 @@ pub struct Account {
     #[allow(dead_code)]
     pub groups: Vec<Group>,
     pub primary: Option<Credential>,
+    pub password_history: Vec<Password>,
     pub passkeys: BTreeMap<Uuid, (String, PasskeyV4)>,
     pub devicekeys: BTreeMap<Uuid, (String, DeviceKeyV4)>,
     pub valid_from: Option<OffsetDateTime>,
@@ -604,6 +612,22 @@ impl DestroySessionTokenEvent {
     }
 }
 
+pub struct DestroyAllSessionTokensEvent {
+    // Who initiated this?
+    pub ident: Identity,
+    // Who is it targeting?
+    pub target: Uuid,
+}
+
+pub struct RevokeOauth2GrantEvent {
+    // Who initiated this?
+    pub ident: Identity,
+    // Who is it targeting?
+    pub target: Uuid,
+    // The name of the oauth2 resource server to revoke.
+    pub rs_name: String,
+}
+
 impl<'a> IdmServerProxyWriteTransaction<'a> {
     pub fn account_destroy_session_token(
         &mut self,
@@ -639,6 +663,82 @@ impl<'a> IdmServerProxyWriteTransaction<'a> {
             })
     }
 
+    /// Log an account out everywhere. Ending the user auth sessions also ends the
+    /// oauth2 sessions that were issued from them.
+    pub fn account_destroy_all_session_tokens(
+        &mut self,
+        dte: &DestroyAllSessionTokensEvent,
+    ) -> Result<(), OperationError> {
+        let modlist = ModifyList::new_purge("user_auth_token_session");
+
+        self.qs_write
+            .impersonate_modify(
+                // Filter as executed
+                &filter!(f_eq("uuid", PartialValue::Uuid(dte.target))),
+                // Filter as intended (acp)
+                &filter_all!(f_eq("uuid", PartialValue::Uuid(dte.target))),
+                &modlist,
+                // As with a single session, you should always be able to logout.
+                &dte.ident.project_with_scope(AccessScope::ReadWrite),
+            )
+            .map_err(|e| {
+                admin_error!("Failed to destroy user auth tokens {:?}", e);
+                e
+            })
+    }
+
+    /// Withdraw the consent an account gave to an oauth2 resource server, and end
+    /// any oauth2 sessions the resource server holds for the account.
+    pub fn account_revoke_oauth2_grant(
+        &mut self,
+        rge: &RevokeOauth2GrantEvent,
+    ) -> Result<(), OperationError> {
+        let rs_uuid = self
+            .qs_write
+            .internal_search(filter!(f_and!([
+                f_eq("class", PVCLASS_OAUTH2_RS.clone()),
+                f_eq("oauth2_rs_name", PartialValue::new_iname(&rge.rs_name))
+            ])))
+            .and_then(|mut entries| {
+                entries
+                    .pop()
+                    .map(|e| e.get_uuid())
+                    .ok_or(OperationError::NoMatchingEntries)
+            })
+            .map_err(|e| {
+                admin_error!(rs_name = %rge.rs_name, "Unable to find oauth2 resource server {:?}", e);
+                e
+            })?;
+
+        // Both attributes accept the uuid of the resource server as a reference,
+        // removing all the values related to it.
+        let modlist = ModifyList::new_list(vec![
+            Modify::Removed(
+                AttrString::from("oauth2_consent_scope_map"),
+                PartialValue::Refer(rs_uuid),
+            ),
+            Modify::Removed(
+                AttrString::from("oauth2_session"),
+                PartialValue::Refer(rs_uuid),
+            ),
+        ]);
+
+        self.qs_write
+            .impersonate_modify(
+                // Filter as executed
+                &filter!(f_eq("uuid", PartialValue::Uuid(rge.target))),
+                // Filter as intended (acp)
+                &filter_all!(f_eq("uuid", PartialValue::Uuid(rge.target))),
+                &modlist,
+                // Like logging out, withdrawing access should not require a re-auth.
+                &rge.ident.project_with_scope(AccessScope::ReadWrite),
+            )
+            .map_err(|e| {
+                admin_error!("Failed to revoke oauth2 grant {:?}", e);
+                e
+            })
+    }
+
     pub fn service_account_into_person(
         &mut self,
         ident: &Identity,
@@ -717,6 +817,13 @@ pub struct ListUserAuthTokenEvent {
     pub target: Uuid,
 }
 
+pub struct ListOauth2GrantEvent {
+    // Who initiated this?
+    pub ident: Identity,
+    // Who is it targeting?
+    pub target: Uuid,
+}
+
 impl<'a> IdmServerProxyReadTransaction<'a> {
     pub fn account_list_user_auth_tokens(
         &mut self,
@@ -755,6 +862,8 @@ impl<'a> IdmServerProxyReadTransaction<'a> {
                                                 expiry: s.expiry,
                                                 issued_at: s.issued_at,
                                                 purpose,
+                                                label: s.label.clone(),
+                                                credential: credential_label(&e, s.cred_id),
                                             })
                                             .map_err(|e| {
                                                 admin_error!("Invalid user auth token {}", u);
@@ -772,6 +881,95 @@ impl<'a> IdmServerProxyReadTransaction<'a> {
             Err(e) => Err(e),
         }
     }
+
+    pub fn account_list_oauth2_grants(
+        &mut self,
+        lge: &ListOauth2GrantEvent,
+        ct: Duration,
+    ) -> Result<Vec<Oauth2Grant>, OperationError> {
+        let srch =
+            SearchEvent::from_target_uuid_request(lge.ident.clone(), lge.target, &self.qs_read)
+                .map_err(|e| {
+                    admin_error!("Failed to begin account list oauth2 grants: {:?}", e);
+                    e
+                })?;
+
+        let Some(entry) = self.qs_read.search_ext(&srch)?.pop() else {
+            return Ok(Vec::new());
+        };
+
+        let curtime_odt = OffsetDateTime::UNIX_EPOCH + ct;
+
+        // Gather the consent and live sessions of each resource server.
+        let mut grants: BTreeMap<Uuid, (BTreeSet<String>, usize, Option<OffsetDateTime>)> =
+            BTreeMap::new();
+
+        if let Some(consents) = entry.get_ava_as_oauthscopemaps("oauth2_consent_scope_map") {
+            for (rs_uuid, scopes) in consents {
+                grants.entry(*rs_uuid).or_default().0 = scopes.clone();
+            }
+        }
+
+        if let Some(sessions) = entry.get_ava_as_oauth2session_map("oauth2_session") {
+            sessions
+                .values()
+                .filter(|session| session.expiry.map(|exp| exp > curtime_odt).unwrap_or(true))
+                .for_each(|session| {
+                    let grant = grants.entry(session.rs_uuid).or_default();
+                    grant.1 += 1;
+                    grant.2 = grant.2.max(Some(session.issued_at));
+                });
+        }
+
+        grants
+            .into_iter()
+            .filter_map(|(rs_uuid, (scopes, session_count, last_issued_at))| {
+                // The resource server may have been deleted since consent was given.
+                let rs_entry = match self.qs_read.internal_search_uuid(rs_uuid) {
+                    Ok(rs_entry) => rs_entry,
+                    Err(OperationError::NoMatchingEntries) => return None,
+                    Err(e) => return Some(Err(e)),
+                };
+
+                let name = rs_entry
+                    .get_ava_single_iname("oauth2_rs_name")
+                    .map(str::to_string)?;
+                let display_name = rs_entry
+                    .get_ava_single_utf8("displayname")
+                    .map(str::to_string)
+                    .unwrap_or_else(|| name.clone());
+
+                Some(Ok(Oauth2Grant {
+                    name,
+                    display_name,
+                    scopes,
+                    session_count,
+                    last_issued_at,
+                }))
+            })
+            .collect()
+    }
+}
+
+/// Describe the credential of an account that a session was established with.
+fn credential_label(entry: &Entry<EntryReduced, EntryCommitted>, cred_id: Uuid) -> Option<String> {
+    if let Some(cred) = entry
+        .get_ava_single_credential("primary_credential")
+        .filter(|cred| cred.uuid == cred_id)
+    {
+        let label = match &cred.type_ {
+            CredentialType::Password(_) => "Password",
+            CredentialType::GeneratedPassword(_) => "Generated Password",
+            CredentialType::PasswordMfa(..) => "Password and MFA",
+            CredentialType::Webauthn(_) => "Security Key",
+        };
+        return Some(label.to_string());
+    }
+
+    entry
+        .get_ava_passkeys("passkeys")
+        .and_then(|passkeys| passkeys.get(&cred_id))
+        .map(|(label, _)| format!("Passkey ({})", label))
 }
 
 #[cfg(test)]
@@ -1,4 +1,5 @@
 use crate::prelude::*;
+use kanidm_proto::v1::AccessRequestState;
 use serde::{Deserialize, Serialize};
 use std::net::IpAddr;
 use time::OffsetDateTime;
@@ -27,4 +28,29 @@ pub enum AuditEvent {
         #[serde(with = "time::serde::timestamp")]
         time: OffsetDateTime,
     },
+    AccessRequest {
+        request: Uuid,
+        requester: Uuid,
+        group: Uuid,
+        /// The account that moved the request into this state.
+        actor: Uuid,
+        state: AccessRequestState,
+        #[serde(with = "time::serde::timestamp")]
+        time: OffsetDateTime,
+    },
+    IdentityVerification {
+        /// The account that submitted the code.
+        actor: Uuid,
+        /// The account that the code was read out by.
+        target: Uuid,
+        verified: bool,
+        #[serde(with = "time::serde::timestamp")]
+        time: OffsetDateTime,
+    },
+    HelpdeskCredentialReset {
+        actor: Uuid,
+        target: Uuid,
+        #[serde(with = "time::serde::timestamp")]
+        time: OffsetDateTime,
+    },
 }
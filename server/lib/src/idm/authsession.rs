@@ -1142,12 +1142,18 @@ impl AuthSession {
                     | AuthType::GeneratedPassword
                     | AuthType::PasswordMfa
                     | AuthType::Passkey => {
+                        // Record where the session came from so the owner can recognise it later.
+                        let label = match &self.source {
+                            Source::Https(ip) => format!("Auth Session from {}", ip),
+                            Source::Internal => "Auth Session".to_string(),
+                        };
+
                         trace!("⚠️   Queued AuthSessionRecord for {}", self.account.uuid);
                         async_tx.send(DelayedAction::AuthSessionRecord(AuthSessionRecord {
                             target_uuid: self.account.uuid,
                             session_id,
                             cred_id,
-                            label: "Auth Session".to_string(),
+                            label,
                             expiry: uat.expiry,
                             issued_at: uat.issued_at,
                             issued_by: IdentityId::User(self.account.uuid),
@@ -0,0 +1,269 @@
+//! An offline list of breached password hashes, in the format distributed by the
+//! [Pwned Passwords](https://haveibeenpwned.com/Passwords) project.
+//!
+//! Each line of the file is `HASH:COUNT`, where the hash is either the SHA-1 or the NTLM
+//! hash of the password in hex. The file *must* be sorted by hash (the "ordered by hash"
+//! download). Since these lists contain hundreds of millions of entries we never load the
+//! file into memory, instead a binary search is performed over the byte offsets of the
+//! file, so that each check only needs a few dozen small reads.
+
+use std::cmp::Ordering;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+use openssl::hash::{hash, MessageDigest};
+use openssl::nid::Nid;
+use openssl::sha::sha1;
+
+use crate::prelude::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BreachListHash {
+    Sha1,
+    Ntlm,
+}
+
+impl BreachListHash {
+    fn from_hex_len(len: usize) -> Option<Self> {
+        match len {
+            40 => Some(BreachListHash::Sha1),
+            32 => Some(BreachListHash::Ntlm),
+            _ => None,
+        }
+    }
+
+    fn digest_hex(self, cleartext: &str) -> Result<String, OperationError> {
+        match self {
+            BreachListHash::Sha1 => Ok(hex::encode_upper(sha1(cleartext.as_bytes()))),
+            BreachListHash::Ntlm => {
+                let clear_utf16le: Vec<u8> = cleartext
+                    .encode_utf16()
+                    .flat_map(|c| c.to_le_bytes())
+                    .collect();
+
+                MessageDigest::from_nid(Nid::MD4)
+                    .and_then(|dgst| hash(dgst, &clear_utf16le).ok())
+                    .map(hex::encode_upper)
+                    .ok_or_else(|| {
+                        admin_error!("Unable to digest MD4 - fips mode may be enabled, or you may need to activate the legacy provider.");
+                        OperationError::CryptographyError
+                    })
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct BreachList {
+    path: PathBuf,
+    hash_type: BreachListHash,
+}
+
+impl BreachList {
+    /// Open a breach list, determining the hash type from the first line of the file.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, OperationError> {
+        let path = path.as_ref().to_path_buf();
+
+        let mut reader = File::open(&path).map(BufReader::new).map_err(|e| {
+            admin_error!(?e, ?path, "Unable to open breached password list");
+            OperationError::FsError
+        })?;
+
+        let mut first = String::new();
+        reader.read_line(&mut first).map_err(|e| {
+            admin_error!(?e, ?path, "Unable to read breached password list");
+            OperationError::FsError
+        })?;
+
+        let hash_hex = line_hash(first.as_bytes());
+        let hash_type = if hash_hex.iter().all(u8::is_ascii_hexdigit) {
+            BreachListHash::from_hex_len(hash_hex.len())
+        } else {
+            None
+        }
+        .ok_or_else(|| {
+            admin_error!(
+                ?path,
+                "Breached password list must contain SHA-1 or NTLM hashes in the form HASH:COUNT"
+            );
+            OperationError::InvalidState
+        })?;
+
+        // Make sure we can actually check against this list before we accept it.
+        hash_type.digest_hex("")?;
+
+        admin_info!(?path, ?hash_type, "Loaded breached password list");
+        Ok(BreachList { path, hash_type })
+    }
+
+    pub fn hash_type(&self) -> BreachListHash {
+        self.hash_type
+    }
+
+    /// Check if this password is present in the breach list.
+    pub fn contains(&self, cleartext: &str) -> Result<bool, OperationError> {
+        let target = self.hash_type.digest_hex(cleartext)?;
+
+        self.search(target.as_bytes()).map_err(|e| {
+            admin_error!(?e, path = ?self.path, "Unable to search breached password list");
+            OperationError::FsError
+        })
+    }
+
+    /// Check if this password must be rejected as breached. If the list can't be
+    /// searched we fail closed, and the password is treated as breached.
+    pub fn is_breached(&self, cleartext: &str) -> bool {
+        self.contains(cleartext).unwrap_or_else(|e| {
+            admin_error!(?e, "Unable to check breached password list, rejecting");
+            true
+        })
+    }
+
+    fn search(&self, target: &[u8]) -> Result<bool, std::io::Error> {
+        let file = File::open(&self.path)?;
+        let mut hi = file.metadata()?.len();
+        let mut reader = BufReader::new(file);
+        let mut buf = Vec::with_capacity(64);
+
+        // lo is always the start of a line, and no line before lo or starting at or
+        // after hi can contain the target.
+        let mut lo = 0;
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+
+            // Find the first line that starts at or after mid.
+            let line_start = if mid == lo {
+                lo
+            } else {
+                reader.seek(SeekFrom::Start(mid - 1))?;
+                buf.clear();
+                mid - 1 + reader.read_until(b'\n', &mut buf)? as u64
+            };
+
+            if line_start >= hi {
+                hi = mid;
+                continue;
+            }
+
+            reader.seek(SeekFrom::Start(line_start))?;
+            buf.clear();
+            let line_len = reader.read_until(b'\n', &mut buf)? as u64;
+            if line_len == 0 {
+                // Unexpected end of file, the file was likely truncated after we opened it.
+                hi = line_start;
+                continue;
+            }
+
+            let hash_hex = line_hash(&buf).to_ascii_uppercase();
+            match target.cmp(hash_hex.as_slice()) {
+                Ordering::Equal => return Ok(true),
+                Ordering::Less => hi = line_start,
+                Ordering::Greater => lo = line_start + line_len,
+            }
+        }
+
+        Ok(false)
+    }
+}
+
+/// Extract the hash portion of a `HASH:COUNT` line.
+fn line_hash(line: &[u8]) -> &[u8] {
+    let hash = line.split(|b| *b == b':').next().unwrap_or_default();
+    let start = hash
+        .iter()
+        .position(|b| !b.is_ascii_whitespace())
+        .unwrap_or(hash.len());
+    let end = hash
+        .iter()
+        .rposition(|b| !b.is_ascii_whitespace())
+        .map(|i| i + 1)
+        .unwrap_or(start);
+    &hash[start..end]
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use super::{BreachList, BreachListHash};
+
+    fn write_list(name: &str, hashes: &[&str]) -> std::path::PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("kanidm-breachlist-{}-{}", name, std::process::id()));
+        let mut f = std::fs::File::create(&path).expect("Unable to create breach list");
+        for (i, h) in hashes.iter().enumerate() {
+            write!(f, "{}:{}\r\n", h, i + 1).expect("Unable to write breach list");
+        }
+        path
+    }
+
+    #[test]
+    fn test_breachlist_sha1() {
+        let _ = sketching::test_init();
+
+        // sha1 of "password", "hunter2" and "correct horse battery staple" amongst
+        // some other valid hashes, sorted.
+        let mut hashes = vec![
+            "000000005AD76BD555C1D6D771DE417A4B87E4B4",
+            "5BAA61E4C9B93F3F0682250B6CF8331B7EE68FD8",
+            "BFB6FA4E8D7E5BC3DBF1C7A2E08C6F0F69E7D0C5",
+            "F3BBBD66A63D4BF1747940578EC3D0103530E21D",
+            "FFFFFFFEE791CBAC0F6305CAF0CEE06BBE131160",
+            "0000000A0E3B9F25FF41DE4B5AC238C2D545C7A8",
+            "ABF7AAD6438836DBE526AA231ABDE2D0EEF74D42",
+        ];
+        hashes.sort_unstable();
+        let path = write_list("sha1", &hashes);
+
+        let list = BreachList::open(&path).expect("Unable to open breach list");
+        assert!(list.hash_type() == BreachListHash::Sha1);
+
+        assert!(list.contains("password").expect("search failed"));
+        assert!(list.contains("hunter2").expect("search failed"));
+        assert!(list
+            .contains("correct horse battery staple")
+            .expect("search failed"));
+        assert!(!list
+            .contains("ntaoeuntnaoeuhraohuercahu😍")
+            .expect("search failed"));
+
+        // Every line must be found, including the first and last.
+        for h in hashes.iter() {
+            assert!(list.search(h.as_bytes()).expect("search failed"));
+        }
+        assert!(!list
+            .search(b"0000000000000000000000000000000000000000")
+            .expect("search failed"));
+        assert!(!list
+            .search(b"FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF")
+            .expect("search failed"));
+
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_breachlist_unreadable() {
+        sketching::test_init();
+
+        let path = write_list("unreadable", &["5BAA61E4C9B93F3F0682250B6CF8331B7EE68FD8"]);
+        let list = BreachList::open(&path).expect("Unable to open breach list");
+        assert!(!list.is_breached("hunter2"));
+
+        // Once the list can't be read, every password is rejected.
+        std::fs::remove_file(&path).expect("Unable to remove breach list");
+        assert!(list.contains("hunter2").is_err());
+        assert!(list.is_breached("hunter2"));
+    }
+
+    #[test]
+    fn test_breachlist_invalid() {
+        let _ = sketching::test_init();
+
+        let path = write_list("invalid", &["password", "hunter2"]);
+        assert!(BreachList::open(&path).is_err());
+        let _ = std::fs::remove_file(path);
+
+        assert!(BreachList::open("/does/not/exist").is_err());
+    }
+}
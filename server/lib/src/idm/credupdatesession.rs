@@ -17,7 +17,7 @@ use webauthn_rs::prelude::{
 };
 
 use crate::credential::totp::{Totp, TOTP_DEFAULT_STEP};
-use crate::credential::{BackupCodes, Credential};
+use crate::credential::{password_history_contains, BackupCodes, Credential, Password};
 use crate::idm::account::Account;
 use crate::idm::server::{IdmServerCredUpdateTransaction, IdmServerProxyWriteTransaction};
 use crate::prelude::*;
@@ -33,6 +33,7 @@ const MINIMUM_INTENT_TTL: Duration = Duration::from_secs(300);
 pub enum PasswordQuality {
     TooShort(usize),
     BadListed,
+    PreviouslyUsed,
     Feedback(Vec<PasswordFeedback>),
 }
 
@@ -41,6 +42,12 @@ pub struct CredentialUpdateIntentToken {
     pub intent_id: String,
 }
 
+/// The time at which an intent token issued at `ct` with the requested ttl expires.
+pub(crate) fn intent_token_expiry(max_ttl: Option<Duration>, ct: Duration) -> Duration {
+    let mttl = max_ttl.unwrap_or_else(|| Duration::new(0, 0));
+    ct + mttl.clamp(MINIMUM_INTENT_TTL, MAXIMUM_INTENT_TTL)
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 struct CredentialUpdateSessionTokenInner {
     pub sessionid: Uuid,
@@ -321,7 +328,7 @@ impl InitCredentialUpdateEvent {
 }
 
 impl<'a> IdmServerProxyWriteTransaction<'a> {
-    fn validate_init_credential_update(
+    pub(crate) fn validate_init_credential_update(
         &mut self,
         target: Uuid,
         ident: &Identity,
@@ -552,9 +559,21 @@ impl<'a> IdmServerProxyWriteTransaction<'a> {
 
         // ==== AUTHORISATION CHECKED ===
 
+        self.create_credential_update_intent(&account, perms, event.max_ttl, ct)
+    }
+
+    /// Issue a credential update intent token for an account. The caller must have already
+    /// established that the requester is authorised to update the credentials described by
+    /// `perms`.
+    pub(crate) fn create_credential_update_intent(
+        &mut self,
+        account: &Account,
+        perms: CredUpdateSessionPerms,
+        max_ttl: Option<Duration>,
+        ct: Duration,
+    ) -> Result<CredentialUpdateIntentToken, OperationError> {
         // Build the intent token.
-        let mttl = event.max_ttl.unwrap_or_else(|| Duration::new(0, 0));
-        let max_ttl = ct + mttl.clamp(MINIMUM_INTENT_TTL, MAXIMUM_INTENT_TTL);
+        let max_ttl = intent_token_expiry(max_ttl, ct);
         // let sessionid = uuid_from_duration(max_ttl, self.sid);
         let intent_id = readable_password_from_random();
 
@@ -1004,7 +1023,10 @@ impl<'a> IdmServerProxyWriteTransaction<'a> {
                 .map_err(|e| {
                     request_error!(error = ?e);
                     e
-                })
+                })?;
+
+            // Let the person know, in case they weren't the one that changed them.
+            self.queue_credentials_changed_message(&session.account, ct)
         }
     }
 
@@ -1146,6 +1168,7 @@ impl<'a> IdmServerCredUpdateTransaction<'a> {
         &self,
         cleartext: &str,
         related_inputs: &[&str],
+        password_history: &[Password],
     ) -> Result<(), PasswordQuality> {
         // password strength and badlisting is always global, rather than per-pw-policy.
         // pw-policy as check on the account is about requirements for mfa for example.
@@ -1279,7 +1302,21 @@ impl<'a> IdmServerCredUpdateTransaction<'a> {
         // also, when pw_badlist_cache is read from DB, it is read as Value (iutf8 lowercase)
         if (*self.pw_badlist_cache).contains(&cleartext.to_lowercase()) {
             security_info!("Password found in badlist, rejecting");
-            Err(PasswordQuality::BadListed)
+            return Err(PasswordQuality::BadListed);
+        }
+
+        // check the offline breach list, if one is configured.
+        if let Some(breach_list) = self.breach_list {
+            if breach_list.is_breached(cleartext) {
+                security_info!("Password found in breached password list, rejecting");
+                return Err(PasswordQuality::BadListed);
+            }
+        }
+
+        // Finally, prevent the reuse of a previous password.
+        if password_history_contains(password_history.iter(), cleartext) {
+            security_info!("Password found in password history, rejecting");
+            Err(PasswordQuality::PreviouslyUsed)
         } else {
             Ok(())
         }
@@ -1305,16 +1342,23 @@ impl<'a> IdmServerCredUpdateTransaction<'a> {
         };
 
         // Check pw quality (future - acc policy applies).
-        self.check_password_quality(pw, session.account.related_inputs().as_slice())
-            .map_err(|e| match e {
-                PasswordQuality::TooShort(sz) => {
-                    OperationError::PasswordQuality(vec![PasswordFeedback::TooShort(sz)])
-                }
-                PasswordQuality::BadListed => {
-                    OperationError::PasswordQuality(vec![PasswordFeedback::BadListed])
-                }
-                PasswordQuality::Feedback(feedback) => OperationError::PasswordQuality(feedback),
-            })?;
+        self.check_password_quality(
+            pw,
+            session.account.related_inputs().as_slice(),
+            session.account.password_history.as_slice(),
+        )
+        .map_err(|e| match e {
+            PasswordQuality::TooShort(sz) => {
+                OperationError::PasswordQuality(vec![PasswordFeedback::TooShort(sz)])
+            }
+            PasswordQuality::BadListed => {
+                OperationError::PasswordQuality(vec![PasswordFeedback::BadListed])
+            }
+            PasswordQuality::PreviouslyUsed => {
+                OperationError::PasswordQuality(vec![PasswordFeedback::PreviouslyUsed])
+            }
+            PasswordQuality::Feedback(feedback) => OperationError::PasswordQuality(feedback),
+        })?;
 
         let ncred = match &session.primary {
             Some(primary) => {
@@ -1739,6 +1783,7 @@ mod tests {
 
     use kanidm_proto::v1::{
         AuthAllowed, AuthIssueSession, AuthMech, CUExtPortal, CredentialDetailType,
+        PasswordFeedback,
     };
     use uuid::uuid;
     use webauthn_authenticator_rs::softpasskey::SoftPasskey;
@@ -2277,6 +2322,91 @@ mod tests {
             .is_none());
     }
 
+    #[idm_test]
+    async fn test_idm_credential_update_password_history(
+        idms: &IdmServer,
+        _idms_delayed: &mut IdmServerDelayed,
+    ) {
+        let test_pw_a = "fo3EitierohF9AelaNgiem0Ei6vup4equo1Oogeevaetehah8Tobeengae3Ci0ooh0uki";
+        let test_pw_b = "Aech4ohp1ahPhai0shu4ieBahGh7pee6aiwahchae4ioPhah3ahxeiZ9uiCh1ahd";
+        let test_pw_c = "iuWohSheiY6oogh3AiLe5Iewee7eeR3Ohj2Ahg0ooKoiN9sheeY2Thohqu9ee0ok";
+        let ct = Duration::from_secs(TEST_CURRENT_TIME);
+
+        let (cust, _) = setup_test_session(idms, ct).await;
+        let cutxn = idms.cred_update_transaction().await;
+        cutxn
+            .credential_primary_set_password(&cust, ct, test_pw_a)
+            .expect("Failed to update the primary cred password");
+        drop(cutxn);
+        commit_session(idms, ct, cust).await;
+
+        // The current password can't be set again.
+        let (cust, _) = renew_test_session(idms, ct).await;
+        let cutxn = idms.cred_update_transaction().await;
+        let err = cutxn
+            .credential_primary_set_password(&cust, ct, test_pw_a)
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            OperationError::PasswordQuality(feedback)
+                if matches!(feedback.as_slice(), [PasswordFeedback::PreviouslyUsed])
+        ));
+
+        cutxn
+            .credential_primary_set_password(&cust, ct, test_pw_b)
+            .expect("Failed to update the primary cred password");
+        drop(cutxn);
+        commit_session(idms, ct, cust).await;
+
+        // Nor can the previous one.
+        let (cust, _) = renew_test_session(idms, ct).await;
+        let cutxn = idms.cred_update_transaction().await;
+        assert!(cutxn
+            .credential_primary_set_password(&cust, ct, test_pw_a)
+            .is_err());
+        drop(cutxn);
+        drop(cust);
+
+        // Only remember the current password from now on.
+        let mut idms_prox_write = idms.proxy_write(ct).await;
+        idms_prox_write
+            .qs_write
+            .internal_modify_uuid(
+                UUID_SYSTEM_CONFIG,
+                &ModifyList::new_purge_and_set("password_history_length", Value::Uint32(1)),
+            )
+            .expect("Failed to set password history length");
+        idms_prox_write.commit().expect("Failed to commit txn");
+
+        let (cust, _) = renew_test_session(idms, ct).await;
+        let cutxn = idms.cred_update_transaction().await;
+        cutxn
+            .credential_primary_set_password(&cust, ct, test_pw_c)
+            .expect("Failed to update the primary cred password");
+        drop(cutxn);
+        commit_session(idms, ct, cust).await;
+
+        let mut idms_prox_write = idms.proxy_write(ct).await;
+        let testperson = idms_prox_write
+            .qs_write
+            .internal_search_uuid(TESTPERSON_UUID)
+            .expect("failed");
+        assert_eq!(
+            testperson
+                .get_ava_password_history("password_history")
+                .map(|h| h.len()),
+            Some(1)
+        );
+        idms_prox_write.commit().expect("Failed to commit txn");
+
+        // The older passwords have been forgotten.
+        let (cust, _) = renew_test_session(idms, ct).await;
+        let cutxn = idms.cred_update_transaction().await;
+        cutxn
+            .credential_primary_set_password(&cust, ct, test_pw_a)
+            .expect("Failed to update the primary cred password");
+    }
+
     // Test set of primary account password
     //    - fail pw quality checks etc
     //    - set correctly.
@@ -1,10 +1,12 @@
 use std::collections::BTreeSet;
 
 use kanidm_proto::v1::UiHint;
-use kanidm_proto::v1::{Group as ProtoGroup, OperationError};
+use kanidm_proto::v1::{Filter as ProtoFilter, Group as ProtoGroup, OperationError};
 use uuid::Uuid;
 
 use crate::entry::{Entry, EntryCommitted, EntryReduced, EntrySealed};
+use crate::idm::server::IdmServerProxyReadTransaction;
+use crate::plugins::dyngroup::DynGroup;
 use crate::prelude::*;
 use crate::value::PartialValue;
 
@@ -140,3 +142,38 @@ impl Group {
         }
     }
 }
+
+pub struct DynGroupPreviewEvent {
+    pub ident: Identity,
+    pub filter: ProtoFilter,
+}
+
+impl<'a> IdmServerProxyReadTransaction<'a> {
+    /// Show which entries would be members of a dynamic group with this filter, so that
+    /// it can be checked before it is saved. Only entries that the requestor can see
+    /// are listed.
+    pub fn dyngroup_preview(
+        &mut self,
+        ev: &DynGroupPreviewEvent,
+    ) -> Result<Vec<String>, OperationError> {
+        let filter = Filter::from_ro(&ev.ident, &ev.filter, &mut self.qs_read)?;
+        DynGroup::validate_filter(self.qs_read.get_schema(), &filter)?;
+
+        let f_valid = filter
+            .validate(self.qs_read.get_schema())
+            .map_err(OperationError::SchemaViolation)?
+            .into_ignore_hidden();
+
+        let entries =
+            self.qs_read
+                .impersonate_search_ext_valid(f_valid.clone(), f_valid, &ev.ident)?;
+
+        Ok(entries
+            .iter()
+            .map(|e| {
+                e.get_ava_single_proto_string("spn")
+                    .unwrap_or_else(|| e.get_uuid().to_string())
+            })
+            .collect())
+    }
+}
@@ -0,0 +1,824 @@
+//! Identity verification allows two people to confirm they are talking to who they
+//! believe they are, such as when a user contacts the helpdesk to have their credentials
+//! reset.
+//!
+//! Each person has a secret `id_verification_key`. For any pair of people the server
+//! derives a time based code from both keys, and the direction of the exchange. Each
+//! party reads their code to the other, who submits it to the server to check it. Once
+//! the user has read their code to the helpdesk, the helpdesk may issue a credential
+//! reset token for the user, without needing the rights to modify the user's credentials
+//! directly.
+//!
+//! ```text
+//! helpdesk: Start                       -> ProvideCode (helpdesk -> user)
+//! user:     SubmitCode (helpdesk -> user) -> Success (user -> helpdesk)
+//! helpdesk: credential reset (user -> helpdesk) -> intent token
+//! ```
+
+use std::time::Duration;
+
+use kanidm_proto::internal::{IdentifyUserRequest, IdentifyUserResponse};
+use openssl::sha;
+use time::OffsetDateTime;
+
+use crate::credential::softlock::{CredSoftLock, CredSoftLockPolicy};
+use crate::credential::totp::{Totp, TotpAlgo, TotpDigits};
+use crate::idm::account::Account;
+use crate::idm::audit::AuditEvent;
+use crate::idm::credupdatesession::CredentialUpdateIntentToken;
+use crate::idm::server::IdmServerProxyWriteTransaction;
+use crate::prelude::*;
+use crate::utils::password_from_random;
+use crate::value::CredUpdateSessionPerms;
+
+/// How long, in seconds, each identity verification code is valid for. Codes are read aloud
+/// so this is longer than a typical totp step.
+const ID_VERIFICATION_TOTP_STEP: u64 = 60;
+
+pub struct IdentifyUserEvent {
+    pub ident: Identity,
+    pub target: Uuid,
+    pub request: IdentifyUserRequest,
+}
+
+pub struct HelpdeskCredentialResetEvent {
+    pub ident: Identity,
+    pub target: Uuid,
+    pub other_totp: u32,
+    pub max_ttl: Option<Duration>,
+}
+
+impl<'a> IdmServerProxyWriteTransaction<'a> {
+    pub fn identify_user(
+        &mut self,
+        ev: &IdentifyUserEvent,
+        ct: Duration,
+    ) -> Result<IdentifyUserResponse, OperationError> {
+        let caller = self.id_verification_caller(&ev.ident, ev.target)?;
+
+        match ev.request {
+            IdentifyUserRequest::Start => {
+                let totp = self.id_verification_totp(&ev.ident, caller, ev.target)?;
+                Ok(IdentifyUserResponse::ProvideCode {
+                    step: ID_VERIFICATION_TOTP_STEP as u32,
+                    totp: id_verification_code(&totp, ct)?,
+                })
+            }
+            IdentifyUserRequest::SubmitCode { other_totp } => {
+                let verified =
+                    self.id_verification_check(&ev.ident, caller, ev.target, other_totp, ct)?;
+
+                self.audit_pending.push(AuditEvent::IdentityVerification {
+                    actor: caller,
+                    target: ev.target,
+                    verified,
+                    time: OffsetDateTime::UNIX_EPOCH + ct,
+                });
+
+                if verified {
+                    let totp = self.id_verification_totp(&ev.ident, caller, ev.target)?;
+                    Ok(IdentifyUserResponse::Success {
+                        step: ID_VERIFICATION_TOTP_STEP as u32,
+                        totp: id_verification_code(&totp, ct)?,
+                    })
+                } else {
+                    Ok(IdentifyUserResponse::CodeFailure)
+                }
+            }
+        }
+    }
+
+    pub fn helpdesk_credential_reset(
+        &mut self,
+        ev: &HelpdeskCredentialResetEvent,
+        ct: Duration,
+    ) -> Result<CredentialUpdateIntentToken, OperationError> {
+        let caller = self.id_verification_caller(&ev.ident, ev.target)?;
+
+        // Resetting credentials is a change, so the helpdesk must have reauthenticated.
+        if ev.ident.access_scope() != AccessScope::ReadWrite {
+            security_access!("identity access scope is not permitted to modify");
+            return Err(OperationError::AccessDenied);
+        }
+
+        if !ev.ident.is_memberof(UUID_IDM_HELPDESK) {
+            security_access!(%caller, "identity is not a member of idm_helpdesk");
+            return Err(OperationError::AccessDenied);
+        }
+
+        let entry = self.qs_write.internal_search_uuid(ev.target)?;
+        // High privilege accounts must be recovered by an administrator instead.
+        if entry.attribute_equality("memberof", &PartialValue::Refer(UUID_IDM_HIGH_PRIVILEGE)) {
+            security_access!(target = %ev.target, "helpdesk may not reset high privilege accounts");
+            return Err(OperationError::AccessDenied);
+        }
+
+        if !self.id_verification_check(&ev.ident, caller, ev.target, ev.other_totp, ct)? {
+            security_access!(%caller, target = %ev.target, "identity verification code was incorrect");
+            // The transaction is not committed on failure, so send this one now.
+            let event = AuditEvent::IdentityVerification {
+                actor: caller,
+                target: ev.target,
+                verified: false,
+                time: OffsetDateTime::UNIX_EPOCH + ct,
+            };
+            if self.audit_tx.send(event).is_err() {
+                error!("Unable to submit identity verification audit event to queue");
+            }
+            return Err(OperationError::AccessDenied);
+        }
+
+        // ==== AUTHORISATION CHECKED ===
+
+        let account = Account::try_from_entry_rw(entry.as_ref(), &mut self.qs_write)?;
+        let perms = CredUpdateSessionPerms {
+            ext_cred_portal_can_view: account.sync_parent_uuid.is_some(),
+            primary_can_edit: true,
+            passkeys_can_edit: true,
+        };
+
+        let token = self.create_credential_update_intent(&account, perms, ev.max_ttl, ct)?;
+
+        security_info!(%caller, target = %ev.target, "Helpdesk issued credential reset");
+        self.audit_pending
+            .push(AuditEvent::HelpdeskCredentialReset {
+                actor: caller,
+                target: ev.target,
+                time: OffsetDateTime::UNIX_EPOCH + ct,
+            });
+
+        Ok(token)
+    }
+
+    /// Both parties of an identity verification must be distinct persons.
+    fn id_verification_caller(
+        &mut self,
+        ident: &Identity,
+        target: Uuid,
+    ) -> Result<Uuid, OperationError> {
+        let caller = ident.get_uuid().ok_or_else(|| {
+            admin_error!("Identity verification requires an authenticated user");
+            OperationError::NotAuthenticated
+        })?;
+
+        if caller == target {
+            admin_error!("Unable to verify your own identity");
+            return Err(OperationError::InvalidRequestState);
+        }
+
+        // The target is resolved with the access controls of the caller, so that an entry
+        // they can't see is indistinguishable from one that does not exist.
+        let filter = filter!(f_and!([
+            f_eq("uuid", PartialValue::Uuid(target)),
+            f_eq("class", PVCLASS_PERSON.clone())
+        ]));
+        let entries = self
+            .qs_write
+            .impersonate_search(filter.clone(), filter, ident)?;
+        if entries.is_empty() {
+            security_access!(%caller, %target, "identity verification target not found");
+            return Err(OperationError::NoMatchingEntries);
+        }
+
+        Ok(caller)
+    }
+
+    /// Check a code that `other` read out to `caller`. Failures are counted for each pair
+    /// of people, and once there are too many the codes are not checked until the
+    /// softlock expires, so that they can't be guessed.
+    fn id_verification_check(
+        &mut self,
+        ident: &Identity,
+        caller: Uuid,
+        other: Uuid,
+        other_totp: u32,
+        ct: Duration,
+    ) -> Result<bool, OperationError> {
+        let softlocks = self.id_verification_softlocks;
+        let mut softlock_write = softlocks.write();
+        let mut slock = softlock_write
+            .get(&(caller, other))
+            .cloned()
+            .unwrap_or_else(|| CredSoftLock::new(CredSoftLockPolicy::IdentityVerification));
+
+        slock.apply_time_step(ct);
+        if !slock.is_valid() {
+            security_access!(%caller, %other, "identity verification is softlocked");
+            return Err(OperationError::AccessDenied);
+        }
+
+        let verified = self
+            .id_verification_totp(ident, other, caller)
+            .map(|totp| totp.verify(other_totp, ct))?;
+
+        if !verified {
+            // Softlocks that have reset are no longer needed, so clean them up as we go.
+            let expired: Vec<_> = softlock_write
+                .iter()
+                .filter(|(_, slock)| slock.is_expired(ct))
+                .map(|(pair, _)| *pair)
+                .collect();
+            for pair in expired.iter() {
+                softlock_write.remove(pair);
+            }
+
+            slock.record_failure(ct);
+            softlock_write.insert((caller, other), slock);
+            softlock_write.commit();
+        }
+
+        Ok(verified)
+    }
+
+    /// The totp for codes that `from` reads out to `to`.
+    fn id_verification_totp(
+        &mut self,
+        ident: &Identity,
+        from: Uuid,
+        to: Uuid,
+    ) -> Result<Totp, OperationError> {
+        let from_key = self.id_verification_key(ident, from)?;
+        let to_key = self.id_verification_key(ident, to)?;
+
+        let mut hasher = sha::Sha256::new();
+        hasher.update(from.as_bytes());
+        hasher.update(from_key.as_bytes());
+        hasher.update(to.as_bytes());
+        hasher.update(to_key.as_bytes());
+
+        Ok(Totp::new(
+            hasher.finish().to_vec(),
+            ID_VERIFICATION_TOTP_STEP,
+            TotpAlgo::Sha256,
+            TotpDigits::Six,
+        ))
+    }
+
+    /// Retrieve the identity verification key of a person, generating one if this is the
+    /// first time they have taken part in an identity verification. Generating a key is a
+    /// change, so it requires the caller to have reauthenticated.
+    fn id_verification_key(
+        &mut self,
+        ident: &Identity,
+        uuid: Uuid,
+    ) -> Result<String, OperationError> {
+        let entry = self.qs_write.internal_search_uuid(uuid)?;
+
+        if !entry.attribute_equality("class", &PVCLASS_PERSON) {
+            admin_error!(%uuid, "Identity verification is only possible between persons");
+            return Err(OperationError::InvalidAccountState(
+                "Identity verification is only possible between persons".to_string(),
+            ));
+        }
+
+        if let Some(key) = entry.get_ava_single_secret("id_verification_key") {
+            return Ok(key.to_string());
+        }
+
+        if ident.access_scope() != AccessScope::ReadWrite {
+            security_access!(%uuid, "identity access scope is not permitted to create an identity verification key");
+            return Err(OperationError::AccessDenied);
+        }
+
+        let key = password_from_random();
+        self.qs_write
+            .internal_modify(
+                &filter!(f_eq("uuid", PartialValue::Uuid(uuid))),
+                &ModifyList::new_purge_and_set("id_verification_key", Value::new_secret_str(&key)),
+            )
+            .map_err(|e| {
+                admin_error!(?e, "Failed to store identity verification key");
+                e
+            })?;
+
+        Ok(key)
+    }
+}
+
+fn id_verification_code(totp: &Totp, ct: Duration) -> Result<u32, OperationError> {
+    totp.do_totp_duration_from_epoch(&ct).map_err(|e| {
+        admin_error!(?e, "Unable to generate identity verification code");
+        OperationError::CryptographyError
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{HelpdeskCredentialResetEvent, IdentifyUserEvent};
+    use crate::idm::audit::AuditEvent;
+    use crate::prelude::*;
+    use crate::testkit::test_person;
+    use kanidm_proto::internal::{IdentifyUserRequest, IdentifyUserResponse};
+
+    const UUID_TEST_HELPDESK: Uuid = uuid::uuid!("a4a6c4a6-6d0e-4b0a-9a3f-2f5bb9a0c101");
+    const UUID_TEST_USER: Uuid = uuid::uuid!("a4a6c4a6-6d0e-4b0a-9a3f-2f5bb9a0c102");
+    const UUID_TEST_OTHER: Uuid = uuid::uuid!("a4a6c4a6-6d0e-4b0a-9a3f-2f5bb9a0c103");
+
+    async fn setup(idms: &IdmServer, ct: Duration) {
+        let mut idms_prox_write = idms.proxy_write(ct).await;
+
+        assert!(idms_prox_write
+            .qs_write
+            .internal_create(vec![
+                test_person("test_helpdesk", UUID_TEST_HELPDESK),
+                test_person("test_user", UUID_TEST_USER),
+                test_person("test_other", UUID_TEST_OTHER),
+            ])
+            .is_ok());
+
+        assert!(idms_prox_write
+            .qs_write
+            .internal_modify_uuid(
+                UUID_IDM_HELPDESK,
+                &ModifyList::new_append("member", Value::Refer(UUID_TEST_HELPDESK))
+            )
+            .is_ok());
+        assert!(idms_prox_write.commit().is_ok());
+    }
+
+    async fn ident_rw(idms: &IdmServer, ct: Duration, uuid: Uuid) -> Identity {
+        let mut idms_prox_write = idms.proxy_write(ct).await;
+        let entry = idms_prox_write
+            .qs_write
+            .internal_search_uuid(uuid)
+            .expect("failed to find entry");
+        Identity::from_impersonate_entry_readwrite(entry)
+    }
+
+    async fn identify(
+        idms: &IdmServer,
+        ct: Duration,
+        ident: &Identity,
+        target: Uuid,
+        request: IdentifyUserRequest,
+    ) -> IdentifyUserResponse {
+        let mut idms_prox_write = idms.proxy_write(ct).await;
+        let ev = IdentifyUserEvent {
+            ident: ident.clone(),
+            target,
+            request,
+        };
+        let res = idms_prox_write
+            .identify_user(&ev, ct)
+            .expect("failed to identify user");
+        assert!(idms_prox_write.commit().is_ok());
+        res
+    }
+
+    fn expect_verification_audit(idms_audit: &mut IdmServerAudit, expect: bool) {
+        match idms_audit.audit_rx().try_recv() {
+            Ok(AuditEvent::IdentityVerification { verified, .. }) => assert_eq!(verified, expect),
+            _ => panic!("expected an identity verification audit event"),
+        }
+    }
+
+    #[idm_test(audit)]
+    async fn test_idm_helpdesk_credential_reset(
+        idms: &IdmServer,
+        _idms_delayed: &mut IdmServerDelayed,
+        idms_audit: &mut IdmServerAudit,
+    ) {
+        let ct = duration_from_epoch_now();
+        setup(idms, ct).await;
+
+        let helpdesk = ident_rw(idms, ct, UUID_TEST_HELPDESK).await;
+        let user = ident_rw(idms, ct, UUID_TEST_USER).await;
+
+        // The helpdesk reads their code to the user.
+        let IdentifyUserResponse::ProvideCode {
+            totp: helpdesk_totp,
+            ..
+        } = identify(
+            idms,
+            ct,
+            &helpdesk,
+            UUID_TEST_USER,
+            IdentifyUserRequest::Start,
+        )
+        .await
+        else {
+            panic!("expected a code to provide");
+        };
+
+        // A wrong code is rejected.
+        let res = identify(
+            idms,
+            ct,
+            &user,
+            UUID_TEST_HELPDESK,
+            IdentifyUserRequest::SubmitCode {
+                other_totp: (helpdesk_totp + 1) % 1_000_000,
+            },
+        )
+        .await;
+        assert_eq!(res, IdentifyUserResponse::CodeFailure);
+        expect_verification_audit(idms_audit, false);
+
+        // The user confirms the helpdesk, and gets their own code to read back.
+        let IdentifyUserResponse::Success {
+            totp: user_totp, ..
+        } = identify(
+            idms,
+            ct,
+            &user,
+            UUID_TEST_HELPDESK,
+            IdentifyUserRequest::SubmitCode {
+                other_totp: helpdesk_totp,
+            },
+        )
+        .await
+        else {
+            panic!("expected the helpdesk code to be accepted");
+        };
+        expect_verification_audit(idms_audit, true);
+
+        // The codes are directional.
+        assert_ne!(helpdesk_totp, user_totp);
+
+        let mut idms_prox_write = idms.proxy_write(ct).await;
+
+        // The helpdesk code can't be used to reset the user.
+        let ev = HelpdeskCredentialResetEvent {
+            ident: helpdesk.clone(),
+            target: UUID_TEST_USER,
+            other_totp: helpdesk_totp,
+            max_ttl: None,
+        };
+        assert!(matches!(
+            idms_prox_write.helpdesk_credential_reset(&ev, ct),
+            Err(OperationError::AccessDenied)
+        ));
+        expect_verification_audit(idms_audit, false);
+
+        // The user can't reset the helpdesk, even with a valid code.
+        let ev = HelpdeskCredentialResetEvent {
+            ident: user.clone(),
+            target: UUID_TEST_HELPDESK,
+            other_totp: helpdesk_totp,
+            max_ttl: None,
+        };
+        assert!(matches!(
+            idms_prox_write.helpdesk_credential_reset(&ev, ct),
+            Err(OperationError::AccessDenied)
+        ));
+
+        // The helpdesk must have reauthenticated.
+        let ev = HelpdeskCredentialResetEvent {
+            ident: helpdesk.project_with_scope(AccessScope::ReadOnly),
+            target: UUID_TEST_USER,
+            other_totp: user_totp,
+            max_ttl: None,
+        };
+        assert!(matches!(
+            idms_prox_write.helpdesk_credential_reset(&ev, ct),
+            Err(OperationError::AccessDenied)
+        ));
+
+        let ev = HelpdeskCredentialResetEvent {
+            ident: helpdesk.clone(),
+            target: UUID_TEST_USER,
+            other_totp: user_totp,
+            max_ttl: None,
+        };
+        let intent = idms_prox_write
+            .helpdesk_credential_reset(&ev, ct)
+            .expect("failed to reset credentials");
+        assert!(idms_prox_write.commit().is_ok());
+
+        match idms_audit.audit_rx().try_recv() {
+            Ok(AuditEvent::HelpdeskCredentialReset { actor, target, .. }) => {
+                assert_eq!(actor, UUID_TEST_HELPDESK);
+                assert_eq!(target, UUID_TEST_USER);
+            }
+            _ => panic!("expected a helpdesk credential reset audit event"),
+        }
+
+        // The intent can be exchanged by the user.
+        let mut idms_prox_write = idms.proxy_write(ct).await;
+        assert!(idms_prox_write
+            .exchange_intent_credential_update(intent, ct)
+            .is_ok());
+    }
+
+    #[idm_test(audit)]
+    async fn test_idm_identity_verification_softlock(
+        idms: &IdmServer,
+        _idms_delayed: &mut IdmServerDelayed,
+        idms_audit: &mut IdmServerAudit,
+    ) {
+        let ct = duration_from_epoch_now();
+        setup(idms, ct).await;
+
+        let helpdesk = ident_rw(idms, ct, UUID_TEST_HELPDESK).await;
+        let user = ident_rw(idms, ct, UUID_TEST_USER).await;
+        let other = ident_rw(idms, ct, UUID_TEST_OTHER).await;
+
+        let IdentifyUserResponse::ProvideCode {
+            totp: helpdesk_totp,
+            ..
+        } = identify(
+            idms,
+            ct,
+            &helpdesk,
+            UUID_TEST_USER,
+            IdentifyUserRequest::Start,
+        )
+        .await
+        else {
+            panic!("expected a code to provide");
+        };
+
+        // A few mistakes are allowed.
+        for _ in 0..3 {
+            let res = identify(
+                idms,
+                ct,
+                &user,
+                UUID_TEST_HELPDESK,
+                IdentifyUserRequest::SubmitCode {
+                    other_totp: (helpdesk_totp + 1) % 1_000_000,
+                },
+            )
+            .await;
+            assert_eq!(res, IdentifyUserResponse::CodeFailure);
+            expect_verification_audit(idms_audit, false);
+        }
+
+        // After that, even the correct code is refused.
+        let mut idms_prox_write = idms.proxy_write(ct).await;
+        let ev = IdentifyUserEvent {
+            ident: user.clone(),
+            target: UUID_TEST_HELPDESK,
+            request: IdentifyUserRequest::SubmitCode {
+                other_totp: helpdesk_totp,
+            },
+        };
+        assert_eq!(
+            idms_prox_write.identify_user(&ev, ct),
+            Err(OperationError::AccessDenied)
+        );
+        drop(idms_prox_write);
+
+        // Other people verifying the helpdesk are not affected.
+        let IdentifyUserResponse::ProvideCode {
+            totp: helpdesk_totp,
+            ..
+        } = identify(
+            idms,
+            ct,
+            &helpdesk,
+            UUID_TEST_OTHER,
+            IdentifyUserRequest::Start,
+        )
+        .await
+        else {
+            panic!("expected a code to provide");
+        };
+        let res = identify(
+            idms,
+            ct,
+            &other,
+            UUID_TEST_HELPDESK,
+            IdentifyUserRequest::SubmitCode {
+                other_totp: helpdesk_totp,
+            },
+        )
+        .await;
+        assert!(matches!(res, IdentifyUserResponse::Success { .. }));
+        expect_verification_audit(idms_audit, true);
+
+        // Failed credential resets are counted, even though their transaction is aborted.
+        let IdentifyUserResponse::ProvideCode {
+            totp: user_totp, ..
+        } = identify(
+            idms,
+            ct,
+            &user,
+            UUID_TEST_HELPDESK,
+            IdentifyUserRequest::Start,
+        )
+        .await
+        else {
+            panic!("expected a code to provide");
+        };
+
+        for _ in 0..3 {
+            let mut idms_prox_write = idms.proxy_write(ct).await;
+            let ev = HelpdeskCredentialResetEvent {
+                ident: helpdesk.clone(),
+                target: UUID_TEST_USER,
+                other_totp: (user_totp + 1) % 1_000_000,
+                max_ttl: None,
+            };
+            assert!(matches!(
+                idms_prox_write.helpdesk_credential_reset(&ev, ct),
+                Err(OperationError::AccessDenied)
+            ));
+            expect_verification_audit(idms_audit, false);
+        }
+
+        let ev = HelpdeskCredentialResetEvent {
+            ident: helpdesk.clone(),
+            target: UUID_TEST_USER,
+            other_totp: user_totp,
+            max_ttl: None,
+        };
+        let mut idms_prox_write = idms.proxy_write(ct).await;
+        assert!(matches!(
+            idms_prox_write.helpdesk_credential_reset(&ev, ct),
+            Err(OperationError::AccessDenied)
+        ));
+        drop(idms_prox_write);
+
+        // The lock expires an hour after the last failure.
+        let ct = ct + Duration::from_secs(3601);
+        let IdentifyUserResponse::ProvideCode {
+            totp: user_totp, ..
+        } = identify(
+            idms,
+            ct,
+            &user,
+            UUID_TEST_HELPDESK,
+            IdentifyUserRequest::Start,
+        )
+        .await
+        else {
+            panic!("expected a code to provide");
+        };
+        let ev = HelpdeskCredentialResetEvent {
+            ident: helpdesk,
+            target: UUID_TEST_USER,
+            other_totp: user_totp,
+            max_ttl: None,
+        };
+        let mut idms_prox_write = idms.proxy_write(ct).await;
+        assert!(idms_prox_write.helpdesk_credential_reset(&ev, ct).is_ok());
+        drop(idms_prox_write);
+
+        // Expired locks are removed when the next failure is recorded.
+        let res = identify(
+            idms,
+            ct,
+            &other,
+            UUID_TEST_HELPDESK,
+            IdentifyUserRequest::SubmitCode {
+                other_totp: (helpdesk_totp + 1) % 1_000_000,
+            },
+        )
+        .await;
+        assert_eq!(res, IdentifyUserResponse::CodeFailure);
+        expect_verification_audit(idms_audit, false);
+
+        let idms_prox_write = idms.proxy_write(ct).await;
+        let softlocks = idms_prox_write.id_verification_softlocks.read();
+        assert_eq!(softlocks.len(), 1);
+        assert!(softlocks.contains_key(&(UUID_TEST_OTHER, UUID_TEST_HELPDESK)));
+    }
+
+    #[idm_test]
+    async fn test_idm_identity_verification_access(
+        idms: &IdmServer,
+        _idms_delayed: &mut IdmServerDelayed,
+    ) {
+        let ct = duration_from_epoch_now();
+        setup(idms, ct).await;
+
+        let helpdesk = ident_rw(idms, ct, UUID_TEST_HELPDESK).await;
+        let helpdesk_ro = helpdesk.project_with_scope(AccessScope::ReadOnly);
+
+        // Entries that don't exist, and entries that aren't persons, are reported the same.
+        let mut idms_prox_write = idms.proxy_write(ct).await;
+        for target in [Uuid::new_v4(), UUID_IDM_HELPDESK] {
+            let ev = IdentifyUserEvent {
+                ident: helpdesk.clone(),
+                target,
+                request: IdentifyUserRequest::Start,
+            };
+            assert_eq!(
+                idms_prox_write.identify_user(&ev, ct),
+                Err(OperationError::NoMatchingEntries)
+            );
+        }
+
+        // A read only session can't create the verification keys.
+        let ev = IdentifyUserEvent {
+            ident: helpdesk_ro.clone(),
+            target: UUID_TEST_USER,
+            request: IdentifyUserRequest::Start,
+        };
+        assert_eq!(
+            idms_prox_write.identify_user(&ev, ct),
+            Err(OperationError::AccessDenied)
+        );
+        drop(idms_prox_write);
+
+        let mut idms_prox_write = idms.proxy_write(ct).await;
+        for uuid in [UUID_TEST_HELPDESK, UUID_TEST_USER] {
+            let entry = idms_prox_write
+                .qs_write
+                .internal_search_uuid(uuid)
+                .expect("failed to find entry");
+            assert!(!entry.attribute_pres("id_verification_key"));
+        }
+        drop(idms_prox_write);
+
+        // Once the keys exist, a read only session can verify.
+        let IdentifyUserResponse::ProvideCode { totp, .. } = identify(
+            idms,
+            ct,
+            &helpdesk,
+            UUID_TEST_USER,
+            IdentifyUserRequest::Start,
+        )
+        .await
+        else {
+            panic!("expected a code to provide");
+        };
+        assert_eq!(
+            identify(
+                idms,
+                ct,
+                &helpdesk_ro,
+                UUID_TEST_USER,
+                IdentifyUserRequest::Start
+            )
+            .await,
+            IdentifyUserResponse::ProvideCode {
+                step: super::ID_VERIFICATION_TOTP_STEP as u32,
+                totp
+            }
+        );
+    }
+
+    #[idm_test]
+    async fn test_idm_helpdesk_credential_reset_high_privilege(
+        idms: &IdmServer,
+        _idms_delayed: &mut IdmServerDelayed,
+    ) {
+        let ct = duration_from_epoch_now();
+        setup(idms, ct).await;
+
+        let helpdesk = ident_rw(idms, ct, UUID_TEST_HELPDESK).await;
+        let admin = ident_rw(idms, ct, UUID_ADMIN).await;
+
+        // Verification between persons works, but admin is not a person.
+        let mut idms_prox_write = idms.proxy_write(ct).await;
+        let ev = IdentifyUserEvent {
+            ident: admin,
+            target: UUID_TEST_HELPDESK,
+            request: IdentifyUserRequest::Start,
+        };
+        assert!(matches!(
+            idms_prox_write.identify_user(&ev, ct),
+            Err(OperationError::InvalidAccountState(_))
+        ));
+
+        // Nor can the helpdesk verify themself.
+        let ev = IdentifyUserEvent {
+            ident: helpdesk.clone(),
+            target: UUID_TEST_HELPDESK,
+            request: IdentifyUserRequest::Start,
+        };
+        assert_eq!(
+            idms_prox_write.identify_user(&ev, ct),
+            Err(OperationError::InvalidRequestState)
+        );
+        drop(idms_prox_write);
+
+        // Members of the helpdesk are high privilege, so can't reset each other.
+        let mut idms_prox_write = idms.proxy_write(ct).await;
+        assert!(idms_prox_write
+            .qs_write
+            .internal_modify_uuid(
+                UUID_IDM_HELPDESK,
+                &ModifyList::new_append("member", Value::Refer(UUID_TEST_OTHER))
+            )
+            .is_ok());
+        assert!(idms_prox_write.commit().is_ok());
+
+        let other = ident_rw(idms, ct, UUID_TEST_OTHER).await;
+        let IdentifyUserResponse::ProvideCode { totp, .. } = identify(
+            idms,
+            ct,
+            &other,
+            UUID_TEST_HELPDESK,
+            IdentifyUserRequest::Start,
+        )
+        .await
+        else {
+            panic!("expected a code to provide");
+        };
+
+        let mut idms_prox_write = idms.proxy_write(ct).await;
+        let ev = HelpdeskCredentialResetEvent {
+            ident: helpdesk,
+            target: UUID_TEST_OTHER,
+            other_totp: totp,
+            max_ttl: None,
+        };
+        assert!(matches!(
+            idms_prox_write.helpdesk_credential_reset(&ev, ct),
+            Err(OperationError::AccessDenied)
+        ));
+    }
+}
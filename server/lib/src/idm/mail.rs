@@ -0,0 +1,400 @@
+//! Messages that are delivered to people by mail, such as credential reset links. Messages
+//! are stored as entries so that they survive a restart, and the mail worker is woken to
+//! deliver them once the transaction that queued them has committed.
+//!
+//! Messages that can't be delivered are retried with an increasing delay, and are dropped
+//! once they have failed too many times.
+
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+use crate::idm::account::Account;
+use crate::idm::credupdatesession::intent_token_expiry;
+use crate::idm::server::IdmServerProxyWriteTransaction;
+use crate::prelude::*;
+
+/// How many times delivery of a message is attempted before it is dropped.
+pub const MAIL_DELIVERY_ATTEMPTS: u32 = 6;
+/// How long to wait before the first retry. This doubles after each failed attempt.
+const MAIL_RETRY_DELAY: Duration = Duration::from_secs(60);
+
+/// The content of a message. The mail worker renders this into the mail that is sent.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "template", rename_all = "snake_case")]
+pub enum MessageTemplate {
+    /// A link that allows the person to set their credentials.
+    CredentialReset {
+        name: String,
+        display_name: String,
+        intent_id: String,
+        #[serde(with = "time::serde::timestamp")]
+        expiry: OffsetDateTime,
+    },
+    /// A notice that the credentials of the person have been changed.
+    CredentialsChanged { name: String, display_name: String },
+}
+
+/// A message that is due for delivery.
+#[derive(Debug, Clone)]
+pub struct OutboundMessage {
+    pub uuid: Uuid,
+    pub to: String,
+    pub template: MessageTemplate,
+    pub attempts: u32,
+}
+
+pub struct CredentialResetEmailEvent {
+    pub ident: Identity,
+    pub target: Uuid,
+    pub max_ttl: Option<Duration>,
+}
+
+impl<'a> IdmServerProxyWriteTransaction<'a> {
+    /// Store a message so that it is delivered once this transaction commits.
+    pub(crate) fn queue_message(
+        &mut self,
+        to: &str,
+        template: &MessageTemplate,
+        ct: Duration,
+    ) -> Result<Uuid, OperationError> {
+        if !self.mail_enabled {
+            admin_error!("Unable to queue message, no mail relay is configured");
+            return Err(OperationError::InvalidState);
+        }
+
+        let value = serde_json::to_string(template).map_err(|e| {
+            admin_error!(?e, "Unable to serialise message template");
+            OperationError::SerdeJsonError
+        })?;
+
+        let message_uuid = Uuid::new_v4();
+        let e = entry_init!(
+            ("class", CLASS_OBJECT.clone()),
+            ("class", CLASS_OUTBOUND_MESSAGE.clone()),
+            ("uuid", Value::Uuid(message_uuid)),
+            ("mail_destination", Value::new_utf8s(to)),
+            ("message_template", Value::new_secret_str(&value)),
+            ("send_after", Value::new_datetime_epoch(ct)),
+            ("delivery_attempts", Value::new_uint32(0))
+        );
+
+        self.qs_write.internal_create(vec![e]).map_err(|e| {
+            admin_error!(?e, "Failed to queue message");
+            e
+        })?;
+
+        self.mail_pending = true;
+        Ok(message_uuid)
+    }
+
+    /// Issue a credential reset token for a person, and mail the link to their primary mail
+    /// address. The token is never revealed to the requester.
+    pub fn credential_reset_email(
+        &mut self,
+        ev: &CredentialResetEmailEvent,
+        ct: Duration,
+    ) -> Result<(), OperationError> {
+        if !self.mail_enabled {
+            admin_error!("Unable to mail credential reset, no mail relay is configured");
+            return Err(OperationError::InvalidState);
+        }
+
+        let (account, perms) = self.validate_init_credential_update(ev.target, &ev.ident)?;
+
+        // ==== AUTHORISATION CHECKED ===
+
+        let Some(to) = account.mail_primary.clone() else {
+            admin_error!(target = %ev.target, "Unable to mail credential reset, the account has no mail address");
+            return Err(OperationError::InvalidAccountState(
+                "Account has no mail address".to_string(),
+            ));
+        };
+
+        let token = self.create_credential_update_intent(&account, perms, ev.max_ttl, ct)?;
+        let template = MessageTemplate::CredentialReset {
+            name: account.name.clone(),
+            display_name: account.displayname.clone(),
+            intent_id: token.intent_id,
+            expiry: OffsetDateTime::UNIX_EPOCH + intent_token_expiry(ev.max_ttl, ct),
+        };
+        let message_uuid = self.queue_message(&to, &template, ct)?;
+
+        security_info!(target = %ev.target, message = %message_uuid, "Credential reset link queued for delivery");
+        Ok(())
+    }
+
+    /// Notify a person that their credentials were changed. This does nothing if mail is not
+    /// configured, or the person has no mail address.
+    pub(crate) fn queue_credentials_changed_message(
+        &mut self,
+        account: &Account,
+        ct: Duration,
+    ) -> Result<(), OperationError> {
+        let (true, Some(to)) = (self.mail_enabled, account.mail_primary.as_deref()) else {
+            return Ok(());
+        };
+
+        let template = MessageTemplate::CredentialsChanged {
+            name: account.name.clone(),
+            display_name: account.displayname.clone(),
+        };
+        self.queue_message(to, &template, ct).map(|_| ())
+    }
+
+    /// The messages that are due for delivery.
+    pub fn mail_queue_due(&mut self, ct: Duration) -> Result<Vec<OutboundMessage>, OperationError> {
+        let now = OffsetDateTime::UNIX_EPOCH + ct;
+        let entries = self
+            .qs_write
+            .internal_search(filter!(f_eq("class", PVCLASS_OUTBOUND_MESSAGE.clone())))?;
+
+        let due = entries
+            .iter()
+            .filter(|e| {
+                e.get_ava_single_datetime("send_after")
+                    .map(|send_after| send_after <= now)
+                    .unwrap_or(false)
+            })
+            .filter_map(|e| {
+                let template = e
+                    .get_ava_single_secret("message_template")
+                    .and_then(|t| {
+                        serde_json::from_str(t)
+                            .map_err(|err| {
+                                admin_warn!(?err, message = %e.get_uuid(), "Ignoring invalid message template");
+                            })
+                            .ok()
+                    })?;
+                Some(OutboundMessage {
+                    uuid: e.get_uuid(),
+                    to: e.get_ava_single_utf8("mail_destination")?.to_string(),
+                    template,
+                    attempts: e.get_ava_single_uint32("delivery_attempts").unwrap_or(0),
+                })
+            })
+            .collect();
+
+        Ok(due)
+    }
+
+    /// Remove a message that has been delivered.
+    pub fn mail_delivered(&mut self, message_uuid: Uuid) -> Result<(), OperationError> {
+        // The template may hold a reset token, so it must not be kept in the recycle bin.
+        self.qs_write
+            .internal_modify_uuid(message_uuid, &ModifyList::new_purge("message_template"))?;
+        self.qs_write.internal_delete_uuid(message_uuid)
+    }
+
+    /// Record that delivery of a message failed. The message is scheduled for another attempt,
+    /// or dropped if it has failed too many times. Returns if the message will be retried.
+    pub fn mail_delivery_failed(
+        &mut self,
+        message: &OutboundMessage,
+        error: &str,
+        ct: Duration,
+    ) -> Result<bool, OperationError> {
+        let attempts = message.attempts + 1;
+        if attempts >= MAIL_DELIVERY_ATTEMPTS {
+            admin_error!(message = %message.uuid, to = %message.to, attempts, %error, "Unable to deliver message, giving up");
+            self.mail_delivered(message.uuid)?;
+            return Ok(false);
+        }
+
+        let delay = MAIL_RETRY_DELAY * 2_u32.pow(attempts - 1);
+        admin_warn!(message = %message.uuid, to = %message.to, attempts, %error, ?delay, "Unable to deliver message, will retry");
+
+        let modlist = ModifyList::new_list(vec![
+            m_purge("send_after"),
+            Modify::Present(
+                AttrString::from("send_after"),
+                Value::new_datetime_epoch(ct + delay),
+            ),
+            m_purge("delivery_attempts"),
+            Modify::Present(
+                AttrString::from("delivery_attempts"),
+                Value::new_uint32(attempts),
+            ),
+        ]);
+        self.qs_write
+            .internal_modify_uuid(message.uuid, &modlist)
+            .map(|_| true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::{CredentialResetEmailEvent, MessageTemplate, MAIL_DELIVERY_ATTEMPTS};
+    use crate::idm::credupdatesession::{
+        CredentialUpdateIntentToken, InitCredentialUpdateIntentEvent,
+    };
+    use crate::prelude::*;
+    use crate::testkit::test_person;
+
+    const UUID_TEST_USER: Uuid = uuid::uuid!("4d2a6a9b-5d1f-46a2-9f3c-0c8f3e4f1a11");
+    const TEST_CURRENT_TIME: u64 = 6000;
+
+    fn reset_event(idm_admin: Arc<EntrySealedCommitted>) -> CredentialResetEmailEvent {
+        CredentialResetEmailEvent {
+            ident: Identity::from_impersonate_entry_readwrite(idm_admin),
+            target: UUID_TEST_USER,
+            max_ttl: None,
+        }
+    }
+
+    #[idm_test]
+    async fn test_idm_mail_credential_reset(
+        idms: &IdmServer,
+        _idms_delayed: &mut IdmServerDelayed,
+    ) {
+        let ct = Duration::from_secs(TEST_CURRENT_TIME);
+        let mut idms_prox_write = idms.proxy_write(ct).await;
+        let mut e = test_person("test_user", UUID_TEST_USER);
+        e.add_ava(
+            "mail",
+            Value::new_email_address_primary_s("test_user@example.com").unwrap(),
+        );
+        assert!(idms_prox_write.qs_write.internal_create(vec![e]).is_ok());
+        let idm_admin = idms_prox_write
+            .qs_write
+            .internal_search_uuid(UUID_IDM_ADMIN)
+            .expect("Unable to find idm_admin");
+
+        // Nothing can be mailed until a relay is configured.
+        assert_eq!(
+            idms_prox_write.credential_reset_email(&reset_event(idm_admin.clone()), ct),
+            Err(OperationError::InvalidState)
+        );
+
+        idms_prox_write.mail_enabled = true;
+        assert!(idms_prox_write
+            .credential_reset_email(&reset_event(idm_admin), ct)
+            .is_ok());
+
+        let mut due = idms_prox_write
+            .mail_queue_due(ct)
+            .expect("Unable to list the mail queue");
+        assert_eq!(due.len(), 1);
+        let message = due.pop().expect("No message was queued");
+        assert_eq!(message.to, "test_user@example.com");
+        assert_eq!(message.attempts, 0);
+        let MessageTemplate::CredentialReset {
+            name, intent_id, ..
+        } = &message.template
+        else {
+            panic!("Unexpected message template");
+        };
+        assert_eq!(name, "test_user");
+
+        // The mailed token can be used to update the credentials.
+        let intent = CredentialUpdateIntentToken {
+            intent_id: intent_id.clone(),
+        };
+        assert!(idms_prox_write
+            .exchange_intent_credential_update(intent, ct)
+            .is_ok());
+
+        // A failed delivery is retried after a delay.
+        assert!(matches!(
+            idms_prox_write.mail_delivery_failed(&message, "relay unavailable", ct),
+            Ok(true)
+        ));
+        assert!(idms_prox_write
+            .mail_queue_due(ct)
+            .expect("Unable to list the mail queue")
+            .is_empty());
+        let retry_ct = ct + Duration::from_secs(60);
+        let message = idms_prox_write
+            .mail_queue_due(retry_ct)
+            .expect("Unable to list the mail queue")
+            .pop()
+            .expect("The message was not retried");
+        assert_eq!(message.attempts, 1);
+
+        // Once delivered, the message is removed.
+        assert!(idms_prox_write.mail_delivered(message.uuid).is_ok());
+        assert!(idms_prox_write
+            .mail_queue_due(retry_ct)
+            .expect("Unable to list the mail queue")
+            .is_empty());
+
+        assert!(idms_prox_write.commit().is_ok());
+    }
+
+    #[idm_test]
+    async fn test_idm_mail_credential_reset_no_mail(
+        idms: &IdmServer,
+        _idms_delayed: &mut IdmServerDelayed,
+    ) {
+        let ct = Duration::from_secs(TEST_CURRENT_TIME);
+        let mut idms_prox_write = idms.proxy_write(ct).await;
+        assert!(idms_prox_write
+            .qs_write
+            .internal_create(vec![test_person("test_user", UUID_TEST_USER)])
+            .is_ok());
+        idms_prox_write.mail_enabled = true;
+        let idm_admin = idms_prox_write
+            .qs_write
+            .internal_search_uuid(UUID_IDM_ADMIN)
+            .expect("Unable to find idm_admin");
+
+        assert!(matches!(
+            idms_prox_write.credential_reset_email(&reset_event(idm_admin.clone()), ct),
+            Err(OperationError::InvalidAccountState(_))
+        ));
+
+        // A token can still be issued to be handed over some other way.
+        let ev = InitCredentialUpdateIntentEvent::new_impersonate_entry(
+            idm_admin,
+            UUID_TEST_USER,
+            Duration::from_secs(3600),
+        );
+        assert!(idms_prox_write
+            .init_credential_update_intent(&ev, ct)
+            .is_ok());
+        assert!(idms_prox_write
+            .mail_queue_due(ct)
+            .expect("Unable to list the mail queue")
+            .is_empty());
+    }
+
+    #[idm_test]
+    async fn test_idm_mail_delivery_gives_up(
+        idms: &IdmServer,
+        _idms_delayed: &mut IdmServerDelayed,
+    ) {
+        let ct = Duration::from_secs(TEST_CURRENT_TIME);
+        let mut idms_prox_write = idms.proxy_write(ct).await;
+        idms_prox_write.mail_enabled = true;
+
+        let template = MessageTemplate::CredentialsChanged {
+            name: "test_user".to_string(),
+            display_name: "Test User".to_string(),
+        };
+        assert!(idms_prox_write
+            .queue_message("test_user@example.com", &template, ct)
+            .is_ok());
+
+        let mut message = idms_prox_write
+            .mail_queue_due(ct)
+            .expect("Unable to list the mail queue")
+            .pop()
+            .expect("No message was queued");
+        assert_eq!(message.template, template);
+
+        message.attempts = MAIL_DELIVERY_ATTEMPTS - 1;
+        assert!(matches!(
+            idms_prox_write.mail_delivery_failed(&message, "mailbox unavailable", ct),
+            Ok(false)
+        ));
+        assert!(idms_prox_write
+            .mail_queue_due(ct + Duration::from_secs(86400))
+            .expect("Unable to list the mail queue")
+            .is_empty());
+
+        assert!(idms_prox_write.commit().is_ok());
+    }
+}
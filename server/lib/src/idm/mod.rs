@@ -3,15 +3,20 @@
 //! actions in the [QueryServer](crate::server::QueryServer). Generally this is where "Identity Management" policy and code
 //! is implemented.
 
+pub mod accessprofile;
+pub mod accessrequest;
 pub mod account;
 pub mod applinks;
 pub mod audit;
 pub mod authsession;
+pub mod breachlist;
 pub mod credupdatesession;
 pub mod delayed;
 pub mod event;
 pub mod group;
+pub mod identityverification;
 pub mod ldap;
+pub mod mail;
 pub mod oauth2;
 pub mod radius;
 pub mod reauth;
@@ -19,6 +24,7 @@ pub mod scim;
 pub mod server;
 pub mod serviceaccount;
 pub mod unix;
+pub mod webhook;
 
 use std::fmt;
 
@@ -7,6 +7,7 @@
 use std::collections::{BTreeMap, BTreeSet};
 use std::convert::TryFrom;
 use std::fmt;
+use std::str::FromStr;
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -14,33 +15,60 @@ use base64::{engine::general_purpose, Engine as _};
 
 use base64urlsafedata::Base64UrlSafeData;
 pub use compact_jwt::{JwkKeySet, OidcToken};
-use compact_jwt::{JwsSigner, OidcClaims, OidcSubject};
+use compact_jwt::{Jws, JwsSigner, JwsUnverified, JwsValidator, OidcClaims, OidcSubject};
 use concread::cowcell::*;
 use fernet::Fernet;
 use hashbrown::HashMap;
 pub use kanidm_proto::oauth2::{
     AccessTokenIntrospectRequest, AccessTokenIntrospectResponse, AccessTokenRequest,
-    AccessTokenResponse, AuthorisationRequest, CodeChallengeMethod, ErrorResponse, GrantTypeReq,
-    OidcDiscoveryResponse, TokenRevokeRequest,
+    AccessTokenResponse, AuthorisationRequest, AuthorisationRequestRef, ClientRegistrationRequest,
+    ClientRegistrationResponse, CodeChallengeMethod, EndSessionRequest, EndSessionResponse,
+    ErrorResponse, GrantTypeReq, OidcDiscoveryResponse, PushedAuthorisationRequest,
+    PushedAuthorisationResponse, TokenRevokeRequest,
 };
 use kanidm_proto::oauth2::{
-    ClaimType, DisplayValue, GrantType, IdTokenSignAlg, ResponseMode, ResponseType, SubjectType,
-    TokenEndpointAuthMethod,
+    ClaimType, DisplayValue, GrantType, IdTokenSignAlg, Oauth2Confirmation, ResponseMode,
+    ResponseType, SubjectType, TokenEndpointAuthMethod, OAUTH2_CLIENT_ASSERTION_TYPE_JWT_BEARER,
+    OAUTH2_PAR_REQUEST_URI_PREFIX, OAUTH2_TOKEN_TYPE_ACCESS_TOKEN,
 };
 use kanidm_proto::v1::UserAuthToken;
+use openssl::hash::MessageDigest;
 use openssl::sha;
+use openssl::x509::X509Ref;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use time::OffsetDateTime;
 use tracing::trace;
 use url::{Origin, Url};
 
-use crate::idm::account::Account;
+use crate::idm::account::{Account, DestroySessionTokenEvent};
 use crate::idm::server::{
     IdmServerProxyReadTransaction, IdmServerProxyWriteTransaction, IdmServerTransaction,
 };
 use crate::prelude::*;
+use crate::utils::password_from_random;
 use crate::value::{Oauth2Session, OAUTHSCOPE_RE};
 
+/// The event that marks a logout token as a back-channel logout.
+const BACKCHANNEL_LOGOUT_EVENT: &str = "http://schemas.openid.net/event/backchannel-logout";
+
+/// How long a back-channel logout token is valid for once issued.
+const BACKCHANNEL_LOGOUT_TOKEN_EXPIRY: u64 = 120;
+
+/// The furthest in the future a client assertion may expire. As we don't track the jti of
+/// assertions we have seen, this limits how long one can be replayed for.
+const CLIENT_ASSERTION_MAX_EXPIRY: i64 = 300;
+
+/// How long the request_uri of a pushed authorisation request is valid for. The client
+/// redirects the user agent with it immediately, so this can be short.
+const PUSHED_AUTHORISATION_REQUEST_EXPIRY: u64 = 60;
+
+/// How many pushed authorisation requests may be waiting to be used, for each client and in
+/// total. Public clients can push requests without a secret, so this bounds the memory that
+/// pushed requests can consume.
+const PUSHED_AUTHORISATION_REQUEST_MAX_PER_CLIENT: usize = 64;
+const PUSHED_AUTHORISATION_REQUEST_MAX: usize = 4096;
+
 #[derive(Serialize, Deserialize, Debug, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub enum Oauth2Error {
@@ -62,6 +90,14 @@ pub enum Oauth2Error {
     InsufficientScope,
     // from https://datatracker.ietf.org/doc/html/rfc7009#section-2.2.1
     UnsupportedTokenType,
+    // from https://datatracker.ietf.org/doc/html/rfc8693#section-2.2.2
+    InvalidTarget,
+    // from https://datatracker.ietf.org/doc/html/rfc9101#section-6.3
+    InvalidRequestUri,
+    InvalidRequestObject,
+    // from https://datatracker.ietf.org/doc/html/rfc7591#section-3.2.2
+    InvalidRedirectUri,
+    InvalidClientMetadata,
 }
 
 impl std::fmt::Display for Oauth2Error {
@@ -81,10 +117,45 @@ impl std::fmt::Display for Oauth2Error {
             Oauth2Error::InvalidToken => "invalid_token",
             Oauth2Error::InsufficientScope => "insufficient_scope",
             Oauth2Error::UnsupportedTokenType => "unsupported_token_type",
+            Oauth2Error::InvalidTarget => "invalid_target",
+            Oauth2Error::InvalidRequestUri => "invalid_request_uri",
+            Oauth2Error::InvalidRequestObject => "invalid_request_object",
+            Oauth2Error::InvalidRedirectUri => "invalid_redirect_uri",
+            Oauth2Error::InvalidClientMetadata => "invalid_client_metadata",
         })
     }
 }
 
+/// An oauth2 session that was removed from an account. Once the change commits, the
+/// resource server is told about this with a back-channel logout.
+#[derive(Debug, Clone)]
+pub struct Oauth2SessionEnded {
+    pub account: Uuid,
+    pub rs_uuid: Uuid,
+    pub session_id: Uuid,
+}
+
+/// A signed back-channel logout token, ready to be posted to a resource server.
+#[derive(Debug, Clone)]
+pub struct Oauth2BackchannelLogout {
+    pub rs_name: String,
+    pub url: Url,
+    pub logout_token: String,
+}
+
+// https://openid.net/specs/openid-connect-backchannel-1_0.html#LogoutToken
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct LogoutToken {
+    iss: Url,
+    sub: OidcSubject,
+    aud: String,
+    iat: i64,
+    exp: i64,
+    jti: Uuid,
+    sid: Uuid,
+    events: BTreeMap<String, serde_json::Value>,
+}
+
 // == internal state formats that we encrypt and send.
 
 #[derive(Serialize, Deserialize, Debug, PartialEq)]
@@ -121,6 +192,16 @@ struct TokenExchangeCode {
     pub nonce: Option<String>,
 }
 
+/// An authorisation request that a client pushed to us, held until the user agent is
+/// redirected to the authorisation endpoint with its request_uri.
+#[derive(Debug, Clone)]
+pub(crate) struct Oauth2PushedAuthorisation {
+    // The resource server that pushed the request, and is the only one that may use it.
+    rs_uuid: Uuid,
+    auth_req: AuthorisationRequest,
+    expiry: Duration,
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub(crate) enum Oauth2TokenType {
     Access {
@@ -135,6 +216,9 @@ pub(crate) enum Oauth2TokenType {
         auth_time: Option<i64>,
         // We stash some details here for oidc.
         nonce: Option<String>,
+        // The thumbprint of the tls client certificate this token is bound to.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        cnf: Option<String>,
     },
     Refresh {
         scopes: BTreeSet<String>,
@@ -191,6 +275,51 @@ pub struct AuthorisePermitSuccess {
     pub code: String,
 }
 
+/// The credentials a client presented alongside its request, rather than within it.
+#[derive(Debug, Clone, Default)]
+pub struct ClientAuthInfo {
+    /// The content of a basic authorization header.
+    pub basic_authz: Option<String>,
+    /// The base64url encoded sha256 thumbprint of the tls client certificate of the
+    /// connection, if one was presented.
+    pub client_cert: Option<String>,
+}
+
+impl From<Option<&str>> for ClientAuthInfo {
+    fn from(basic_authz: Option<&str>) -> Self {
+        ClientAuthInfo {
+            basic_authz: basic_authz.map(str::to_string),
+            client_cert: None,
+        }
+    }
+}
+
+impl From<&str> for ClientAuthInfo {
+    fn from(basic_authz: &str) -> Self {
+        Some(basic_authz).into()
+    }
+}
+
+/// The thumbprint that a tls client certificate is registered and bound to tokens by.
+/// <https://datatracker.ietf.org/doc/html/rfc8705#section-3.1>
+pub fn tls_client_cert_thumbprint(cert: &X509Ref) -> Result<String, OperationError> {
+    cert.digest(MessageDigest::sha256())
+        .map(|digest| general_purpose::URL_SAFE_NO_PAD.encode(digest))
+        .map_err(|e| {
+            admin_error!(err = ?e, "Unable to digest tls client certificate");
+            OperationError::CryptographyError
+        })
+}
+
+/// The client credentials that may be sent in the body of a request.
+#[derive(Default, Clone, Copy)]
+struct ClientCredentials<'b> {
+    client_id: Option<&'b str>,
+    client_secret: Option<&'b str>,
+    client_assertion_type: Option<&'b str>,
+    client_assertion: Option<&'b str>,
+}
+
 #[derive(Clone)]
 enum OauthRSType {
     Basic {
@@ -234,8 +363,24 @@ pub struct Oauth2RS {
     token_endpoint: Url,
     userinfo_endpoint: Url,
     jwks_uri: Url,
+    end_session_endpoint: Url,
+    pushed_authorization_request_endpoint: Url,
+    registration_endpoint: Url,
     scopes_supported: BTreeSet<String>,
     prefer_short_username: bool,
+    // Where back-channel logout tokens are sent when a session of this rs ends.
+    backchannel_logout_uri: Option<Url>,
+    // The resource servers that may exchange their access tokens for ours.
+    token_exchange_from: BTreeSet<Uuid>,
+    // The keys this rs signs client assertions with, for private_key_jwt authentication.
+    client_jwks: Option<JwkKeySet>,
+    // The thumbprints of the tls client certificates this rs may authenticate with.
+    tls_client_certs: BTreeSet<String>,
+    // If authorisation requests must be pushed to us by the client before the user agent
+    // is redirected, so that they can't be tampered with.
+    require_pushed_authorisation: bool,
+    // If this is a first party rs that users are never asked to consent to.
+    consent_prompt_disable: bool,
     type_: OauthRSType,
 }
 
@@ -415,6 +560,39 @@ impl<'a> Oauth2ResourceServersWriteTransaction<'a> {
                     .get_ava_single_bool("oauth2_prefer_short_username")
                     .unwrap_or(false);
 
+                let backchannel_logout_uri = ent
+                    .get_ava_single_url("oauth2_rs_backchannel_logout_uri")
+                    .cloned();
+
+                let token_exchange_from = ent
+                    .get_ava_refer("oauth2_rs_token_exchange_from")
+                    .cloned()
+                    .unwrap_or_default();
+
+                // An invalid key set must not allow the basic secret to be used again, so
+                // it is loaded as an empty set that no assertion can satisfy.
+                let client_jwks = ent
+                    .get_ava_single_utf8("oauth2_rs_jwks")
+                    .map(|jwks| {
+                        serde_json::from_str::<JwkKeySet>(jwks).unwrap_or_else(|e| {
+                            admin_error!(err = ?e, "{} has an invalid jwks, client assertions will be rejected", name);
+                            JwkKeySet { keys: Vec::new() }
+                        })
+                    });
+
+                let tls_client_certs = ent
+                    .get_ava_iter_utf8("oauth2_rs_tls_client_cert")
+                    .map(|iter| iter.map(str::to_string).collect())
+                    .unwrap_or_default();
+
+                let require_pushed_authorisation = ent
+                    .get_ava_single_bool("oauth2_require_pushed_authorisation")
+                    .unwrap_or(false);
+
+                let consent_prompt_disable = ent
+                    .get_ava_single_bool("oauth2_consent_prompt_disable")
+                    .unwrap_or(false);
+
                 let mut authorization_endpoint = self.inner.origin.clone();
                 authorization_endpoint.set_path("/ui/oauth2");
 
@@ -427,6 +605,16 @@ impl<'a> Oauth2ResourceServersWriteTransaction<'a> {
                 let mut jwks_uri = self.inner.origin.clone();
                 jwks_uri.set_path(&format!("/oauth2/openid/{name}/public_key.jwk"));
 
+                // Like authorisation, logout needs the users session, so it's handled by the ui.
+                let mut end_session_endpoint = self.inner.origin.clone();
+                end_session_endpoint.set_path("/ui/oauth2/logout");
+
+                let mut pushed_authorization_request_endpoint = self.inner.origin.clone();
+                pushed_authorization_request_endpoint.set_path("/oauth2/par");
+
+                let mut registration_endpoint = self.inner.origin.clone();
+                registration_endpoint.set_path("/oauth2/register");
+
                 let mut iss = self.inner.origin.clone();
                 iss.set_path(&format!("/oauth2/openid/{name}"));
 
@@ -460,8 +648,17 @@ impl<'a> Oauth2ResourceServersWriteTransaction<'a> {
                     token_endpoint,
                     userinfo_endpoint,
                     jwks_uri,
+                    end_session_endpoint,
+                    pushed_authorization_request_endpoint,
+                    registration_endpoint,
                     scopes_supported,
                     prefer_short_username,
+                    backchannel_logout_uri,
+                    token_exchange_from,
+                    client_jwks,
+                    tls_client_certs,
+                    require_pushed_authorisation,
+                    consent_prompt_disable,
                     type_,
                 };
 
@@ -485,29 +682,21 @@ impl<'a> Oauth2ResourceServersWriteTransaction<'a> {
 impl<'a> IdmServerProxyWriteTransaction<'a> {
     pub fn oauth2_token_revoke(
         &mut self,
-        client_authz: &str,
+        client_auth_info: &ClientAuthInfo,
         revoke_req: &TokenRevokeRequest,
         ct: Duration,
     ) -> Result<(), Oauth2Error> {
-        let (client_id, secret) = parse_basic_authz(client_authz)?;
-
-        // Get the o2rs for the handle.
-        let o2rs = self.oauth2rs.inner.rs_set.get(&client_id).ok_or_else(|| {
-            admin_warn!("Invalid oauth2 client_id");
-            Oauth2Error::AuthenticationRequired
-        })?;
-
-        // check the secret.
-        match &o2rs.type_ {
-            OauthRSType::Basic { authz_secret, .. } => {
-                if authz_secret != &secret {
-                    security_info!("Invalid oauth2 client_id secret");
-                    return Err(Oauth2Error::AuthenticationRequired);
-                }
-            }
-            // Relies on the token to be valid.
-            OauthRSType::Public => {}
-        };
+        let (o2rs, _) = oauth2_client_authenticate(
+            &self.oauth2rs.inner.rs_set,
+            client_auth_info,
+            ClientCredentials {
+                client_id: revoke_req.client_id.as_deref(),
+                client_assertion_type: revoke_req.client_assertion_type.as_deref(),
+                client_assertion: revoke_req.client_assertion.as_deref(),
+                ..Default::default()
+            },
+            ct,
+        )?;
 
         // We are authenticated! Yay! Now we can actually check things ...
 
@@ -571,64 +760,202 @@ impl<'a> IdmServerProxyWriteTransaction<'a> {
         }
     }
 
-    pub fn check_oauth2_token_exchange(
+    /// Handle an RP-initiated logout. The users session is only ended when the request
+    /// carries an id_token_hint issued to them, otherwise the user is asked to confirm
+    /// the logout themself.
+    pub fn oauth2_end_session(
         &mut self,
-        client_authz: Option<&str>,
-        token_req: &AccessTokenRequest,
-        ct: Duration,
-    ) -> Result<AccessTokenResponse, Oauth2Error> {
-        // Public clients will send the client_id via the ATR, so we need to handle this case.
-        let (client_id, secret) = if let Some(client_authz) = client_authz {
-            let (client_id, secret) = parse_basic_authz(client_authz)?;
-            (client_id, Some(secret))
-        } else {
-            match (&token_req.client_id, &token_req.client_secret) {
-                (Some(a), b) => (a.clone(), b.clone()),
-                _ => {
-                    // We at least need the client_id, else we can't proceed!
-                    security_info!(
-                        "Invalid oauth2 authentication - no basic auth or missing client_id in access token request"
+        ident: Option<&Identity>,
+        end_session_req: &EndSessionRequest,
+    ) -> Result<EndSessionResponse, Oauth2Error> {
+        let hint = end_session_req
+            .id_token_hint
+            .as_deref()
+            .map(|hint| {
+                JwsUnverified::from_str(hint).map_err(|_| {
+                    admin_warn!("Invalid id_token_hint");
+                    Oauth2Error::InvalidRequest
+                })
+            })
+            .transpose()?;
+
+        // The client may be named directly, or be the audience of the hint.
+        let client_id = match (&end_session_req.client_id, &hint) {
+            (Some(client_id), _) => Some(client_id.clone()),
+            (None, Some(_)) => end_session_req
+                .id_token_hint
+                .as_deref()
+                .and_then(id_token_hint_audience),
+            (None, None) => None,
+        };
+
+        let o2rs = client_id
+            .map(|client_id| {
+                self.oauth2rs.inner.rs_set.get(&client_id).ok_or_else(|| {
+                    admin_warn!("Invalid oauth2 client_id");
+                    Oauth2Error::InvalidClientId
+                })
+            })
+            .transpose()?;
+
+        let subject = match (o2rs, &hint) {
+            (Some(o2rs), Some(hint)) => {
+                let validator = o2rs.jws_signer.get_validator().map_err(|e| {
+                    admin_error!(err = ?e, "Unable to access the oauth2 token validator");
+                    Oauth2Error::ServerError(OperationError::CryptographyError)
+                })?;
+                // The hint has usually expired by the time the user logs out, so we only
+                // check that it was issued to this client.
+                let token = hint
+                    .validate::<OidcToken>(&validator)
+                    .map(Jws::into_inner)
+                    .map_err(|_| {
+                        security_info!("Invalid id_token_hint signature");
+                        Oauth2Error::InvalidRequest
+                    })?;
+                if token.aud != o2rs.name || token.iss != o2rs.iss {
+                    security_info!("id_token_hint was not issued to this client");
+                    return Err(Oauth2Error::InvalidRequest);
+                }
+                match token.sub {
+                    OidcSubject::U(uuid) => Some(uuid),
+                    OidcSubject::S(_) => None,
+                }
+            }
+            _ => None,
+        };
+
+        let redirect_uri = match (o2rs, &end_session_req.post_logout_redirect_uri) {
+            (Some(o2rs), Some(uri)) => {
+                // post_logout_redirect_uri must be part of the client_id origin.
+                if uri.origin() != o2rs.origin {
+                    admin_warn!(
+                        origin = ?o2rs.origin,
+                        "Invalid oauth2 post_logout_redirect_uri (must be related to origin {:?}) - got {:?}",
+                        o2rs.origin,
+                        uri.origin()
                     );
-                    return Err(Oauth2Error::AuthenticationRequired);
+                    return Err(Oauth2Error::InvalidOrigin);
+                }
+
+                if o2rs.origin_https && uri.scheme() != "https" {
+                    admin_warn!(
+                        origin = ?o2rs.origin,
+                        "Invalid oauth2 post_logout_redirect_uri (must be https for secure origin) - got {:?}", uri.scheme()
+                    );
+                    return Err(Oauth2Error::InvalidOrigin);
                 }
+
+                let mut uri = uri.clone();
+                if let Some(state) = &end_session_req.state {
+                    uri.query_pairs_mut().append_pair("state", state);
+                }
+                Some(uri)
+            }
+            (None, Some(_)) => {
+                admin_warn!("post_logout_redirect_uri requires the client to be identified");
+                return Err(Oauth2Error::InvalidRequest);
+            }
+            (_, None) => None,
+        };
+
+        let logged_out = match (ident, subject) {
+            (Some(ident), Some(subject))
+                if subject != UUID_ANONYMOUS && ident.get_uuid() == Some(subject) =>
+            {
+                let dte = DestroySessionTokenEvent {
+                    ident: ident.clone(),
+                    target: subject,
+                    token_id: ident.get_session_id(),
+                };
+                self.account_destroy_session_token(&dte)
+                    .map_err(Oauth2Error::ServerError)?;
+                true
             }
+            _ => false,
         };
 
+        Ok(EndSessionResponse {
+            logged_out,
+            redirect_uri,
+        })
+    }
+
+    /// Sign the back-channel logout tokens for oauth2 sessions that have ended. Sessions
+    /// of resource servers without a back-channel logout uri are skipped.
+    pub(crate) fn oauth2_backchannel_logouts(
+        &self,
+        ended: Vec<Oauth2SessionEnded>,
+        ct: Duration,
+    ) -> Vec<Oauth2BackchannelLogout> {
+        let iat = ct.as_secs() as i64;
+        let exp = iat + BACKCHANNEL_LOGOUT_TOKEN_EXPIRY as i64;
+
+        ended
+            .into_iter()
+            .filter_map(|ended| {
+                let o2rs = self
+                    .oauth2rs
+                    .inner
+                    .rs_set
+                    .values()
+                    .find(|o2rs| o2rs.uuid == ended.rs_uuid)?;
+                let url = o2rs.backchannel_logout_uri.clone()?;
+
+                let logout_token = LogoutToken {
+                    iss: o2rs.iss.clone(),
+                    sub: OidcSubject::U(ended.account),
+                    aud: o2rs.name.clone(),
+                    iat,
+                    exp,
+                    jti: Uuid::new_v4(),
+                    sid: ended.session_id,
+                    events: BTreeMap::from([(
+                        BACKCHANNEL_LOGOUT_EVENT.to_string(),
+                        serde_json::Value::Object(serde_json::Map::new()),
+                    )]),
+                };
+
+                Jws::new(logout_token)
+                    .sign(&o2rs.jws_signer)
+                    .map(|signed| Oauth2BackchannelLogout {
+                        rs_name: o2rs.name.clone(),
+                        url,
+                        logout_token: signed.to_string(),
+                    })
+                    .map_err(|e| {
+                        admin_error!(err = ?e, rs = %o2rs.name, "Unable to sign back-channel logout token");
+                    })
+                    .ok()
+            })
+            .collect()
+    }
+
+    pub fn check_oauth2_token_exchange(
+        &mut self,
+        client_auth_info: &ClientAuthInfo,
+        token_req: &AccessTokenRequest,
+        ct: Duration,
+    ) -> Result<AccessTokenResponse, Oauth2Error> {
         // DANGER: Why do we have to do this? During the use of qs for internal search
         // and other operations we need qs to be mut. But when we borrow oauth2rs here we
         // cause multiple borrows to occur on struct members that freaks rust out. This *IS*
         // safe however because no element of the search or write process calls the oauth2rs
         // excepting for this idm layer within a single thread, meaning that stripping the
         // lifetime here is safe since we are the sole accessor.
-        let o2rs: &Oauth2RS = unsafe {
-            let s = self.oauth2rs.inner.rs_set.get(&client_id).ok_or_else(|| {
-                admin_warn!("Invalid oauth2 client_id");
-                Oauth2Error::AuthenticationRequired
-            })?;
-            &*(s as *const _)
-        };
-
-        // check the secret.
-        match &o2rs.type_ {
-            OauthRSType::Basic { authz_secret, .. } => {
-                match secret {
-                    Some(secret) => {
-                        if authz_secret != &secret {
-                            security_info!("Invalid oauth2 client_id secret");
-                            return Err(Oauth2Error::AuthenticationRequired);
-                        }
-                    }
-                    None => {
-                        // We can only get here if we relied on the atr for the client_id and secret
-                        security_info!(
-                            "Invalid oauth2 authentication - no secret in access token request"
-                        );
-                        return Err(Oauth2Error::AuthenticationRequired);
-                    }
-                }
-            }
-            // Relies on the token to be valid - no further action needed.
-            OauthRSType::Public => {}
+        let (o2rs, cnf): (&Oauth2RS, _) = unsafe {
+            let (s, cnf) = oauth2_client_authenticate(
+                &self.oauth2rs.inner.rs_set,
+                client_auth_info,
+                ClientCredentials {
+                    client_id: token_req.client_id.as_deref(),
+                    client_secret: token_req.client_secret.as_deref(),
+                    client_assertion_type: token_req.client_assertion_type.as_deref(),
+                    client_assertion: token_req.client_assertion.as_deref(),
+                },
+                ct,
+            )?;
+            (&*(s as *const _), cnf)
         };
 
         // We are authenticated! Yay! Now we can actually check things ...
@@ -646,12 +973,39 @@ impl<'a> IdmServerProxyWriteTransaction<'a> {
                 code,
                 redirect_uri,
                 code_verifier.as_deref(),
+                cnf,
                 ct,
             ),
             GrantTypeReq::RefreshToken {
                 refresh_token,
                 scope,
-            } => self.check_oauth2_token_refresh(o2rs, refresh_token, scope.as_ref(), ct),
+            } => self.check_oauth2_token_refresh(o2rs, refresh_token, scope.as_ref(), cnf, ct),
+            GrantTypeReq::TokenExchange {
+                subject_token,
+                subject_token_type,
+                requested_token_type,
+                audience,
+                scope,
+            } => {
+                if subject_token_type != OAUTH2_TOKEN_TYPE_ACCESS_TOKEN
+                    || requested_token_type
+                        .as_deref()
+                        .map(|t| t != OAUTH2_TOKEN_TYPE_ACCESS_TOKEN)
+                        .unwrap_or(false)
+                {
+                    admin_warn!(
+                        "Invalid oauth2 token exchange - only access tokens may be exchanged"
+                    );
+                    return Err(Oauth2Error::InvalidRequest);
+                }
+                self.check_oauth2_token_exchange_delegation(
+                    o2rs,
+                    subject_token,
+                    audience,
+                    scope.as_ref(),
+                    ct,
+                )
+            }
         }
     }
 
@@ -752,6 +1106,7 @@ impl<'a> IdmServerProxyWriteTransaction<'a> {
         token_req_code: &str,
         token_req_redirect_uri: &Url,
         token_req_code_verifier: Option<&str>,
+        cnf: Option<String>,
         ct: Duration,
     ) -> Result<AccessTokenResponse, Oauth2Error> {
         // Check the token_req is within the valid time, and correctly signed for
@@ -842,6 +1197,7 @@ impl<'a> IdmServerProxyWriteTransaction<'a> {
             parent_session_id,
             session_id,
             nonce,
+            cnf,
         )
     }
 
@@ -850,6 +1206,7 @@ impl<'a> IdmServerProxyWriteTransaction<'a> {
         o2rs: &Oauth2RS,
         refresh_token: &str,
         req_scopes: Option<&BTreeSet<String>>,
+        cnf: Option<String>,
         ct: Duration,
     ) -> Result<AccessTokenResponse, Oauth2Error> {
         // Validate the refresh token decrypts and it's expiry is within the valid window.
@@ -966,11 +1323,198 @@ impl<'a> IdmServerProxyWriteTransaction<'a> {
                     parent_session_id,
                     session_id,
                     nonce,
+                    cnf,
                 )
             }
         }
     }
 
+    /// Exchange an access token that was issued to `o2rs` for one that can be used with
+    /// the resource server named by `audience`. The new session is a child of the subject
+    /// token's session, so that ending it ends the delegated session too.
+    fn check_oauth2_token_exchange_delegation(
+        &mut self,
+        o2rs: &Oauth2RS,
+        subject_token: &str,
+        audience: &str,
+        req_scopes: Option<&BTreeSet<String>>,
+        ct: Duration,
+    ) -> Result<AccessTokenResponse, Oauth2Error> {
+        let token: Oauth2TokenType = o2rs
+            .token_fernet
+            .decrypt(subject_token)
+            .map_err(|_| {
+                admin_error!("Failed to decrypt token exchange subject token");
+                Oauth2Error::InvalidGrant
+            })
+            .and_then(|data| {
+                serde_json::from_slice(&data).map_err(|e| {
+                    admin_error!("Failed to deserialise token - {:?}", e);
+                    Oauth2Error::InvalidGrant
+                })
+            })?;
+
+        let Oauth2TokenType::Access {
+            scopes: subject_scopes,
+            parent_session_id,
+            session_id: subject_session_id,
+            expiry,
+            uuid,
+            iat,
+            ..
+        } = token
+        else {
+            admin_error!("attempt to exchange a refresh token");
+            return Err(Oauth2Error::InvalidGrant);
+        };
+
+        let odt_ct = OffsetDateTime::UNIX_EPOCH + ct;
+        if expiry <= odt_ct {
+            security_info!(?uuid, "subject token has expired");
+            return Err(Oauth2Error::InvalidGrant);
+        }
+
+        if uuid == UUID_ANONYMOUS {
+            admin_error!("Invalid oauth2 token exchange - refusing to delegate for anonymous");
+            return Err(Oauth2Error::AccessDenied);
+        }
+
+        // Only resource servers that named us in their allow list can be exchanged into. We
+        // don't distinguish unknown targets from disallowed ones.
+        let target = self
+            .oauth2rs
+            .inner
+            .rs_set
+            .get(audience)
+            .filter(|target| target.token_exchange_from.contains(&o2rs.uuid))
+            .cloned()
+            .ok_or_else(|| {
+                security_info!(
+                    client = %o2rs.name,
+                    %audience,
+                    "Resource server is not permitted to exchange tokens for this audience"
+                );
+                Oauth2Error::InvalidTarget
+            })?;
+
+        // Check the subject session (and the session above it) is still valid.
+        let valid = self
+            .check_oauth2_account_uuid_valid(uuid, subject_session_id, parent_session_id, iat, ct)
+            .map_err(|_| admin_error!("Account is not valid"));
+
+        let Ok(Some(entry)) = valid else {
+            security_info!(?uuid, "subject token session is not valid");
+            return Err(Oauth2Error::InvalidGrant);
+        };
+
+        // The scopes the account is entitled to on the target resource server.
+        let memberof = entry.get_ava_refer("memberof");
+        let avail_scopes: BTreeSet<String> = target
+            .scope_maps
+            .iter()
+            .filter_map(|(u, m)| {
+                if memberof.map(|mo| mo.contains(u)).unwrap_or(false) {
+                    Some(m.iter())
+                } else {
+                    None
+                }
+            })
+            .flatten()
+            .cloned()
+            .collect();
+
+        // A delegated token can never hold more than the subject token it was exchanged
+        // from, so only the scopes that the two resource servers share can be granted. No
+        // id_token is issued by an exchange, so openid is never granted.
+        let grantable_scopes: BTreeSet<String> = subject_scopes
+            .intersection(&avail_scopes)
+            .filter(|s| s.as_str() != "openid")
+            .cloned()
+            .collect();
+
+        // Without a request, we grant all of them.
+        let scopes: BTreeSet<String> = match req_scopes {
+            Some(req_scopes) => {
+                if !req_scopes.is_subset(&grantable_scopes) {
+                    admin_warn!(
+                        requested_scopes = ?req_scopes,
+                        subject_scopes = ?subject_scopes,
+                        available_scopes = ?avail_scopes,
+                        "Subject token does not grant access to the requested scopes"
+                    );
+                    return Err(Oauth2Error::InvalidScope);
+                }
+                req_scopes.clone()
+            }
+            None => grantable_scopes,
+        };
+
+        if scopes.is_empty() {
+            admin_warn!("Invalid oauth2 token exchange - no scopes would be granted");
+            return Err(Oauth2Error::InvalidScope);
+        }
+
+        // ----------
+        // good to go
+
+        let session_id = Uuid::new_v4();
+        let iat = ct.as_secs() as i64;
+        let expiry = odt_ct + Duration::from_secs(OAUTH2_ACCESS_TOKEN_EXPIRY as u64);
+
+        let access_token_raw = Oauth2TokenType::Access {
+            scopes: scopes.clone(),
+            parent_session_id: subject_session_id,
+            session_id,
+            expiry,
+            uuid,
+            iat,
+            nbf: iat,
+            auth_time: None,
+            nonce: None,
+            cnf: None,
+        };
+
+        let access_token_data = serde_json::to_vec(&access_token_raw).map_err(|e| {
+            admin_error!(err = ?e, "Unable to encode token data");
+            Oauth2Error::ServerError(OperationError::SerdeJsonError)
+        })?;
+
+        let access_token = target
+            .token_fernet
+            .encrypt_at_time(&access_token_data, ct.as_secs());
+
+        // There is no refresh token, so the session ends with the access token.
+        let session = Value::Oauth2Session(
+            session_id,
+            Oauth2Session {
+                parent: subject_session_id,
+                expiry: Some(expiry),
+                issued_at: odt_ct,
+                rs_uuid: target.uuid,
+            },
+        );
+
+        self.qs_write
+            .internal_modify(
+                &filter!(f_eq("uuid", PartialValue::Uuid(uuid))),
+                &ModifyList::new_append("oauth2_session", session),
+            )
+            .map_err(|e| {
+                admin_error!("Failed to persist oauth2 session record {:?}", e);
+                Oauth2Error::ServerError(e)
+            })?;
+
+        Ok(AccessTokenResponse {
+            access_token,
+            token_type: "bearer".to_string(),
+            expires_in: OAUTH2_ACCESS_TOKEN_EXPIRY,
+            refresh_token: None,
+            scope: Some(str_join(&scopes)),
+            id_token: None,
+            issued_token_type: Some(OAUTH2_TOKEN_TYPE_ACCESS_TOKEN.to_string()),
+        })
+    }
+
     fn generate_access_token_response(
         &mut self,
         o2rs: &Oauth2RS,
@@ -981,6 +1525,7 @@ impl<'a> IdmServerProxyWriteTransaction<'a> {
         parent_session_id: Uuid,
         session_id: Uuid,
         nonce: Option<String>,
+        cnf: Option<String>,
     ) -> Result<AccessTokenResponse, Oauth2Error> {
         let odt_ct = OffsetDateTime::UNIX_EPOCH + ct;
         let iat = ct.as_secs() as i64;
@@ -1040,7 +1585,9 @@ impl<'a> IdmServerProxyWriteTransaction<'a> {
             };
 
             let s_claims = s_claims_for_account(o2rs, &account, &scopes);
-            let extra_claims = extra_claims_for_account(&account, &scopes);
+            let mut extra_claims = extra_claims_for_account(&account, &scopes);
+            // The session id lets the client match back-channel logout tokens to this session.
+            extra_claims.insert("sid".to_string(), session_id.to_string().into());
 
             let oidc = OidcToken {
                 iss,
@@ -1087,6 +1634,7 @@ impl<'a> IdmServerProxyWriteTransaction<'a> {
             nbf: iat,
             auth_time: None,
             nonce: nonce.clone(),
+            cnf,
         };
 
         let access_token_data = serde_json::to_vec(&access_token_raw).map_err(|e| {
@@ -1153,6 +1701,7 @@ impl<'a> IdmServerProxyWriteTransaction<'a> {
             refresh_token: Some(refresh_token),
             scope,
             id_token,
+            issued_token_type: None,
         })
     }
 
@@ -1198,10 +1747,350 @@ impl<'a> IdmServerProxyWriteTransaction<'a> {
                 })
             })
     }
-}
 
-impl<'a> IdmServerProxyReadTransaction<'a> {
-    pub fn check_oauth2_authorisation(
+    /// Register a new resource server on behalf of an oauth2 registrar. The resource server
+    /// is constrained to the domains and scope maps the registrar was granted.
+    /// <https://datatracker.ietf.org/doc/html/rfc7591#section-3>
+    pub fn oauth2_client_register(
+        &mut self,
+        ident: &Identity,
+        reg_req: &ClientRegistrationRequest,
+        ct: Duration,
+    ) -> Result<ClientRegistrationResponse, Oauth2Error> {
+        let registrar = oauth2_registrar_write(ident)?;
+        let metadata = oauth2_client_metadata_validate(&registrar, reg_req)?;
+
+        let rs_uuid = Uuid::new_v4();
+        let client_id = format!("dyn_{}", rs_uuid.simple());
+        let displayname = metadata
+            .client_name
+            .as_deref()
+            .unwrap_or(client_id.as_str());
+
+        let mut e = entry_init!(
+            ("class", CLASS_OBJECT.clone()),
+            ("class", Value::new_class("oauth2_resource_server")),
+            ("uuid", Value::Uuid(rs_uuid)),
+            ("oauth2_rs_name", Value::new_iname(&client_id)),
+            ("displayname", Value::new_utf8s(displayname)),
+            ("oauth2_rs_origin", Value::Url(metadata.origin.clone())),
+            ("oauth2_rs_registered_by", Value::Refer(registrar.uuid))
+        );
+        if metadata.public {
+            e.add_ava("class", Value::new_class("oauth2_resource_server_public"));
+        } else {
+            e.add_ava("class", Value::new_class("oauth2_resource_server_basic"));
+        }
+        if let Some(jwks) = metadata.jwks {
+            e.add_ava("oauth2_rs_jwks", Value::new_utf8(jwks));
+        }
+        for redirect_uri in metadata.redirect_uris {
+            e.add_ava("oauth2_rs_redirect_uri", Value::Url(redirect_uri));
+        }
+        for (group, scopes) in metadata.scope_maps {
+            let scope_map = Value::new_oauthscopemap(group, scopes)
+                .ok_or(Oauth2Error::InvalidClientMetadata)?;
+            e.add_ava("oauth2_rs_scope_map", scope_map);
+        }
+
+        self.qs_write.internal_create(vec![e]).map_err(|e| {
+            admin_error!(?e, "Failed to create dynamically registered oauth2 client");
+            Oauth2Error::ServerError(e)
+        })?;
+
+        security_info!(%client_id, registrar = %registrar.uuid, "Registered oauth2 client");
+
+        // The secret and keys were generated as the entry was created.
+        let entry = self
+            .qs_write
+            .internal_search_uuid(rs_uuid)
+            .map_err(Oauth2Error::ServerError)?;
+        oauth2_client_registration_response(
+            &self.oauth2rs.inner.origin,
+            &entry,
+            Some(ct.as_secs() as i64),
+        )
+    }
+
+    /// Replace the metadata of a resource server that this registrar registered.
+    /// <https://datatracker.ietf.org/doc/html/rfc7592#section-2.2>
+    pub fn oauth2_client_registration_update(
+        &mut self,
+        ident: &Identity,
+        client_id: &str,
+        reg_req: &ClientRegistrationRequest,
+    ) -> Result<ClientRegistrationResponse, Oauth2Error> {
+        let registrar = oauth2_registrar_write(ident)?;
+
+        if reg_req
+            .client_id
+            .as_deref()
+            .map(|req_client_id| req_client_id != client_id)
+            .unwrap_or(false)
+        {
+            security_info!(%client_id, "Client registration update has a mismatched client_id");
+            return Err(Oauth2Error::InvalidRequest);
+        }
+
+        let entry = oauth2_registered_client(&mut self.qs_write, &registrar, client_id)?;
+        let metadata = oauth2_client_metadata_validate(&registrar, reg_req)?;
+
+        // Changing the client type would need the credentials to be regenerated, so a new
+        // registration is needed instead.
+        if metadata.public != entry.attribute_equality("class", &PVCLASS_OAUTH2_PUBLIC) {
+            security_info!(%client_id, "Client registration update may not change the client type");
+            return Err(Oauth2Error::InvalidClientMetadata);
+        }
+
+        let displayname = metadata.client_name.as_deref().unwrap_or(client_id);
+        let mut modlist = ModifyList::new_list(vec![
+            Modify::Purged("displayname".into()),
+            Modify::Present("displayname".into(), Value::new_utf8s(displayname)),
+            Modify::Purged("oauth2_rs_origin".into()),
+            Modify::Present("oauth2_rs_origin".into(), Value::Url(metadata.origin)),
+            Modify::Purged("oauth2_rs_jwks".into()),
+            Modify::Purged("oauth2_rs_redirect_uri".into()),
+            Modify::Purged("oauth2_rs_scope_map".into()),
+        ]);
+        if let Some(jwks) = metadata.jwks {
+            modlist.push_mod(Modify::Present(
+                "oauth2_rs_jwks".into(),
+                Value::new_utf8(jwks),
+            ));
+        }
+        for redirect_uri in metadata.redirect_uris {
+            modlist.push_mod(Modify::Present(
+                "oauth2_rs_redirect_uri".into(),
+                Value::Url(redirect_uri),
+            ));
+        }
+        for (group, scopes) in metadata.scope_maps {
+            let scope_map = Value::new_oauthscopemap(group, scopes)
+                .ok_or(Oauth2Error::InvalidClientMetadata)?;
+            modlist.push_mod(Modify::Present("oauth2_rs_scope_map".into(), scope_map));
+        }
+
+        self.qs_write
+            .internal_modify_uuid(entry.get_uuid(), &modlist)
+            .map_err(|e| {
+                admin_error!(?e, "Failed to update dynamically registered oauth2 client");
+                Oauth2Error::ServerError(e)
+            })?;
+
+        security_info!(%client_id, registrar = %registrar.uuid, "Updated oauth2 client registration");
+
+        let entry = self
+            .qs_write
+            .internal_search_uuid(entry.get_uuid())
+            .map_err(Oauth2Error::ServerError)?;
+        oauth2_client_registration_response(&self.oauth2rs.inner.origin, &entry, None)
+    }
+
+    /// Delete a resource server that this registrar registered.
+    /// <https://datatracker.ietf.org/doc/html/rfc7592#section-2.3>
+    pub fn oauth2_client_registration_delete(
+        &mut self,
+        ident: &Identity,
+        client_id: &str,
+    ) -> Result<(), Oauth2Error> {
+        let registrar = oauth2_registrar_write(ident)?;
+        let entry = oauth2_registered_client(&mut self.qs_write, &registrar, client_id)?;
+
+        self.qs_write
+            .internal_delete_uuid(entry.get_uuid())
+            .map_err(|e| {
+                admin_error!(?e, "Failed to delete dynamically registered oauth2 client");
+                Oauth2Error::ServerError(e)
+            })?;
+
+        security_info!(%client_id, registrar = %registrar.uuid, "Deleted oauth2 client registration");
+        Ok(())
+    }
+}
+
+impl<'a> IdmServerProxyReadTransaction<'a> {
+    /// Validate a pushed authorisation request from a client. The request is held by this
+    /// server under a random handle that is returned as the request_uri, and can be used
+    /// once when the user agent is redirected to the authorisation endpoint. Like the consent
+    /// that follows it, the request must be resumed on the server it was pushed to.
+    /// <https://datatracker.ietf.org/doc/html/rfc9126#section-2>
+    pub fn check_oauth2_pushed_authorisation(
+        &self,
+        client_auth_info: &ClientAuthInfo,
+        par_req: &PushedAuthorisationRequest,
+        ct: Duration,
+    ) -> Result<PushedAuthorisationResponse, Oauth2Error> {
+        let (o2rs, _) = oauth2_client_authenticate(
+            &self.oauth2rs.inner.rs_set,
+            client_auth_info,
+            ClientCredentials {
+                client_id: Some(par_req.client_id.as_str()),
+                client_secret: par_req.client_secret.as_deref(),
+                client_assertion_type: par_req.client_assertion_type.as_deref(),
+                client_assertion: par_req.client_assertion.as_deref(),
+            },
+            ct,
+        )?;
+
+        if o2rs.name != par_req.client_id {
+            security_info!(
+                "Invalid oauth2 pushed authorisation request - client_id does not match the authenticated client"
+            );
+            return Err(Oauth2Error::InvalidRequest);
+        }
+
+        if par_req.params.contains_key("request_uri") {
+            admin_warn!("Invalid oauth2 pushed authorisation request - request_uri is not allowed");
+            return Err(Oauth2Error::InvalidRequest);
+        }
+
+        // When a request object is sent, only its parameters are used.
+        let auth_req = match &par_req.request {
+            Some(request) => oauth2_request_object_validate(o2rs, request, ct)?,
+            None => {
+                let mut params: serde_json::Map<String, serde_json::Value> = par_req
+                    .params
+                    .iter()
+                    .map(|(k, v)| (k.clone(), serde_json::Value::String(v.clone())))
+                    .collect();
+                params.insert(
+                    "client_id".to_string(),
+                    serde_json::Value::String(par_req.client_id.clone()),
+                );
+                serde_json::from_value(serde_json::Value::Object(params)).map_err(|e| {
+                    admin_warn!(err = ?e, "Invalid oauth2 pushed authorisation request");
+                    Oauth2Error::InvalidRequest
+                })?
+            }
+        };
+
+        // Check what we can now, so the client learns of errors before the user agent is
+        // involved. The rest is checked by check_oauth2_authorisation once the user is known.
+        if auth_req.response_type != "code" {
+            admin_warn!("Invalid oauth2 response_type (should be 'code')");
+            return Err(Oauth2Error::UnsupportedResponseType);
+        }
+        oauth2_redirect_uri_validate(o2rs, &auth_req.redirect_uri)?;
+
+        let handle = password_from_random();
+
+        let mut pushed_write = self.oauth2_pushed_requests.write();
+        // Forget the requests that were never used.
+        let expired: Vec<String> = pushed_write
+            .iter()
+            .filter(|(_, pushed)| pushed.expiry <= ct)
+            .map(|(handle, _)| handle.clone())
+            .collect();
+        for handle in expired {
+            pushed_write.remove(&handle);
+        }
+
+        let client_pending = pushed_write
+            .values()
+            .filter(|pushed| pushed.rs_uuid == o2rs.uuid)
+            .count();
+        if client_pending >= PUSHED_AUTHORISATION_REQUEST_MAX_PER_CLIENT
+            || pushed_write.len() >= PUSHED_AUTHORISATION_REQUEST_MAX
+        {
+            security_info!(
+                ?o2rs.name,
+                client_pending,
+                "Too many pushed authorisation requests are waiting to be used"
+            );
+            pushed_write.commit();
+            return Err(Oauth2Error::TemporarilyUnavailable);
+        }
+
+        pushed_write.insert(
+            handle.clone(),
+            Oauth2PushedAuthorisation {
+                rs_uuid: o2rs.uuid,
+                auth_req,
+                expiry: ct + Duration::from_secs(PUSHED_AUTHORISATION_REQUEST_EXPIRY),
+            },
+        );
+        pushed_write.commit();
+
+        let request_uri = format!("{}{}", OAUTH2_PAR_REQUEST_URI_PREFIX, handle);
+
+        Ok(PushedAuthorisationResponse {
+            request_uri,
+            expires_in: PUSHED_AUTHORISATION_REQUEST_EXPIRY,
+        })
+    }
+
+    /// Resolve an authorisation request that refers to a pushed authorisation request or a
+    /// signed request object into its parameters, which are then checked with
+    /// [Self::check_oauth2_authorisation]. This enforces that resource servers requiring
+    /// pushed authorisation requests can only be authorised with one.
+    pub fn resolve_oauth2_authorisation_request(
+        &self,
+        auth_req: AuthorisationRequestRef,
+        ct: Duration,
+    ) -> Result<AuthorisationRequest, Oauth2Error> {
+        let client_id = match &auth_req {
+            AuthorisationRequestRef::Pushed { client_id, .. }
+            | AuthorisationRequestRef::Signed { client_id, .. } => client_id,
+            AuthorisationRequestRef::Direct(auth_req) => &auth_req.client_id,
+        };
+
+        let o2rs = self.oauth2rs.inner.rs_set.get(client_id).ok_or_else(|| {
+            admin_warn!(
+                "Invalid oauth2 client_id ({}) Have you configured the oauth2 resource server?",
+                client_id
+            );
+            Oauth2Error::InvalidClientId
+        })?;
+
+        match auth_req {
+            AuthorisationRequestRef::Pushed { request_uri, .. } => {
+                let handle = request_uri
+                    .strip_prefix(OAUTH2_PAR_REQUEST_URI_PREFIX)
+                    .map(str::to_string)
+                    .ok_or_else(|| {
+                        admin_warn!("Invalid oauth2 request_uri - only pushed authorisation requests are supported");
+                        Oauth2Error::InvalidRequestUri
+                    })?;
+
+                // Each pushed request can only be used once, and only by the client that
+                // pushed it.
+                let mut pushed_write = self.oauth2_pushed_requests.write();
+                let owned = pushed_write
+                    .get(&handle)
+                    .map(|pushed| pushed.rs_uuid == o2rs.uuid)
+                    .unwrap_or(false);
+                let pushed = if owned {
+                    pushed_write.remove(&handle)
+                } else {
+                    None
+                };
+                pushed_write.commit();
+
+                pushed
+                    .filter(|pushed| pushed.expiry > ct)
+                    .map(|pushed| pushed.auth_req)
+                    .ok_or_else(|| {
+                        admin_warn!(
+                            "Invalid oauth2 request_uri - unknown, already used, or expired"
+                        );
+                        Oauth2Error::InvalidRequestUri
+                    })
+            }
+            _ if o2rs.require_pushed_authorisation => {
+                security_info!(
+                    ?o2rs.name,
+                    "Invalid oauth2 request - this resource server requires pushed authorisation requests"
+                );
+                Err(Oauth2Error::InvalidRequest)
+            }
+            AuthorisationRequestRef::Signed { request, .. } => {
+                oauth2_request_object_validate(o2rs, &request, ct)
+            }
+            AuthorisationRequestRef::Direct(auth_req) => Ok(*auth_req),
+        }
+    }
+
+    pub fn check_oauth2_authorisation(
         &self,
         ident: &Identity,
         uat: &UserAuthToken,
@@ -1242,24 +2131,7 @@ impl<'a> IdmServerProxyReadTransaction<'a> {
                 Oauth2Error::InvalidClientId
             })?;
 
-        // redirect_uri must be part of the client_id origin.
-        if auth_req.redirect_uri.origin() != o2rs.origin {
-            admin_warn!(
-                origin = ?o2rs.origin,
-                "Invalid oauth2 redirect_uri (must be related to origin {:?}) - got {:?}",
-                o2rs.origin,
-                auth_req.redirect_uri.origin()
-            );
-            return Err(Oauth2Error::InvalidOrigin);
-        }
-
-        if o2rs.origin_https && auth_req.redirect_uri.scheme() != "https" {
-            admin_warn!(
-                origin = ?o2rs.origin,
-                "Invalid oauth2 redirect_uri (must be https for secure origin) - got {:?}", auth_req.redirect_uri.scheme()
-            );
-            return Err(Oauth2Error::InvalidOrigin);
-        }
+        oauth2_redirect_uri_validate(o2rs, &auth_req.redirect_uri)?;
 
         let require_pkce = match &o2rs.type_ {
             OauthRSType::Basic { enable_pkce, .. } => *enable_pkce,
@@ -1404,12 +2276,19 @@ impl<'a> IdmServerProxyReadTransaction<'a> {
                 false
             };
 
-        if consent_previously_granted {
+        if consent_previously_granted || o2rs.consent_prompt_disable {
             let pretty_scopes: Vec<String> = granted_scopes.iter().map(|s| s.to_owned()).collect();
-            admin_info!(
-                "User has previously consented, permitting with scopes: {}",
-                pretty_scopes.join(",")
-            );
+            if consent_previously_granted {
+                admin_info!(
+                    "User has previously consented, permitting with scopes: {}",
+                    pretty_scopes.join(",")
+                );
+            } else {
+                admin_info!(
+                    "Consent prompt is disabled for this resource server, permitting with scopes: {}",
+                    pretty_scopes.join(",")
+                );
+            }
 
             // Setup for the permit success
             let xchg_code = TokenExchangeCode {
@@ -1545,29 +2424,22 @@ impl<'a> IdmServerProxyReadTransaction<'a> {
 
     pub fn check_oauth2_token_introspect(
         &mut self,
-        client_authz: &str,
+        client_auth_info: &ClientAuthInfo,
         intr_req: &AccessTokenIntrospectRequest,
         ct: Duration,
     ) -> Result<AccessTokenIntrospectResponse, Oauth2Error> {
-        let (client_id, secret) = parse_basic_authz(client_authz)?;
-
-        // Get the o2rs for the handle.
-        let o2rs = self.oauth2rs.inner.rs_set.get(&client_id).ok_or_else(|| {
-            admin_warn!("Invalid oauth2 client_id");
-            Oauth2Error::AuthenticationRequired
-        })?;
-
-        // check the secret.
-        match &o2rs.type_ {
-            OauthRSType::Basic { authz_secret, .. } => {
-                if authz_secret != &secret {
-                    security_info!("Invalid oauth2 client_id secret");
-                    return Err(Oauth2Error::AuthenticationRequired);
-                }
-            }
-            // Relies on the token to be valid.
-            OauthRSType::Public => {}
-        };
+        let (o2rs, _) = oauth2_client_authenticate(
+            &self.oauth2rs.inner.rs_set,
+            client_auth_info,
+            ClientCredentials {
+                client_id: intr_req.client_id.as_deref(),
+                client_assertion_type: intr_req.client_assertion_type.as_deref(),
+                client_assertion: intr_req.client_assertion.as_deref(),
+                ..Default::default()
+            },
+            ct,
+        )?;
+        let client_id = o2rs.name.clone();
 
         // We are authenticated! Yay! Now we can actually check things ...
 
@@ -1596,6 +2468,7 @@ impl<'a> IdmServerProxyReadTransaction<'a> {
                 nbf,
                 auth_time: _,
                 nonce: _,
+                cnf,
             } => {
                 // Has this token expired?
                 let odt_ct = OffsetDateTime::UNIX_EPOCH + ct;
@@ -1646,6 +2519,7 @@ impl<'a> IdmServerProxyReadTransaction<'a> {
                     aud: Some(client_id),
                     iss: None,
                     jti: None,
+                    cnf: cnf.map(|x5t_s256| Oauth2Confirmation { x5t_s256 }),
                 })
             }
             Oauth2TokenType::Refresh { .. } => Ok(AccessTokenIntrospectResponse::inactive()),
@@ -1656,6 +2530,7 @@ impl<'a> IdmServerProxyReadTransaction<'a> {
         &mut self,
         client_id: &str,
         token_str: &str,
+        client_auth_info: &ClientAuthInfo,
         ct: Duration,
     ) -> Result<OidcToken, Oauth2Error> {
         // DANGER: Why do we have to do this? During the use of qs for internal search
@@ -1699,6 +2574,7 @@ impl<'a> IdmServerProxyReadTransaction<'a> {
                 nbf,
                 auth_time: _,
                 nonce,
+                cnf,
             } => {
                 // Has this token expired?
                 let odt_ct = OffsetDateTime::UNIX_EPOCH + ct;
@@ -1707,6 +2583,19 @@ impl<'a> IdmServerProxyReadTransaction<'a> {
                     return Err(Oauth2Error::InvalidToken);
                 }
 
+                // A token bound to a client certificate may only be used over a connection
+                // that presents the same certificate.
+                // <https://datatracker.ietf.org/doc/html/rfc8705#section-3>
+                if let Some(x5t_s256) = cnf {
+                    if client_auth_info.client_cert.as_deref() != Some(x5t_s256.as_str()) {
+                        security_info!(
+                            ?uuid,
+                            "access token is bound to a client certificate that was not presented"
+                        );
+                        return Err(Oauth2Error::InvalidToken);
+                    }
+                }
+
                 // Is the user expired, or the oauth2 session invalid?
                 let valid = self
                     .check_oauth2_account_uuid_valid(uuid, session_id, parent_session_id, iat, ct)
@@ -1775,10 +2664,26 @@ impl<'a> IdmServerProxyReadTransaction<'a> {
         let token_endpoint = o2rs.token_endpoint.clone();
         let userinfo_endpoint = Some(o2rs.userinfo_endpoint.clone());
         let jwks_uri = o2rs.jwks_uri.clone();
+        let end_session_endpoint = Some(o2rs.end_session_endpoint.clone());
+        let pushed_authorization_request_endpoint =
+            Some(o2rs.pushed_authorization_request_endpoint.clone());
+        let require_pushed_authorization_requests = o2rs.require_pushed_authorisation;
+        let registration_endpoint = Some(o2rs.registration_endpoint.clone());
+        let backchannel_logout_supported = o2rs.backchannel_logout_uri.is_some();
         let scopes_supported = Some(o2rs.scopes_supported.iter().cloned().collect());
         let response_types_supported = vec![ResponseType::Code];
         let response_modes_supported = vec![ResponseMode::Query];
-        let grant_types_supported = vec![GrantType::AuthorisationCode];
+        let mut grant_types_supported = vec![GrantType::AuthorisationCode];
+        // Only advertise token exchange if another resource server accepts our tokens.
+        if self
+            .oauth2rs
+            .inner
+            .rs_set
+            .values()
+            .any(|rs| rs.token_exchange_from.contains(&o2rs.uuid))
+        {
+            grant_types_supported.push(GrantType::TokenExchange);
+        }
         let subject_types_supported = vec![SubjectType::Public];
 
         let id_token_signing_alg_values_supported = match &o2rs.jws_signer {
@@ -1791,10 +2696,19 @@ impl<'a> IdmServerProxyReadTransaction<'a> {
         };
 
         let userinfo_signing_alg_values_supported = None;
-        let token_endpoint_auth_methods_supported = vec![
+        let mut token_endpoint_auth_methods_supported = vec![
             TokenEndpointAuthMethod::ClientSecretBasic,
             TokenEndpointAuthMethod::ClientSecretPost,
+            TokenEndpointAuthMethod::PrivateKeyJwt,
         ];
+        // Certificates are only requested from clients when tls client auth is enabled, so
+        // we only advertise it to those that have registered one.
+        let tls_client_certificate_bound_access_tokens = !o2rs.tls_client_certs.is_empty();
+        if tls_client_certificate_bound_access_tokens {
+            token_endpoint_auth_methods_supported.push(TokenEndpointAuthMethod::TlsClientAuth);
+        }
+        let token_endpoint_auth_signing_alg_values_supported =
+            Some(vec!["ES256".to_string(), "RS256".to_string()]);
         let display_values_supported = Some(vec![DisplayValue::Page]);
         let claim_types_supported = vec![ClaimType::Normal];
         // What claims can we offer?
@@ -1807,7 +2721,12 @@ impl<'a> IdmServerProxyReadTransaction<'a> {
             token_endpoint,
             userinfo_endpoint,
             jwks_uri,
-            registration_endpoint: None,
+            registration_endpoint,
+            end_session_endpoint,
+            pushed_authorization_request_endpoint,
+            require_pushed_authorization_requests,
+            backchannel_logout_supported,
+            backchannel_logout_session_supported: backchannel_logout_supported,
             scopes_supported,
             response_types_supported,
             response_modes_supported,
@@ -1820,11 +2739,15 @@ impl<'a> IdmServerProxyReadTransaction<'a> {
             userinfo_signing_alg_values_supported,
             userinfo_encryption_alg_values_supported: None,
             userinfo_encryption_enc_values_supported: None,
-            request_object_signing_alg_values_supported: None,
+            request_object_signing_alg_values_supported: Some(vec![
+                "ES256".to_string(),
+                "RS256".to_string(),
+            ]),
             request_object_encryption_alg_values_supported: None,
             request_object_encryption_enc_values_supported: None,
             token_endpoint_auth_methods_supported,
-            token_endpoint_auth_signing_alg_values_supported: None,
+            token_endpoint_auth_signing_alg_values_supported,
+            tls_client_certificate_bound_access_tokens,
             display_values_supported,
             claim_types_supported,
             claims_supported,
@@ -1832,9 +2755,9 @@ impl<'a> IdmServerProxyReadTransaction<'a> {
             claims_locales_supported: None,
             ui_locales_supported: None,
             claims_parameter_supported: false,
-            // I think?
             request_parameter_supported: true,
-            request_uri_parameter_supported: false,
+            // Only the request_uri of our pushed authorisation requests.
+            request_uri_parameter_supported: true,
             require_request_uri_registration: false,
             op_policy_uri: None,
             op_tos_uri: None,
@@ -1857,6 +2780,33 @@ impl<'a> IdmServerProxyReadTransaction<'a> {
             })
             .map(|jwk| JwkKeySet { keys: vec![jwk] })
     }
+
+    /// Read the metadata of a resource server that this registrar registered.
+    /// <https://datatracker.ietf.org/doc/html/rfc7592#section-2.1>
+    pub fn oauth2_client_registration_get(
+        &mut self,
+        ident: &Identity,
+        client_id: &str,
+    ) -> Result<ClientRegistrationResponse, Oauth2Error> {
+        let registrar = oauth2_registrar(ident)?;
+        let entry = oauth2_registered_client(&mut self.qs_read, &registrar, client_id)?;
+        oauth2_client_registration_response(&self.oauth2rs.inner.origin, &entry, None)
+    }
+}
+
+/// Read the audience of an id_token_hint before it's verified, so that we know which
+/// client's key to verify it with.
+fn id_token_hint_audience(hint: &str) -> Option<String> {
+    #[derive(Deserialize)]
+    struct Audience {
+        aud: String,
+    }
+
+    let payload = hint.split('.').nth(1)?;
+    let payload = general_purpose::URL_SAFE_NO_PAD.decode(payload).ok()?;
+    serde_json::from_slice::<Audience>(&payload)
+        .map(|a| a.aud)
+        .ok()
 }
 
 // TODO: this can be handled by the auth header parsers in axum
@@ -1891,61 +2841,616 @@ fn parse_basic_authz(client_authz: &str) -> Result<(String, String), Oauth2Error
     Ok((client_id.to_string(), secret.to_string()))
 }
 
-fn s_claims_for_account(
-    o2rs: &Oauth2RS,
-    account: &Account,
-    scopes: &BTreeSet<String>,
-) -> OidcClaims {
-    let preferred_username = if o2rs.prefer_short_username {
-        Some(account.name.clone())
+/// Authenticate the client of a request to the token, introspection or revocation endpoints.
+/// This returns the resource server the client is, and the thumbprint of the tls client
+/// certificate that any tokens issued to the client must be bound to.
+fn oauth2_client_authenticate<'b>(
+    rs_set: &'b HashMap<String, Oauth2RS>,
+    client_auth_info: &ClientAuthInfo,
+    creds: ClientCredentials<'_>,
+    ct: Duration,
+) -> Result<(&'b Oauth2RS, Option<String>), Oauth2Error> {
+    if let Some(client_assertion_type) = creds.client_assertion_type {
+        if client_assertion_type != OAUTH2_CLIENT_ASSERTION_TYPE_JWT_BEARER {
+            security_info!(
+                ?client_assertion_type,
+                "Unsupported oauth2 client assertion type"
+            );
+            return Err(Oauth2Error::AuthenticationRequired);
+        }
+        let Some(client_assertion) = creds.client_assertion else {
+            security_info!("Invalid oauth2 authentication - missing client assertion");
+            return Err(Oauth2Error::AuthenticationRequired);
+        };
+        let o2rs = oauth2_client_assertion_validate(rs_set, creds.client_id, client_assertion, ct)?;
+        return Ok((o2rs, oauth2_client_cert_bound(o2rs, client_auth_info)));
+    }
+
+    // Public clients will send the client_id in the body, so we need to handle this case.
+    let (client_id, secret) = if let Some(basic_authz) = &client_auth_info.basic_authz {
+        let (client_id, secret) = parse_basic_authz(basic_authz)?;
+        (client_id, Some(secret))
     } else {
-        Some(account.spn.clone())
+        match creds.client_id {
+            Some(client_id) => (
+                client_id.to_string(),
+                creds.client_secret.map(str::to_string),
+            ),
+            None => {
+                // We at least need the client_id, else we can't proceed!
+                security_info!(
+                    "Invalid oauth2 authentication - no basic auth or missing client_id in request"
+                );
+                return Err(Oauth2Error::AuthenticationRequired);
+            }
+        }
     };
 
-    let (email, email_verified) = if scopes.contains(&"email".to_string()) {
-        if let Some(mp) = &account.mail_primary {
-            (Some(mp.clone()), Some(true))
-        } else {
-            (None, None)
+    let o2rs = rs_set.get(&client_id).ok_or_else(|| {
+        admin_warn!("Invalid oauth2 client_id");
+        Oauth2Error::AuthenticationRequired
+    })?;
+
+    let cnf = oauth2_client_cert_bound(o2rs, client_auth_info);
+
+    match &o2rs.type_ {
+        // A registered tls client certificate is sufficient to authenticate the client.
+        OauthRSType::Basic { .. } if cnf.is_some() => {}
+        // Once a client has keys, the shared secret is no longer accepted.
+        OauthRSType::Basic { .. }
+            if o2rs.client_jwks.is_some() || !o2rs.tls_client_certs.is_empty() =>
+        {
+            security_info!(
+                "Invalid oauth2 authentication - secret authentication is disabled for this client"
+            );
+            return Err(Oauth2Error::AuthenticationRequired);
         }
-    } else {
-        (None, None)
+        OauthRSType::Basic { authz_secret, .. } => match secret {
+            Some(secret) => {
+                if authz_secret != &secret {
+                    security_info!("Invalid oauth2 client_id secret");
+                    return Err(Oauth2Error::AuthenticationRequired);
+                }
+            }
+            None => {
+                // We can only get here if we relied on the body for the client_id and secret
+                security_info!("Invalid oauth2 authentication - no secret in request");
+                return Err(Oauth2Error::AuthenticationRequired);
+            }
+        },
+        // Relies on the token to be valid - no further action needed.
+        OauthRSType::Public => {}
     };
 
-    OidcClaims {
-        // Map from displayname
-        name: Some(account.displayname.clone()),
-        scopes: scopes.iter().cloned().collect(),
-        preferred_username,
-        email,
-        email_verified,
-        ..Default::default()
-    }
+    Ok((o2rs, cnf))
 }
-fn extra_claims_for_account(
-    account: &Account,
-    scopes: &BTreeSet<String>,
-) -> BTreeMap<String, serde_json::Value> {
-    let mut extra_claims = BTreeMap::new();
-    if scopes.contains(&"groups".to_string()) {
-        extra_claims.insert(
-            "groups".to_string(),
-            account.groups.iter().map(|x| x.to_proto().uuid).collect(),
-        );
-    }
-    extra_claims
+
+/// If the connection presented a tls client certificate registered to this resource server
+/// return its thumbprint.
+fn oauth2_client_cert_bound(o2rs: &Oauth2RS, client_auth_info: &ClientAuthInfo) -> Option<String> {
+    client_auth_info
+        .client_cert
+        .as_ref()
+        .filter(|thumbprint| o2rs.tls_client_certs.contains(thumbprint.as_str()))
+        .cloned()
 }
 
-fn str_join(set: &BTreeSet<String>) -> String {
-    let alloc_len = set.iter().fold(0, |acc, s| acc + s.len() + 1);
-    let mut buf = String::with_capacity(alloc_len);
-    set.iter().for_each(|s| {
-        buf.push_str(s);
-        buf.push(' ');
-    });
+/// Validate a private_key_jwt client assertion, returning the resource server that signed it.
+/// <https://datatracker.ietf.org/doc/html/rfc7523#section-3>
+fn oauth2_client_assertion_validate<'b>(
+    rs_set: &'b HashMap<String, Oauth2RS>,
+    client_id: Option<&str>,
+    client_assertion: &str,
+    ct: Duration,
+) -> Result<&'b Oauth2RS, Oauth2Error> {
+    #[derive(Serialize, Deserialize, Clone)]
+    #[serde(untagged)]
+    enum Audience {
+        One(String),
+        Many(Vec<String>),
+    }
 
-    // Remove the excess trailing space.
-    let _ = buf.pop();
+    #[derive(Serialize, Deserialize, Clone)]
+    struct ClientAssertionClaims {
+        iss: String,
+        sub: String,
+        aud: Audience,
+        exp: i64,
+        nbf: Option<i64>,
+    }
+
+    let jwsu = JwsUnverified::from_str(client_assertion).map_err(|e| {
+        security_info!(err = ?e, "Invalid oauth2 client assertion");
+        Oauth2Error::AuthenticationRequired
+    })?;
+    // The client_id is optional, in which case the kid tells us who signed this.
+    let candidates: Vec<&Oauth2RS> = match client_id {
+        Some(client_id) => rs_set.get(client_id).into_iter().collect(),
+        None => rs_set.values().collect(),
+    };
+
+    let (o2rs, claims) = candidates
+        .into_iter()
+        .find_map(|o2rs| {
+            oauth2_client_jws_verify::<ClientAssertionClaims>(o2rs, &jwsu, client_id.is_some())
+                .map(|claims| (o2rs, claims))
+        })
+        .ok_or_else(|| {
+            security_info!("Invalid oauth2 client assertion - no client key is able to verify it");
+            Oauth2Error::AuthenticationRequired
+        })?;
+
+    if claims.iss != o2rs.name || claims.sub != o2rs.name {
+        security_info!(iss = %claims.iss, sub = %claims.sub, "Invalid oauth2 client assertion - iss and sub must be the client_id");
+        return Err(Oauth2Error::AuthenticationRequired);
+    }
+
+    // We accept either our token endpoint or the issuer as the audience.
+    let aud_valid = |aud: &str| aud == o2rs.token_endpoint.as_str() || aud == o2rs.iss.as_str();
+    let aud_valid = match &claims.aud {
+        Audience::One(aud) => aud_valid(aud),
+        Audience::Many(auds) => auds.iter().any(|aud| aud_valid(aud)),
+    };
+    if !aud_valid {
+        security_info!("Invalid oauth2 client assertion - aud is not this server");
+        return Err(Oauth2Error::AuthenticationRequired);
+    }
+
+    let ct_secs = ct.as_secs() as i64;
+    if claims.exp <= ct_secs || claims.exp > ct_secs + CLIENT_ASSERTION_MAX_EXPIRY {
+        security_info!(exp = %claims.exp, "Invalid oauth2 client assertion - expired, or expiry too far in the future");
+        return Err(Oauth2Error::AuthenticationRequired);
+    }
+    if claims.nbf.map(|nbf| nbf > ct_secs).unwrap_or(false) {
+        security_info!("Invalid oauth2 client assertion - not yet valid");
+        return Err(Oauth2Error::AuthenticationRequired);
+    }
+
+    Ok(o2rs)
+}
+
+/// Verify a jws with the keys a resource server has registered, returning its content.
+/// When the jws has no kid, every key is tried only if `allow_no_kid` is set.
+fn oauth2_client_jws_verify<V>(
+    o2rs: &Oauth2RS,
+    jwsu: &JwsUnverified,
+    allow_no_kid: bool,
+) -> Option<V>
+where
+    V: Clone + DeserializeOwned + Serialize,
+{
+    let kid = jwsu.get_jwk_kid();
+    o2rs.client_jwks
+        .iter()
+        .flat_map(|jwks| jwks.keys.iter())
+        .filter_map(|jwk| JwsValidator::try_from(jwk).ok())
+        .filter(|validator| match (kid, validator.get_jwk_kid()) {
+            (Some(kid), Some(v_kid)) => kid == v_kid,
+            (None, _) => allow_no_kid,
+            (Some(_), None) => true,
+        })
+        .find_map(|validator| jwsu.validate::<V>(&validator).ok())
+        .map(|jws| jws.into_inner())
+}
+
+/// Validate a signed request object, returning the authorisation request it contains.
+/// <https://datatracker.ietf.org/doc/html/rfc9101#section-6>
+fn oauth2_request_object_validate(
+    o2rs: &Oauth2RS,
+    request: &str,
+    ct: Duration,
+) -> Result<AuthorisationRequest, Oauth2Error> {
+    let jwsu = JwsUnverified::from_str(request).map_err(|e| {
+        security_info!(err = ?e, "Invalid oauth2 request object");
+        Oauth2Error::InvalidRequestObject
+    })?;
+
+    let mut claims: serde_json::Map<String, serde_json::Value> =
+        oauth2_client_jws_verify(o2rs, &jwsu, true).ok_or_else(|| {
+            security_info!(?o2rs.name, "Invalid oauth2 request object - no client key is able to verify it");
+            Oauth2Error::InvalidRequestObject
+        })?;
+
+    // The request object is issued by the client, for us.
+    if claims.get("iss").and_then(|iss| iss.as_str()) != Some(o2rs.name.as_str()) {
+        security_info!("Invalid oauth2 request object - iss must be the client_id");
+        return Err(Oauth2Error::InvalidRequestObject);
+    }
+
+    let aud_valid = match claims.get("aud") {
+        Some(serde_json::Value::String(aud)) => aud == o2rs.iss.as_str(),
+        Some(serde_json::Value::Array(auds)) => auds
+            .iter()
+            .any(|aud| aud.as_str() == Some(o2rs.iss.as_str())),
+        _ => false,
+    };
+    if !aud_valid {
+        security_info!("Invalid oauth2 request object - aud must be the issuer");
+        return Err(Oauth2Error::InvalidRequestObject);
+    }
+
+    let ct_secs = ct.as_secs() as i64;
+    let expired = claims
+        .get("exp")
+        .map(|exp| exp.as_i64().map(|exp| exp <= ct_secs).unwrap_or(true))
+        .unwrap_or(false);
+    let not_yet_valid = claims
+        .get("nbf")
+        .map(|nbf| nbf.as_i64().map(|nbf| nbf > ct_secs).unwrap_or(true))
+        .unwrap_or(false);
+    if expired || not_yet_valid {
+        security_info!("Invalid oauth2 request object - expired or not yet valid");
+        return Err(Oauth2Error::InvalidRequestObject);
+    }
+
+    if claims
+        .get("client_id")
+        .map(|client_id| client_id.as_str() != Some(o2rs.name.as_str()))
+        .unwrap_or(false)
+    {
+        security_info!("Invalid oauth2 request object - client_id does not match the signer");
+        return Err(Oauth2Error::InvalidRequestObject);
+    }
+
+    // Remove the jwt claims so that only the authorisation parameters remain.
+    for claim in ["iss", "aud", "exp", "nbf", "iat", "jti"] {
+        claims.remove(claim);
+    }
+    claims.insert(
+        "client_id".to_string(),
+        serde_json::Value::String(o2rs.name.clone()),
+    );
+
+    serde_json::from_value(serde_json::Value::Object(claims)).map_err(|e| {
+        admin_warn!(err = ?e, "Invalid oauth2 request object - invalid authorisation parameters");
+        Oauth2Error::InvalidRequestObject
+    })
+}
+
+/// The redirect_uri of an authorisation request must be within the origin of the resource
+/// server, and must be https if the origin is.
+fn oauth2_redirect_uri_validate(o2rs: &Oauth2RS, redirect_uri: &Url) -> Result<(), Oauth2Error> {
+    if redirect_uri.origin() != o2rs.origin {
+        admin_warn!(
+            origin = ?o2rs.origin,
+            "Invalid oauth2 redirect_uri (must be related to origin {:?}) - got {:?}",
+            o2rs.origin,
+            redirect_uri.origin()
+        );
+        return Err(Oauth2Error::InvalidOrigin);
+    }
+
+    if o2rs.origin_https && redirect_uri.scheme() != "https" {
+        admin_warn!(
+            origin = ?o2rs.origin,
+            "Invalid oauth2 redirect_uri (must be https for secure origin) - got {:?}", redirect_uri.scheme()
+        );
+        return Err(Oauth2Error::InvalidOrigin);
+    }
+
+    Ok(())
+}
+
+/// A service account that may dynamically register resource servers, and the constraints
+/// on the resource servers it may register.
+struct Oauth2Registrar {
+    uuid: Uuid,
+    domains: Vec<String>,
+    scope_maps: BTreeMap<Uuid, BTreeSet<String>>,
+}
+
+/// The metadata of a dynamically registered client, once checked against its registrar.
+struct Oauth2ClientMetadata {
+    origin: Url,
+    redirect_uris: Vec<Url>,
+    client_name: Option<String>,
+    public: bool,
+    jwks: Option<String>,
+    scope_maps: BTreeMap<Uuid, BTreeSet<String>>,
+}
+
+fn oauth2_registrar(ident: &Identity) -> Result<Oauth2Registrar, Oauth2Error> {
+    let Some(entry) = ident.get_user_entry() else {
+        security_info!("Only service accounts may dynamically register oauth2 clients");
+        return Err(Oauth2Error::AccessDenied);
+    };
+
+    if !entry.attribute_equality("class", &PVCLASS_OAUTH2_REGISTRAR) {
+        security_info!(uuid = ?entry.get_uuid(), "Account is not an oauth2 registrar");
+        return Err(Oauth2Error::AccessDenied);
+    }
+
+    let domains = entry
+        .get_ava_iter_iutf8("oauth2_registrar_domain")
+        .map(|iter| iter.map(str::to_string).collect())
+        .unwrap_or_default();
+
+    let scope_maps = entry
+        .get_ava_as_oauthscopemaps("oauth2_registrar_scope_map")
+        .cloned()
+        .unwrap_or_default();
+
+    Ok(Oauth2Registrar {
+        uuid: entry.get_uuid(),
+        domains,
+        scope_maps,
+    })
+}
+
+/// As changes to registrations are made with internal operations, the registrar must hold
+/// a read-write token to make them.
+fn oauth2_registrar_write(ident: &Identity) -> Result<Oauth2Registrar, Oauth2Error> {
+    if ident.access_scope() != AccessScope::ReadWrite {
+        security_access!(
+            "identity access scope is not permitted to change oauth2 client registrations"
+        );
+        return Err(Oauth2Error::AccessDenied);
+    }
+    oauth2_registrar(ident)
+}
+
+/// Find a resource server that was registered by this registrar. Registrars can't tell which
+/// other resource servers exist, so a client they did not register is the same as one that
+/// does not exist.
+fn oauth2_registered_client<'a, T: QueryServerTransaction<'a>>(
+    qs: &mut T,
+    registrar: &Oauth2Registrar,
+    client_id: &str,
+) -> Result<Arc<EntrySealedCommitted>, Oauth2Error> {
+    let filter = filter!(f_and!([
+        f_eq("class", PVCLASS_OAUTH2_RS.clone()),
+        f_eq("oauth2_rs_name", PartialValue::new_iname(client_id))
+    ]));
+
+    let entry = qs
+        .internal_search(filter)
+        .map_err(Oauth2Error::ServerError)?
+        .pop();
+
+    match entry {
+        Some(entry)
+            if entry.get_ava_single_refer("oauth2_rs_registered_by") == Some(registrar.uuid) =>
+        {
+            Ok(entry)
+        }
+        _ => {
+            security_info!(%client_id, registrar = %registrar.uuid, "Oauth2 client was not registered by this registrar");
+            Err(Oauth2Error::InvalidClientId)
+        }
+    }
+}
+
+fn oauth2_client_metadata_validate(
+    registrar: &Oauth2Registrar,
+    reg_req: &ClientRegistrationRequest,
+) -> Result<Oauth2ClientMetadata, Oauth2Error> {
+    let Some(redirect_uri) = reg_req.redirect_uris.first() else {
+        security_info!("Client registration has no redirect uris");
+        return Err(Oauth2Error::InvalidRedirectUri);
+    };
+
+    // A resource server has a single origin, which all of its redirects must be within.
+    let origin = redirect_uri.origin();
+    if reg_req
+        .redirect_uris
+        .iter()
+        .any(|uri| uri.scheme() != "https" || uri.origin() != origin || uri.fragment().is_some())
+    {
+        security_info!(
+            ?origin,
+            "Client registration redirect uris must use https and share a single origin"
+        );
+        return Err(Oauth2Error::InvalidRedirectUri);
+    }
+
+    let host = redirect_uri.host_str().unwrap_or_default();
+    let domain_permitted = registrar.domains.iter().any(|domain| {
+        host == domain
+            || host
+                .strip_suffix(domain.as_str())
+                .map(|sub| sub.ends_with('.'))
+                .unwrap_or(false)
+    });
+    if !domain_permitted {
+        security_info!(%host, registrar = %registrar.uuid, "Client registration redirect uri is not within a domain of the registrar");
+        return Err(Oauth2Error::InvalidRedirectUri);
+    }
+
+    let origin =
+        Url::parse(&origin.ascii_serialization()).map_err(|_| Oauth2Error::InvalidRedirectUri)?;
+
+    if reg_req
+        .grant_types
+        .iter()
+        .any(|grant_type| grant_type != "authorization_code" && grant_type != "refresh_token")
+        || reg_req
+            .response_types
+            .iter()
+            .any(|response_type| response_type != "code")
+    {
+        security_info!("Client registration may only use the authorization code flow");
+        return Err(Oauth2Error::InvalidClientMetadata);
+    }
+
+    let (public, private_key_jwt) = match reg_req
+        .token_endpoint_auth_method
+        .as_deref()
+        .unwrap_or("client_secret_basic")
+    {
+        "client_secret_basic" | "client_secret_post" => (false, false),
+        "private_key_jwt" => (false, true),
+        "none" => (true, false),
+        method => {
+            security_info!(%method, "Unsupported client registration token endpoint auth method");
+            return Err(Oauth2Error::InvalidClientMetadata);
+        }
+    };
+
+    let jwks = match (&reg_req.jwks, private_key_jwt) {
+        (Some(jwks), true) => {
+            serde_json::from_value::<JwkKeySet>(jwks.clone()).map_err(|e| {
+                security_info!(?e, "Client registration jwks is invalid");
+                Oauth2Error::InvalidClientMetadata
+            })?;
+            Some(jwks.to_string())
+        }
+        (None, false) => None,
+        _ => {
+            security_info!("Client registration jwks must be present only for private_key_jwt");
+            return Err(Oauth2Error::InvalidClientMetadata);
+        }
+    };
+
+    // The client may narrow the scopes of the registrar, but never widen them.
+    let scope_maps: BTreeMap<Uuid, BTreeSet<String>> = match &reg_req.scope {
+        Some(requested) => {
+            if let Some(scope) = requested
+                .iter()
+                .find(|scope| !registrar.scope_maps.values().any(|s| s.contains(*scope)))
+            {
+                security_info!(%scope, "Client registration requested a scope the registrar may not grant");
+                return Err(Oauth2Error::InvalidClientMetadata);
+            }
+            registrar
+                .scope_maps
+                .iter()
+                .map(|(group, scopes)| (*group, scopes.intersection(requested).cloned().collect()))
+                .filter(|(_, scopes): &(Uuid, BTreeSet<String>)| !scopes.is_empty())
+                .collect()
+        }
+        None => registrar.scope_maps.clone(),
+    };
+    if scope_maps.is_empty() {
+        security_info!(registrar = %registrar.uuid, "Client registration would have no scopes");
+        return Err(Oauth2Error::InvalidClientMetadata);
+    }
+
+    Ok(Oauth2ClientMetadata {
+        origin,
+        redirect_uris: reg_req.redirect_uris.clone(),
+        client_name: reg_req.client_name.clone(),
+        public,
+        jwks,
+        scope_maps,
+    })
+}
+
+fn oauth2_client_registration_response(
+    origin: &Url,
+    entry: &EntrySealedCommitted,
+    client_id_issued_at: Option<i64>,
+) -> Result<ClientRegistrationResponse, Oauth2Error> {
+    let client_id = entry
+        .get_ava_single_iname("oauth2_rs_name")
+        .map(str::to_string)
+        .ok_or(Oauth2Error::ServerError(OperationError::InvalidValueState))?;
+    let client_name = entry
+        .get_ava_single_utf8("displayname")
+        .map(str::to_string)
+        .ok_or(Oauth2Error::ServerError(OperationError::InvalidValueState))?;
+    let mut redirect_uris: Vec<Url> = entry
+        .get_ava_set("oauth2_rs_redirect_uri")
+        .and_then(|vs| vs.as_url_set())
+        .map(|redirect_uris| redirect_uris.iter().cloned().collect())
+        .ok_or(Oauth2Error::ServerError(OperationError::InvalidValueState))?;
+    // The set has no order of its own, so keep the response stable.
+    redirect_uris.sort_unstable();
+
+    let (token_endpoint_auth_method, client_secret) =
+        if entry.attribute_equality("class", &PVCLASS_OAUTH2_PUBLIC) {
+            ("none", None)
+        } else if entry.get_ava_single_utf8("oauth2_rs_jwks").is_some() {
+            ("private_key_jwt", None)
+        } else {
+            (
+                "client_secret_basic",
+                entry
+                    .get_ava_single_secret("oauth2_rs_basic_secret")
+                    .map(str::to_string),
+            )
+        };
+
+    let scope = entry
+        .get_ava_as_oauthscopemaps("oauth2_rs_scope_map")
+        .map(|scope_maps| scope_maps.values().flatten().cloned().collect())
+        .unwrap_or_default();
+
+    let mut registration_client_uri = origin.clone();
+    registration_client_uri.set_path(&format!("/oauth2/register/{client_id}"));
+
+    Ok(ClientRegistrationResponse {
+        client_id,
+        client_secret_expires_at: client_secret.as_ref().map(|_| 0),
+        client_secret,
+        client_id_issued_at,
+        registration_client_uri,
+        redirect_uris,
+        client_name,
+        token_endpoint_auth_method: token_endpoint_auth_method.to_string(),
+        grant_types: vec![
+            "authorization_code".to_string(),
+            "refresh_token".to_string(),
+        ],
+        response_types: vec!["code".to_string()],
+        scope,
+    })
+}
+
+fn s_claims_for_account(
+    o2rs: &Oauth2RS,
+    account: &Account,
+    scopes: &BTreeSet<String>,
+) -> OidcClaims {
+    let preferred_username = if o2rs.prefer_short_username {
+        Some(account.name.clone())
+    } else {
+        Some(account.spn.clone())
+    };
+
+    let (email, email_verified) = if scopes.contains(&"email".to_string()) {
+        if let Some(mp) = &account.mail_primary {
+            (Some(mp.clone()), Some(true))
+        } else {
+            (None, None)
+        }
+    } else {
+        (None, None)
+    };
+
+    OidcClaims {
+        // Map from displayname
+        name: Some(account.displayname.clone()),
+        scopes: scopes.iter().cloned().collect(),
+        preferred_username,
+        email,
+        email_verified,
+        ..Default::default()
+    }
+}
+fn extra_claims_for_account(
+    account: &Account,
+    scopes: &BTreeSet<String>,
+) -> BTreeMap<String, serde_json::Value> {
+    let mut extra_claims = BTreeMap::new();
+    if scopes.contains(&"groups".to_string()) {
+        extra_claims.insert(
+            "groups".to_string(),
+            account.groups.iter().map(|x| x.to_proto().uuid).collect(),
+        );
+    }
+    extra_claims
+}
+
+fn str_join(set: &BTreeSet<String>) -> String {
+    let alloc_len = set.iter().fold(0, |acc, s| acc + s.len() + 1);
+    let mut buf = String::with_capacity(alloc_len);
+    set.iter().for_each(|s| {
+        buf.push_str(s);
+        buf.push(' ');
+    });
+
+    // Remove the excess trailing space.
+    let _ = buf.pop();
 
     buf
 }
@@ -1953,24 +3458,35 @@ fn str_join(set: &BTreeSet<String>) -> String {
 #[cfg(test)]
 mod tests {
     use base64::{engine::general_purpose, Engine as _};
+    use std::collections::{BTreeMap, BTreeSet};
     use std::convert::TryFrom;
     use std::str::FromStr;
     use std::time::Duration;
 
     use base64urlsafedata::Base64UrlSafeData;
-    use compact_jwt::{JwaAlg, Jwk, JwkUse, JwsValidator, OidcSubject, OidcUnverified};
+    use compact_jwt::{
+        JwaAlg, Jwk, JwkKeySet, JwkUse, Jws, JwsSigner, JwsUnverified, JwsValidator, OidcSubject,
+        OidcUnverified,
+    };
     use kanidm_proto::oauth2::*;
     use kanidm_proto::v1::UserAuthToken;
     use openssl::sha;
 
-    use crate::idm::oauth2::{AuthoriseResponse, Oauth2Error};
+    use crate::idm::account::{
+        DestroyAllSessionTokensEvent, ListOauth2GrantEvent, ListUserAuthTokenEvent,
+        RevokeOauth2GrantEvent,
+    };
+    use crate::idm::oauth2::{AuthoriseResponse, ClientAuthInfo, Oauth2Error};
     use crate::idm::server::{IdmServer, IdmServerTransaction};
     use crate::prelude::*;
 
     use crate::credential::Credential;
     use kanidm_lib_crypto::CryptoPolicy;
 
-    use super::Oauth2TokenType;
+    use super::{
+        Oauth2TokenType, BACKCHANNEL_LOGOUT_EVENT, PUSHED_AUTHORISATION_REQUEST_EXPIRY,
+        PUSHED_AUTHORISATION_REQUEST_MAX_PER_CLIENT,
+    };
 
     const TEST_CURRENT_TIME: u64 = 6000;
     const UAT_EXPIRE: u64 = 5;
@@ -2312,10 +3828,12 @@ mod tests {
             },
             client_id: Some("test_resource_server".to_string()),
             client_secret: Some(secret),
+            client_assertion_type: None,
+            client_assertion: None,
         };
 
         let token_response = idms_prox_write
-            .check_oauth2_token_exchange(None, &token_req, ct)
+            .check_oauth2_token_exchange(&ClientAuthInfo::default(), &token_req, ct)
             .expect("Failed to perform oauth2 token exchange");
 
         // 🎉 We got a token! In the future we can then check introspection from this point.
@@ -2378,10 +3896,12 @@ mod tests {
             },
             client_id: Some("test_resource_server".to_string()),
             client_secret: None,
+            client_assertion_type: None,
+            client_assertion: None,
         };
 
         let token_response = idms_prox_write
-            .check_oauth2_token_exchange(None, &token_req, ct)
+            .check_oauth2_token_exchange(&ClientAuthInfo::default(), &token_req, ct)
             .expect("Failed to perform oauth2 token exchange");
 
         // 🎉 We got a token! In the future we can then check introspection from this point.
@@ -2702,7 +4222,7 @@ mod tests {
 
         assert!(
             idms_prox_write
-                .check_oauth2_token_exchange(Some("not base64"), &token_req, ct)
+                .check_oauth2_token_exchange(&Some("not base64").into(), &token_req, ct)
                 .unwrap_err()
                 == Oauth2Error::AuthenticationRequired
         );
@@ -2712,7 +4232,7 @@ mod tests {
             Some(general_purpose::STANDARD.encode(format!("test_resource_server {secret}")));
         assert!(
             idms_prox_write
-                .check_oauth2_token_exchange(client_authz.as_deref(), &token_req, ct)
+                .check_oauth2_token_exchange(&client_authz.as_deref().into(), &token_req, ct)
                 .unwrap_err()
                 == Oauth2Error::AuthenticationRequired
         );
@@ -2722,7 +4242,7 @@ mod tests {
             Some(general_purpose::STANDARD.encode(format!("NOT A REAL SERVER:{secret}")));
         assert!(
             idms_prox_write
-                .check_oauth2_token_exchange(client_authz.as_deref(), &token_req, ct)
+                .check_oauth2_token_exchange(&client_authz.as_deref().into(), &token_req, ct)
                 .unwrap_err()
                 == Oauth2Error::AuthenticationRequired
         );
@@ -2731,7 +4251,7 @@ mod tests {
         let client_authz = Some(general_purpose::STANDARD.encode("test_resource_server:12345"));
         assert!(
             idms_prox_write
-                .check_oauth2_token_exchange(client_authz.as_deref(), &token_req, ct)
+                .check_oauth2_token_exchange(&client_authz.as_deref().into(), &token_req, ct)
                 .unwrap_err()
                 == Oauth2Error::AuthenticationRequired
         );
@@ -2743,7 +4263,7 @@ mod tests {
         assert!(
             idms_prox_write
                 .check_oauth2_token_exchange(
-                    client_authz.as_deref(),
+                    &client_authz.as_deref().into(),
                     &token_req,
                     ct + Duration::from_secs(TOKEN_EXPIRE)
                 )
@@ -2756,7 +4276,7 @@ mod tests {
         assert!(
             idms_prox_write
                 .check_oauth2_token_exchange(
-                    client_authz.as_deref(),
+                    &client_authz.as_deref().into(),
                     &token_req,
                     ct + Duration::from_secs(UAT_EXPIRE)
                 )
@@ -2777,7 +4297,7 @@ mod tests {
         };
         assert!(
             idms_prox_read
-                .check_oauth2_token_exchange(client_authz.as_deref(), &token_req, ct)
+                .check_oauth2_token_exchange(&client_authz.as_deref().into(), &token_req, ct)
                 .unwrap_err()
                 == Oauth2Error::InvalidRequest
         );
@@ -2792,7 +4312,7 @@ mod tests {
         .into();
         assert!(
             idms_prox_write
-                .check_oauth2_token_exchange(client_authz.as_deref(), &token_req, ct)
+                .check_oauth2_token_exchange(&client_authz.as_deref().into(), &token_req, ct)
                 .unwrap_err()
                 == Oauth2Error::InvalidOrigin
         );
@@ -2806,7 +4326,7 @@ mod tests {
         .into();
         assert!(
             idms_prox_write
-                .check_oauth2_token_exchange(client_authz.as_deref(), &token_req, ct)
+                .check_oauth2_token_exchange(&client_authz.as_deref().into(), &token_req, ct)
                 .unwrap_err()
                 == Oauth2Error::InvalidRequest
         );
@@ -2860,7 +4380,7 @@ mod tests {
         }
         .into();
         let oauth2_token = idms_prox_write
-            .check_oauth2_token_exchange(client_authz.as_deref(), &token_req, ct)
+            .check_oauth2_token_exchange(&client_authz.as_deref().into(), &token_req, ct)
             .expect("Unable to exchange for oauth2 token");
 
         assert!(idms_prox_write.commit().is_ok());
@@ -2871,9 +4391,12 @@ mod tests {
         let intr_request = AccessTokenIntrospectRequest {
             token: oauth2_token.access_token,
             token_type_hint: None,
+            client_id: None,
+            client_assertion_type: None,
+            client_assertion: None,
         };
         let intr_response = idms_prox_read
-            .check_oauth2_token_introspect(client_authz.as_deref().unwrap(), &intr_request, ct)
+            .check_oauth2_token_introspect(&client_authz.as_deref().into(), &intr_request, ct)
             .expect("Failed to inspect token");
 
         eprintln!("👉  {intr_response:?}");
@@ -2906,7 +4429,7 @@ mod tests {
         // check again.
         let mut idms_prox_read = idms.proxy_read().await;
         let intr_response = idms_prox_read
-            .check_oauth2_token_introspect(&client_authz.unwrap(), &intr_request, ct)
+            .check_oauth2_token_introspect(&client_authz.as_deref().into(), &intr_request, ct)
             .expect("Failed to inspect token");
 
         assert!(!intr_response.active);
@@ -2957,7 +4480,7 @@ mod tests {
         }
         .into();
         let oauth2_token = idms_prox_write
-            .check_oauth2_token_exchange(client_authz.as_deref(), &token_req, ct)
+            .check_oauth2_token_exchange(&client_authz.as_deref().into(), &token_req, ct)
             .expect("Unable to exchange for oauth2 token");
 
         assert!(idms_prox_write.commit().is_ok());
@@ -2969,9 +4492,12 @@ mod tests {
         let intr_request = AccessTokenIntrospectRequest {
             token: oauth2_token.access_token.clone(),
             token_type_hint: None,
+            client_id: None,
+            client_assertion_type: None,
+            client_assertion: None,
         };
         let intr_response = idms_prox_read
-            .check_oauth2_token_introspect(client_authz.as_deref().unwrap(), &intr_request, ct)
+            .check_oauth2_token_introspect(&client_authz.as_deref().into(), &intr_request, ct)
             .expect("Failed to inspect token");
         eprintln!("👉  {intr_response:?}");
         assert!(intr_response.active);
@@ -2984,9 +4510,12 @@ mod tests {
         let revoke_request = TokenRevokeRequest {
             token: oauth2_token.access_token.clone(),
             token_type_hint: None,
+            client_id: None,
+            client_assertion_type: None,
+            client_assertion: None,
         };
         let e = idms_prox_write
-            .oauth2_token_revoke(bad_client_authz.as_deref().unwrap(), &revoke_request, ct)
+            .oauth2_token_revoke(&bad_client_authz.as_deref().into(), &revoke_request, ct)
             .unwrap_err();
         assert!(matches!(e, Oauth2Error::AuthenticationRequired));
         assert!(idms_prox_write.commit().is_ok());
@@ -2996,9 +4525,12 @@ mod tests {
         let revoke_request = TokenRevokeRequest {
             token: "this is an invalid token, nothing will happen!".to_string(),
             token_type_hint: None,
+            client_id: None,
+            client_assertion_type: None,
+            client_assertion: None,
         };
         let e = idms_prox_write
-            .oauth2_token_revoke(client_authz.as_deref().unwrap(), &revoke_request, ct)
+            .oauth2_token_revoke(&client_authz.as_deref().into(), &revoke_request, ct)
             .unwrap_err();
         assert!(matches!(e, Oauth2Error::InvalidRequest));
         assert!(idms_prox_write.commit().is_ok());
@@ -3006,7 +4538,7 @@ mod tests {
         // Check our token is still valid.
         let mut idms_prox_read = idms.proxy_read().await;
         let intr_response = idms_prox_read
-            .check_oauth2_token_introspect(client_authz.as_deref().unwrap(), &intr_request, ct)
+            .check_oauth2_token_introspect(&client_authz.as_deref().into(), &intr_request, ct)
             .expect("Failed to inspect token");
         assert!(intr_response.active);
         drop(idms_prox_read);
@@ -3016,16 +4548,19 @@ mod tests {
         let revoke_request = TokenRevokeRequest {
             token: oauth2_token.access_token.clone(),
             token_type_hint: None,
+            client_id: None,
+            client_assertion_type: None,
+            client_assertion: None,
         };
         assert!(idms_prox_write
-            .oauth2_token_revoke(client_authz.as_deref().unwrap(), &revoke_request, ct,)
+            .oauth2_token_revoke(&client_authz.as_deref().into(), &revoke_request, ct,)
             .is_ok());
         assert!(idms_prox_write.commit().is_ok());
 
         // Check it is still valid - this is because we are still in the GRACE window.
         let mut idms_prox_read = idms.proxy_read().await;
         let intr_response = idms_prox_read
-            .check_oauth2_token_introspect(client_authz.as_deref().unwrap(), &intr_request, ct)
+            .check_oauth2_token_introspect(&client_authz.as_deref().into(), &intr_request, ct)
             .expect("Failed to inspect token");
 
         assert!(intr_response.active);
@@ -3037,7 +4572,7 @@ mod tests {
         // Assert it is now invalid.
         let mut idms_prox_read = idms.proxy_read().await;
         let intr_response = idms_prox_read
-            .check_oauth2_token_introspect(client_authz.as_deref().unwrap(), &intr_request, ct)
+            .check_oauth2_token_introspect(&client_authz.as_deref().into(), &intr_request, ct)
             .expect("Failed to inspect token");
 
         assert!(!intr_response.active);
@@ -3048,13 +4583,985 @@ mod tests {
         let revoke_request = TokenRevokeRequest {
             token: oauth2_token.access_token,
             token_type_hint: None,
+            client_id: None,
+            client_assertion_type: None,
+            client_assertion: None,
         };
         assert!(idms_prox_write
-            .oauth2_token_revoke(client_authz.as_deref().unwrap(), &revoke_request, ct,)
+            .oauth2_token_revoke(&client_authz.as_deref().into(), &revoke_request, ct,)
             .is_ok());
         assert!(idms_prox_write.commit().is_ok());
     }
 
+    #[idm_test]
+    async fn test_idm_oauth2_token_exchange_delegation(
+        idms: &IdmServer,
+        _idms_delayed: &mut IdmServerDelayed,
+    ) {
+        let ct = Duration::from_secs(TEST_CURRENT_TIME);
+        let (secret, uat, ident, rs_uuid) =
+            setup_oauth2_resource_server_basic(idms, ct, true, false, false).await;
+        let client_authz =
+            Some(general_purpose::STANDARD.encode(format!("test_resource_server:{secret}")));
+
+        // A downstream resource server that the first may be allowed to exchange into.
+        let mut idms_prox_write = idms.proxy_write(ct).await;
+        let downstream_uuid = Uuid::new_v4();
+        let e: Entry<EntryInit, EntryNew> = entry_init!(
+            ("class", Value::new_class("object")),
+            ("class", Value::new_class("oauth2_resource_server")),
+            ("class", Value::new_class("oauth2_resource_server_basic")),
+            ("uuid", Value::Uuid(downstream_uuid)),
+            ("oauth2_rs_name", Value::new_iname("test_downstream")),
+            ("displayname", Value::new_utf8s("test_downstream")),
+            (
+                "oauth2_rs_origin",
+                Value::new_url_s("https://downstream.example.com").unwrap()
+            ),
+            (
+                "oauth2_rs_scope_map",
+                Value::new_oauthscopemap(
+                    UUID_IDM_ALL_ACCOUNTS,
+                    btreeset!["read".to_string(), "write".to_string()]
+                )
+                .expect("invalid oauthscope")
+            )
+        );
+        let ce = CreateEvent::new_internal(vec![e]);
+        assert!(idms_prox_write.qs_write.create(&ce).is_ok());
+        let downstream_secret = idms_prox_write
+            .qs_write
+            .internal_search_uuid(downstream_uuid)
+            .expect("Failed to retrieve oauth2 resource entry")
+            .get_ava_single_secret("oauth2_rs_basic_secret")
+            .map(str::to_string)
+            .expect("No oauth2_rs_basic_secret found");
+        let downstream_authz =
+            general_purpose::STANDARD.encode(format!("test_downstream:{downstream_secret}"));
+
+        // The subject token will be able to read, but not write.
+        idms_prox_write
+            .qs_write
+            .internal_modify_uuid(
+                rs_uuid,
+                &ModifyList::new_list(vec![
+                    Modify::Removed(
+                        AttrString::from("oauth2_rs_scope_map"),
+                        PartialValue::Refer(UUID_IDM_ALL_ACCOUNTS),
+                    ),
+                    Modify::Present(
+                        AttrString::from("oauth2_rs_scope_map"),
+                        Value::new_oauthscopemap(
+                            UUID_IDM_ALL_ACCOUNTS,
+                            btreeset!["openid".to_string(), "read".to_string()],
+                        )
+                        .expect("invalid oauthscope"),
+                    ),
+                ]),
+            )
+            .expect("Failed to update scope map");
+        assert!(idms_prox_write.commit().is_ok());
+
+        // Get a token for the first resource server.
+        let idms_prox_read = idms.proxy_read().await;
+        let (code_verifier, code_challenge) = create_code_verifier!("Whar Garble");
+        let consent_request = good_authorisation_request!(
+            idms_prox_read,
+            &ident,
+            &uat,
+            ct,
+            code_challenge,
+            "openid read".to_string()
+        );
+        let AuthoriseResponse::ConsentRequested { consent_token, .. } = consent_request else {
+            unreachable!();
+        };
+        drop(idms_prox_read);
+
+        let mut idms_prox_write = idms.proxy_write(ct).await;
+        let permit_success = idms_prox_write
+            .check_oauth2_authorise_permit(&ident, &uat, &consent_token, ct)
+            .expect("Failed to perform oauth2 permit");
+        let token_req: AccessTokenRequest = GrantTypeReq::AuthorizationCode {
+            code: permit_success.code,
+            redirect_uri: Url::parse("https://demo.example.com/oauth2/result").unwrap(),
+            code_verifier,
+        }
+        .into();
+        let subject_token = idms_prox_write
+            .check_oauth2_token_exchange(&client_authz.as_deref().into(), &token_req, ct)
+            .expect("Failed to perform oauth2 token exchange")
+            .access_token;
+
+        let exchange_req =
+            |audience: &str, scope: Option<BTreeSet<String>>| -> AccessTokenRequest {
+                GrantTypeReq::TokenExchange {
+                    subject_token: subject_token.clone(),
+                    subject_token_type: OAUTH2_TOKEN_TYPE_ACCESS_TOKEN.to_string(),
+                    requested_token_type: None,
+                    audience: audience.to_string(),
+                    scope,
+                }
+                .into()
+            };
+
+        // The downstream has not allowed us to exchange into it yet.
+        assert!(matches!(
+            idms_prox_write.check_oauth2_token_exchange(
+                &client_authz.as_deref().into(),
+                &exchange_req("test_downstream", Some(btreeset!["read".to_string()])),
+                ct
+            ),
+            Err(Oauth2Error::InvalidTarget)
+        ));
+
+        idms_prox_write
+            .qs_write
+            .internal_modify_uuid(
+                downstream_uuid,
+                &ModifyList::new_append("oauth2_rs_token_exchange_from", Value::Refer(rs_uuid)),
+            )
+            .expect("Failed to allow token exchange");
+        assert!(idms_prox_write.commit().is_ok());
+
+        let mut idms_prox_write = idms.proxy_write(ct).await;
+
+        // Unknown audience.
+        assert!(matches!(
+            idms_prox_write.check_oauth2_token_exchange(
+                &client_authz.as_deref().into(),
+                &exchange_req("test_nonexistant", Some(btreeset!["read".to_string()])),
+                ct
+            ),
+            Err(Oauth2Error::InvalidTarget)
+        ));
+
+        // Scopes the account doesn't have on the downstream.
+        assert!(matches!(
+            idms_prox_write.check_oauth2_token_exchange(
+                &client_authz.as_deref().into(),
+                &exchange_req("test_downstream", Some(btreeset!["admin".to_string()])),
+                ct
+            ),
+            Err(Oauth2Error::InvalidScope)
+        ));
+
+        // Scopes the account has on the downstream, but that the subject token doesn't hold.
+        assert!(matches!(
+            idms_prox_write.check_oauth2_token_exchange(
+                &client_authz.as_deref().into(),
+                &exchange_req(
+                    "test_downstream",
+                    Some(btreeset!["read".to_string(), "write".to_string()])
+                ),
+                ct
+            ),
+            Err(Oauth2Error::InvalidScope)
+        ));
+
+        // Only access tokens can be exchanged.
+        let bad_type_req: AccessTokenRequest = GrantTypeReq::TokenExchange {
+            subject_token: subject_token.clone(),
+            subject_token_type: "urn:ietf:params:oauth:token-type:id_token".to_string(),
+            requested_token_type: None,
+            audience: "test_downstream".to_string(),
+            scope: None,
+        }
+        .into();
+        assert!(matches!(
+            idms_prox_write.check_oauth2_token_exchange(
+                &client_authz.as_deref().into(),
+                &bad_type_req,
+                ct
+            ),
+            Err(Oauth2Error::InvalidRequest)
+        ));
+
+        // The downstream can't exchange the tokens of the first resource server.
+        assert!(matches!(
+            idms_prox_write.check_oauth2_token_exchange(
+                &Some(downstream_authz.as_str()).into(),
+                &exchange_req("test_downstream", Some(btreeset!["read".to_string()])),
+                ct
+            ),
+            Err(Oauth2Error::InvalidGrant)
+        ));
+
+        // Without a request, only the scopes the subject token holds are granted.
+        let token_response = idms_prox_write
+            .check_oauth2_token_exchange(
+                &client_authz.as_deref().into(),
+                &exchange_req("test_downstream", None),
+                ct,
+            )
+            .expect("Failed to exchange token");
+        assert!(token_response.scope.as_deref() == Some("read"));
+
+        let token_response = idms_prox_write
+            .check_oauth2_token_exchange(
+                &client_authz.as_deref().into(),
+                &exchange_req("test_downstream", Some(btreeset!["read".to_string()])),
+                ct,
+            )
+            .expect("Failed to exchange token");
+
+        assert!(
+            token_response.issued_token_type.as_deref() == Some(OAUTH2_TOKEN_TYPE_ACCESS_TOKEN)
+        );
+        assert!(token_response.scope.as_deref() == Some("read"));
+        assert!(token_response.refresh_token.is_none());
+        assert!(token_response.id_token.is_none());
+        assert!(idms_prox_write.commit().is_ok());
+
+        // The exchanged token is valid for the downstream, even once the grace window has
+        // passed and its parent must be present.
+        let ct = ct + GRACE_WINDOW;
+        let intr_request = AccessTokenIntrospectRequest {
+            token: token_response.access_token.clone(),
+            token_type_hint: None,
+            client_id: None,
+            client_assertion_type: None,
+            client_assertion: None,
+        };
+        let mut idms_prox_read = idms.proxy_read().await;
+        let intr_response = idms_prox_read
+            .check_oauth2_token_introspect(&downstream_authz.as_str().into(), &intr_request, ct)
+            .expect("Failed to inspect token");
+        assert!(intr_response.active);
+        assert!(intr_response.scope.as_deref() == Some("read"));
+        assert!(intr_response.client_id.as_deref() == Some("test_downstream"));
+        drop(idms_prox_read);
+
+        // Revoking the subject token revokes the exchanged token too.
+        let mut idms_prox_write = idms.proxy_write(ct).await;
+        let revoke_request = TokenRevokeRequest {
+            token: subject_token.clone(),
+            token_type_hint: None,
+            client_id: None,
+            client_assertion_type: None,
+            client_assertion: None,
+        };
+        assert!(idms_prox_write
+            .oauth2_token_revoke(&client_authz.as_deref().into(), &revoke_request, ct)
+            .is_ok());
+        assert!(idms_prox_write.commit().is_ok());
+
+        let mut idms_prox_read = idms.proxy_read().await;
+        let intr_response = idms_prox_read
+            .check_oauth2_token_introspect(&downstream_authz.as_str().into(), &intr_request, ct)
+            .expect("Failed to inspect token");
+        assert!(!intr_response.active);
+
+        let entry = idms_prox_read
+            .qs_read
+            .internal_search_uuid(UUID_ADMIN)
+            .expect("failed to get admin");
+        assert!(entry
+            .get_ava_as_oauth2session_map("oauth2_session")
+            .map(|sessions| sessions.is_empty())
+            .unwrap_or(true));
+    }
+
+    #[idm_test]
+    async fn test_idm_oauth2_client_assertion_and_tls_client_auth(
+        idms: &IdmServer,
+        _idms_delayed: &mut IdmServerDelayed,
+    ) {
+        let ct = Duration::from_secs(TEST_CURRENT_TIME);
+        let (secret, uat, ident, rs_uuid) =
+            setup_oauth2_resource_server_basic(idms, ct, true, false, false).await;
+        let client_authz =
+            Some(general_purpose::STANDARD.encode(format!("test_resource_server:{secret}")));
+
+        // The keys the client authenticates with.
+        let client_signer = JwsSigner::generate_es256().expect("failed to generate key");
+        let jwks = JwkKeySet {
+            keys: vec![client_signer
+                .public_key_as_jwk()
+                .expect("failed to get public key")],
+        };
+        let thumbprint = "LdJ6y0eGmN2cC5C8n4m2gZ5N3k1nXj9pQ0mQW5o2v9U".to_string();
+
+        let mut idms_prox_write = idms.proxy_write(ct).await;
+        idms_prox_write
+            .qs_write
+            .internal_modify_uuid(
+                rs_uuid,
+                &ModifyList::new_list(vec![
+                    Modify::Present(
+                        "oauth2_rs_jwks".into(),
+                        Value::new_utf8(serde_json::to_string(&jwks).expect("invalid jwks")),
+                    ),
+                    Modify::Present(
+                        "oauth2_rs_tls_client_cert".into(),
+                        Value::new_utf8(thumbprint.clone()),
+                    ),
+                ]),
+            )
+            .expect("Failed to register client keys");
+        assert!(idms_prox_write.commit().is_ok());
+
+        let sign_assertion = |signer: &JwsSigner, aud: &str, exp: u64| -> String {
+            let claims = serde_json::json!({
+                "iss": "test_resource_server",
+                "sub": "test_resource_server",
+                "aud": aud,
+                "exp": ct.as_secs() + exp,
+                "jti": Uuid::new_v4().to_string(),
+            });
+            Jws::new(claims)
+                .sign(signer)
+                .expect("failed to sign assertion")
+                .to_string()
+        };
+        let token_endpoint = "https://idm.example.com/oauth2/token";
+
+        let assertion_req = |assertion: String| -> AccessTokenIntrospectRequest {
+            AccessTokenIntrospectRequest {
+                token: String::new(),
+                token_type_hint: None,
+                client_id: None,
+                client_assertion_type: Some(OAUTH2_CLIENT_ASSERTION_TYPE_JWT_BEARER.to_string()),
+                client_assertion: Some(assertion),
+            }
+        };
+
+        // Get a code to exchange.
+        let idms_prox_read = idms.proxy_read().await;
+        let (code_verifier, code_challenge) = create_code_verifier!("Whar Garble");
+        let consent_request = good_authorisation_request!(
+            idms_prox_read,
+            &ident,
+            &uat,
+            ct,
+            code_challenge,
+            "openid".to_string()
+        );
+        let AuthoriseResponse::ConsentRequested { consent_token, .. } = consent_request else {
+            unreachable!();
+        };
+        drop(idms_prox_read);
+
+        let mut idms_prox_write = idms.proxy_write(ct).await;
+        let permit_success = idms_prox_write
+            .check_oauth2_authorise_permit(&ident, &uat, &consent_token, ct)
+            .expect("Failed to perform oauth2 permit");
+        let mut token_req: AccessTokenRequest = GrantTypeReq::AuthorizationCode {
+            code: permit_success.code,
+            redirect_uri: Url::parse("https://demo.example.com/oauth2/result").unwrap(),
+            code_verifier,
+        }
+        .into();
+
+        // Now that the client has keys, the shared secret is no longer accepted.
+        assert!(matches!(
+            idms_prox_write.check_oauth2_token_exchange(
+                &client_authz.as_deref().into(),
+                &token_req,
+                ct
+            ),
+            Err(Oauth2Error::AuthenticationRequired)
+        ));
+
+        // Assertions signed by an unknown key, for another audience, or that live too long
+        // are rejected.
+        let other_signer = JwsSigner::generate_es256().expect("failed to generate key");
+        for assertion in [
+            sign_assertion(&other_signer, token_endpoint, 60),
+            sign_assertion(&client_signer, "https://other.example.com/token", 60),
+            sign_assertion(&client_signer, token_endpoint, 3600),
+        ] {
+            token_req.client_assertion_type =
+                Some(OAUTH2_CLIENT_ASSERTION_TYPE_JWT_BEARER.to_string());
+            token_req.client_assertion = Some(assertion);
+            assert!(matches!(
+                idms_prox_write.check_oauth2_token_exchange(
+                    &ClientAuthInfo::default(),
+                    &token_req,
+                    ct
+                ),
+                Err(Oauth2Error::AuthenticationRequired)
+            ));
+        }
+
+        // A valid assertion authenticates the client, and as the connection presented the
+        // registered certificate, the token is bound to it.
+        token_req.client_assertion = Some(sign_assertion(&client_signer, token_endpoint, 60));
+        let client_auth_info = ClientAuthInfo {
+            basic_authz: None,
+            client_cert: Some(thumbprint.clone()),
+        };
+        let token_response = idms_prox_write
+            .check_oauth2_token_exchange(&client_auth_info, &token_req, ct)
+            .expect("Failed to exchange code with a client assertion");
+        assert!(idms_prox_write.commit().is_ok());
+
+        // Introspect with an assertion that names the client only by its kid, and then
+        // with a certificate alone.
+        let mut idms_prox_read = idms.proxy_read().await;
+        let mut intr_request = assertion_req(sign_assertion(
+            &client_signer,
+            "https://idm.example.com/oauth2/openid/test_resource_server",
+            60,
+        ));
+        intr_request.token = token_response.access_token.clone();
+        let intr_response = idms_prox_read
+            .check_oauth2_token_introspect(&ClientAuthInfo::default(), &intr_request, ct)
+            .expect("Failed to inspect token");
+        assert!(intr_response.active);
+        assert!(
+            intr_response.cnf
+                == Some(Oauth2Confirmation {
+                    x5t_s256: thumbprint.clone()
+                })
+        );
+
+        let intr_request = AccessTokenIntrospectRequest {
+            token: token_response.access_token.clone(),
+            token_type_hint: None,
+            client_id: Some("test_resource_server".to_string()),
+            client_assertion_type: None,
+            client_assertion: None,
+        };
+        let intr_response = idms_prox_read
+            .check_oauth2_token_introspect(&client_auth_info, &intr_request, ct)
+            .expect("Failed to inspect token");
+        assert!(intr_response.active);
+
+        // An unregistered certificate does not authenticate the client.
+        let other_cert = ClientAuthInfo {
+            basic_authz: None,
+            client_cert: Some("b3RoZXI".to_string()),
+        };
+        assert!(matches!(
+            idms_prox_read.check_oauth2_token_introspect(&other_cert, &intr_request, ct),
+            Err(Oauth2Error::AuthenticationRequired)
+        ));
+
+        // The bound token can only be used at the userinfo endpoint over a connection that
+        // presents the same certificate.
+        for client_auth_info in [&ClientAuthInfo::default(), &other_cert] {
+            assert!(matches!(
+                idms_prox_read.oauth2_openid_userinfo(
+                    "test_resource_server",
+                    &token_response.access_token,
+                    client_auth_info,
+                    ct,
+                ),
+                Err(Oauth2Error::InvalidToken)
+            ));
+        }
+        assert!(idms_prox_read
+            .oauth2_openid_userinfo(
+                "test_resource_server",
+                &token_response.access_token,
+                &client_auth_info,
+                ct,
+            )
+            .is_ok());
+    }
+
+    #[idm_test]
+    async fn test_idm_oauth2_pushed_authorisation_and_request_objects(
+        idms: &IdmServer,
+        _idms_delayed: &mut IdmServerDelayed,
+    ) {
+        let ct = Duration::from_secs(TEST_CURRENT_TIME);
+        let (secret, uat, ident, rs_uuid) =
+            setup_oauth2_resource_server_basic(idms, ct, true, false, false).await;
+        let client_authz =
+            general_purpose::STANDARD.encode(format!("test_resource_server:{secret}"));
+        let (_code_verifier, code_challenge) = create_code_verifier!("Whar Garble");
+        let code_challenge = general_purpose::URL_SAFE_NO_PAD.encode(code_challenge);
+        let issuer = "https://idm.example.com/oauth2/openid/test_resource_server";
+
+        let par_params = |redirect_uri: &str| -> BTreeMap<String, String> {
+            [
+                ("response_type", "code"),
+                ("state", "123"),
+                ("redirect_uri", redirect_uri),
+                ("scope", "openid"),
+                ("code_challenge", code_challenge.as_str()),
+                ("code_challenge_method", "S256"),
+            ]
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+        };
+        let mut par_req = PushedAuthorisationRequest {
+            client_id: "test_resource_server".to_string(),
+            client_secret: None,
+            client_assertion_type: None,
+            client_assertion: None,
+            request: None,
+            params: par_params("https://demo.example.com/oauth2/result"),
+        };
+
+        let idms_prox_read = idms.proxy_read().await;
+
+        // The client must authenticate to push a request.
+        assert!(matches!(
+            idms_prox_read.check_oauth2_pushed_authorisation(
+                &ClientAuthInfo::default(),
+                &par_req,
+                ct
+            ),
+            Err(Oauth2Error::AuthenticationRequired)
+        ));
+
+        let par_res = idms_prox_read
+            .check_oauth2_pushed_authorisation(&client_authz.as_str().into(), &par_req, ct)
+            .expect("Failed to push authorisation request");
+        assert!(par_res
+            .request_uri
+            .starts_with(OAUTH2_PAR_REQUEST_URI_PREFIX));
+
+        let pushed_ref = AuthorisationRequestRef::Pushed {
+            client_id: "test_resource_server".to_string(),
+            request_uri: par_res.request_uri.clone(),
+        };
+        let auth_req = idms_prox_read
+            .resolve_oauth2_authorisation_request(pushed_ref.clone(), ct)
+            .expect("Failed to resolve pushed authorisation request");
+        assert!(auth_req.state == "123");
+        assert!(matches!(
+            idms_prox_read.check_oauth2_authorisation(&ident, &uat, &auth_req, ct),
+            Ok(AuthoriseResponse::ConsentRequested { .. })
+        ));
+
+        // The request_uri can only be used once.
+        assert!(matches!(
+            idms_prox_read.resolve_oauth2_authorisation_request(pushed_ref, ct),
+            Err(Oauth2Error::InvalidRequestUri)
+        ));
+
+        // It expires, and can't be altered.
+        let par_res = idms_prox_read
+            .check_oauth2_pushed_authorisation(&client_authz.as_str().into(), &par_req, ct)
+            .expect("Failed to push authorisation request");
+        assert!(matches!(
+            idms_prox_read.resolve_oauth2_authorisation_request(
+                AuthorisationRequestRef::Pushed {
+                    client_id: "test_resource_server".to_string(),
+                    request_uri: par_res.request_uri.clone(),
+                },
+                ct + Duration::from_secs(PUSHED_AUTHORISATION_REQUEST_EXPIRY + 1)
+            ),
+            Err(Oauth2Error::InvalidRequestUri)
+        ));
+        let par_res = idms_prox_read
+            .check_oauth2_pushed_authorisation(&client_authz.as_str().into(), &par_req, ct)
+            .expect("Failed to push authorisation request");
+        assert!(matches!(
+            idms_prox_read.resolve_oauth2_authorisation_request(
+                AuthorisationRequestRef::Pushed {
+                    client_id: "test_resource_server".to_string(),
+                    request_uri: format!("{}A", par_res.request_uri),
+                },
+                ct
+            ),
+            Err(Oauth2Error::InvalidRequestUri)
+        ));
+
+        // Only so many requests may be waiting to be used, until they expire.
+        for _ in 1..PUSHED_AUTHORISATION_REQUEST_MAX_PER_CLIENT {
+            assert!(idms_prox_read
+                .check_oauth2_pushed_authorisation(&client_authz.as_str().into(), &par_req, ct)
+                .is_ok());
+        }
+        assert!(matches!(
+            idms_prox_read.check_oauth2_pushed_authorisation(
+                &client_authz.as_str().into(),
+                &par_req,
+                ct
+            ),
+            Err(Oauth2Error::TemporarilyUnavailable)
+        ));
+        assert!(idms_prox_read
+            .check_oauth2_pushed_authorisation(
+                &client_authz.as_str().into(),
+                &par_req,
+                ct + Duration::from_secs(PUSHED_AUTHORISATION_REQUEST_EXPIRY)
+            )
+            .is_ok());
+
+        // Invalid requests are rejected when they are pushed.
+        par_req.params = par_params("https://evil.example.com/oauth2/result");
+        assert!(matches!(
+            idms_prox_read.check_oauth2_pushed_authorisation(
+                &client_authz.as_str().into(),
+                &par_req,
+                ct
+            ),
+            Err(Oauth2Error::InvalidOrigin)
+        ));
+        par_req.params = par_params("https://demo.example.com/oauth2/result");
+        par_req
+            .params
+            .insert("request_uri".to_string(), par_res.request_uri.clone());
+        assert!(matches!(
+            idms_prox_read.check_oauth2_pushed_authorisation(
+                &client_authz.as_str().into(),
+                &par_req,
+                ct
+            ),
+            Err(Oauth2Error::InvalidRequest)
+        ));
+        drop(idms_prox_read);
+
+        // Register the keys the client signs request objects with.
+        let client_signer = JwsSigner::generate_es256().expect("failed to generate key");
+        let jwks = JwkKeySet {
+            keys: vec![client_signer
+                .public_key_as_jwk()
+                .expect("failed to get public key")],
+        };
+        let mut idms_prox_write = idms.proxy_write(ct).await;
+        idms_prox_write
+            .qs_write
+            .internal_modify_uuid(
+                rs_uuid,
+                &ModifyList::new_list(vec![Modify::Present(
+                    "oauth2_rs_jwks".into(),
+                    Value::new_utf8(serde_json::to_string(&jwks).expect("invalid jwks")),
+                )]),
+            )
+            .expect("Failed to register client keys");
+        assert!(idms_prox_write.commit().is_ok());
+
+        let sign_request = |signer: &JwsSigner, aud: &str, exp: u64| -> String {
+            let mut claims: serde_json::Map<String, serde_json::Value> =
+                par_params("https://demo.example.com/oauth2/result")
+                    .into_iter()
+                    .map(|(k, v)| (k, serde_json::Value::String(v)))
+                    .collect();
+            claims.insert("iss".to_string(), "test_resource_server".into());
+            claims.insert("aud".to_string(), aud.into());
+            claims.insert("exp".to_string(), exp.into());
+            Jws::new(claims)
+                .sign(signer)
+                .expect("failed to sign request object")
+                .to_string()
+        };
+        let signed_ref = |request: String| AuthorisationRequestRef::Signed {
+            client_id: "test_resource_server".to_string(),
+            request,
+        };
+
+        let idms_prox_read = idms.proxy_read().await;
+        let auth_req = idms_prox_read
+            .resolve_oauth2_authorisation_request(
+                signed_ref(sign_request(&client_signer, issuer, ct.as_secs() + 60)),
+                ct,
+            )
+            .expect("Failed to resolve signed request object");
+        assert!(auth_req.client_id == "test_resource_server");
+        assert!(matches!(
+            idms_prox_read.check_oauth2_authorisation(&ident, &uat, &auth_req, ct),
+            Ok(AuthoriseResponse::ConsentRequested { .. })
+        ));
+
+        // Request objects signed by another key, for another audience, or that have expired
+        // are rejected.
+        let other_signer = JwsSigner::generate_es256().expect("failed to generate key");
+        for request in [
+            sign_request(&other_signer, issuer, ct.as_secs() + 60),
+            sign_request(
+                &client_signer,
+                "https://other.example.com",
+                ct.as_secs() + 60,
+            ),
+            sign_request(&client_signer, issuer, ct.as_secs() - 1),
+        ] {
+            assert!(matches!(
+                idms_prox_read.resolve_oauth2_authorisation_request(signed_ref(request), ct),
+                Err(Oauth2Error::InvalidRequestObject)
+            ));
+        }
+        drop(idms_prox_read);
+
+        let mut idms_prox_write = idms.proxy_write(ct).await;
+        idms_prox_write
+            .qs_write
+            .internal_modify_uuid(
+                rs_uuid,
+                &ModifyList::new_purge_and_set(
+                    "oauth2_require_pushed_authorisation",
+                    Value::new_bool(true),
+                ),
+            )
+            .expect("Failed to require pushed authorisation");
+        assert!(idms_prox_write.commit().is_ok());
+
+        let idms_prox_read = idms.proxy_read().await;
+        let discovery = idms_prox_read
+            .oauth2_openid_discovery("test_resource_server")
+            .expect("Failed to get discovery");
+        assert!(discovery.require_pushed_authorization_requests);
+
+        // Now only pushed requests are accepted, which here is a pushed request object
+        // from a client authenticating with an assertion.
+        let direct_req =
+            idms_prox_read.resolve_oauth2_authorisation_request(auth_req.clone().into(), ct);
+        assert!(matches!(direct_req, Err(Oauth2Error::InvalidRequest)));
+        assert!(matches!(
+            idms_prox_read.resolve_oauth2_authorisation_request(
+                signed_ref(sign_request(&client_signer, issuer, ct.as_secs() + 60)),
+                ct
+            ),
+            Err(Oauth2Error::InvalidRequest)
+        ));
+
+        let assertion = Jws::new(serde_json::json!({
+            "iss": "test_resource_server",
+            "sub": "test_resource_server",
+            "aud": issuer,
+            "exp": ct.as_secs() + 60,
+        }))
+        .sign(&client_signer)
+        .expect("failed to sign assertion")
+        .to_string();
+        let par_req = PushedAuthorisationRequest {
+            client_id: "test_resource_server".to_string(),
+            client_secret: None,
+            client_assertion_type: Some(OAUTH2_CLIENT_ASSERTION_TYPE_JWT_BEARER.to_string()),
+            client_assertion: Some(assertion),
+            request: Some(sign_request(&client_signer, issuer, ct.as_secs() + 60)),
+            params: BTreeMap::new(),
+        };
+        let par_res = idms_prox_read
+            .check_oauth2_pushed_authorisation(&ClientAuthInfo::default(), &par_req, ct)
+            .expect("Failed to push request object");
+        let auth_req = idms_prox_read
+            .resolve_oauth2_authorisation_request(
+                AuthorisationRequestRef::Pushed {
+                    client_id: "test_resource_server".to_string(),
+                    request_uri: par_res.request_uri,
+                },
+                ct,
+            )
+            .expect("Failed to resolve pushed request object");
+        assert!(matches!(
+            idms_prox_read.check_oauth2_authorisation(&ident, &uat, &auth_req, ct),
+            Ok(AuthoriseResponse::ConsentRequested { .. })
+        ));
+    }
+
+    #[idm_test]
+    async fn test_idm_oauth2_dynamic_client_registration(
+        idms: &IdmServer,
+        _idms_delayed: &mut IdmServerDelayed,
+    ) {
+        let ct = Duration::from_secs(TEST_CURRENT_TIME);
+        let mut idms_prox_write = idms.proxy_write(ct).await;
+
+        let registrar_uuid = Uuid::new_v4();
+        let other_uuid = Uuid::new_v4();
+        let registrar = |uuid: Uuid, name: &str| -> Entry<EntryInit, EntryNew> {
+            entry_init!(
+                ("class", Value::new_class("object")),
+                ("class", Value::new_class("account")),
+                ("class", Value::new_class("service_account")),
+                ("class", Value::new_class("oauth2_registrar")),
+                ("uuid", Value::Uuid(uuid)),
+                ("name", Value::new_iname(name)),
+                ("displayname", Value::new_utf8s(name)),
+                ("oauth2_registrar_domain", Value::new_iutf8("example.com")),
+                (
+                    "oauth2_registrar_scope_map",
+                    Value::new_oauthscopemap(
+                        UUID_IDM_ALL_ACCOUNTS,
+                        btreeset!["openid".to_string(), "profile".to_string()]
+                    )
+                    .expect("invalid oauthscope")
+                )
+            )
+        };
+        let ce = CreateEvent::new_internal(vec![
+            registrar(registrar_uuid, "test_registrar"),
+            registrar(other_uuid, "test_other_registrar"),
+        ]);
+        assert!(idms_prox_write.qs_write.create(&ce).is_ok());
+
+        let registrar_entry = idms_prox_write
+            .qs_write
+            .internal_search_uuid(registrar_uuid)
+            .expect("Failed to retrieve registrar");
+        let ident = Identity::from_impersonate_entry_readwrite(registrar_entry.clone());
+        let other_ident = Identity::from_impersonate_entry_readwrite(
+            idms_prox_write
+                .qs_write
+                .internal_search_uuid(other_uuid)
+                .expect("Failed to retrieve registrar"),
+        );
+        let admin_ident = Identity::from_impersonate_entry_readwrite(
+            idms_prox_write
+                .qs_write
+                .internal_search_uuid(UUID_ADMIN)
+                .expect("Failed to retrieve admin"),
+        );
+
+        let mut reg_req = ClientRegistrationRequest {
+            redirect_uris: vec![Url::parse("https://app.example.com/oauth2/callback").unwrap()],
+            client_name: Some("Test Application".to_string()),
+            grant_types: vec!["authorization_code".to_string()],
+            response_types: vec!["code".to_string()],
+            scope: Some(btreeset!["openid".to_string()]),
+            ..Default::default()
+        };
+
+        // Only registrars may register clients, and only with a read-write token.
+        assert!(matches!(
+            idms_prox_write.oauth2_client_register(&admin_ident, &reg_req, ct),
+            Err(Oauth2Error::AccessDenied)
+        ));
+        assert!(matches!(
+            idms_prox_write.oauth2_client_register(
+                &Identity::from_impersonate_entry_readonly(registrar_entry),
+                &reg_req,
+                ct
+            ),
+            Err(Oauth2Error::AccessDenied)
+        ));
+
+        // Redirects must be https, within the registrars domains, and share an origin.
+        for redirect_uris in [
+            vec!["https://example.org/oauth2/callback"],
+            vec!["https://badexample.com/oauth2/callback"],
+            vec!["http://app.example.com/oauth2/callback"],
+            vec![
+                "https://app.example.com/oauth2/callback",
+                "https://other.example.com/oauth2/callback",
+            ],
+        ] {
+            let reg_req = ClientRegistrationRequest {
+                redirect_uris: redirect_uris
+                    .into_iter()
+                    .map(|uri| Url::parse(uri).unwrap())
+                    .collect(),
+                ..reg_req.clone()
+            };
+            assert!(matches!(
+                idms_prox_write.oauth2_client_register(&ident, &reg_req, ct),
+                Err(Oauth2Error::InvalidRedirectUri)
+            ));
+        }
+
+        // Scopes can't be wider than the registrar was granted.
+        let wide_req = ClientRegistrationRequest {
+            scope: Some(btreeset!["openid".to_string(), "groups".to_string()]),
+            ..reg_req.clone()
+        };
+        assert!(matches!(
+            idms_prox_write.oauth2_client_register(&ident, &wide_req, ct),
+            Err(Oauth2Error::InvalidClientMetadata)
+        ));
+
+        let implicit_req = ClientRegistrationRequest {
+            response_types: vec!["token".to_string()],
+            ..reg_req.clone()
+        };
+        assert!(matches!(
+            idms_prox_write.oauth2_client_register(&ident, &implicit_req, ct),
+            Err(Oauth2Error::InvalidClientMetadata)
+        ));
+
+        let reg_res = idms_prox_write
+            .oauth2_client_register(&ident, &reg_req, ct)
+            .expect("Failed to register client");
+        assert!(reg_res.client_id.starts_with("dyn_"));
+        assert!(reg_res.client_secret.is_some());
+        assert_eq!(reg_res.client_secret_expires_at, Some(0));
+        assert_eq!(reg_res.token_endpoint_auth_method, "client_secret_basic");
+        assert_eq!(reg_res.client_name, "Test Application");
+        assert_eq!(reg_res.scope, btreeset!["openid".to_string()]);
+        assert_eq!(
+            reg_res.redirect_uris,
+            vec![Url::parse("https://app.example.com/oauth2/callback").unwrap()]
+        );
+        assert_eq!(
+            reg_res.registration_client_uri,
+            Url::parse(&format!(
+                "https://idm.example.com/oauth2/register/{}",
+                reg_res.client_id
+            ))
+            .unwrap()
+        );
+        let client_id = reg_res.client_id.clone();
+
+        assert!(idms_prox_write.commit().is_ok());
+
+        // The client is now usable, but only visible to the registrar that created it.
+        let mut idms_prox_read = idms.proxy_read().await;
+        assert!(idms_prox_read
+            .oauth2rs
+            .inner
+            .rs_set
+            .contains_key(&client_id));
+        assert!(matches!(
+            idms_prox_read.oauth2_client_registration_get(&other_ident, &client_id),
+            Err(Oauth2Error::InvalidClientId)
+        ));
+        assert!(matches!(
+            idms_prox_read.oauth2_client_registration_get(&ident, "test_resource_server"),
+            Err(Oauth2Error::InvalidClientId)
+        ));
+        let get_res = idms_prox_read
+            .oauth2_client_registration_get(&ident, &client_id)
+            .expect("Failed to read client registration");
+        assert_eq!(get_res.client_id, client_id);
+        assert!(get_res.client_id_issued_at.is_none());
+        assert_eq!(get_res.redirect_uris, reg_res.redirect_uris);
+        drop(idms_prox_read);
+
+        let mut idms_prox_write = idms.proxy_write(ct).await;
+        reg_req.client_name = Some("Renamed Application".to_string());
+        reg_req.scope = None;
+        reg_req.redirect_uris = vec![
+            Url::parse("https://app.example.com/oauth2/callback").unwrap(),
+            Url::parse("https://app.example.com/oauth2/silent").unwrap(),
+        ];
+        let update_res = idms_prox_write
+            .oauth2_client_registration_update(&ident, &client_id, &reg_req)
+            .expect("Failed to update client registration");
+        assert_eq!(update_res.client_name, "Renamed Application");
+        assert_eq!(update_res.redirect_uris, reg_req.redirect_uris);
+        assert_eq!(
+            update_res.scope,
+            btreeset!["openid".to_string(), "profile".to_string()]
+        );
+
+        // The client type can't change once registered.
+        let public_req = ClientRegistrationRequest {
+            token_endpoint_auth_method: Some("none".to_string()),
+            ..reg_req.clone()
+        };
+        assert!(matches!(
+            idms_prox_write.oauth2_client_registration_update(&ident, &client_id, &public_req),
+            Err(Oauth2Error::InvalidClientMetadata)
+        ));
+
+        assert!(matches!(
+            idms_prox_write.oauth2_client_registration_delete(&other_ident, &client_id),
+            Err(Oauth2Error::InvalidClientId)
+        ));
+        assert!(idms_prox_write
+            .oauth2_client_registration_delete(&ident, &client_id)
+            .is_ok());
+        assert!(idms_prox_write.commit().is_ok());
+
+        let mut idms_prox_read = idms.proxy_read().await;
+        assert!(!idms_prox_read
+            .oauth2rs
+            .inner
+            .rs_set
+            .contains_key(&client_id));
+        assert!(matches!(
+            idms_prox_read.oauth2_client_registration_get(&ident, &client_id),
+            Err(Oauth2Error::InvalidClientId)
+        ));
+    }
+
     #[idm_test]
     async fn test_idm_oauth2_session_cleanup_post_rs_delete(
         idms: &IdmServer,
@@ -3103,7 +5610,7 @@ mod tests {
         .into();
 
         let oauth2_token = idms_prox_write
-            .check_oauth2_token_exchange(client_authz.as_deref(), &token_req, ct)
+            .check_oauth2_token_exchange(&client_authz.as_deref().into(), &token_req, ct)
             .expect("Unable to exchange for oauth2 token");
 
         let reflected_token = idms_prox_write
@@ -3323,6 +5830,19 @@ mod tests {
                 .unwrap()
         );
 
+        assert!(
+            discovery.end_session_endpoint
+                == Some(Url::parse("https://idm.example.com/ui/oauth2/logout").unwrap())
+        );
+        assert!(
+            discovery.pushed_authorization_request_endpoint
+                == Some(Url::parse("https://idm.example.com/oauth2/par").unwrap())
+        );
+        assert!(!discovery.require_pushed_authorization_requests);
+        // No back-channel logout uri is configured.
+        assert!(!discovery.backchannel_logout_supported);
+        assert!(!discovery.backchannel_logout_session_supported);
+
         eprintln!("{:?}", discovery.scopes_supported);
         assert!(
             discovery.scopes_supported
@@ -3343,38 +5863,45 @@ mod tests {
             discovery.token_endpoint_auth_methods_supported
                 == vec![
                     TokenEndpointAuthMethod::ClientSecretBasic,
-                    TokenEndpointAuthMethod::ClientSecretPost
+                    TokenEndpointAuthMethod::ClientSecretPost,
+                    TokenEndpointAuthMethod::PrivateKeyJwt
                 ]
         );
+        assert!(!discovery.tls_client_certificate_bound_access_tokens);
         assert!(discovery.display_values_supported == Some(vec![DisplayValue::Page]));
         assert!(discovery.claim_types_supported == vec![ClaimType::Normal]);
         assert!(discovery.claims_supported.is_none());
         assert!(discovery.service_documentation.is_some());
 
-        assert!(discovery.registration_endpoint.is_none());
+        assert!(
+            discovery.registration_endpoint
+                == Some(Url::parse("https://idm.example.com/oauth2/register").unwrap())
+        );
         assert!(discovery.acr_values_supported.is_none());
         assert!(discovery.id_token_encryption_alg_values_supported.is_none());
         assert!(discovery.id_token_encryption_enc_values_supported.is_none());
         assert!(discovery.userinfo_encryption_alg_values_supported.is_none());
         assert!(discovery.userinfo_encryption_enc_values_supported.is_none());
-        assert!(discovery
-            .request_object_signing_alg_values_supported
-            .is_none());
+        assert!(
+            discovery.request_object_signing_alg_values_supported
+                == Some(vec!["ES256".to_string(), "RS256".to_string()])
+        );
         assert!(discovery
             .request_object_encryption_alg_values_supported
             .is_none());
         assert!(discovery
             .request_object_encryption_enc_values_supported
             .is_none());
-        assert!(discovery
-            .token_endpoint_auth_signing_alg_values_supported
-            .is_none());
+        assert!(
+            discovery.token_endpoint_auth_signing_alg_values_supported
+                == Some(vec!["ES256".to_string(), "RS256".to_string()])
+        );
         assert!(discovery.claims_locales_supported.is_none());
         assert!(discovery.ui_locales_supported.is_none());
         assert!(discovery.op_policy_uri.is_none());
         assert!(discovery.op_tos_uri.is_none());
         assert!(!discovery.claims_parameter_supported);
-        assert!(!discovery.request_uri_parameter_supported);
+        assert!(discovery.request_uri_parameter_supported);
         assert!(!discovery.require_request_uri_registration);
         assert!(discovery.request_parameter_supported);
     }
@@ -3428,7 +5955,7 @@ mod tests {
         .into();
 
         let token_response = idms_prox_write
-            .check_oauth2_token_exchange(client_authz.as_deref(), &token_req, ct)
+            .check_oauth2_token_exchange(&client_authz.as_deref().into(), &token_req, ct)
             .expect("Failed to perform oauth2 token exchange");
 
         // 🎉 We got a token!
@@ -3487,11 +6014,18 @@ mod tests {
         assert!(oidc.s_claims.name == Some("System Administrator".to_string()));
         assert!(oidc.s_claims.preferred_username == Some("admin@example.com".to_string()));
         assert!(oidc.s_claims.scopes == vec!["openid".to_string(), "supplement".to_string()]);
-        assert!(oidc.claims.is_empty());
+        // Without the groups scope, the session id is the only extra claim.
+        assert!(oidc.claims.len() == 1);
+        assert!(oidc.claims.contains_key("sid"));
         // Does our access token work with the userinfo endpoint?
         // Do the id_token details line up to the userinfo?
         let userinfo = idms_prox_read
-            .oauth2_openid_userinfo("test_resource_server", &access_token, ct)
+            .oauth2_openid_userinfo(
+                "test_resource_server",
+                &access_token,
+                &ClientAuthInfo::default(),
+                ct,
+            )
             .expect("failed to get userinfo");
 
         assert!(oidc.iss == userinfo.iss);
@@ -3524,7 +6058,7 @@ mod tests {
         .into();
 
         let token_response = idms_prox_write
-            .check_oauth2_token_exchange(client_authz.as_deref(), &token_req, ct)
+            .check_oauth2_token_exchange(&client_authz.as_deref().into(), &token_req, ct)
             .expect("Unable to exchange for oauth2 token");
 
         let access_token = token_response.access_token;
@@ -3535,7 +6069,12 @@ mod tests {
         let mut idms_prox_read = idms.proxy_read().await;
 
         let userinfo = idms_prox_read
-            .oauth2_openid_userinfo("test_resource_server", &access_token, ct)
+            .oauth2_openid_userinfo(
+                "test_resource_server",
+                &access_token,
+                &ClientAuthInfo::default(),
+                ct,
+            )
             .expect("failed to get userinfo");
 
         assert!(oidc.iss == userinfo.iss);
@@ -3555,6 +6094,160 @@ mod tests {
         assert!(userinfo.claims.is_empty());
     }
 
+    #[idm_test]
+    async fn test_idm_oauth2_openid_end_session(
+        idms: &IdmServer,
+        _idms_delayed: &mut IdmServerDelayed,
+    ) {
+        let ct = Duration::from_secs(TEST_CURRENT_TIME);
+        let (secret, uat, ident, rs_uuid) =
+            setup_oauth2_resource_server_basic(idms, ct, true, false, false).await;
+        let client_authz =
+            Some(general_purpose::STANDARD.encode(format!("test_resource_server:{secret}")));
+
+        // Enable back-channel logout for the resource server.
+        let mut idms_prox_write = idms.proxy_write(ct).await;
+        idms_prox_write
+            .qs_write
+            .internal_modify_uuid(
+                rs_uuid,
+                &ModifyList::new_purge_and_set(
+                    "oauth2_rs_backchannel_logout_uri",
+                    Value::new_url_s("https://demo.example.com/backchannel_logout").unwrap(),
+                ),
+            )
+            .expect("Failed to set backchannel logout uri");
+        assert!(idms_prox_write.commit().is_ok());
+
+        let idms_prox_read = idms.proxy_read().await;
+
+        let (code_verifier, code_challenge) = create_code_verifier!("Whar Garble");
+
+        let consent_request = good_authorisation_request!(
+            idms_prox_read,
+            &ident,
+            &uat,
+            ct,
+            code_challenge,
+            "openid".to_string()
+        );
+
+        let AuthoriseResponse::ConsentRequested { consent_token, .. } = consent_request else {
+            unreachable!();
+        };
+
+        drop(idms_prox_read);
+        let mut idms_prox_write = idms.proxy_write(ct).await;
+
+        let permit_success = idms_prox_write
+            .check_oauth2_authorise_permit(&ident, &uat, &consent_token, ct)
+            .expect("Failed to perform oauth2 permit");
+
+        let token_req: AccessTokenRequest = GrantTypeReq::AuthorizationCode {
+            code: permit_success.code,
+            redirect_uri: Url::parse("https://demo.example.com/oauth2/result").unwrap(),
+            code_verifier,
+        }
+        .into();
+
+        let token_response = idms_prox_write
+            .check_oauth2_token_exchange(&client_authz.as_deref().into(), &token_req, ct)
+            .expect("Failed to perform oauth2 token exchange");
+
+        let id_token = token_response.id_token.expect("No id_token in response!");
+        assert!(idms_prox_write.commit().is_ok());
+
+        let mut idms_prox_write = idms.proxy_write(ct).await;
+
+        // A redirect outside of the client origin is rejected.
+        let end_session_req = EndSessionRequest {
+            id_token_hint: Some(id_token.clone()),
+            post_logout_redirect_uri: Some(Url::parse("https://evil.example.com/").unwrap()),
+            ..Default::default()
+        };
+        assert!(matches!(
+            idms_prox_write.oauth2_end_session(Some(&ident), &end_session_req),
+            Err(Oauth2Error::InvalidOrigin)
+        ));
+
+        // As is a redirect without a client to check it against.
+        let end_session_req = EndSessionRequest {
+            post_logout_redirect_uri: Some(Url::parse("https://demo.example.com/").unwrap()),
+            ..Default::default()
+        };
+        assert!(matches!(
+            idms_prox_write.oauth2_end_session(Some(&ident), &end_session_req),
+            Err(Oauth2Error::InvalidRequest)
+        ));
+
+        // Without an identified session, nothing is ended but we still redirect.
+        let end_session_req = EndSessionRequest {
+            id_token_hint: Some(id_token.clone()),
+            post_logout_redirect_uri: Some(
+                Url::parse("https://demo.example.com/logged_out").unwrap(),
+            ),
+            state: Some("abcdef".to_string()),
+            ..Default::default()
+        };
+        let resp = idms_prox_write
+            .oauth2_end_session(None, &end_session_req)
+            .expect("Failed to end session");
+        assert!(!resp.logged_out);
+
+        let resp = idms_prox_write
+            .oauth2_end_session(Some(&ident), &end_session_req)
+            .expect("Failed to end session");
+        assert!(resp.logged_out);
+        assert!(
+            resp.redirect_uri
+                == Some(Url::parse("https://demo.example.com/logged_out?state=abcdef").unwrap())
+        );
+
+        // Ending the users session ended the oauth2 session, which the rs is told about.
+        let ended = std::mem::take(&mut idms_prox_write.qs_write.oauth2_sessions_ended);
+        assert!(ended.len() == 1);
+        let mut logouts = idms_prox_write.oauth2_backchannel_logouts(ended, ct);
+        let logout = logouts.pop().expect("No backchannel logout");
+        assert!(logouts.is_empty());
+        assert!(logout.rs_name == "test_resource_server");
+        assert!(logout.url == Url::parse("https://demo.example.com/backchannel_logout").unwrap());
+        assert!(idms_prox_write.commit().is_ok());
+
+        let mut idms_prox_read = idms.proxy_read().await;
+        let mut jwkset = idms_prox_read
+            .oauth2_openid_publickey("test_resource_server")
+            .expect("Failed to get public key");
+        let public_jwk = jwkset.keys.pop().expect("no such jwk");
+        let jws_validator = JwsValidator::try_from(&public_jwk).expect("failed to build validator");
+
+        let id_token = JwsUnverified::from_str(&id_token)
+            .and_then(|jwsu| jwsu.validate::<serde_json::Value>(&jws_validator))
+            .map(Jws::into_inner)
+            .expect("Failed to verify id_token");
+        let logout_token = JwsUnverified::from_str(&logout.logout_token)
+            .and_then(|jwsu| jwsu.validate::<serde_json::Value>(&jws_validator))
+            .map(Jws::into_inner)
+            .expect("Failed to verify logout token");
+
+        assert!(logout_token["sid"] == id_token["sid"]);
+        assert!(logout_token["sub"] == id_token["sub"]);
+        assert!(logout_token["aud"] == "test_resource_server");
+        assert!(logout_token["events"]
+            .get(BACKCHANNEL_LOGOUT_EVENT)
+            .is_some());
+        assert!(logout_token.get("nonce").is_none());
+
+        // The users session is gone.
+        let entry = idms_prox_read
+            .qs_read
+            .internal_search_uuid(UUID_ADMIN)
+            .expect("failed to get admin");
+        assert!(!entry
+            .get_ava_as_session_map("user_auth_token_session")
+            .map(|sessions| sessions.contains_key(&uat.session_id))
+            .unwrap_or(false));
+    }
+
     #[idm_test]
     async fn test_idm_oauth2_openid_short_username(
         idms: &IdmServer,
@@ -3606,7 +6299,7 @@ mod tests {
         .into();
 
         let token_response = idms_prox_write
-            .check_oauth2_token_exchange(client_authz.as_deref(), &token_req, ct)
+            .check_oauth2_token_exchange(&client_authz.as_deref().into(), &token_req, ct)
             .expect("Failed to perform oauth2 token exchange");
 
         let id_token = token_response.id_token.expect("No id_token in response!");
@@ -3635,7 +6328,12 @@ mod tests {
         assert!(oidc.s_claims.preferred_username == Some("admin".to_string()));
         // Do the id_token details line up to the userinfo?
         let userinfo = idms_prox_read
-            .oauth2_openid_userinfo("test_resource_server", &access_token, ct)
+            .oauth2_openid_userinfo(
+                "test_resource_server",
+                &access_token,
+                &ClientAuthInfo::default(),
+                ct,
+            )
             .expect("failed to get userinfo");
 
         assert!(oidc.s_claims == userinfo.s_claims);
@@ -3692,7 +6390,7 @@ mod tests {
         .into();
 
         let token_response = idms_prox_write
-            .check_oauth2_token_exchange(client_authz.as_deref(), &token_req, ct)
+            .check_oauth2_token_exchange(&client_authz.as_deref().into(), &token_req, ct)
             .expect("Failed to perform oauth2 token exchange");
 
         let id_token = token_response.id_token.expect("No id_token in response!");
@@ -3730,7 +6428,12 @@ mod tests {
 
         // Do the id_token details line up to the userinfo?
         let userinfo = idms_prox_read
-            .oauth2_openid_userinfo("test_resource_server", &access_token, ct)
+            .oauth2_openid_userinfo(
+                "test_resource_server",
+                &access_token,
+                &ClientAuthInfo::default(),
+                ct,
+            )
             .expect("failed to get userinfo");
 
         // does the userinfo endpoint provide the same groups?
@@ -3853,10 +6556,12 @@ mod tests {
             },
             client_id: Some("test_resource_server".to_string()),
             client_secret: Some(secret),
+            client_assertion_type: None,
+            client_assertion: None,
         };
 
         let token_response = idms_prox_write
-            .check_oauth2_token_exchange(None, &token_req, ct)
+            .check_oauth2_token_exchange(&ClientAuthInfo::default(), &token_req, ct)
             .expect("Failed to perform oauth2 token exchange");
 
         // 🎉 We got a token!
@@ -4138,6 +6843,245 @@ mod tests {
         assert!(idms_prox_write.commit().is_ok());
     }
 
+    #[idm_test]
+    async fn test_idm_oauth2_grant_list_and_revoke(
+        idms: &IdmServer,
+        _idms_delayed: &mut IdmServerDelayed,
+    ) {
+        let ct = Duration::from_secs(TEST_CURRENT_TIME);
+        let (secret, uat, ident, o2rs_uuid) =
+            setup_oauth2_resource_server_basic(idms, ct, true, false, false).await;
+
+        let idms_prox_read = idms.proxy_read().await;
+
+        let (code_verifier, code_challenge) = create_code_verifier!("Whar Garble");
+        let consent_request = good_authorisation_request!(
+            idms_prox_read,
+            &ident,
+            &uat,
+            ct,
+            code_challenge,
+            "openid".to_string()
+        );
+
+        let AuthoriseResponse::ConsentRequested { consent_token, .. } = consent_request else {
+            unreachable!();
+        };
+
+        drop(idms_prox_read);
+        let mut idms_prox_write = idms.proxy_write(ct).await;
+
+        let permit_success = idms_prox_write
+            .check_oauth2_authorise_permit(&ident, &uat, &consent_token, ct)
+            .expect("Failed to perform oauth2 permit");
+
+        let token_req = AccessTokenRequest {
+            grant_type: GrantTypeReq::AuthorizationCode {
+                code: permit_success.code,
+                redirect_uri: Url::parse("https://demo.example.com/oauth2/result").unwrap(),
+                code_verifier,
+            },
+            client_id: Some("test_resource_server".to_string()),
+            client_secret: Some(secret),
+            client_assertion_type: None,
+            client_assertion: None,
+        };
+
+        let _token_response = idms_prox_write
+            .check_oauth2_token_exchange(&ClientAuthInfo::default(), &token_req, ct)
+            .expect("Failed to perform oauth2 token exchange");
+
+        assert!(idms_prox_write.commit().is_ok());
+
+        // The user can see the consent and the session the resource server holds.
+        let mut idms_prox_read = idms.proxy_read().await;
+
+        let lge = ListOauth2GrantEvent {
+            ident: ident.clone(),
+            target: UUID_ADMIN,
+        };
+        let grants = idms_prox_read
+            .account_list_oauth2_grants(&lge, ct)
+            .expect("Failed to list oauth2 grants");
+
+        assert_eq!(grants.len(), 1);
+        assert_eq!(grants[0].name, "test_resource_server");
+        assert_eq!(grants[0].session_count, 1);
+        assert!(grants[0].scopes.contains("openid"));
+
+        // As well as the credential that their own session was established with.
+        let lte = ListUserAuthTokenEvent {
+            ident: ident.clone(),
+            target: UUID_ADMIN,
+        };
+        let uats = idms_prox_read
+            .account_list_user_auth_tokens(&lte)
+            .expect("Failed to list user auth tokens");
+
+        assert_eq!(uats.len(), 1);
+        assert_eq!(uats[0].credential.as_deref(), Some("Password"));
+
+        drop(idms_prox_read);
+
+        // Revoke the grant, which removes both the consent and the session.
+        let mut idms_prox_write = idms.proxy_write(ct).await;
+
+        let rge = RevokeOauth2GrantEvent {
+            ident: ident.clone(),
+            target: UUID_ADMIN,
+            rs_name: "test_resource_server".to_string(),
+        };
+        assert!(idms_prox_write.account_revoke_oauth2_grant(&rge).is_ok());
+
+        let ident = idms_prox_write
+            .process_uat_to_identity(&uat, ct)
+            .expect("Unable to process uat");
+        assert!(ident.get_oauth2_consent_scopes(o2rs_uuid).is_none());
+
+        let entry = idms_prox_write
+            .qs_write
+            .internal_search_uuid(UUID_ADMIN)
+            .expect("failed to get entry");
+        assert!(entry
+            .get_ava_as_oauth2session_map("oauth2_session")
+            .is_none());
+
+        // Revoking an unknown resource server is an error.
+        let rge = RevokeOauth2GrantEvent {
+            ident: ident.clone(),
+            target: UUID_ADMIN,
+            rs_name: "unknown_resource_server".to_string(),
+        };
+        assert!(idms_prox_write.account_revoke_oauth2_grant(&rge).is_err());
+
+        // Finally, log out everywhere.
+        let dte = DestroyAllSessionTokensEvent {
+            ident,
+            target: UUID_ADMIN,
+        };
+        assert!(idms_prox_write
+            .account_destroy_all_session_tokens(&dte)
+            .is_ok());
+
+        let entry = idms_prox_write
+            .qs_write
+            .internal_search_uuid(UUID_ADMIN)
+            .expect("failed to get entry");
+        assert!(entry
+            .get_ava_as_session_map("user_auth_token_session")
+            .is_none());
+
+        assert!(idms_prox_write.commit().is_ok());
+    }
+
+    #[idm_test]
+    async fn test_idm_oauth2_grant_revoke_by_admin(
+        idms: &IdmServer,
+        _idms_delayed: &mut IdmServerDelayed,
+    ) {
+        let ct = Duration::from_secs(TEST_CURRENT_TIME);
+        let (_secret, _uat, _ident, o2rs_uuid) =
+            setup_oauth2_resource_server_basic(idms, ct, true, false, false).await;
+        let (_idm_admin_uat, idm_admin_ident) = setup_idm_admin(idms, ct).await;
+
+        // A person who has consented to the resource server.
+        let mut idms_prox_write = idms.proxy_write(ct).await;
+
+        let person_uuid = Uuid::new_v4();
+        let e = entry_init!(
+            ("class", Value::new_class("object")),
+            ("class", Value::new_class("account")),
+            ("class", Value::new_class("person")),
+            ("name", Value::new_iname("testperson")),
+            ("uuid", Value::Uuid(person_uuid)),
+            ("displayname", Value::new_utf8s("Test Person")),
+            (
+                "oauth2_consent_scope_map",
+                Value::new_oauthscopemap(o2rs_uuid, btreeset!["openid".to_string()])
+                    .expect("invalid oauthscope")
+            )
+        );
+        assert!(idms_prox_write.qs_write.internal_create(vec![e]).is_ok());
+        assert!(idms_prox_write.commit().is_ok());
+
+        // An administrator can review the consent of the person.
+        let mut idms_prox_read = idms.proxy_read().await;
+
+        let lge = ListOauth2GrantEvent {
+            ident: idm_admin_ident.clone(),
+            target: person_uuid,
+        };
+        let grants = idms_prox_read
+            .account_list_oauth2_grants(&lge, ct)
+            .expect("Failed to list oauth2 grants");
+
+        assert_eq!(grants.len(), 1);
+        assert_eq!(grants[0].name, "test_resource_server");
+        assert_eq!(grants[0].session_count, 0);
+
+        drop(idms_prox_read);
+
+        // And withdraw it on their behalf.
+        let mut idms_prox_write = idms.proxy_write(ct).await;
+
+        let rge = RevokeOauth2GrantEvent {
+            ident: idm_admin_ident,
+            target: person_uuid,
+            rs_name: "test_resource_server".to_string(),
+        };
+        assert!(idms_prox_write.account_revoke_oauth2_grant(&rge).is_ok());
+
+        let entry = idms_prox_write
+            .qs_write
+            .internal_search_uuid(person_uuid)
+            .expect("failed to get entry");
+        assert!(entry
+            .get_ava_as_oauthscopemaps("oauth2_consent_scope_map")
+            .is_none());
+
+        assert!(idms_prox_write.commit().is_ok());
+    }
+
+    #[idm_test]
+    async fn test_idm_oauth2_consent_prompt_disable(
+        idms: &IdmServer,
+        _idms_delayed: &mut IdmServerDelayed,
+    ) {
+        let ct = Duration::from_secs(TEST_CURRENT_TIME);
+        let (_secret, uat, ident, o2rs_uuid) =
+            setup_oauth2_resource_server_basic(idms, ct, true, false, false).await;
+
+        // Mark the resource server as a first party application.
+        let mut idms_prox_write = idms.proxy_write(ct).await;
+        idms_prox_write
+            .qs_write
+            .internal_modify_uuid(
+                o2rs_uuid,
+                &ModifyList::new_purge_and_set(
+                    "oauth2_consent_prompt_disable",
+                    Value::new_bool(true),
+                ),
+            )
+            .expect("Failed to disable consent prompt");
+        assert!(idms_prox_write.commit().is_ok());
+
+        let idms_prox_read = idms.proxy_read().await;
+
+        let (_code_verifier, code_challenge) = create_code_verifier!("Whar Garble");
+        let consent_request = good_authorisation_request!(
+            idms_prox_read,
+            &ident,
+            &uat,
+            ct,
+            code_challenge,
+            "openid".to_string()
+        );
+
+        // The user is not asked for consent, and none is recorded on their behalf.
+        assert!(matches!(consent_request, AuthoriseResponse::Permitted(_)));
+        assert!(ident.get_oauth2_consent_scopes(o2rs_uuid).is_none());
+    }
+
     #[idm_test]
     // https://datatracker.ietf.org/doc/html/draft-ietf-oauth-security-topics#section-4.8
     //
@@ -4220,11 +7164,13 @@ mod tests {
             },
             client_id: Some("test_resource_server".to_string()),
             client_secret: Some(secret),
+            client_assertion_type: None,
+            client_assertion: None,
         };
 
         // Assert the exchange fails.
         assert!(matches!(
-            idms_prox_write.check_oauth2_token_exchange(None, &token_req, ct),
+            idms_prox_write.check_oauth2_token_exchange(&ClientAuthInfo::default(), &token_req, ct),
             Err(Oauth2Error::InvalidRequest)
         ));
 
@@ -4312,11 +7258,13 @@ mod tests {
             },
             client_id: Some("test_resource_server".to_string()),
             client_secret: Some(secret),
+            client_assertion_type: None,
+            client_assertion: None,
         };
 
         // Assert the exchange fails.
         assert!(matches!(
-            idms_prox_write.check_oauth2_token_exchange(None, &token_req, ct),
+            idms_prox_write.check_oauth2_token_exchange(&ClientAuthInfo::default(), &token_req, ct),
             Err(Oauth2Error::InvalidOrigin)
         ));
 
@@ -4369,7 +7317,7 @@ mod tests {
         }
         .into();
         let access_token_response_1 = idms_prox_write
-            .check_oauth2_token_exchange(client_authz.as_deref(), &token_req, ct)
+            .check_oauth2_token_exchange(&client_authz.as_deref().into(), &token_req, ct)
             .expect("Unable to exchange for oauth2 token");
 
         assert!(idms_prox_write.commit().is_ok());
@@ -4409,7 +7357,7 @@ mod tests {
         .into();
 
         let access_token_response_2 = idms_prox_write
-            .check_oauth2_token_exchange(client_authz.as_deref(), &token_req, ct)
+            .check_oauth2_token_exchange(&client_authz.as_deref().into(), &token_req, ct)
             .expect("Unable to exchange for oauth2 token");
 
         assert!(idms_prox_write.commit().is_ok());
@@ -4450,7 +7398,7 @@ mod tests {
         .into();
 
         let access_token_response_3 = idms_prox_write
-            .check_oauth2_token_exchange(client_authz.as_deref(), &token_req, ct)
+            .check_oauth2_token_exchange(&client_authz.as_deref().into(), &token_req, ct)
             .expect("Unable to exchange for oauth2 token");
 
         assert!(idms_prox_write.commit().is_ok());
@@ -4484,7 +7432,7 @@ mod tests {
         }
         .into();
         let access_token_response_4 = idms_prox_write
-            .check_oauth2_token_exchange(client_authz.as_deref(), &token_req, ct)
+            .check_oauth2_token_exchange(&client_authz.as_deref().into(), &token_req, ct)
             .unwrap_err();
 
         assert!(access_token_response_4 == Oauth2Error::InvalidToken);
@@ -4511,9 +7459,12 @@ mod tests {
         let revoke_request = TokenRevokeRequest {
             token: access_token_response_1.access_token.clone(),
             token_type_hint: None,
+            client_id: None,
+            client_assertion_type: None,
+            client_assertion: None,
         };
         assert!(idms_prox_write
-            .oauth2_token_revoke(client_authz.as_deref().unwrap(), &revoke_request, ct,)
+            .oauth2_token_revoke(&client_authz.as_deref().into(), &revoke_request, ct,)
             .is_ok());
         assert!(idms_prox_write.commit().is_ok());
 
@@ -4533,7 +7484,7 @@ mod tests {
         }
         .into();
         let access_token_response_2 = idms_prox_write
-            .check_oauth2_token_exchange(client_authz.as_deref(), &token_req, ct)
+            .check_oauth2_token_exchange(&client_authz.as_deref().into(), &token_req, ct)
             // Should be unable to exchange.
             .unwrap_err();
 
@@ -4575,7 +7526,7 @@ mod tests {
         }
         .into();
         let access_token_response_2 = idms_prox_write
-            .check_oauth2_token_exchange(client_authz.as_deref(), &token_req, ct)
+            .check_oauth2_token_exchange(&client_authz.as_deref().into(), &token_req, ct)
             .unwrap_err();
 
         assert!(access_token_response_2 == Oauth2Error::AuthenticationRequired);
@@ -4612,7 +7563,7 @@ mod tests {
         }
         .into();
         let access_token_response_2 = idms_prox_write
-            .check_oauth2_token_exchange(client_authz.as_deref(), &token_req, ct)
+            .check_oauth2_token_exchange(&client_authz.as_deref().into(), &token_req, ct)
             .unwrap_err();
 
         assert!(access_token_response_2 == Oauth2Error::InvalidScope);
@@ -4652,7 +7603,7 @@ mod tests {
         .into();
 
         let _access_token_response_2 = idms_prox_write
-            .check_oauth2_token_exchange(client_authz.as_deref(), &token_req, ct)
+            .check_oauth2_token_exchange(&client_authz.as_deref().into(), &token_req, ct)
             .expect("Unable to exchange for oauth2 token");
 
         assert!(idms_prox_write.commit().is_ok());
@@ -4674,7 +7625,7 @@ mod tests {
         .into();
 
         let access_token_response_3 = idms_prox_write
-            .check_oauth2_token_exchange(client_authz.as_deref(), &token_req, ct)
+            .check_oauth2_token_exchange(&client_authz.as_deref().into(), &token_req, ct)
             .unwrap_err();
 
         assert!(access_token_response_3 == Oauth2Error::InvalidGrant);
@@ -4731,7 +7682,7 @@ mod tests {
         .into();
 
         let access_token_response_2 = idms_prox_write
-            .check_oauth2_token_exchange(client_authz.as_deref(), &token_req, ct)
+            .check_oauth2_token_exchange(&client_authz.as_deref().into(), &token_req, ct)
             .expect("Unable to exchange for oauth2 token");
 
         // DO NOT COMMIT HERE - this is what forces the session issued_at
@@ -4755,7 +7706,7 @@ mod tests {
         .into();
 
         let _access_token_response_3 = idms_prox_write
-            .check_oauth2_token_exchange(client_authz.as_deref(), &token_req, ct)
+            .check_oauth2_token_exchange(&client_authz.as_deref().into(), &token_req, ct)
             .expect("Unable to exchange for oauth2 token");
 
         assert!(idms_prox_write.commit().is_ok());
@@ -4775,5 +7726,21 @@ mod tests {
         "#,
         );
         assert!(token_req.is_ok());
+
+        let token_req: Result<AccessTokenRequest, serde_json::Error> = serde_json::from_str(
+            r#"
+            {
+                "grant_type": "urn:ietf:params:oauth:grant-type:token-exchange",
+                "subject_token": "some_access_token",
+                "subject_token_type": "urn:ietf:params:oauth:token-type:access_token",
+                "audience": "downstream",
+                "scope": "read write"
+            }
+        "#,
+        );
+        assert!(matches!(
+            token_req.map(|req| req.grant_type),
+            Ok(GrantTypeReq::TokenExchange { scope: Some(scope), .. }) if scope.len() == 2
+        ));
     }
 }
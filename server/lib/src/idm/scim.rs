@@ -5,10 +5,18 @@ use base64urlsafedata::Base64UrlSafeData;
 use compact_jwt::{Jws, JwsSigner};
 use kanidm_proto::internal::ScimSyncToken;
 use kanidm_proto::scim_v1::*;
+use kanidm_proto::scim_v2::{
+    ScimV2Email, ScimV2Group, ScimV2ListResponse, ScimV2Meta, ScimV2Name, ScimV2PatchRequest,
+    ScimV2Reference, ScimV2Resource, ScimV2User, SCIM_V2_MAX_RESULTS,
+    SCIM_V2_MESSAGE_LIST_RESPONSE, SCIM_V2_SCHEMA_GROUP, SCIM_V2_SCHEMA_USER,
+};
 use kanidm_proto::v1::ApiTokenPurpose;
 use std::collections::{BTreeMap, BTreeSet};
+use time::OffsetDateTime;
 
 use crate::credential::totp::{Totp, TotpAlgo, TotpDigits};
+use crate::event::{CreateEvent, DeleteEvent};
+use crate::filter::{FC, FILTER_DEPTH_MAX};
 use crate::idm::server::{IdmServerProxyReadTransaction, IdmServerProxyWriteTransaction};
 use crate::prelude::*;
 use crate::value::ApiToken;
@@ -1482,8 +1490,842 @@ impl<'a> IdmServerProxyReadTransaction<'a> {
     }
 }
 
+// SCIM 2.0 provisioning.
+//
+// These map the standard Users and Groups resources of RFC 7643 onto persons and groups.
+// Unlike sync above, these are performed as the requesting identity, so access controls
+// apply to every read and write.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScimV2ResourceType {
+    User,
+    Group,
+}
+
+impl ScimV2ResourceType {
+    fn class(self) -> &'static PartialValue {
+        match self {
+            ScimV2ResourceType::User => &PVCLASS_PERSON,
+            ScimV2ResourceType::Group => &PVCLASS_GROUP,
+        }
+    }
+
+    /// The path of this resource type's endpoint, relative to the SCIM base url.
+    pub fn endpoint(self) -> &'static str {
+        match self {
+            ScimV2ResourceType::User => "Users",
+            ScimV2ResourceType::Group => "Groups",
+        }
+    }
+
+    /// Map a SCIM attribute path to the attribute it is stored in. Paths are case
+    /// insensitive.
+    fn attr(self, path: &str) -> Option<&'static str> {
+        let path = path.to_lowercase();
+        match (self, path.as_str()) {
+            (_, "id") => Some("uuid"),
+            (ScimV2ResourceType::User, "username") => Some("name"),
+            (ScimV2ResourceType::User, "displayname") => Some("displayname"),
+            (ScimV2ResourceType::User, "name") | (ScimV2ResourceType::User, "name.formatted") => {
+                Some("legalname")
+            }
+            (ScimV2ResourceType::User, "emails") | (ScimV2ResourceType::User, "emails.value") => {
+                Some("mail")
+            }
+            (ScimV2ResourceType::User, "groups") | (ScimV2ResourceType::User, "groups.value") => {
+                Some("memberof")
+            }
+            (ScimV2ResourceType::Group, "displayname") => Some("name"),
+            (ScimV2ResourceType::Group, "members")
+            | (ScimV2ResourceType::Group, "members.value") => Some("member"),
+            _ => None,
+        }
+    }
+}
+
+pub struct ScimV2SearchEvent {
+    pub ident: Identity,
+    pub resource: ScimV2ResourceType,
+    pub filter: Option<String>,
+    /// The 1-based index of the first result to return.
+    pub start_index: Option<usize>,
+    pub count: Option<usize>,
+}
+
+pub struct ScimV2GetEvent {
+    pub ident: Identity,
+    pub resource: ScimV2ResourceType,
+    pub target: Uuid,
+}
+
+pub struct ScimV2CreateEvent {
+    pub ident: Identity,
+    pub resource: ScimV2Resource,
+}
+
+pub struct ScimV2ReplaceEvent {
+    pub ident: Identity,
+    pub target: Uuid,
+    pub resource: ScimV2Resource,
+}
+
+pub struct ScimV2PatchEvent {
+    pub ident: Identity,
+    pub resource: ScimV2ResourceType,
+    pub target: Uuid,
+    pub request: ScimV2PatchRequest,
+}
+
+pub struct ScimV2DeleteEvent {
+    pub ident: Identity,
+    pub resource: ScimV2ResourceType,
+    pub target: Uuid,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum ScimV2FilterToken {
+    Open,
+    Close,
+    Word(String),
+    Value(String),
+}
+
+fn scim_v2_filter_tokenise(input: &str) -> Result<Vec<ScimV2FilterToken>, OperationError> {
+    let invalid = |msg: &str| OperationError::ScimInvalidFilter(msg.to_string());
+
+    let mut tokens = Vec::new();
+    let mut chars = input.char_indices().peekable();
+
+    while let Some((start, c)) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {}
+            '(' => tokens.push(ScimV2FilterToken::Open),
+            ')' => tokens.push(ScimV2FilterToken::Close),
+            '"' => {
+                // Find the closing quote, then let serde handle any escapes.
+                let mut escaped = false;
+                let mut end = None;
+                for (i, c) in chars.by_ref() {
+                    match c {
+                        '\\' if !escaped => escaped = true,
+                        '"' if !escaped => {
+                            end = Some(i);
+                            break;
+                        }
+                        _ => escaped = false,
+                    }
+                }
+                let end = end.ok_or_else(|| invalid("unterminated string"))?;
+                let value: String = serde_json::from_str(&input[start..=end])
+                    .map_err(|_| invalid("invalid string"))?;
+                tokens.push(ScimV2FilterToken::Value(value));
+            }
+            _ => {
+                let mut end = start + c.len_utf8();
+                while let Some((i, c)) = chars.peek() {
+                    if c.is_whitespace() || *c == '(' || *c == ')' || *c == '"' {
+                        break;
+                    }
+                    end = i + c.len_utf8();
+                    chars.next();
+                }
+                tokens.push(ScimV2FilterToken::Word(input[start..end].to_string()));
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// Convert a SCIM value to the form it is stored as. This is used both to compare values in
+/// filters and to remove values.
+fn scim_v2_partial_value(attr: &str, value: &str) -> Result<PartialValue, OperationError> {
+    match attr {
+        "uuid" | "member" | "memberof" => {
+            let uuid = Uuid::parse_str(value).map_err(|_| {
+                OperationError::InvalidAttribute(format!("{} is not a valid id", value))
+            })?;
+            Ok(if attr == "uuid" {
+                PartialValue::Uuid(uuid)
+            } else {
+                PartialValue::Refer(uuid)
+            })
+        }
+        "name" => Ok(PartialValue::new_iname(value)),
+        "mail" => Ok(PartialValue::new_email_address_s(value)),
+        _ => Ok(PartialValue::new_utf8s(value)),
+    }
+}
+
+/// A recursive descent parser for the filters of RFC 7644 section 3.4.2.2. The `eq`, `ne`,
+/// `co` and `pr` operators are supported, combined with `and`, `or`, `not` and grouping.
+///
+/// As with other filters, the nesting depth and the number of elements are limited, since
+/// these filters are provided by the client.
+struct ScimV2FilterParser {
+    resource: ScimV2ResourceType,
+    tokens: Vec<ScimV2FilterToken>,
+    pos: usize,
+    elems: usize,
+}
+
+impl ScimV2FilterParser {
+    fn parse(
+        resource: ScimV2ResourceType,
+        input: &str,
+        elems: usize,
+    ) -> Result<FC<'static>, OperationError> {
+        let mut parser = ScimV2FilterParser {
+            resource,
+            tokens: scim_v2_filter_tokenise(input)?,
+            pos: 0,
+            elems,
+        };
+        let f = parser.expr(FILTER_DEPTH_MAX)?;
+        if parser.pos != parser.tokens.len() {
+            return Err(OperationError::ScimInvalidFilter(
+                "unexpected trailing input".to_string(),
+            ));
+        }
+        Ok(f)
+    }
+
+    fn next(&mut self) -> Option<ScimV2FilterToken> {
+        let t = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        t
+    }
+
+    fn next_is_word(&self, word: &str) -> bool {
+        matches!(self.tokens.get(self.pos), Some(ScimV2FilterToken::Word(w)) if w.eq_ignore_ascii_case(word))
+    }
+
+    fn expect_close(&mut self) -> Result<(), OperationError> {
+        match self.next() {
+            Some(ScimV2FilterToken::Close) => Ok(()),
+            _ => Err(OperationError::ScimInvalidFilter(
+                "expected closing parenthesis".to_string(),
+            )),
+        }
+    }
+
+    fn expr(&mut self, depth: usize) -> Result<FC<'static>, OperationError> {
+        let ndepth = depth.checked_sub(1).ok_or(OperationError::ResourceLimit)?;
+        let mut terms = vec![self.term(ndepth)?];
+        while self.next_is_word("or") {
+            self.pos += 1;
+            terms.push(self.term(ndepth)?);
+        }
+        Ok(if terms.len() == 1 {
+            terms.remove(0)
+        } else {
+            f_or(terms)
+        })
+    }
+
+    fn term(&mut self, depth: usize) -> Result<FC<'static>, OperationError> {
+        let mut factors = vec![self.factor(depth)?];
+        while self.next_is_word("and") {
+            self.pos += 1;
+            factors.push(self.factor(depth)?);
+        }
+        Ok(if factors.len() == 1 {
+            factors.remove(0)
+        } else {
+            f_and(factors)
+        })
+    }
+
+    fn factor(&mut self, depth: usize) -> Result<FC<'static>, OperationError> {
+        self.elems = self
+            .elems
+            .checked_sub(1)
+            .ok_or(OperationError::ResourceLimit)?;
+
+        if self.next_is_word("not") {
+            self.pos += 1;
+            match self.next() {
+                Some(ScimV2FilterToken::Open) => {}
+                _ => {
+                    return Err(OperationError::ScimInvalidFilter(
+                        "expected parenthesis after not".to_string(),
+                    ))
+                }
+            }
+            let inner = self.expr(depth)?;
+            self.expect_close()?;
+            return Ok(f_andnot(inner));
+        }
+
+        match self.next() {
+            Some(ScimV2FilterToken::Open) => {
+                let inner = self.expr(depth)?;
+                self.expect_close()?;
+                Ok(inner)
+            }
+            Some(ScimV2FilterToken::Word(path)) => self.comparison(&path),
+            _ => Err(OperationError::ScimInvalidFilter(
+                "expected an attribute".to_string(),
+            )),
+        }
+    }
+
+    fn comparison(&mut self, path: &str) -> Result<FC<'static>, OperationError> {
+        let attr = self.resource.attr(path).ok_or_else(|| {
+            OperationError::ScimInvalidFilter(format!("unsupported attribute {}", path))
+        })?;
+
+        let Some(ScimV2FilterToken::Word(op)) = self.next() else {
+            return Err(OperationError::ScimInvalidFilter(
+                "expected an operator".to_string(),
+            ));
+        };
+        let op = op.to_lowercase();
+
+        if op == "pr" {
+            return Ok(f_pres(attr));
+        }
+
+        let Some(ScimV2FilterToken::Value(value)) = self.next() else {
+            return Err(OperationError::ScimInvalidFilter(
+                "expected a string value".to_string(),
+            ));
+        };
+        let pv = scim_v2_partial_value(attr, &value)
+            .map_err(|_| OperationError::ScimInvalidFilter(format!("invalid value {}", value)))?;
+
+        match op.as_str() {
+            "eq" => Ok(f_eq(attr, pv)),
+            "ne" => Ok(f_andnot(f_eq(attr, pv))),
+            "co" if !matches!(attr, "uuid" | "member" | "memberof") => Ok(f_sub(attr, pv)),
+            _ => Err(OperationError::ScimInvalidFilter(format!(
+                "unsupported operator {} for {}",
+                op, path
+            ))),
+        }
+    }
+}
+
+/// Parse a PATCH path into the attribute it targets, and an optional value selected by a
+/// `[value eq "..."]` filter.
+fn scim_v2_patch_path(path: &str) -> Result<(&str, Option<String>), OperationError> {
+    let invalid = || OperationError::ScimInvalidPath(path.to_string());
+
+    let Some((attr, selector)) = path.split_once('[') else {
+        return Ok((path, None));
+    };
+
+    let selector = selector.strip_suffix(']').ok_or_else(invalid)?;
+    match scim_v2_filter_tokenise(selector)
+        .map_err(|_| invalid())?
+        .as_slice()
+    {
+        [ScimV2FilterToken::Word(sub), ScimV2FilterToken::Word(op), ScimV2FilterToken::Value(v)]
+            if sub.eq_ignore_ascii_case("value") && op.eq_ignore_ascii_case("eq") =>
+        {
+            Ok((attr, Some(v.clone())))
+        }
+        _ => Err(invalid()),
+    }
+}
+
+/// Extract the values of a PATCH operation. Values may be plain strings, or objects with a
+/// `value` (or for a name, `formatted`) member, and may be given singly or as an array.
+fn scim_v2_patch_strings(value: &serde_json::Value) -> Result<Vec<(String, bool)>, OperationError> {
+    let items: Vec<&serde_json::Value> = match value {
+        serde_json::Value::Array(items) => items.iter().collect(),
+        v => vec![v],
+    };
+
+    items
+        .into_iter()
+        .map(|item| match item {
+            serde_json::Value::String(s) => Ok((s.clone(), false)),
+            serde_json::Value::Object(o) => o
+                .get("value")
+                .or_else(|| o.get("formatted"))
+                .and_then(|v| v.as_str())
+                .map(|s| {
+                    let primary = o.get("primary").and_then(|p| p.as_bool()).unwrap_or(false);
+                    (s.to_string(), primary)
+                })
+                .ok_or_else(|| OperationError::InvalidAttribute("missing value".to_string())),
+            _ => Err(OperationError::InvalidAttribute(
+                "unsupported value".to_string(),
+            )),
+        })
+        .collect()
+}
+
+fn scim_v2_value(attr: &str, value: &str, primary: bool) -> Result<Value, OperationError> {
+    let invalid = || OperationError::InvalidAttribute(format!("invalid value for {}", attr));
+    match attr {
+        "name" => Ok(Value::new_iname(value)),
+        "displayname" | "legalname" => Ok(Value::new_utf8s(value)),
+        "mail" if primary => Value::new_email_address_primary_s(value).ok_or_else(invalid),
+        "mail" => Value::new_email_address_s(value).ok_or_else(invalid),
+        "member" => Uuid::parse_str(value)
+            .map(Value::Refer)
+            .map_err(|_| invalid()),
+        _ => Err(OperationError::ScimInvalidPath(format!(
+            "{} is read only",
+            attr
+        ))),
+    }
+}
+
+fn scim_v2_emails(emails: &[ScimV2Email]) -> Result<Vec<Value>, OperationError> {
+    emails
+        .iter()
+        .map(|e| scim_v2_value("mail", &e.value, e.primary))
+        .collect()
+}
+
+fn scim_v2_active_mods(active: bool, ct: Duration) -> Vec<Modify> {
+    let mut mods = vec![Modify::Purged(AttrString::from("account_expire"))];
+    if !active {
+        mods.push(Modify::Present(
+            AttrString::from("account_expire"),
+            Value::new_datetime_epoch(ct),
+        ));
+    }
+    mods
+}
+
+/// The attributes a PUT replaces, and their new values.
+fn scim_v2_replace_values(
+    resource: &ScimV2Resource,
+) -> Result<Vec<(&'static str, Vec<Value>)>, OperationError> {
+    match resource {
+        ScimV2Resource::User(user) => {
+            let legalname = user.name.as_ref().and_then(|n| n.formatted.as_deref());
+            let displayname = user
+                .display_name
+                .as_deref()
+                .or(legalname)
+                .unwrap_or(user.user_name.as_str());
+
+            Ok(vec![
+                ("name", vec![Value::new_iname(&user.user_name)]),
+                ("displayname", vec![Value::new_utf8s(displayname)]),
+                (
+                    "legalname",
+                    legalname.map(Value::new_utf8s).into_iter().collect(),
+                ),
+                ("mail", scim_v2_emails(&user.emails)?),
+            ])
+        }
+        ScimV2Resource::Group(group) => Ok(vec![
+            ("name", vec![Value::new_iname(&group.display_name)]),
+            (
+                "member",
+                group
+                    .members
+                    .iter()
+                    .map(|m| Value::Refer(m.value))
+                    .collect(),
+            ),
+        ]),
+    }
+}
+
+fn scim_v2_patch_mods(
+    resource: ScimV2ResourceType,
+    op: &str,
+    path: Option<&str>,
+    value: Option<&serde_json::Value>,
+    ct: Duration,
+) -> Result<Vec<Modify>, OperationError> {
+    let op = op.to_lowercase();
+    if !matches!(op.as_str(), "add" | "replace" | "remove") {
+        return Err(OperationError::InvalidAttribute(format!(
+            "unsupported patch operation {}",
+            op
+        )));
+    }
+
+    let Some(path) = path else {
+        // Without a path the value is an object of attributes to change.
+        return match value {
+            Some(serde_json::Value::Object(attrs)) if op != "remove" => {
+                let mut mods = Vec::new();
+                for (path, value) in attrs {
+                    mods.extend(scim_v2_patch_mods(
+                        resource,
+                        &op,
+                        Some(path),
+                        Some(value),
+                        ct,
+                    )?);
+                }
+                Ok(mods)
+            }
+            _ => Err(OperationError::ScimInvalidPath(
+                "a path is required".to_string(),
+            )),
+        };
+    };
+
+    let (path, selected) = scim_v2_patch_path(path)?;
+
+    if resource == ScimV2ResourceType::User && path.eq_ignore_ascii_case("active") {
+        let active = match (op.as_str(), value) {
+            ("remove", _) => true,
+            (_, Some(serde_json::Value::Bool(b))) => *b,
+            // Some clients send booleans as strings.
+            (_, Some(serde_json::Value::String(s))) => s.to_lowercase().parse().map_err(|_| {
+                OperationError::InvalidAttribute("invalid value for active".to_string())
+            })?,
+            _ => {
+                return Err(OperationError::InvalidAttribute(
+                    "invalid value for active".to_string(),
+                ))
+            }
+        };
+        return Ok(scim_v2_active_mods(active, ct));
+    }
+
+    let attr = resource
+        .attr(path)
+        .ok_or_else(|| OperationError::ScimInvalidPath(path.to_string()))?;
+
+    if op == "remove" {
+        let removed: Vec<String> = match (selected, value) {
+            (Some(s), _) => vec![s],
+            (None, Some(v)) => scim_v2_patch_strings(v)?
+                .into_iter()
+                .map(|(s, _)| s)
+                .collect(),
+            (None, None) => return Ok(vec![Modify::Purged(AttrString::from(attr))]),
+        };
+        return removed
+            .iter()
+            .map(|s| scim_v2_partial_value(attr, s).map(|pv| Modify::Removed(attr.into(), pv)))
+            .collect();
+    }
+
+    let value =
+        value.ok_or_else(|| OperationError::InvalidAttribute("missing value".to_string()))?;
+    let values = scim_v2_patch_strings(value)?
+        .iter()
+        .map(|(s, primary)| scim_v2_value(attr, s, *primary))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // Adding to a single value attribute replaces it.
+    let single_value = matches!(attr, "name" | "displayname" | "legalname");
+    let mut mods = Vec::with_capacity(values.len() + 1);
+    if op == "replace" || single_value {
+        mods.push(Modify::Purged(AttrString::from(attr)));
+    }
+    mods.extend(
+        values
+            .into_iter()
+            .map(|v| Modify::Present(AttrString::from(attr), v)),
+    );
+    Ok(mods)
+}
+
+fn scim_v2_refs<'e>(
+    e: &'e Entry<EntryReduced, EntryCommitted>,
+    attr: &str,
+    endpoint: &'e str,
+) -> impl Iterator<Item = ScimV2Reference> + 'e {
+    e.get_ava_refer(attr)
+        .into_iter()
+        .flatten()
+        .map(move |uuid| ScimV2Reference {
+            value: *uuid,
+            display: None,
+            reference: Some(format!("{}/{}", endpoint, uuid)),
+        })
+}
+
+fn scim_v2_entry_to_resource(
+    resource: ScimV2ResourceType,
+    e: &Entry<EntryReduced, EntryCommitted>,
+    ct: Duration,
+) -> ScimV2Resource {
+    let meta = Some(ScimV2Meta {
+        resource_type: match resource {
+            ScimV2ResourceType::User => "User".to_string(),
+            ScimV2ResourceType::Group => "Group".to_string(),
+        },
+        location: None,
+    });
+
+    match resource {
+        ScimV2ResourceType::User => {
+            let now = OffsetDateTime::UNIX_EPOCH + ct;
+            let expired = e
+                .get_ava_single_datetime("account_expire")
+                .map(|odt| odt <= now)
+                .unwrap_or(false);
+            let not_yet_valid = e
+                .get_ava_single_datetime("account_valid_from")
+                .map(|odt| odt > now)
+                .unwrap_or(false);
+
+            let primary = e.get_ava_mail_primary("mail");
+            let emails = e
+                .get_ava_iter_mail("mail")
+                .into_iter()
+                .flatten()
+                .map(|m| ScimV2Email {
+                    value: m.to_string(),
+                    primary: Some(m) == primary,
+                    kind: None,
+                })
+                .collect();
+
+            ScimV2Resource::User(ScimV2User {
+                schemas: vec![SCIM_V2_SCHEMA_USER.to_string()],
+                id: Some(e.get_uuid()),
+                external_id: None,
+                user_name: e
+                    .get_ava_single_iname("name")
+                    .unwrap_or_default()
+                    .to_string(),
+                display_name: e.get_ava_single_utf8("displayname").map(str::to_string),
+                name: e.get_ava_single_utf8("legalname").map(|n| ScimV2Name {
+                    formatted: Some(n.to_string()),
+                    ..Default::default()
+                }),
+                emails,
+                active: Some(!expired && !not_yet_valid),
+                groups: scim_v2_refs(e, "memberof", "Groups").collect(),
+                meta,
+            })
+        }
+        ScimV2ResourceType::Group => ScimV2Resource::Group(ScimV2Group {
+            schemas: vec![SCIM_V2_SCHEMA_GROUP.to_string()],
+            id: Some(e.get_uuid()),
+            external_id: None,
+            display_name: e
+                .get_ava_single_iname("name")
+                .unwrap_or_default()
+                .to_string(),
+            // Members may be users or other groups, but we only know which by looking
+            // them up, so refer to them as users since that's by far the common case.
+            members: scim_v2_refs(e, "member", "Users").collect(),
+            meta,
+        }),
+    }
+}
+
+fn scim_v2_resource_type(resource: &ScimV2Resource) -> ScimV2ResourceType {
+    match resource {
+        ScimV2Resource::User(_) => ScimV2ResourceType::User,
+        ScimV2Resource::Group(_) => ScimV2ResourceType::Group,
+    }
+}
+
+fn scim_v2_target_filter(resource: ScimV2ResourceType, target: Uuid) -> Filter<FilterInvalid> {
+    filter!(f_and(vec![
+        f_eq("class", resource.class().clone()),
+        f_eq("uuid", PartialValue::Uuid(target))
+    ]))
+}
+
+fn scim_v2_get<'a, T: QueryServerTransaction<'a>>(
+    qs: &mut T,
+    ident: &Identity,
+    resource: ScimV2ResourceType,
+    target: Uuid,
+    ct: Duration,
+) -> Result<ScimV2Resource, OperationError> {
+    let filter = scim_v2_target_filter(resource, target);
+    let mut entries = qs.impersonate_search_ext(filter.clone(), filter, ident)?;
+    match entries.pop() {
+        Some(e) if entries.is_empty() => Ok(scim_v2_entry_to_resource(resource, &e, ct)),
+        _ => Err(OperationError::NoMatchingEntries),
+    }
+}
+
+impl<'a> IdmServerProxyWriteTransaction<'a> {
+    pub fn scim_v2_create(
+        &mut self,
+        ev: &ScimV2CreateEvent,
+    ) -> Result<ScimV2Resource, OperationError> {
+        let ct = self.qs_write.get_curtime();
+        let resource = scim_v2_resource_type(&ev.resource);
+        let mut entry = EntryInitNew::new();
+        entry.add_ava("class", Value::new_class("object"));
+        match &ev.resource {
+            ScimV2Resource::User(user) => {
+                entry.add_ava("class", Value::new_class("account"));
+                entry.add_ava("class", Value::new_class("person"));
+                if user.active == Some(false) {
+                    entry.add_ava("account_expire", Value::new_datetime_epoch(ct));
+                }
+            }
+            ScimV2Resource::Group(_) => {
+                entry.add_ava("class", Value::new_class("group"));
+            }
+        }
+        let values = scim_v2_replace_values(&ev.resource)?;
+        // The uuid is generated on create, so we find the new entry again by its name.
+        let name = values
+            .iter()
+            .find(|(attr, _)| *attr == "name")
+            .and_then(|(_, v)| v.first())
+            .and_then(|v| v.to_str())
+            .map(PartialValue::new_iname)
+            .ok_or(OperationError::InvalidEntryState)?;
+        for (attr, values) in values {
+            if !values.is_empty() {
+                entry.set_ava(attr, values);
+            }
+        }
+
+        let ce = CreateEvent {
+            ident: ev.ident.clone(),
+            entries: vec![entry],
+        };
+        self.qs_write.create(&ce)?;
+
+        let uuid = self
+            .qs_write
+            .internal_search(filter!(f_and(vec![
+                f_eq("class", resource.class().clone()),
+                f_eq("name", name)
+            ])))?
+            .pop()
+            .map(|e| e.get_uuid())
+            .ok_or(OperationError::NoMatchingEntries)?;
+
+        scim_v2_get(&mut self.qs_write, &ev.ident, resource, uuid, ct)
+    }
+
+    pub fn scim_v2_replace(
+        &mut self,
+        ev: &ScimV2ReplaceEvent,
+    ) -> Result<ScimV2Resource, OperationError> {
+        let ct = self.qs_write.get_curtime();
+        let resource = scim_v2_resource_type(&ev.resource);
+
+        let mut mods = Vec::new();
+        for (attr, values) in scim_v2_replace_values(&ev.resource)? {
+            mods.push(Modify::Purged(AttrString::from(attr)));
+            mods.extend(
+                values
+                    .into_iter()
+                    .map(|v| Modify::Present(AttrString::from(attr), v)),
+            );
+        }
+        if let ScimV2Resource::User(ScimV2User {
+            active: Some(active),
+            ..
+        }) = &ev.resource
+        {
+            mods.extend(scim_v2_active_mods(*active, ct));
+        }
+
+        self.scim_v2_modify(&ev.ident, resource, ev.target, mods, ct)
+    }
+
+    pub fn scim_v2_patch(
+        &mut self,
+        ev: &ScimV2PatchEvent,
+    ) -> Result<ScimV2Resource, OperationError> {
+        let ct = self.qs_write.get_curtime();
+
+        let mut mods = Vec::new();
+        for op in ev.request.operations.iter() {
+            mods.extend(scim_v2_patch_mods(
+                ev.resource,
+                &op.op,
+                op.path.as_deref(),
+                op.value.as_ref(),
+                ct,
+            )?);
+        }
+
+        self.scim_v2_modify(&ev.ident, ev.resource, ev.target, mods, ct)
+    }
+
+    fn scim_v2_modify(
+        &mut self,
+        ident: &Identity,
+        resource: ScimV2ResourceType,
+        target: Uuid,
+        mods: Vec<Modify>,
+        ct: Duration,
+    ) -> Result<ScimV2Resource, OperationError> {
+        if !mods.is_empty() {
+            let filter = scim_v2_target_filter(resource, target);
+            let modlist = ModifyList::new_list(mods);
+            self.qs_write
+                .impersonate_modify(&filter, &filter, &modlist, ident)?;
+        }
+
+        scim_v2_get(&mut self.qs_write, ident, resource, target, ct)
+    }
+
+    pub fn scim_v2_delete(&mut self, ev: &ScimV2DeleteEvent) -> Result<(), OperationError> {
+        let filter = scim_v2_target_filter(ev.resource, ev.target);
+        let de = DeleteEvent::from_parts(ev.ident.clone(), &filter, &mut self.qs_write)?;
+        self.qs_write.delete(&de)
+    }
+}
+
+impl<'a> IdmServerProxyReadTransaction<'a> {
+    pub fn scim_v2_search(
+        &mut self,
+        ev: &ScimV2SearchEvent,
+        ct: Duration,
+    ) -> Result<ScimV2ListResponse<ScimV2Resource>, OperationError> {
+        let mut conditions = vec![f_eq("class", ev.resource.class().clone())];
+        if let Some(filter) = ev.filter.as_deref() {
+            conditions.push(ScimV2FilterParser::parse(
+                ev.resource,
+                filter,
+                ev.ident.limits.filter_max_elements,
+            )?);
+        }
+        let filter = filter!(f_and(conditions));
+
+        let mut entries = self
+            .qs_read
+            .impersonate_search_ext(filter.clone(), filter, &ev.ident)?;
+        // Sort so that pages are stable between requests.
+        entries.sort_unstable_by_key(|e| e.get_uuid());
+
+        let total_results = entries.len();
+        let start_index = ev.start_index.unwrap_or(1).max(1);
+        let count = ev
+            .count
+            .unwrap_or(SCIM_V2_MAX_RESULTS)
+            .min(SCIM_V2_MAX_RESULTS);
+
+        let resources: Vec<_> = entries
+            .iter()
+            .skip(start_index - 1)
+            .take(count)
+            .map(|e| scim_v2_entry_to_resource(ev.resource, e, ct))
+            .collect();
+
+        Ok(ScimV2ListResponse {
+            schemas: vec![SCIM_V2_MESSAGE_LIST_RESPONSE.to_string()],
+            total_results,
+            start_index,
+            items_per_page: resources.len(),
+            resources,
+        })
+    }
+
+    pub fn scim_v2_get(
+        &mut self,
+        ev: &ScimV2GetEvent,
+        ct: Duration,
+    ) -> Result<ScimV2Resource, OperationError> {
+        scim_v2_get(&mut self.qs_read, &ev.ident, ev.resource, ev.target, ct)
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use crate::be::Limits;
+    use crate::filter::FILTER_DEPTH_MAX;
     use crate::idm::server::{IdmServerProxyWriteTransaction, IdmServerTransaction};
     use crate::prelude::*;
     use base64urlsafedata::Base64UrlSafeData;
@@ -1493,9 +2335,15 @@ mod tests {
     use std::sync::Arc;
     use std::time::Duration;
 
+    use kanidm_proto::scim_v2::{
+        ScimV2Group, ScimV2PatchOp, ScimV2PatchRequest, ScimV2Reference, ScimV2Resource, ScimV2User,
+    };
+
     use super::{
         GenerateScimSyncTokenEvent, ScimSyncFinaliseEvent, ScimSyncTerminateEvent, ScimSyncToken,
-        ScimSyncUpdateEvent,
+        ScimSyncUpdateEvent, ScimV2CreateEvent, ScimV2DeleteEvent, ScimV2FilterParser,
+        ScimV2GetEvent, ScimV2PatchEvent, ScimV2ReplaceEvent, ScimV2ResourceType,
+        ScimV2SearchEvent,
     };
 
     const TEST_CURRENT_TIME: u64 = 6000;
@@ -3030,6 +3878,320 @@ mod tests {
         assert!(idms_prox_write.commit().is_ok());
     }
 
+    #[test]
+    fn test_scim_v2_filter_parse() {
+        let max_elements = Limits::default().filter_max_elements;
+        let parse = |f: &str| {
+            ScimV2FilterParser::parse(ScimV2ResourceType::User, f, max_elements).map(Filter::new)
+        };
+
+        assert_eq!(
+            parse(r#"userName eq "Bjensen""#),
+            Ok(Filter::new(f_eq(
+                "name",
+                PartialValue::new_iname("bjensen")
+            )))
+        );
+        assert_eq!(
+            parse(r#"emails pr and not (displayName co "Jensen" or name.formatted ne "B J")"#),
+            Ok(Filter::new(f_and(vec![
+                f_pres("mail"),
+                f_andnot(f_or(vec![
+                    f_sub("displayname", PartialValue::new_utf8s("Jensen")),
+                    f_andnot(f_eq("legalname", PartialValue::new_utf8s("B J"))),
+                ]))
+            ])))
+        );
+        assert_eq!(
+            parse(r#"(id eq "5d3f8f30-7a7e-4f7e-9d4e-6b6f1c0b0a01")"#),
+            Ok(Filter::new(f_eq(
+                "uuid",
+                PartialValue::Uuid(uuid::uuid!("5d3f8f30-7a7e-4f7e-9d4e-6b6f1c0b0a01"))
+            )))
+        );
+        assert_eq!(
+            parse(r#"displayName eq "quote \" here""#),
+            Ok(Filter::new(f_eq(
+                "displayname",
+                PartialValue::new_utf8s("quote \" here")
+            )))
+        );
+
+        // Unsupported operators, attributes and malformed filters are rejected.
+        assert!(matches!(
+            parse(r#"userName sw "bj""#),
+            Err(OperationError::ScimInvalidFilter(_))
+        ));
+        assert!(matches!(
+            parse(r#"title eq "Tour Guide""#),
+            Err(OperationError::ScimInvalidFilter(_))
+        ));
+        assert!(matches!(
+            parse(r#"groups co "abc""#),
+            Err(OperationError::ScimInvalidFilter(_))
+        ));
+        assert!(matches!(
+            parse(r#"(userName eq "bjensen""#),
+            Err(OperationError::ScimInvalidFilter(_))
+        ));
+        assert!(matches!(
+            parse(r#"userName eq "bjensen" extra"#),
+            Err(OperationError::ScimInvalidFilter(_))
+        ));
+        assert!(matches!(
+            parse(r#"userName eq "bjensen"#),
+            Err(OperationError::ScimInvalidFilter(_))
+        ));
+
+        // Filters are limited in depth and size, like any other filter.
+        let deep = format!(
+            "{}userName eq \"bjensen\"{}",
+            "(".repeat(FILTER_DEPTH_MAX),
+            ")".repeat(FILTER_DEPTH_MAX)
+        );
+        assert_eq!(parse(&deep), Err(OperationError::ResourceLimit));
+        let deep = format!(
+            "{}userName eq \"bjensen\"{}",
+            "(".repeat(FILTER_DEPTH_MAX - 2),
+            ")".repeat(FILTER_DEPTH_MAX - 2)
+        );
+        assert!(parse(&deep).is_ok());
+        assert_eq!(
+            parse(&"not (".repeat(100_000)),
+            Err(OperationError::ResourceLimit)
+        );
+
+        let wide = vec![r#"userName eq "bjensen""#; max_elements + 1].join(" or ");
+        assert_eq!(parse(&wide), Err(OperationError::ResourceLimit));
+        let wide = vec![r#"userName eq "bjensen""#; max_elements].join(" or ");
+        assert!(parse(&wide).is_ok());
+    }
+
+    #[idm_test]
+    async fn test_idm_scim_v2_users_and_groups(
+        idms: &IdmServer,
+        _idms_delayed: &mut IdmServerDelayed,
+    ) {
+        let ct = Duration::from_secs(TEST_CURRENT_TIME);
+
+        let mut idms_prox_write = idms.proxy_write(ct).await;
+        let admin = idms_prox_write
+            .qs_write
+            .internal_search_uuid(UUID_IDM_ADMIN)
+            .expect("failed to find idm_admin");
+        let ident = Identity::from_impersonate_entry_readwrite(admin);
+
+        let user: ScimV2User = serde_json::from_str(
+            r#"{
+                "schemas": ["urn:ietf:params:scim:schemas:core:2.0:User"],
+                "externalId": "701984",
+                "userName": "bjensen",
+                "name": { "formatted": "Barbara Jensen" },
+                "emails": [{ "value": "bjensen@example.com", "primary": true }],
+                "active": true
+            }"#,
+        )
+        .expect("failed to parse user");
+
+        let ScimV2Resource::User(user) = idms_prox_write
+            .scim_v2_create(&ScimV2CreateEvent {
+                ident: ident.clone(),
+                resource: ScimV2Resource::User(user),
+            })
+            .expect("failed to create user")
+        else {
+            panic!("expected a user");
+        };
+        let user_uuid = user.id.expect("user has no id");
+        assert_eq!(user.user_name, "bjensen");
+        // Without a display name, the formatted name is used.
+        assert_eq!(user.display_name.as_deref(), Some("Barbara Jensen"));
+        assert_eq!(user.emails.len(), 1);
+        assert!(user.emails[0].primary);
+        assert_eq!(user.active, Some(true));
+
+        let group = ScimV2Group {
+            display_name: "sales".to_string(),
+            members: vec![ScimV2Reference {
+                value: user_uuid,
+                display: None,
+                reference: None,
+            }],
+            ..Default::default()
+        };
+        let ScimV2Resource::Group(group) = idms_prox_write
+            .scim_v2_create(&ScimV2CreateEvent {
+                ident: ident.clone(),
+                resource: ScimV2Resource::Group(group),
+            })
+            .expect("failed to create group")
+        else {
+            panic!("expected a group");
+        };
+        let group_uuid = group.id.expect("group has no id");
+        assert_eq!(group.members.len(), 1);
+
+        // Deactivate the user and change their display name.
+        let request: ScimV2PatchRequest = serde_json::from_str(
+            r#"{
+                "schemas": ["urn:ietf:params:scim:api:messages:2.0:PatchOp"],
+                "Operations": [
+                    { "op": "Replace", "path": "active", "value": "False" },
+                    { "op": "replace", "value": { "displayName": "Babs" } },
+                    { "op": "add", "path": "emails", "value": [{ "value": "babs@example.com" }] }
+                ]
+            }"#,
+        )
+        .expect("failed to parse patch");
+        let ScimV2Resource::User(user) = idms_prox_write
+            .scim_v2_patch(&ScimV2PatchEvent {
+                ident: ident.clone(),
+                resource: ScimV2ResourceType::User,
+                target: user_uuid,
+                request,
+            })
+            .expect("failed to patch user")
+        else {
+            panic!("expected a user");
+        };
+        assert_eq!(user.display_name.as_deref(), Some("Babs"));
+        assert_eq!(user.active, Some(false));
+        assert_eq!(user.emails.len(), 2);
+        assert!(user.groups.iter().any(|g| g.value == group_uuid));
+
+        // Users and groups aren't interchangeable.
+        assert_eq!(
+            idms_prox_write.scim_v2_patch(&ScimV2PatchEvent {
+                ident: ident.clone(),
+                resource: ScimV2ResourceType::Group,
+                target: user_uuid,
+                request: ScimV2PatchRequest {
+                    schemas: Vec::new(),
+                    operations: vec![ScimV2PatchOp {
+                        op: "remove".to_string(),
+                        path: Some("members".to_string()),
+                        value: None,
+                    }],
+                },
+            }),
+            Err(OperationError::NoMatchingEntries)
+        );
+
+        let request = ScimV2PatchRequest {
+            schemas: Vec::new(),
+            operations: vec![ScimV2PatchOp {
+                op: "remove".to_string(),
+                path: Some(format!(r#"members[value eq "{}"]"#, user_uuid)),
+                value: None,
+            }],
+        };
+        let ScimV2Resource::Group(group) = idms_prox_write
+            .scim_v2_patch(&ScimV2PatchEvent {
+                ident: ident.clone(),
+                resource: ScimV2ResourceType::Group,
+                target: group_uuid,
+                request,
+            })
+            .expect("failed to patch group")
+        else {
+            panic!("expected a group");
+        };
+        assert!(group.members.is_empty());
+
+        // Replacing the user removes anything not given.
+        let ScimV2Resource::User(user) = idms_prox_write
+            .scim_v2_replace(&ScimV2ReplaceEvent {
+                ident: ident.clone(),
+                target: user_uuid,
+                resource: ScimV2Resource::User(ScimV2User {
+                    user_name: "bjensen".to_string(),
+                    display_name: Some("Barbara Jensen".to_string()),
+                    active: Some(true),
+                    ..Default::default()
+                }),
+            })
+            .expect("failed to replace user")
+        else {
+            panic!("expected a user");
+        };
+        assert!(user.emails.is_empty());
+        assert!(user.name.is_none());
+        assert_eq!(user.active, Some(true));
+
+        assert!(idms_prox_write.commit().is_ok());
+
+        let mut idms_prox_read = idms.proxy_read().await;
+        let list = idms_prox_read
+            .scim_v2_search(
+                &ScimV2SearchEvent {
+                    ident: ident.clone(),
+                    resource: ScimV2ResourceType::User,
+                    filter: Some(r#"userName eq "bjensen""#.to_string()),
+                    start_index: None,
+                    count: None,
+                },
+                ct,
+            )
+            .expect("failed to search users");
+        assert_eq!(list.total_results, 1);
+        assert_eq!(list.resources[0].id(), Some(user_uuid));
+
+        // Groups are never returned from the user endpoint, and pages are bounded.
+        let list = idms_prox_read
+            .scim_v2_search(
+                &ScimV2SearchEvent {
+                    ident: ident.clone(),
+                    resource: ScimV2ResourceType::User,
+                    filter: Some(r#"userName eq "sales""#.to_string()),
+                    start_index: None,
+                    count: None,
+                },
+                ct,
+            )
+            .expect("failed to search users");
+        assert_eq!(list.total_results, 0);
+
+        let list = idms_prox_read
+            .scim_v2_search(
+                &ScimV2SearchEvent {
+                    ident: ident.clone(),
+                    resource: ScimV2ResourceType::Group,
+                    filter: None,
+                    start_index: Some(2),
+                    count: Some(1),
+                },
+                ct,
+            )
+            .expect("failed to search groups");
+        assert!(list.total_results > 2);
+        assert_eq!(list.start_index, 2);
+        assert_eq!(list.items_per_page, 1);
+        drop(idms_prox_read);
+
+        let mut idms_prox_write = idms.proxy_write(ct).await;
+        assert!(idms_prox_write
+            .scim_v2_delete(&ScimV2DeleteEvent {
+                ident: ident.clone(),
+                resource: ScimV2ResourceType::User,
+                target: user_uuid,
+            })
+            .is_ok());
+        assert!(idms_prox_write.commit().is_ok());
+
+        let mut idms_prox_read = idms.proxy_read().await;
+        assert_eq!(
+            idms_prox_read.scim_v2_get(
+                &ScimV2GetEvent {
+                    ident,
+                    resource: ScimV2ResourceType::User,
+                    target: user_uuid,
+                },
+                ct,
+            ),
+            Err(OperationError::NoMatchingEntries)
+        );
+    }
+
     const TEST_SYNC_SCIM_IPA_1: &str = r#"
 {
   "from_state": "Refresh",
@@ -28,10 +28,11 @@ use webauthn_rs::prelude::{Webauthn, WebauthnBuilder};
 
 use super::event::ReadBackupCodeEvent;
 use super::ldap::{LdapBoundToken, LdapSession};
-use crate::credential::{softlock::CredSoftLock, Credential};
+use crate::credential::{password_history_contains, softlock::CredSoftLock, Credential, Password};
 use crate::idm::account::Account;
 use crate::idm::audit::AuditEvent;
 use crate::idm::authsession::AuthSession;
+use crate::idm::breachlist::BreachList;
 use crate::idm::credupdatesession::CredentialUpdateSessionMutex;
 use crate::idm::delayed::{
     AuthSessionRecord, BackupCodeRemoval, DelayedAction, PasswordUpgrade, UnixPasswordUpgrade,
@@ -46,13 +47,14 @@ use crate::idm::event::{
     UnixUserTokenEvent,
 };
 use crate::idm::oauth2::{
-    Oauth2ResourceServers, Oauth2ResourceServersReadTransaction,
-    Oauth2ResourceServersWriteTransaction,
+    Oauth2BackchannelLogout, Oauth2PushedAuthorisation, Oauth2ResourceServers,
+    Oauth2ResourceServersReadTransaction, Oauth2ResourceServersWriteTransaction,
 };
 use crate::idm::radius::RadiusAccount;
 use crate::idm::scim::SyncAccount;
 use crate::idm::serviceaccount::ServiceAccount;
 use crate::idm::unix::{UnixGroup, UnixUserAccount};
+use crate::idm::webhook::WebhookDelivery;
 use crate::idm::AuthState;
 use crate::prelude::*;
 use crate::utils::{password_from_random, readable_password_from_random, uuid_from_duration, Sid};
@@ -77,17 +79,28 @@ pub struct IdmServer {
     session_ticket: Semaphore,
     sessions: BptreeMap<Uuid, AuthSessionMutex>,
     softlocks: HashMap<Uuid, CredSoftLockMutex>,
+    /// Failed identity verifications for each (caller, target) pair.
+    id_verification_softlocks: HashMap<(Uuid, Uuid), CredSoftLock>,
     /// A set of in progress credential registrations
     cred_update_sessions: BptreeMap<Uuid, CredentialUpdateSessionMutex>,
+    /// Oauth2 authorisation requests pushed by clients, that have not been used yet.
+    oauth2_pushed_requests: BptreeMap<String, Oauth2PushedAuthorisation>,
     /// Reference to the query server.
     qs: QueryServer,
     /// The configured crypto policy for the IDM server. Later this could be transactional and loaded from the db similar to access. But today it's just to allow dynamic pbkdf2rounds
     crypto_policy: CryptoPolicy,
     async_tx: Sender<DelayedAction>,
     audit_tx: Sender<AuditEvent>,
+    webhook_tx: Sender<WebhookDelivery>,
+    backchannel_tx: Sender<Oauth2BackchannelLogout>,
+    /// Whether a mail relay is configured, so that messages may be queued for delivery.
+    mail_enabled: bool,
+    mail_tx: Sender<()>,
     /// [Webauthn] verifier/config
     webauthn: Webauthn,
     pw_badlist_cache: Arc<CowCell<HashSet<String>>>,
+    /// An optional offline list of breached password hashes.
+    breach_list: Option<BreachList>,
     oauth2rs: Arc<Oauth2ResourceServers>,
     domain_keys: Arc<CowCell<DomainKeys>>,
 }
@@ -114,6 +127,7 @@ pub struct IdmServerCredUpdateTransaction<'a> {
     // sid: Sid,
     pub(crate) webauthn: &'a Webauthn,
     pub(crate) pw_badlist_cache: CowCellReadTxn<HashSet<String>>,
+    pub(crate) breach_list: Option<&'a BreachList>,
     pub(crate) cred_update_sessions: BptreeMapReadTxn<'a, Uuid, CredentialUpdateSessionMutex>,
     pub(crate) domain_keys: CowCellReadTxn<DomainKeys>,
     pub(crate) crypto_policy: &'a CryptoPolicy,
@@ -124,6 +138,7 @@ pub struct IdmServerProxyReadTransaction<'a> {
     pub qs_read: QueryServerReadTransaction<'a>,
     pub(crate) domain_keys: CowCellReadTxn<DomainKeys>,
     pub(crate) oauth2rs: Oauth2ResourceServersReadTransaction,
+    pub(crate) oauth2_pushed_requests: &'a BptreeMap<String, Oauth2PushedAuthorisation>,
 }
 
 pub struct IdmServerProxyWriteTransaction<'a> {
@@ -132,12 +147,24 @@ pub struct IdmServerProxyWriteTransaction<'a> {
     pub qs_write: QueryServerWriteTransaction<'a>,
     /// Associate to an event origin ID, which has a TS and a UUID instead
     pub(crate) cred_update_sessions: BptreeMapWriteTxn<'a, Uuid, CredentialUpdateSessionMutex>,
+    /// These are committed as they change rather than with this transaction, so that a
+    /// failed identity verification is counted even when the transaction is aborted.
+    pub(crate) id_verification_softlocks: &'a HashMap<(Uuid, Uuid), CredSoftLock>,
     pub(crate) sid: Sid,
     crypto_policy: &'a CryptoPolicy,
     webauthn: &'a Webauthn,
     pw_badlist_cache: CowCellWriteTxn<'a, HashSet<String>>,
+    breach_list: Option<&'a BreachList>,
     pub(crate) domain_keys: CowCellWriteTxn<'a, DomainKeys>,
     pub(crate) oauth2rs: Oauth2ResourceServersWriteTransaction<'a>,
+    pub(crate) audit_tx: Sender<AuditEvent>,
+    /// Audit events that are sent once this transaction commits.
+    pub(crate) audit_pending: Vec<AuditEvent>,
+    backchannel_tx: Sender<Oauth2BackchannelLogout>,
+    pub(crate) mail_enabled: bool,
+    mail_tx: Sender<()>,
+    /// Set when a message is queued, to wake the mail worker once this transaction commits.
+    pub(crate) mail_pending: bool,
 }
 
 pub struct IdmServerDelayed {
@@ -148,16 +175,43 @@ pub struct IdmServerAudit {
     pub(crate) audit_rx: Receiver<AuditEvent>,
 }
 
+pub struct IdmServerWebhook {
+    pub(crate) webhook_rx: Receiver<WebhookDelivery>,
+}
+
+pub struct IdmServerBackchannel {
+    pub(crate) backchannel_rx: Receiver<Oauth2BackchannelLogout>,
+}
+
+pub struct IdmServerMail {
+    pub(crate) mail_rx: Receiver<()>,
+}
+
 impl IdmServer {
     pub async fn new(
         qs: QueryServer,
         origin: &str,
-    ) -> Result<(IdmServer, IdmServerDelayed, IdmServerAudit), OperationError> {
+        breach_list: Option<BreachList>,
+        mail_enabled: bool,
+    ) -> Result<
+        (
+            IdmServer,
+            IdmServerDelayed,
+            IdmServerAudit,
+            IdmServerWebhook,
+            IdmServerBackchannel,
+            IdmServerMail,
+        ),
+        OperationError,
+    > {
         // This is calculated back from:
         //  100 password auths / thread -> 0.010 sec per op
         let crypto_policy = CryptoPolicy::time_target(Duration::from_millis(10));
         let (async_tx, async_rx) = unbounded();
         let (audit_tx, audit_rx) = unbounded();
+        let (webhook_tx, webhook_rx) = unbounded();
+        let (backchannel_tx, backchannel_rx) = unbounded();
+        let (mail_tx, mail_rx) = unbounded();
 
         // Get the domain name, as the relying party id.
         let (
@@ -247,18 +301,28 @@ impl IdmServer {
                 session_ticket: Semaphore::new(1),
                 sessions: BptreeMap::new(),
                 softlocks: HashMap::new(),
+                id_verification_softlocks: HashMap::new(),
                 cred_update_sessions: BptreeMap::new(),
+                oauth2_pushed_requests: BptreeMap::new(),
                 qs,
                 crypto_policy,
                 async_tx,
                 audit_tx,
+                webhook_tx,
+                backchannel_tx,
+                mail_enabled,
+                mail_tx,
                 webauthn,
                 pw_badlist_cache: Arc::new(CowCell::new(pw_badlist_set)),
+                breach_list,
                 domain_keys,
                 oauth2rs: Arc::new(oauth2rs),
             },
             IdmServerDelayed { async_rx },
             IdmServerAudit { audit_rx },
+            IdmServerWebhook { webhook_rx },
+            IdmServerBackchannel { backchannel_rx },
+            IdmServerMail { mail_rx },
         ))
     }
 
@@ -295,13 +359,15 @@ impl IdmServer {
             qs_read: self.qs.read().await,
             domain_keys: self.domain_keys.read(),
             oauth2rs: self.oauth2rs.read(),
+            oauth2_pushed_requests: &self.oauth2_pushed_requests,
             // async_tx: self.async_tx.clone(),
         }
     }
 
     #[instrument(level = "debug", skip_all)]
     pub async fn proxy_write(&self, ts: Duration) -> IdmServerProxyWriteTransaction<'_> {
-        let qs_write = self.qs.write(ts).await;
+        let mut qs_write = self.qs.write(ts).await;
+        qs_write.webhook_tx = Some(self.webhook_tx.clone());
 
         let mut sid = [0; 4];
         let mut rng = StdRng::from_entropy();
@@ -309,13 +375,21 @@ impl IdmServer {
 
         IdmServerProxyWriteTransaction {
             cred_update_sessions: self.cred_update_sessions.write(),
+            id_verification_softlocks: &self.id_verification_softlocks,
             qs_write,
             sid,
             crypto_policy: &self.crypto_policy,
             webauthn: &self.webauthn,
             pw_badlist_cache: self.pw_badlist_cache.write(),
+            breach_list: self.breach_list.as_ref(),
             domain_keys: self.domain_keys.write(),
             oauth2rs: self.oauth2rs.write(),
+            audit_tx: self.audit_tx.clone(),
+            audit_pending: Vec::new(),
+            backchannel_tx: self.backchannel_tx.clone(),
+            mail_enabled: self.mail_enabled,
+            mail_tx: self.mail_tx.clone(),
+            mail_pending: false,
         }
     }
 
@@ -325,6 +399,7 @@ impl IdmServer {
             // sid: Sid,
             webauthn: &self.webauthn,
             pw_badlist_cache: self.pw_badlist_cache.read(),
+            breach_list: self.breach_list.as_ref(),
             cred_update_sessions: self.cred_update_sessions.read(),
             domain_keys: self.domain_keys.read(),
             crypto_policy: &self.crypto_policy,
@@ -366,6 +441,24 @@ impl IdmServerAudit {
     }
 }
 
+impl IdmServerWebhook {
+    pub fn webhook_rx(&mut self) -> &mut Receiver<WebhookDelivery> {
+        &mut self.webhook_rx
+    }
+}
+
+impl IdmServerBackchannel {
+    pub fn backchannel_rx(&mut self) -> &mut Receiver<Oauth2BackchannelLogout> {
+        &mut self.backchannel_rx
+    }
+}
+
+impl IdmServerMail {
+    pub fn mail_rx(&mut self) -> &mut Receiver<()> {
+        &mut self.mail_rx
+    }
+}
+
 impl IdmServerDelayed {
     #[cfg(test)]
     pub(crate) fn check_is_empty_or_panic(&mut self) {
@@ -620,15 +713,19 @@ pub trait IdmServerTransaction<'a> {
         if ct >= Duration::from_secs(iat as u64) + GRACE_WINDOW {
             // We are past the grace window. Enforce session presence.
             // We enforce both sessions are present in case of inconsistency
-            // that may occur with replication.
-            let oauth2_session_valid = entry
-                .get_ava_as_oauth2session_map("oauth2_session")
+            // that may occur with replication. The parent of a session from a token exchange
+            // is the oauth2 session it was exchanged from.
+            let oauth2_sessions = entry.get_ava_as_oauth2session_map("oauth2_session");
+            let oauth2_session_valid = oauth2_sessions
                 .map(|map| map.get(&session_id).is_some())
                 .unwrap_or(false);
             let uat_session_valid = entry
                 .get_ava_as_session_map("user_auth_token_session")
                 .map(|map| map.get(&parent_session_id).is_some())
-                .unwrap_or(false);
+                .unwrap_or(false)
+                || oauth2_sessions
+                    .map(|map| map.get(&parent_session_id).is_some())
+                    .unwrap_or(false);
 
             if oauth2_session_valid && uat_session_valid {
                 security_info!("A valid session value exists for this token");
@@ -1518,6 +1615,7 @@ impl<'a> IdmServerProxyWriteTransaction<'a> {
         &mut self,
         cleartext: &str,
         related_inputs: &[&str],
+        password_history: &[Password],
     ) -> Result<(), OperationError> {
         // password strength and badlisting is always global, rather than per-pw-policy.
         // pw-policy as check on the account is about requirements for mfa for example.
@@ -1565,8 +1663,26 @@ impl<'a> IdmServerProxyWriteTransaction<'a> {
         // also, when pw_badlist_cache is read from DB, it is read as Value (iutf8 lowercase)
         if (*self.pw_badlist_cache).contains(&cleartext.to_lowercase()) {
             security_info!("Password found in badlist, rejecting");
-            Err(OperationError::PasswordQuality(vec![
+            return Err(OperationError::PasswordQuality(vec![
                 PasswordFeedback::BadListed,
+            ]));
+        }
+
+        // check the offline breach list, if one is configured.
+        if let Some(breach_list) = self.breach_list {
+            if breach_list.is_breached(cleartext) {
+                security_info!("Password found in breached password list, rejecting");
+                return Err(OperationError::PasswordQuality(vec![
+                    PasswordFeedback::BadListed,
+                ]));
+            }
+        }
+
+        // Finally, prevent the reuse of a previous password.
+        if password_history_contains(password_history.iter(), cleartext) {
+            security_info!("Password found in password history, rejecting");
+            Err(OperationError::PasswordQuality(vec![
+                PasswordFeedback::PreviouslyUsed,
             ]))
         } else {
             Ok(())
@@ -1644,11 +1760,15 @@ impl<'a> IdmServerProxyWriteTransaction<'a> {
         // Check the password quality.
         // Ask if tis all good - this step checks pwpolicy and such
 
-        self.check_password_quality(pce.cleartext.as_str(), account.related_inputs().as_slice())
-            .map_err(|e| {
-                request_error!(err = ?e, "check_password_quality");
-                e
-            })?;
+        self.check_password_quality(
+            pce.cleartext.as_str(),
+            account.related_inputs().as_slice(),
+            account.password_history.as_slice(),
+        )
+        .map_err(|e| {
+            request_error!(err = ?e, "check_password_quality");
+            e
+        })?;
 
         // And actually really apply it now.
         self.qs_write.modify_apply(mp).map_err(|e| {
@@ -1719,11 +1839,15 @@ impl<'a> IdmServerProxyWriteTransaction<'a> {
         // If we got here, then pre-apply succeeded, and that means access control
         // passed. Now we can do the extra checks.
 
-        self.check_password_quality(pce.cleartext.as_str(), account.related_inputs().as_slice())
-            .map_err(|e| {
-                admin_error!(?e, "Failed to checked password quality");
-                e
-            })?;
+        self.check_password_quality(
+            pce.cleartext.as_str(),
+            account.related_inputs().as_slice(),
+            account.password_history(),
+        )
+        .map_err(|e| {
+            admin_error!(?e, "Failed to checked password quality");
+            e
+        })?;
 
         // And actually really apply it now.
         self.qs_write.modify_apply(mp).map_err(|e| {
@@ -2026,13 +2150,38 @@ impl<'a> IdmServerProxyWriteTransaction<'a> {
                     self.domain_keys.cookie_key = new_cookie_key;
                 })?;
         }
+        // Logout tokens are signed with the (possibly reloaded) resource server keys.
+        let sessions_ended = std::mem::take(&mut self.qs_write.oauth2_sessions_ended);
+        let backchannel_pending =
+            self.oauth2_backchannel_logouts(sessions_ended, self.qs_write.get_curtime());
         // Commit everything.
         self.oauth2rs.commit();
         self.domain_keys.commit();
         self.pw_badlist_cache.commit();
         self.cred_update_sessions.commit();
         trace!("cred_update_session.commit");
-        self.qs_write.commit()
+        let audit_tx = self.audit_tx;
+        let audit_pending = self.audit_pending;
+        let backchannel_tx = self.backchannel_tx;
+        let mail_tx = self.mail_tx;
+        let mail_pending = self.mail_pending;
+        self.qs_write.commit().map(|()| {
+            // Only report the events once the changes they describe are durable.
+            for audit_event in audit_pending {
+                if audit_tx.send(audit_event).is_err() {
+                    error!("Unable to submit audit event to queue");
+                }
+            }
+            for logout in backchannel_pending {
+                if backchannel_tx.send(logout).is_err() {
+                    error!("Unable to submit oauth2 back-channel logout to queue");
+                }
+            }
+            // The messages themselves are stored, so the worker only needs to be told to look.
+            if mail_pending && mail_tx.send(()).is_err() {
+                error!("Unable to wake the mail worker");
+            }
+        })
     }
 
     fn reload_password_badlist(&mut self) -> Result<(), OperationError> {
@@ -2053,7 +2202,9 @@ mod tests {
     use std::convert::TryFrom;
     use std::time::Duration;
 
-    use kanidm_proto::v1::{AuthAllowed, AuthIssueSession, AuthMech, OperationError};
+    use kanidm_proto::v1::{
+        AuthAllowed, AuthIssueSession, AuthMech, OperationError, PasswordFeedback,
+    };
     use smartstring::alias::String as AttrString;
     use time::OffsetDateTime;
     use uuid::Uuid;
@@ -2469,10 +2620,13 @@ mod tests {
     #[idm_test]
     async fn test_idm_simple_password_reset(idms: &IdmServer, _idms_delayed: &IdmServerDelayed) {
         let pce = PasswordChangeEvent::new_internal(UUID_ADMIN, TEST_PASSWORD);
+        let pce_inc = PasswordChangeEvent::new_internal(UUID_ADMIN, TEST_PASSWORD_INC);
 
         let mut idms_prox_write = idms.proxy_write(duration_from_epoch_now()).await;
         assert!(idms_prox_write.set_account_password(&pce).is_ok());
-        assert!(idms_prox_write.set_account_password(&pce).is_ok());
+        assert!(idms_prox_write.set_account_password(&pce_inc).is_ok());
+        // The previous password is in the history and can't be reused.
+        assert!(idms_prox_write.set_account_password(&pce).is_err());
         assert!(idms_prox_write.commit().is_ok());
     }
 
@@ -2679,6 +2833,35 @@ mod tests {
         assert!(tok_g.spn == "admin@example.com");
     }
 
+    #[idm_test]
+    async fn test_idm_unix_password_history(idms: &IdmServer, _idms_delayed: &IdmServerDelayed) {
+        let mut idms_prox_write = idms.proxy_write(duration_from_epoch_now()).await;
+        // make the admin a valid posix account
+        let me_posix = ModifyEvent::new_internal_invalid(
+            filter!(f_eq("name", PartialValue::new_iname("admin"))),
+            ModifyList::new_list(vec![
+                Modify::Present(AttrString::from("class"), Value::new_class("posixaccount")),
+                Modify::Present(AttrString::from("gidnumber"), Value::new_uint32(2001)),
+            ]),
+        );
+        assert!(idms_prox_write.qs_write.modify(&me_posix).is_ok());
+
+        let pce = UnixPasswordChangeEvent::new_internal(UUID_ADMIN, TEST_PASSWORD);
+        assert!(idms_prox_write.set_unix_account_password(&pce).is_ok());
+
+        // Setting the same password again is rejected.
+        assert!(matches!(
+            idms_prox_write.set_unix_account_password(&pce),
+            Err(OperationError::PasswordQuality(feedback))
+                if matches!(feedback.as_slice(), [PasswordFeedback::PreviouslyUsed])
+        ));
+
+        // The primary password history is separate.
+        let pce = PasswordChangeEvent::new_internal(UUID_ADMIN, TEST_PASSWORD);
+        assert!(idms_prox_write.set_account_password(&pce).is_ok());
+        assert!(idms_prox_write.commit().is_ok());
+    }
+
     #[idm_test]
     async fn test_idm_simple_unix_password_reset(
         idms: &IdmServer,
@@ -10,7 +10,7 @@ use uuid::Uuid;
 use kanidm_lib_crypto::CryptoPolicy;
 
 use crate::credential::softlock::CredSoftLockPolicy;
-use crate::credential::Credential;
+use crate::credential::{Credential, Password};
 use crate::idm::delayed::{DelayedAction, UnixPasswordUpgrade};
 use crate::modify::{ModifyInvalid, ModifyList};
 use crate::prelude::*;
@@ -26,6 +26,7 @@ pub(crate) struct UnixUserAccount {
     pub sshkeys: Vec<String>,
     pub groups: Vec<UnixGroup>,
     cred: Option<Credential>,
+    password_history: Vec<Password>,
     pub valid_from: Option<OffsetDateTime>,
     pub expire: Option<OffsetDateTime>,
     pub radius_secret: Option<String>,
@@ -83,6 +84,11 @@ macro_rules! try_from_entry {
             .get_ava_single_credential("unix_password")
             .map(|v| v.clone());
 
+        let password_history = $value
+            .get_ava_password_history("unix_password_history")
+            .map(|h| h.values().cloned().collect())
+            .unwrap_or_default();
+
         let radius_secret = $value
             .get_ava_single_secret("radius_secret")
             .map(str::to_string);
@@ -106,6 +112,7 @@ macro_rules! try_from_entry {
             sshkeys,
             groups: $groups,
             cred,
+            password_history,
             valid_from,
             expire,
             radius_secret,
@@ -235,6 +242,11 @@ impl UnixUserAccount {
         inputs
     }
 
+    // Get the unix passwords previously set on this account.
+    pub fn password_history(&self) -> &[Password] {
+        &self.password_history
+    }
+
     pub(crate) fn verify_unix_credential(
         &self,
         cleartext: &str,
@@ -0,0 +1,232 @@
+//! Webhooks notify external systems of changes to accounts and groups. Events are
+//! collected by the webhook plugin as changes are written, and are handed to the
+//! delivery worker once the transaction that caused them has committed.
+//!
+//! Events that can not be delivered are stored on the webhook entry so that an
+//! administrator can inspect and retry them.
+
+use kanidm_proto::v1::{WebhookDeadLetter, WebhookEvent};
+
+use crate::idm::server::IdmServerProxyWriteTransaction;
+use crate::prelude::*;
+
+/// An event that is ready to be posted to a webhook.
+#[derive(Debug, Clone)]
+pub struct WebhookDelivery {
+    pub webhook: Uuid,
+    pub url: Url,
+    pub secret: String,
+    pub event: WebhookEvent,
+}
+
+pub struct WebhookRetryEvent {
+    pub ident: Identity,
+    pub target: Uuid,
+}
+
+impl<'a> IdmServerProxyWriteTransaction<'a> {
+    /// Store an event that could not be delivered on its webhook.
+    pub fn webhook_dead_letter(
+        &mut self,
+        delivery: &WebhookDelivery,
+        attempts: u32,
+        error: String,
+    ) -> Result<(), OperationError> {
+        let webhook = self.qs_write.internal_search_uuid(delivery.webhook)?;
+
+        let stored = webhook
+            .get_ava_set("webhook_dead_letter")
+            .map(|vs| vs.len())
+            .unwrap_or(0);
+        if stored >= WEBHOOK_DEAD_LETTER_MAX {
+            admin_error!(
+                webhook = ?delivery.webhook,
+                event = ?delivery.event.id,
+                "Too many undelivered events stored for webhook, dropping event"
+            );
+            return Ok(());
+        }
+
+        let dead_letter = WebhookDeadLetter {
+            event: delivery.event.clone(),
+            attempts,
+            error,
+        };
+        let value = serde_json::to_string(&dead_letter).map_err(|e| {
+            admin_error!(?e, "Unable to serialise webhook dead letter");
+            OperationError::SerdeJsonError
+        })?;
+
+        self.qs_write.internal_modify_uuid(
+            delivery.webhook,
+            &ModifyList::new_append("webhook_dead_letter", Value::new_utf8(value)),
+        )
+    }
+
+    /// Queue all of the stored undelivered events of a webhook for another delivery
+    /// attempt, returning how many were queued.
+    pub fn webhook_dead_letter_retry(
+        &mut self,
+        ev: &WebhookRetryEvent,
+    ) -> Result<usize, OperationError> {
+        let filter = filter!(f_and!([
+            f_eq("class", PVCLASS_WEBHOOK.clone()),
+            f_eq("uuid", PartialValue::Uuid(ev.target))
+        ]));
+
+        let webhook = self
+            .qs_write
+            .impersonate_search(filter.clone(), filter.clone(), &ev.ident)?
+            .pop()
+            .ok_or(OperationError::NoMatchingEntries)?;
+
+        let dead_letters: Vec<WebhookDeadLetter> = webhook
+            .get_ava_iter_utf8("webhook_dead_letter")
+            .map(|iter| {
+                iter.filter_map(|v| {
+                    serde_json::from_str(v)
+                        .map_err(|e| {
+                            admin_warn!(?e, "Ignoring invalid webhook dead letter");
+                        })
+                        .ok()
+                })
+                .collect()
+            })
+            .unwrap_or_default();
+
+        if dead_letters.is_empty() {
+            return Ok(0);
+        }
+
+        // Removing the dead letters checks that the caller may manage this webhook.
+        self.qs_write.impersonate_modify(
+            &filter,
+            &filter,
+            &ModifyList::new_purge("webhook_dead_letter"),
+            &ev.ident,
+        )?;
+
+        let url = webhook.get_ava_single_url("webhook_url").cloned();
+        let secret = webhook
+            .get_ava_single_secret("webhook_secret")
+            .map(str::to_string);
+        let (url, secret) = url.zip(secret).ok_or_else(|| {
+            admin_error!("Webhook is missing a url or secret");
+            OperationError::InvalidEntryState
+        })?;
+
+        let queued = dead_letters.len();
+        self.qs_write
+            .webhook_pending
+            .extend(dead_letters.into_iter().map(|dl| WebhookDelivery {
+                webhook: ev.target,
+                url: url.clone(),
+                secret: secret.clone(),
+                event: dl.event,
+            }));
+
+        Ok(queued)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use kanidm_proto::v1::WebhookEventType;
+
+    use crate::idm::webhook::WebhookRetryEvent;
+    use crate::prelude::*;
+
+    const UUID_TEST_WEBHOOK: Uuid = uuid!("7b9a59f2-9e9b-4d0c-b2a4-6cfc1d0b3d61");
+    const UUID_TEST_PERSON: Uuid = uuid!("0f0d7d3a-1f0f-4bd0-a4b8-36b6d8d0a1e2");
+
+    #[idm_test]
+    async fn test_idm_webhook_dead_letter_retry(
+        idms: &IdmServer,
+        _idms_delayed: &IdmServerDelayed,
+    ) {
+        let ct = duration_from_epoch_now();
+        let mut idms_prox_write = idms.proxy_write(ct).await;
+
+        let e_webhook = entry_init!(
+            ("class", Value::new_class("object")),
+            ("class", Value::new_class("webhook")),
+            ("name", Value::new_iname("test_webhook")),
+            ("uuid", Value::Uuid(UUID_TEST_WEBHOOK)),
+            (
+                "webhook_url",
+                Value::new_url_s("https://hooks.example.com/kanidm").unwrap()
+            ),
+            ("webhook_event", Value::new_iutf8("account_created"))
+        );
+        let e_person = entry_init!(
+            ("class", Value::new_class("object")),
+            ("class", Value::new_class("account")),
+            ("class", Value::new_class("person")),
+            ("name", Value::new_iname("test_person")),
+            ("uuid", Value::Uuid(UUID_TEST_PERSON)),
+            ("displayname", Value::new_utf8s("Test Person"))
+        );
+        assert!(idms_prox_write
+            .qs_write
+            .internal_create(vec![e_webhook, e_person])
+            .is_ok());
+
+        // Creating the account queued an event for the webhook.
+        let mut pending = std::mem::take(&mut idms_prox_write.qs_write.webhook_pending);
+        assert_eq!(pending.len(), 1);
+        let delivery = pending.pop().unwrap();
+        assert_eq!(delivery.webhook, UUID_TEST_WEBHOOK);
+        assert_eq!(delivery.event.event, WebhookEventType::AccountCreated);
+        assert_eq!(delivery.event.uuid, UUID_TEST_PERSON);
+
+        // It failed to deliver, so it's kept for later.
+        assert!(idms_prox_write
+            .webhook_dead_letter(&delivery, 4, "connection refused".to_string())
+            .is_ok());
+
+        let webhook = idms_prox_write
+            .qs_write
+            .internal_search_uuid(UUID_TEST_WEBHOOK)
+            .expect("failed");
+        assert_eq!(
+            webhook
+                .get_ava_set("webhook_dead_letter")
+                .map(|vs| vs.len()),
+            Some(1)
+        );
+
+        // Anonymous may not retry the delivery.
+        let anon = idms_prox_write
+            .qs_write
+            .internal_search_uuid(UUID_ANONYMOUS)
+            .expect("failed");
+        let ev = WebhookRetryEvent {
+            ident: Identity::from_impersonate_entry_readwrite(anon),
+            target: UUID_TEST_WEBHOOK,
+        };
+        assert!(idms_prox_write.webhook_dead_letter_retry(&ev).is_err());
+
+        // An admin can, which requeues the event and clears it from the webhook.
+        let admin = idms_prox_write
+            .qs_write
+            .internal_search_uuid(UUID_ADMIN)
+            .expect("failed");
+        let ev = WebhookRetryEvent {
+            ident: Identity::from_impersonate_entry_readwrite(admin),
+            target: UUID_TEST_WEBHOOK,
+        };
+        assert_eq!(idms_prox_write.webhook_dead_letter_retry(&ev), Ok(1));
+
+        let pending = std::mem::take(&mut idms_prox_write.qs_write.webhook_pending);
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].event.id, delivery.event.id);
+
+        let webhook = idms_prox_write
+            .qs_write
+            .internal_search_uuid(UUID_TEST_WEBHOOK)
+            .expect("failed");
+        assert!(!webhook.attribute_pres("webhook_dead_letter"));
+
+        assert!(idms_prox_write.commit().is_ok());
+    }
+}
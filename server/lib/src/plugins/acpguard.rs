@@ -0,0 +1,301 @@
+// Access Control Profile Guard
+//
+// Access control profiles are parsed when the access controls are reloaded at the end of
+// a transaction, and a profile that fails to parse causes the whole transaction to fail
+// with little indication of what was wrong. This plugin parses profiles as they are
+// written so that invalid target filters, receivers and attribute lists are rejected
+// with a useful error, and it checks that the attributes and classes named by a profile
+// actually exist in the schema.
+
+use std::sync::Arc;
+
+use kanidm_proto::v1::OperationError;
+
+use crate::event::{CreateEvent, ModifyEvent};
+use crate::plugins::Plugin;
+use crate::prelude::*;
+use crate::schema::SchemaTransaction;
+use crate::server::access::profiles::{
+    AccessControlCreate, AccessControlDelete, AccessControlModify, AccessControlProfile,
+    AccessControlSearch,
+};
+
+pub struct AcpGuard {}
+
+const ACP_ATTR_LISTS: [&str; 4] = [
+    "acp_search_attr",
+    "acp_modify_presentattr",
+    "acp_modify_removedattr",
+    "acp_create_attr",
+];
+
+const ACP_CLASS_LISTS: [&str; 2] = ["acp_modify_class", "acp_create_class"];
+
+impl AcpGuard {
+    fn validate(
+        qs: &mut QueryServerWriteTransaction,
+        e: &EntrySealedCommitted,
+    ) -> Result<(), OperationError> {
+        // Parse the profile in the same way that the access control reload will.
+        AccessControlProfile::try_from(qs, e)?;
+        if e.attribute_equality("class", &PVCLASS_ACS) {
+            AccessControlSearch::try_from(qs, e)?;
+        }
+        if e.attribute_equality("class", &PVCLASS_ACM) {
+            AccessControlModify::try_from(qs, e)?;
+        }
+        if e.attribute_equality("class", &PVCLASS_ACC) {
+            AccessControlCreate::try_from(qs, e)?;
+        }
+        if e.attribute_equality("class", &PVCLASS_ACD) {
+            AccessControlDelete::try_from(qs, e)?;
+        }
+
+        let schema = qs.get_schema();
+        let attributes = schema.get_attributes();
+        for attr in ACP_ATTR_LISTS {
+            if let Some(unknown) = e
+                .get_ava_iter_iutf8(attr)
+                .and_then(|mut i| i.find(|a| !attributes.contains_key(*a)))
+            {
+                admin_error!(
+                    ?attr,
+                    ?unknown,
+                    "Access control profile refers to an unknown attribute"
+                );
+                return Err(OperationError::InvalidAcpState(format!(
+                    "{} contains unknown attribute {}",
+                    attr, unknown
+                )));
+            }
+        }
+
+        let classes = schema.get_classes();
+        for attr in ACP_CLASS_LISTS {
+            if let Some(unknown) = e
+                .get_ava_iter_iutf8(attr)
+                .and_then(|mut i| i.find(|c| !classes.contains_key(*c)))
+            {
+                admin_error!(
+                    ?attr,
+                    ?unknown,
+                    "Access control profile refers to an unknown class"
+                );
+                return Err(OperationError::InvalidAcpState(format!(
+                    "{} contains unknown class {}",
+                    attr, unknown
+                )));
+            }
+        }
+
+        // Referential integrity has already asserted the receiver exists, but access is
+        // only granted through group membership.
+        if let Some(receiver) = e.get_ava_single_refer("acp_receiver_group") {
+            let filt = filter!(f_and!([
+                f_eq("uuid", PartialValue::Uuid(receiver)),
+                f_eq("class", PVCLASS_GROUP.clone())
+            ]));
+            if !qs.internal_exists(filt)? {
+                admin_error!(?receiver, "Access control profile receiver is not a group");
+                return Err(OperationError::InvalidAcpState(
+                    "acp_receiver_group must refer to a group".to_string(),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn validate_all<'a>(
+        qs: &mut QueryServerWriteTransaction,
+        cand: impl Iterator<Item = &'a EntrySealedCommitted>,
+    ) -> Result<(), OperationError> {
+        cand.filter(|e| e.attribute_equality("class", &PVCLASS_ACP))
+            .try_for_each(|e| Self::validate(qs, e))
+    }
+}
+
+impl Plugin for AcpGuard {
+    fn id() -> &'static str {
+        "plugin_acp_guard"
+    }
+
+    #[instrument(level = "debug", name = "acpguard_post_create", skip_all)]
+    fn post_create(
+        qs: &mut QueryServerWriteTransaction,
+        cand: &[EntrySealedCommitted],
+        ce: &CreateEvent,
+    ) -> Result<(), OperationError> {
+        if ce.ident.is_internal() {
+            trace!("Internal operation, not enforcing acp guard");
+            return Ok(());
+        }
+        Self::validate_all(qs, cand.iter())
+    }
+
+    #[instrument(level = "debug", name = "acpguard_post_modify", skip_all)]
+    fn post_modify(
+        qs: &mut QueryServerWriteTransaction,
+        _pre_cand: &[Arc<EntrySealedCommitted>],
+        cand: &[EntrySealedCommitted],
+        me: &ModifyEvent,
+    ) -> Result<(), OperationError> {
+        if me.ident.is_internal() {
+            trace!("Internal operation, not enforcing acp guard");
+            return Ok(());
+        }
+        Self::validate_all(qs, cand.iter())
+    }
+
+    #[instrument(level = "debug", name = "acpguard_post_batch_modify", skip_all)]
+    fn post_batch_modify(
+        qs: &mut QueryServerWriteTransaction,
+        _pre_cand: &[Arc<EntrySealedCommitted>],
+        cand: &[EntrySealedCommitted],
+        me: &BatchModifyEvent,
+    ) -> Result<(), OperationError> {
+        if me.ident.is_internal() {
+            trace!("Internal operation, not enforcing acp guard");
+            return Ok(());
+        }
+        Self::validate_all(qs, cand.iter())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::event::CreateEvent;
+    use crate::prelude::*;
+    use kanidm_proto::v1::OperationError;
+
+    fn acp(name: &str, receiver: Uuid, targetscope: &str) -> EntryInitNew {
+        entry_init!(
+            ("class", Value::new_class("access_control_profile")),
+            ("class", Value::new_class("access_control_search")),
+            ("name", Value::new_iname(name)),
+            ("acp_receiver_group", Value::Refer(receiver)),
+            (
+                "acp_targetscope",
+                Value::new_json_filter_s(targetscope).expect("filter")
+            ),
+            ("acp_search_attr", Value::new_iutf8("class")),
+            ("acp_search_attr", Value::new_iutf8("name"))
+        )
+    }
+
+    fn create_acp(
+        server_txn: &mut QueryServerWriteTransaction,
+        e: EntryInitNew,
+    ) -> Result<(), OperationError> {
+        let admin = server_txn
+            .internal_search_uuid(UUID_ADMIN)
+            .expect("failed to find admin");
+        let ce = CreateEvent::new_impersonate_identity(
+            Identity::from_impersonate_entry_readwrite(admin),
+            vec![e],
+        );
+        server_txn.create(&ce)
+    }
+
+    #[qs_test]
+    async fn test_acpguard_create(server: &QueryServer) {
+        let mut server_txn = server.write(duration_from_epoch_now()).await;
+
+        assert!(create_acp(
+            &mut server_txn,
+            acp(
+                "test_acp_valid",
+                UUID_IDM_ADMINS,
+                "{\"eq\":[\"class\",\"person\"]}"
+            )
+        )
+        .is_ok());
+
+        // Target filters must be valid against the schema.
+        assert!(matches!(
+            create_acp(
+                &mut server_txn,
+                acp(
+                    "test_acp_bad_filter",
+                    UUID_IDM_ADMINS,
+                    "{\"eq\":[\"nonexistant\",\"person\"]}"
+                )
+            ),
+            Err(OperationError::InvalidAttributeName(_))
+        ));
+
+        // Receivers must be groups.
+        assert!(matches!(
+            create_acp(
+                &mut server_txn,
+                acp(
+                    "test_acp_bad_receiver",
+                    UUID_ADMIN,
+                    "{\"eq\":[\"class\",\"person\"]}"
+                )
+            ),
+            Err(OperationError::InvalidAcpState(_))
+        ));
+
+        // Attributes and classes must exist.
+        let mut e = acp(
+            "test_acp_bad_attr",
+            UUID_IDM_ADMINS,
+            "{\"eq\":[\"class\",\"person\"]}",
+        );
+        e.add_ava("acp_search_attr", Value::new_iutf8("nonexistant"));
+        assert!(matches!(
+            create_acp(&mut server_txn, e),
+            Err(OperationError::InvalidAcpState(_))
+        ));
+
+        let mut e = acp(
+            "test_acp_bad_class",
+            UUID_IDM_ADMINS,
+            "{\"eq\":[\"class\",\"person\"]}",
+        );
+        e.add_ava("class", Value::new_class("access_control_modify"));
+        e.add_ava("acp_modify_presentattr", Value::new_iutf8("class"));
+        e.add_ava("acp_modify_class", Value::new_iutf8("nonexistant"));
+        assert!(matches!(
+            create_acp(&mut server_txn, e),
+            Err(OperationError::InvalidAcpState(_))
+        ));
+    }
+
+    #[qs_test]
+    async fn test_acpguard_modify(server: &QueryServer) {
+        let mut server_txn = server.write(duration_from_epoch_now()).await;
+
+        assert!(create_acp(
+            &mut server_txn,
+            acp(
+                "test_acp_valid",
+                UUID_IDM_ADMINS,
+                "{\"eq\":[\"class\",\"person\"]}"
+            )
+        )
+        .is_ok());
+
+        let admin = server_txn
+            .internal_search_uuid(UUID_ADMIN)
+            .expect("failed to find admin");
+
+        let me = ModifyEvent::new_impersonate_entry(
+            admin.clone(),
+            filter!(f_eq("name", PartialValue::new_iname("test_acp_valid"))),
+            ModifyList::new_append("acp_search_attr", Value::new_iutf8("displayname")),
+        );
+        assert!(server_txn.modify(&me).is_ok());
+
+        let me = ModifyEvent::new_impersonate_entry(
+            admin,
+            filter!(f_eq("name", PartialValue::new_iname("test_acp_valid"))),
+            ModifyList::new_append("acp_search_attr", Value::new_iutf8("nonexistant")),
+        );
+        assert!(matches!(
+            server_txn.modify(&me),
+            Err(OperationError::InvalidAcpState(_))
+        ));
+    }
+}
@@ -3,9 +3,10 @@
 // matter a lot when it comes to replication based on first-wins or
 // both change approaches.
 //
-//
-use std::collections::BTreeMap;
+// An attribute may be unique throughout the database, or only amongst the
+// entries of a single class if the schema defines a unique_scope.
 use std::collections::VecDeque;
+use std::collections::{BTreeMap, BTreeSet};
 use std::sync::Arc;
 
 use kanidm_proto::v1::{ConsistencyError, PluginError};
@@ -14,13 +15,43 @@ use tracing::trace;
 use crate::event::{CreateEvent, ModifyEvent};
 use crate::plugins::Plugin;
 use crate::prelude::*;
-use crate::schema::SchemaTransaction;
+use crate::repl::cid::Cid;
+use crate::schema::{SchemaAttribute, SchemaTransaction};
 
 pub struct AttrUnique;
 
+// Determine if an entry is subject to the uniqueness of this attribute. Recycled and
+// conflict entries are never considered, and scoped attributes only apply to entries
+// of the scope class.
+fn is_in_scope<VALID, STATE>(e: &Entry<VALID, STATE>, attr: &SchemaAttribute) -> bool {
+    if e.attribute_equality("class", &PVCLASS_RECYCLED)
+        || e.attribute_equality("class", &PVCLASS_CONFLICT)
+    {
+        return false;
+    }
+    attr.unique_scope
+        .as_ref()
+        .map(|scope| e.attribute_equality("class", &PartialValue::new_class(scope.as_str())))
+        .unwrap_or(true)
+}
+
+// and[ attr eq k, andnot [ uuid eq v ]]
+// Basically this says where value but also not self. If the attribute is scoped,
+// then only entries of the scope class are considered.
+fn conflict_filter(attr: &SchemaAttribute, v: PartialValue, uuid: Uuid) -> FC<'_> {
+    let mut terms = vec![
+        FC::Eq(attr.name.as_str(), v),
+        f_andnot(FC::Eq("uuid", PartialValue::Uuid(uuid))),
+    ];
+    if let Some(scope) = &attr.unique_scope {
+        terms.push(f_eq("class", PartialValue::new_class(scope.as_str())));
+    }
+    f_and(terms)
+}
+
 fn get_cand_attr_set<VALID, STATE>(
     cand: &[Entry<VALID, STATE>],
-    attr: &str,
+    attr: &SchemaAttribute,
 ) -> Result<BTreeMap<PartialValue, Uuid>, OperationError> {
     // This is building both the set of values to search for uniqueness, but ALSO
     // is detecting if any modified or current entries in the cand set also duplicated
@@ -28,13 +59,14 @@ fn get_cand_attr_set<VALID, STATE>(
     let mut cand_attr: BTreeMap<PartialValue, Uuid> = BTreeMap::new();
 
     cand.iter()
+        .filter(|e| is_in_scope(*e, attr))
         .try_for_each(|e| {
             let uuid = e
                 .get_ava_single_uuid("uuid")
                 .ok_or(OperationError::InvalidEntryState)?;
             // Get the value and uuid
             //for each value in the ava.
-            e.get_ava_set(attr)
+            e.get_ava_set(attr.name.as_str())
                 .map(|vs| {
                     vs.to_partialvalue_iter()
                         .try_for_each(|v| match cand_attr.insert(v, uuid) {
@@ -42,7 +74,7 @@ fn get_cand_attr_set<VALID, STATE>(
                             Some(vr) => {
                                 admin_error!(
                                     "ava already exists -> {:?}: {:?} conflicts to {:?}",
-                                    attr,
+                                    attr.name,
                                     vr,
                                     e.get_display_id()
                                 );
@@ -60,12 +92,12 @@ fn get_cand_attr_set<VALID, STATE>(
 fn enforce_unique<VALID, STATE>(
     qs: &mut QueryServerWriteTransaction,
     cand: &[Entry<VALID, STATE>],
-    attr: &str,
+    attr: &SchemaAttribute,
 ) -> Result<(), OperationError> {
     // Build a set of all the value -> uuid for the cands.
     // If already exist, reject due to dup.
     let cand_attr = get_cand_attr_set(cand, attr).map_err(|e| {
-        admin_error!(err = ?e, ?attr, "failed to get cand attr set");
+        admin_error!(err = ?e, attr = ?attr.name, "failed to get cand attr set");
         e
     })?;
 
@@ -81,14 +113,7 @@ fn enforce_unique<VALID, STATE>(
         // for each cand_attr
         cand_attr
             .iter()
-            .map(|(v, uuid)| {
-                // and[ attr eq k, andnot [ uuid eq v ]]
-                // Basically this says where name but also not self.
-                f_and(vec![
-                    FC::Eq(attr, v.clone()),
-                    f_andnot(FC::Eq("uuid", PartialValue::Uuid(*uuid))),
-                ])
-            })
+            .map(|(v, uuid)| conflict_filter(attr, v.clone(), *uuid))
             .collect()
     ));
 
@@ -116,14 +141,7 @@ fn enforce_unique<VALID, STATE>(
         // First create the vec of filters.
         let mut cand_filters: Vec<_> = cand_attr
             .into_iter()
-            .map(|(v, uuid)| {
-                // and[ attr eq k, andnot [ uuid eq v ]]
-                // Basically this says where name but also not self.
-                f_and(vec![
-                    FC::Eq(attr, v),
-                    f_andnot(FC::Eq("uuid", PartialValue::Uuid(uuid))),
-                ])
-            })
+            .map(|(v, uuid)| conflict_filter(attr, v, uuid))
             .collect();
 
         // Fast-ish path. There is 0 or 1 element, so we just fast return.
@@ -197,7 +215,7 @@ impl Plugin for AttrUnique {
 
         let r: Result<(), OperationError> = uniqueattrs
             .iter()
-            .try_for_each(|attr| enforce_unique(qs, cand, attr.as_str()));
+            .try_for_each(|attr| enforce_unique(qs, cand, attr));
         r
     }
 
@@ -215,7 +233,7 @@ impl Plugin for AttrUnique {
 
         let r: Result<(), OperationError> = uniqueattrs
             .iter()
-            .try_for_each(|attr| enforce_unique(qs, cand, attr.as_str()));
+            .try_for_each(|attr| enforce_unique(qs, cand, attr));
         r
     }
 
@@ -233,7 +251,7 @@ impl Plugin for AttrUnique {
 
         let r: Result<(), OperationError> = uniqueattrs
             .iter()
-            .try_for_each(|attr| enforce_unique(qs, cand, attr.as_str()));
+            .try_for_each(|attr| enforce_unique(qs, cand, attr));
         r
     }
 
@@ -248,10 +266,138 @@ impl Plugin for AttrUnique {
 
         let r: Result<(), OperationError> = uniqueattrs
             .iter()
-            .try_for_each(|attr| enforce_unique(qs, cand, attr.as_str()));
+            .try_for_each(|attr| enforce_unique(qs, cand, attr));
         r
     }
 
+    #[instrument(level = "debug", name = "attrunique_pre_repl_incremental", skip_all)]
+    fn pre_repl_incremental(
+        qs: &mut QueryServerWriteTransaction,
+        cand: &mut [(EntryIncrementalCommitted, Arc<EntrySealedCommitted>)],
+    ) -> Result<(), OperationError> {
+        let uniqueattrs = {
+            let schema = qs.get_schema();
+            schema.get_attributes_unique()
+        };
+
+        // Incoming changes can't be rejected, so duplicates are resolved by moving entries
+        // to a conflict state. The holder of a value that wrote it first retains it, and
+        // all others become conflicts. As every node makes the same choice, the nodes
+        // converge once replication completes in both directions.
+        let mut conflict_uuids: BTreeSet<Uuid> = BTreeSet::new();
+
+        for attr in uniqueattrs.iter() {
+            let mut holders: BTreeMap<PartialValue, Vec<(Cid, Uuid)>> = BTreeMap::new();
+
+            for (e, _) in cand.iter() {
+                if !is_in_scope(e, attr) {
+                    continue;
+                }
+                let Some(vs) = e.get_ava_set(attr.name.as_str()) else {
+                    continue;
+                };
+                let Some(cid) = e.get_changestate().get_attr_cid(attr.name.as_str()) else {
+                    continue;
+                };
+                for v in vs.to_partialvalue_iter() {
+                    holders
+                        .entry(v)
+                        .or_default()
+                        .push((cid.clone(), e.get_uuid()));
+                }
+            }
+
+            if holders.is_empty() {
+                continue;
+            }
+
+            // Find the existing holders of these values, excluding the entries that
+            // are being replaced by this change.
+            let filt_in = filter!(f_and(vec![
+                f_or(
+                    holders
+                        .keys()
+                        .map(|v| FC::Eq(attr.name.as_str(), v.clone()))
+                        .collect()
+                ),
+                f_andnot(f_or(
+                    cand.iter()
+                        .map(|(e, _)| FC::Eq("uuid", PartialValue::Uuid(e.get_uuid())))
+                        .collect()
+                )),
+            ]));
+
+            let existing = qs.internal_search(filt_in).map_err(|e| {
+                admin_error!("internal search error {:?}", e);
+                e
+            })?;
+
+            for e in existing.iter().filter(|e| is_in_scope(e.as_ref(), attr)) {
+                let (Some(vs), Some(cid)) = (
+                    e.get_ava_set(attr.name.as_str()),
+                    e.get_changestate().get_attr_cid(attr.name.as_str()),
+                ) else {
+                    continue;
+                };
+                for v in vs.to_partialvalue_iter() {
+                    if let Some(h) = holders.get_mut(&v) {
+                        h.push((cid.clone(), e.get_uuid()));
+                    }
+                }
+            }
+
+            for (v, mut h) in holders.into_iter() {
+                if h.len() < 2 {
+                    continue;
+                }
+                h.sort_unstable();
+                for (_, uuid) in h.into_iter().skip(1) {
+                    warn!(attr = ?attr.name, ?v, ?uuid, "Duplicate unique value, moving entry to a conflict state");
+                    conflict_uuids.insert(uuid);
+                }
+            }
+        }
+
+        if conflict_uuids.is_empty() {
+            return Ok(());
+        }
+
+        for (e, _) in cand.iter_mut() {
+            if conflict_uuids.remove(&e.get_uuid()) {
+                e.set_conflict();
+            }
+        }
+
+        if conflict_uuids.is_empty() {
+            return Ok(());
+        }
+
+        // The remaining conflicts are entries we already hold. These are changed
+        // by this node so that the conflict state is replicated to our partners.
+        let filt_in = filter!(f_or(
+            conflict_uuids
+                .iter()
+                .map(|u| f_eq("uuid", PartialValue::Uuid(*u)))
+                .collect()
+        ));
+        let work_set = qs
+            .internal_search_writeable(&filt_in)?
+            .into_iter()
+            .map(|(pre, post)| {
+                let uuid = pre.get_uuid();
+                let mut post = post.to_recycled();
+                post.add_ava("class", Value::new_class("conflict"));
+                post.add_ava("source_uuid", Value::Uuid(uuid));
+                (pre, post)
+            })
+            .collect();
+
+        qs.internal_apply_writable(work_set).map_err(|e| {
+            admin_error!("Unable to move entries to a conflict state {:?}", e);
+            e
+        })
+    }
+
     #[instrument(level = "debug", name = "attrunique_verify", skip(qs))]
     fn verify(qs: &mut QueryServerReadTransaction) -> Vec<Result<(), ConsistencyError>> {
         // Only check live entries, not recycled.
@@ -276,9 +422,9 @@ impl Plugin for AttrUnique {
 
         for attr in uniqueattrs.iter() {
             // We do a fully in memory check.
-            if get_cand_attr_set(&all_cand, attr.as_str()).is_err() {
+            if get_cand_attr_set(&all_cand, attr).is_err() {
                 res.push(Err(ConsistencyError::DuplicateUniqueAttribute(
-                    attr.to_string(),
+                    attr.name.to_string(),
                 )))
             }
         }
@@ -446,4 +592,64 @@ mod tests {
     fn test_verify_name_unique() {
         // Can we preload two dups and verify to show we detect?
     }
+
+    // An attribute with a unique scope only needs to be unique within that class.
+    #[qs_test]
+    async fn test_pre_create_scoped_unique(server: &QueryServer) {
+        let mut server_txn = server.write(duration_from_epoch_now()).await;
+        assert!(server_txn
+            .internal_create(vec![
+                entry_init!(
+                    ("class", Value::new_class("object")),
+                    ("class", Value::new_class("attributetype")),
+                    ("attributename", Value::new_iutf8("employeenumber")),
+                    ("description", Value::new_utf8s("employeenumber")),
+                    ("syntax", Value::Syntax(SyntaxType::Utf8String)),
+                    ("multivalue", Value::new_bool(false)),
+                    ("unique", Value::new_bool(true)),
+                    ("unique_scope", Value::new_iutf8("person"))
+                ),
+                entry_init!(
+                    ("class", Value::new_class("object")),
+                    ("class", Value::new_class("classtype")),
+                    ("classname", Value::new_iutf8("employee")),
+                    ("description", Value::new_utf8s("employee")),
+                    ("may", Value::new_iutf8("employeenumber"))
+                ),
+            ])
+            .is_ok());
+        assert!(server_txn.reload_schema().is_ok());
+
+        let person = |name: &str| {
+            entry_init!(
+                ("class", Value::new_class("object")),
+                ("class", Value::new_class("person")),
+                ("class", Value::new_class("employee")),
+                ("name", Value::new_iname(name)),
+                ("description", Value::new_utf8s(name)),
+                ("displayname", Value::new_utf8s(name)),
+                ("employeenumber", Value::new_utf8s("1234"))
+            )
+        };
+
+        assert!(server_txn
+            .internal_create(vec![person("testperson1")])
+            .is_ok());
+
+        // A group is outside of the scope, so may share the value.
+        assert!(server_txn
+            .internal_create(vec![entry_init!(
+                ("class", Value::new_class("object")),
+                ("class", Value::new_class("group")),
+                ("class", Value::new_class("employee")),
+                ("name", Value::new_iname("testgroup1")),
+                ("employeenumber", Value::new_utf8s("1234"))
+            )])
+            .is_ok());
+
+        assert!(matches!(
+            server_txn.internal_create(vec![person("testperson2")]),
+            Err(OperationError::Plugin(PluginError::AttrUnique(_)))
+        ));
+    }
 }
@@ -1,10 +1,11 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 use std::sync::Arc;
 
 use kanidm_proto::v1::Filter as ProtoFilter;
 
 use crate::filter::FilterInvalid;
 use crate::prelude::*;
+use crate::schema::SchemaTransaction;
 
 #[derive(Clone, Default)]
 pub struct DynGroupCache {
@@ -24,13 +25,8 @@ impl DynGroup {
         ident_internal: &Identity,
         dyn_groups: &mut DynGroupCache,
         n_dyn_groups: &[&Entry<EntrySealed, EntryCommitted>],
+        filter_changed: &BTreeSet<Uuid>,
     ) -> Result<(), OperationError> {
-        if !ident.is_internal() {
-            // It should be impossible to trigger this right now due to protected plugin.
-            error!("It is currently an error to create a dynamic group");
-            return Err(OperationError::SystemProtectedObject);
-        }
-
         // Search all the new groups first.
         let filt = filter!(FC::Or(
             n_dyn_groups
@@ -56,6 +52,16 @@ impl DynGroup {
             })?;
 
             let uuid = pre.get_uuid();
+
+            // Filters from the builtin groups are trusted, everything else must be
+            // able to be evaluated efficiently, and by the requestor.
+            if !ident.is_internal() {
+                Self::validate_filter(qs.get_schema(), &scope_i)?;
+                if filter_changed.contains(&uuid) {
+                    Self::check_filter_access(qs, ident, &scope_i)?;
+                }
+            }
+
             // Add our uuid as affected.
             affected_uuids.push(uuid);
 
@@ -95,6 +101,68 @@ impl DynGroup {
         Ok(())
     }
 
+    /// Check that a filter is suitable to define the membership of a dynamic group. As
+    /// the filter is evaluated against the whole directory, it may only reference
+    /// attributes that are indexed.
+    pub(crate) fn validate_filter(
+        schema: &dyn SchemaTransaction,
+        filter: &Filter<FilterInvalid>,
+    ) -> Result<(), OperationError> {
+        let filter = filter.validate(schema).map_err(|e| {
+            admin_error!("dyngroup_filter schema validation failed {:?}", e);
+            OperationError::SchemaViolation(e)
+        })?;
+
+        let attributes = schema.get_attributes();
+        for attr in filter.get_attr_set() {
+            if !attributes
+                .get(attr)
+                .map(|sa| !sa.index.is_empty())
+                .unwrap_or(false)
+            {
+                admin_error!("dyngroup_filter references unindexed attribute {}", attr);
+                return Err(OperationError::InvalidAttribute(format!(
+                    "{} is not indexed and can not be used in a dynamic group filter",
+                    attr
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// The members of a dynamic group can be read by anyone who can read the group, so
+    /// a requestor may only set a filter that matches the same entries when they search
+    /// with it themself. Otherwise the filter could reveal attributes they can't read.
+    fn check_filter_access(
+        qs: &mut QueryServerWriteTransaction,
+        ident: &Identity,
+        filter: &Filter<FilterInvalid>,
+    ) -> Result<(), OperationError> {
+        let f_valid = filter
+            .validate(qs.get_schema())
+            .map_err(OperationError::SchemaViolation)?
+            .into_ignore_hidden();
+
+        let visible: BTreeSet<Uuid> = qs
+            .impersonate_search_valid(f_valid.clone(), f_valid.clone(), ident)?
+            .iter()
+            .map(|e| e.get_uuid())
+            .collect();
+
+        let se = SearchEvent::new_internal(f_valid);
+        let hidden = qs
+            .search(&se)?
+            .iter()
+            .any(|e| !visible.contains(&e.get_uuid()));
+
+        if hidden {
+            security_access!("dyngroup_filter matches entries the requestor can not search");
+            Err(OperationError::AccessDenied)
+        } else {
+            Ok(())
+        }
+    }
+
     #[instrument(level = "debug", name = "dyngroup_reload", skip(qs))]
     pub fn reload(qs: &mut QueryServerWriteTransaction) -> Result<(), OperationError> {
         let ident_internal = Identity::from_internal();
@@ -220,6 +288,7 @@ impl DynGroup {
                 &ident_internal,
                 dyn_groups,
                 n_dyn_groups.as_slice(),
+                &n_dyn_groups.iter().map(|e| e.get_uuid()).collect(),
             )?;
         }
 
@@ -255,6 +324,18 @@ impl DynGroup {
             .iter()
             .partition(|entry| entry.attribute_equality("class", &PVCLASS_DYNGROUP));
 
+        // Entries may become dyngroups in this modification, so compare by uuid.
+        let filter_changed: BTreeSet<Uuid> = n_dyn_groups
+            .iter()
+            .filter(|post| {
+                !pre_cand.iter().any(|pre| {
+                    pre.get_uuid() == post.get_uuid()
+                        && pre.get_ava_set("dyngroup_filter") == post.get_ava_set("dyngroup_filter")
+                })
+            })
+            .map(|post| post.get_uuid())
+            .collect();
+
         // DANGER: Why do we have to do this? During the use of qs for internal search
         // and other operations we need qs to be mut. But when we borrow dyn groups here we
         // cause multiple borrows to occur on struct members that freaks rust out. This *IS*
@@ -281,6 +362,7 @@ impl DynGroup {
                 &ident_internal,
                 dyn_groups,
                 n_dyn_groups.as_slice(),
+                &filter_changed,
             )?;
         }
 
@@ -292,14 +374,24 @@ impl DynGroup {
         for (dg_uuid, dg_filter) in dyn_groups.insts.iter() {
             let dg_filter_valid = dg_filter
                 .validate(qs.get_schema())
-                .map_err(OperationError::SchemaViolation)
-                .and_then(|f| {
-                    f.resolve(&ident_internal, None, Some(qs.get_resolve_filter_cache()))
-                })?;
+                .map_err(OperationError::SchemaViolation)?;
+            let dg_attrs = dg_filter_valid.get_attr_set();
+            let dg_filter_valid = dg_filter_valid.resolve(
+                &ident_internal,
+                None,
+                Some(qs.get_resolve_filter_cache()),
+            )?;
 
+            // Only entries where an attribute of the filter was changed can have moved
+            // in or out of the group, so skip evaluating the filter for everything else.
             let matches: Vec<_> = pre_entries
                 .iter()
                 .zip(post_entries.iter())
+                .filter(|(pre, post)| {
+                    dg_attrs
+                        .iter()
+                        .any(|a| pre.get_ava_set(a) != post.get_ava_set(a))
+                })
                 .filter_map(|(pre, post)| {
                     let pre_t = pre.entry_match_no_index(&dg_filter_valid);
                     let post_t = post.entry_match_no_index(&dg_filter_valid);
@@ -358,9 +450,12 @@ impl DynGroup {
 mod tests {
     use kanidm_proto::v1::Filter as ProtoFilter;
 
+    use super::DynGroup;
     use crate::prelude::*;
 
     const UUID_TEST_GROUP: Uuid = uuid::uuid!("7bfd9931-06c2-4608-8a46-78719bb746fe");
+    const UUID_TEST_PERSON: Uuid = uuid::uuid!("4cbd6bd3-7bd4-4f4a-b1f6-5ac1d6f0b9e2");
+    const UUID_TEST_MANAGER: Uuid = uuid::uuid!("a8fcf1b2-3b0b-4d5e-9a34-0d8c2f0e6e51");
 
     #[test]
     fn test_create_dyngroup_add_new_group() {
@@ -918,4 +1013,165 @@ mod tests {
             }
         );
     }
+
+    #[qs_test]
+    async fn test_dyngroup_filter_indexed_attributes(server: &QueryServer) {
+        let server_txn = server.write(duration_from_epoch_now()).await;
+
+        let f_indexed = filter!(f_and!([
+            f_eq("class", PVCLASS_PERSON.clone()),
+            f_sub(
+                "mail",
+                PartialValue::new_email_address_s("@contractor.example")
+            )
+        ]));
+        assert!(DynGroup::validate_filter(server_txn.get_schema(), &f_indexed).is_ok());
+
+        let f_unindexed = filter!(f_eq("description", PartialValue::new_utf8s("contractor")));
+        assert!(matches!(
+            DynGroup::validate_filter(server_txn.get_schema(), &f_unindexed),
+            Err(OperationError::InvalidAttribute(_))
+        ));
+    }
+
+    #[qs_test]
+    async fn test_create_dyngroup_filter_access(server: &QueryServer) {
+        let mut server_txn = server.write(duration_from_epoch_now()).await;
+
+        let e_manager = entry_init!(
+            ("class", Value::new_class("object")),
+            ("class", Value::new_class("person")),
+            ("class", Value::new_class("account")),
+            ("name", Value::new_iname("testmanager")),
+            ("uuid", Value::Uuid(UUID_TEST_MANAGER)),
+            ("displayname", Value::new_utf8s("testmanager"))
+        );
+
+        let e_person = entry_init!(
+            ("class", Value::new_class("object")),
+            ("class", Value::new_class("person")),
+            ("class", Value::new_class("account")),
+            ("name", Value::new_iname("testperson")),
+            ("uuid", Value::Uuid(UUID_TEST_PERSON)),
+            ("displayname", Value::new_utf8s("testperson")),
+            ("legalname", Value::new_utf8s("Secret Name"))
+        );
+
+        assert!(server_txn
+            .internal_create(vec![e_manager, e_person])
+            .is_ok());
+        assert!(server_txn
+            .internal_modify_uuid(
+                UUID_IDM_GROUP_MANAGE_PRIV,
+                &ModifyList::new_append("member", Value::Refer(UUID_TEST_MANAGER))
+            )
+            .is_ok());
+
+        let manager = server_txn
+            .internal_search_uuid(UUID_TEST_MANAGER)
+            .expect("Unable to access manager.");
+        let ident = Identity::from_impersonate_entry_readwrite(manager);
+
+        let dyngroup = |name: &str, filter: ProtoFilter| {
+            entry_init!(
+                ("class", Value::new_class("object")),
+                ("class", Value::new_class("group")),
+                ("class", Value::new_class("dyngroup")),
+                ("name", Value::new_iname(name)),
+                ("dyngroup_filter", Value::JsonFilt(filter.clone()))
+            )
+        };
+
+        // The manager can't search the legal names of people, so a group over them would
+        // disclose who has which legal name.
+        let ce = CreateEvent::new_impersonate_identity(
+            ident.clone(),
+            vec![dyngroup(
+                "test_legalname",
+                ProtoFilter::Eq("legalname".to_string(), "Secret Name".to_string()),
+            )],
+        );
+        assert_eq!(server_txn.create(&ce), Err(OperationError::AccessDenied));
+
+        let ce = CreateEvent::new_impersonate_identity(
+            ident,
+            vec![dyngroup(
+                "test_people",
+                ProtoFilter::Eq("name".to_string(), "testperson".to_string()),
+            )],
+        );
+        assert!(server_txn.create(&ce).is_ok());
+
+        assert!(server_txn.commit().is_ok());
+    }
+
+    #[qs_test]
+    async fn test_modify_dyngroup_relevant_attributes(server: &QueryServer) {
+        let mut server_txn = server.write(duration_from_epoch_now()).await;
+
+        let e_dyn = entry_init!(
+            ("class", Value::new_class("object")),
+            ("class", Value::new_class("group")),
+            ("class", Value::new_class("dyngroup")),
+            ("name", Value::new_iname("test_dyngroup")),
+            ("uuid", Value::Uuid(UUID_TEST_GROUP)),
+            (
+                "dyngroup_filter",
+                Value::JsonFilt(ProtoFilter::And(vec![
+                    ProtoFilter::Eq("class".to_string(), "person".to_string()),
+                    ProtoFilter::Sub("mail".to_string(), "@contractor.example".to_string())
+                ]))
+            )
+        );
+
+        let e_person = entry_init!(
+            ("class", Value::new_class("object")),
+            ("class", Value::new_class("person")),
+            ("name", Value::new_iname("testperson")),
+            ("uuid", Value::Uuid(UUID_TEST_PERSON)),
+            ("displayname", Value::new_utf8s("testperson")),
+            (
+                "mail",
+                Value::new_email_address_s("testperson@contractor.example").unwrap()
+            )
+        );
+
+        assert!(server_txn.internal_create(vec![e_dyn, e_person]).is_ok());
+
+        let is_member = |server_txn: &mut QueryServerWriteTransaction| {
+            server_txn
+                .internal_search_uuid(UUID_TEST_GROUP)
+                .expect("Unable to access group.")
+                .attribute_equality("dynmember", &PartialValue::Refer(UUID_TEST_PERSON))
+        };
+        assert!(is_member(&mut server_txn));
+
+        // Changing an attribute the filter doesn't reference leaves membership as is.
+        assert!(server_txn
+            .internal_modify_uuid(
+                UUID_TEST_PERSON,
+                &ModifyList::new_purge_and_set("displayname", Value::new_utf8s("renamed"))
+            )
+            .is_ok());
+        assert!(is_member(&mut server_txn));
+
+        // Moving the mail address out of the domain removes the member.
+        assert!(server_txn
+            .internal_modify_uuid(
+                UUID_TEST_PERSON,
+                &ModifyList::new_purge_and_set(
+                    "mail",
+                    Value::new_email_address_s("testperson@example.com").unwrap()
+                )
+            )
+            .is_ok());
+        assert!(!is_member(&mut server_txn));
+
+        let person = server_txn
+            .internal_search_uuid(UUID_TEST_PERSON)
+            .expect("Unable to access person.");
+        assert!(!person.attribute_equality("memberof", &PartialValue::Refer(UUID_TEST_GROUP)));
+
+        assert!(server_txn.commit().is_ok());
+    }
 }
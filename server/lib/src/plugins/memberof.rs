@@ -9,13 +9,20 @@
 //
 // As a result, we first need to run refint to clean up all dangling references, then memberof
 // fixes the graph of memberships
+//
+// Members of a group may be time bounded by member_validity. A bounded member is only
+// reflected in memberof while the validity is in effect at the time of the write. As memberof
+// is only computed during a write, the purge_member_validity task is responsible for
+// expiring and activating these memberships as time passes.
 
 use std::collections::BTreeSet;
 use std::sync::Arc;
+use std::time::Duration;
 
 use hashbrown::HashMap;
 use kanidm_proto::v1::{ConsistencyError, OperationError};
 
+use crate::entry::EntrySealedCommitted;
 use crate::entry::{Entry, EntryCommitted, EntrySealed, EntryTuple};
 use crate::event::{CreateEvent, DeleteEvent, ModifyEvent};
 use crate::plugins::Plugin;
@@ -24,6 +31,49 @@ use crate::value::PartialValue;
 
 pub struct MemberOf;
 
+/// Is uuid a member of this group at the current time? Dynamic group members are never
+/// time bounded.
+fn is_active_member(group: &EntrySealedCommitted, uuid: Uuid, ct: Duration) -> bool {
+    group.attribute_equality("dynmember", &PartialValue::Refer(uuid))
+        || group
+            .get_ava_member_validity("member_validity")
+            .and_then(|mvs| mvs.get(&uuid))
+            .map(|mv| mv.is_active(ct))
+            .unwrap_or(true)
+}
+
+/// Is the membership of uuid in this group time bounded?
+fn is_bounded_member(group: &EntrySealedCommitted, uuid: Uuid) -> bool {
+    !group.attribute_equality("dynmember", &PartialValue::Refer(uuid))
+        && group.attribute_equality("member_validity", &PartialValue::Refer(uuid))
+}
+
+/// Remove the validity of members that were removed from the group, so that the validity
+/// can't apply to a member that is later re-added. A validity may be set before the member
+/// is added, so that the membership is never in effect outside of the validity.
+fn strip_removed_validity(
+    pre_cand: &[Arc<EntrySealedCommitted>],
+    cand: &mut [EntryInvalidCommitted],
+) {
+    for (pre, post) in pre_cand.iter().zip(cand.iter_mut()) {
+        let removed: Vec<_> = match (
+            pre.get_ava_as_refuuid("member"),
+            post.get_ava_member_validity("member_validity"),
+        ) {
+            (Some(members), Some(mvs)) => members
+                .filter(|u| {
+                    mvs.contains_key(u)
+                        && !post.attribute_equality("member", &PartialValue::Refer(*u))
+                })
+                .collect(),
+            _ => continue,
+        };
+        for u in removed {
+            post.remove_ava("member_validity", &PartialValue::Refer(u));
+        }
+    }
+}
+
 fn do_memberof(
     qs: &mut QueryServerWriteTransaction,
     uuid: Uuid,
@@ -43,6 +93,13 @@ fn do_memberof(
             e
         })?;
 
+    // Exclude the groups where our membership is not in effect.
+    let ct = qs.get_curtime();
+    let groups: Vec<_> = groups
+        .into_iter()
+        .filter(|g| is_active_member(g, uuid, ct))
+        .collect();
+
     // Ensure we are MO capable. We only add this if it's not already present.
     tgte.add_ava_if_not_exist("class", CLASS_MEMBEROF.clone());
     // Clear the dmo + mos, we will recreate them now.
@@ -210,6 +267,28 @@ impl Plugin for MemberOf {
         "memberof"
     }
 
+    #[instrument(level = "debug", name = "memberof_pre_modify", skip_all)]
+    fn pre_modify(
+        _qs: &mut QueryServerWriteTransaction,
+        pre_cand: &[Arc<EntrySealedCommitted>],
+        cand: &mut Vec<EntryInvalidCommitted>,
+        _me: &ModifyEvent,
+    ) -> Result<(), OperationError> {
+        strip_removed_validity(pre_cand, cand);
+        Ok(())
+    }
+
+    #[instrument(level = "debug", name = "memberof_pre_batch_modify", skip_all)]
+    fn pre_batch_modify(
+        _qs: &mut QueryServerWriteTransaction,
+        pre_cand: &[Arc<EntrySealedCommitted>],
+        cand: &mut Vec<EntryInvalidCommitted>,
+        _me: &BatchModifyEvent,
+    ) -> Result<(), OperationError> {
+        strip_removed_validity(pre_cand, cand);
+        Ok(())
+    }
+
     #[instrument(level = "debug", name = "memberof_post_create", skip(qs, cand, ce))]
     fn post_create(
         qs: &mut QueryServerWriteTransaction,
@@ -318,8 +397,19 @@ impl Plugin for MemberOf {
             };
             // for all direct -> add uuid to map
 
-            let d_groups_set: BTreeSet<Uuid> =
-                direct_memberof.iter().map(|e| e.get_uuid()).collect();
+            // Time bounded memberships are only refreshed by the purge task, so they may
+            // legitimately be stale and are excluded from the comparison.
+            let bounded: BTreeSet<Uuid> = direct_memberof
+                .iter()
+                .filter(|g| is_bounded_member(g, uuid))
+                .map(|g| g.get_uuid())
+                .collect();
+
+            let d_groups_set: BTreeSet<Uuid> = direct_memberof
+                .iter()
+                .map(|e| e.get_uuid())
+                .filter(|u| !bounded.contains(u))
+                .collect();
 
             let d_groups_set = if d_groups_set.is_empty() {
                 None
@@ -329,11 +419,20 @@ impl Plugin for MemberOf {
 
             trace!("DMO search groups {:?} -> {:?}", e.get_uuid(), d_groups_set);
 
-            match (e.get_ava_set("directmemberof"), d_groups_set) {
+            let edmos = e.get_ava_set("directmemberof").filter(|edmos| {
+                edmos
+                    .as_refer_set()
+                    .map(|a| a.iter().any(|u| !bounded.contains(u)))
+                    .unwrap_or(true)
+            });
+
+            match (edmos, d_groups_set) {
                 (Some(edmos), Some(b)) => {
                     // Can they both be reference sets?
                     match edmos.as_refer_set() {
                         Some(a) => {
+                            let a: BTreeSet<Uuid> =
+                                a.iter().filter(|u| !bounded.contains(u)).copied().collect();
                             let diff: Vec<_> = a.symmetric_difference(&b).collect();
                             if !diff.is_empty() {
                                 admin_error!(
@@ -453,7 +552,12 @@ impl MemberOf {
 
 #[cfg(test)]
 mod tests {
+    use std::time::Duration;
+
+    use time::OffsetDateTime;
+
     use crate::prelude::*;
+    use crate::value::MemberValidity;
 
     const UUID_A: &str = "aaaaaaaa-f82e-4484-a407-181aa03bda5c";
     const UUID_B: &str = "bbbbbbbb-2438-4384-9891-48f4c8172e9b";
@@ -1607,4 +1711,151 @@ mod tests {
             }
         );
     }
+
+    #[qs_test]
+    async fn test_member_validity_boundary(server: &QueryServer) {
+        // A -> B, until the boundary.
+        // A -> C, from the boundary.
+        let ct = Duration::from_secs(duration_from_epoch_now().as_secs());
+        let boundary = ct + Duration::from_secs(3600);
+        let at_boundary = Some(OffsetDateTime::UNIX_EPOCH + boundary);
+
+        let mut ea: Entry<EntryInit, EntryNew> = Entry::unsafe_from_entry_str(EA);
+        let eb: Entry<EntryInit, EntryNew> = Entry::unsafe_from_entry_str(EB);
+        let ec: Entry<EntryInit, EntryNew> = Entry::unsafe_from_entry_str(EC);
+
+        ea.add_ava("member", Value::new_refer_s(UUID_B).unwrap());
+        ea.add_ava("member", Value::new_refer_s(UUID_C).unwrap());
+        ea.add_ava(
+            "member_validity",
+            Value::MemberValidity(
+                Uuid::parse_str(UUID_B).unwrap(),
+                MemberValidity {
+                    not_before: None,
+                    not_after: at_boundary,
+                },
+            ),
+        );
+        ea.add_ava(
+            "member_validity",
+            Value::MemberValidity(
+                Uuid::parse_str(UUID_C).unwrap(),
+                MemberValidity {
+                    not_before: at_boundary,
+                    not_after: None,
+                },
+            ),
+        );
+
+        let mut server_txn = server.write(ct).await;
+        assert!(server_txn.internal_create(vec![ea, eb, ec]).is_ok());
+        assert!(server_txn.commit().is_ok());
+
+        // Just before the boundary nothing changes.
+        let mut server_txn = server.write(boundary - Duration::from_secs(1)).await;
+        assert!(server_txn.purge_member_validity().is_ok());
+        assert_memberof!(server_txn, UUID_B, UUID_A);
+        assert_not_memberof!(server_txn, UUID_C, UUID_A);
+        assert!(server_txn.commit().is_ok());
+
+        // Past the boundary, memberof is unchanged until the purge runs.
+        let mut server_txn = server.write(boundary + Duration::from_secs(1)).await;
+        assert_memberof!(server_txn, UUID_B, UUID_A);
+        assert_not_memberof!(server_txn, UUID_C, UUID_A);
+        drop(server_txn);
+
+        // The purge at the boundary ends B and starts C.
+        let mut server_txn = server.write(boundary).await;
+        assert!(server_txn.purge_member_validity().is_ok());
+        assert_not_memberof!(server_txn, UUID_B, UUID_A);
+        assert_memberof!(server_txn, UUID_C, UUID_A);
+        assert!(server_txn.commit().is_ok());
+    }
+
+    #[qs_test]
+    async fn test_member_validity(server: &QueryServer) {
+        // A -> B, until an hour from now.
+        // A -> C, from an hour from now.
+        let ct = duration_from_epoch_now();
+        let in_an_hour = Some(OffsetDateTime::UNIX_EPOCH + ct + Duration::from_secs(3600));
+
+        let mut ea: Entry<EntryInit, EntryNew> = Entry::unsafe_from_entry_str(EA);
+        let eb: Entry<EntryInit, EntryNew> = Entry::unsafe_from_entry_str(EB);
+        let ec: Entry<EntryInit, EntryNew> = Entry::unsafe_from_entry_str(EC);
+
+        ea.add_ava("member", Value::new_refer_s(UUID_B).unwrap());
+        ea.add_ava("member", Value::new_refer_s(UUID_C).unwrap());
+        ea.add_ava(
+            "member_validity",
+            Value::MemberValidity(
+                Uuid::parse_str(UUID_B).unwrap(),
+                MemberValidity {
+                    not_before: None,
+                    not_after: in_an_hour,
+                },
+            ),
+        );
+        ea.add_ava(
+            "member_validity",
+            Value::MemberValidity(
+                Uuid::parse_str(UUID_C).unwrap(),
+                MemberValidity {
+                    not_before: in_an_hour,
+                    not_after: None,
+                },
+            ),
+        );
+
+        let mut server_txn = server.write(ct).await;
+        assert!(server_txn.internal_create(vec![ea, eb, ec]).is_ok());
+
+        assert_memberof!(server_txn, UUID_B, UUID_A);
+        assert_not_memberof!(server_txn, UUID_C, UUID_A);
+        // Nothing has changed yet.
+        assert!(server_txn.purge_member_validity().is_ok());
+        assert_memberof!(server_txn, UUID_B, UUID_A);
+        assert_not_memberof!(server_txn, UUID_C, UUID_A);
+        assert!(server_txn.commit().is_ok());
+
+        // An hour later, B has expired and C is now in effect.
+        let mut server_txn = server.write(ct + Duration::from_secs(3601)).await;
+        assert!(server_txn.purge_member_validity().is_ok());
+
+        assert_not_memberof!(server_txn, UUID_B, UUID_A);
+        assert_memberof!(server_txn, UUID_C, UUID_A);
+
+        let ea = server_txn
+            .internal_search_uuid(Uuid::parse_str(UUID_A).unwrap())
+            .expect("Failed to get group");
+        assert!(!ea.attribute_equality("member", &PartialValue::new_refer_s(UUID_B).unwrap()));
+        assert!(ea.get_ava_set("member_validity").is_none());
+
+        // Removing a member removes its validity.
+        let ml = ModifyList::new_list(vec![Modify::Present(
+            "member_validity".into(),
+            Value::MemberValidity(
+                Uuid::parse_str(UUID_C).unwrap(),
+                MemberValidity {
+                    not_before: None,
+                    not_after: Some(OffsetDateTime::UNIX_EPOCH + ct + Duration::from_secs(7200)),
+                },
+            ),
+        )]);
+        assert!(server_txn
+            .internal_modify_uuid(Uuid::parse_str(UUID_A).unwrap(), &ml)
+            .is_ok());
+
+        let ml = ModifyList::new_remove("member", PartialValue::new_refer_s(UUID_C).unwrap());
+        assert!(server_txn
+            .internal_modify_uuid(Uuid::parse_str(UUID_A).unwrap(), &ml)
+            .is_ok());
+        assert_not_memberof!(server_txn, UUID_C, UUID_A);
+
+        let ea = server_txn
+            .internal_search_uuid(Uuid::parse_str(UUID_A).unwrap())
+            .expect("Failed to get group");
+        assert!(ea.get_ava_set("member_validity").is_none());
+
+        assert!(server_txn.commit().is_ok());
+    }
 }
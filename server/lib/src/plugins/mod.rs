@@ -11,6 +11,7 @@ use crate::entry::{Entry, EntryCommitted, EntryInvalid, EntryNew, EntrySealed};
 use crate::event::{CreateEvent, DeleteEvent, ModifyEvent};
 use crate::prelude::*;
 
+mod acpguard;
 mod attrunique;
 mod base;
 mod cred_import;
@@ -21,9 +22,13 @@ mod jwskeygen;
 mod memberof;
 mod namehistory;
 mod protected;
+mod pwhistory;
+pub(crate) mod recyclebin;
 mod refint;
+mod schemaguard;
 mod session;
 mod spn;
+mod webhook;
 
 trait Plugin {
     fn id() -> &'static str;
@@ -209,6 +214,9 @@ impl Plugins {
             .and_then(|_| domain::Domain::pre_create_transform(qs, cand, ce))
             .and_then(|_| spn::Spn::pre_create_transform(qs, cand, ce))
             .and_then(|_| namehistory::NameHistory::pre_create_transform(qs, cand, ce))
+            .and_then(|_| pwhistory::PasswordHistory::pre_create_transform(qs, cand, ce))
+            .and_then(|_| recyclebin::RecycleBin::pre_create_transform(qs, cand, ce))
+            .and_then(|_| webhook::Webhook::pre_create_transform(qs, cand, ce))
             // Should always be last
             .and_then(|_| attrunique::AttrUnique::pre_create_transform(qs, cand, ce))
     }
@@ -220,6 +228,7 @@ impl Plugins {
         ce: &CreateEvent,
     ) -> Result<(), OperationError> {
         protected::Protected::pre_create(qs, cand, ce)
+            .and_then(|_| schemaguard::SchemaGuard::pre_create(qs, cand, ce))
     }
 
     #[instrument(level = "debug", name = "plugins::run_post_create", skip_all)]
@@ -229,7 +238,9 @@ impl Plugins {
         ce: &CreateEvent,
     ) -> Result<(), OperationError> {
         refint::ReferentialIntegrity::post_create(qs, cand, ce)
+            .and_then(|_| acpguard::AcpGuard::post_create(qs, cand, ce))
             .and_then(|_| memberof::MemberOf::post_create(qs, cand, ce))
+            .and_then(|_| webhook::Webhook::post_create(qs, cand, ce))
     }
 
     #[instrument(level = "debug", name = "plugins::run_pre_modify", skip_all)]
@@ -248,6 +259,11 @@ impl Plugins {
             .and_then(|_| spn::Spn::pre_modify(qs, pre_cand, cand, me))
             .and_then(|_| session::SessionConsistency::pre_modify(qs, pre_cand, cand, me))
             .and_then(|_| namehistory::NameHistory::pre_modify(qs, pre_cand, cand, me))
+            .and_then(|_| pwhistory::PasswordHistory::pre_modify(qs, pre_cand, cand, me))
+            .and_then(|_| recyclebin::RecycleBin::pre_modify(qs, pre_cand, cand, me))
+            .and_then(|_| memberof::MemberOf::pre_modify(qs, pre_cand, cand, me))
+            .and_then(|_| webhook::Webhook::pre_modify(qs, pre_cand, cand, me))
+            .and_then(|_| schemaguard::SchemaGuard::pre_modify(qs, pre_cand, cand, me))
             // attr unique should always be last
             .and_then(|_| attrunique::AttrUnique::pre_modify(qs, pre_cand, cand, me))
     }
@@ -259,9 +275,13 @@ impl Plugins {
         cand: &[Entry<EntrySealed, EntryCommitted>],
         me: &ModifyEvent,
     ) -> Result<(), OperationError> {
-        refint::ReferentialIntegrity::post_modify(qs, pre_cand, cand, me)
+        schemaguard::SchemaGuard::post_modify(qs, pre_cand, cand, me)
+            .and_then(|_| refint::ReferentialIntegrity::post_modify(qs, pre_cand, cand, me))
+            .and_then(|_| acpguard::AcpGuard::post_modify(qs, pre_cand, cand, me))
             .and_then(|_| spn::Spn::post_modify(qs, pre_cand, cand, me))
             .and_then(|_| memberof::MemberOf::post_modify(qs, pre_cand, cand, me))
+            .and_then(|_| session::SessionConsistency::post_modify(qs, pre_cand, cand, me))
+            .and_then(|_| webhook::Webhook::post_modify(qs, pre_cand, cand, me))
     }
 
     #[instrument(level = "debug", name = "plugins::run_pre_batch_modify", skip_all)]
@@ -280,6 +300,11 @@ impl Plugins {
             .and_then(|_| spn::Spn::pre_batch_modify(qs, pre_cand, cand, me))
             .and_then(|_| session::SessionConsistency::pre_batch_modify(qs, pre_cand, cand, me))
             .and_then(|_| namehistory::NameHistory::pre_batch_modify(qs, pre_cand, cand, me))
+            .and_then(|_| pwhistory::PasswordHistory::pre_batch_modify(qs, pre_cand, cand, me))
+            .and_then(|_| recyclebin::RecycleBin::pre_batch_modify(qs, pre_cand, cand, me))
+            .and_then(|_| memberof::MemberOf::pre_batch_modify(qs, pre_cand, cand, me))
+            .and_then(|_| webhook::Webhook::pre_batch_modify(qs, pre_cand, cand, me))
+            .and_then(|_| schemaguard::SchemaGuard::pre_batch_modify(qs, pre_cand, cand, me))
             // attr unique should always be last
             .and_then(|_| attrunique::AttrUnique::pre_batch_modify(qs, pre_cand, cand, me))
     }
@@ -291,9 +316,13 @@ impl Plugins {
         cand: &[Entry<EntrySealed, EntryCommitted>],
         me: &BatchModifyEvent,
     ) -> Result<(), OperationError> {
-        refint::ReferentialIntegrity::post_batch_modify(qs, pre_cand, cand, me)
+        schemaguard::SchemaGuard::post_batch_modify(qs, pre_cand, cand, me)
+            .and_then(|_| refint::ReferentialIntegrity::post_batch_modify(qs, pre_cand, cand, me))
+            .and_then(|_| acpguard::AcpGuard::post_batch_modify(qs, pre_cand, cand, me))
             .and_then(|_| spn::Spn::post_batch_modify(qs, pre_cand, cand, me))
             .and_then(|_| memberof::MemberOf::post_batch_modify(qs, pre_cand, cand, me))
+            .and_then(|_| session::SessionConsistency::post_batch_modify(qs, pre_cand, cand, me))
+            .and_then(|_| webhook::Webhook::post_batch_modify(qs, pre_cand, cand, me))
     }
 
     #[instrument(level = "debug", name = "plugins::run_pre_delete", skip_all)]
@@ -303,6 +332,7 @@ impl Plugins {
         de: &DeleteEvent,
     ) -> Result<(), OperationError> {
         protected::Protected::pre_delete(qs, cand, de)
+            .and_then(|_| schemaguard::SchemaGuard::pre_delete(qs, cand, de))
     }
 
     #[instrument(level = "debug", name = "plugins::run_post_delete", skip_all)]
@@ -313,6 +343,8 @@ impl Plugins {
     ) -> Result<(), OperationError> {
         refint::ReferentialIntegrity::post_delete(qs, cand, de)
             .and_then(|_| memberof::MemberOf::post_delete(qs, cand, de))
+            .and_then(|_| session::SessionConsistency::post_delete(qs, cand, de))
+            .and_then(|_| webhook::Webhook::post_delete(qs, cand, de))
     }
 
     #[instrument(level = "debug", name = "plugins::run_pre_repl_refresh", skip_all)]
@@ -29,9 +29,17 @@ lazy_static! {
         m.insert("fernet_private_key_str");
         m.insert("es256_private_key_der");
         m.insert("badlist_password");
+        m.insert("password_history_length");
+        m.insert("recycle_bin_retention");
         m.insert("domain_display_name");
         m
     };
+    // The builtin dynamic groups are relied upon by the default access controls, so
+    // unlike other dynamic groups they may not be altered.
+    static ref PROTECTED_DYNGROUPS: [PartialValue; 2] = [
+        PartialValue::Uuid(UUID_IDM_ALL_PERSONS),
+        PartialValue::Uuid(UUID_IDM_ALL_ACCOUNTS),
+    ];
 }
 
 impl Plugin for Protected {
@@ -58,7 +66,9 @@ impl Plugin for Protected {
                 || cand.attribute_equality("class", &PVCLASS_SYSTEM_CONFIG)
                 || cand.attribute_equality("class", &PVCLASS_TOMBSTONE)
                 || cand.attribute_equality("class", &PVCLASS_RECYCLED)
-                || cand.attribute_equality("class", &PVCLASS_DYNGROUP)
+                || PROTECTED_DYNGROUPS
+                    .iter()
+                    .any(|pv| cand.attribute_equality("uuid", pv))
             {
                 Err(OperationError::SystemProtectedObject)
             } else {
@@ -104,7 +114,9 @@ impl Plugin for Protected {
         cand.iter().try_fold((), |(), cand| {
             if cand.attribute_equality("class", &PVCLASS_TOMBSTONE)
                 || cand.attribute_equality("class", &PVCLASS_RECYCLED)
-                || cand.attribute_equality("class", &PVCLASS_DYNGROUP)
+                || PROTECTED_DYNGROUPS
+                    .iter()
+                    .any(|pv| cand.attribute_equality("uuid", pv))
             {
                 Err(OperationError::SystemProtectedObject)
             } else {
@@ -183,7 +195,9 @@ impl Plugin for Protected {
         cand.iter().try_fold((), |(), cand| {
             if cand.attribute_equality("class", &PVCLASS_TOMBSTONE)
                 || cand.attribute_equality("class", &PVCLASS_RECYCLED)
-                || cand.attribute_equality("class", &PVCLASS_DYNGROUP)
+                || PROTECTED_DYNGROUPS
+                    .iter()
+                    .any(|pv| cand.attribute_equality("uuid", pv))
             {
                 Err(OperationError::SystemProtectedObject)
             } else {
@@ -245,7 +259,9 @@ impl Plugin for Protected {
                 || cand.attribute_equality("class", &PVCLASS_SYSTEM_CONFIG)
                 || cand.attribute_equality("class", &PVCLASS_TOMBSTONE)
                 || cand.attribute_equality("class", &PVCLASS_RECYCLED)
-                || cand.attribute_equality("class", &PVCLASS_DYNGROUP)
+                || PROTECTED_DYNGROUPS
+                    .iter()
+                    .any(|pv| cand.attribute_equality("uuid", pv))
             {
                 Err(OperationError::SystemProtectedObject)
             } else {
@@ -488,6 +504,20 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_pre_delete_builtin_dyngroup_deny() {
+        // Other dynamic groups may be removed, but not the builtin ones.
+        let preload = PRELOAD.clone();
+
+        run_delete_test!(
+            Err(OperationError::SystemProtectedObject),
+            preload,
+            filter!(f_eq("name", PartialValue::new_iname("idm_all_persons"))),
+            Some(E_TEST_ACCOUNT.clone()),
+            |_| {}
+        );
+    }
+
     #[test]
     fn test_modify_domain() {
         // Can edit *my* domain_ssid and domain_name
@@ -0,0 +1,150 @@
+// Password History
+//
+// When the primary or unix password of an account changes, record the hash of the new
+// password so that it can't be reused later. The number of passwords kept is bounded by
+// the password_history_length of the system config.
+
+use std::collections::BTreeSet;
+use std::sync::Arc;
+use std::time::Duration;
+
+use kanidm_proto::v1::OperationError;
+
+use crate::entry::{EntryInvalidCommitted, EntrySealedCommitted};
+use crate::event::ModifyEvent;
+use crate::plugins::Plugin;
+use crate::prelude::*;
+
+pub struct PasswordHistory {}
+
+// The credential attribute, and the attribute that holds its history.
+const HISTORY_ATTRIBUTES: [(&str, &str); 2] = [
+    ("primary_credential", "password_history"),
+    ("unix_password", "unix_password_history"),
+];
+
+impl PasswordHistory {
+    fn update_history<STATE: Clone>(
+        qs: &mut QueryServerWriteTransaction,
+        pre: Option<&EntrySealedCommitted>,
+        post: &mut Entry<EntryInvalid, STATE>,
+        length: &mut Option<u32>,
+    ) -> Result<(), OperationError> {
+        for (cred_attr, history_attr) in HISTORY_ATTRIBUTES.iter() {
+            let pre_pw = pre
+                .and_then(|e| e.get_ava_single_credential(cred_attr))
+                .and_then(|c| c.password_ref().ok());
+
+            let changed_pw = post
+                .get_ava_single_credential(cred_attr)
+                .and_then(|c| c.password_ref().ok())
+                .filter(|pw| pre_pw != Some(*pw))
+                .cloned();
+
+            let history_len = post
+                .get_ava_password_history(history_attr)
+                .map(|h| h.len())
+                .unwrap_or(0);
+
+            if changed_pw.is_none() && history_len == 0 {
+                continue;
+            }
+
+            let max = match length {
+                Some(l) => *l,
+                None => *length.insert(qs.get_password_history_length()?),
+            } as usize;
+
+            if let Some(pw) = changed_pw {
+                if max > 0 {
+                    // If the password already changed in this transaction, the txn cid
+                    // is taken. Step past it so the earlier password is still kept.
+                    let mut cid = qs.get_txn_cid().clone();
+                    while post
+                        .get_ava_password_history(history_attr)
+                        .map(|h| h.contains_key(&cid))
+                        .unwrap_or(false)
+                    {
+                        cid.ts += Duration::from_nanos(1);
+                    }
+                    post.add_ava(history_attr, Value::PasswordHistory(cid, pw));
+                }
+            }
+
+            // Only keep the newest passwords.
+            let expired: BTreeSet<_> = post
+                .get_ava_password_history(history_attr)
+                .map(|h| {
+                    let excess = h.len().saturating_sub(max);
+                    h.keys()
+                        .take(excess)
+                        .cloned()
+                        .map(PartialValue::Cid)
+                        .collect()
+                })
+                .unwrap_or_default();
+
+            if !expired.is_empty() {
+                trace!(
+                    ?history_attr,
+                    count = expired.len(),
+                    "trimming password history"
+                );
+                post.remove_avas(history_attr, &expired);
+            }
+        }
+        Ok(())
+    }
+
+    fn handle_modify(
+        qs: &mut QueryServerWriteTransaction,
+        pre_cand: &[Arc<EntrySealedCommitted>],
+        cand: &mut [EntryInvalidCommitted],
+    ) -> Result<(), OperationError> {
+        let mut length = None;
+        for (pre, post) in pre_cand.iter().zip(cand.iter_mut()) {
+            if post.attribute_equality("class", &PVCLASS_ACCOUNT) {
+                Self::update_history(qs, Some(pre.as_ref()), post, &mut length)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Plugin for PasswordHistory {
+    fn id() -> &'static str {
+        "plugin_password_history"
+    }
+
+    fn pre_create_transform(
+        qs: &mut QueryServerWriteTransaction,
+        cand: &mut Vec<EntryInvalidNew>,
+        _ce: &CreateEvent,
+    ) -> Result<(), OperationError> {
+        let mut length = None;
+        for post in cand.iter_mut() {
+            if post.attribute_equality("class", &PVCLASS_ACCOUNT) {
+                Self::update_history(qs, None, post, &mut length)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn pre_modify(
+        qs: &mut QueryServerWriteTransaction,
+        pre_cand: &[Arc<EntrySealedCommitted>],
+        cand: &mut Vec<EntryInvalidCommitted>,
+        _me: &ModifyEvent,
+    ) -> Result<(), OperationError> {
+        Self::handle_modify(qs, pre_cand, cand)
+    }
+
+    fn pre_batch_modify(
+        qs: &mut QueryServerWriteTransaction,
+        pre_cand: &[Arc<EntrySealedCommitted>],
+        cand: &mut Vec<EntryInvalidCommitted>,
+        _me: &BatchModifyEvent,
+    ) -> Result<(), OperationError> {
+        Self::handle_modify(qs, pre_cand, cand)
+    }
+}
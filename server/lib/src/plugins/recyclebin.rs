@@ -0,0 +1,132 @@
+// Recycle Bin
+//
+// The per class retention periods of the recycle bin are stored in the system config as
+// "class:seconds" strings. Validate them as they are written so that the purge never has
+// to guess at what an administrator intended.
+
+use std::sync::Arc;
+
+use crate::event::{CreateEvent, ModifyEvent};
+use crate::plugins::Plugin;
+use crate::prelude::*;
+
+pub struct RecycleBin {}
+
+/// Parse a recycle_bin_retention value into the class it applies to and the number of
+/// seconds that entries of that class are retained for.
+pub(crate) fn parse_retention(v: &str) -> Option<(String, u64)> {
+    v.split_once(':').and_then(|(class, secs)| {
+        let class = class.trim();
+        if class.is_empty() {
+            return None;
+        }
+        secs.trim()
+            .parse::<u64>()
+            .ok()
+            .map(|secs| (class.to_string(), secs))
+    })
+}
+
+impl Plugin for RecycleBin {
+    fn id() -> &'static str {
+        "plugin_recycle_bin"
+    }
+
+    #[instrument(level = "debug", name = "recyclebin_pre_create_transform", skip_all)]
+    fn pre_create_transform(
+        _qs: &mut QueryServerWriteTransaction,
+        cand: &mut Vec<Entry<EntryInvalid, EntryNew>>,
+        _ce: &CreateEvent,
+    ) -> Result<(), OperationError> {
+        Self::validate_inner(cand)
+    }
+
+    #[instrument(level = "debug", name = "recyclebin_pre_modify", skip_all)]
+    fn pre_modify(
+        _qs: &mut QueryServerWriteTransaction,
+        _pre_cand: &[Arc<EntrySealedCommitted>],
+        cand: &mut Vec<Entry<EntryInvalid, EntryCommitted>>,
+        _me: &ModifyEvent,
+    ) -> Result<(), OperationError> {
+        Self::validate_inner(cand)
+    }
+
+    #[instrument(level = "debug", name = "recyclebin_pre_batch_modify", skip_all)]
+    fn pre_batch_modify(
+        _qs: &mut QueryServerWriteTransaction,
+        _pre_cand: &[Arc<EntrySealedCommitted>],
+        cand: &mut Vec<Entry<EntryInvalid, EntryCommitted>>,
+        _me: &BatchModifyEvent,
+    ) -> Result<(), OperationError> {
+        Self::validate_inner(cand)
+    }
+}
+
+impl RecycleBin {
+    fn validate_inner<T: Clone>(cand: &mut [Entry<EntryInvalid, T>]) -> Result<(), OperationError> {
+        cand.iter()
+            .filter_map(|e| e.get_ava_iter_iutf8("recycle_bin_retention"))
+            .flatten()
+            .try_for_each(|v| {
+                if parse_retention(v).is_some() {
+                    Ok(())
+                } else {
+                    admin_error!(?v, "Invalid recycle_bin_retention value");
+                    Err(OperationError::InvalidAttribute(format!(
+                        "{} is not a valid recycle bin retention, expected class:seconds",
+                        v
+                    )))
+                }
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn test_modify_recycle_bin_retention() {
+        let preload = Vec::new();
+        run_modify_test!(
+            Ok(()),
+            preload,
+            filter!(f_eq("uuid", PVUUID_SYSTEM_CONFIG.clone())),
+            ModifyList::new_list(vec![Modify::Present(
+                AttrString::from("recycle_bin_retention"),
+                Value::new_iutf8("group:2592000")
+            )]),
+            None,
+            |_| {},
+            |_| {}
+        );
+    }
+
+    #[test]
+    fn test_modify_recycle_bin_retention_invalid() {
+        for invalid in [
+            "group",
+            "group:",
+            ":2592000",
+            "group:thirty_days",
+            "group:-1",
+        ] {
+            let preload = Vec::new();
+            run_modify_test!(
+                Err(OperationError::InvalidAttribute(format!(
+                    "{} is not a valid recycle bin retention, expected class:seconds",
+                    invalid
+                ))),
+                preload,
+                filter!(f_eq("uuid", PVUUID_SYSTEM_CONFIG.clone())),
+                ModifyList::new_list(vec![Modify::Present(
+                    AttrString::from("recycle_bin_retention"),
+                    Value::new_iutf8(invalid)
+                )]),
+                None,
+                |_| {},
+                |_| {}
+            );
+        }
+    }
+}
@@ -0,0 +1,848 @@
+// Schema Guard
+//
+// Administrators may extend the schema with their own attribute and class types, and may
+// extend the may/must of existing classes. This plugin ensures that these changes can't
+// leave existing entries in a state that no longer conforms to the schema, that schema
+// which is still in use can't be deleted, and that deprecated attributes and classes are
+// not added to entries.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use kanidm_proto::v1::{OperationError, PluginError, SchemaError};
+
+use crate::entry::{Eattrs, EntryInvalidCommitted, EntrySealedCommitted};
+use crate::event::{CreateEvent, DeleteEvent, ModifyEvent};
+use crate::plugins::Plugin;
+use crate::prelude::*;
+use crate::schema::SchemaTransaction;
+
+pub struct SchemaGuard {}
+
+// The syntaxes that an administrator may use for their own attributes. The remaining
+// syntaxes hold server internal state such as credentials, sessions and keys.
+const CUSTOM_ATTRIBUTE_SYNTAX: [SyntaxType; 11] = [
+    SyntaxType::Utf8String,
+    SyntaxType::Utf8StringInsensitive,
+    SyntaxType::Utf8StringIname,
+    SyntaxType::Uuid,
+    SyntaxType::Boolean,
+    SyntaxType::ReferenceUuid,
+    SyntaxType::SshKey,
+    SyntaxType::Uint32,
+    SyntaxType::DateTime,
+    SyntaxType::EmailAddress,
+    SyntaxType::Url,
+];
+
+const SCHEMA_NAME_MAX_LEN: usize = 64;
+
+fn is_schema_entry<VALID, STATE>(e: &Entry<VALID, STATE>) -> bool {
+    e.attribute_equality("class", &PVCLASS_ATTRIBUTETYPE)
+        || e.attribute_equality("class", &PVCLASS_CLASSTYPE)
+}
+
+fn schema_name<VALID, STATE>(e: &Entry<VALID, STATE>) -> Option<&str> {
+    e.get_ava_single_iutf8("attributename")
+        .or_else(|| e.get_ava_single_iutf8("classname"))
+}
+
+// Names must be usable in filters, ldap and json, so we keep them simple.
+fn validate_schema_name(name: &str) -> Result<(), OperationError> {
+    let mut chars = name.chars();
+    let valid = name.len() <= SCHEMA_NAME_MAX_LEN
+        && chars
+            .next()
+            .map(|c| c.is_ascii_lowercase())
+            .unwrap_or(false)
+        && chars.all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+
+    if valid {
+        Ok(())
+    } else {
+        admin_error!(
+            ?name,
+            "Schema names must start with a letter, and only contain a-z, 0-9 and _"
+        );
+        Err(OperationError::InvalidAttributeName(name.to_string()))
+    }
+}
+
+impl SchemaGuard {
+    // Check a schema definition that is being created or altered by an administrator.
+    fn validate_definition<VALID, STATE>(
+        schema: &dyn SchemaTransaction,
+        pre: Option<&EntrySealedCommitted>,
+        post: &Entry<VALID, STATE>,
+    ) -> Result<(), OperationError> {
+        let name = schema_name(post).ok_or(OperationError::InvalidEntryState)?;
+
+        if let Some(pre) = pre {
+            // Renaming would orphan every value or class that uses the old name.
+            if schema_name(pre) != Some(name) {
+                admin_error!(?name, "Schema types may not be renamed");
+                return Err(OperationError::InvalidAttribute(
+                    "schema types may not be renamed".to_string(),
+                ));
+            }
+        } else {
+            validate_schema_name(name)?;
+        }
+
+        if post.attribute_equality("class", &PVCLASS_ATTRIBUTETYPE) {
+            let syntax = post.get_ava_single_syntax("syntax");
+            let syntax_changed = pre.map(|p| p.get_ava_single_syntax("syntax")) != Some(syntax);
+            if syntax_changed
+                && !syntax
+                    .map(|s| CUSTOM_ATTRIBUTE_SYNTAX.contains(&s))
+                    .unwrap_or(false)
+            {
+                admin_error!(?name, ?syntax, "Syntax is not valid for a custom attribute");
+                return Err(OperationError::InvalidAttribute(match syntax {
+                    Some(s) => format!("syntax {} may not be used by custom attributes", s),
+                    None => "a syntax is required for custom attributes".to_string(),
+                }));
+            }
+
+            if let Some(scope) = post.get_ava_single_iutf8("unique_scope") {
+                if !post.get_ava_single_bool("unique").unwrap_or(false) {
+                    admin_error!(?name, "A unique scope requires the attribute to be unique");
+                    return Err(OperationError::InvalidAttribute(
+                        "unique_scope may only be set on unique attributes".to_string(),
+                    ));
+                }
+                if !schema.get_classes().contains_key(scope) {
+                    admin_error!(?name, ?scope, "Unique scope is not a known class");
+                    return Err(OperationError::InvalidAttribute(format!(
+                        "unique_scope {} is not a known class",
+                        scope
+                    )));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    // Deprecated attributes may only be retained or removed, and deprecated classes may
+    // not be added to an entry.
+    fn check_deprecated(
+        schema: &dyn SchemaTransaction,
+        pre: Option<&Eattrs>,
+        post: &Eattrs,
+    ) -> Result<(), OperationError> {
+        let attributes = schema.get_attributes();
+        for (attr, vs) in post.iter() {
+            if !attributes.get(attr).map(|a| a.deprecated).unwrap_or(false) {
+                continue;
+            }
+
+            let pre_vs = pre.and_then(|p| p.get(attr));
+            let added = vs
+                .to_partialvalue_iter()
+                .any(|pv| !pre_vs.map(|p| p.contains(&pv)).unwrap_or(false));
+
+            if added {
+                admin_error!(?attr, "Unable to add values to a deprecated attribute");
+                return Err(OperationError::SchemaViolation(
+                    SchemaError::DeprecatedAttribute(attr.to_string()),
+                ));
+            }
+        }
+
+        let classes = schema.get_classes();
+        let pre_classes = pre
+            .and_then(|p| p.get("class"))
+            .and_then(|vs| vs.as_iutf8_set());
+        let post_classes = post.get("class").and_then(|vs| vs.as_iutf8_set());
+
+        if let Some(post_classes) = post_classes {
+            for class in post_classes.iter() {
+                let added = !pre_classes.map(|p| p.contains(class)).unwrap_or(false);
+                if added
+                    && classes
+                        .get(class.as_str())
+                        .map(|c| c.deprecated)
+                        .unwrap_or(false)
+                {
+                    admin_error!(?class, "Unable to add a deprecated class to an entry");
+                    return Err(OperationError::SchemaViolation(
+                        SchemaError::DeprecatedClass(class.to_string()),
+                    ));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn pre_modify_inner(
+        qs: &mut QueryServerWriteTransaction,
+        pre_cand: &[Arc<EntrySealedCommitted>],
+        cand: &[EntryInvalidCommitted],
+    ) -> Result<(), OperationError> {
+        let schema = qs.get_schema();
+        pre_cand
+            .iter()
+            .zip(cand.iter())
+            .try_for_each(|(pre, post)| {
+                if is_schema_entry(post) {
+                    Self::validate_definition(schema, Some(pre.as_ref()), post)?;
+                }
+                Self::check_deprecated(schema, Some(pre.get_ava()), post.get_ava())
+            })
+    }
+
+    // Once schema has changed, re-check every entry that uses the altered attributes or
+    // classes against the new schema. If any no longer validate, the change is rejected.
+    fn verify_in_use<'a>(
+        qs: &mut QueryServerWriteTransaction,
+        changed: impl Iterator<Item = (Option<&'a EntrySealedCommitted>, &'a EntrySealedCommitted)>,
+    ) -> Result<(), OperationError> {
+        let mut terms = Vec::new();
+        let mut now_unique = Vec::new();
+
+        for (pre, post) in changed {
+            if post.attribute_equality("class", &PVCLASS_ATTRIBUTETYPE) {
+                if let Some(name) = post.get_ava_single_iutf8("attributename") {
+                    terms.push(f_pres(name));
+
+                    let was_unique = pre
+                        .and_then(|p| p.get_ava_single_bool("unique"))
+                        .unwrap_or(false);
+                    let pre_scope = pre.and_then(|p| p.get_ava_single_iutf8("unique_scope"));
+                    let scope = post.get_ava_single_iutf8("unique_scope");
+                    // Changing the scope may bring entries with the same value together.
+                    if post.get_ava_single_bool("unique").unwrap_or(false)
+                        && (!was_unique || pre_scope != scope)
+                    {
+                        now_unique.push((name.to_string(), scope.map(PartialValue::new_class)));
+                    }
+                }
+            } else if post.attribute_equality("class", &PVCLASS_CLASSTYPE) {
+                if let Some(name) = post.get_ava_single_iutf8("classname") {
+                    terms.push(f_eq("class", PartialValue::new_class(name)));
+                }
+            }
+        }
+
+        if terms.is_empty() {
+            return Ok(());
+        }
+
+        // Bring the schema up to date for this transaction so we validate against it.
+        qs.reload_schema()?;
+
+        let entries = qs.internal_search(filter!(f_or(terms)))?;
+
+        let cid = qs.get_txn_cid().clone();
+        let schema = qs.get_schema();
+        entries.iter().try_for_each(|e| {
+            e.as_ref()
+                .clone()
+                .invalidate(cid.clone())
+                .validate(schema)
+                .map(|_| ())
+                .map_err(|err| {
+                    admin_error!(
+                        ?err,
+                        entry = %e.get_display_id(),
+                        "Schema change would invalidate an existing entry"
+                    );
+                    OperationError::SchemaViolation(err)
+                })
+        })?;
+
+        // An attribute that has become unique must not already have duplicate values.
+        for (attr, scope) in now_unique.iter() {
+            let mut seen: BTreeMap<PartialValue, Uuid> = BTreeMap::new();
+            let in_scope = entries.iter().filter(|e| {
+                scope
+                    .as_ref()
+                    .map(|s| e.attribute_equality("class", s))
+                    .unwrap_or(true)
+            });
+            for e in in_scope {
+                let Some(vs) = e.get_ava_set(attr) else {
+                    continue;
+                };
+                for pv in vs.to_partialvalue_iter() {
+                    if let Some(other) = seen.insert(pv, e.get_uuid()) {
+                        admin_error!(
+                            ?attr,
+                            entry = %e.get_display_id(),
+                            ?other,
+                            "Unable to make attribute unique, duplicate values exist"
+                        );
+                        return Err(OperationError::Plugin(PluginError::AttrUnique(
+                            "duplicate value exists".to_string(),
+                        )));
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Plugin for SchemaGuard {
+    fn id() -> &'static str {
+        "plugin_schema_guard"
+    }
+
+    #[instrument(level = "debug", name = "schemaguard_pre_create", skip_all)]
+    fn pre_create(
+        qs: &mut QueryServerWriteTransaction,
+        cand: &[EntrySealedNew],
+        ce: &CreateEvent,
+    ) -> Result<(), OperationError> {
+        if ce.ident.is_internal() {
+            trace!("Internal operation, not enforcing schema guard");
+            return Ok(());
+        }
+
+        let schema = qs.get_schema();
+        cand.iter().try_for_each(|e| {
+            if is_schema_entry(e) {
+                Self::validate_definition(schema, None, e)?;
+            }
+            Self::check_deprecated(schema, None, e.get_ava())
+        })
+    }
+
+    #[instrument(level = "debug", name = "schemaguard_pre_modify", skip_all)]
+    fn pre_modify(
+        qs: &mut QueryServerWriteTransaction,
+        pre_cand: &[Arc<EntrySealedCommitted>],
+        cand: &mut Vec<EntryInvalidCommitted>,
+        me: &ModifyEvent,
+    ) -> Result<(), OperationError> {
+        if me.ident.is_internal() {
+            trace!("Internal operation, not enforcing schema guard");
+            return Ok(());
+        }
+        Self::pre_modify_inner(qs, pre_cand, cand)
+    }
+
+    #[instrument(level = "debug", name = "schemaguard_pre_batch_modify", skip_all)]
+    fn pre_batch_modify(
+        qs: &mut QueryServerWriteTransaction,
+        pre_cand: &[Arc<EntrySealedCommitted>],
+        cand: &mut Vec<EntryInvalidCommitted>,
+        me: &BatchModifyEvent,
+    ) -> Result<(), OperationError> {
+        if me.ident.is_internal() {
+            trace!("Internal operation, not enforcing schema guard");
+            return Ok(());
+        }
+        Self::pre_modify_inner(qs, pre_cand, cand)
+    }
+
+    #[instrument(level = "debug", name = "schemaguard_post_modify", skip_all)]
+    fn post_modify(
+        qs: &mut QueryServerWriteTransaction,
+        pre_cand: &[Arc<EntrySealedCommitted>],
+        cand: &[EntrySealedCommitted],
+        me: &ModifyEvent,
+    ) -> Result<(), OperationError> {
+        if me.ident.is_internal() {
+            return Ok(());
+        }
+        let changed = pre_cand
+            .iter()
+            .map(|pre| Some(pre.as_ref()))
+            .zip(cand.iter())
+            .filter(|(_, post)| is_schema_entry(*post));
+        Self::verify_in_use(qs, changed)
+    }
+
+    #[instrument(level = "debug", name = "schemaguard_post_batch_modify", skip_all)]
+    fn post_batch_modify(
+        qs: &mut QueryServerWriteTransaction,
+        pre_cand: &[Arc<EntrySealedCommitted>],
+        cand: &[EntrySealedCommitted],
+        me: &BatchModifyEvent,
+    ) -> Result<(), OperationError> {
+        if me.ident.is_internal() {
+            return Ok(());
+        }
+        let changed = pre_cand
+            .iter()
+            .map(|pre| Some(pre.as_ref()))
+            .zip(cand.iter())
+            .filter(|(_, post)| is_schema_entry(*post));
+        Self::verify_in_use(qs, changed)
+    }
+
+    #[instrument(level = "debug", name = "schemaguard_pre_delete", skip_all)]
+    fn pre_delete(
+        qs: &mut QueryServerWriteTransaction,
+        cand: &mut Vec<EntryInvalidCommitted>,
+        de: &DeleteEvent,
+    ) -> Result<(), OperationError> {
+        if de.ident.is_internal() {
+            trace!("Internal operation, not enforcing schema guard");
+            return Ok(());
+        }
+
+        cand.iter().try_for_each(|e| {
+            let filt = if let Some(name) = e.get_ava_single_iutf8("attributename") {
+                // The attribute may be in use by an entry, or by a class.
+                let pv = PartialValue::new_iutf8(name);
+                filter!(f_or!([
+                    f_pres(name),
+                    f_eq("systemmay", pv.clone()),
+                    f_eq("may", pv.clone()),
+                    f_eq("systemmust", pv.clone()),
+                    f_eq("must", pv)
+                ]))
+            } else if let Some(name) = e.get_ava_single_iutf8("classname") {
+                filter!(f_eq("class", PartialValue::new_class(name)))
+            } else {
+                return Ok(());
+            };
+
+            if qs.internal_exists(filt)? {
+                let name = schema_name(e).unwrap_or_default();
+                admin_error!(?name, "Unable to delete schema that is in use");
+                Err(OperationError::SchemaViolation(SchemaError::InUse(
+                    name.to_string(),
+                )))
+            } else {
+                Ok(())
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::event::{CreateEvent, DeleteEvent, ModifyEvent};
+    use crate::prelude::*;
+    use kanidm_proto::v1::{OperationError, PluginError, SchemaError};
+    use std::sync::Arc;
+
+    const UUID_TEST_PERSON: Uuid = uuid::uuid!("cc8e95b4-c24f-4d68-ba54-8bed76f63930");
+
+    fn attributetype(name: &str, syntax: SyntaxType, multivalue: bool) -> EntryInitNew {
+        entry_init!(
+            ("class", Value::new_class("object")),
+            ("class", Value::new_class("attributetype")),
+            ("attributename", Value::new_iutf8(name)),
+            ("description", Value::new_utf8s(name)),
+            ("syntax", Value::Syntax(syntax)),
+            ("multivalue", Value::new_bool(multivalue)),
+            ("unique", Value::new_bool(false))
+        )
+    }
+
+    // Allow system admins to manage employee details on people, so that these changes
+    // are subject to the same checks as any other administrator action.
+    fn setup(server_txn: &mut QueryServerWriteTransaction) -> Arc<EntrySealedCommitted> {
+        assert!(server_txn
+            .internal_create(vec![
+                attributetype("employeenumber", SyntaxType::Utf8String, false),
+                attributetype("costcentre", SyntaxType::Utf8StringInsensitive, true),
+                entry_init!(
+                    ("class", Value::new_class("object")),
+                    ("class", Value::new_class("classtype")),
+                    ("classname", Value::new_iutf8("employee")),
+                    ("description", Value::new_utf8s("employee")),
+                    ("may", Value::new_iutf8("employeenumber")),
+                    ("may", Value::new_iutf8("costcentre"))
+                ),
+            ])
+            .is_ok());
+        // The schema must be reloaded before it can be used.
+        assert!(server_txn.reload_schema().is_ok());
+
+        assert!(server_txn
+            .internal_create(vec![
+                entry_init!(
+                    ("class", Value::new_class("object")),
+                    ("class", Value::new_class("person")),
+                    ("class", Value::new_class("employee")),
+                    ("name", Value::new_iname("testperson1")),
+                    ("uuid", Value::Uuid(UUID_TEST_PERSON)),
+                    ("description", Value::new_utf8s("testperson1")),
+                    ("displayname", Value::new_utf8s("testperson1")),
+                    ("employeenumber", Value::new_utf8s("1234")),
+                    ("costcentre", Value::new_iutf8("finance")),
+                    ("costcentre", Value::new_iutf8("legal"))
+                ),
+                entry_init!(
+                    ("class", Value::new_class("object")),
+                    ("class", Value::new_class("access_control_profile")),
+                    ("class", Value::new_class("access_control_modify")),
+                    ("class", Value::new_class("access_control_search")),
+                    ("name", Value::new_iname("test_acp_employee_manage")),
+                    ("acp_receiver_group", Value::Refer(UUID_SYSTEM_ADMINS)),
+                    (
+                        "acp_targetscope",
+                        Value::new_json_filter_s("{\"eq\":[\"class\",\"person\"]}")
+                            .expect("filter")
+                    ),
+                    ("acp_search_attr", Value::new_iutf8("class")),
+                    ("acp_search_attr", Value::new_iutf8("uuid")),
+                    ("acp_search_attr", Value::new_iutf8("employeenumber")),
+                    ("acp_modify_class", Value::new_iutf8("employee")),
+                    ("acp_modify_presentattr", Value::new_iutf8("class")),
+                    ("acp_modify_presentattr", Value::new_iutf8("employeenumber")),
+                    ("acp_modify_removedattr", Value::new_iutf8("class")),
+                    ("acp_modify_removedattr", Value::new_iutf8("employeenumber")),
+                    ("acp_modify_removedattr", Value::new_iutf8("costcentre"))
+                ),
+            ])
+            .is_ok());
+        assert!(server_txn.reload().is_ok());
+
+        server_txn
+            .internal_search_uuid(UUID_ADMIN)
+            .expect("failed to find admin")
+    }
+
+    fn modify_schema(
+        server_txn: &mut QueryServerWriteTransaction,
+        admin: &Arc<EntrySealedCommitted>,
+        filter: Filter<FilterInvalid>,
+        modlist: ModifyList<ModifyInvalid>,
+    ) -> Result<(), OperationError> {
+        let me = ModifyEvent::new_impersonate_entry(admin.clone(), filter, modlist);
+        server_txn.modify(&me)
+    }
+
+    #[qs_test]
+    async fn test_schemaguard_create_definition(server: &QueryServer) {
+        let mut server_txn = server.write(duration_from_epoch_now()).await;
+        let admin = setup(&mut server_txn);
+        let ident = Identity::from_impersonate_entry_readwrite(admin);
+
+        let ce = CreateEvent::new_impersonate_identity(
+            ident.clone(),
+            vec![attributetype("badgeid", SyntaxType::Utf8String, false)],
+        );
+        assert!(server_txn.create(&ce).is_ok());
+
+        let ce = CreateEvent::new_impersonate_identity(
+            ident.clone(),
+            vec![attributetype("badge-id", SyntaxType::Utf8String, false)],
+        );
+        assert!(matches!(
+            server_txn.create(&ce),
+            Err(OperationError::InvalidAttributeName(_))
+        ));
+
+        let ce = CreateEvent::new_impersonate_identity(
+            ident,
+            vec![attributetype("badgecred", SyntaxType::Credential, false)],
+        );
+        assert!(matches!(
+            server_txn.create(&ce),
+            Err(OperationError::InvalidAttribute(_))
+        ));
+
+        assert!(server_txn.commit().is_ok());
+    }
+
+    #[qs_test]
+    async fn test_schemaguard_modify_in_use(server: &QueryServer) {
+        let mut server_txn = server.write(duration_from_epoch_now()).await;
+        let admin = setup(&mut server_txn);
+        assert!(server_txn.commit().is_ok());
+
+        let attr_filter =
+            |name: &str| filter!(f_eq("attributename", PartialValue::new_iutf8(name)));
+        let class_filter = filter!(f_eq("classname", PartialValue::new_iutf8("employee")));
+
+        // The value on the person would no longer be valid.
+        let mut server_txn = server.write(duration_from_epoch_now()).await;
+        assert!(matches!(
+            modify_schema(
+                &mut server_txn,
+                &admin,
+                attr_filter("employeenumber"),
+                ModifyList::new_purge_and_set("syntax", Value::Syntax(SyntaxType::Uint32)),
+            ),
+            Err(OperationError::SchemaViolation(
+                SchemaError::InvalidAttributeSyntax(_)
+            ))
+        ));
+        drop(server_txn);
+
+        // The person has two cost centres.
+        let mut server_txn = server.write(duration_from_epoch_now()).await;
+        assert!(matches!(
+            modify_schema(
+                &mut server_txn,
+                &admin,
+                attr_filter("costcentre"),
+                ModifyList::new_purge_and_set("multivalue", Value::new_bool(false)),
+            ),
+            Err(OperationError::SchemaViolation(
+                SchemaError::InvalidAttributeSyntax(_)
+            ))
+        ));
+        drop(server_txn);
+
+        // The person has an employee number, so this can be required.
+        let mut server_txn = server.write(duration_from_epoch_now()).await;
+        assert!(modify_schema(
+            &mut server_txn,
+            &admin,
+            class_filter.clone(),
+            ModifyList::new_list(vec![
+                Modify::Removed(
+                    AttrString::from("may"),
+                    PartialValue::new_iutf8("employeenumber")
+                ),
+                Modify::Present(AttrString::from("must"), Value::new_iutf8("employeenumber")),
+            ]),
+        )
+        .is_ok());
+        assert!(server_txn.commit().is_ok());
+
+        // But there is no badge id on the person.
+        let mut server_txn = server.write(duration_from_epoch_now()).await;
+        assert!(server_txn
+            .internal_create(vec![attributetype(
+                "badgeid",
+                SyntaxType::Utf8String,
+                false
+            )])
+            .is_ok());
+        assert!(server_txn.commit().is_ok());
+
+        let mut server_txn = server.write(duration_from_epoch_now()).await;
+        assert!(matches!(
+            modify_schema(
+                &mut server_txn,
+                &admin,
+                class_filter.clone(),
+                ModifyList::new_append("must", Value::new_iutf8("badgeid")),
+            ),
+            Err(OperationError::SchemaViolation(
+                SchemaError::MissingMustAttribute(_)
+            ))
+        ));
+        drop(server_txn);
+
+        // Removing costcentre from the class would leave it on the person.
+        let mut server_txn = server.write(duration_from_epoch_now()).await;
+        assert!(matches!(
+            modify_schema(
+                &mut server_txn,
+                &admin,
+                class_filter,
+                ModifyList::new_remove("may", PartialValue::new_iutf8("costcentre")),
+            ),
+            Err(OperationError::SchemaViolation(
+                SchemaError::AttributeNotValidForClass(_)
+            ))
+        ));
+        drop(server_txn);
+
+        // Nothing was changed by the failed modifications.
+        let mut server_txn = server.write(duration_from_epoch_now()).await;
+        let e = server_txn
+            .internal_search_uuid(UUID_TEST_PERSON)
+            .expect("failed to find person");
+        assert!(e.get_ava_single_utf8("employeenumber") == Some("1234"));
+        assert!(server_txn.commit().is_ok());
+    }
+
+    #[qs_test]
+    async fn test_schemaguard_deprecated(server: &QueryServer) {
+        let mut server_txn = server.write(duration_from_epoch_now()).await;
+        let admin = setup(&mut server_txn);
+
+        assert!(modify_schema(
+            &mut server_txn,
+            &admin,
+            filter!(f_eq(
+                "attributename",
+                PartialValue::new_iutf8("employeenumber")
+            )),
+            ModifyList::new_purge_and_set("deprecated", Value::new_bool(true)),
+        )
+        .is_ok());
+        assert!(server_txn.commit().is_ok());
+
+        let mut server_txn = server.write(duration_from_epoch_now()).await;
+        let person = filter!(f_eq("uuid", PartialValue::Uuid(UUID_TEST_PERSON)));
+
+        // New values can't be added.
+        assert!(matches!(
+            modify_schema(
+                &mut server_txn,
+                &admin,
+                person.clone(),
+                ModifyList::new_purge_and_set("employeenumber", Value::new_utf8s("5678")),
+            ),
+            Err(OperationError::SchemaViolation(
+                SchemaError::DeprecatedAttribute(_)
+            ))
+        ));
+
+        // But the existing value can be removed.
+        assert!(modify_schema(
+            &mut server_txn,
+            &admin,
+            person.clone(),
+            ModifyList::new_purge("employeenumber"),
+        )
+        .is_ok());
+
+        // Deprecated classes can be removed, but not added back.
+        assert!(modify_schema(
+            &mut server_txn,
+            &admin,
+            filter!(f_eq("classname", PartialValue::new_iutf8("employee"))),
+            ModifyList::new_purge_and_set("deprecated", Value::new_bool(true)),
+        )
+        .is_ok());
+
+        assert!(modify_schema(
+            &mut server_txn,
+            &admin,
+            person.clone(),
+            ModifyList::new_list(vec![
+                Modify::Removed(
+                    AttrString::from("class"),
+                    PartialValue::new_class("employee")
+                ),
+                Modify::Purged(AttrString::from("costcentre")),
+            ]),
+        )
+        .is_ok());
+
+        assert!(matches!(
+            modify_schema(
+                &mut server_txn,
+                &admin,
+                person,
+                ModifyList::new_append("class", Value::new_class("employee")),
+            ),
+            Err(OperationError::SchemaViolation(
+                SchemaError::DeprecatedClass(_)
+            ))
+        ));
+
+        assert!(server_txn.commit().is_ok());
+    }
+
+    #[qs_test]
+    async fn test_schemaguard_delete_in_use(server: &QueryServer) {
+        let mut server_txn = server.write(duration_from_epoch_now()).await;
+        let admin = setup(&mut server_txn);
+
+        assert!(server_txn
+            .internal_create(vec![entry_init!(
+                ("class", Value::new_class("object")),
+                ("class", Value::new_class("access_control_profile")),
+                ("class", Value::new_class("access_control_delete")),
+                ("name", Value::new_iname("test_acp_schema_delete")),
+                ("acp_receiver_group", Value::Refer(UUID_SYSTEM_ADMINS)),
+                (
+                    "acp_targetscope",
+                    Value::new_json_filter_s("{\"eq\":[\"class\",\"attributetype\"]}")
+                        .expect("filter")
+                )
+            )])
+            .is_ok());
+        assert!(server_txn.reload().is_ok());
+
+        let de = DeleteEvent::new_impersonate_identity(
+            Identity::from_impersonate_entry_readwrite(admin),
+            filter!(f_eq(
+                "attributename",
+                PartialValue::new_iutf8("employeenumber")
+            )),
+        );
+        assert!(matches!(
+            server_txn.delete(&de),
+            Err(OperationError::SchemaViolation(SchemaError::InUse(_)))
+        ));
+    }
+
+    #[qs_test]
+    async fn test_schemaguard_unique_scope(server: &QueryServer) {
+        let mut server_txn = server.write(duration_from_epoch_now()).await;
+        let admin = setup(&mut server_txn);
+        // A group that shares the employee number of the person.
+        assert!(server_txn
+            .internal_create(vec![entry_init!(
+                ("class", Value::new_class("object")),
+                ("class", Value::new_class("group")),
+                ("class", Value::new_class("employee")),
+                ("name", Value::new_iname("testgroup1")),
+                ("employeenumber", Value::new_utf8s("1234"))
+            )])
+            .is_ok());
+        assert!(server_txn.commit().is_ok());
+
+        let attr_filter =
+            |name: &str| filter!(f_eq("attributename", PartialValue::new_iutf8(name)));
+
+        // Only unique attributes may have a scope.
+        let mut server_txn = server.write(duration_from_epoch_now()).await;
+        assert!(matches!(
+            modify_schema(
+                &mut server_txn,
+                &admin,
+                attr_filter("costcentre"),
+                ModifyList::new_purge_and_set("unique_scope", Value::new_iutf8("person")),
+            ),
+            Err(OperationError::InvalidAttribute(_))
+        ));
+        drop(server_txn);
+
+        // The scope must be a class.
+        let mut server_txn = server.write(duration_from_epoch_now()).await;
+        assert!(matches!(
+            modify_schema(
+                &mut server_txn,
+                &admin,
+                attr_filter("employeenumber"),
+                ModifyList::new_list(vec![
+                    Modify::Purged(AttrString::from("unique")),
+                    Modify::Present(AttrString::from("unique"), Value::new_bool(true)),
+                    Modify::Present(
+                        AttrString::from("unique_scope"),
+                        Value::new_iutf8("nothing")
+                    ),
+                ]),
+            ),
+            Err(OperationError::InvalidAttribute(_))
+        ));
+        drop(server_txn);
+
+        // The employee number is only held by a single person.
+        let mut server_txn = server.write(duration_from_epoch_now()).await;
+        assert!(modify_schema(
+            &mut server_txn,
+            &admin,
+            attr_filter("employeenumber"),
+            ModifyList::new_list(vec![
+                Modify::Purged(AttrString::from("unique")),
+                Modify::Present(AttrString::from("unique"), Value::new_bool(true)),
+                Modify::Present(AttrString::from("unique_scope"), Value::new_iutf8("person")),
+            ]),
+        )
+        .is_ok());
+        assert!(server_txn.commit().is_ok());
+
+        // But throughout the database it is shared with the group.
+        let mut server_txn = server.write(duration_from_epoch_now()).await;
+        assert!(matches!(
+            modify_schema(
+                &mut server_txn,
+                &admin,
+                attr_filter("employeenumber"),
+                ModifyList::new_purge("unique_scope"),
+            ),
+            Err(OperationError::Plugin(PluginError::AttrUnique(_)))
+        ));
+        drop(server_txn);
+    }
+}
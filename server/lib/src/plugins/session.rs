@@ -5,9 +5,11 @@
 //! oauth2 session should also be terminated.
 //!
 //! This plugin is also responsible for invaliding old sessions that are past
-//! their expiry.
+//! their expiry, and for recording which oauth2 sessions have ended so that their
+//! resource servers can be sent a back-channel logout once the change commits.
 
-use crate::event::ModifyEvent;
+use crate::event::{DeleteEvent, ModifyEvent};
+use crate::idm::oauth2::Oauth2SessionEnded;
 use crate::plugins::Plugin;
 use crate::prelude::*;
 use std::collections::BTreeSet;
@@ -24,34 +26,82 @@ impl Plugin for SessionConsistency {
     #[instrument(level = "debug", name = "session_consistency", skip_all)]
     fn pre_modify(
         qs: &mut QueryServerWriteTransaction,
-        _pre_cand: &[Arc<EntrySealedCommitted>],
+        pre_cand: &[Arc<EntrySealedCommitted>],
         cand: &mut Vec<Entry<EntryInvalid, EntryCommitted>>,
         _me: &ModifyEvent,
     ) -> Result<(), OperationError> {
-        Self::modify_inner(qs, cand)
+        Self::modify_inner(qs, pre_cand, cand)
     }
 
     #[instrument(level = "debug", name = "session_consistency", skip_all)]
     fn pre_batch_modify(
         qs: &mut QueryServerWriteTransaction,
-        _pre_cand: &[Arc<EntrySealedCommitted>],
+        pre_cand: &[Arc<EntrySealedCommitted>],
         cand: &mut Vec<Entry<EntryInvalid, EntryCommitted>>,
         _me: &BatchModifyEvent,
     ) -> Result<(), OperationError> {
-        Self::modify_inner(qs, cand)
+        Self::modify_inner(qs, pre_cand, cand)
     }
+
+    #[instrument(level = "debug", name = "session_consistency", skip_all)]
+    fn post_modify(
+        qs: &mut QueryServerWriteTransaction,
+        pre_cand: &[Arc<EntrySealedCommitted>],
+        cand: &[EntrySealedCommitted],
+        _me: &ModifyEvent,
+    ) -> Result<(), OperationError> {
+        Self::record_ended(qs, pre_cand.iter().map(|pre| pre.as_ref()).zip(cand.iter()));
+        Ok(())
+    }
+
+    #[instrument(level = "debug", name = "session_consistency", skip_all)]
+    fn post_batch_modify(
+        qs: &mut QueryServerWriteTransaction,
+        pre_cand: &[Arc<EntrySealedCommitted>],
+        cand: &[EntrySealedCommitted],
+        _me: &BatchModifyEvent,
+    ) -> Result<(), OperationError> {
+        Self::record_ended(qs, pre_cand.iter().map(|pre| pre.as_ref()).zip(cand.iter()));
+        Ok(())
+    }
+
+    #[instrument(level = "debug", name = "session_consistency", skip_all)]
+    fn post_delete(
+        qs: &mut QueryServerWriteTransaction,
+        cand: &[EntrySealedCommitted],
+        _de: &DeleteEvent,
+    ) -> Result<(), OperationError> {
+        // A deleted account has no sessions left.
+        let curtime_odt = OffsetDateTime::UNIX_EPOCH + qs.get_curtime();
+        let ended: Vec<_> = cand
+            .iter()
+            .flat_map(|entry| Self::live_oauth2_sessions(entry, &BTreeSet::new(), curtime_odt))
+            .collect();
+        qs.oauth2_sessions_ended.extend(ended);
+        Ok(())
+    }
+}
+
+/// The ids of the sessions present before a change that are no longer present after it.
+fn ended_session_ids(pre: Option<BTreeSet<Uuid>>, post: Option<BTreeSet<Uuid>>) -> BTreeSet<Uuid> {
+    let post = post.unwrap_or_default();
+    pre.unwrap_or_default()
+        .into_iter()
+        .filter(|session_id| !post.contains(session_id))
+        .collect()
 }
 
 impl SessionConsistency {
     fn modify_inner<T: Clone + std::fmt::Debug>(
         qs: &mut QueryServerWriteTransaction,
+        pre_cand: &[Arc<EntrySealedCommitted>],
         cand: &mut [Entry<EntryInvalid, T>],
     ) -> Result<(), OperationError> {
         let curtime = qs.get_curtime();
         let curtime_odt = OffsetDateTime::UNIX_EPOCH + curtime;
 
         // We need to assert a number of properties. We must do these *in order*.
-        cand.iter_mut().try_for_each(|entry| {
+        pre_cand.iter().zip(cand.iter_mut()).try_for_each(|(pre, entry)| {
             // * If the session's credential is no longer on the account, we remove the session.
             let cred_ids: BTreeSet<Uuid> =
                 entry
@@ -102,27 +152,45 @@ impl SessionConsistency {
                 entry.remove_avas("user_auth_token_session", expired);
             }
 
+            // The sessions that were ended by this change, such as by a logout or a revoke.
+            let mut ended_parents = ended_session_ids(
+                pre.get_ava_as_session_map("user_auth_token_session").map(|m| m.keys().copied().collect()),
+                entry.get_ava_as_session_map("user_auth_token_session").map(|m| m.keys().copied().collect()),
+            );
+            ended_parents.extend(ended_session_ids(
+                pre.get_ava_as_oauth2session_map("oauth2_session").map(|m| m.keys().copied().collect()),
+                entry.get_ava_as_oauth2session_map("oauth2_session").map(|m| m.keys().copied().collect()),
+            ));
+
             // * If an oauth2 session is past it's expiry, remove it.
+            // * If an oauth2 session's parent session was just ended, remove it.
             // * If an oauth2 session is past the grace window, and no parent session exists, remove it.
+            // * If an oauth2 session's parent is an oauth2 session being removed, remove it.
             let oauth2_remove: Option<BTreeSet<_>> = entry.get_ava_as_oauth2session_map("oauth2_session").map(|oauth2_sessions| {
                 // If we have oauth2 sessions, we need to be able to lookup if sessions exist in the uat.
                 let sessions = entry.get_ava_as_session_map("user_auth_token_session");
 
-                oauth2_sessions.iter().filter_map(|(o2_session_id, session)| {
+                let mut remove: BTreeSet<Uuid> = oauth2_sessions.iter().filter_map(|(o2_session_id, session)| {
                     match &session.expiry {
                         Some(exp) if exp <= &curtime_odt => {
                             info!(%o2_session_id, "Removing expired oauth2 session");
-                            Some(PartialValue::Refer(*o2_session_id))
+                            Some(*o2_session_id)
+                        }
+                        _ if ended_parents.contains(&session.parent) => {
+                            info!(%o2_session_id, parent_id = %session.parent, "Removing oauth2 session whose parent session has ended");
+                            Some(*o2_session_id)
                         }
                         _ => {
                             // Okay, now check the issued / grace time for parent enforcement.
                             if session.issued_at + GRACE_WINDOW <= curtime_odt {
-                                if sessions.map(|s| s.contains_key(&session.parent)).unwrap_or(false) {
+                                // Sessions from a token exchange are children of an oauth2 session.
+                                if sessions.map(|s| s.contains_key(&session.parent)).unwrap_or(false)
+                                    || oauth2_sessions.contains_key(&session.parent) {
                                     // The parent exists, go ahead
                                     None
                                 } else {
                                     info!(%o2_session_id, parent_id = %session.parent, "Removing unbound oauth2 session");
-                                    Some(PartialValue::Refer(*o2_session_id))
+                                    Some(*o2_session_id)
                                 }
                             } else {
                                 // Grace window is still in effect
@@ -133,7 +201,29 @@ impl SessionConsistency {
                     }
 
                 })
-                .collect()
+                .collect();
+
+                // Cascade to the sessions exchanged from the sessions we are removing.
+                loop {
+                    let children: Vec<Uuid> = oauth2_sessions
+                        .iter()
+                        .filter(|(o2_session_id, session)| {
+                            !remove.contains(o2_session_id) && remove.contains(&session.parent)
+                        })
+                        .map(|(o2_session_id, _)| *o2_session_id)
+                        .collect();
+
+                    if children.is_empty() {
+                        break;
+                    }
+
+                    for o2_session_id in children {
+                        info!(%o2_session_id, "Removing oauth2 session whose parent oauth2 session was removed");
+                        remove.insert(o2_session_id);
+                    }
+                }
+
+                remove.into_iter().map(PartialValue::Refer).collect()
             });
 
             if let Some(oauth2_remove) = oauth2_remove.as_ref() {
@@ -143,6 +233,51 @@ impl SessionConsistency {
             Ok(())
         })
     }
+
+    /// The oauth2 sessions of an entry that have not expired, excluding those in `keep`.
+    fn live_oauth2_sessions(
+        entry: &EntrySealedCommitted,
+        keep: &BTreeSet<Uuid>,
+        curtime_odt: OffsetDateTime,
+    ) -> Vec<Oauth2SessionEnded> {
+        let account = entry.get_uuid();
+        entry
+            .get_ava_as_oauth2session_map("oauth2_session")
+            .map(|oauth2_sessions| {
+                oauth2_sessions
+                    .iter()
+                    .filter(|(session_id, session)| {
+                        !keep.contains(session_id)
+                            && session.expiry.map(|exp| exp > curtime_odt).unwrap_or(true)
+                    })
+                    .map(|(session_id, session)| Oauth2SessionEnded {
+                        account,
+                        rs_uuid: session.rs_uuid,
+                        session_id: *session_id,
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Record the oauth2 sessions that were removed by a modification. Sessions that
+    /// simply expired are not recorded, as the resource server already knows they ended.
+    fn record_ended<'b>(
+        qs: &mut QueryServerWriteTransaction,
+        changes: impl Iterator<Item = (&'b EntrySealedCommitted, &'b EntrySealedCommitted)>,
+    ) {
+        let curtime_odt = OffsetDateTime::UNIX_EPOCH + qs.get_curtime();
+        let ended: Vec<_> = changes
+            .flat_map(|(pre, post)| {
+                let remaining: BTreeSet<Uuid> = post
+                    .get_ava_as_oauth2session_map("oauth2_session")
+                    .map(|sessions| sessions.keys().copied().collect())
+                    .unwrap_or_default();
+                Self::live_oauth2_sessions(pre, &remaining, curtime_odt)
+            })
+            .collect();
+        qs.oauth2_sessions_ended.extend(ended);
+    }
 }
 
 #[cfg(test)]
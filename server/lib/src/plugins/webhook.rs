@@ -0,0 +1,524 @@
+//! The webhook plugin validates webhook subscriptions, and records the events that a
+//! write causes for each subscribed webhook. The recorded events are only delivered
+//! once the transaction commits.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::Arc;
+
+use kanidm_proto::v1::{Filter as ProtoFilter, WebhookEvent, WebhookEventType};
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+
+use crate::event::{CreateEvent, DeleteEvent, ModifyEvent};
+use crate::filter::FilterValidResolved;
+use crate::idm::webhook::WebhookDelivery;
+use crate::plugins::Plugin;
+use crate::prelude::*;
+use crate::utils::password_from_random;
+
+pub struct Webhook {}
+
+struct WebhookConfig {
+    uuid: Uuid,
+    url: Url,
+    secret: String,
+    events: BTreeSet<WebhookEventType>,
+    filter: Option<Filter<FilterValidResolved>>,
+}
+
+impl Plugin for Webhook {
+    fn id() -> &'static str {
+        "plugin_webhook"
+    }
+
+    #[instrument(level = "debug", name = "webhook_pre_create_transform", skip_all)]
+    fn pre_create_transform(
+        qs: &mut QueryServerWriteTransaction,
+        cand: &mut Vec<Entry<EntryInvalid, EntryNew>>,
+        _ce: &CreateEvent,
+    ) -> Result<(), OperationError> {
+        Self::modify_inner(qs, cand)
+    }
+
+    #[instrument(level = "debug", name = "webhook_pre_modify", skip_all)]
+    fn pre_modify(
+        qs: &mut QueryServerWriteTransaction,
+        _pre_cand: &[Arc<EntrySealedCommitted>],
+        cand: &mut Vec<Entry<EntryInvalid, EntryCommitted>>,
+        _me: &ModifyEvent,
+    ) -> Result<(), OperationError> {
+        Self::modify_inner(qs, cand)
+    }
+
+    #[instrument(level = "debug", name = "webhook_pre_batch_modify", skip_all)]
+    fn pre_batch_modify(
+        qs: &mut QueryServerWriteTransaction,
+        _pre_cand: &[Arc<EntrySealedCommitted>],
+        cand: &mut Vec<Entry<EntryInvalid, EntryCommitted>>,
+        _me: &BatchModifyEvent,
+    ) -> Result<(), OperationError> {
+        Self::modify_inner(qs, cand)
+    }
+
+    #[instrument(level = "debug", name = "webhook_post_create", skip_all)]
+    fn post_create(
+        qs: &mut QueryServerWriteTransaction,
+        cand: &[EntrySealedCommitted],
+        _ce: &CreateEvent,
+    ) -> Result<(), OperationError> {
+        Self::record(qs, cand.iter().map(|post| (None, Some(post))))
+    }
+
+    #[instrument(level = "debug", name = "webhook_post_modify", skip_all)]
+    fn post_modify(
+        qs: &mut QueryServerWriteTransaction,
+        pre_cand: &[Arc<EntrySealedCommitted>],
+        cand: &[EntrySealedCommitted],
+        _me: &ModifyEvent,
+    ) -> Result<(), OperationError> {
+        Self::record(
+            qs,
+            pre_cand
+                .iter()
+                .zip(cand.iter())
+                .map(|(pre, post)| (Some(pre.as_ref()), Some(post))),
+        )
+    }
+
+    #[instrument(level = "debug", name = "webhook_post_batch_modify", skip_all)]
+    fn post_batch_modify(
+        qs: &mut QueryServerWriteTransaction,
+        pre_cand: &[Arc<EntrySealedCommitted>],
+        cand: &[EntrySealedCommitted],
+        _me: &BatchModifyEvent,
+    ) -> Result<(), OperationError> {
+        Self::record(
+            qs,
+            pre_cand
+                .iter()
+                .zip(cand.iter())
+                .map(|(pre, post)| (Some(pre.as_ref()), Some(post))),
+        )
+    }
+
+    #[instrument(level = "debug", name = "webhook_post_delete", skip_all)]
+    fn post_delete(
+        qs: &mut QueryServerWriteTransaction,
+        cand: &[EntrySealedCommitted],
+        _de: &DeleteEvent,
+    ) -> Result<(), OperationError> {
+        Self::record(qs, cand.iter().map(|pre| (Some(pre), None)))
+    }
+}
+
+impl Webhook {
+    fn modify_inner<T: Clone>(
+        qs: &mut QueryServerWriteTransaction,
+        cand: &mut [Entry<EntryInvalid, T>],
+    ) -> Result<(), OperationError> {
+        let ident_internal = Identity::from_internal();
+
+        cand.iter_mut()
+            .filter(|e| e.attribute_equality("class", &PVCLASS_WEBHOOK))
+            .try_for_each(|e| {
+                if let Some(events) = e.get_ava_iter_iutf8("webhook_event") {
+                    for ev in events {
+                        if ev.parse::<WebhookEventType>().is_err() {
+                            return Err(OperationError::InvalidAttribute(format!(
+                                "{} is not a valid webhook event",
+                                ev
+                            )));
+                        }
+                    }
+                }
+
+                if let Some(scope_f) = e.get_ava_single_protofilter("webhook_filter") {
+                    Filter::from_rw(&ident_internal, scope_f, qs)?
+                        .validate(qs.get_schema())
+                        .map_err(OperationError::SchemaViolation)?;
+                }
+
+                if !e.attribute_pres("webhook_secret") {
+                    security_info!("regenerating webhook secret");
+                    e.add_ava("webhook_secret", Value::SecretValue(password_from_random()));
+                }
+
+                Ok(())
+            })
+    }
+
+    fn load_webhooks(
+        qs: &mut QueryServerWriteTransaction,
+    ) -> Result<Vec<WebhookConfig>, OperationError> {
+        let ident_internal = Identity::from_internal();
+
+        qs.internal_search(filter!(f_eq("class", PVCLASS_WEBHOOK.clone())))?
+            .iter()
+            .filter_map(|e| {
+                let url = e.get_ava_single_url("webhook_url").cloned();
+                let secret = e
+                    .get_ava_single_secret("webhook_secret")
+                    .map(str::to_string);
+                let Some((url, secret)) = url.zip(secret) else {
+                    admin_warn!(uuid = ?e.get_uuid(), "Ignoring webhook without a url or secret");
+                    return None;
+                };
+                let events = e
+                    .get_ava_iter_iutf8("webhook_event")
+                    .map(|iter| iter.filter_map(|ev| ev.parse().ok()).collect())
+                    .unwrap_or_default();
+                let scope_f: Option<ProtoFilter> =
+                    e.get_ava_single_protofilter("webhook_filter").cloned();
+                Some((e.get_uuid(), url, secret, events, scope_f))
+            })
+            .collect::<Vec<_>>()
+            .into_iter()
+            .map(|(uuid, url, secret, events, scope_f)| {
+                let filter = scope_f
+                    .map(|scope_f| {
+                        Filter::from_rw(&ident_internal, &scope_f, qs)?
+                            .validate(qs.get_schema())
+                            .map_err(OperationError::SchemaViolation)?
+                            .resolve(&ident_internal, None, Some(qs.get_resolve_filter_cache()))
+                    })
+                    .transpose()?;
+                Ok(WebhookConfig {
+                    uuid,
+                    url,
+                    secret,
+                    events,
+                    filter,
+                })
+            })
+            .collect()
+    }
+
+    /// Work out which events a change to an entry represents. `pre` is absent for created
+    /// entries, and `post` is absent for deleted entries.
+    fn changes(
+        pre: Option<&EntrySealedCommitted>,
+        post: Option<&EntrySealedCommitted>,
+        ct: OffsetDateTime,
+    ) -> Vec<(WebhookEventType, Vec<Uuid>, Vec<Uuid>)> {
+        let mut changes = Vec::new();
+        let is_account = |e: &EntrySealedCommitted| e.attribute_equality("class", &PVCLASS_ACCOUNT);
+
+        match (pre, post) {
+            (None, Some(post)) if is_account(post) => {
+                changes.push((WebhookEventType::AccountCreated, Vec::new(), Vec::new()))
+            }
+            (Some(pre), None) if is_account(pre) => {
+                changes.push((WebhookEventType::AccountDeleted, Vec::new(), Vec::new()))
+            }
+            (Some(pre), Some(post)) if is_account(post) => {
+                let pre_expire = pre.get_ava_single_datetime("account_expire");
+                let post_expire = post.get_ava_single_datetime("account_expire");
+                if pre_expire != post_expire && post_expire.map(|t| t <= ct).unwrap_or(false) {
+                    changes.push((WebhookEventType::AccountDisabled, Vec::new(), Vec::new()))
+                }
+            }
+            _ => {}
+        }
+
+        if let Some(post) = post {
+            if post.attribute_equality("class", &PVCLASS_GROUP) {
+                let empty = BTreeSet::new();
+                let pre_members = pre
+                    .and_then(|pre| pre.get_ava_refer("member"))
+                    .unwrap_or(&empty);
+                let post_members = post.get_ava_refer("member").unwrap_or(&empty);
+
+                let added: Vec<_> = post_members.difference(pre_members).copied().collect();
+                let removed: Vec<_> = pre_members.difference(post_members).copied().collect();
+                if !added.is_empty() || !removed.is_empty() {
+                    changes.push((WebhookEventType::GroupMembership, added, removed));
+                }
+            }
+        }
+
+        changes
+    }
+
+    fn record<'b>(
+        qs: &mut QueryServerWriteTransaction,
+        entries: impl Iterator<
+            Item = (
+                Option<&'b EntrySealedCommitted>,
+                Option<&'b EntrySealedCommitted>,
+            ),
+        >,
+    ) -> Result<(), OperationError> {
+        let ct = OffsetDateTime::UNIX_EPOCH + qs.get_curtime();
+
+        let mut changes = Vec::new();
+        let mut removed_from: BTreeMap<Uuid, Vec<Uuid>> = BTreeMap::new();
+        for (pre, post) in entries {
+            let Some(entry) = post.or(pre) else {
+                continue;
+            };
+            if post.is_none() {
+                // Referential integrity removes a deleted entry from its groups without
+                // running the plugins, so those membership changes are reported here.
+                if let Some(groups) = entry.get_ava_refer("directmemberof") {
+                    groups
+                        .iter()
+                        .for_each(|g| removed_from.entry(*g).or_default().push(entry.get_uuid()));
+                }
+            }
+            changes.extend(
+                Self::changes(pre, post, ct)
+                    .into_iter()
+                    .map(|change| (entry, change)),
+            );
+        }
+
+        if changes.is_empty() && removed_from.is_empty() {
+            return Ok(());
+        }
+
+        let webhooks = Self::load_webhooks(qs)?;
+        if webhooks.is_empty() {
+            return Ok(());
+        }
+
+        let time = ct.format(&Rfc3339).map_err(|e| {
+            admin_error!(?e, "Unable to format webhook event time");
+            OperationError::InvalidState
+        })?;
+
+        // A group that was deleted in the same operation can't be found, and has no
+        // membership left to report.
+        let groups: Vec<_> = removed_from
+            .into_iter()
+            .filter_map(|(g, removed)| {
+                qs.internal_search_uuid(g).ok().map(|group| {
+                    (
+                        group,
+                        (WebhookEventType::GroupMembership, Vec::new(), removed),
+                    )
+                })
+            })
+            .collect();
+
+        let deliveries: Vec<_> = changes
+            .into_iter()
+            .chain(
+                groups
+                    .iter()
+                    .map(|(group, change)| (group.as_ref(), change.clone())),
+            )
+            .flat_map(|(entry, (event, added, removed))| {
+                let time = &time;
+                webhooks
+                    .iter()
+                    .filter(move |webhook| webhook.events.contains(&event))
+                    .filter(move |webhook| {
+                        webhook
+                            .filter
+                            .as_ref()
+                            .map(|filter| entry.entry_match_no_index(filter))
+                            .unwrap_or(true)
+                    })
+                    .map(move |webhook| WebhookDelivery {
+                        webhook: webhook.uuid,
+                        url: webhook.url.clone(),
+                        secret: webhook.secret.clone(),
+                        event: WebhookEvent {
+                            id: Uuid::new_v4(),
+                            event,
+                            uuid: entry.get_uuid(),
+                            spn: entry.get_ava_single_proto_string("spn"),
+                            members_added: added.clone(),
+                            members_removed: removed.clone(),
+                            time: time.clone(),
+                        },
+                    })
+            })
+            .collect();
+
+        qs.webhook_pending.extend(deliveries);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use kanidm_proto::v1::WebhookEventType;
+
+    use crate::prelude::*;
+    use crate::testkit::test_person;
+
+    const UUID_TEST_WEBHOOK: Uuid = uuid!("2b0e5e39-3d5a-4a52-9f0c-7c0c6c4f7e01");
+    const UUID_TEST_PERSON: Uuid = uuid!("2b0e5e39-3d5a-4a52-9f0c-7c0c6c4f7e02");
+    const UUID_TEST_GROUP: Uuid = uuid!("2b0e5e39-3d5a-4a52-9f0c-7c0c6c4f7e03");
+
+    fn webhook(events: &[&str]) -> EntryInitNew {
+        let mut e = entry_init!(
+            ("class", Value::new_class("object")),
+            ("class", Value::new_class("webhook")),
+            ("name", Value::new_iname("test_webhook")),
+            ("uuid", Value::Uuid(UUID_TEST_WEBHOOK)),
+            (
+                "webhook_url",
+                Value::new_url_s("https://hooks.example.com/kanidm").unwrap()
+            )
+        );
+        events
+            .iter()
+            .for_each(|ev| e.add_ava("webhook_event", Value::new_iutf8(ev)));
+        e
+    }
+
+    #[qs_test]
+    async fn test_webhook_validate(server: &QueryServer) {
+        let mut server_txn = server.write(duration_from_epoch_now()).await;
+
+        // Unknown event types are rejected.
+        assert!(matches!(
+            server_txn.internal_create(vec![webhook(&["account_renamed"])]),
+            Err(OperationError::InvalidAttribute(_))
+        ));
+
+        // So are filters on attributes that don't exist.
+        let mut e = webhook(&["account_created"]);
+        e.add_ava(
+            "webhook_filter",
+            Value::new_json_filter_s("{\"eq\":[\"not_an_attribute\",\"a\"]}").unwrap(),
+        );
+        assert!(server_txn.internal_create(vec![e]).is_err());
+
+        // A valid webhook has a secret generated.
+        assert!(server_txn
+            .internal_create(vec![webhook(&["account_created"])])
+            .is_ok());
+        let e = server_txn
+            .internal_search_uuid(UUID_TEST_WEBHOOK)
+            .expect("failed");
+        assert!(e.get_ava_single_secret("webhook_secret").is_some());
+
+        assert!(server_txn.commit().is_ok());
+    }
+
+    #[qs_test]
+    async fn test_webhook_record_events(server: &QueryServer) {
+        let mut server_txn = server.write(duration_from_epoch_now()).await;
+
+        let mut e = webhook(&["account_created", "account_disabled", "group_membership"]);
+        e.add_ava(
+            "webhook_filter",
+            Value::new_json_filter_s(
+                "{\"or\":[{\"eq\":[\"class\",\"person\"]},{\"eq\":[\"name\",\"test_group\"]}]}",
+            )
+            .unwrap(),
+        );
+        assert!(server_txn.internal_create(vec![e]).is_ok());
+        // Creating the webhook itself is not an event.
+        assert!(server_txn.webhook_pending.is_empty());
+
+        let group = entry_init!(
+            ("class", Value::new_class("object")),
+            ("class", Value::new_class("group")),
+            ("name", Value::new_iname("test_group")),
+            ("uuid", Value::Uuid(UUID_TEST_GROUP))
+        );
+        assert!(server_txn
+            .internal_create(vec![test_person("test_person", UUID_TEST_PERSON), group])
+            .is_ok());
+
+        let pending = std::mem::take(&mut server_txn.webhook_pending);
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].event.event, WebhookEventType::AccountCreated);
+        assert_eq!(pending[0].event.uuid, UUID_TEST_PERSON);
+        assert!(pending[0]
+            .event
+            .spn
+            .as_deref()
+            .map(|spn| spn.starts_with("test_person@"))
+            .unwrap_or(false));
+
+        // Adding a member is reported with the added uuid.
+        assert!(server_txn
+            .internal_modify_uuid(
+                UUID_TEST_GROUP,
+                &ModifyList::new_append("member", Value::Refer(UUID_TEST_PERSON))
+            )
+            .is_ok());
+        let pending = std::mem::take(&mut server_txn.webhook_pending);
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].event.event, WebhookEventType::GroupMembership);
+        assert_eq!(pending[0].event.uuid, UUID_TEST_GROUP);
+        assert_eq!(pending[0].event.members_added, vec![UUID_TEST_PERSON]);
+        assert!(pending[0].event.members_removed.is_empty());
+
+        // Setting an expiry in the future is not a disable.
+        let ct = server_txn.get_curtime();
+        assert!(server_txn
+            .internal_modify_uuid(
+                UUID_TEST_PERSON,
+                &ModifyList::new_purge_and_set(
+                    "account_expire",
+                    Value::new_datetime_epoch(ct + Duration::from_secs(86400))
+                )
+            )
+            .is_ok());
+        assert!(server_txn.webhook_pending.is_empty());
+
+        // But expiring it now is.
+        assert!(server_txn
+            .internal_modify_uuid(
+                UUID_TEST_PERSON,
+                &ModifyList::new_purge_and_set("account_expire", Value::new_datetime_epoch(ct))
+            )
+            .is_ok());
+        let pending = std::mem::take(&mut server_txn.webhook_pending);
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].event.event, WebhookEventType::AccountDisabled);
+
+        // Deleting the person removes it from the group, but the webhook didn't ask
+        // for deletes.
+        assert!(server_txn.internal_delete_uuid(UUID_TEST_PERSON).is_ok());
+        let pending = std::mem::take(&mut server_txn.webhook_pending);
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].event.event, WebhookEventType::GroupMembership);
+        assert_eq!(pending[0].event.members_removed, vec![UUID_TEST_PERSON]);
+
+        assert!(server_txn.commit().is_ok());
+    }
+
+    #[qs_test]
+    async fn test_webhook_deliver_on_commit(server: &QueryServer) {
+        let (webhook_tx, mut webhook_rx) = tokio::sync::mpsc::unbounded_channel();
+
+        let mut server_txn = server.write(duration_from_epoch_now()).await;
+        server_txn.webhook_tx = Some(webhook_tx.clone());
+        assert!(server_txn
+            .internal_create(vec![webhook(&["account_created"])])
+            .is_ok());
+        assert!(server_txn.commit().is_ok());
+
+        // Events of a transaction that doesn't commit are never delivered.
+        let mut server_txn = server.write(duration_from_epoch_now()).await;
+        server_txn.webhook_tx = Some(webhook_tx.clone());
+        assert!(server_txn
+            .internal_create(vec![test_person("test_person", UUID_TEST_PERSON)])
+            .is_ok());
+        assert_eq!(server_txn.webhook_pending.len(), 1);
+        drop(server_txn);
+        assert!(webhook_rx.try_recv().is_err());
+
+        // Events are only sent once the query server transaction commits.
+        let mut server_txn = server.write(duration_from_epoch_now()).await;
+        server_txn.webhook_tx = Some(webhook_tx);
+        assert!(server_txn
+            .internal_create(vec![test_person("test_person", UUID_TEST_PERSON)])
+            .is_ok());
+        assert!(webhook_rx.try_recv().is_err());
+        assert!(server_txn.commit().is_ok());
+
+        let delivery = webhook_rx.try_recv().expect("No webhook delivery");
+        assert_eq!(delivery.webhook, UUID_TEST_WEBHOOK);
+        assert_eq!(delivery.event.event, WebhookEventType::AccountCreated);
+        assert_eq!(delivery.event.uuid, UUID_TEST_PERSON);
+        assert!(webhook_rx.try_recv().is_err());
+    }
+}
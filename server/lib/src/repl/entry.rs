@@ -108,6 +108,14 @@ impl EntryChangeState {
         }
     }
 
+    /// The cid of the last change to this attribute, if the entry is live.
+    pub(crate) fn get_attr_cid(&self, attr: &str) -> Option<&Cid> {
+        match &self.st {
+            State::Live { at: _, changes } => changes.get(attr),
+            State::Tombstone { .. } => None,
+        }
+    }
+
     pub fn tombstone(&mut self, cid: &Cid) {
         match &mut self.st {
             State::Live { at: _, changes: _ } => self.st = State::Tombstone { at: cid.clone() },
@@ -52,7 +52,7 @@ impl From<&ReplCidV1> for Cid {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct ReplCidRange {
     #[serde(rename = "m")]
     pub ts_min: Duration,
@@ -225,6 +225,13 @@ pub struct ReplOauthScopeMapV1 {
     pub data: BTreeSet<String>,
 }
 
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct ReplMemberValidityV1 {
+    pub refer: Uuid,
+    pub not_before: Option<String>,
+    pub not_after: Option<String>,
+}
+
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
 pub struct ReplOauth2SessionV1 {
     pub refer: Uuid,
@@ -390,6 +397,12 @@ pub enum ReplAttrV1 {
     AuditLogString {
         set: Vec<(Cid, String)>,
     },
+    PasswordHistory {
+        set: Vec<(Cid, ReplPasswordV1)>,
+    },
+    MemberValidity {
+        set: Vec<ReplMemberValidityV1>,
+    },
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
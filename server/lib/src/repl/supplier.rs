@@ -76,6 +76,14 @@ impl<'a> QueryServerReadTransaction<'a> {
             OperationError::Backend
         })?;
 
+        // Outbound messages are delivered by the server that queued them, and may contain
+        // secrets such as reset tokens, so they never leave it. Once they are tombstoned
+        // they are no longer outbound messages and replicate as normal.
+        let entries: Vec<_> = entries
+            .into_iter()
+            .filter(|e| !e.attribute_equality("class", &PVCLASS_OUTBOUND_MESSAGE))
+            .collect();
+
         // Separate the entries into schema, meta and remaining.
         let (schema_entries, rem_entries): (Vec<_>, Vec<_>) = entries.into_iter().partition(|e| {
             e.get_ava_set("class")
@@ -172,20 +180,24 @@ impl<'a> QueryServerReadTransaction<'a> {
             f_eq("uuid", PVUUID_SYSTEM_CONFIG.clone()),
         ]));
 
-        let entry_filter = filter_all!(f_or!([
-            f_and!([
-                f_pres("class"),
-                f_andnot(f_or(vec![
-                    // These are from above!
-                    f_eq("class", PVCLASS_ATTRIBUTETYPE.clone()),
-                    f_eq("class", PVCLASS_CLASSTYPE.clone()),
-                    f_eq("uuid", PVUUID_DOMAIN_INFO.clone()),
-                    f_eq("uuid", PVUUID_SYSTEM_INFO.clone()),
-                    f_eq("uuid", PVUUID_SYSTEM_CONFIG.clone()),
-                ])),
+        let entry_filter = filter_all!(f_and!([
+            f_or!([
+                f_and!([
+                    f_pres("class"),
+                    f_andnot(f_or(vec![
+                        // These are from above!
+                        f_eq("class", PVCLASS_ATTRIBUTETYPE.clone()),
+                        f_eq("class", PVCLASS_CLASSTYPE.clone()),
+                        f_eq("uuid", PVUUID_DOMAIN_INFO.clone()),
+                        f_eq("uuid", PVUUID_SYSTEM_INFO.clone()),
+                        f_eq("uuid", PVUUID_SYSTEM_CONFIG.clone()),
+                    ])),
+                ]),
+                f_eq("class", PVCLASS_TOMBSTONE.clone()),
+                f_eq("class", PVCLASS_RECYCLED.clone()),
             ]),
-            f_eq("class", PVCLASS_TOMBSTONE.clone()),
-            f_eq("class", PVCLASS_RECYCLED.clone()),
+            // Outbound messages stay on the server that queued them.
+            f_andnot(f_eq("class", PVCLASS_OUTBOUND_MESSAGE.clone())),
         ]));
 
         let schema_entries = self
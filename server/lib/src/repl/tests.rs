@@ -5,6 +5,7 @@ use crate::repl::entry::State;
 use crate::repl::proto::ReplIncrementalContext;
 use crate::repl::ruv::ReplicationUpdateVectorTransaction;
 use crate::repl::ruv::{RangeDiffStatus, ReplicationUpdateVector};
+use crate::schema::SchemaTransaction;
 use std::collections::BTreeMap;
 
 fn repl_initialise(
@@ -454,6 +455,90 @@ async fn test_repl_increment_basic_entry_tombstone(server_a: &QueryServer, serve
     drop(server_b_txn);
 }
 
+// Test that outbound messages are not replicated, and that their tombstones are.
+#[qs_pair_test]
+async fn test_repl_increment_outbound_message(server_a: &QueryServer, server_b: &QueryServer) {
+    let ct = duration_from_epoch_now();
+
+    let mut server_a_txn = server_a.write(ct).await;
+    let mut server_b_txn = server_b.read().await;
+
+    assert!(repl_initialise(&mut server_b_txn, &mut server_a_txn)
+        .and_then(|_| server_a_txn.commit())
+        .is_ok());
+    drop(server_b_txn);
+
+    // Queue a message on B.
+    let mut server_b_txn = server_b.write(ct).await;
+    let t_uuid = Uuid::new_v4();
+    assert!(server_b_txn
+        .internal_create(vec![entry_init!(
+            ("class", Value::new_class("object")),
+            ("class", Value::new_class("outbound_message")),
+            ("uuid", Value::Uuid(t_uuid)),
+            (
+                "mail_destination",
+                Value::new_utf8s("testperson1@example.com")
+            ),
+            ("message_template", Value::new_secret_str("reset token")),
+            ("send_after", Value::new_datetime_epoch(ct))
+        ),])
+        .is_ok());
+    server_b_txn.commit().expect("Failed to commit");
+
+    // It is not sent incrementally.
+    let mut server_a_txn = server_a.write(ct).await;
+    let mut server_b_txn = server_b.read().await;
+
+    repl_incremental(&mut server_b_txn, &mut server_a_txn);
+
+    assert_eq!(
+        server_a_txn.internal_search_all_uuid(t_uuid),
+        Err(OperationError::NoMatchingEntries)
+    );
+    server_a_txn.commit().expect("Failed to commit");
+
+    // Nor in a refresh.
+    let mut server_a_txn = server_a.write(ct).await;
+    assert!(repl_initialise(&mut server_b_txn, &mut server_a_txn).is_ok());
+    assert_eq!(
+        server_a_txn.internal_search_all_uuid(t_uuid),
+        Err(OperationError::NoMatchingEntries)
+    );
+    server_a_txn.commit().expect("Failed to commit");
+    drop(server_b_txn);
+
+    // Once delivered, the message is deleted and eventually becomes a tombstone.
+    let mut server_b_txn = server_b.write(ct).await;
+    assert!(server_b_txn.internal_delete_uuid(t_uuid).is_ok());
+    server_b_txn.commit().expect("Failed to commit");
+
+    let ct = ct + Duration::from_secs(RECYCLEBIN_MAX_AGE + 1);
+
+    let mut server_b_txn = server_b.write(ct).await;
+    assert!(server_b_txn.purge_recycled().is_ok());
+    server_b_txn.commit().expect("Failed to commit");
+
+    let mut server_a_txn = server_a.write(ct).await;
+    let mut server_b_txn = server_b.read().await;
+
+    repl_incremental(&mut server_b_txn, &mut server_a_txn);
+
+    let e1 = server_a_txn
+        .internal_search_all_uuid(t_uuid)
+        .expect("Unable to access new entry.");
+    let e2 = server_b_txn
+        .internal_search_all_uuid(t_uuid)
+        .expect("Unable to access entry.");
+
+    assert!(e1.attribute_equality("class", &PVCLASS_TOMBSTONE));
+
+    assert!(e1 == e2);
+
+    server_a_txn.commit().expect("Failed to commit");
+    drop(server_b_txn);
+}
+
 // Test that adding an entry -> tombstone then the tombstone is trimmed raises
 // a replication error.
 #[qs_pair_test]
@@ -1682,6 +1767,180 @@ async fn test_repl_increment_consumer_lagging_attributes(
 // Test change of a domain name over incremental.
 
 // Test schema addition / change over incremental.
+#[qs_pair_test]
+async fn test_repl_increment_schema_addition(server_a: &QueryServer, server_b: &QueryServer) {
+    let ct = duration_from_epoch_now();
+
+    let mut server_a_txn = server_a.write(ct).await;
+    let mut server_b_txn = server_b.read().await;
+
+    assert!(repl_initialise(&mut server_b_txn, &mut server_a_txn)
+        .and_then(|_| server_a_txn.commit())
+        .is_ok());
+    drop(server_b_txn);
+
+    // Add a custom indexed attribute and a class that uses it.
+    let mut server_b_txn = server_b.write(ct).await;
+    assert!(server_b_txn
+        .internal_create(vec![
+            entry_init!(
+                ("class", Value::new_class("object")),
+                ("class", Value::new_class("attributetype")),
+                ("attributename", Value::new_iutf8("badgeid")),
+                ("description", Value::new_utf8s("badgeid")),
+                ("syntax", Value::Syntax(SyntaxType::Utf8StringInsensitive)),
+                ("multivalue", Value::new_bool(false)),
+                ("unique", Value::new_bool(true)),
+                ("index", Value::Index(IndexType::Equality))
+            ),
+            entry_init!(
+                ("class", Value::new_class("object")),
+                ("class", Value::new_class("classtype")),
+                ("classname", Value::new_iutf8("badgeholder")),
+                ("description", Value::new_utf8s("badgeholder")),
+                ("must", Value::new_iutf8("badgeid"))
+            ),
+        ])
+        .is_ok());
+    server_b_txn.commit().expect("Failed to commit");
+
+    // And an entry that uses the new schema.
+    let mut server_b_txn = server_b.write(ct).await;
+    let t_uuid = Uuid::new_v4();
+    assert!(server_b_txn
+        .internal_create(vec![entry_init!(
+            ("class", Value::new_class("object")),
+            ("class", Value::new_class("person")),
+            ("class", Value::new_class("badgeholder")),
+            ("name", Value::new_iname("testperson1")),
+            ("uuid", Value::Uuid(t_uuid)),
+            ("description", Value::new_utf8s("testperson1")),
+            ("displayname", Value::new_utf8s("testperson1")),
+            ("badgeid", Value::new_iutf8("b1234"))
+        ),])
+        .is_ok());
+    server_b_txn.commit().expect("Failed to commit");
+
+    let mut server_a_txn = server_a.write(ct).await;
+    let mut server_b_txn = server_b.read().await;
+
+    repl_incremental(&mut server_b_txn, &mut server_a_txn);
+
+    let e1 = server_a_txn
+        .internal_search_all_uuid(t_uuid)
+        .expect("Unable to access entry.");
+    let e2 = server_b_txn
+        .internal_search_all_uuid(t_uuid)
+        .expect("Unable to access entry.");
+
+    assert!(e1 == e2);
+
+    // The schema is now known on A, and the new index is usable.
+    assert!(server_a_txn
+        .get_schema()
+        .get_classes()
+        .contains_key("badgeholder"));
+    let r = server_a_txn
+        .internal_search(filter!(f_eq("badgeid", PartialValue::new_iutf8("b1234"))))
+        .expect("Unable to search");
+    assert!(r.len() == 1);
+
+    server_a_txn.commit().expect("Failed to commit");
+    drop(server_b_txn);
+}
+
+// Test that when two nodes create entries with the same unique value, the later
+// writer becomes a conflict on both nodes.
+#[qs_pair_test]
+async fn test_repl_increment_attrunique_conflict(server_a: &QueryServer, server_b: &QueryServer) {
+    let ct = duration_from_epoch_now();
+
+    let mut server_a_txn = server_a.write(ct).await;
+    let mut server_b_txn = server_b.read().await;
+
+    assert!(repl_initialise(&mut server_b_txn, &mut server_a_txn)
+        .and_then(|_| server_a_txn.commit())
+        .is_ok());
+    drop(server_b_txn);
+
+    let person = |uuid: Uuid| {
+        entry_init!(
+            ("class", Value::new_class("object")),
+            ("class", Value::new_class("person")),
+            ("name", Value::new_iname("testperson1")),
+            ("uuid", Value::Uuid(uuid)),
+            ("description", Value::new_utf8s("testperson1")),
+            ("displayname", Value::new_utf8s("testperson1"))
+        )
+    };
+
+    // A writes first, so its entry retains the name.
+    let a_uuid = Uuid::new_v4();
+    let mut server_a_txn = server_a.write(ct).await;
+    assert!(server_a_txn.internal_create(vec![person(a_uuid)]).is_ok());
+    server_a_txn.commit().expect("Failed to commit");
+
+    let ct = ct + Duration::from_secs(1);
+    let b_uuid = Uuid::new_v4();
+    let mut server_b_txn = server_b.write(ct).await;
+    assert!(server_b_txn.internal_create(vec![person(b_uuid)]).is_ok());
+    server_b_txn.commit().expect("Failed to commit");
+
+    // B -> A - the incoming entry is the conflict.
+    let mut server_a_txn = server_a.write(ct).await;
+    let mut server_b_txn = server_b.read().await;
+
+    trace!("========================================");
+    repl_incremental(&mut server_b_txn, &mut server_a_txn);
+
+    let e = server_a_txn
+        .internal_search_all_uuid(b_uuid)
+        .expect("Unable to access entry.");
+    assert!(e.attribute_equality("class", &PVCLASS_CONFLICT));
+    assert!(server_a_txn.internal_search_uuid(a_uuid).is_ok());
+
+    server_a_txn.commit().expect("Failed to commit");
+    drop(server_b_txn);
+
+    // A -> B - the existing entry on B is moved to a conflict.
+    let ct = ct + Duration::from_secs(1);
+    let mut server_a_txn = server_a.read().await;
+    let mut server_b_txn = server_b.write(ct).await;
+
+    trace!("========================================");
+    repl_incremental(&mut server_a_txn, &mut server_b_txn);
+
+    let e = server_b_txn
+        .internal_search_all_uuid(b_uuid)
+        .expect("Unable to access entry.");
+    assert!(e.attribute_equality("class", &PVCLASS_CONFLICT));
+    assert!(server_b_txn.internal_search_uuid(a_uuid).is_ok());
+
+    server_b_txn.commit().expect("Failed to commit");
+    drop(server_a_txn);
+
+    // B -> A - the conflict state is sent back, and both nodes agree.
+    let mut server_a_txn = server_a.write(ct).await;
+    let mut server_b_txn = server_b.read().await;
+
+    trace!("========================================");
+    repl_incremental(&mut server_b_txn, &mut server_a_txn);
+
+    let e = server_a_txn
+        .internal_search_all_uuid(b_uuid)
+        .expect("Unable to access entry.");
+    assert!(e.attribute_equality("class", &PVCLASS_CONFLICT));
+    let r = server_a_txn
+        .internal_search(filter!(f_eq(
+            "name",
+            PartialValue::new_iname("testperson1")
+        )))
+        .expect("Unable to search");
+    assert!(r.len() == 1 && r[0].get_uuid() == a_uuid);
+
+    server_a_txn.commit().expect("Failed to commit");
+    drop(server_b_txn);
+}
 
 // Test change of domain version over incremental.
 
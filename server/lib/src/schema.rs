@@ -47,7 +47,7 @@ use crate::valueset::ValueSet;
 pub struct Schema {
     classes: CowCell<HashMap<AttrString, SchemaClass>>,
     attributes: CowCell<HashMap<AttrString, SchemaAttribute>>,
-    unique_cache: CowCell<Vec<AttrString>>,
+    unique_cache: CowCell<Vec<SchemaAttribute>>,
     ref_cache: CowCell<HashMap<AttrString, SchemaAttribute>>,
 }
 
@@ -58,7 +58,7 @@ pub struct SchemaWriteTransaction<'a> {
     classes: CowCellWriteTxn<'a, HashMap<AttrString, SchemaClass>>,
     attributes: CowCellWriteTxn<'a, HashMap<AttrString, SchemaAttribute>>,
 
-    unique_cache: CowCellWriteTxn<'a, Vec<AttrString>>,
+    unique_cache: CowCellWriteTxn<'a, Vec<SchemaAttribute>>,
     ref_cache: CowCellWriteTxn<'a, HashMap<AttrString, SchemaAttribute>>,
 }
 
@@ -67,7 +67,7 @@ pub struct SchemaReadTransaction {
     classes: CowCellReadTxn<HashMap<AttrString, SchemaClass>>,
     attributes: CowCellReadTxn<HashMap<AttrString, SchemaAttribute>>,
 
-    unique_cache: CowCellReadTxn<Vec<AttrString>>,
+    unique_cache: CowCellReadTxn<Vec<SchemaAttribute>>,
     ref_cache: CowCellReadTxn<HashMap<AttrString, SchemaAttribute>>,
 }
 
@@ -93,6 +93,10 @@ pub struct SchemaAttribute {
     pub replicated: bool,
     pub index: Vec<IndexType>,
     pub syntax: SyntaxType,
+    /// A deprecated attribute may remain on existing entries, but new values may not be added.
+    pub deprecated: bool,
+    /// When set, a unique attribute is only required to be unique amongst entries of this class.
+    pub unique_scope: Option<AttrString>,
 }
 
 impl SchemaAttribute {
@@ -158,6 +162,12 @@ impl SchemaAttribute {
             OperationError::InvalidSchemaState("missing syntax".to_string())
         })?;
 
+        let deprecated = value.get_ava_single_bool("deprecated").unwrap_or(false);
+
+        let unique_scope = value
+            .get_ava_single_iutf8("unique_scope")
+            .map(AttrString::from);
+
         Ok(SchemaAttribute {
             name,
             uuid,
@@ -169,6 +179,8 @@ impl SchemaAttribute {
             replicated,
             index,
             syntax,
+            deprecated,
+            unique_scope,
         })
     }
 
@@ -213,6 +225,8 @@ impl SchemaAttribute {
             // Comparing on the label.
             SyntaxType::TotpSecret => matches!(v, PartialValue::Utf8(_)),
             SyntaxType::AuditLogString => matches!(v, PartialValue::Utf8(_)),
+            SyntaxType::PasswordHistory => matches!(v, PartialValue::Cid(_)),
+            SyntaxType::MemberValidity => matches!(v, PartialValue::Refer(_)),
         };
         if r {
             Ok(())
@@ -263,6 +277,8 @@ impl SchemaAttribute {
                 SyntaxType::UiHint => matches!(v, Value::UiHint(_)),
                 SyntaxType::TotpSecret => matches!(v, Value::TotpSecret(_, _)),
                 SyntaxType::AuditLogString => matches!(v, Value::Utf8(_)),
+                SyntaxType::PasswordHistory => matches!(v, Value::PasswordHistory(_, _)),
+                SyntaxType::MemberValidity => matches!(v, Value::MemberValidity(_, _)),
             };
         if r {
             Ok(())
@@ -339,6 +355,8 @@ pub struct SchemaClass {
     /// A list of classes that can not co-exist with this item at the same time.
     pub systemexcludes: Vec<AttrString>,
     pub excludes: Vec<AttrString>,
+    /// A deprecated class may remain on existing entries, but can not be added to new entries.
+    pub deprecated: bool,
 }
 
 impl SchemaClass {
@@ -408,6 +426,8 @@ impl SchemaClass {
             .map(|i| i.map(AttrString::from).collect())
             .unwrap_or_else(Vec::new);
 
+        let deprecated = value.get_ava_single_bool("deprecated").unwrap_or(false);
+
         Ok(SchemaClass {
             name,
             uuid,
@@ -421,6 +441,7 @@ impl SchemaClass {
             supplements,
             systemexcludes,
             excludes,
+            deprecated,
         })
     }
 
@@ -439,7 +460,7 @@ pub trait SchemaTransaction {
     fn get_classes(&self) -> &HashMap<AttrString, SchemaClass>;
     fn get_attributes(&self) -> &HashMap<AttrString, SchemaAttribute>;
 
-    fn get_attributes_unique(&self) -> &Vec<AttrString>;
+    fn get_attributes_unique(&self) -> &Vec<SchemaAttribute>;
     fn get_reference_types(&self) -> &HashMap<AttrString, SchemaAttribute>;
 
     fn validate(&self) -> Vec<Result<(), ConsistencyError>> {
@@ -627,6 +648,7 @@ impl<'a> SchemaWriteTransaction<'a> {
             // Update the unique and ref caches.
             if a.syntax == SyntaxType::ReferenceUuid ||
                 a.syntax == SyntaxType::OauthScopeMap ||
+                a.syntax == SyntaxType::MemberValidity ||
                 // So that when an rs is removed we trigger removal of the sessions.
                 a.syntax == SyntaxType::Oauth2Session
             // May not need to be a ref type since it doesn't have external links/impact?
@@ -635,7 +657,7 @@ impl<'a> SchemaWriteTransaction<'a> {
                 self.ref_cache.insert(a.name.clone(), a.clone());
             }
             if a.unique {
-                self.unique_cache.push(a.name.clone());
+                self.unique_cache.push(a.clone());
             }
             // Finally insert.
             self.attributes.insert(a.name.clone(), a);
@@ -702,6 +724,8 @@ impl<'a> SchemaWriteTransaction<'a> {
                 replicated: true,
                 index: vec![IndexType::Equality, IndexType::Presence],
                 syntax: SyntaxType::Utf8StringInsensitive,
+                deprecated: false,
+                unique_scope: None,
             },
         );
         self.attributes.insert(
@@ -719,6 +743,8 @@ impl<'a> SchemaWriteTransaction<'a> {
                 replicated: true,
                 index: vec![IndexType::Equality, IndexType::Presence],
                 syntax: SyntaxType::Uuid,
+                deprecated: false,
+                unique_scope: None,
             },
         );
         self.attributes.insert(
@@ -738,6 +764,8 @@ impl<'a> SchemaWriteTransaction<'a> {
                 replicated: true,
                 index: vec![IndexType::Equality, IndexType::Presence],
                 syntax: SyntaxType::Uuid,
+                deprecated: false,
+                unique_scope: None,
             },
         );
         self.attributes.insert(
@@ -755,6 +783,8 @@ impl<'a> SchemaWriteTransaction<'a> {
                 replicated: true,
                 index: vec![],
                 syntax: SyntaxType::Cid,
+                deprecated: false,
+                unique_scope: None,
             },
         );
         self.attributes.insert(
@@ -770,6 +800,8 @@ impl<'a> SchemaWriteTransaction<'a> {
                 replicated: true,
                 index: vec![IndexType::Equality, IndexType::Presence],
                 syntax: SyntaxType::Utf8StringIname,
+                deprecated: false,
+                unique_scope: None,
             },
         );
         self.attributes.insert(
@@ -787,6 +819,8 @@ impl<'a> SchemaWriteTransaction<'a> {
                 replicated: true,
                 index: vec![IndexType::Equality],
                 syntax: SyntaxType::SecurityPrincipalName,
+                deprecated: false,
+                unique_scope: None,
             },
         );
         self.attributes.insert(
@@ -802,6 +836,8 @@ impl<'a> SchemaWriteTransaction<'a> {
                 replicated: true,
                 index: vec![IndexType::Equality],
                 syntax: SyntaxType::Utf8StringInsensitive,
+                deprecated: false,
+                unique_scope: None,
             },
         );
         self.attributes.insert(
@@ -817,6 +853,8 @@ impl<'a> SchemaWriteTransaction<'a> {
                 replicated: true,
                 index: vec![IndexType::Equality],
                 syntax: SyntaxType::Utf8StringInsensitive,
+                deprecated: false,
+                unique_scope: None,
             },
         );
         self.attributes.insert(
@@ -832,6 +870,8 @@ impl<'a> SchemaWriteTransaction<'a> {
                 replicated: true,
                 index: vec![],
                 syntax: SyntaxType::Utf8String,
+                deprecated: false,
+                unique_scope: None,
             },
         );
         self.attributes.insert(AttrString::from("multivalue"), SchemaAttribute {
@@ -845,6 +885,8 @@ impl<'a> SchemaWriteTransaction<'a> {
                 replicated: true,
                 index: vec![],
                 syntax: SyntaxType::Boolean,
+                deprecated: false,
+                unique_scope: None,
             });
         self.attributes.insert(AttrString::from("phantom"), SchemaAttribute {
                 name: AttrString::from("phantom"),
@@ -857,6 +899,8 @@ impl<'a> SchemaWriteTransaction<'a> {
                 replicated: true,
                 index: vec![],
                 syntax: SyntaxType::Boolean,
+                deprecated: false,
+                unique_scope: None,
             });
         self.attributes.insert(AttrString::from("sync_allowed"), SchemaAttribute {
                 name: AttrString::from("sync_allowed"),
@@ -869,6 +913,36 @@ impl<'a> SchemaWriteTransaction<'a> {
                 replicated: true,
                 index: vec![],
                 syntax: SyntaxType::Boolean,
+                deprecated: false,
+                unique_scope: None,
+            });
+        self.attributes.insert(AttrString::from("deprecated"), SchemaAttribute {
+                name: AttrString::from("deprecated"),
+                uuid: UUID_SCHEMA_ATTR_DEPRECATED,
+                description: String::from("If true, this attribute or class is deprecated and may no longer be added to entries"),
+                multivalue: false,
+                unique: false,
+                phantom: false,
+                sync_allowed: false,
+                replicated: true,
+                index: vec![],
+                syntax: SyntaxType::Boolean,
+                deprecated: false,
+                unique_scope: None,
+            });
+        self.attributes.insert(AttrString::from("unique_scope"), SchemaAttribute {
+                name: AttrString::from("unique_scope"),
+                uuid: UUID_SCHEMA_ATTR_UNIQUE_SCOPE,
+                description: String::from("If set, a unique attribute only needs to be unique amongst entries of this class"),
+                multivalue: false,
+                unique: false,
+                phantom: false,
+                sync_allowed: false,
+                replicated: true,
+                index: vec![],
+                syntax: SyntaxType::Utf8StringInsensitive,
+                deprecated: false,
+                unique_scope: None,
             });
         self.attributes.insert(AttrString::from("replicated"), SchemaAttribute {
                 name: AttrString::from("replicated"),
@@ -881,6 +955,8 @@ impl<'a> SchemaWriteTransaction<'a> {
                 replicated: true,
                 index: vec![],
                 syntax: SyntaxType::Boolean,
+                deprecated: false,
+                unique_scope: None,
             });
         self.attributes.insert(
             AttrString::from("unique"),
@@ -897,6 +973,8 @@ impl<'a> SchemaWriteTransaction<'a> {
                 replicated: true,
                 index: vec![],
                 syntax: SyntaxType::Boolean,
+                deprecated: false,
+                unique_scope: None,
             },
         );
         self.attributes.insert(
@@ -914,6 +992,8 @@ impl<'a> SchemaWriteTransaction<'a> {
                 replicated: true,
                 index: vec![],
                 syntax: SyntaxType::IndexId,
+                deprecated: false,
+                unique_scope: None,
             },
         );
         self.attributes.insert(
@@ -931,6 +1011,8 @@ impl<'a> SchemaWriteTransaction<'a> {
                 replicated: true,
                 index: vec![IndexType::Equality],
                 syntax: SyntaxType::SyntaxId,
+                deprecated: false,
+                unique_scope: None,
             },
         );
         self.attributes.insert(
@@ -948,6 +1030,8 @@ impl<'a> SchemaWriteTransaction<'a> {
                 replicated: true,
                 index: vec![],
                 syntax: SyntaxType::Utf8StringInsensitive,
+                deprecated: false,
+                unique_scope: None,
             },
         );
         self.attributes.insert(
@@ -965,6 +1049,8 @@ impl<'a> SchemaWriteTransaction<'a> {
                 replicated: true,
                 index: vec![],
                 syntax: SyntaxType::Utf8StringInsensitive,
+                deprecated: false,
+                unique_scope: None,
             },
         );
         self.attributes.insert(
@@ -982,6 +1068,8 @@ impl<'a> SchemaWriteTransaction<'a> {
                 replicated: true,
                 index: vec![],
                 syntax: SyntaxType::Utf8StringInsensitive,
+                deprecated: false,
+                unique_scope: None,
             },
         );
         self.attributes.insert(
@@ -999,6 +1087,8 @@ impl<'a> SchemaWriteTransaction<'a> {
                 replicated: true,
                 index: vec![],
                 syntax: SyntaxType::Utf8StringInsensitive,
+                deprecated: false,
+                unique_scope: None,
             },
         );
         self.attributes.insert(
@@ -1016,6 +1106,8 @@ impl<'a> SchemaWriteTransaction<'a> {
                     replicated: true,
                     index: vec![],
                     syntax: SyntaxType::Utf8StringInsensitive,
+                    deprecated: false,
+                    unique_scope: None,
                 },
             );
         self.attributes.insert(
@@ -1033,6 +1125,8 @@ impl<'a> SchemaWriteTransaction<'a> {
                     replicated: true,
                     index: vec![],
                     syntax: SyntaxType::Utf8StringInsensitive,
+                    deprecated: false,
+                    unique_scope: None,
                 },
             );
         self.attributes.insert(
@@ -1050,6 +1144,8 @@ impl<'a> SchemaWriteTransaction<'a> {
                 replicated: true,
                 index: vec![],
                 syntax: SyntaxType::Utf8StringInsensitive,
+                deprecated: false,
+                unique_scope: None,
             },
         );
         self.attributes.insert(
@@ -1067,6 +1163,8 @@ impl<'a> SchemaWriteTransaction<'a> {
                     replicated: true,
                     index: vec![],
                     syntax: SyntaxType::Utf8StringInsensitive,
+                    deprecated: false,
+                    unique_scope: None,
                 },
             );
 
@@ -1085,6 +1183,8 @@ impl<'a> SchemaWriteTransaction<'a> {
                     replicated: true,
                     index: vec![IndexType::Equality],
                     syntax: SyntaxType::Boolean,
+                    deprecated: false,
+                    unique_scope: None,
                 },
             );
 
@@ -1103,6 +1203,8 @@ impl<'a> SchemaWriteTransaction<'a> {
                 replicated: true,
                 index: vec![IndexType::Equality, IndexType::SubString],
                 syntax: SyntaxType::JsonFilter,
+                deprecated: false,
+                unique_scope: None,
             },
         );
         self.attributes.insert(
@@ -1120,6 +1222,8 @@ impl<'a> SchemaWriteTransaction<'a> {
                 replicated: true,
                 index: vec![IndexType::Equality],
                 syntax: SyntaxType::ReferenceUuid,
+                deprecated: false,
+                unique_scope: None,
             },
         );
 
@@ -1138,6 +1242,8 @@ impl<'a> SchemaWriteTransaction<'a> {
                 replicated: true,
                 index: vec![IndexType::Equality, IndexType::SubString],
                 syntax: SyntaxType::JsonFilter,
+                deprecated: false,
+                unique_scope: None,
             },
         );
         self.attributes.insert(
@@ -1155,6 +1261,8 @@ impl<'a> SchemaWriteTransaction<'a> {
                 replicated: true,
                 index: vec![IndexType::Equality],
                 syntax: SyntaxType::Utf8StringInsensitive,
+                deprecated: false,
+                unique_scope: None,
             },
         );
         self.attributes.insert(
@@ -1170,6 +1278,8 @@ impl<'a> SchemaWriteTransaction<'a> {
                 replicated: true,
                 index: vec![IndexType::Equality],
                 syntax: SyntaxType::Utf8StringInsensitive,
+                deprecated: false,
+                unique_scope: None,
             },
         );
         self.attributes.insert(
@@ -1187,6 +1297,8 @@ impl<'a> SchemaWriteTransaction<'a> {
                 replicated: true,
                 index: vec![IndexType::Equality],
                 syntax: SyntaxType::Utf8StringInsensitive,
+                deprecated: false,
+                unique_scope: None,
             },
         );
 
@@ -1205,6 +1317,8 @@ impl<'a> SchemaWriteTransaction<'a> {
                 replicated: true,
                 index: vec![IndexType::Equality],
                 syntax: SyntaxType::Utf8StringInsensitive,
+                deprecated: false,
+                unique_scope: None,
             },
         );
         self.attributes.insert(
@@ -1222,6 +1336,8 @@ impl<'a> SchemaWriteTransaction<'a> {
                 replicated: true,
                 index: vec![IndexType::Equality],
                 syntax: SyntaxType::Utf8StringInsensitive,
+                deprecated: false,
+                unique_scope: None,
             },
         );
         self.attributes.insert(
@@ -1237,6 +1353,8 @@ impl<'a> SchemaWriteTransaction<'a> {
                     replicated: true,
                     index: vec![IndexType::Equality],
                     syntax: SyntaxType::Utf8StringInsensitive,
+                    deprecated: false,
+                    unique_scope: None,
                 },
             );
         // MO/Member
@@ -1253,6 +1371,8 @@ impl<'a> SchemaWriteTransaction<'a> {
                 replicated: false,
                 index: vec![IndexType::Equality],
                 syntax: SyntaxType::ReferenceUuid,
+                deprecated: false,
+                unique_scope: None,
             },
         );
         self.attributes.insert(
@@ -1268,6 +1388,8 @@ impl<'a> SchemaWriteTransaction<'a> {
                 replicated: false,
                 index: vec![IndexType::Equality],
                 syntax: SyntaxType::ReferenceUuid,
+                deprecated: false,
+                unique_scope: None,
             },
         );
         self.attributes.insert(
@@ -1283,6 +1405,8 @@ impl<'a> SchemaWriteTransaction<'a> {
                 replicated: true,
                 index: vec![IndexType::Equality],
                 syntax: SyntaxType::ReferenceUuid,
+                deprecated: false,
+                unique_scope: None,
             },
         );
         self.attributes.insert(
@@ -1298,6 +1422,8 @@ impl<'a> SchemaWriteTransaction<'a> {
                 replicated: false,
                 index: vec![IndexType::Equality],
                 syntax: SyntaxType::ReferenceUuid,
+                deprecated: false,
+                unique_scope: None,
             },
         );
         // Migration related
@@ -1316,6 +1442,8 @@ impl<'a> SchemaWriteTransaction<'a> {
                 replicated: true,
                 index: vec![],
                 syntax: SyntaxType::Uint32,
+                deprecated: false,
+                unique_scope: None,
             },
         );
         // Domain for sysinfo
@@ -1332,6 +1460,8 @@ impl<'a> SchemaWriteTransaction<'a> {
                 replicated: true,
                 index: vec![IndexType::Equality],
                 syntax: SyntaxType::Utf8StringIname,
+                deprecated: false,
+                unique_scope: None,
             },
         );
         self.attributes.insert(
@@ -1349,6 +1479,8 @@ impl<'a> SchemaWriteTransaction<'a> {
                 replicated: true,
                 index: vec![],
                 syntax: SyntaxType::Utf8StringInsensitive,
+                deprecated: false,
+                unique_scope: None,
             },
         );
         self.attributes.insert(
@@ -1366,6 +1498,8 @@ impl<'a> SchemaWriteTransaction<'a> {
                 replicated: true,
                 index: vec![],
                 syntax: SyntaxType::Utf8StringInsensitive,
+                deprecated: false,
+                unique_scope: None,
             },
         );
 
@@ -1385,6 +1519,8 @@ impl<'a> SchemaWriteTransaction<'a> {
                 replicated: true,
                 index: vec![IndexType::Equality],
                 syntax: SyntaxType::Utf8StringInsensitive,
+                deprecated: false,
+                unique_scope: None,
             },
         );
         self.attributes.insert(
@@ -1402,6 +1538,8 @@ impl<'a> SchemaWriteTransaction<'a> {
                 replicated: true,
                 index: vec![IndexType::Equality],
                 syntax: SyntaxType::ReferenceUuid,
+                deprecated: false,
+                unique_scope: None,
             },
         );
         self.attributes.insert(
@@ -1417,6 +1555,8 @@ impl<'a> SchemaWriteTransaction<'a> {
                 replicated: true,
                 index: vec![],
                 syntax: SyntaxType::Utf8StringInsensitive,
+                deprecated: false,
+                unique_scope: None,
             },
         );
 
@@ -1433,6 +1573,8 @@ impl<'a> SchemaWriteTransaction<'a> {
                 replicated: false,
                 index: vec![],
                 syntax: SyntaxType::Utf8String,
+                deprecated: false,
+                unique_scope: None,
             },
         );
 
@@ -1449,6 +1591,8 @@ impl<'a> SchemaWriteTransaction<'a> {
                 replicated: false,
                 index: vec![],
                 syntax: SyntaxType::TotpSecret,
+                deprecated: false,
+                unique_scope: None,
             },
         );
 
@@ -1466,6 +1610,8 @@ impl<'a> SchemaWriteTransaction<'a> {
                 replicated: false,
                 index: vec![],
                 syntax: SyntaxType::Utf8StringInsensitive,
+                deprecated: false,
+                unique_scope: None,
             },
         );
         self.attributes.insert(
@@ -1481,6 +1627,8 @@ impl<'a> SchemaWriteTransaction<'a> {
                 replicated: false,
                 index: vec![],
                 syntax: SyntaxType::Utf8StringInsensitive,
+                deprecated: false,
+                unique_scope: None,
             },
         );
         self.attributes.insert(
@@ -1496,6 +1644,8 @@ impl<'a> SchemaWriteTransaction<'a> {
                 replicated: false,
                 index: vec![],
                 syntax: SyntaxType::Uuid,
+                deprecated: false,
+                unique_scope: None,
             },
         );
         self.attributes.insert(
@@ -1511,6 +1661,8 @@ impl<'a> SchemaWriteTransaction<'a> {
                 replicated: false,
                 index: vec![],
                 syntax: SyntaxType::Utf8StringInsensitive,
+                deprecated: false,
+                unique_scope: None,
             },
         );
         self.attributes.insert(
@@ -1526,6 +1678,8 @@ impl<'a> SchemaWriteTransaction<'a> {
                 replicated: false,
                 index: vec![],
                 syntax: SyntaxType::Utf8StringIname,
+                deprecated: false,
+                unique_scope: None,
             },
         );
         self.attributes.insert(
@@ -1541,6 +1695,8 @@ impl<'a> SchemaWriteTransaction<'a> {
                 replicated: false,
                 index: vec![],
                 syntax: SyntaxType::SshKey,
+                deprecated: false,
+                unique_scope: None,
             },
         );
         self.attributes.insert(
@@ -1556,6 +1712,8 @@ impl<'a> SchemaWriteTransaction<'a> {
                 replicated: false,
                 index: vec![],
                 syntax: SyntaxType::SshKey,
+                deprecated: false,
+                unique_scope: None,
             },
         );
         self.attributes.insert(
@@ -1571,6 +1729,8 @@ impl<'a> SchemaWriteTransaction<'a> {
                 replicated: false,
                 index: vec![],
                 syntax: SyntaxType::EmailAddress,
+                deprecated: false,
+                unique_scope: None,
             },
         );
         self.attributes.insert(
@@ -1586,6 +1746,8 @@ impl<'a> SchemaWriteTransaction<'a> {
                 replicated: false,
                 index: vec![],
                 syntax: SyntaxType::EmailAddress,
+                deprecated: false,
+                unique_scope: None,
             },
         );
         self.attributes.insert(
@@ -1601,6 +1763,8 @@ impl<'a> SchemaWriteTransaction<'a> {
                 replicated: false,
                 index: vec![],
                 syntax: SyntaxType::EmailAddress,
+                deprecated: false,
+                unique_scope: None,
             },
         );
         self.attributes.insert(
@@ -1616,6 +1780,8 @@ impl<'a> SchemaWriteTransaction<'a> {
                 replicated: false,
                 index: vec![],
                 syntax: SyntaxType::EmailAddress,
+                deprecated: false,
+                unique_scope: None,
             },
         );
         self.attributes.insert(
@@ -1631,6 +1797,8 @@ impl<'a> SchemaWriteTransaction<'a> {
                 replicated: false,
                 index: vec![],
                 syntax: SyntaxType::Uint32,
+                deprecated: false,
+                unique_scope: None,
             },
         );
         // end LDAP masking phantoms
@@ -1646,6 +1814,8 @@ impl<'a> SchemaWriteTransaction<'a> {
                     AttrString::from("phantom"),
                     AttrString::from("sync_allowed"),
                     AttrString::from("index"),
+                    AttrString::from("deprecated"),
+                    AttrString::from("unique_scope"),
                 ],
                 systemmust: vec![
                     AttrString::from("class"),
@@ -1675,6 +1845,7 @@ impl<'a> SchemaWriteTransaction<'a> {
                     AttrString::from("supplements"),
                     AttrString::from("systemexcludes"),
                     AttrString::from("excludes"),
+                    AttrString::from("deprecated"),
                 ],
                 systemmust: vec![
                     AttrString::from("class"),
@@ -1845,6 +2016,15 @@ impl<'a> SchemaWriteTransaction<'a> {
                 ..Default::default()
             },
         );
+        self.classes.insert(
+            AttrString::from("access_control_entry_manager"),
+            SchemaClass {
+                name: AttrString::from("access_control_entry_manager"),
+                uuid: UUID_SCHEMA_CLASS_ACCESS_CONTROL_ENTRY_MANAGER,
+                description: String::from("System Access Control Entry Manager Class - the profile only applies to the entries the receiver manages"),
+                ..Default::default()
+            },
+        );
         self.classes.insert(
             AttrString::from("system"),
             SchemaClass {
@@ -1883,7 +2063,7 @@ impl<'a> SchemaWriteTransaction<'a> {
 }
 
 impl<'a> SchemaTransaction for SchemaWriteTransaction<'a> {
-    fn get_attributes_unique(&self) -> &Vec<AttrString> {
+    fn get_attributes_unique(&self) -> &Vec<SchemaAttribute> {
         &self.unique_cache
     }
 
@@ -1901,7 +2081,7 @@ impl<'a> SchemaTransaction for SchemaWriteTransaction<'a> {
 }
 
 impl SchemaTransaction for SchemaReadTransaction {
-    fn get_attributes_unique(&self) -> &Vec<AttrString> {
+    fn get_attributes_unique(&self) -> &Vec<SchemaAttribute> {
         &self.unique_cache
     }
 
@@ -172,8 +172,7 @@ pub trait AccessControlsTransaction<'a> {
                         // Now, for each of the acp's that apply to our receiver, resolve their
                         // related target filters.
                         acs.acp
-                            .targetscope
-                            .resolve(ident, None, Some(acp_resolve_filter_cache))
+                            .resolve_targetscope(ident, acp_resolve_filter_cache)
                             .map_err(|e| {
                                 admin_error!(
                                     ?e,
@@ -345,8 +344,7 @@ pub trait AccessControlsTransaction<'a> {
                 if let Some(receiver) = acs.acp.receiver {
                     if ident.is_memberof(receiver) {
                         acs.acp
-                            .targetscope
-                            .resolve(ident, None, Some(acp_resolve_filter_cache))
+                            .resolve_targetscope(ident, acp_resolve_filter_cache)
                             .map_err(|e| {
                                 admin_error!(
                                     "A internal filter/event was passed for resolution!?!? {:?}",
@@ -641,8 +639,7 @@ pub trait AccessControlsTransaction<'a> {
                 if let Some(receiver) = acs.acp.receiver {
                     if ce.ident.is_memberof(receiver) {
                         acs.acp
-                            .targetscope
-                            .resolve(&ce.ident, None, Some(acp_resolve_filter_cache))
+                            .resolve_targetscope(&ce.ident, acp_resolve_filter_cache)
                             .map_err(|e| {
                                 admin_error!(
                                     "A internal filter/event was passed for resolution!?!? {:?}",
@@ -693,8 +690,7 @@ pub trait AccessControlsTransaction<'a> {
                 if let Some(receiver) = acs.acp.receiver {
                     if ident.is_memberof(receiver) {
                         acs.acp
-                            .targetscope
-                            .resolve(ident, None, Some(acp_resolve_filter_cache))
+                            .resolve_targetscope(ident, acp_resolve_filter_cache)
                             .map_err(|e| {
                                 admin_error!(
                                     "A internal filter/event was passed for resolution!?!? {:?}",
@@ -1984,6 +1980,80 @@ mod tests {
         test_acp_modify!(&me_pres_rw, vec![acp_allow], &r_set, true);
     }
 
+    #[test]
+    fn test_access_enforce_entry_manager_modify() {
+        // Managed directly by the account.
+        let ev1 = entry_init!(
+            ("class", Value::new_class("object")),
+            ("class", Value::new_class("group")),
+            ("name", Value::new_iname("testgroup1")),
+            ("entry_managed_by", Value::Refer(UUID_TEST_ACCOUNT_1))
+        )
+        .into_sealed_committed();
+        // Managed by a group the account is a member of.
+        let ev2 = entry_init!(
+            ("class", Value::new_class("object")),
+            ("class", Value::new_class("group")),
+            ("name", Value::new_iname("testgroup2")),
+            ("entry_managed_by", Value::Refer(UUID_TEST_GROUP_1))
+        )
+        .into_sealed_committed();
+        // Managed by someone else.
+        let ev3 = entry_init!(
+            ("class", Value::new_class("object")),
+            ("class", Value::new_class("group")),
+            ("name", Value::new_iname("testgroup3")),
+            ("entry_managed_by", Value::Refer(UUID_TEST_GROUP_2))
+        )
+        .into_sealed_committed();
+        // Not managed at all.
+        let ev4 = entry_init!(
+            ("class", Value::new_class("object")),
+            ("class", Value::new_class("group")),
+            ("name", Value::new_iname("testgroup4"))
+        )
+        .into_sealed_committed();
+
+        let me_pres = ModifyEvent::new_impersonate_entry(
+            E_TEST_ACCOUNT_1.clone(),
+            filter_all!(f_eq("class", PartialValue::new_class("group"))),
+            modlist!([m_pres("member", &Value::Refer(UUID_TEST_ACCOUNT_2))]),
+        );
+
+        let mut acp_manager = AccessControlModify::from_raw(
+            "test_modify_entry_manager",
+            Uuid::new_v4(),
+            UUID_TEST_GROUP_1,
+            // To modify groups
+            filter_valid!(f_eq("class", PartialValue::new_class("group"))),
+            // Allow pres member
+            "member",
+            // Allow rem member
+            "member",
+            "",
+        );
+        acp_manager.acp.entry_manager = true;
+
+        test_acp_modify!(
+            &me_pres,
+            vec![acp_manager.clone()],
+            &[Arc::new(ev1), Arc::new(ev2.clone())],
+            true
+        );
+        test_acp_modify!(
+            &me_pres,
+            vec![acp_manager.clone()],
+            &[Arc::new(ev2.clone()), Arc::new(ev3)],
+            false
+        );
+        test_acp_modify!(
+            &me_pres,
+            vec![acp_manager],
+            &[Arc::new(ev2), Arc::new(ev4)],
+            false
+        );
+    }
+
     macro_rules! test_acp_create {
         (
             $ce:expr,
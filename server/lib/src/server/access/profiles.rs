@@ -1,7 +1,10 @@
 use crate::prelude::*;
 use std::collections::BTreeSet;
 
-use crate::filter::{Filter, FilterValid};
+use concread::arcache::ARCacheReadTxn;
+
+use crate::filter::{Filter, FilterValid, FilterValidResolved};
+use crate::server::identity::IdentityId;
 
 use kanidm_proto::v1::Filter as ProtoFilter;
 
@@ -57,6 +60,7 @@ impl AccessControlSearch {
                 uuid,
                 receiver: Some(receiver),
                 targetscope,
+                entry_manager: false,
             },
             attrs: attrs.split_whitespace().map(AttrString::from).collect(),
         }
@@ -100,6 +104,7 @@ impl AccessControlDelete {
                 uuid,
                 receiver: Some(receiver),
                 targetscope,
+                entry_manager: false,
             },
         }
     }
@@ -158,6 +163,7 @@ impl AccessControlCreate {
                 uuid,
                 receiver: Some(receiver),
                 targetscope,
+                entry_manager: false,
             },
             classes: classes.split_whitespace().map(AttrString::from).collect(),
             attrs: attrs.split_whitespace().map(AttrString::from).collect(),
@@ -226,6 +232,7 @@ impl AccessControlModify {
                 uuid,
                 receiver: Some(receiver),
                 targetscope,
+                entry_manager: false,
             },
             classes: classes.split_whitespace().map(AttrString::from).collect(),
             presattrs: presattrs.split_whitespace().map(AttrString::from).collect(),
@@ -258,10 +265,13 @@ pub struct AccessControlProfile {
     //  exclude
     //    Group
     pub targetscope: Filter<FilterValid>,
+    // If true, this profile only applies to the entries within the targetscope that are
+    // managed by the receiver through entry_managed_by.
+    pub entry_manager: bool,
 }
 
 impl AccessControlProfile {
-    pub(super) fn try_from(
+    pub(crate) fn try_from(
         qs: &mut QueryServerWriteTransaction,
         value: &Entry<EntrySealed, EntryCommitted>,
     ) -> Result<Self, OperationError> {
@@ -317,11 +327,36 @@ impl AccessControlProfile {
             OperationError::SchemaViolation(e)
         })?;
 
+        let entry_manager = value.attribute_equality("class", &PVCLASS_ACEM);
+
         Ok(AccessControlProfile {
             name,
             uuid,
             receiver,
             targetscope,
+            entry_manager,
         })
     }
+
+    /// Resolve the entries that this profile targets for an identity. An entry manager
+    /// profile only targets the entries that are managed by the identity, or by a group
+    /// that the identity is a member of.
+    pub(super) fn resolve_targetscope(
+        &self,
+        ident: &Identity,
+        rsv_cache: &mut ARCacheReadTxn<
+            '_,
+            (IdentityId, Filter<FilterValid>),
+            Filter<FilterValidResolved>,
+            (),
+        >,
+    ) -> Result<Filter<FilterValidResolved>, OperationError> {
+        if self.entry_manager {
+            self.targetscope
+                .constrain_to_ident_refer("entry_managed_by", ident)
+                .resolve(ident, None, Some(rsv_cache))
+        } else {
+            self.targetscope.resolve(ident, None, Some(rsv_cache))
+        }
+    }
 }
@@ -162,6 +162,19 @@ impl Identity {
         }
     }
 
+    /// Create an identity for an entry that is only used to evaluate which access controls
+    /// apply to it. This identity must never be used to perform an operation.
+    pub(crate) fn from_entry_for_access_check(
+        entry: Arc<Entry<EntrySealed, EntryCommitted>>,
+    ) -> Self {
+        Identity {
+            origin: IdentType::User(IdentUser { entry }),
+            session_id: uuid!("00000000-0000-0000-0000-000000000000"),
+            scope: AccessScope::ReadWrite,
+            limits: Limits::unlimited(),
+        }
+    }
+
     pub fn access_scope(&self) -> AccessScope {
         self.scope
     }
@@ -154,7 +154,7 @@ impl<'a> QueryServerWriteTransaction<'a> {
         trace!("internal_migrate_or_create operating on {:?}", e.get_uuid());
 
         let Some(filt) = e.filter_from_attrs(&[AttrString::from("uuid")]) else {
-            return Err(OperationError::FilterGeneration)
+            return Err(OperationError::FilterGeneration);
         };
 
         trace!("internal_migrate_or_create search {:?}", filt);
@@ -437,6 +437,40 @@ impl<'a> QueryServerWriteTransaction<'a> {
         let idm_schema_attrs = [
             E_SCHEMA_ATTR_SYNC_CREDENTIAL_PORTAL.clone(),
             E_SCHEMA_ATTR_SYNC_YIELD_AUTHORITY.clone(),
+            E_SCHEMA_ATTR_PASSWORD_HISTORY.clone(),
+            E_SCHEMA_ATTR_UNIX_PASSWORD_HISTORY.clone(),
+            E_SCHEMA_ATTR_PASSWORD_HISTORY_LENGTH.clone(),
+            E_SCHEMA_ATTR_ENTRY_MANAGED_BY.clone(),
+            E_SCHEMA_ATTR_MEMBER_VALIDITY.clone(),
+            E_SCHEMA_ATTR_ACCESS_REQUEST_APPROVERS.clone(),
+            E_SCHEMA_ATTR_ACCESS_REQUEST_MAX_DURATION.clone(),
+            E_SCHEMA_ATTR_ACCESS_REQUEST_REQUESTER.clone(),
+            E_SCHEMA_ATTR_ACCESS_REQUEST_GROUP.clone(),
+            E_SCHEMA_ATTR_ACCESS_REQUEST_STATE.clone(),
+            E_SCHEMA_ATTR_ACCESS_REQUEST_DURATION.clone(),
+            E_SCHEMA_ATTR_ACCESS_REQUEST_DECIDED_BY.clone(),
+            E_SCHEMA_ATTR_ACCESS_REQUEST_EXPIRY.clone(),
+            E_SCHEMA_ATTR_RECYCLE_BIN_RETENTION.clone(),
+            E_SCHEMA_ATTR_WEBHOOK_URL.clone(),
+            E_SCHEMA_ATTR_WEBHOOK_EVENT.clone(),
+            E_SCHEMA_ATTR_WEBHOOK_FILTER.clone(),
+            E_SCHEMA_ATTR_WEBHOOK_SECRET.clone(),
+            E_SCHEMA_ATTR_WEBHOOK_DEAD_LETTER.clone(),
+            E_SCHEMA_ATTR_OAUTH2_RS_BACKCHANNEL_LOGOUT_URI.clone(),
+            E_SCHEMA_ATTR_OAUTH2_RS_TOKEN_EXCHANGE_FROM.clone(),
+            E_SCHEMA_ATTR_OAUTH2_RS_JWKS.clone(),
+            E_SCHEMA_ATTR_OAUTH2_RS_TLS_CLIENT_CERT.clone(),
+            E_SCHEMA_ATTR_OAUTH2_REQUIRE_PUSHED_AUTHORISATION.clone(),
+            E_SCHEMA_ATTR_OAUTH2_REGISTRAR_DOMAIN.clone(),
+            E_SCHEMA_ATTR_OAUTH2_REGISTRAR_SCOPE_MAP.clone(),
+            E_SCHEMA_ATTR_OAUTH2_RS_REGISTERED_BY.clone(),
+            E_SCHEMA_ATTR_OAUTH2_RS_REDIRECT_URI.clone(),
+            E_SCHEMA_ATTR_OAUTH2_CONSENT_PROMPT_DISABLE.clone(),
+            E_SCHEMA_ATTR_ID_VERIFICATION_KEY.clone(),
+            E_SCHEMA_ATTR_MAIL_DESTINATION.clone(),
+            E_SCHEMA_ATTR_MESSAGE_TEMPLATE.clone(),
+            E_SCHEMA_ATTR_SEND_AFTER.clone(),
+            E_SCHEMA_ATTR_DELIVERY_ATTEMPTS.clone(),
         ];
 
         let r: Result<(), _> = idm_schema_attrs
@@ -525,6 +559,10 @@ impl<'a> QueryServerWriteTransaction<'a> {
         let idm_schema_classes = [
             E_SCHEMA_CLASS_OAUTH2_RS_BASIC.clone(),
             E_SCHEMA_CLASS_OAUTH2_RS_PUBLIC.clone(),
+            E_SCHEMA_CLASS_ACCESS_REQUEST.clone(),
+            E_SCHEMA_CLASS_WEBHOOK.clone(),
+            E_SCHEMA_CLASS_OAUTH2_REGISTRAR.clone(),
+            E_SCHEMA_CLASS_OUTBOUND_MESSAGE.clone(),
         ];
 
         let r: Result<(), _> = idm_schema_classes
@@ -620,6 +658,7 @@ impl<'a> QueryServerWriteTransaction<'a> {
             JSON_IDM_HP_OAUTH2_MANAGE_PRIV_V1,
             JSON_IDM_HP_SERVICE_ACCOUNT_INTO_PERSON_MIGRATE_PRIV,
             JSON_IDM_HP_SYNC_ACCOUNT_MANAGE_PRIV,
+            JSON_IDM_HELPDESK_V1,
             // All members must exist before we write HP
             JSON_IDM_HIGH_PRIVILEGE_V1,
         ];
@@ -680,6 +719,11 @@ impl<'a> QueryServerWriteTransaction<'a> {
             E_IDM_ACCOUNT_MAIL_READ_PRIV.clone(),
             E_IDM_ACP_ACCOUNT_MAIL_READ_PRIV_V1.clone(),
             E_IDM_ACCOUNT_SELF_ACP_WRITE_V1.clone(),
+            E_IDM_ACP_GROUP_ENTRY_MANAGER_V1.clone(),
+            E_IDM_ACP_SERVICE_ACCOUNT_ENTRY_MANAGER_V1.clone(),
+            E_IDM_ACP_WEBHOOK_MANAGE_V1.clone(),
+            E_IDM_ACP_OAUTH2_REGISTRAR_MANAGE_V1.clone(),
+            E_IDM_ACP_HELPDESK_V1.clone(),
         ];
 
         let res: Result<(), _> = idm_entries
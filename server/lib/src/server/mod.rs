@@ -8,6 +8,7 @@ use concread::arcache::{ARCache, ARCacheBuilder, ARCacheReadTxn};
 use concread::cowcell::*;
 use hashbrown::{HashMap, HashSet};
 use std::collections::BTreeSet;
+use tokio::sync::mpsc::UnboundedSender;
 use tokio::sync::{Semaphore, SemaphorePermit};
 use tracing::trace;
 
@@ -16,6 +17,8 @@ use kanidm_proto::v1::{ConsistencyError, UiHint};
 use crate::be::{Backend, BackendReadTransaction, BackendTransaction, BackendWriteTransaction};
 // We use so many, we just import them all ...
 use crate::filter::{Filter, FilterInvalid, FilterValid, FilterValidResolved};
+use crate::idm::oauth2::Oauth2SessionEnded;
+use crate::idm::webhook::WebhookDelivery;
 use crate::plugins::dyngroup::{DynGroup, DynGroupCache};
 use crate::plugins::Plugins;
 use crate::prelude::*;
@@ -26,7 +29,7 @@ use crate::schema::{
     Schema, SchemaAttribute, SchemaClass, SchemaReadTransaction, SchemaTransaction,
     SchemaWriteTransaction,
 };
-use crate::value::EXTRACT_VAL_DN;
+use crate::value::{MemberValidity, EXTRACT_VAL_DN};
 use crate::valueset::uuid_to_proto_string;
 
 use self::access::{
@@ -122,6 +125,15 @@ pub struct QueryServerWriteTransaction<'a> {
     resolve_filter_cache:
         ARCacheReadTxn<'a, (IdentityId, Filter<FilterValid>), Filter<FilterValidResolved>, ()>,
     dyngroup_cache: CowCellWriteTxn<'a, DynGroupCache>,
+    /// Webhook events caused by this transaction, delivered once it commits.
+    pub(crate) webhook_pending: Vec<WebhookDelivery>,
+    /// Where webhook events are sent once this transaction commits. This is only set
+    /// when the idm server is running a delivery worker, so events of transactions
+    /// without one (such as the offline admin tasks) are discarded.
+    pub(crate) webhook_tx: Option<UnboundedSender<WebhookDelivery>>,
+    /// Oauth2 sessions ended by this transaction, whose resource servers are sent a
+    /// back-channel logout once it commits.
+    pub(crate) oauth2_sessions_ended: Vec<Oauth2SessionEnded>,
 }
 
 /// The `QueryServerTransaction` trait provides a set of common read only operations to be
@@ -309,9 +321,9 @@ pub trait QueryServerTransaction<'a> {
         let r = self.get_be_txn().uuid2spn(uuid)?;
 
         if let Some(ref n) = r {
-            // Shouldn't we be doing more graceful error handling here?
-            // Or, if we know it will always be true, we should remove this.
-            debug_assert!(n.is_spn() || n.is_iname());
+            // Entries without a spn or name (such as oauth2 resource servers) fall back
+            // to their uuid in uuid2spn, so that is valid here too.
+            debug_assert!(n.is_spn() || n.is_iname() || n.is_uuid());
         }
 
         Ok(r)
@@ -565,6 +577,18 @@ pub trait QueryServerTransaction<'a> {
                         .map_err(|()| OperationError::InvalidAttribute("Invalid uihint syntax".to_string())),
                     SyntaxType::TotpSecret => Err(OperationError::InvalidAttribute("TotpSecret Values can not be supplied through modification".to_string())),
                     SyntaxType::AuditLogString => Err(OperationError::InvalidAttribute("Audit logs are generated and not able to be set.".to_string())),
+                    SyntaxType::PasswordHistory => Err(OperationError::InvalidAttribute("Password history is generated and not able to be set.".to_string())),
+                    SyntaxType::MemberValidity => {
+                        // <member>,<not_before>,<not_after>
+                        let (member, validity) = value.split_once(',')
+                            .ok_or_else(|| OperationError::InvalidAttribute("Invalid member validity syntax".to_string()))?;
+                        let validity = MemberValidity::from_str(validity)
+                            .map_err(|()| OperationError::InvalidAttribute("Invalid member validity syntax".to_string()))?;
+                        let un = self
+                            .name_to_uuid(member)
+                            .unwrap_or(UUID_DOES_NOT_EXIST);
+                        Ok(Value::MemberValidity(un, validity))
+                    }
                 }
             }
             None => {
@@ -611,6 +635,7 @@ pub trait QueryServerTransaction<'a> {
                     // integrity processing. Exceptions are self-contained value types!
                     SyntaxType::ReferenceUuid
                     | SyntaxType::OauthScopeMap
+                    | SyntaxType::MemberValidity
                     | SyntaxType::Session
                     | SyntaxType::ApiToken
                     | SyntaxType::Oauth2Session => {
@@ -633,9 +658,10 @@ pub trait QueryServerTransaction<'a> {
                     SyntaxType::Uint32 => PartialValue::new_uint32_str(value).ok_or_else(|| {
                         OperationError::InvalidAttribute("Invalid uint32 syntax".to_string())
                     }),
-                    SyntaxType::Cid => PartialValue::new_cid_s(value).ok_or_else(|| {
-                        OperationError::InvalidAttribute("Invalid cid syntax".to_string())
-                    }),
+                    SyntaxType::Cid | SyntaxType::PasswordHistory => PartialValue::new_cid_s(value)
+                        .ok_or_else(|| {
+                            OperationError::InvalidAttribute("Invalid cid syntax".to_string())
+                        }),
                     SyntaxType::NsUniqueId => Ok(PartialValue::new_nsuniqueid_s(value)),
                     SyntaxType::DateTime => PartialValue::new_datetime_s(value).ok_or_else(|| {
                         OperationError::InvalidAttribute(
@@ -710,6 +736,19 @@ pub trait QueryServerTransaction<'a> {
                 })
                 .collect();
             v
+        } else if let Some(r_map) = value.as_member_validity_map() {
+            let v: Result<Vec<_>, _> = r_map
+                .iter()
+                .map(|(u, mv)| {
+                    let nv = self.uuid_to_spn(*u)?;
+                    let u = match nv {
+                        Some(v) => v.to_proto_string_clone(),
+                        None => uuid_to_proto_string(*u),
+                    };
+                    Ok(format!("{u},{mv}"))
+                })
+                .collect();
+            v
         } else {
             let v: Vec<_> = value.to_proto_string_clone_iter().collect();
             Ok(v)
@@ -818,6 +857,48 @@ pub trait QueryServerTransaction<'a> {
             })
     }
 
+    // This is a helper to get the number of previous passwords that may not be reused.
+    fn get_password_history_length(&mut self) -> Result<u32, OperationError> {
+        match self.internal_search_uuid(UUID_SYSTEM_CONFIG) {
+            Ok(e) => Ok(e
+                .get_ava_single_uint32("password_history_length")
+                .unwrap_or(PW_HISTORY_DEFAULT_LENGTH)),
+            // The system config doesn't exist yet during the initial setup.
+            Err(OperationError::NoMatchingEntries) => Ok(PW_HISTORY_DEFAULT_LENGTH),
+            Err(e) => {
+                admin_error!(?e, "Failed to retrieve system configuration");
+                Err(e)
+            }
+        }
+    }
+
+    // This is a helper to get the per class recycle bin retention periods. Values are
+    // validated as they are written, but any that can not be parsed are still skipped so
+    // that a bad value can't block the purge.
+    fn get_recycle_bin_retention(&mut self) -> Result<Vec<(String, u64)>, OperationError> {
+        match self.internal_search_uuid(UUID_SYSTEM_CONFIG) {
+            Ok(e) => Ok(e
+                .get_ava_iter_iutf8("recycle_bin_retention")
+                .map(|vs_str_iter| {
+                    vs_str_iter
+                        .filter_map(|v| {
+                            let parsed = crate::plugins::recyclebin::parse_retention(v);
+                            if parsed.is_none() {
+                                admin_warn!(?v, "Ignoring invalid recycle_bin_retention value");
+                            }
+                            parsed
+                        })
+                        .collect()
+                })
+                .unwrap_or_default()),
+            Err(OperationError::NoMatchingEntries) => Ok(Vec::new()),
+            Err(e) => {
+                admin_error!(?e, "Failed to retrieve system configuration");
+                Err(e)
+            }
+        }
+    }
+
     fn get_oauth2rs_set(&mut self) -> Result<Vec<Arc<EntrySealedCommitted>>, OperationError> {
         self.internal_search(filter!(f_eq("class", PVCLASS_OAUTH2_RS.clone(),)))
     }
@@ -1176,6 +1257,9 @@ impl QueryServer {
             _write_ticket: write_ticket,
             resolve_filter_cache: self.resolve_filter_cache.read(),
             dyngroup_cache: self.dyngroup_cache.write(),
+            webhook_pending: Vec::new(),
+            webhook_tx: None,
+            oauth2_sessions_ended: Vec::new(),
         }
     }
 
@@ -1245,12 +1329,27 @@ impl<'a> QueryServerWriteTransaction<'a> {
         if valid_r.is_empty() {
             // Now use this to reload the backend idxmeta
             trace!("Reloading idxmeta ...");
-            self.be_txn
-                .update_idxmeta(self.schema.reload_idxmeta())
-                .map_err(|e| {
-                    admin_error!("reload schema update idxmeta {:?}", e);
-                    e
-                })
+            let idxkeys = self.schema.reload_idxmeta();
+
+            // Once running, new indexes can only come from schema that was added or altered
+            // by an administrator or replication. Existing entries must be indexed before
+            // the new index can be relied on.
+            let reindex = *self.phase >= ServerPhase::Running && {
+                let current = &self.be_txn.get_idxmeta_ref().idxkeys;
+                idxkeys.iter().any(|k| !current.contains_key(k))
+            };
+
+            self.be_txn.update_idxmeta(idxkeys).map_err(|e| {
+                admin_error!("reload schema update idxmeta {:?}", e);
+                e
+            })?;
+
+            if reindex {
+                admin_info!("New schema indexes were added, reindexing");
+                self.reindex()
+            } else {
+                Ok(())
+            }
         } else {
             // Log the failures?
             admin_error!("Schema reload failed -> {:?}", valid_r);
@@ -1573,6 +1672,8 @@ impl<'a> QueryServerWriteTransaction<'a> {
             accesscontrols,
             cid,
             dyngroup_cache,
+            webhook_pending,
+            webhook_tx,
             ..
         } = self;
         debug_assert!(!committed);
@@ -1592,6 +1693,25 @@ impl<'a> QueryServerWriteTransaction<'a> {
             .map(|_| dyngroup_cache.commit())
             .and_then(|_| accesscontrols.commit())
             .and_then(|_| be_txn.commit())
+            .map(|_| {
+                // Only deliver the events once the changes they describe are durable.
+                match webhook_tx {
+                    Some(webhook_tx) => {
+                        for delivery in webhook_pending {
+                            if webhook_tx.send(delivery).is_err() {
+                                error!("Unable to submit webhook event to queue");
+                            }
+                        }
+                    }
+                    None if !webhook_pending.is_empty() => {
+                        admin_warn!(
+                            count = webhook_pending.len(),
+                            "No webhook delivery worker is running, discarding webhook events"
+                        );
+                    }
+                    None => {}
+                }
+            })
     }
     pub(crate) fn get_txn_cid(&self) -> &Cid {
         &self.cid
@@ -1,8 +1,10 @@
 use super::modify::ModifyPartial;
-use crate::event::ReviveRecycledEvent;
+use crate::event::{DeleteEvent, PurgeRecycledEntryEvent, ReviveRecycledEvent};
 use crate::prelude::*;
 use crate::server::Plugins;
+use crate::value::MemberValidity;
 use hashbrown::HashMap;
+use std::sync::Arc;
 
 impl<'a> QueryServerWriteTransaction<'a> {
     #[instrument(level = "debug", skip_all)]
@@ -27,9 +29,18 @@ impl<'a> QueryServerWriteTransaction<'a> {
 
     #[instrument(level = "debug", skip_all)]
     pub fn purge_recycled(&mut self) -> Result<(), OperationError> {
-        // Send everything that is recycled to tombstone
-        // Search all recycled
-        let cid = self.cid.sub_secs(RECYCLEBIN_MAX_AGE).map_err(|e| {
+        // Send everything that is recycled to tombstone. Each class may define how long
+        // its entries are retained in the recycle bin, so we search from the shortest
+        // of those and then check each entry against the retention that applies to it.
+        let retention = self.get_recycle_bin_retention()?;
+        let min_age = retention
+            .iter()
+            .map(|(_, secs)| *secs)
+            .chain(std::iter::once(RECYCLEBIN_MAX_AGE))
+            .min()
+            .unwrap_or(RECYCLEBIN_MAX_AGE);
+
+        let cid = self.cid.sub_secs(min_age).map_err(|e| {
             admin_error!(err = ?e, "Unable to generate search cid");
             e
         })?;
@@ -38,11 +49,91 @@ impl<'a> QueryServerWriteTransaction<'a> {
             f_lt("last_modified_cid", PartialValue::new_cid(cid)),
         ])))?;
 
+        // When an entry matches multiple classes, the longest retention wins.
+        let rc: Vec<_> = rc
+            .into_iter()
+            .filter(|e| {
+                let max_age = retention
+                    .iter()
+                    .filter(|(class, _)| {
+                        e.attribute_equality("class", &PartialValue::new_class(class))
+                    })
+                    .map(|(_, secs)| *secs)
+                    .max()
+                    .unwrap_or(RECYCLEBIN_MAX_AGE);
+                self.cid
+                    .sub_secs(max_age)
+                    .map(|cid| {
+                        e.attribute_lessthan("last_modified_cid", &PartialValue::new_cid(cid))
+                    })
+                    .unwrap_or(false)
+            })
+            .collect();
+
         if rc.is_empty() {
             admin_info!("No recycled items present - purge operation success");
             return Ok(());
         }
 
+        self.tombstone_recycled(&rc).map(|_| {
+            admin_info!("Purge recycled operation success");
+        })
+    }
+
+    /// Immediately send the recycled entries matching this filter to tombstones,
+    /// regardless of how long they have been in the recycle bin.
+    #[instrument(level = "debug", skip_all)]
+    pub fn purge_recycled_entry(
+        &mut self,
+        pe: &PurgeRecycledEntryEvent,
+    ) -> Result<(), OperationError> {
+        if !pe.ident.is_internal() {
+            security_info!(name = %pe.ident, "purge recycled initiator");
+        }
+
+        let pre_candidates =
+            self.impersonate_search_valid(pe.filter.clone(), pe.filter.clone(), &pe.ident)?;
+
+        if pre_candidates.is_empty() {
+            request_error!(
+                "purge recycled: no candidates match filter, failure {:?}",
+                pe.filter
+            );
+            return Err(OperationError::NoMatchingEntries);
+        };
+
+        // Check access against a "fake" delete, since the entry will be irrecoverable.
+        let de = DeleteEvent {
+            ident: pe.ident.clone(),
+            filter: pe.filter.clone(),
+            filter_orig: pe.filter.clone(),
+        };
+
+        let access = self.get_accesscontrols();
+        let op_allow = access
+            .delete_allow_operation(&de, &pre_candidates)
+            .map_err(|e| {
+                admin_error!("Unable to check delete access {:?}", e);
+                e
+            })?;
+        if !op_allow {
+            return Err(OperationError::AccessDenied);
+        }
+
+        if pre_candidates.iter().any(|e| e.mask_recycled().is_some()) {
+            admin_warn!("Refusing to purge entries that are not recycled!");
+            return Err(OperationError::AccessDenied);
+        }
+
+        self.tombstone_recycled(&pre_candidates).map(|_| {
+            admin_info!("Purge recycled entry operation success");
+        })
+    }
+
+    fn tombstone_recycled(
+        &mut self,
+        rc: &[Arc<EntrySealedCommitted>],
+    ) -> Result<(), OperationError> {
         // Modify them to strip all avas except uuid
         let tombstone_cand: Result<Vec<_>, _> = rc
             .iter()
@@ -62,13 +153,80 @@ impl<'a> QueryServerWriteTransaction<'a> {
 
         // Backend Modify
         self.be_txn
-            .modify(&self.cid, &rc, &tombstone_cand)
+            .modify(&self.cid, rc, &tombstone_cand)
             .map_err(|e| {
                 admin_error!("Purge recycled operation failed (backend), {:?}", e);
                 e
             })
+    }
+
+    #[instrument(level = "debug", skip_all)]
+    pub fn purge_member_validity(&mut self) -> Result<(), OperationError> {
+        // Memberof is only evaluated during a write, so as time passes we need to remove
+        // expired members, and rewrite the validity of members that have come into effect
+        // so that their memberof is updated.
+        let ct = self.curtime;
+        let candidates = self.internal_search(filter!(f_and!([
+            f_eq("class", PVCLASS_GROUP.clone()),
+            f_pres("member_validity")
+        ])))?;
+
+        let modset: Vec<_> = candidates
+            .iter()
+            .filter_map(|e| {
+                let mods: Vec<_> = e
+                    .get_ava_member_validity("member_validity")?
+                    .iter()
+                    .flat_map(|(u, mv)| {
+                        if mv.is_expired(ct) {
+                            vec![
+                                Modify::Removed("member".into(), PartialValue::Refer(*u)),
+                                Modify::Removed("member_validity".into(), PartialValue::Refer(*u)),
+                            ]
+                        } else if mv.not_before.is_some() && !mv.is_pending(ct) {
+                            let mut m = vec![Modify::Removed(
+                                "member_validity".into(),
+                                PartialValue::Refer(*u),
+                            )];
+                            if mv.not_after.is_some() {
+                                m.push(Modify::Present(
+                                    "member_validity".into(),
+                                    Value::MemberValidity(
+                                        *u,
+                                        MemberValidity {
+                                            not_before: None,
+                                            not_after: mv.not_after,
+                                        },
+                                    ),
+                                ));
+                            }
+                            m
+                        } else {
+                            Vec::new()
+                        }
+                    })
+                    .collect();
+
+                if mods.is_empty() {
+                    None
+                } else {
+                    Some((e.get_uuid(), ModifyList::new_list(mods)))
+                }
+            })
+            .collect();
+
+        if modset.is_empty() {
+            admin_info!("No member validity changes - purge operation success");
+            return Ok(());
+        }
+
+        self.internal_batch_modify(modset.into_iter())
+            .map_err(|e| {
+                admin_error!(err = ?e, "Purge member validity operation failed");
+                e
+            })
             .map(|_| {
-                admin_info!("Purge recycled operation success");
+                admin_info!("Purge member validity operation success");
             })
     }
 
@@ -141,22 +299,28 @@ impl<'a> QueryServerWriteTransaction<'a> {
         let mut dm_mods: HashMap<Uuid, ModifyList<ModifyInvalid>> =
             HashMap::with_capacity(pre_candidates.len());
 
-        for e in &pre_candidates {
-            // Get this entries uuid.
-            let u: Uuid = e.get_uuid();
-
-            if let Some(riter) = e.get_ava_as_refuuid("directmemberof") {
-                for g_uuid in riter {
-                    dm_mods
-                        .entry(g_uuid)
-                        .and_modify(|mlist| {
-                            let m = Modify::Present(AttrString::from("member"), Value::Refer(u));
-                            mlist.push_mod(m);
-                        })
-                        .or_insert({
-                            let m = Modify::Present(AttrString::from("member"), Value::Refer(u));
-                            ModifyList::new_list(vec![m])
-                        });
+        // Memberships are only restored when requested, otherwise the entry is revived
+        // with the memberships that remain after refint removed it from its groups.
+        if re.restore_memberships {
+            for e in &pre_candidates {
+                // Get this entries uuid.
+                let u: Uuid = e.get_uuid();
+
+                if let Some(riter) = e.get_ava_as_refuuid("directmemberof") {
+                    for g_uuid in riter {
+                        dm_mods
+                            .entry(g_uuid)
+                            .and_modify(|mlist| {
+                                let m =
+                                    Modify::Present(AttrString::from("member"), Value::Refer(u));
+                                mlist.push_mod(m);
+                            })
+                            .or_insert({
+                                let m =
+                                    Modify::Present(AttrString::from("member"), Value::Refer(u));
+                                ModifyList::new_list(vec![m])
+                            });
+                    }
                 }
             }
         }
@@ -240,7 +404,7 @@ mod tests {
     use crate::server::ModifyEvent;
     use crate::server::SearchEvent;
 
-    use super::ReviveRecycledEvent;
+    use super::{PurgeRecycledEntryEvent, ReviveRecycledEvent};
 
     #[qs_test]
     async fn test_recycle_simple(server: &QueryServer) {
@@ -759,4 +923,153 @@ mod tests {
 
         assert!(server_txn.commit().is_ok());
     }
+
+    #[qs_test]
+    async fn test_revive_without_directmemberships(server: &QueryServer) {
+        let mut server_txn = server.write(duration_from_epoch_now()).await;
+        let admin = server_txn.internal_search_uuid(UUID_ADMIN).expect("failed");
+
+        let u1 = create_user("u1", "3c5e0f1e-4d47-4c57-9b39-3f9b0b8e1a01");
+        let g1 = create_group(
+            "g1",
+            "aa9c2a52-6f53-4b4e-a0b4-0b5f6dc4e502",
+            &["3c5e0f1e-4d47-4c57-9b39-3f9b0b8e1a01"],
+        );
+
+        let ce = CreateEvent::new_internal(vec![u1, g1]);
+        assert!(server_txn.create(&ce).is_ok());
+
+        let de =
+            DeleteEvent::new_internal_invalid(filter!(f_eq("name", PartialValue::new_iname("u1"))));
+        assert!(server_txn.delete(&de).is_ok());
+
+        // Revive without asking for the memberships to be restored.
+        let mut rev = ReviveRecycledEvent::new_impersonate_entry(
+            admin,
+            filter_all!(f_eq("name", PartialValue::new_iname("u1"))),
+        );
+        rev.restore_memberships = false;
+        assert!(server_txn.revive_recycled(&rev).is_ok());
+
+        assert!(!check_entry_has_mo(
+            &mut server_txn,
+            "u1",
+            "aa9c2a52-6f53-4b4e-a0b4-0b5f6dc4e502"
+        ));
+
+        let g1 = server_txn
+            .internal_search_uuid(uuid!("aa9c2a52-6f53-4b4e-a0b4-0b5f6dc4e502"))
+            .expect("failed");
+        assert!(!g1.attribute_pres("member"));
+
+        assert!(server_txn.commit().is_ok());
+    }
+
+    #[qs_test]
+    async fn test_recycle_class_retention(server: &QueryServer) {
+        let time_p1 = duration_from_epoch_now();
+        let time_p2 = time_p1 + Duration::from_secs(RECYCLEBIN_MAX_AGE * 2);
+        let time_p3 = time_p1 + Duration::from_secs(RECYCLEBIN_MAX_AGE * 8);
+
+        let filt_i_ts = filter_all!(f_eq("class", PartialValue::new_class("tombstone")));
+
+        let mut server_txn = server.write(time_p1).await;
+
+        // Groups are retained for longer than the default.
+        let modl = ModifyList::new_list(vec![Modify::Present(
+            AttrString::from("recycle_bin_retention"),
+            Value::new_iutf8(&format!("group:{}", RECYCLEBIN_MAX_AGE * 4)),
+        )]);
+        assert!(server_txn
+            .internal_modify_uuid(UUID_SYSTEM_CONFIG, &modl)
+            .is_ok());
+
+        let u1 = create_user("u1", "0a3e6f8c-1f6b-4d7e-9c59-58f1f2c9d101");
+        let g1 = create_group("g1", "5d1e6a27-94b3-4c7c-8bd8-1c2f1a6e6b02", &[]);
+        let ce = CreateEvent::new_internal(vec![u1, g1]);
+        assert!(server_txn.create(&ce).is_ok());
+
+        let de = DeleteEvent::new_internal_invalid(filter!(f_or!([
+            f_eq("name", PartialValue::new_iname("u1")),
+            f_eq("name", PartialValue::new_iname("g1")),
+        ])));
+        assert!(server_txn.delete(&de).is_ok());
+        assert!(server_txn.commit().is_ok());
+
+        // The person has passed the default retention, but the group has not.
+        let mut server_txn = server.write(time_p2).await;
+        assert!(server_txn.purge_recycled().is_ok());
+        let ts = server_txn
+            .internal_search(filt_i_ts.clone())
+            .expect("internal search failed");
+        assert!(ts.len() == 1);
+        assert!(ts[0].get_uuid() == uuid!("0a3e6f8c-1f6b-4d7e-9c59-58f1f2c9d101"));
+        assert!(server_txn.commit().is_ok());
+
+        // Now the group is past its retention too.
+        let mut server_txn = server.write(time_p3).await;
+        assert!(server_txn.purge_recycled().is_ok());
+        let ts = server_txn
+            .internal_search(filt_i_ts)
+            .expect("internal search failed");
+        assert!(ts.len() == 2);
+        assert!(server_txn.commit().is_ok());
+    }
+
+    #[qs_test]
+    async fn test_purge_recycled_entry(server: &QueryServer) {
+        let mut server_txn = server.write(duration_from_epoch_now()).await;
+        let admin = server_txn.internal_search_uuid(UUID_ADMIN).expect("failed");
+
+        let u1 = create_user("u1", "e2f0d1b3-3c3a-4f0e-8a5e-7d2f0b1c9a01");
+        let u2 = create_user("u2", "e2f0d1b3-3c3a-4f0e-8a5e-7d2f0b1c9a02");
+        let ce = CreateEvent::new_internal(vec![u1, u2]);
+        assert!(server_txn.create(&ce).is_ok());
+
+        // A live entry can't be purged.
+        let pe = PurgeRecycledEntryEvent::new_impersonate_entry(
+            admin.clone(),
+            filter_rec!(f_eq("name", PartialValue::new_iname("u1"))),
+        );
+        assert!(server_txn.purge_recycled_entry(&pe) == Err(OperationError::NoMatchingEntries));
+
+        let de = DeleteEvent::new_internal_invalid(filter!(f_or!([
+            f_eq("name", PartialValue::new_iname("u1")),
+            f_eq("name", PartialValue::new_iname("u2")),
+        ])));
+        assert!(server_txn.delete(&de).is_ok());
+
+        // Only the requested entry is purged, even though the default retention
+        // has not passed.
+        assert!(server_txn.purge_recycled_entry(&pe).is_ok());
+
+        let ts = server_txn
+            .internal_search(filter_all!(f_eq(
+                "class",
+                PartialValue::new_class("tombstone")
+            )))
+            .expect("internal search failed");
+        assert!(ts.len() == 1);
+        assert!(ts[0].get_uuid() == uuid!("e2f0d1b3-3c3a-4f0e-8a5e-7d2f0b1c9a01"));
+
+        // And it can no longer be revived.
+        assert!(server_txn
+            .internal_revive_uuid(uuid!("e2f0d1b3-3c3a-4f0e-8a5e-7d2f0b1c9a01"))
+            .is_ok());
+        assert!(server_txn
+            .internal_search_uuid(uuid!("e2f0d1b3-3c3a-4f0e-8a5e-7d2f0b1c9a01"))
+            .is_err());
+
+        // An anonymous user may not purge entries.
+        let anon = server_txn
+            .internal_search_uuid(UUID_ANONYMOUS)
+            .expect("failed");
+        let pe = PurgeRecycledEntryEvent::new_impersonate_entry(
+            anon,
+            filter_rec!(f_eq("name", PartialValue::new_iname("u2"))),
+        );
+        assert!(server_txn.purge_recycled_entry(&pe).is_err());
+
+        assert!(server_txn.commit().is_ok());
+    }
 }
@@ -63,7 +63,21 @@ pub async fn setup_idm_test() -> (IdmServer, IdmServerDelayed, IdmServerAudit) {
     qs.initialise_helper(duration_from_epoch_now())
         .await
         .expect("init failed!");
-    IdmServer::new(qs, "https://idm.example.com")
-        .await
-        .expect("Failed to setup idms")
+    let (idms, idms_delayed, idms_audit, _idms_webhook, _idms_backchannel, _idms_mail) =
+        IdmServer::new(qs, "https://idm.example.com", None, false)
+            .await
+            .expect("Failed to setup idms");
+    (idms, idms_delayed, idms_audit)
+}
+
+/// A minimal person, for tests that need accounts to act on or as.
+pub fn test_person(name: &str, uuid: Uuid) -> EntryInitNew {
+    entry_init!(
+        ("class", Value::new_class("object")),
+        ("class", Value::new_class("account")),
+        ("class", Value::new_class("person")),
+        ("name", Value::new_iname(name)),
+        ("uuid", Value::Uuid(uuid)),
+        ("displayname", Value::new_utf8s(name))
+    )
 }
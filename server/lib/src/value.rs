@@ -29,7 +29,7 @@ use kanidm_proto::v1::UatPurposeStatus;
 use kanidm_proto::v1::UiHint;
 
 use crate::be::dbentry::DbIdentSpn;
-use crate::credential::{totp::Totp, Credential};
+use crate::credential::{totp::Totp, Credential, Password};
 use crate::prelude::*;
 use crate::repl::cid::Cid;
 use crate::server::identity::IdentityId;
@@ -249,6 +249,8 @@ pub enum SyntaxType {
     TotpSecret = 30,
     ApiToken = 31,
     AuditLogString = 32,
+    PasswordHistory = 33,
+    MemberValidity = 34,
 }
 
 impl TryFrom<&str> for SyntaxType {
@@ -291,6 +293,8 @@ impl TryFrom<&str> for SyntaxType {
             "TOTPSECRET" => Ok(SyntaxType::TotpSecret),
             "APITOKEN" => Ok(SyntaxType::ApiToken),
             "AUDIT_LOG_STRING" => Ok(SyntaxType::AuditLogString),
+            "PASSWORD_HISTORY" => Ok(SyntaxType::PasswordHistory),
+            "MEMBER_VALIDITY" => Ok(SyntaxType::MemberValidity),
             _ => Err(()),
         }
     }
@@ -332,6 +336,8 @@ impl fmt::Display for SyntaxType {
             SyntaxType::TotpSecret => "TOTPSECRET",
             SyntaxType::ApiToken => "APITOKEN",
             SyntaxType::AuditLogString => "AUDIT_LOG_STRING",
+            SyntaxType::PasswordHistory => "PASSWORD_HISTORY",
+            SyntaxType::MemberValidity => "MEMBER_VALIDITY",
         })
     }
 }
@@ -848,6 +854,77 @@ pub struct Oauth2Session {
     pub rs_uuid: Uuid,
 }
 
+/// The period in which a member of a group is considered to be a member of that group. Outside
+/// of this period the member is retained in the group, but the membership is not reflected
+/// in memberof.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MemberValidity {
+    pub not_before: Option<OffsetDateTime>,
+    pub not_after: Option<OffsetDateTime>,
+}
+
+impl MemberValidity {
+    /// Is this membership in effect at the current time?
+    pub fn is_active(&self, ct: Duration) -> bool {
+        let now = OffsetDateTime::UNIX_EPOCH + ct;
+        self.not_before.map(|nb| nb <= now).unwrap_or(true)
+            && self.not_after.map(|na| now < na).unwrap_or(true)
+    }
+
+    /// Is this membership yet to come into effect?
+    pub fn is_pending(&self, ct: Duration) -> bool {
+        let now = OffsetDateTime::UNIX_EPOCH + ct;
+        self.not_before.map(|nb| now < nb).unwrap_or(false)
+    }
+
+    /// Has this membership ended, and can never be in effect again?
+    pub fn is_expired(&self, ct: Duration) -> bool {
+        let now = OffsetDateTime::UNIX_EPOCH + ct;
+        self.not_after.map(|na| na <= now).unwrap_or(false)
+    }
+}
+
+impl FromStr for MemberValidity {
+    type Err = ();
+
+    /// Parse a validity of the form `<not_before>,<not_after>` where either time is an
+    /// rfc3339 timestamp, or empty if that bound is not set.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parse_time = |t: &str| {
+            if t.is_empty() {
+                Ok(None)
+            } else {
+                OffsetDateTime::parse(t, &Rfc3339)
+                    .map(|odt| Some(odt.to_offset(time::UtcOffset::UTC)))
+                    .map_err(|_| ())
+            }
+        };
+        let (nb, na) = s.split_once(',').ok_or(())?;
+        let mv = MemberValidity {
+            not_before: parse_time(nb.trim())?,
+            not_after: parse_time(na.trim())?,
+        };
+        match (mv.not_before, mv.not_after) {
+            (None, None) => Err(()),
+            (Some(nb), Some(na)) if na <= nb => Err(()),
+            _ => Ok(mv),
+        }
+    }
+}
+
+impl fmt::Display for MemberValidity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let fmt_time =
+            |t: Option<OffsetDateTime>| t.and_then(|t| t.format(&Rfc3339).ok()).unwrap_or_default();
+        write!(
+            f,
+            "{},{}",
+            fmt_time(self.not_before),
+            fmt_time(self.not_after)
+        )
+    }
+}
+
 /// A value is a complete unit of data for an attribute. It is made up of a PartialValue, which is
 /// used for selection, filtering, searching, matching etc. It also contains supplemental data
 /// which may be stored inside of the Value, such as credential secrets, blobs etc.
@@ -900,6 +977,8 @@ pub enum Value {
 
     TotpSecret(String, Totp),
     AuditLogString(Cid, String),
+    PasswordHistory(Cid, Password),
+    MemberValidity(Uuid, MemberValidity),
 }
 
 impl PartialEq for Value {
@@ -930,13 +1009,16 @@ impl PartialEq for Value {
             // Uint32
             (Value::Uint32(a), Value::Uint32(b)) => a.eq(b),
             // Cid
-            (Value::Cid(a), Value::Cid(b)) => a.eq(b),
+            (Value::Cid(a), Value::Cid(b))
+            | (Value::PasswordHistory(a, _), Value::PasswordHistory(b, _)) => a.eq(b),
             // DateTime
             (Value::DateTime(a), Value::DateTime(b)) => a.eq(b),
             // Url
             (Value::Url(a), Value::Url(b)) => a.eq(b),
             // OauthScopeMap
             (Value::OauthScopeMap(a, c), Value::OauthScopeMap(b, d)) => a.eq(b) && c.eq(d),
+            // MemberValidity
+            (Value::MemberValidity(a, c), Value::MemberValidity(b, d)) => a.eq(b) && c.eq(d),
 
             (Value::Address(_), Value::Address(_))
             | (Value::PrivateBinary(_), Value::PrivateBinary(_))
@@ -1679,6 +1761,11 @@ impl Value {
             Value::EmailAddress(mail, _) => VALIDATE_EMAIL_RE.is_match(mail.as_str()),
             Value::OauthScope(s) => OAUTHSCOPE_RE.is_match(s),
             Value::OauthScopeMap(_, m) => m.iter().all(|s| OAUTHSCOPE_RE.is_match(s)),
+            Value::MemberValidity(_, mv) => match (mv.not_before, mv.not_after) {
+                (None, None) => false,
+                (Some(nb), Some(na)) => nb < na,
+                _ => true,
+            },
 
             Value::PhoneNumber(_, _) => true,
             Value::Address(_) => true,
@@ -1699,6 +1786,7 @@ impl Value {
             | Value::Session(_, _)
             | Value::Oauth2Session(_, _)
             | Value::JwsKeyRs256(_)
+            | Value::PasswordHistory(_, _)
             | Value::UiHint(_) => true,
         }
     }
@@ -348,8 +348,14 @@ impl ValueSetT for ValueSetEmailAddress {
         }
     }
 
-    fn substring(&self, _pv: &PartialValue) -> bool {
-        false
+    fn substring(&self, pv: &PartialValue) -> bool {
+        match pv {
+            PartialValue::EmailAddress(s2) => self.set.iter().any(|s1| s1.contains(s2)),
+            _ => {
+                debug_assert!(false);
+                false
+            }
+        }
     }
 
     fn lessthan(&self, _pv: &PartialValue) -> bool {
@@ -0,0 +1,232 @@
+use std::collections::BTreeMap;
+
+use time::OffsetDateTime;
+
+use crate::be::dbvalue::DbValueMemberValidityV1;
+use crate::prelude::*;
+use crate::repl::proto::{ReplAttrV1, ReplMemberValidityV1};
+use crate::schema::SchemaAttribute;
+use crate::value::MemberValidity;
+use crate::valueset::{uuid_to_proto_string, DbValueSetV2, ValueSet};
+
+fn time_to_string(t: Option<OffsetDateTime>) -> Option<String> {
+    t.map(|t| {
+        debug_assert!(t.offset() == time::UtcOffset::UTC);
+        #[allow(clippy::expect_used)]
+        t.format(&Rfc3339)
+            .expect("Failed to format timestamp into RFC3339")
+    })
+}
+
+fn time_from_string(s: Option<&String>) -> Result<Option<OffsetDateTime>, OperationError> {
+    s.map(|s| {
+        OffsetDateTime::parse(s, &Rfc3339)
+            .map(|odt| odt.to_offset(time::UtcOffset::UTC))
+            .map_err(|_| OperationError::InvalidValueState)
+    })
+    .transpose()
+}
+
+/// The validity periods of time bounded group members, keyed by the uuid of the member.
+#[derive(Debug, Clone)]
+pub struct ValueSetMemberValidity {
+    map: BTreeMap<Uuid, MemberValidity>,
+}
+
+impl ValueSetMemberValidity {
+    pub fn new(u: Uuid, mv: MemberValidity) -> Box<Self> {
+        let mut map = BTreeMap::new();
+        map.insert(u, mv);
+        Box::new(ValueSetMemberValidity { map })
+    }
+
+    pub fn push(&mut self, u: Uuid, mv: MemberValidity) -> bool {
+        self.map.insert(u, mv).is_none()
+    }
+
+    pub fn from_dbvs2(data: Vec<DbValueMemberValidityV1>) -> Result<ValueSet, OperationError> {
+        let map = data
+            .into_iter()
+            .map(
+                |DbValueMemberValidityV1 {
+                     refer,
+                     not_before,
+                     not_after,
+                 }| {
+                    Ok((
+                        refer,
+                        MemberValidity {
+                            not_before: time_from_string(not_before.as_ref())?,
+                            not_after: time_from_string(not_after.as_ref())?,
+                        },
+                    ))
+                },
+            )
+            .collect::<Result<_, _>>()?;
+        Ok(Box::new(ValueSetMemberValidity { map }))
+    }
+
+    pub fn from_repl_v1(data: &[ReplMemberValidityV1]) -> Result<ValueSet, OperationError> {
+        let map = data
+            .iter()
+            .map(
+                |ReplMemberValidityV1 {
+                     refer,
+                     not_before,
+                     not_after,
+                 }| {
+                    Ok((
+                        *refer,
+                        MemberValidity {
+                            not_before: time_from_string(not_before.as_ref())?,
+                            not_after: time_from_string(not_after.as_ref())?,
+                        },
+                    ))
+                },
+            )
+            .collect::<Result<_, _>>()?;
+        Ok(Box::new(ValueSetMemberValidity { map }))
+    }
+}
+
+impl ValueSetT for ValueSetMemberValidity {
+    fn insert_checked(&mut self, value: Value) -> Result<bool, OperationError> {
+        match value {
+            // As with oauth2 scope maps, a present of an existing member replaces
+            // the validity of that member.
+            Value::MemberValidity(u, mv) => {
+                self.map.insert(u, mv);
+                Ok(true)
+            }
+            _ => {
+                debug_assert!(false);
+                Err(OperationError::InvalidValueState)
+            }
+        }
+    }
+
+    fn clear(&mut self) {
+        self.map.clear();
+    }
+
+    fn remove(&mut self, pv: &PartialValue) -> bool {
+        match pv {
+            PartialValue::Refer(u) => self.map.remove(u).is_some(),
+            _ => false,
+        }
+    }
+
+    fn contains(&self, pv: &PartialValue) -> bool {
+        match pv {
+            PartialValue::Refer(u) => self.map.contains_key(u),
+            _ => false,
+        }
+    }
+
+    fn substring(&self, _pv: &PartialValue) -> bool {
+        false
+    }
+
+    fn lessthan(&self, _pv: &PartialValue) -> bool {
+        false
+    }
+
+    fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    fn generate_idx_eq_keys(&self) -> Vec<String> {
+        self.map
+            .keys()
+            .map(|u| u.as_hyphenated().to_string())
+            .collect()
+    }
+
+    fn syntax(&self) -> SyntaxType {
+        SyntaxType::MemberValidity
+    }
+
+    fn validate(&self, _schema_attr: &SchemaAttribute) -> bool {
+        self.map
+            .values()
+            .all(|mv| match (mv.not_before, mv.not_after) {
+                (None, None) => false,
+                (Some(nb), Some(na)) => nb < na,
+                _ => true,
+            })
+    }
+
+    fn to_proto_string_clone_iter(&self) -> Box<dyn Iterator<Item = String> + '_> {
+        Box::new(
+            self.map
+                .iter()
+                .map(|(u, mv)| format!("{},{}", uuid_to_proto_string(*u), mv)),
+        )
+    }
+
+    fn to_db_valueset_v2(&self) -> DbValueSetV2 {
+        DbValueSetV2::MemberValidity(
+            self.map
+                .iter()
+                .map(|(u, mv)| DbValueMemberValidityV1 {
+                    refer: *u,
+                    not_before: time_to_string(mv.not_before),
+                    not_after: time_to_string(mv.not_after),
+                })
+                .collect(),
+        )
+    }
+
+    fn to_repl_v1(&self) -> ReplAttrV1 {
+        ReplAttrV1::MemberValidity {
+            set: self
+                .map
+                .iter()
+                .map(|(u, mv)| ReplMemberValidityV1 {
+                    refer: *u,
+                    not_before: time_to_string(mv.not_before),
+                    not_after: time_to_string(mv.not_after),
+                })
+                .collect(),
+        }
+    }
+
+    fn to_partialvalue_iter(&self) -> Box<dyn Iterator<Item = PartialValue> + '_> {
+        Box::new(self.map.keys().cloned().map(PartialValue::Refer))
+    }
+
+    fn to_value_iter(&self) -> Box<dyn Iterator<Item = Value> + '_> {
+        Box::new(
+            self.map
+                .iter()
+                .map(|(u, mv)| Value::MemberValidity(*u, *mv)),
+        )
+    }
+
+    fn equal(&self, other: &ValueSet) -> bool {
+        if let Some(other) = other.as_member_validity_map() {
+            &self.map == other
+        } else {
+            debug_assert!(false);
+            false
+        }
+    }
+
+    fn merge(&mut self, other: &ValueSet) -> Result<(), OperationError> {
+        if let Some(b) = other.as_member_validity_map() {
+            mergemaps!(self.map, b)
+        } else {
+            debug_assert!(false);
+            Err(OperationError::InvalidValueState)
+        }
+    }
+
+    fn as_member_validity_map(&self) -> Option<&BTreeMap<Uuid, MemberValidity>> {
+        Some(&self.map)
+    }
+
+    fn as_ref_uuid_iter(&self) -> Option<Box<dyn Iterator<Item = Uuid> + '_>> {
+        // This allows referential integrity to remove the validity of deleted members.
+        Some(Box::new(self.map.keys().copied()))
+    }
+}
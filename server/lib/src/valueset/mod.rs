@@ -13,11 +13,11 @@ use kanidm_proto::v1::Filter as ProtoFilter;
 use kanidm_proto::v1::UiHint;
 
 use crate::be::dbvalue::DbValueSetV2;
-use crate::credential::{totp::Totp, Credential};
+use crate::credential::{totp::Totp, Credential, Password};
 use crate::prelude::*;
 use crate::repl::{cid::Cid, proto::ReplAttrV1};
 use crate::schema::SchemaAttribute;
-use crate::value::{Address, ApiToken, IntentTokenState, Oauth2Session, Session};
+use crate::value::{Address, ApiToken, IntentTokenState, MemberValidity, Oauth2Session, Session};
 use crate::valueset::auditlogstring::ValueSetAuditLogString;
 
 pub use self::address::{ValueSetAddress, ValueSetEmailAddress};
@@ -31,8 +31,10 @@ pub use self::index::ValueSetIndex;
 pub use self::iutf8::ValueSetIutf8;
 pub use self::json::ValueSetJsonFilter;
 pub use self::jws::{ValueSetJwsKeyEs256, ValueSetJwsKeyRs256};
+pub use self::membervalidity::ValueSetMemberValidity;
 pub use self::nsuniqueid::ValueSetNsUniqueId;
 pub use self::oauth::{ValueSetOauthScope, ValueSetOauthScopeMap};
+pub use self::pwhistory::ValueSetPasswordHistory;
 pub use self::restricted::ValueSetRestricted;
 pub use self::secret::ValueSetSecret;
 pub use self::session::{ValueSetApiToken, ValueSetOauth2Session, ValueSetSession};
@@ -58,8 +60,10 @@ mod index;
 mod iutf8;
 mod json;
 mod jws;
+mod membervalidity;
 mod nsuniqueid;
 mod oauth;
+mod pwhistory;
 mod restricted;
 mod secret;
 mod session;
@@ -532,6 +536,15 @@ pub trait ValueSetT: std::fmt::Debug + DynClone {
         None
     }
 
+    fn as_password_history(&self) -> Option<&BTreeMap<Cid, Password>> {
+        debug_assert!(false);
+        None
+    }
+
+    fn as_member_validity_map(&self) -> Option<&BTreeMap<Uuid, MemberValidity>> {
+        None
+    }
+
     fn repl_merge_valueset(
         &self,
         _older: &ValueSet,
@@ -605,6 +618,8 @@ pub fn from_result_value_iter(
         Value::EmailAddress(a, _) => ValueSetEmailAddress::new(a),
         Value::UiHint(u) => ValueSetUiHint::new(u),
         Value::AuditLogString(c, s) => ValueSetAuditLogString::new((c, s)),
+        Value::PasswordHistory(c, p) => ValueSetPasswordHistory::new(c, p),
+        Value::MemberValidity(u, mv) => ValueSetMemberValidity::new(u, mv),
         Value::PhoneNumber(_, _)
         | Value::Passkey(_, _, _)
         | Value::DeviceKey(_, _, _)
@@ -670,6 +685,8 @@ pub fn from_value_iter(mut iter: impl Iterator<Item = Value>) -> Result<ValueSet
         Value::UiHint(u) => ValueSetUiHint::new(u),
         Value::TotpSecret(l, t) => ValueSetTotpSecret::new(l, t),
         Value::AuditLogString(c, s) => ValueSetAuditLogString::new((c, s)),
+        Value::PasswordHistory(c, p) => ValueSetPasswordHistory::new(c, p),
+        Value::MemberValidity(u, mv) => ValueSetMemberValidity::new(u, mv),
         Value::PhoneNumber(_, _) => {
             debug_assert!(false);
             return Err(OperationError::InvalidValueState);
@@ -720,6 +737,8 @@ pub fn from_db_valueset_v2(dbvs: DbValueSetV2) -> Result<ValueSet, OperationErro
         DbValueSetV2::UiHint(set) => ValueSetUiHint::from_dbvs2(set),
         DbValueSetV2::TotpSecret(set) => ValueSetTotpSecret::from_dbvs2(set),
         DbValueSetV2::AuditLogString(set) => ValueSetAuditLogString::from_dbvs2(set),
+        DbValueSetV2::PasswordHistory(set) => ValueSetPasswordHistory::from_dbvs2(set),
+        DbValueSetV2::MemberValidity(set) => ValueSetMemberValidity::from_dbvs2(set),
         DbValueSetV2::PhoneNumber(_, _) | DbValueSetV2::TrustedDeviceEnrollment(_) => {
             debug_assert!(false);
             Err(OperationError::InvalidValueState)
@@ -767,5 +786,7 @@ pub fn from_repl_v1(rv1: &ReplAttrV1) -> Result<ValueSet, OperationError> {
         ReplAttrV1::ApiToken { set } => ValueSetApiToken::from_repl_v1(set),
         ReplAttrV1::TotpSecret { set } => ValueSetTotpSecret::from_repl_v1(set),
         ReplAttrV1::AuditLogString { set } => ValueSetAuditLogString::from_repl_v1(set),
+        ReplAttrV1::PasswordHistory { set } => ValueSetPasswordHistory::from_repl_v1(set),
+        ReplAttrV1::MemberValidity { set } => ValueSetMemberValidity::from_repl_v1(set),
     }
 }
@@ -0,0 +1,164 @@
+use std::collections::BTreeMap;
+
+use crate::be::dbvalue::DbPasswordV1;
+use crate::credential::Password;
+use crate::prelude::*;
+use crate::repl::cid::Cid;
+use crate::repl::proto::{ReplAttrV1, ReplPasswordV1};
+use crate::schema::SchemaAttribute;
+use crate::valueset::{DbValueSetV2, ValueSet};
+
+/// The hashes of passwords previously set on an account, keyed by the change
+/// id they were set at. This is maintained by the password history plugin.
+#[derive(Debug, Clone)]
+pub struct ValueSetPasswordHistory {
+    map: BTreeMap<Cid, Password>,
+}
+
+impl ValueSetPasswordHistory {
+    pub fn new(c: Cid, p: Password) -> Box<Self> {
+        let mut map = BTreeMap::new();
+        map.insert(c, p);
+        Box::new(ValueSetPasswordHistory { map })
+    }
+
+    pub fn push(&mut self, c: Cid, p: Password) -> bool {
+        self.map.insert(c, p).is_none()
+    }
+
+    pub fn from_dbvs2(data: Vec<(Cid, DbPasswordV1)>) -> Result<ValueSet, OperationError> {
+        let map = data
+            .into_iter()
+            .map(|(c, data)| {
+                Password::try_from(data)
+                    .map_err(|()| OperationError::InvalidValueState)
+                    .map(|p| (c, p))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Box::new(ValueSetPasswordHistory { map }))
+    }
+
+    pub fn from_repl_v1(data: &[(Cid, ReplPasswordV1)]) -> Result<ValueSet, OperationError> {
+        let map = data
+            .iter()
+            .map(|(c, data)| {
+                Password::try_from(data)
+                    .map_err(|()| OperationError::InvalidValueState)
+                    .map(|p| (c.clone(), p))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Box::new(ValueSetPasswordHistory { map }))
+    }
+}
+
+impl ValueSetT for ValueSetPasswordHistory {
+    fn insert_checked(&mut self, value: Value) -> Result<bool, OperationError> {
+        match value {
+            Value::PasswordHistory(c, p) => Ok(self.push(c, p)),
+            _ => {
+                debug_assert!(false);
+                Err(OperationError::InvalidValueState)
+            }
+        }
+    }
+
+    fn clear(&mut self) {
+        self.map.clear();
+    }
+
+    fn remove(&mut self, pv: &PartialValue) -> bool {
+        match pv {
+            PartialValue::Cid(c) => self.map.remove(c).is_some(),
+            _ => false,
+        }
+    }
+
+    fn contains(&self, pv: &PartialValue) -> bool {
+        match pv {
+            PartialValue::Cid(c) => self.map.contains_key(c),
+            _ => false,
+        }
+    }
+
+    fn substring(&self, _pv: &PartialValue) -> bool {
+        false
+    }
+
+    fn lessthan(&self, _pv: &PartialValue) -> bool {
+        false
+    }
+
+    fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    fn generate_idx_eq_keys(&self) -> Vec<String> {
+        self.map.keys().map(|c| c.to_string()).collect()
+    }
+
+    fn syntax(&self) -> SyntaxType {
+        SyntaxType::PasswordHistory
+    }
+
+    fn validate(&self, _schema_attr: &SchemaAttribute) -> bool {
+        true
+    }
+
+    fn to_proto_string_clone_iter(&self) -> Box<dyn Iterator<Item = String> + '_> {
+        // Never disclose the hashes, only when they were set.
+        Box::new(self.map.keys().map(|c| c.to_string()))
+    }
+
+    fn to_db_valueset_v2(&self) -> DbValueSetV2 {
+        DbValueSetV2::PasswordHistory(
+            self.map
+                .iter()
+                .map(|(c, p)| (c.clone(), p.to_dbpasswordv1()))
+                .collect(),
+        )
+    }
+
+    fn to_repl_v1(&self) -> ReplAttrV1 {
+        ReplAttrV1::PasswordHistory {
+            set: self
+                .map
+                .iter()
+                .map(|(c, p)| (c.clone(), p.to_repl_v1()))
+                .collect(),
+        }
+    }
+
+    fn to_partialvalue_iter(&self) -> Box<dyn Iterator<Item = PartialValue> + '_> {
+        Box::new(self.map.keys().cloned().map(PartialValue::Cid))
+    }
+
+    fn to_value_iter(&self) -> Box<dyn Iterator<Item = Value> + '_> {
+        Box::new(
+            self.map
+                .iter()
+                .map(|(c, p)| Value::PasswordHistory(c.clone(), p.clone())),
+        )
+    }
+
+    fn equal(&self, other: &ValueSet) -> bool {
+        if let Some(other) = other.as_password_history() {
+            self.map == *other
+        } else {
+            debug_assert!(false);
+            false
+        }
+    }
+
+    fn merge(&mut self, other: &ValueSet) -> Result<(), OperationError> {
+        if let Some(b) = other.as_password_history() {
+            mergemaps!(self.map, b)
+        } else {
+            debug_assert!(false);
+            Err(OperationError::InvalidValueState)
+        }
+    }
+
+    fn as_password_history(&self) -> Option<&BTreeMap<Cid, Password>> {
+        Some(&self.map)
+    }
+}
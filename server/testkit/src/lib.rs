@@ -13,8 +13,8 @@
 use std::net::TcpStream;
 use std::sync::atomic::{AtomicU16, Ordering};
 
-use kanidm_client::{KanidmClient, KanidmClientBuilder};
-use kanidm_proto::v1::{Filter, Modify, ModifyList};
+use kanidm_client::{ClientError, KanidmClient, KanidmClientBuilder};
+use kanidm_proto::v1::{Filter, Modify, ModifyList, OperationError, PasswordFeedback};
 use kanidmd_core::config::{Configuration, IntegrationTestConfig};
 use kanidmd_core::{create_server_core, CoreHandle};
 use tokio::task;
@@ -260,11 +260,18 @@ pub async fn login_account(rsclient: &KanidmClient, id: &str) {
         .await
         .expect("Failed to add user to idm_people_extend_priv");
 
-    #[allow(clippy::expect_used)]
-    rsclient
+    // The password may already be set from an earlier login, in which case the
+    // password history rejects it as previously used.
+    #[allow(clippy::panic)]
+    match rsclient
         .idm_person_account_primary_credential_set_password(id, NOT_ADMIN_TEST_PASSWORD)
         .await
-        .expect("Failed to set password for user");
+    {
+        Ok(()) => {}
+        Err(ClientError::Http(_, Some(OperationError::PasswordQuality(feedback)), _))
+            if matches!(feedback.as_slice(), [PasswordFeedback::PreviouslyUsed]) => {}
+        Err(e) => panic!("Failed to set password for user: {:?}", e),
+    }
 
     let _ = rsclient.logout().await;
     let res = rsclient
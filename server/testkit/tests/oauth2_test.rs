@@ -2,12 +2,15 @@
 use std::collections::HashMap;
 use std::convert::TryFrom;
 use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
 
-use compact_jwt::{JwkKeySet, JwsValidator, OidcToken, OidcUnverified};
+use compact_jwt::{JwkKeySet, Jws, JwsSigner, JwsValidator, OidcToken, OidcUnverified};
 use kanidm_proto::constants::APPLICATION_JSON;
 use kanidm_proto::oauth2::{
     AccessTokenIntrospectRequest, AccessTokenIntrospectResponse, AccessTokenRequest,
-    AccessTokenResponse, AuthorisationResponse, GrantTypeReq, OidcDiscoveryResponse,
+    AccessTokenResponse, AuthorisationResponse, ClientRegistrationResponse, EndSessionRequest,
+    EndSessionResponse, GrantTypeReq, OidcDiscoveryResponse, PushedAuthorisationResponse,
+    OAUTH2_CLIENT_ASSERTION_TYPE_JWT_BEARER,
 };
 use oauth2_ext::PkceCodeChallenge;
 use reqwest::header::{HeaderValue, CONTENT_TYPE};
@@ -200,6 +203,13 @@ async fn test_oauth2_openid_basic_flow(rsclient: KanidmClient) {
             .unwrap()
     );
 
+    assert!(
+        discovery.end_session_endpoint
+            == Some(Url::parse(&format!("{}/ui/oauth2/logout", url)).unwrap())
+    );
+    // No back-channel logout uri is configured.
+    assert!(!discovery.backchannel_logout_supported);
+
     // Step 0 - get the jwks public key.
     let response = client
         .get(format!(
@@ -272,7 +282,7 @@ async fn test_oauth2_openid_basic_flow(rsclient: KanidmClient) {
 
     let response = client
         .get(format!("{}/oauth2/authorise/permit", url))
-        .bearer_auth(oauth_test_uat)
+        .bearer_auth(oauth_test_uat.clone())
         .query(&[("token", consent_token.as_str())])
         .send()
         .await
@@ -336,6 +346,9 @@ async fn test_oauth2_openid_basic_flow(rsclient: KanidmClient) {
     let intr_request = AccessTokenIntrospectRequest {
         token: atr.access_token.clone(),
         token_type_hint: None,
+        client_id: None,
+        client_assertion_type: None,
+        client_assertion: None,
     };
 
     let response = client
@@ -405,7 +418,41 @@ async fn test_oauth2_openid_basic_flow(rsclient: KanidmClient) {
     eprintln!("userinfo {userinfo:?}");
     eprintln!("oidc {oidc:?}");
 
-    assert!(userinfo == oidc);
+    // The session id is only part of the id_token.
+    assert!(oidc.claims.contains_key("sid"));
+    let mut oidc_claims = oidc.clone();
+    oidc_claims.claims.remove("sid");
+    assert!(userinfo == oidc_claims);
+
+    // Step 6 - the resource server sends the user to logout with the id_token as a hint.
+    let end_session_req = EndSessionRequest {
+        id_token_hint: atr.id_token.clone(),
+        post_logout_redirect_uri: Some(
+            Url::parse("https://demo.example.com/logged_out").expect("Invalid URL"),
+        ),
+        state: Some("YWJjZGVm".to_string()),
+        ..Default::default()
+    };
+
+    let response = client
+        .post(format!("{}/oauth2/end_session", url))
+        .bearer_auth(oauth_test_uat)
+        .json(&end_session_req)
+        .send()
+        .await
+        .expect("Failed to send end session request.");
+
+    assert!(response.status() == reqwest::StatusCode::OK);
+    let esr = response
+        .json::<EndSessionResponse>()
+        .await
+        .expect("Unable to decode EndSessionResponse");
+
+    assert!(esr.logged_out);
+    assert!(
+        esr.redirect_uri
+            == Some(Url::parse("https://demo.example.com/logged_out?state=YWJjZGVm").unwrap())
+    );
 
     // auth back with admin so we can test deleting things
     let res = rsclient
@@ -608,6 +655,8 @@ async fn test_oauth2_openid_public_flow(rsclient: KanidmClient) {
         },
         client_id: Some("test_integration".to_string()),
         client_secret: None,
+        client_assertion_type: None,
+        client_assertion: None,
     };
 
     let response = client
@@ -675,7 +724,11 @@ async fn test_oauth2_openid_public_flow(rsclient: KanidmClient) {
     eprintln!("userinfo {userinfo:?}");
     eprintln!("oidc {oidc:?}");
 
-    assert!(userinfo == oidc);
+    // The session id is only part of the id_token.
+    assert!(oidc.claims.contains_key("sid"));
+    let mut oidc_claims = oidc.clone();
+    oidc_claims.claims.remove("sid");
+    assert!(userinfo == oidc_claims);
 
     // auth back with admin so we can test deleting things
     let res = rsclient
@@ -783,3 +836,539 @@ async fn test_oauth2_token_revoke_post(rsclient: KanidmClient) {
     println!("{:?}", response);
     assert!(response.status() == StatusCode::UNSUPPORTED_MEDIA_TYPE);
 }
+
+#[kanidmd_testkit::test]
+async fn test_oauth2_private_key_jwt_client_auth(rsclient: KanidmClient) {
+    let res = rsclient
+        .auth_simple_password("admin", ADMIN_TEST_PASSWORD)
+        .await;
+    assert!(res.is_ok());
+
+    rsclient
+        .idm_oauth2_rs_basic_create(
+            TEST_INTEGRATION_RS_ID,
+            TEST_INTEGRATION_RS_DISPLAY,
+            TEST_INTEGRATION_RS_URL,
+        )
+        .await
+        .expect("Failed to create oauth2 config");
+
+    let signer = JwsSigner::generate_es256().expect("Failed to generate signer");
+    let jwks = JwkKeySet {
+        keys: vec![signer
+            .public_key_as_jwk()
+            .expect("Failed to get public key")],
+    };
+    rsclient
+        .idm_oauth2_rs_set_jwks(
+            TEST_INTEGRATION_RS_ID,
+            &serde_json::to_string(&jwks).expect("Failed to serialise jwks"),
+        )
+        .await
+        .expect("Failed to set jwks");
+
+    let url = rsclient.get_url().to_string();
+    let client = reqwest::Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .no_proxy()
+        .build()
+        .expect("Failed to create client.");
+
+    let discovery: OidcDiscoveryResponse = client
+        .get(format!(
+            "{}/oauth2/openid/test_integration/.well-known/openid-configuration",
+            url
+        ))
+        .send()
+        .await
+        .expect("Failed to send request.")
+        .json()
+        .await
+        .expect("Failed to access response body");
+
+    let exp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("Failed to get the current time")
+        .as_secs()
+        + 60;
+    let assertion = Jws::new(serde_json::json!({
+        "iss": TEST_INTEGRATION_RS_ID,
+        "sub": TEST_INTEGRATION_RS_ID,
+        "aud": discovery.token_endpoint.as_str(),
+        "exp": exp,
+    }))
+    .sign(&signer)
+    .expect("Failed to sign assertion")
+    .to_string();
+
+    let intr_request = AccessTokenIntrospectRequest {
+        token: "invalid".to_string(),
+        token_type_hint: None,
+        client_id: Some(TEST_INTEGRATION_RS_ID.to_string()),
+        client_assertion_type: Some(OAUTH2_CLIENT_ASSERTION_TYPE_JWT_BEARER.to_string()),
+        client_assertion: Some(assertion),
+    };
+
+    // The client authenticates, so we get as far as rejecting the token itself.
+    let response = client
+        .post(format!("{}/oauth2/token/introspect", url))
+        .form(&intr_request)
+        .send()
+        .await
+        .expect("Failed to send token introspection request.");
+    assert!(response.status() == StatusCode::BAD_REQUEST);
+
+    // Once the keys are removed the same assertion no longer authenticates.
+    rsclient
+        .idm_oauth2_rs_clear_jwks(TEST_INTEGRATION_RS_ID)
+        .await
+        .expect("Failed to clear jwks");
+
+    let response = client
+        .post(format!("{}/oauth2/token/introspect", url))
+        .form(&intr_request)
+        .send()
+        .await
+        .expect("Failed to send token introspection request.");
+    assert!(response.status() == StatusCode::UNAUTHORIZED);
+}
+
+#[kanidmd_testkit::test]
+async fn test_oauth2_pushed_authorisation_request(rsclient: KanidmClient) {
+    let res = rsclient
+        .auth_simple_password("admin", ADMIN_TEST_PASSWORD)
+        .await;
+    assert!(res.is_ok());
+
+    rsclient
+        .idm_oauth2_rs_basic_create(
+            TEST_INTEGRATION_RS_ID,
+            TEST_INTEGRATION_RS_DISPLAY,
+            TEST_INTEGRATION_RS_URL,
+        )
+        .await
+        .expect("Failed to create oauth2 config");
+
+    rsclient
+        .idm_oauth2_rs_update_scope_map(
+            TEST_INTEGRATION_RS_ID,
+            TEST_INTEGRATION_RS_GROUP_ALL,
+            vec!["openid"],
+        )
+        .await
+        .expect("Failed to update oauth2 scopes");
+
+    rsclient
+        .idm_oauth2_rs_require_pushed_authorisation(TEST_INTEGRATION_RS_ID)
+        .await
+        .expect("Failed to require pushed authorisation");
+
+    let client_secret = rsclient
+        .idm_oauth2_rs_get_basic_secret(TEST_INTEGRATION_RS_ID)
+        .await
+        .ok()
+        .flatten()
+        .expect("Failed to retrieve test_integration basic secret");
+
+    rsclient
+        .idm_group_add_members("idm_admins", &["admin"])
+        .await
+        .unwrap();
+
+    rsclient
+        .idm_person_account_create("oauth_test", "oauth_test")
+        .await
+        .expect("Failed to create account details");
+
+    rsclient
+        .idm_person_account_primary_credential_set_password("oauth_test", ADMIN_TEST_PASSWORD)
+        .await
+        .expect("Failed to configure account password");
+
+    let res = rsclient
+        .auth_simple_password("oauth_test", ADMIN_TEST_PASSWORD)
+        .await;
+    assert!(res.is_ok());
+    let oauth_test_uat = rsclient
+        .get_token()
+        .await
+        .expect("No user auth token found");
+
+    let url = rsclient.get_url().to_string();
+    let client = reqwest::Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .no_proxy()
+        .build()
+        .expect("Failed to create client.");
+
+    let (pkce_code_challenge, _pkce_code_verifier) = PkceCodeChallenge::new_random_sha256();
+    let auth_params = [
+        ("response_type", "code"),
+        ("client_id", TEST_INTEGRATION_RS_ID),
+        ("state", "YWJjZGVm"),
+        ("code_challenge", pkce_code_challenge.as_str()),
+        ("code_challenge_method", "S256"),
+        ("redirect_uri", "https://demo.example.com/oauth2/flow"),
+        ("scope", "openid"),
+    ];
+
+    // The resource server requires pushed requests, so a direct request is refused.
+    let response = client
+        .get(format!("{}/oauth2/authorise", url))
+        .bearer_auth(oauth_test_uat.clone())
+        .query(&auth_params)
+        .send()
+        .await
+        .expect("Failed to send request.");
+    assert!(response.status() == StatusCode::BAD_REQUEST);
+
+    let response = client
+        .post(format!("{}/oauth2/par", url))
+        .basic_auth(TEST_INTEGRATION_RS_ID, Some(client_secret))
+        .form(&auth_params)
+        .send()
+        .await
+        .expect("Failed to send pushed authorisation request.");
+    assert!(response.status() == StatusCode::CREATED);
+
+    let par_res: PushedAuthorisationResponse = response
+        .json()
+        .await
+        .expect("Failed to access response body");
+    assert!(par_res.expires_in > 0);
+
+    let response = client
+        .get(format!("{}/oauth2/authorise", url))
+        .bearer_auth(oauth_test_uat.clone())
+        .query(&[
+            ("client_id", TEST_INTEGRATION_RS_ID),
+            ("request_uri", par_res.request_uri.as_str()),
+        ])
+        .send()
+        .await
+        .expect("Failed to send request.");
+    assert!(response.status() == StatusCode::OK);
+
+    let consent_req: AuthorisationResponse = response
+        .json()
+        .await
+        .expect("Failed to access response body");
+    assert!(matches!(
+        consent_req,
+        AuthorisationResponse::ConsentRequested { .. }
+    ));
+
+    // The request_uri can't be used again.
+    let response = client
+        .get(format!("{}/oauth2/authorise", url))
+        .bearer_auth(oauth_test_uat.clone())
+        .query(&[
+            ("client_id", TEST_INTEGRATION_RS_ID),
+            ("request_uri", par_res.request_uri.as_str()),
+        ])
+        .send()
+        .await
+        .expect("Failed to send request.");
+    assert!(response.status() == StatusCode::BAD_REQUEST);
+}
+
+#[kanidmd_testkit::test]
+async fn test_oauth2_dynamic_client_registration(rsclient: KanidmClient) {
+    let res = rsclient
+        .auth_simple_password("admin", ADMIN_TEST_PASSWORD)
+        .await;
+    assert!(res.is_ok());
+    let admin_uat = rsclient
+        .get_token()
+        .await
+        .expect("No user auth token found");
+
+    rsclient
+        .idm_group_add_members("idm_admins", &["admin"])
+        .await
+        .unwrap();
+
+    rsclient
+        .idm_service_account_create("test_registrar", "Test Registrar")
+        .await
+        .expect("Failed to create service account");
+
+    rsclient
+        .idm_service_account_oauth2_registrar_add_domain("test_registrar", "example.com")
+        .await
+        .expect("Failed to add registrar domain");
+
+    rsclient
+        .idm_service_account_oauth2_registrar_update_scope_map(
+            "test_registrar",
+            TEST_INTEGRATION_RS_GROUP_ALL,
+            vec!["openid", "email"],
+        )
+        .await
+        .expect("Failed to update registrar scopes");
+
+    let registrar_token = rsclient
+        .idm_service_account_generate_api_token("test_registrar", "registrar", None, true)
+        .await
+        .expect("Failed to create service account api token");
+
+    let url = rsclient.get_url().to_string();
+    let client = reqwest::Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .no_proxy()
+        .build()
+        .expect("Failed to create client.");
+
+    let reg_req = serde_json::json!({
+        "redirect_uris": ["https://app.example.com/oauth2/flow"],
+        "client_name": "Registered Application",
+        "grant_types": ["authorization_code"],
+        "scope": "openid"
+    });
+
+    // A registrar token is required, and only registrars may register.
+    let response = client
+        .post(format!("{}/oauth2/register", url))
+        .json(&reg_req)
+        .send()
+        .await
+        .expect("Failed to send client registration.");
+    assert!(response.status() == StatusCode::UNAUTHORIZED);
+
+    let response = client
+        .post(format!("{}/oauth2/register", url))
+        .bearer_auth(admin_uat)
+        .json(&reg_req)
+        .send()
+        .await
+        .expect("Failed to send client registration.");
+    assert!(response.status() == StatusCode::FORBIDDEN);
+
+    // Redirects are limited to the domains of the registrar.
+    let response = client
+        .post(format!("{}/oauth2/register", url))
+        .bearer_auth(registrar_token.clone())
+        .json(&serde_json::json!({
+            "redirect_uris": ["https://app.example.org/oauth2/flow"],
+        }))
+        .send()
+        .await
+        .expect("Failed to send client registration.");
+    assert!(response.status() == StatusCode::BAD_REQUEST);
+
+    let response = client
+        .post(format!("{}/oauth2/register", url))
+        .bearer_auth(registrar_token.clone())
+        .json(&reg_req)
+        .send()
+        .await
+        .expect("Failed to send client registration.");
+    assert_no_cache!(response);
+    assert!(response.status() == StatusCode::CREATED);
+
+    let reg_res: ClientRegistrationResponse = response
+        .json()
+        .await
+        .expect("Failed to access response body");
+    assert!(reg_res.client_secret.is_some());
+    assert!(reg_res.scope.contains("openid"));
+    assert!(!reg_res.scope.contains("email"));
+
+    // The registered client is a normal resource server to administrators.
+    let rs = rsclient
+        .idm_oauth2_rs_get(&reg_res.client_id)
+        .await
+        .expect("Failed to get oauth2 resource server");
+    assert!(rs.is_some());
+
+    let response = client
+        .put(reg_res.registration_client_uri.clone())
+        .bearer_auth(registrar_token.clone())
+        .json(&serde_json::json!({
+            "client_id": reg_res.client_id,
+            "redirect_uris": ["https://app.example.com/oauth2/flow"],
+            "client_name": "Renamed Application",
+        }))
+        .send()
+        .await
+        .expect("Failed to send client registration update.");
+    assert!(response.status() == StatusCode::OK);
+
+    let response = client
+        .get(reg_res.registration_client_uri.clone())
+        .bearer_auth(registrar_token.clone())
+        .send()
+        .await
+        .expect("Failed to send client registration read.");
+    assert!(response.status() == StatusCode::OK);
+    let get_res: ClientRegistrationResponse = response
+        .json()
+        .await
+        .expect("Failed to access response body");
+    assert!(get_res.client_name == "Renamed Application");
+    assert!(get_res.scope.contains("email"));
+    assert!(
+        get_res.redirect_uris
+            == vec![Url::parse("https://app.example.com/oauth2/flow").expect("Invalid url")]
+    );
+
+    let response = client
+        .delete(reg_res.registration_client_uri.clone())
+        .bearer_auth(registrar_token.clone())
+        .send()
+        .await
+        .expect("Failed to send client registration delete.");
+    assert!(response.status() == StatusCode::NO_CONTENT);
+
+    let response = client
+        .get(reg_res.registration_client_uri)
+        .bearer_auth(registrar_token)
+        .send()
+        .await
+        .expect("Failed to send client registration read.");
+    assert!(response.status() == StatusCode::UNAUTHORIZED);
+}
+
+#[kanidmd_testkit::test]
+async fn test_oauth2_consent_prompt_disable_and_revoke(rsclient: KanidmClient) {
+    let res = rsclient
+        .auth_simple_password("admin", ADMIN_TEST_PASSWORD)
+        .await;
+    assert!(res.is_ok());
+
+    rsclient
+        .idm_oauth2_rs_basic_create(
+            TEST_INTEGRATION_RS_ID,
+            TEST_INTEGRATION_RS_DISPLAY,
+            TEST_INTEGRATION_RS_URL,
+        )
+        .await
+        .expect("Failed to create oauth2 config");
+
+    rsclient
+        .idm_oauth2_rs_update_scope_map(
+            TEST_INTEGRATION_RS_ID,
+            TEST_INTEGRATION_RS_GROUP_ALL,
+            vec!["openid"],
+        )
+        .await
+        .expect("Failed to update oauth2 scopes");
+
+    // This is a first party application, so users are not asked for consent.
+    rsclient
+        .idm_oauth2_rs_disable_consent_prompt(TEST_INTEGRATION_RS_ID)
+        .await
+        .expect("Failed to disable the consent prompt");
+
+    let client_secret = rsclient
+        .idm_oauth2_rs_get_basic_secret(TEST_INTEGRATION_RS_ID)
+        .await
+        .ok()
+        .flatten()
+        .expect("Failed to retrieve test_integration basic secret");
+
+    rsclient
+        .idm_group_add_members("idm_admins", &["admin"])
+        .await
+        .unwrap();
+
+    rsclient
+        .idm_person_account_create("oauth_test", "oauth_test")
+        .await
+        .expect("Failed to create account details");
+
+    rsclient
+        .idm_person_account_primary_credential_set_password("oauth_test", ADMIN_TEST_PASSWORD)
+        .await
+        .expect("Failed to configure account password");
+
+    let admin_uat = rsclient
+        .get_token()
+        .await
+        .expect("No user auth token found");
+
+    let res = rsclient
+        .auth_simple_password("oauth_test", ADMIN_TEST_PASSWORD)
+        .await;
+    assert!(res.is_ok());
+    let oauth_test_uat = rsclient
+        .get_token()
+        .await
+        .expect("No user auth token found");
+
+    let url = rsclient.get_url().to_string();
+    let client = reqwest::Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .no_proxy()
+        .build()
+        .expect("Failed to create client.");
+
+    let (pkce_code_challenge, pkce_code_verifier) = PkceCodeChallenge::new_random_sha256();
+
+    // The authorisation is permitted immediately, without a consent request.
+    let response = client
+        .get(format!("{}/oauth2/authorise", url))
+        .bearer_auth(oauth_test_uat)
+        .query(&[
+            ("response_type", "code"),
+            ("client_id", TEST_INTEGRATION_RS_ID),
+            ("state", "YWJjZGVm"),
+            ("code_challenge", pkce_code_challenge.as_str()),
+            ("code_challenge_method", "S256"),
+            ("redirect_uri", "https://demo.example.com/oauth2/flow"),
+            ("scope", "openid"),
+        ])
+        .send()
+        .await
+        .expect("Failed to send request.");
+    assert!(response.status() == StatusCode::FOUND);
+
+    let redir_str = response
+        .headers()
+        .get("Location")
+        .and_then(|hv| hv.to_str().ok().map(str::to_string))
+        .expect("Invalid redirect url");
+    let redir_url = Url::parse(&redir_str).expect("Url parse failure");
+    let code = redir_url
+        .query_pairs()
+        .find_map(|(k, v)| (k == "code").then(|| v.to_string()))
+        .expect("code not found!");
+
+    let form_req: AccessTokenRequest = GrantTypeReq::AuthorizationCode {
+        code,
+        redirect_uri: Url::parse("https://demo.example.com/oauth2/flow").expect("Invalid URL"),
+        code_verifier: Some(pkce_code_verifier.secret().clone()),
+    }
+    .into();
+
+    let response = client
+        .post(format!("{}/oauth2/token", url))
+        .basic_auth(TEST_INTEGRATION_RS_ID, Some(client_secret))
+        .form(&form_req)
+        .send()
+        .await
+        .expect("Failed to send code exchange request.");
+    assert!(response.status() == StatusCode::OK);
+
+    // An administrator can review and revoke the grant of the user.
+    rsclient.set_token(admin_uat).await;
+
+    let grants = rsclient
+        .idm_account_list_oauth2_grant("oauth_test")
+        .await
+        .expect("Failed to list oauth2 grants");
+    assert!(grants.len() == 1);
+    assert!(grants[0].name == TEST_INTEGRATION_RS_ID);
+    assert!(grants[0].session_count == 1);
+
+    rsclient
+        .idm_account_revoke_oauth2_grant("oauth_test", TEST_INTEGRATION_RS_ID)
+        .await
+        .expect("Failed to revoke oauth2 grant");
+
+    let grants = rsclient
+        .idm_account_list_oauth2_grant("oauth_test")
+        .await
+        .expect("Failed to list oauth2 grants");
+    assert!(grants.is_empty());
+}
@@ -1,8 +1,10 @@
 #![deny(warnings)]
 use std::time::SystemTime;
 
+use kanidm_proto::internal::{IdentifyUserRequest, IdentifyUserResponse};
 use kanidm_proto::v1::{
-    ApiToken, CURegState, CredentialDetailType, Entry, Filter, Modify, ModifyList, UserAuthToken,
+    AccessCheckAttrs, AccessProfile, AccessRequestState, ApiToken, CURegState,
+    CredentialDetailType, Entry, Filter, Modify, ModifyList, UserAuthToken,
 };
 use kanidmd_lib::credential::totp::Totp;
 use tracing::debug;
@@ -273,6 +275,246 @@ async fn test_server_rest_schema_read(rsclient: KanidmClient) {
     println!("{:?}", c);
 }
 
+#[kanidmd_testkit::test]
+async fn test_server_rest_schema_lifecycle(rsclient: KanidmClient) {
+    let res = rsclient
+        .auth_simple_password("admin", ADMIN_TEST_PASSWORD)
+        .await;
+    assert!(res.is_ok());
+
+    // Only a restricted set of syntaxes may be used.
+    assert!(rsclient
+        .idm_schema_attributetype_create(
+            "costcentre",
+            "Cost centre",
+            "CREDENTIAL",
+            false,
+            false,
+            None,
+            vec![]
+        )
+        .await
+        .is_err());
+    // Names may only contain letters, digits and underscores.
+    assert!(rsclient
+        .idm_schema_attributetype_create(
+            "cost-centre",
+            "Cost centre",
+            "UTF8STRING",
+            false,
+            false,
+            None,
+            vec![]
+        )
+        .await
+        .is_err());
+
+    rsclient
+        .idm_schema_attributetype_create(
+            "costcentre",
+            "Cost centre",
+            "UTF8STRING_INSENSITIVE",
+            false,
+            false,
+            None,
+            vec![],
+        )
+        .await
+        .unwrap();
+
+    rsclient
+        .idm_schema_attributetype_update(
+            "costcentre",
+            Some("The cost centre of this entry"),
+            Some(true),
+            None,
+            None,
+            Some(vec!["EQUALITY".to_string()]),
+        )
+        .await
+        .unwrap();
+
+    let a = rsclient
+        .idm_schema_attributetype_get("costcentre")
+        .await
+        .unwrap()
+        .expect("attribute not found");
+    assert!(a.attrs.get("multivalue") == Some(&vec!["true".to_string()]));
+    assert!(a.attrs.get("index") == Some(&vec!["EQUALITY".to_string()]));
+
+    // Only unique attributes may be scoped to a class.
+    assert!(rsclient
+        .idm_schema_attributetype_update("costcentre", None, None, None, Some("person"), None)
+        .await
+        .is_err());
+    rsclient
+        .idm_schema_attributetype_update("costcentre", None, None, Some(true), Some("person"), None)
+        .await
+        .unwrap();
+    let a = rsclient
+        .idm_schema_attributetype_get("costcentre")
+        .await
+        .unwrap()
+        .expect("attribute not found");
+    assert!(a.attrs.get("unique_scope") == Some(&vec!["person".to_string()]));
+
+    // An empty scope makes the attribute unique across all entries.
+    rsclient
+        .idm_schema_attributetype_update("costcentre", None, None, None, Some(""), None)
+        .await
+        .unwrap();
+    let a = rsclient
+        .idm_schema_attributetype_get("costcentre")
+        .await
+        .unwrap()
+        .expect("attribute not found");
+    assert!(!a.attrs.contains_key("unique_scope"));
+    assert!(a.attrs.get("unique") == Some(&vec!["true".to_string()]));
+
+    rsclient
+        .idm_schema_classtype_create(
+            "costcentreholder",
+            "An entry with a cost centre",
+            vec!["costcentre".to_string()],
+            vec![],
+        )
+        .await
+        .unwrap();
+
+    // Classes may only reference attributes that exist.
+    assert!(rsclient
+        .idm_schema_classtype_update(
+            "costcentreholder",
+            None,
+            Some(vec!["costcentre".to_string(), "nonexistant".to_string()]),
+            None
+        )
+        .await
+        .is_err());
+
+    rsclient
+        .idm_schema_classtype_deprecate("costcentreholder", true)
+        .await
+        .unwrap();
+    let c = rsclient
+        .idm_schema_classtype_get("costcentreholder")
+        .await
+        .unwrap()
+        .expect("class not found");
+    assert!(c.attrs.get("deprecated") == Some(&vec!["true".to_string()]));
+
+    // A deprecated class can be restored.
+    rsclient
+        .idm_schema_classtype_deprecate("costcentreholder", false)
+        .await
+        .unwrap();
+    let c = rsclient
+        .idm_schema_classtype_get("costcentreholder")
+        .await
+        .unwrap()
+        .expect("class not found");
+    assert!(c.attrs.get("deprecated") == Some(&vec!["false".to_string()]));
+}
+
+#[kanidmd_testkit::test]
+async fn test_server_rest_access_profile_lifecycle(rsclient: KanidmClient) {
+    let res = rsclient
+        .auth_simple_password("admin", ADMIN_TEST_PASSWORD)
+        .await;
+    assert!(res.is_ok());
+
+    rsclient
+        .idm_group_add_members("idm_admins", &["admin"])
+        .await
+        .unwrap();
+
+    rsclient.idm_group_create("test_acp_readers").await.unwrap();
+    rsclient
+        .idm_person_account_create("test_acp_person", "Test Person")
+        .await
+        .unwrap();
+    rsclient
+        .idm_person_account_create("test_acp_other", "Other Person")
+        .await
+        .unwrap();
+    rsclient
+        .idm_group_add_members("test_acp_readers", &["test_acp_person"])
+        .await
+        .unwrap();
+
+    let profile = AccessProfile {
+        name: Some("test_acp_legalname_read".to_string()),
+        receiver_group: Some("test_acp_readers".to_string()),
+        targetscope: Some(Filter::Eq("class".to_string(), "person".to_string())),
+        search_attrs: Some(vec!["legalname".to_string()]),
+        ..Default::default()
+    };
+
+    // Attributes must exist in the schema.
+    let mut invalid = profile.clone();
+    invalid.search_attrs = Some(vec!["nonexistant".to_string()]);
+    assert!(rsclient.idm_access_profile_create(invalid).await.is_err());
+
+    rsclient.idm_access_profile_create(profile).await.unwrap();
+
+    let acps = rsclient.idm_access_profile_list().await.unwrap();
+    assert!(acps.iter().any(|e| e
+        .attrs
+        .get("name")
+        .map(|n| n.contains(&"test_acp_legalname_read".to_string()))
+        .unwrap_or(false)));
+
+    let r = rsclient
+        .idm_access_profile_check("test_acp_person", "admin")
+        .await
+        .unwrap();
+    // Admin is not a person, so it is not in scope.
+    assert!(
+        !matches!(r.search, AccessCheckAttrs::Allow(ref a) if a.contains(&"legalname".to_string()))
+    );
+
+    let r = rsclient
+        .idm_access_profile_check("test_acp_person", "test_acp_other")
+        .await
+        .unwrap();
+    assert!(
+        matches!(r.search, AccessCheckAttrs::Allow(ref a) if a.contains(&"legalname".to_string()))
+    );
+    assert!(!r.delete);
+
+    // Replace the search access with delete.
+    rsclient
+        .idm_access_profile_update(
+            "test_acp_legalname_read",
+            AccessProfile {
+                search_attrs: Some(Vec::new()),
+                delete: Some(true),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+    let r = rsclient
+        .idm_access_profile_check("test_acp_person", "test_acp_other")
+        .await
+        .unwrap();
+    assert!(
+        !matches!(r.search, AccessCheckAttrs::Allow(ref a) if a.contains(&"legalname".to_string()))
+    );
+    assert!(r.delete);
+
+    rsclient
+        .idm_access_profile_delete("test_acp_legalname_read")
+        .await
+        .unwrap();
+    assert!(rsclient
+        .idm_access_profile_get("test_acp_legalname_read")
+        .await
+        .unwrap()
+        .is_none());
+}
+
 // Test resetting a radius cred, and then checking/viewing it.
 #[kanidmd_testkit::test]
 async fn test_server_radius_credential_lifecycle(rsclient: KanidmClient) {
@@ -496,6 +738,25 @@ async fn test_server_rest_domain_lifecycle(rsclient: KanidmClient) {
     );
 }
 
+#[kanidmd_testkit::test]
+async fn test_server_rest_password_history_length(rsclient: KanidmClient) {
+    let res = rsclient
+        .auth_simple_password("admin", ADMIN_TEST_PASSWORD)
+        .await;
+    assert!(res.is_ok());
+
+    // Not set by default, so the builtin length applies.
+    let length = rsclient.system_password_history_length_get().await.unwrap();
+    assert!(length.is_none());
+
+    rsclient
+        .system_password_history_length_set(3)
+        .await
+        .unwrap();
+    let length = rsclient.system_password_history_length_get().await.unwrap();
+    assert!(length == Some(3));
+}
+
 #[kanidmd_testkit::test]
 async fn test_server_rest_posix_lifecycle(rsclient: KanidmClient) {
     let res = rsclient
@@ -755,6 +1016,97 @@ async fn test_server_rest_recycle_lifecycle(rsclient: KanidmClient) {
     assert!(acc.is_some());
 }
 
+#[kanidmd_testkit::test]
+async fn test_server_rest_recycle_members_and_purge(rsclient: KanidmClient) {
+    let res = rsclient
+        .auth_simple_password("admin", ADMIN_TEST_PASSWORD)
+        .await;
+    assert!(res.is_ok());
+
+    rsclient
+        .idm_group_add_members("idm_admins", &["admin"])
+        .await
+        .unwrap();
+
+    rsclient
+        .idm_person_account_create("recycle_member", "Recycle Member")
+        .await
+        .unwrap();
+    rsclient.idm_group_create("recycle_group").await.unwrap();
+    rsclient
+        .idm_group_add_members("recycle_group", &["recycle_member"])
+        .await
+        .unwrap();
+
+    // Deleting removes the membership, reviving restores it by default.
+    rsclient
+        .idm_person_account_delete("recycle_member")
+        .await
+        .unwrap();
+    let members = rsclient
+        .idm_group_get_members("recycle_group")
+        .await
+        .unwrap();
+    assert!(members.is_none());
+
+    rsclient.recycle_bin_revive("recycle_member").await.unwrap();
+    let members = rsclient
+        .idm_group_get_members("recycle_group")
+        .await
+        .unwrap()
+        .expect("No members");
+    assert!(members.iter().any(|m| m.starts_with("recycle_member@")));
+
+    // Unless the caller opts out of restoring them.
+    rsclient
+        .idm_person_account_delete("recycle_member")
+        .await
+        .unwrap();
+    rsclient
+        .recycle_bin_revive_without_members("recycle_member")
+        .await
+        .unwrap();
+    let members = rsclient
+        .idm_group_get_members("recycle_group")
+        .await
+        .unwrap();
+    assert!(members.is_none());
+
+    // Once purged, the entry is gone from the recycle bin for good.
+    rsclient
+        .idm_person_account_delete("recycle_member")
+        .await
+        .unwrap();
+    rsclient.recycle_bin_purge("recycle_member").await.unwrap();
+    let r_user = rsclient.recycle_bin_get("recycle_member").await.unwrap();
+    assert!(r_user.is_none());
+    assert!(rsclient.recycle_bin_revive("recycle_member").await.is_err());
+
+    // Per class retention can be managed.
+    rsclient
+        .system_recycle_bin_retention_set("group", 86400)
+        .await
+        .unwrap();
+    rsclient
+        .system_recycle_bin_retention_set("person", 3600)
+        .await
+        .unwrap();
+    rsclient
+        .system_recycle_bin_retention_set("group", 172800)
+        .await
+        .unwrap();
+    let mut retention = rsclient.system_recycle_bin_retention_get().await.unwrap();
+    retention.sort();
+    assert_eq!(retention, vec!["group:172800", "person:3600"]);
+
+    rsclient
+        .system_recycle_bin_retention_remove("person")
+        .await
+        .unwrap();
+    let retention = rsclient.system_recycle_bin_retention_get().await.unwrap();
+    assert_eq!(retention, vec!["group:172800"]);
+}
+
 #[kanidmd_testkit::test]
 async fn test_server_rest_account_import_password(rsclient: KanidmClient) {
     let res = rsclient
@@ -816,6 +1168,65 @@ async fn test_server_rest_account_import_password(rsclient: KanidmClient) {
     }
 }
 
+#[kanidmd_testkit::test]
+async fn test_server_rest_oauth2_token_exchange_source(rsclient: KanidmClient) {
+    let res = rsclient
+        .auth_simple_password("admin", ADMIN_TEST_PASSWORD)
+        .await;
+    assert!(res.is_ok());
+
+    rsclient
+        .idm_oauth2_rs_basic_create("test_gateway", "Test Gateway", "https://gw.example.com")
+        .await
+        .expect("Failed to create oauth2 config");
+    rsclient
+        .idm_oauth2_rs_basic_create(
+            "test_downstream",
+            "Test Downstream",
+            "https://downstream.example.com",
+        )
+        .await
+        .expect("Failed to create oauth2 config");
+
+    // Unknown sources are rejected.
+    assert!(rsclient
+        .idm_oauth2_rs_add_token_exchange_source("test_downstream", "test_nonexistant")
+        .await
+        .is_err());
+
+    rsclient
+        .idm_oauth2_rs_add_token_exchange_source("test_downstream", "test_gateway")
+        .await
+        .expect("Failed to add token exchange source");
+
+    let downstream = rsclient
+        .idm_oauth2_rs_get("test_downstream")
+        .await
+        .ok()
+        .flatten()
+        .expect("Failed to retrieve test_downstream config");
+    assert!(downstream
+        .attrs
+        .get("oauth2_rs_token_exchange_from")
+        .map(|sources| sources.len() == 1)
+        .unwrap_or(false));
+
+    rsclient
+        .idm_oauth2_rs_delete_token_exchange_source("test_downstream", "test_gateway")
+        .await
+        .expect("Failed to delete token exchange source");
+
+    let downstream = rsclient
+        .idm_oauth2_rs_get("test_downstream")
+        .await
+        .ok()
+        .flatten()
+        .expect("Failed to retrieve test_downstream config");
+    assert!(!downstream
+        .attrs
+        .contains_key("oauth2_rs_token_exchange_from"));
+}
+
 #[kanidmd_testkit::test]
 async fn test_server_rest_oauth2_basic_lifecycle(rsclient: KanidmClient) {
     let res = rsclient
@@ -1392,40 +1803,124 @@ async fn test_server_user_auth_token_lifecycle(rsclient: KanidmClient) {
 }
 
 #[kanidmd_testkit::test]
-async fn test_server_user_auth_reauthentication(rsclient: KanidmClient) {
-    let mut wa = setup_demo_account_passkey(&rsclient).await;
-
+async fn test_server_user_auth_token_self_management(rsclient: KanidmClient) {
     let res = rsclient
-        .auth_passkey_begin("demo_account")
-        .await
-        .expect("Failed to start passkey auth");
-
-    let pkc = wa
-        .do_authentication(rsclient.get_origin().clone(), res)
-        .map(Box::new)
-        .expect("Failed to authentication with soft passkey");
-
-    let res = rsclient.auth_passkey_complete(pkc).await;
+        .auth_simple_password("admin", ADMIN_TEST_PASSWORD)
+        .await;
     assert!(res.is_ok());
 
-    // Assert we are still readonly
-    let token = rsclient
-        .get_token()
+    // Not recommended in production!
+    rsclient
+        .idm_group_add_members("idm_admins", &["admin"])
         .await
-        .expect("Must have a bearer token");
-    let jwtu = JwsUnverified::from_str(&token).expect("Failed to parse jwsu");
+        .unwrap();
 
-    let uat: UserAuthToken = jwtu
-        .validate_embeded()
-        .map(|jws| jws.into_inner())
-        .expect("Unable to open up token.");
+    rsclient
+        .idm_person_account_create("demo_account", "Deeeeemo")
+        .await
+        .unwrap();
 
-    let now = time::OffsetDateTime::now_utc();
-    assert!(!uat.purpose_readwrite_active(now));
+    {
+        let intent_token = rsclient
+            .idm_person_account_credential_update_intent("demo_account", None)
+            .await
+            .unwrap();
 
-    // The auth is done, now we have to setup to re-auth for our session.
-    // Should we bother looking at the internals of the token here to assert
-    // it all worked? I don't think we have to because the server tests have
+        let _ = rsclient.logout().await;
+        let (session_token, _status) = rsclient
+            .idm_account_credential_update_exchange(intent_token)
+            .await
+            .unwrap();
+
+        let _status = rsclient
+            .idm_account_credential_update_set_password(&session_token, "eicieY7ahchaoCh0eeTa")
+            .await
+            .unwrap();
+
+        rsclient
+            .idm_account_credential_update_commit(&session_token)
+            .await
+            .unwrap();
+    }
+
+    let _ = rsclient.logout().await;
+    let res = rsclient
+        .auth_simple_password("demo_account", "eicieY7ahchaoCh0eeTa")
+        .await;
+    assert!(res.is_ok());
+
+    // The user can see their own session, where it came from and how it was established.
+    let sessions = rsclient
+        .idm_account_list_user_auth_token("demo_account")
+        .await
+        .expect("Failed to list user auth tokens");
+
+    assert_eq!(sessions.len(), 1);
+    assert!(sessions[0].label.starts_with("Auth Session from "));
+    assert_eq!(sessions[0].credential.as_deref(), Some("Password"));
+
+    // No oauth2 applications have been granted access yet.
+    let grants = rsclient
+        .idm_account_list_oauth2_grant("demo_account")
+        .await
+        .expect("Failed to list oauth2 grants");
+    assert!(grants.is_empty());
+
+    // Revoking a grant for an application that doesn't exist fails.
+    assert!(rsclient
+        .idm_account_revoke_oauth2_grant("demo_account", "test_integration")
+        .await
+        .is_err());
+
+    // Log out everywhere, which ends the session we are using too. It remains usable
+    // here only because it was issued within the session grace window.
+    rsclient
+        .idm_account_destroy_user_auth_token_all("demo_account")
+        .await
+        .expect("Failed to destroy user auth tokens");
+
+    let sessions = rsclient
+        .idm_account_list_user_auth_token("demo_account")
+        .await
+        .expect("Failed to list user auth tokens");
+    assert!(sessions.is_empty());
+}
+
+#[kanidmd_testkit::test]
+async fn test_server_user_auth_reauthentication(rsclient: KanidmClient) {
+    let mut wa = setup_demo_account_passkey(&rsclient).await;
+
+    let res = rsclient
+        .auth_passkey_begin("demo_account")
+        .await
+        .expect("Failed to start passkey auth");
+
+    let pkc = wa
+        .do_authentication(rsclient.get_origin().clone(), res)
+        .map(Box::new)
+        .expect("Failed to authentication with soft passkey");
+
+    let res = rsclient.auth_passkey_complete(pkc).await;
+    assert!(res.is_ok());
+
+    // Assert we are still readonly
+    let token = rsclient
+        .get_token()
+        .await
+        .expect("Must have a bearer token");
+    let jwtu = JwsUnverified::from_str(&token).expect("Failed to parse jwsu");
+
+    let uat: UserAuthToken = jwtu
+        .validate_embeded()
+        .map(|jws| jws.into_inner())
+        .expect("Unable to open up token.");
+
+    let now = time::OffsetDateTime::now_utc();
+    assert!(!uat.purpose_readwrite_active(now));
+
+    // The auth is done, now we have to setup to re-auth for our session.
+    // Should we bother looking at the internals of the token here to assert
+    // it all worked? I don't think we have to because the server tests have
     // already checked all those bits.
 
     let res = rsclient
@@ -1459,3 +1954,970 @@ async fn test_server_user_auth_reauthentication(rsclient: KanidmClient) {
     eprintln!("{:?} {:?}", now, uat.purpose);
     assert!(uat.purpose_readwrite_active(now));
 }
+
+#[kanidmd_testkit::test]
+async fn test_server_group_entry_managed_by(rsclient: KanidmClient) {
+    let res = rsclient
+        .auth_simple_password("admin", ADMIN_TEST_PASSWORD)
+        .await;
+    assert!(res.is_ok());
+
+    rsclient
+        .idm_group_add_members("idm_admins", &["admin"])
+        .await
+        .unwrap();
+
+    rsclient
+        .idm_person_account_create("test_team_lead", "Team Lead")
+        .await
+        .unwrap();
+    rsclient
+        .idm_person_account_primary_credential_set_password(
+            "test_team_lead",
+            "eicieY7ahchaoCh0eeTa",
+        )
+        .await
+        .unwrap();
+    rsclient
+        .idm_person_account_create("test_team_member", "Team Member")
+        .await
+        .unwrap();
+
+    rsclient.idm_group_create("test_team").await.unwrap();
+    rsclient.idm_group_create("test_other_team").await.unwrap();
+    rsclient
+        .idm_group_set_entry_managed_by("test_team", "test_team_lead")
+        .await
+        .unwrap();
+
+    // The team lead can now manage the members of their group, but no other.
+    rsclient.logout().await.unwrap();
+    let res = rsclient
+        .auth_simple_password("test_team_lead", "eicieY7ahchaoCh0eeTa")
+        .await;
+    assert!(res.is_ok());
+    // Changing group membership requires a read-write session.
+    rsclient
+        .reauth_simple_password("eicieY7ahchaoCh0eeTa")
+        .await
+        .unwrap();
+
+    rsclient
+        .idm_group_add_members("test_team", &["test_team_member"])
+        .await
+        .unwrap();
+    let members = rsclient
+        .idm_group_get_members("test_team")
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(members.iter().any(|m| m.starts_with("test_team_member")));
+
+    assert!(rsclient
+        .idm_group_add_members("test_other_team", &["test_team_member"])
+        .await
+        .is_err());
+
+    // The team lead can not hand the group to someone else.
+    assert!(rsclient
+        .idm_group_set_entry_managed_by("test_team", "test_team_member")
+        .await
+        .is_err());
+
+    rsclient
+        .idm_group_remove_members("test_team", &["test_team_member"])
+        .await
+        .unwrap();
+}
+
+#[kanidmd_testkit::test]
+async fn test_server_group_member_validity(rsclient: KanidmClient) {
+    let res = rsclient
+        .auth_simple_password("admin", ADMIN_TEST_PASSWORD)
+        .await;
+    assert!(res.is_ok());
+
+    rsclient
+        .idm_group_add_members("idm_admins", &["admin"])
+        .await
+        .unwrap();
+
+    rsclient.idm_group_create("test_rota").await.unwrap();
+    rsclient
+        .idm_person_account_create("test_oncall", "On Call")
+        .await
+        .unwrap();
+    rsclient
+        .idm_person_account_create("test_contractor", "Contractor")
+        .await
+        .unwrap();
+
+    // Invalid periods are rejected.
+    assert!(rsclient
+        .idm_group_set_member_validity("test_rota", "test_oncall", None, None)
+        .await
+        .is_err());
+    assert!(rsclient
+        .idm_group_set_member_validity(
+            "test_rota",
+            "test_oncall",
+            Some("2099-01-02T00:00:00Z"),
+            Some("2099-01-01T00:00:00Z")
+        )
+        .await
+        .is_err());
+
+    // The on call member only joins the rota in the future, the contractor is a member
+    // until their contract ends.
+    rsclient
+        .idm_group_set_member_validity(
+            "test_rota",
+            "test_oncall",
+            Some("2099-01-01T00:00:00Z"),
+            None,
+        )
+        .await
+        .unwrap();
+    rsclient
+        .idm_group_set_member_validity(
+            "test_rota",
+            "test_contractor",
+            None,
+            Some("2099-01-01T00:00:00Z"),
+        )
+        .await
+        .unwrap();
+    rsclient
+        .idm_group_add_members("test_rota", &["test_oncall", "test_contractor"])
+        .await
+        .unwrap();
+
+    let validity = rsclient
+        .idm_group_get_member_validity("test_rota")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(validity.len(), 2);
+    assert!(validity
+        .iter()
+        .any(|v| v.starts_with("test_oncall@") && v.ends_with(",2099-01-01T00:00:00Z,")));
+
+    let oncall = rsclient
+        .idm_person_account_get("test_oncall")
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(!oncall
+        .attrs
+        .get("memberof")
+        .map(|mo| mo.iter().any(|g| g.starts_with("test_rota@")))
+        .unwrap_or(false));
+
+    let contractor = rsclient
+        .idm_person_account_get("test_contractor")
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(contractor
+        .attrs
+        .get("memberof")
+        .map(|mo| mo.iter().any(|g| g.starts_with("test_rota@")))
+        .unwrap_or(false));
+
+    // Removing the member removes the validity.
+    rsclient
+        .idm_group_remove_members("test_rota", &["test_oncall"])
+        .await
+        .unwrap();
+    let validity = rsclient
+        .idm_group_get_member_validity("test_rota")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(validity.len(), 1);
+}
+
+#[kanidmd_testkit::test]
+async fn test_server_access_request_workflow(rsclient: KanidmClient) {
+    let res = rsclient
+        .auth_simple_password("admin", ADMIN_TEST_PASSWORD)
+        .await;
+    assert!(res.is_ok());
+
+    rsclient
+        .idm_group_add_members("idm_admins", &["admin"])
+        .await
+        .unwrap();
+
+    for (name, password) in [
+        ("test_requester", "ahm2Ood3ooph9aiB"),
+        ("test_approver", "Ohng5quee9uthoo8"),
+    ] {
+        rsclient
+            .idm_person_account_create(name, name)
+            .await
+            .unwrap();
+        rsclient
+            .idm_person_account_primary_credential_set_password(name, password)
+            .await
+            .unwrap();
+    }
+
+    rsclient.idm_group_create("test_approvers").await.unwrap();
+    rsclient
+        .idm_group_add_members("test_approvers", &["test_approver"])
+        .await
+        .unwrap();
+    rsclient
+        .idm_group_set_access_request_approvers("idm_admins", &["test_approvers"])
+        .await
+        .unwrap();
+    rsclient
+        .idm_group_set_access_request_max_duration("idm_admins", 1800)
+        .await
+        .unwrap();
+    rsclient.logout().await.unwrap();
+
+    // Any person may request access, even without privileges.
+    let res = rsclient
+        .auth_simple_password("test_requester", "ahm2Ood3ooph9aiB")
+        .await;
+    assert!(res.is_ok());
+    assert!(rsclient
+        .idm_access_request_create("idm_admins", Some("incident 42"), Some(3600))
+        .await
+        .is_err());
+    let request = rsclient
+        .idm_access_request_create("idm_admins", Some("incident 42"), None)
+        .await
+        .unwrap()
+        .to_string();
+    let requests = rsclient.idm_access_request_list().await.unwrap();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].state, AccessRequestState::Pending);
+    assert_eq!(requests[0].duration, 1800);
+    rsclient.logout().await.unwrap();
+
+    // The approver must reauthenticate to approve.
+    let res = rsclient
+        .auth_simple_password("test_approver", "Ohng5quee9uthoo8")
+        .await;
+    assert!(res.is_ok());
+    assert!(rsclient.idm_access_request_approve(&request).await.is_err());
+    rsclient
+        .reauth_simple_password("Ohng5quee9uthoo8")
+        .await
+        .unwrap();
+    rsclient.idm_access_request_approve(&request).await.unwrap();
+
+    let r = rsclient.idm_access_request_get(&request).await.unwrap();
+    assert_eq!(r.state, AccessRequestState::Approved);
+    assert!(r.expiry.is_some());
+    rsclient.logout().await.unwrap();
+
+    let res = rsclient
+        .auth_simple_password("admin", ADMIN_TEST_PASSWORD)
+        .await;
+    assert!(res.is_ok());
+    let requester = rsclient
+        .idm_person_account_get("test_requester")
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(requester
+        .attrs
+        .get("memberof")
+        .map(|mo| mo.iter().any(|g| g.starts_with("idm_admins@")))
+        .unwrap_or(false));
+}
+
+#[kanidmd_testkit::test]
+async fn test_server_group_dynamic(rsclient: KanidmClient) {
+    let res = rsclient
+        .auth_simple_password("admin", ADMIN_TEST_PASSWORD)
+        .await;
+    assert!(res.is_ok());
+
+    rsclient
+        .idm_group_add_members("idm_admins", &["admin"])
+        .await
+        .unwrap();
+
+    rsclient
+        .idm_person_account_create("test_contractor", "Contractor")
+        .await
+        .unwrap();
+    rsclient
+        .idm_person_account_set_attr("test_contractor", "mail", &["one@contractor.example"])
+        .await
+        .unwrap();
+    rsclient
+        .idm_person_account_create("test_employee", "Employee")
+        .await
+        .unwrap();
+    rsclient
+        .idm_person_account_set_attr("test_employee", "mail", &["two@example.com"])
+        .await
+        .unwrap();
+
+    let filter = Filter::And(vec![
+        Filter::Eq("class".to_string(), "person".to_string()),
+        Filter::Sub("mail".to_string(), "@contractor.example".to_string()),
+    ]);
+
+    // Check what the membership will be before creating the group.
+    let preview = rsclient.idm_group_dynamic_preview(&filter).await.unwrap();
+    assert!(preview.iter().any(|m| m.starts_with("test_contractor@")));
+    assert!(!preview.iter().any(|m| m.starts_with("test_employee@")));
+
+    // Only indexed attributes may be used.
+    let unindexed = Filter::Eq("description".to_string(), "contractor".to_string());
+    assert!(rsclient
+        .idm_group_dynamic_preview(&unindexed)
+        .await
+        .is_err());
+    assert!(rsclient
+        .idm_group_create_dynamic("test_invalid_dyngroup", &unindexed)
+        .await
+        .is_err());
+
+    rsclient
+        .idm_group_create_dynamic("test_contractors", &filter)
+        .await
+        .unwrap();
+
+    let contractor = rsclient
+        .idm_person_account_get("test_contractor")
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(contractor
+        .attrs
+        .get("memberof")
+        .unwrap()
+        .iter()
+        .any(|g| g.starts_with("test_contractors@")));
+
+    // Changing the mail address moves the person out of the group.
+    rsclient
+        .idm_person_account_set_attr("test_contractor", "mail", &["one@example.com"])
+        .await
+        .unwrap();
+    let contractor = rsclient
+        .idm_person_account_get("test_contractor")
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(!contractor
+        .attrs
+        .get("memberof")
+        .map(|mo| mo.iter().any(|g| g.starts_with("test_contractors@")))
+        .unwrap_or(false));
+
+    // Dynamic groups can be removed like any other group, but not the builtin ones.
+    rsclient.idm_group_delete("test_contractors").await.unwrap();
+    assert!(rsclient.idm_group_delete("idm_all_persons").await.is_err());
+}
+
+/// Accept a single http request on the listener, and return its headers and body.
+async fn webhook_receive(listener: &tokio::net::TcpListener) -> (Vec<String>, Vec<u8>) {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let (mut stream, _) = listener.accept().await.unwrap();
+    let mut buf = Vec::new();
+    let mut chunk = [0; 4096];
+    let (headers, body_start) = loop {
+        let n = stream.read(&mut chunk).await.unwrap();
+        assert!(n > 0);
+        buf.extend_from_slice(&chunk[..n]);
+        if let Some(pos) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+            let headers: Vec<String> = String::from_utf8_lossy(&buf[..pos])
+                .lines()
+                .map(str::to_string)
+                .collect();
+            break (headers, pos + 4);
+        }
+    };
+
+    let content_length: usize = headers
+        .iter()
+        .find_map(|h| {
+            h.to_lowercase()
+                .strip_prefix("content-length:")
+                .map(|v| v.trim().parse().unwrap())
+        })
+        .unwrap();
+    while buf.len() < body_start + content_length {
+        let n = stream.read(&mut chunk).await.unwrap();
+        assert!(n > 0);
+        buf.extend_from_slice(&chunk[..n]);
+    }
+
+    stream
+        .write_all(b"HTTP/1.1 204 No Content\r\nConnection: close\r\n\r\n")
+        .await
+        .unwrap();
+
+    (
+        headers,
+        buf[body_start..body_start + content_length].to_vec(),
+    )
+}
+
+#[kanidmd_testkit::test]
+async fn test_server_rest_webhook(rsclient: KanidmClient) {
+    use kanidm_proto::v1::{WebhookEvent, WebhookEventType};
+    use openssl::hash::MessageDigest;
+    use openssl::pkey::PKey;
+    use openssl::sign::Signer;
+    use std::time::Duration;
+
+    let res = rsclient
+        .auth_simple_password("admin", ADMIN_TEST_PASSWORD)
+        .await;
+    assert!(res.is_ok());
+
+    rsclient
+        .idm_group_add_members("idm_admins", &["admin"])
+        .await
+        .unwrap();
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let hook_url = format!("http://{}/hook", listener.local_addr().unwrap());
+
+    // Unknown events are rejected.
+    assert!(rsclient
+        .perform_post_request::<_, ()>(
+            "/v1/webhook",
+            serde_json::from_str::<Entry>(&format!(
+                r#"{{"attrs": {{"name": ["bad_hook"], "webhook_url": ["{}"], "webhook_event": ["account_renamed"]}}}}"#,
+                hook_url
+            ))
+            .unwrap()
+        )
+        .await
+        .is_err());
+
+    rsclient
+        .idm_webhook_create(
+            "hr_hook",
+            &hook_url,
+            &[WebhookEventType::AccountCreated],
+            Some(r#"{"eq": ["class", "person"]}"#),
+        )
+        .await
+        .unwrap();
+    let secret = rsclient
+        .idm_webhook_get_secret("hr_hook")
+        .await
+        .unwrap()
+        .unwrap();
+
+    // Service accounts don't match the filter, so only the person is sent.
+    rsclient
+        .idm_service_account_create("webhook_service", "Webhook Service")
+        .await
+        .unwrap();
+    rsclient
+        .idm_person_account_create("webhook_person", "Webhook Person")
+        .await
+        .unwrap();
+
+    let (headers, body) = tokio::time::timeout(Duration::from_secs(10), webhook_receive(&listener))
+        .await
+        .unwrap();
+
+    let event: WebhookEvent = serde_json::from_slice(&body).unwrap();
+    assert_eq!(event.event, WebhookEventType::AccountCreated);
+    assert!(event.spn.as_deref().unwrap().starts_with("webhook_person@"));
+
+    let key = PKey::hmac(secret.as_bytes()).unwrap();
+    let mut signer = Signer::new(MessageDigest::sha256(), &key).unwrap();
+    let expected = format!(
+        "x-kanidm-signature: sha256={}",
+        hex::encode(signer.sign_oneshot_to_vec(&body).unwrap())
+    );
+    assert!(headers.iter().any(|h| h.to_lowercase() == expected));
+
+    // A webhook that can't be reached keeps the event once it gives up.
+    let closed = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let closed_url = format!("http://{}/hook", closed.local_addr().unwrap());
+    drop(closed);
+
+    rsclient
+        .idm_webhook_create(
+            "chat_hook",
+            &closed_url,
+            &[WebhookEventType::GroupMembership],
+            None,
+        )
+        .await
+        .unwrap();
+    rsclient.idm_group_create("webhook_group").await.unwrap();
+    rsclient
+        .idm_group_add_members("webhook_group", &["webhook_person"])
+        .await
+        .unwrap();
+
+    let mut dead_letters = Vec::new();
+    for _ in 0..30 {
+        dead_letters = rsclient
+            .idm_webhook_dead_letters("chat_hook")
+            .await
+            .unwrap();
+        if !dead_letters.is_empty() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_secs(1)).await;
+    }
+    assert_eq!(dead_letters.len(), 1);
+    assert_eq!(
+        dead_letters[0].event.event,
+        WebhookEventType::GroupMembership
+    );
+    assert_eq!(dead_letters[0].attempts, 4);
+    assert_eq!(dead_letters[0].event.members_added.len(), 1);
+
+    // Once the subscriber is fixed, the event can be delivered again.
+    rsclient
+        .idm_webhook_set_url("chat_hook", &hook_url)
+        .await
+        .unwrap();
+    assert_eq!(
+        rsclient
+            .idm_webhook_dead_letters_retry("chat_hook")
+            .await
+            .unwrap(),
+        1
+    );
+
+    let (_, body) = tokio::time::timeout(Duration::from_secs(10), webhook_receive(&listener))
+        .await
+        .unwrap();
+    let event: WebhookEvent = serde_json::from_slice(&body).unwrap();
+    assert_eq!(event.id, dead_letters[0].event.id);
+    assert!(rsclient
+        .idm_webhook_dead_letters("chat_hook")
+        .await
+        .unwrap()
+        .is_empty());
+
+    rsclient.idm_webhook_delete("chat_hook").await.unwrap();
+    rsclient.idm_webhook_delete("hr_hook").await.unwrap();
+}
+
+#[kanidmd_testkit::test]
+async fn test_server_rest_scim_v2(rsclient: KanidmClient) {
+    use kanidm_proto::scim_v2::{ScimV2Error, ScimV2Group, ScimV2ListResponse, ScimV2User};
+    use reqwest::StatusCode;
+
+    let res = rsclient
+        .auth_simple_password("admin", ADMIN_TEST_PASSWORD)
+        .await;
+    assert!(res.is_ok());
+
+    rsclient
+        .idm_group_add_members("idm_admins", &["admin"])
+        .await
+        .unwrap();
+
+    let token = rsclient.get_token().await.unwrap();
+    let base = format!("{}/scim/v2", rsclient.get_url());
+    let client = reqwest::ClientBuilder::new()
+        .danger_accept_invalid_certs(true)
+        .build()
+        .unwrap();
+
+    // Discovery doesn't need authentication.
+    let response = client
+        .get(format!("{}/ServiceProviderConfig", base))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers()[reqwest::header::CONTENT_TYPE],
+        "application/scim+json"
+    );
+    let config: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(config["patch"]["supported"], true);
+
+    let response = client.get(format!("{}/Users", base)).send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    let error: ScimV2Error = response.json().await.unwrap();
+    assert_eq!(error.status, "401");
+
+    // Create a user as a provisioning client would.
+    let user = serde_json::json!({
+        "schemas": ["urn:ietf:params:scim:schemas:core:2.0:User"],
+        "userName": "scim_user",
+        "displayName": "Scim User",
+        "emails": [{ "value": "scim_user@example.com", "primary": true, "type": "work" }],
+        "active": true
+    });
+    let response = client
+        .post(format!("{}/Users", base))
+        .bearer_auth(&token)
+        .json(&user)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let location = response.headers()[reqwest::header::LOCATION]
+        .to_str()
+        .unwrap()
+        .to_string();
+    let created: ScimV2User = response.json().await.unwrap();
+    let user_id = created.id.unwrap();
+    assert_eq!(location, format!("{}/Users/{}", base, user_id));
+    assert_eq!(
+        created.meta.and_then(|m| m.location).as_deref(),
+        Some(location.as_str())
+    );
+
+    // The same user name can't be provisioned twice.
+    let response = client
+        .post(format!("{}/Users", base))
+        .bearer_auth(&token)
+        .json(&user)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+    let error: ScimV2Error = response.json().await.unwrap();
+    assert_eq!(error.scim_type.as_deref(), Some("uniqueness"));
+
+    let response = client
+        .get(format!("{}/Users", base))
+        .query(&[("filter", r#"userName eq "scim_user""#)])
+        .bearer_auth(&token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let list: ScimV2ListResponse<ScimV2User> = response.json().await.unwrap();
+    assert_eq!(list.total_results, 1);
+    assert_eq!(list.resources[0].id, Some(user_id));
+
+    let response = client
+        .get(format!("{}/Users", base))
+        .query(&[("filter", r#"userName sw "scim""#)])
+        .bearer_auth(&token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let error: ScimV2Error = response.json().await.unwrap();
+    assert_eq!(error.scim_type.as_deref(), Some("invalidFilter"));
+
+    let response = client
+        .post(format!("{}/Groups", base))
+        .bearer_auth(&token)
+        .json(&serde_json::json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:Group"],
+            "displayName": "scim_group",
+            "members": [{ "value": user_id }]
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let group: ScimV2Group = response.json().await.unwrap();
+    let group_id = group.id.unwrap();
+    assert_eq!(group.members.len(), 1);
+
+    // Deprovisioning usually starts by deactivating the user and removing memberships.
+    let response = client
+        .patch(format!("{}/Users/{}", base, user_id))
+        .bearer_auth(&token)
+        .json(&serde_json::json!({
+            "schemas": ["urn:ietf:params:scim:api:messages:2.0:PatchOp"],
+            "Operations": [{ "op": "replace", "path": "active", "value": false }]
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let patched: ScimV2User = response.json().await.unwrap();
+    assert_eq!(patched.active, Some(false));
+
+    let response = client
+        .patch(format!("{}/Groups/{}", base, group_id))
+        .bearer_auth(&token)
+        .json(&serde_json::json!({
+            "schemas": ["urn:ietf:params:scim:api:messages:2.0:PatchOp"],
+            "Operations": [{ "op": "remove", "path": format!("members[value eq \"{}\"]", user_id) }]
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let group: ScimV2Group = response.json().await.unwrap();
+    assert!(group.members.is_empty());
+
+    let response = client
+        .delete(format!("{}/Users/{}", base, user_id))
+        .bearer_auth(&token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+    let response = client
+        .get(format!("{}/Users/{}", base, user_id))
+        .bearer_auth(&token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[kanidmd_testkit::test]
+async fn test_server_helpdesk_credential_reset(rsclient: KanidmClient) {
+    let res = rsclient
+        .auth_simple_password("admin", ADMIN_TEST_PASSWORD)
+        .await;
+    assert!(res.is_ok());
+
+    rsclient
+        .idm_group_add_members("idm_admins", &["admin"])
+        .await
+        .unwrap();
+
+    for (name, password) in [
+        ("test_helpdesk", "eiThae4eeQu1ahx0"),
+        ("test_user", "Aech5ohjieh5eePh"),
+    ] {
+        rsclient
+            .idm_person_account_create(name, name)
+            .await
+            .unwrap();
+        rsclient
+            .idm_person_account_primary_credential_set_password(name, password)
+            .await
+            .unwrap();
+    }
+
+    rsclient
+        .idm_group_add_members("idm_helpdesk", &["test_helpdesk"])
+        .await
+        .unwrap();
+    let _ = rsclient.logout().await;
+
+    // The helpdesk reads their code to the user. The first verification creates the
+    // verification keys, so the helpdesk must reauthenticate.
+    let res = rsclient
+        .auth_simple_password("test_helpdesk", "eiThae4eeQu1ahx0")
+        .await;
+    assert!(res.is_ok());
+    assert!(rsclient
+        .idm_person_identify_user("test_user", IdentifyUserRequest::Start)
+        .await
+        .is_err());
+    rsclient
+        .reauth_simple_password("eiThae4eeQu1ahx0")
+        .await
+        .unwrap();
+    let IdentifyUserResponse::ProvideCode {
+        totp: helpdesk_totp,
+        ..
+    } = rsclient
+        .idm_person_identify_user("test_user", IdentifyUserRequest::Start)
+        .await
+        .unwrap()
+    else {
+        panic!("expected a code to provide");
+    };
+    let _ = rsclient.logout().await;
+
+    // The user confirms the helpdesk, and reads their own code back.
+    let res = rsclient
+        .auth_simple_password("test_user", "Aech5ohjieh5eePh")
+        .await;
+    assert!(res.is_ok());
+    let res = rsclient
+        .idm_person_identify_user(
+            "test_helpdesk",
+            IdentifyUserRequest::SubmitCode {
+                other_totp: (helpdesk_totp + 1) % 1_000_000,
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(res, IdentifyUserResponse::CodeFailure);
+    let IdentifyUserResponse::Success {
+        totp: user_totp, ..
+    } = rsclient
+        .idm_person_identify_user(
+            "test_helpdesk",
+            IdentifyUserRequest::SubmitCode {
+                other_totp: helpdesk_totp,
+            },
+        )
+        .await
+        .unwrap()
+    else {
+        panic!("expected the helpdesk code to be accepted");
+    };
+    // The user can't reset the helpdesk.
+    assert!(rsclient
+        .idm_person_credential_helpdesk_reset("test_helpdesk", helpdesk_totp, None)
+        .await
+        .is_err());
+    let _ = rsclient.logout().await;
+
+    // The helpdesk must reauthenticate before the reset is released.
+    let res = rsclient
+        .auth_simple_password("test_helpdesk", "eiThae4eeQu1ahx0")
+        .await;
+    assert!(res.is_ok());
+    assert!(rsclient
+        .idm_person_credential_helpdesk_reset("test_user", user_totp, None)
+        .await
+        .is_err());
+    rsclient
+        .reauth_simple_password("eiThae4eeQu1ahx0")
+        .await
+        .unwrap();
+    let intent_token = rsclient
+        .idm_person_credential_helpdesk_reset("test_user", user_totp, None)
+        .await
+        .unwrap();
+    let _ = rsclient.logout().await;
+
+    // The user can then reset their credentials with the token.
+    assert!(rsclient
+        .idm_account_credential_update_exchange(intent_token)
+        .await
+        .is_ok());
+}
+
+/// Act as a mail relay for a single connection, and return the messages submitted on it.
+async fn smtp_receive(listener: &tokio::net::TcpListener) -> Vec<String> {
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+    let (stream, _) = listener.accept().await.unwrap();
+    let (read, mut write) = stream.into_split();
+    let mut lines = BufReader::new(read).lines();
+    write.write_all(b"220 localhost ESMTP\r\n").await.unwrap();
+
+    let mut messages = Vec::new();
+    while let Some(line) = lines.next_line().await.unwrap() {
+        let command = line.to_uppercase();
+        if command.starts_with("DATA") {
+            write
+                .write_all(b"354 End data with <CR><LF>.<CR><LF>\r\n")
+                .await
+                .unwrap();
+            let mut message = String::new();
+            while let Some(line) = lines.next_line().await.unwrap() {
+                if line == "." {
+                    break;
+                }
+                message.push_str(&line);
+                message.push('\n');
+            }
+            messages.push(message);
+            write.write_all(b"250 OK\r\n").await.unwrap();
+        } else if command.starts_with("QUIT") {
+            write.write_all(b"221 Bye\r\n").await.unwrap();
+            break;
+        } else {
+            write.write_all(b"250 OK\r\n").await.unwrap();
+        }
+    }
+    messages
+}
+
+#[tokio::test]
+async fn test_server_credential_reset_email() {
+    use kanidm_proto::v1::CUIntentToken;
+    use kanidmd_core::config::{Configuration, MailConfig, MailTls};
+    use std::time::Duration;
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let config = Configuration {
+        mail: Some(MailConfig {
+            smtp_host: "127.0.0.1".to_string(),
+            smtp_port: Some(listener.local_addr().unwrap().port()),
+            smtp_tls: MailTls::None,
+            smtp_username: None,
+            smtp_password_file: None,
+            from: "Kanidm <idm@localhost>".to_string(),
+            template_path: None,
+        }),
+        ..Configuration::new_for_test()
+    };
+    let (rsclient, mut core_handle) = kanidmd_testkit::setup_async_test(config).await;
+
+    let res = rsclient
+        .auth_simple_password("admin", ADMIN_TEST_PASSWORD)
+        .await;
+    assert!(res.is_ok());
+    rsclient
+        .idm_group_add_members("idm_admins", &["admin"])
+        .await
+        .unwrap();
+
+    rsclient
+        .idm_person_account_create("test_user", "Test User")
+        .await
+        .unwrap();
+
+    // Without a mail address there is nowhere to send the link.
+    assert!(rsclient
+        .idm_person_credential_reset_email("test_user", None)
+        .await
+        .is_err());
+
+    rsclient
+        .idm_person_account_set_attr("test_user", "mail", &["test_user@example.com"])
+        .await
+        .unwrap();
+    rsclient
+        .idm_person_credential_reset_email("test_user", None)
+        .await
+        .unwrap();
+    let _ = rsclient.logout().await;
+
+    let messages = tokio::time::timeout(Duration::from_secs(30), smtp_receive(&listener))
+        .await
+        .expect("The credential reset link was not delivered");
+    assert_eq!(messages.len(), 1);
+    let message = &messages[0];
+    assert!(message.contains("To: test_user@example.com"));
+    assert!(message.contains("Hello Test User,"));
+
+    // The mailed code resets the credentials, and the person is told that they changed.
+    let token = message
+        .lines()
+        .skip_while(|line| !line.starts_with("Alternatively, enter this code"))
+        .nth(2)
+        .expect("The message has no reset code")
+        .to_string();
+    let (session_token, _status) = rsclient
+        .idm_account_credential_update_exchange(CUIntentToken { token })
+        .await
+        .unwrap();
+    rsclient
+        .idm_account_credential_update_set_password(&session_token, "Aech5ohjieh5eePh")
+        .await
+        .unwrap();
+    rsclient
+        .idm_account_credential_update_commit(&session_token)
+        .await
+        .unwrap();
+
+    let messages = tokio::time::timeout(Duration::from_secs(30), smtp_receive(&listener))
+        .await
+        .expect("The credential change notice was not delivered");
+    assert_eq!(messages.len(), 1);
+    assert!(messages[0].contains("Subject: The credentials of your account were changed"));
+
+    assert!(rsclient
+        .auth_simple_password("test_user", "Aech5ohjieh5eePh")
+        .await
+        .is_ok());
+
+    core_handle.shutdown().await;
+}
@@ -1,15 +1,21 @@
 use std::collections::BTreeMap;
 
 use gloo::console;
+use kanidm_proto::v1::{ApiToken, ApiTokenGenerate, ApiTokenPurpose};
+use wasm_bindgen::UnwrapThrowExt;
 use yew::{html, Component, Context, Html, Properties};
-use yew_router::prelude::Link;
+use yew_router::prelude::{Link, RouterScopeExt};
 
-use crate::components::admin_menu::{Entity, EntityType, GetError};
+use crate::components::admin_menu::{
+    split_values, submit_change, ChangeError, Entity, EntityType, GetError,
+};
 use crate::components::alpha_warning_banner;
 use crate::constants::{
     CSS_BREADCRUMB_ITEM, CSS_BREADCRUMB_ITEM_ACTIVE, CSS_CELL, CSS_DT, CSS_TABLE,
 };
-use crate::utils::{do_alert_error, do_page_header};
+use crate::utils::{
+    do_alert_error, do_page_header, get_inputelement_by_id, get_value_from_element_id,
+};
 use crate::views::AdminRoute;
 use crate::{do_request, RequestMethod};
 
@@ -24,6 +30,7 @@ impl From<GetError> for AdminListAccountsMsg {
 
 pub struct AdminListAccounts {
     state: ViewState,
+    change_error: Option<ChangeError>,
 }
 
 // callback messaging for this confused pile of crab-bait
@@ -36,6 +43,9 @@ pub enum AdminListAccountsMsg {
         emsg: String,
         kopid: Option<String>,
     },
+    /// Create a new account of this type from the details in the form
+    Create(EntityType),
+    ChangeFailed(ChangeError),
 }
 
 enum ViewState {
@@ -153,10 +163,11 @@ impl Component for AdminListAccounts {
         });
         AdminListAccounts {
             state: ViewState::Loading,
+            change_error: None,
         }
     }
 
-    fn view(&self, _ctx: &Context<Self>) -> Html {
+    fn view(&self, ctx: &Context<Self>) -> Html {
         html! {
             <>
 
@@ -166,6 +177,26 @@ impl Component for AdminListAccounts {
             </ol>
             {do_page_header("Account Administration")}
             { alpha_warning_banner() }
+            { self.change_error.as_ref().map(ChangeError::view).unwrap_or_default() }
+            <form class="row g-2 mb-3" onsubmit={ ctx.link().callback(|e: yew::SubmitEvent| {
+                e.prevent_default();
+                AdminListAccountsMsg::Create(EntityType::Person)
+            }) }>
+              <div class="col-md-3">
+                <input type="text" class="form-control" id="account_name" placeholder="Username" />
+              </div>
+              <div class="col-md-4">
+                <input type="text" class="form-control" id="account_displayname" placeholder="Display name" />
+              </div>
+              <div class="col-md-2">
+                <button type="submit" class="btn btn-primary w-100">{ "Create Person" }</button>
+              </div>
+              <div class="col-md-3">
+                <button type="button" class="btn btn-secondary w-100"
+                  onclick={ ctx.link().callback(|_| AdminListAccountsMsg::Create(EntityType::ServiceAccount)) }
+                >{ "Create Service Account" }</button>
+              </div>
+            </form>
         <div id={"accountlist"}>
         {match &self.state {
             ViewState::Loading => {
@@ -256,8 +287,43 @@ impl Component for AdminListAccounts {
         }
     }
 
-    fn update(&mut self, _ctx: &Context<Self>, msg: Self::Message) -> bool {
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
         match msg {
+            AdminListAccountsMsg::Create(object_type) => {
+                let name = get_value_from_element_id("account_name").unwrap_or_default();
+                let displayname =
+                    get_value_from_element_id("account_displayname").unwrap_or_default();
+                if name.trim().is_empty() || displayname.trim().is_empty() {
+                    return false;
+                }
+
+                let endpoint = match object_type {
+                    EntityType::ServiceAccount => "/v1/service_account",
+                    _ => "/v1/person",
+                };
+                let body = serde_json::json!({
+                    "attrs": {
+                        "name": [name.trim()],
+                        "displayname": [displayname.trim()],
+                    }
+                });
+
+                self.change_error = None;
+                ctx.link().send_future(async move {
+                    match submit_change(endpoint, RequestMethod::POST, Some(body)).await {
+                        Ok(_) => match get_accounts().await {
+                            Ok(v) => v,
+                            Err(v) => v.into(),
+                        },
+                        Err(e) => AdminListAccountsMsg::ChangeFailed(e),
+                    }
+                });
+                return false;
+            }
+            AdminListAccountsMsg::ChangeFailed(change_error) => {
+                self.change_error = Some(change_error);
+                return true;
+            }
             AdminListAccountsMsg::Responded { response } => {
                 // TODO: do we paginate here?
                 /*
@@ -300,8 +366,8 @@ impl From<GetError> for AdminViewPersonMsg {
 }
 
 pub struct AdminViewPerson {
-    #[allow(dead_code)]
     state: ViewAccountState,
+    change_error: Option<ChangeError>,
 }
 
 #[derive(Properties, PartialEq, Eq, Clone)]
@@ -313,9 +379,19 @@ pub struct AdminViewAccountProps {
 // callback messaging for this confused pile of crab-bait
 pub enum AdminViewPersonMsg {
     /// When the server responds and we need to update the page
-    Responded { response: Entity },
+    Responded {
+        response: Entity,
+    },
     #[allow(dead_code)]
-    Failed { emsg: String, kopid: Option<String> },
+    Failed {
+        emsg: String,
+        kopid: Option<String>,
+    },
+    /// Save the details in the edit form
+    Save,
+    Delete,
+    Deleted,
+    ChangeFailed(ChangeError),
 }
 
 impl Component for AdminViewPerson {
@@ -332,10 +408,12 @@ impl Component for AdminViewPerson {
         });
         AdminViewPerson {
             state: ViewAccountState::Loading,
+            change_error: None,
         }
     }
 
-    fn update(&mut self, _ctx: &Context<Self>, msg: Self::Message) -> bool {
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        let uuid = ctx.props().uuid.clone();
         match msg {
             AdminViewPersonMsg::Responded { response } => {
                 self.state = ViewAccountState::Responded { response }
@@ -343,6 +421,90 @@ impl Component for AdminViewPerson {
             AdminViewPersonMsg::Failed { emsg, kopid } => {
                 self.state = ViewAccountState::Failed { emsg, kopid }
             }
+            AdminViewPersonMsg::Save => {
+                let displayname = get_value_from_element_id("person_displayname")
+                    .unwrap_or_default()
+                    .trim()
+                    .to_string();
+                let legalname = get_value_from_element_id("person_legalname")
+                    .unwrap_or_default()
+                    .trim()
+                    .to_string();
+                let mail =
+                    split_values(&get_value_from_element_id("person_mail").unwrap_or_default());
+
+                let legalname = if legalname.is_empty() {
+                    Vec::new()
+                } else {
+                    vec![legalname]
+                };
+
+                // Only send the attributes that were changed.
+                let changes: Vec<_> = match &self.state {
+                    ViewAccountState::Responded { response } => [
+                        (
+                            "displayname",
+                            vec![displayname],
+                            &response.attrs.displayname,
+                        ),
+                        ("legalname", legalname, &response.attrs.legalname),
+                        ("mail", mail, &response.attrs.mail),
+                    ]
+                    .into_iter()
+                    .filter(|(_, values, current)| values != *current)
+                    .map(|(attr, values, _)| (attr, values))
+                    .collect(),
+                    _ => return false,
+                };
+
+                self.change_error = None;
+                ctx.link().send_future(async move {
+                    for (attr, values) in changes {
+                        let uri = format!("/v1/person/{}/_attr/{}", uuid, attr);
+                        // An empty value removes the attribute.
+                        let res = if values.is_empty() {
+                            submit_change(&uri, RequestMethod::DELETE, None).await
+                        } else {
+                            submit_change(&uri, RequestMethod::PUT, Some(serde_json::json!(values)))
+                                .await
+                        };
+                        if let Err(e) = res {
+                            return AdminViewPersonMsg::ChangeFailed(e);
+                        }
+                    }
+                    match get_person(&uuid).await {
+                        Ok(v) => v,
+                        Err(v) => v.into(),
+                    }
+                });
+                return false;
+            }
+            AdminViewPersonMsg::Delete => {
+                self.change_error = None;
+                ctx.link().send_future(async move {
+                    match submit_change(
+                        &format!("/v1/person/{}", uuid),
+                        RequestMethod::DELETE,
+                        None,
+                    )
+                    .await
+                    {
+                        Ok(_) => AdminViewPersonMsg::Deleted,
+                        Err(e) => AdminViewPersonMsg::ChangeFailed(e),
+                    }
+                });
+                return false;
+            }
+            AdminViewPersonMsg::Deleted => {
+                ctx.link()
+                    .navigator()
+                    .expect_throw("failed to read history")
+                    .push(&AdminRoute::AdminListAccounts);
+                return false;
+            }
+            AdminViewPersonMsg::ChangeFailed(change_error) => {
+                self.change_error = Some(change_error);
+            }
         }
         true
     }
@@ -359,7 +521,6 @@ impl Component for AdminViewPerson {
                 html! {{"You are not authorized to view this page!"}}
             }
             ViewAccountState::Responded { response } => {
-                // TODO: This is pretty lacking in detail, even logged in as the idm_admin user, so will have to work out how to pull all the details
                 let username = match response.attrs.name.first() {
                     Some(value) => value.to_owned(),
                     None => String::from("Unable to query username"),
@@ -368,18 +529,14 @@ impl Component for AdminViewPerson {
                     Some(value) => value.to_string(),
                     None => String::from("Display Name Unset"),
                 };
-                // let user_groups = userinfo.youare.attrs.get("memberof");
-
-                // let mail_primary = match userinfo.uat.mail_primary.as_ref() {
-                //     Some(email_address) => {
-                //         html! {
-                //             <a href={ format!("mailto:{}", &email_address)}>
-                //             {email_address}
-                //             </a>
-                //         }
-                //     }
-                //     None => html! { {"<primary email is unset>"}},
-                // };
+                let legal_name = response
+                    .attrs
+                    .legalname
+                    .first()
+                    .cloned()
+                    .unwrap_or_default();
+                let mail = response.attrs.mail.join(" ");
+
                 html! {
                     <>
                     <ol class="breadcrumb">
@@ -389,55 +546,51 @@ impl Component for AdminViewPerson {
                     </ol>
                     {do_page_header(display_name.as_str())}
                     {alpha_warning_banner()}
+                    { self.change_error.as_ref().map(ChangeError::view).unwrap_or_default() }
 
-                    // <dt class={CSS_DT}>{ "Display Name" }</dt>
-                    // <dl class="row">
-                    // <dd class="col">{  }</dd>
-
-                //         <dt class={CSS_DT}>{ "Primary Email" }</dt>
-                //         <dd class="col">{mail_primary}</dd>
-
-                //         <dt class={CSS_DT}>{ "Group Memberships" }</dt>
-                //         <dd class="col">
-                //             <ul class="list-group">
-                //             {
-                //             match user_groups {
-                //                 Some(grouplist) => html!{
-                //                     {
-                //                         for grouplist.iter()
-                //                             .map(|group|
-                //                     {
-                //                         html!{ <li>{
-                //                             #[allow(clippy::unwrap_used)]
-                //                             group.split('@').next().unwrap().to_string()
-                //                         }</li> }
-
-                //                     })
-                //                 }
-                //                 },
-                //                 None => html!{
-                //                     <li>{"Not a member of any groups"}</li>
-                //                     }
-                //                 }
-                //             }
-                //             </ul>
-                //         </dd>
-
-
-                //     <dt class={CSS_DT}>
-                //     { "User's SPN" }
-                //     </dt>
-                //       <dd class="col">
-                //       { username.to_string() }{"@"}{ domain }
-                //       </dd>
-
+                    <dl class="row">
                     <dt class={CSS_DT}>{ "Username" }</dt>
                     <dd class="col">{ username }</dd>
 
                     <dt class={CSS_DT}>{ "User's UUID" }</dt>
                     <dd class="col">{ ctx.props().to_owned().uuid }</dd>
 
-                // </dl>
+                    <dt class={CSS_DT}>{ "Group Memberships" }</dt>
+                    <dd class="col">
+                      <ul class="list-unstyled">
+                      {
+                        response.attrs.memberof.iter().map(|group| html! {
+                            <li>{ group }</li>
+                        }).collect::<Html>()
+                      }
+                      </ul>
+                    </dd>
+                    </dl>
+
+                    <h4>{ "Edit Details" }</h4>
+                    <form class="mb-4" onsubmit={ ctx.link().callback(|e: yew::SubmitEvent| {
+                        e.prevent_default();
+                        AdminViewPersonMsg::Save
+                    }) }>
+                      <div class="mb-2">
+                        <label for="person_displayname" class="form-label">{ "Display Name" }</label>
+                        <input type="text" class="form-control" id="person_displayname" value={ display_name } />
+                      </div>
+                      <div class="mb-2">
+                        <label for="person_legalname" class="form-label">{ "Legal Name" }</label>
+                        <input type="text" class="form-control" id="person_legalname" value={ legal_name } />
+                      </div>
+                      <div class="mb-2">
+                        <label for="person_mail" class="form-label">{ "Email Addresses" }</label>
+                        <input type="text" class="form-control" id="person_mail" value={ mail } />
+                        <div class="form-text">{ "Separate addresses with spaces. The first is the primary address." }</div>
+                      </div>
+                      <button type="submit" class="btn btn-primary">{ "Save" }</button>
+                    </form>
+
+                    <button type="button" class="btn btn-danger"
+                      onclick={ ctx.link().callback(|_| AdminViewPersonMsg::Delete) }
+                    >{ "Delete Person" }</button>
                 </>
                 }
             }
@@ -455,16 +608,33 @@ impl From<GetError> for AdminViewServiceAccountMsg {
 }
 
 pub struct AdminViewServiceAccount {
-    #[allow(dead_code)]
     state: ViewAccountState,
+    api_tokens: Vec<ApiToken>,
+    /// A token that was just generated, shown once so it can be copied.
+    new_api_token: Option<String>,
+    change_error: Option<ChangeError>,
 }
 
 // callback messaging for this confused pile of crab-bait
 pub enum AdminViewServiceAccountMsg {
     /// When the server responds and we need to update the page
-    Responded { response: Entity },
+    Responded {
+        response: Entity,
+        api_tokens: Vec<ApiToken>,
+    },
     #[allow(dead_code)]
-    Failed { emsg: String, kopid: Option<String> },
+    Failed {
+        emsg: String,
+        kopid: Option<String>,
+    },
+    /// Save the details in the edit form
+    Save,
+    GenerateApiToken,
+    ApiTokenGenerated(String),
+    DestroyApiToken(String),
+    Delete,
+    Deleted,
+    ChangeFailed(ChangeError),
 }
 
 impl Component for AdminViewServiceAccount {
@@ -481,10 +651,13 @@ impl Component for AdminViewServiceAccount {
         });
         AdminViewServiceAccount {
             state: ViewAccountState::Loading,
+            api_tokens: Vec::new(),
+            new_api_token: None,
+            change_error: None,
         }
     }
 
-    fn view(&self, _ctx: &Context<Self>) -> Html {
+    fn view(&self, ctx: &Context<Self>) -> Html {
         match &self.state {
             ViewAccountState::Loading => html! {{"Loading..."}},
             ViewAccountState::Responded { response } => {
@@ -497,9 +670,15 @@ impl Component for AdminViewServiceAccount {
                     Some(value) => value.as_str(),
                     None => "Unable to pull displayname",
                 };
-
-                let description = match account.description.first() {
-                    Some(value) => html! {<p>{"Description: "}{value.as_str()}</p>},
+                let description = account.description.first().cloned().unwrap_or_default();
+
+                let new_api_token = match &self.new_api_token {
+                    Some(token) => html! {
+                      <div class="alert alert-success" role="alert">
+                        <p>{ "Copy this token now, it will not be shown again." }</p>
+                        <code class="text-break">{ token }</code>
+                      </div>
+                    },
                     None => html! {},
                 };
 
@@ -512,8 +691,46 @@ impl Component for AdminViewServiceAccount {
                 </ol>
                 {do_page_header(&format!("Service Account: {}", username))}
                 {alpha_warning_banner()}
-                <p>{"Display Name: "}{displayname}</p>
-                {description}
+                { self.change_error.as_ref().map(ChangeError::view).unwrap_or_default() }
+
+                <h4>{ "Edit Details" }</h4>
+                <form class="mb-4" onsubmit={ ctx.link().callback(|e: yew::SubmitEvent| {
+                    e.prevent_default();
+                    AdminViewServiceAccountMsg::Save
+                }) }>
+                  <div class="mb-2">
+                    <label for="sa_displayname" class="form-label">{ "Display Name" }</label>
+                    <input type="text" class="form-control" id="sa_displayname" value={ displayname.to_string() } />
+                  </div>
+                  <div class="mb-2">
+                    <label for="sa_description" class="form-label">{ "Description" }</label>
+                    <input type="text" class="form-control" id="sa_description" value={ description } />
+                  </div>
+                  <button type="submit" class="btn btn-primary">{ "Save" }</button>
+                </form>
+
+                <h4>{ "API Tokens" }</h4>
+                { new_api_token }
+                { self.view_api_tokens(ctx) }
+                <form class="row g-2 mb-4" onsubmit={ ctx.link().callback(|e: yew::SubmitEvent| {
+                    e.prevent_default();
+                    AdminViewServiceAccountMsg::GenerateApiToken
+                }) }>
+                  <div class="col-md-6">
+                    <input type="text" class="form-control" id="sa_api_token_label" placeholder="Token label" />
+                  </div>
+                  <div class="col-md-3 form-check pt-2">
+                    <input type="checkbox" class="form-check-input" id="sa_api_token_rw" />
+                    <label for="sa_api_token_rw" class="form-check-label">{ "Read write" }</label>
+                  </div>
+                  <div class="col-md-3">
+                    <button type="submit" class="btn btn-primary w-100">{ "Generate Token" }</button>
+                  </div>
+                </form>
+
+                <button type="button" class="btn btn-danger"
+                  onclick={ ctx.link().callback(|_| AdminViewServiceAccountMsg::Delete) }
+                >{ "Delete Service Account" }</button>
                 </>
                 }
             }
@@ -527,19 +744,197 @@ impl Component for AdminViewServiceAccount {
         }
     }
 
-    fn update(&mut self, _ctx: &Context<Self>, msg: Self::Message) -> bool {
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        let uuid = ctx.props().uuid.clone();
         match msg {
-            AdminViewServiceAccountMsg::Responded { response } => {
-                self.state = ViewAccountState::Responded { response }
+            AdminViewServiceAccountMsg::Responded {
+                response,
+                api_tokens,
+            } => {
+                self.state = ViewAccountState::Responded { response };
+                self.api_tokens = api_tokens;
             }
             AdminViewServiceAccountMsg::Failed { emsg, kopid } => {
                 self.state = ViewAccountState::Failed { emsg, kopid }
             }
+            AdminViewServiceAccountMsg::Save => {
+                let displayname = get_value_from_element_id("sa_displayname")
+                    .unwrap_or_default()
+                    .trim()
+                    .to_string();
+                let description = get_value_from_element_id("sa_description")
+                    .unwrap_or_default()
+                    .trim()
+                    .to_string();
+
+                // Only send the attributes that were changed.
+                let changes: Vec<_> = match &self.state {
+                    ViewAccountState::Responded { response } => [
+                        ("displayname", displayname, &response.attrs.displayname),
+                        ("description", description, &response.attrs.description),
+                    ]
+                    .into_iter()
+                    .filter(|(_, value, current)| current.first() != Some(value))
+                    .filter(|(_, value, current)| !(value.is_empty() && current.is_empty()))
+                    .map(|(attr, value, _)| (attr, value))
+                    .collect(),
+                    _ => return false,
+                };
+
+                self.change_error = None;
+                ctx.link().send_future(async move {
+                    for (attr, value) in changes {
+                        let uri = format!("/v1/service_account/{}/_attr/{}", uuid, attr);
+                        // An empty value removes the attribute.
+                        let res = if value.is_empty() {
+                            submit_change(&uri, RequestMethod::DELETE, None).await
+                        } else {
+                            submit_change(
+                                &uri,
+                                RequestMethod::PUT,
+                                Some(serde_json::json!([value])),
+                            )
+                            .await
+                        };
+                        if let Err(e) = res {
+                            return AdminViewServiceAccountMsg::ChangeFailed(e);
+                        }
+                    }
+                    match get_service_account(&uuid).await {
+                        Ok(v) => v,
+                        Err(v) => v.into(),
+                    }
+                });
+                return false;
+            }
+            AdminViewServiceAccountMsg::GenerateApiToken => {
+                let label = get_value_from_element_id("sa_api_token_label")
+                    .unwrap_or_default()
+                    .trim()
+                    .to_string();
+                if label.is_empty() {
+                    return false;
+                }
+                let read_write = get_inputelement_by_id("sa_api_token_rw")
+                    .map(|element| element.checked())
+                    .unwrap_or_default();
+                let body = serde_json::to_value(ApiTokenGenerate {
+                    label,
+                    expiry: None,
+                    read_write,
+                })
+                .expect_throw("Failed to serialise the api token request");
+
+                self.change_error = None;
+                self.new_api_token = None;
+                ctx.link().send_future(async move {
+                    let uri = format!("/v1/service_account/{}/_api_token", uuid);
+                    match submit_change(&uri, RequestMethod::POST, Some(body)).await {
+                        Ok(value) => AdminViewServiceAccountMsg::ApiTokenGenerated(
+                            value.as_string().unwrap_or_default(),
+                        ),
+                        Err(e) => AdminViewServiceAccountMsg::ChangeFailed(e),
+                    }
+                });
+                return false;
+            }
+            AdminViewServiceAccountMsg::ApiTokenGenerated(token) => {
+                self.new_api_token = Some(token);
+                ctx.link().send_future(async move {
+                    match get_service_account(&uuid).await {
+                        Ok(v) => v,
+                        Err(v) => v.into(),
+                    }
+                });
+            }
+            AdminViewServiceAccountMsg::DestroyApiToken(token_id) => {
+                self.change_error = None;
+                ctx.link().send_future(async move {
+                    let uri = format!("/v1/service_account/{}/_api_token/{}", uuid, token_id);
+                    match submit_change(&uri, RequestMethod::DELETE, None).await {
+                        Ok(_) => match get_service_account(&uuid).await {
+                            Ok(v) => v,
+                            Err(v) => v.into(),
+                        },
+                        Err(e) => AdminViewServiceAccountMsg::ChangeFailed(e),
+                    }
+                });
+                return false;
+            }
+            AdminViewServiceAccountMsg::Delete => {
+                self.change_error = None;
+                ctx.link().send_future(async move {
+                    let uri = format!("/v1/service_account/{}", uuid);
+                    match submit_change(&uri, RequestMethod::DELETE, None).await {
+                        Ok(_) => AdminViewServiceAccountMsg::Deleted,
+                        Err(e) => AdminViewServiceAccountMsg::ChangeFailed(e),
+                    }
+                });
+                return false;
+            }
+            AdminViewServiceAccountMsg::Deleted => {
+                ctx.link()
+                    .navigator()
+                    .expect_throw("failed to read history")
+                    .push(&AdminRoute::AdminListAccounts);
+                return false;
+            }
+            AdminViewServiceAccountMsg::ChangeFailed(change_error) => {
+                self.change_error = Some(change_error);
+            }
         }
         true
     }
 }
 
+impl AdminViewServiceAccount {
+    fn view_api_tokens(&self, ctx: &Context<Self>) -> Html {
+        if self.api_tokens.is_empty() {
+            return html! { <p>{ "This service account has no API tokens." }</p> };
+        }
+
+        let scope_col = "col";
+        html! {
+          <table class={CSS_TABLE}>
+            <thead>
+              <tr>
+                <th scope={scope_col}>{ "Label" }</th>
+                <th scope={scope_col}>{ "Access" }</th>
+                <th scope={scope_col}>{ "Issued" }</th>
+                <th scope={scope_col}>{ "Expires" }</th>
+                <th scope={scope_col}></th>
+              </tr>
+            </thead>
+            <tbody>
+            {
+              self.api_tokens.iter().map(|token| {
+                  let token_id = token.token_id.to_string();
+                  let access = match token.purpose {
+                      ApiTokenPurpose::ReadOnly => "Read only",
+                      ApiTokenPurpose::ReadWrite => "Read write",
+                      ApiTokenPurpose::Synchronise => "Synchronise",
+                  };
+                  html! {
+                    <tr key={token_id.clone()}>
+                      <td class={CSS_CELL}>{ token.label.as_str() }</td>
+                      <td class={CSS_CELL}>{ access }</td>
+                      <td class={CSS_CELL}>{ token.issued_at.to_string() }</td>
+                      <td class={CSS_CELL}>{ token.expiry.map(|e| e.to_string()).unwrap_or_else(|| "Never".to_string()) }</td>
+                      <td class={CSS_CELL}>
+                        <button type="button" class="btn btn-sm btn-secondary"
+                          onclick={ ctx.link().callback(move |_| AdminViewServiceAccountMsg::DestroyApiToken(token_id.clone())) }
+                        >{ "Destroy" }</button>
+                      </td>
+                    </tr>
+                  }
+              }).collect::<Html>()
+            }
+            </tbody>
+          </table>
+        }
+    }
+}
+
 /// pull the details for a single person by UUID
 pub async fn get_person(uuid: &str) -> Result<AdminViewPersonMsg, GetError> {
     let (_, _, value, _) = match do_request(
@@ -596,5 +991,33 @@ pub async fn get_service_account(uuid: &str) -> Result<AdminViewServiceAccountMs
             });
         }
     };
-    Ok(AdminViewServiceAccountMsg::Responded { response: data })
+
+    let (_, _, value, _) = match do_request(
+        format!("/v1/service_account/{}/_api_token", uuid).as_str(),
+        RequestMethod::GET,
+        None,
+    )
+    .await
+    {
+        Ok(val) => val,
+        Err(error) => {
+            return Err(GetError {
+                err: format!("{:?}", error),
+            })
+        }
+    };
+
+    let api_tokens: Vec<ApiToken> = match serde_wasm_bindgen::from_value(value) {
+        Ok(value) => value,
+        Err(error) => {
+            return Err(GetError {
+                err: format!("Failed to grab the api token data into JSON: {:?}", error),
+            });
+        }
+    };
+
+    Ok(AdminViewServiceAccountMsg::Responded {
+        response: data,
+        api_tokens,
+    })
 }
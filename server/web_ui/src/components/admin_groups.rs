@@ -1,13 +1,16 @@
 use std::collections::BTreeMap;
 
 use gloo::console;
+use wasm_bindgen::UnwrapThrowExt;
 use yew::{html, Component, Context, Html, Properties};
-use yew_router::prelude::Link;
+use yew_router::prelude::{Link, RouterScopeExt};
 
-use crate::components::admin_menu::{Entity, EntityType, GetError};
+use crate::components::admin_menu::{
+    split_values, submit_change, ChangeError, Entity, EntityType, GetError,
+};
 use crate::components::alpha_warning_banner;
 use crate::constants::{CSS_BREADCRUMB_ITEM, CSS_BREADCRUMB_ITEM_ACTIVE, CSS_CELL, CSS_TABLE};
-use crate::utils::{do_alert_error, do_page_header};
+use crate::utils::{do_alert_error, do_page_header, get_value_from_element_id};
 use crate::views::AdminRoute;
 use crate::{do_request, RequestMethod};
 
@@ -22,6 +25,7 @@ impl From<GetError> for AdminListGroupsMsg {
 
 pub struct AdminListGroups {
     state: GroupsViewState,
+    change_error: Option<ChangeError>,
 }
 
 // callback messaging for this confused pile of crab-bait
@@ -34,6 +38,9 @@ pub enum AdminListGroupsMsg {
         emsg: String,
         kopid: Option<String>,
     },
+    /// Create a new group from the details in the form
+    Create,
+    ChangeFailed(ChangeError),
 }
 
 enum GroupsViewState {
@@ -130,6 +137,7 @@ impl Component for AdminListGroups {
         });
         AdminListGroups {
             state: GroupsViewState::Loading,
+            change_error: None,
         }
     }
 
@@ -139,6 +147,8 @@ impl Component for AdminListGroups {
               {do_page_header("Group Administration")}
 
               { alpha_warning_banner() }
+              { self.change_error.as_ref().map(ChangeError::view).unwrap_or_default() }
+              { self.view_create_form(ctx) }
         <div id={"grouplist"}>
         {self.view_state(ctx)}
         </div>
@@ -146,8 +156,39 @@ impl Component for AdminListGroups {
         }
     }
 
-    fn update(&mut self, _ctx: &Context<Self>, msg: Self::Message) -> bool {
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
         match msg {
+            AdminListGroupsMsg::Create => {
+                let name = get_value_from_element_id("group_name").unwrap_or_default();
+                let description =
+                    get_value_from_element_id("group_description").unwrap_or_default();
+                if name.trim().is_empty() {
+                    return false;
+                }
+
+                let mut attrs = BTreeMap::new();
+                attrs.insert("name", vec![name.trim().to_string()]);
+                if !description.trim().is_empty() {
+                    attrs.insert("description", vec![description.trim().to_string()]);
+                }
+                let body = serde_json::json!({ "attrs": attrs });
+
+                self.change_error = None;
+                ctx.link().send_future(async move {
+                    match submit_change("/v1/group", RequestMethod::POST, Some(body)).await {
+                        Ok(_) => match get_groups().await {
+                            Ok(v) => v,
+                            Err(v) => v.into(),
+                        },
+                        Err(e) => AdminListGroupsMsg::ChangeFailed(e),
+                    }
+                });
+                return false;
+            }
+            AdminListGroupsMsg::ChangeFailed(change_error) => {
+                self.change_error = Some(change_error);
+                return true;
+            }
             AdminListGroupsMsg::Responded { response } => {
                 // TODO: do we paginate here?
                 #[cfg(test)]
@@ -171,6 +212,25 @@ impl Component for AdminListGroups {
 }
 
 impl AdminListGroups {
+    fn view_create_form(&self, ctx: &Context<Self>) -> Html {
+        html! {
+          <form class="row g-2 mb-3" onsubmit={ ctx.link().callback(|e: yew::SubmitEvent| {
+              e.prevent_default();
+              AdminListGroupsMsg::Create
+          }) }>
+            <div class="col-md-4">
+              <input type="text" class="form-control" id="group_name" placeholder="Group name" />
+            </div>
+            <div class="col-md-6">
+              <input type="text" class="form-control" id="group_description" placeholder="Description (optional)" />
+            </div>
+            <div class="col-md-2">
+              <button type="submit" class="btn btn-primary w-100">{ "Create Group" }</button>
+            </div>
+          </form>
+        }
+    }
+
     /// output the information based on what's in the current state
     fn view_state(&self, _ctx: &Context<Self>) -> Html {
         match &self.state {
@@ -243,11 +303,21 @@ pub struct AdminViewGroupProps {
 // callback messaging for group detail view
 pub enum AdminViewGroupMsg {
     /// When the server responds and we need to update the page
-    Responded { response: Entity },
+    Responded {
+        response: Entity,
+    },
     #[allow(dead_code)]
-    Failed { emsg: String, kopid: Option<String> },
+    Failed {
+        emsg: String,
+        kopid: Option<String>,
+    },
     #[allow(dead_code)]
     NotAuthorized {},
+    AddMembers,
+    RemoveMember(String),
+    Delete,
+    Deleted,
+    ChangeFailed(ChangeError),
 }
 
 impl From<GetError> for AdminViewGroupMsg {
@@ -278,6 +348,7 @@ enum GroupViewState {
 
 pub struct AdminViewGroup {
     state: GroupViewState,
+    change_error: Option<ChangeError>,
 }
 
 impl Component for AdminViewGroup {
@@ -296,10 +367,11 @@ impl Component for AdminViewGroup {
 
         AdminViewGroup {
             state: GroupViewState::Loading,
+            change_error: None,
         }
     }
 
-    fn view(&self, _ctx: &Context<Self>) -> Html {
+    fn view(&self, ctx: &Context<Self>) -> Html {
         match &self.state {
             GroupViewState::Loading => html! {"Loading..."},
             GroupViewState::Responded { response } => {
@@ -324,9 +396,15 @@ impl Component for AdminViewGroup {
                     <li class={CSS_BREADCRUMB_ITEM_ACTIVE} aria-current="page">{group_name}</li>
                     </ol>
                     {do_page_header(&page_title)}
+                    { self.change_error.as_ref().map(ChangeError::view).unwrap_or_default() }
                     <p>{"UUID: "}{group_uuid}</p>
-                    // TODO: pull group membership and show members
-                    <p>{"Group Membership will show up here soon..."}</p>
+                    if let Some(description) = response.attrs.description.first() {
+                        <p>{"Description: "}{description}</p>
+                    }
+                    { self.view_members(ctx, &response.attrs.member) }
+                    <button type="button" class="btn btn-danger"
+                      onclick={ ctx.link().callback(|_| AdminViewGroupMsg::Delete) }
+                    >{ "Delete Group" }</button>
                     </>
                 }
             }
@@ -344,8 +422,56 @@ impl Component for AdminViewGroup {
         }
     }
 
-    fn update(&mut self, _ctx: &Context<Self>, msg: Self::Message) -> bool {
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        let uuid = ctx.props().uuid.clone();
         match msg {
+            AdminViewGroupMsg::AddMembers => {
+                let members = split_values(
+                    &get_value_from_element_id("group_member_add").unwrap_or_default(),
+                );
+                if members.is_empty() {
+                    return false;
+                }
+                self.change(
+                    ctx,
+                    format!("/v1/group/{}/_attr/member", uuid),
+                    RequestMethod::POST,
+                    Some(serde_json::json!(members)),
+                );
+                true
+            }
+            AdminViewGroupMsg::RemoveMember(member) => {
+                self.change(
+                    ctx,
+                    format!("/v1/group/{}/_attr/member", uuid),
+                    RequestMethod::DELETE,
+                    Some(serde_json::json!([member])),
+                );
+                true
+            }
+            AdminViewGroupMsg::Delete => {
+                self.change_error = None;
+                ctx.link().send_future(async move {
+                    match submit_change(&format!("/v1/group/{}", uuid), RequestMethod::DELETE, None)
+                        .await
+                    {
+                        Ok(_) => AdminViewGroupMsg::Deleted,
+                        Err(e) => AdminViewGroupMsg::ChangeFailed(e),
+                    }
+                });
+                false
+            }
+            AdminViewGroupMsg::Deleted => {
+                ctx.link()
+                    .navigator()
+                    .expect_throw("failed to read history")
+                    .push(&AdminRoute::AdminListGroups);
+                false
+            }
+            AdminViewGroupMsg::ChangeFailed(change_error) => {
+                self.change_error = Some(change_error);
+                true
+            }
             AdminViewGroupMsg::Responded { response } => {
                 self.state = GroupViewState::Responded { response };
                 true
@@ -362,6 +488,72 @@ impl Component for AdminViewGroup {
     }
 }
 
+impl AdminViewGroup {
+    fn view_members(&self, ctx: &Context<Self>, members: &[String]) -> Html {
+        html! {
+          <>
+            <h4>{ "Members" }</h4>
+            if members.is_empty() {
+                <p>{ "This group has no members." }</p>
+            } else {
+                <table class={CSS_TABLE}>
+                  <tbody>
+                  {
+                    members.iter().map(|member| {
+                        let remove = member.clone();
+                        html! {
+                          <tr key={member.clone()}>
+                            <td class={CSS_CELL}>{ member }</td>
+                            <td class={CSS_CELL}>
+                              <button type="button" class="btn btn-sm btn-secondary"
+                                onclick={ ctx.link().callback(move |_| AdminViewGroupMsg::RemoveMember(remove.clone())) }
+                              >{ "Remove" }</button>
+                            </td>
+                          </tr>
+                        }
+                    }).collect::<Html>()
+                  }
+                  </tbody>
+                </table>
+            }
+            <form class="row g-2 mb-4" onsubmit={ ctx.link().callback(|e: yew::SubmitEvent| {
+                e.prevent_default();
+                AdminViewGroupMsg::AddMembers
+            }) }>
+              <div class="col-md-10">
+                <input type="text" class="form-control" id="group_member_add"
+                  placeholder="Account or group names, separated by spaces" />
+              </div>
+              <div class="col-md-2">
+                <button type="submit" class="btn btn-primary w-100">{ "Add Members" }</button>
+              </div>
+            </form>
+          </>
+        }
+    }
+
+    /// Submit a change to the group, and reload it once it's done.
+    fn change(
+        &mut self,
+        ctx: &Context<Self>,
+        uri: String,
+        method: RequestMethod,
+        body: Option<serde_json::Value>,
+    ) {
+        let uuid = ctx.props().uuid.clone();
+        self.change_error = None;
+        ctx.link().send_future(async move {
+            match submit_change(&uri, method, body).await {
+                Ok(_) => match get_group(&uuid).await {
+                    Ok(v) => v,
+                    Err(v) => v.into(),
+                },
+                Err(e) => AdminViewGroupMsg::ChangeFailed(e),
+            }
+        });
+    }
+}
+
 /// pull the details for a single group by UUID
 pub async fn get_group(groupid: &str) -> Result<AdminViewGroupMsg, GetError> {
     let endpoint = format!("/v1/group/{}", groupid);
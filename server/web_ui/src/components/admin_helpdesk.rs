@@ -0,0 +1,247 @@
+use kanidm_proto::internal::{
+    HelpdeskCredentialResetRequest, IdentifyUserRequest, IdentifyUserResponse,
+};
+use kanidm_proto::v1::CUIntentToken;
+use wasm_bindgen::UnwrapThrowExt;
+use yew::{html, Component, Context, Html, Properties};
+use yew_router::prelude::Link;
+
+use crate::components::admin_menu::{submit_change, ChangeError};
+use crate::components::alpha_warning_banner;
+use crate::constants::{CSS_BREADCRUMB_ITEM, CSS_BREADCRUMB_ITEM_ACTIVE};
+use crate::utils::{do_page_header, get_value_from_element_id, origin};
+use crate::views::AdminRoute;
+use crate::RequestMethod;
+
+const ID_HELPDESK_USER: &str = "helpdesk_user";
+const ID_HELPDESK_CODE: &str = "helpdesk_code";
+
+pub enum AdminHelpdeskMsg {
+    Start,
+    ProvideCode {
+        account_id: String,
+        step: u32,
+        totp: u32,
+    },
+    Reset,
+    Ready {
+        account_id: String,
+        token: CUIntentToken,
+    },
+    Restart,
+    Failed(ChangeError),
+}
+
+enum ViewState {
+    /// Waiting for the helpdesk to enter who they are helping.
+    Init,
+    Waiting,
+    /// The helpdesk reads the code to the person, and waits for the person's code in return.
+    ProvideCode {
+        account_id: String,
+        step: u32,
+        totp: u32,
+    },
+    /// The person's identity is verified, and the reset token can be released to them.
+    Ready {
+        account_id: String,
+        token: CUIntentToken,
+    },
+}
+
+#[derive(PartialEq, Properties, Eq)]
+pub struct AdminHelpdeskProps {}
+
+pub struct AdminHelpdesk {
+    state: ViewState,
+    change_error: Option<ChangeError>,
+}
+
+impl Component for AdminHelpdesk {
+    type Message = AdminHelpdeskMsg;
+    type Properties = AdminHelpdeskProps;
+
+    fn create(_ctx: &Context<Self>) -> Self {
+        AdminHelpdesk {
+            state: ViewState::Init,
+            change_error: None,
+        }
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let body = match &self.state {
+            ViewState::Init => html! {
+              <form onsubmit={ ctx.link().callback(|e: yew::SubmitEvent| {
+                  e.prevent_default();
+                  AdminHelpdeskMsg::Start
+              }) }>
+                <label for={ID_HELPDESK_USER} class="form-label">{ "Username of the person you are helping" }</label>
+                <div class="input-group">
+                  <input type="text" class="form-control" id={ID_HELPDESK_USER} required=true />
+                  <button type="submit" class="btn btn-primary">{ "Verify Identity" }</button>
+                </div>
+              </form>
+            },
+            ViewState::Waiting => html! {
+              <div class="spinner-border text-dark" role="status">
+                <span class="visually-hidden">{ "Loading..." }</span>
+              </div>
+            },
+            ViewState::ProvideCode {
+                account_id,
+                step,
+                totp,
+            } => html! {
+              <>
+                <p>{ format!("Read this code to {}. It is valid for {} seconds.", account_id, step) }</p>
+                <p class="fs-2 font-monospace">{ format!("{:06}", totp) }</p>
+                <p>{ format!("{} can enter this code on their profile page to confirm they are talking to you. They will then be shown a code to read back to you.", account_id) }</p>
+                <form onsubmit={ ctx.link().callback(|e: yew::SubmitEvent| {
+                    e.prevent_default();
+                    AdminHelpdeskMsg::Reset
+                }) }>
+                  <label for={ID_HELPDESK_CODE} class="form-label">{ format!("Code read to you by {}", account_id) }</label>
+                  <div class="input-group mb-3">
+                    <input type="text" class="form-control" id={ID_HELPDESK_CODE}
+                      inputmode="numeric" autocomplete="off" required=true />
+                    <button type="submit" class="btn btn-primary">{ "Reset Credentials" }</button>
+                  </div>
+                </form>
+                <button type="button" class="btn btn-secondary"
+                  onclick={ ctx.link().callback(|_| AdminHelpdeskMsg::Restart) }
+                >{ "Cancel" }</button>
+              </>
+            },
+            ViewState::Ready { account_id, token } => {
+                let mut url = origin();
+                url.set_path("/ui/reset");
+                url.query_pairs_mut()
+                    .append_pair("token", token.token.as_str());
+
+                html! {
+                  <>
+                    <div class="alert alert-success" role="alert">
+                      { format!("The identity of {} is verified.", account_id) }
+                    </div>
+                    <p>{ "Send this link to the person so they can reset their credentials." }</p>
+                    <p><a href={ url.to_string() }>{ url.to_string() }</a></p>
+                    <p>{ format!("Code: {}", token.token) }</p>
+                    <button type="button" class="btn btn-secondary"
+                      onclick={ ctx.link().callback(|_| AdminHelpdeskMsg::Restart) }
+                    >{ "Done" }</button>
+                  </>
+                }
+            }
+        };
+
+        html! {
+          <>
+            <ol class="breadcrumb">
+            <li class={CSS_BREADCRUMB_ITEM}><Link<AdminRoute> to={AdminRoute::AdminMenu}>{"Admin"}</Link<AdminRoute>></li>
+            <li class={CSS_BREADCRUMB_ITEM_ACTIVE} aria-current="page">{"Helpdesk"}</li>
+            </ol>
+            {do_page_header("Helpdesk")}
+            { alpha_warning_banner() }
+            { self.change_error.as_ref().map(ChangeError::view).unwrap_or_default() }
+            { body }
+          </>
+        }
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            AdminHelpdeskMsg::Start => {
+                let account_id = get_value_from_element_id(ID_HELPDESK_USER)
+                    .unwrap_or_default()
+                    .trim()
+                    .to_string();
+                if account_id.is_empty() {
+                    return false;
+                }
+                self.change_error = None;
+                self.state = ViewState::Waiting;
+                ctx.link().send_future(async move {
+                    let uri = format!("/v1/person/{}/_identify_user", account_id);
+                    let body = serde_json::to_value(IdentifyUserRequest::Start)
+                        .expect_throw("Failed to serialise the request");
+                    match submit_change(&uri, RequestMethod::POST, Some(body)).await {
+                        Ok(value) => match serde_wasm_bindgen::from_value(value)
+                            .expect_throw("Invalid response type - IdentifyUserResponse")
+                        {
+                            IdentifyUserResponse::ProvideCode { step, totp } => {
+                                AdminHelpdeskMsg::ProvideCode {
+                                    account_id,
+                                    step,
+                                    totp,
+                                }
+                            }
+                            _ => AdminHelpdeskMsg::Failed(ChangeError {
+                                emsg: "Unexpected response from the server".to_string(),
+                                kopid: None,
+                            }),
+                        },
+                        Err(e) => AdminHelpdeskMsg::Failed(e),
+                    }
+                });
+            }
+            AdminHelpdeskMsg::ProvideCode {
+                account_id,
+                step,
+                totp,
+            } => {
+                self.state = ViewState::ProvideCode {
+                    account_id,
+                    step,
+                    totp,
+                };
+            }
+            AdminHelpdeskMsg::Reset => {
+                let ViewState::ProvideCode { account_id, .. } = &self.state else {
+                    return false;
+                };
+                let Some(other_totp) = get_value_from_element_id(ID_HELPDESK_CODE)
+                    .and_then(|code| code.trim().parse::<u32>().ok())
+                else {
+                    self.change_error = Some(ChangeError {
+                        emsg: "The code must be a number".to_string(),
+                        kopid: None,
+                    });
+                    return true;
+                };
+                let account_id = account_id.clone();
+                self.change_error = None;
+                ctx.link().send_future(async move {
+                    let uri = format!("/v1/person/{}/_credential/_helpdesk_reset", account_id);
+                    let body = serde_json::to_value(HelpdeskCredentialResetRequest {
+                        other_totp,
+                        ttl: None,
+                    })
+                    .expect_throw("Failed to serialise the request");
+                    match submit_change(&uri, RequestMethod::POST, Some(body)).await {
+                        Ok(value) => AdminHelpdeskMsg::Ready {
+                            account_id,
+                            token: serde_wasm_bindgen::from_value(value)
+                                .expect_throw("Invalid response type - CUIntentToken"),
+                        },
+                        Err(e) => AdminHelpdeskMsg::Failed(e),
+                    }
+                });
+            }
+            AdminHelpdeskMsg::Ready { account_id, token } => {
+                self.state = ViewState::Ready { account_id, token };
+            }
+            AdminHelpdeskMsg::Restart => {
+                self.change_error = None;
+                self.state = ViewState::Init;
+            }
+            AdminHelpdeskMsg::Failed(change_error) => {
+                // A failed reset leaves the code on screen so that the person can read theirs again.
+                if matches!(self.state, ViewState::Waiting) {
+                    self.state = ViewState::Init;
+                }
+                self.change_error = Some(change_error);
+            }
+        }
+        true
+    }
+}
@@ -1,11 +1,12 @@
 use serde::{Deserialize, Serialize};
+use wasm_bindgen::{JsValue, UnwrapThrowExt};
 use yew::{html, Component, Context, Html, Properties};
 use yew_router::prelude::Link;
 
 use crate::components::alpha_warning_banner;
 use crate::constants::{CSS_CARD, CSS_CARD_BODY, CSS_LINK_DARK_STRETCHED, CSS_PAGE_HEADER};
-// use crate::error::FetchError;
 use crate::views::AdminRoute;
+use crate::{do_request, RequestMethod};
 
 #[derive(Eq, PartialEq, Properties)]
 pub struct Props;
@@ -85,6 +86,40 @@ impl Component for AdminMenu {
             </div>
           </div>
 
+          // card for domain and system settings
+          <div class="col">
+            <div class={CSS_CARD}>
+            <Link<AdminRoute> classes={CSS_LINK_DARK_STRETCHED} to={AdminRoute::AdminSystem}>
+            <img src={"/pkg/img/icon-settings.svg"} />
+            </Link<AdminRoute>>
+              <div class={CSS_CARD_BODY}>
+              <h3>
+              <Link<AdminRoute> classes={CSS_LINK_DARK_STRETCHED} to={AdminRoute::AdminSystem}>
+              { "System Settings" }
+              </Link<AdminRoute>>
+              </h3>
+              </div>
+
+            </div>
+          </div>
+
+          // card for the helpdesk
+          <div class="col">
+            <div class={CSS_CARD}>
+            <Link<AdminRoute> classes={CSS_LINK_DARK_STRETCHED} to={AdminRoute::AdminHelpdesk}>
+            <img src={"/pkg/img/icon-person.svg"} />
+            </Link<AdminRoute>>
+              <div class={CSS_CARD_BODY}>
+              <h3>
+              <Link<AdminRoute> classes={CSS_LINK_DARK_STRETCHED} to={AdminRoute::AdminHelpdesk}>
+              { "Helpdesk" }
+              </Link<AdminRoute>>
+              </h3>
+              </div>
+
+            </div>
+          </div>
+
         </div>
         </>
         }
@@ -106,10 +141,26 @@ pub struct Attributes {
     #[serde(skip_serializing_if = "Vec::is_empty", default)]
     pub uuid: Vec<String>,
 
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub legalname: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub mail: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub member: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub memberof: Vec<String>,
+
     #[serde(skip_serializing_if = "Vec::is_empty", default)]
     pub oauth2_rs_name: Vec<String>,
     #[serde(skip_serializing_if = "Vec::is_empty", default)]
     pub oauth2_rs_origin: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub oauth2_rs_scope_map: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub oauth2_rs_sup_scope_map: Vec<String>,
+
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub domain_display_name: Vec<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
@@ -138,3 +189,52 @@ pub struct Entity {
 pub struct GetError {
     pub err: String,
 }
+
+/// The server refused a change made from one of the admin pages.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ChangeError {
+    pub emsg: String,
+    pub kopid: Option<String>,
+}
+
+impl ChangeError {
+    pub fn view(&self) -> Html {
+        let message = match &self.kopid {
+            Some(k) => format!("An error occurred - {} - {}", self.emsg, k),
+            None => format!("An error occurred - {} - No Operation ID", self.emsg),
+        };
+        html! {
+          <div class="alert alert-danger" role="alert">{ message }</div>
+        }
+    }
+}
+
+/// Send a change to one of the /v1 routes, returning the response body when it was accepted.
+pub async fn submit_change(
+    uri: &str,
+    method: RequestMethod,
+    body: Option<serde_json::Value>,
+) -> Result<JsValue, ChangeError> {
+    let body = body.map(|b| {
+        serde_json::to_string(&b)
+            .map(|s| JsValue::from(&s))
+            .expect_throw("Failed to serialise the change")
+    });
+
+    match do_request(uri, method, body).await {
+        Ok((_, 200, value, _)) => Ok(value),
+        Ok((kopid, _, value, _)) => Err(ChangeError {
+            emsg: value.as_string().unwrap_or_default(),
+            kopid,
+        }),
+        Err(fe) => Err(ChangeError {
+            emsg: fe.as_string(),
+            kopid: None,
+        }),
+    }
+}
+
+/// Split a free text field into values, one per line or separated by spaces.
+pub fn split_values(input: &str) -> Vec<String> {
+    input.split_whitespace().map(str::to_string).collect()
+}
@@ -4,10 +4,12 @@ use gloo::console;
 use yew::{html, Component, Context, Html, Properties};
 use yew_router::prelude::Link;
 
-use crate::components::admin_menu::{Entity, EntityType, GetError};
+use crate::components::admin_menu::{
+    split_values, submit_change, ChangeError, Entity, EntityType, GetError,
+};
 use crate::components::alpha_warning_banner;
 use crate::constants::{CSS_BREADCRUMB_ITEM, CSS_BREADCRUMB_ITEM_ACTIVE, CSS_CELL, CSS_TABLE};
-use crate::utils::{do_alert_error, do_page_header};
+use crate::utils::{do_alert_error, do_page_header, get_value_from_element_id};
 use crate::views::AdminRoute;
 use crate::{do_request, RequestMethod};
 
@@ -267,6 +269,32 @@ pub enum AdminViewOAuth2Msg {
         emsg: String,
         kopid: Option<String>,
     },
+    UpdateScopeMap(ScopeMapKind),
+    DeleteScopeMap(ScopeMapKind, String),
+    ChangeFailed(ChangeError),
+}
+
+/// The scope maps of a resource server, and the route that edits each of them.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ScopeMapKind {
+    Scope,
+    Supplementary,
+}
+
+impl ScopeMapKind {
+    fn path(self) -> &'static str {
+        match self {
+            ScopeMapKind::Scope => "_scopemap",
+            ScopeMapKind::Supplementary => "_sup_scopemap",
+        }
+    }
+
+    fn id(self) -> &'static str {
+        match self {
+            ScopeMapKind::Scope => "scopemap",
+            ScopeMapKind::Supplementary => "sup_scopemap",
+        }
+    }
 }
 
 #[derive(PartialEq, Eq, Properties)]
@@ -293,6 +321,7 @@ enum ViewState {
 
 pub struct AdminViewOAuth2 {
     state: ViewState,
+    change_error: Option<ChangeError>,
 }
 
 impl Component for AdminViewOAuth2 {
@@ -311,10 +340,11 @@ impl Component for AdminViewOAuth2 {
         });
         AdminViewOAuth2 {
             state: ViewState::Loading,
+            change_error: None,
         }
     }
 
-    fn view(&self, _ctx: &Context<Self>) -> Html {
+    fn view(&self, ctx: &Context<Self>) -> Html {
         match &self.state {
             ViewState::Loading => {
                 html! {"Waiting on the OAuth2 data to load..."}
@@ -357,11 +387,17 @@ impl Component for AdminViewOAuth2 {
                   </ol>
                   {do_page_header(display_name.as_str())}
                   {alpha_warning_banner()}
+                  { self.change_error.as_ref().map(ChangeError::view).unwrap_or_default() }
 
                   <p>{"UUID: "}{uuid}</p>
                   <p>{description}</p>
                   <p>{"RS Name: "}{oauth2_rs_name}</p>
                   <p>{"Origin: "}{oauth2_rs_origin}</p>
+
+                  <h4>{ "Scope Maps" }</h4>
+                  { self.view_scope_maps(ctx, ScopeMapKind::Scope, &oauth2_object.attrs.oauth2_rs_scope_map) }
+                  <h4>{ "Supplementary Scope Maps" }</h4>
+                  { self.view_scope_maps(ctx, ScopeMapKind::Supplementary, &oauth2_object.attrs.oauth2_rs_sup_scope_map) }
                   </>
                 }
             }
@@ -379,8 +415,36 @@ impl Component for AdminViewOAuth2 {
         }
     }
 
-    fn update(&mut self, _ctx: &Context<Self>, msg: Self::Message) -> bool {
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
         match msg {
+            AdminViewOAuth2Msg::UpdateScopeMap(kind) => {
+                let group = get_value_from_element_id(&format!("{}_group", kind.id()))
+                    .unwrap_or_default()
+                    .trim()
+                    .to_string();
+                let scopes = split_values(
+                    &get_value_from_element_id(&format!("{}_scopes", kind.id()))
+                        .unwrap_or_default(),
+                );
+                if group.is_empty() || scopes.is_empty() {
+                    return false;
+                }
+                self.change(
+                    ctx,
+                    kind,
+                    group,
+                    RequestMethod::POST,
+                    Some(serde_json::json!(scopes)),
+                );
+                return false;
+            }
+            AdminViewOAuth2Msg::DeleteScopeMap(kind, group) => {
+                self.change(ctx, kind, group, RequestMethod::DELETE, None);
+                return false;
+            }
+            AdminViewOAuth2Msg::ChangeFailed(change_error) => {
+                self.change_error = Some(change_error);
+            }
             AdminViewOAuth2Msg::Responded { response } => {
                 // TODO: do we paginate here?
                 /*
@@ -405,6 +469,82 @@ impl Component for AdminViewOAuth2 {
     }
 }
 
+impl AdminViewOAuth2 {
+    fn view_scope_maps(
+        &self,
+        ctx: &Context<Self>,
+        kind: ScopeMapKind,
+        scope_maps: &[String],
+    ) -> Html {
+        let group_id = format!("{}_group", kind.id());
+        let scopes_id = format!("{}_scopes", kind.id());
+        html! {
+          <>
+            <table class={CSS_TABLE}>
+              <tbody>
+              {
+                scope_maps.iter().map(|scope_map| {
+                    // These are displayed as "group: {scopes}"
+                    let group = scope_map
+                        .split_once(": ")
+                        .map(|(group, _)| group.to_string())
+                        .unwrap_or_else(|| scope_map.clone());
+                    html! {
+                      <tr key={scope_map.clone()}>
+                        <td class={CSS_CELL}>{ scope_map }</td>
+                        <td class={CSS_CELL}>
+                          <button type="button" class="btn btn-sm btn-secondary"
+                            onclick={ ctx.link().callback(move |_| AdminViewOAuth2Msg::DeleteScopeMap(kind, group.clone())) }
+                          >{ "Remove" }</button>
+                        </td>
+                      </tr>
+                    }
+                }).collect::<Html>()
+              }
+              </tbody>
+            </table>
+            <form class="row g-2 mb-4" onsubmit={ ctx.link().callback(move |e: yew::SubmitEvent| {
+                e.prevent_default();
+                AdminViewOAuth2Msg::UpdateScopeMap(kind)
+            }) }>
+              <div class="col-md-4">
+                <input type="text" class="form-control" id={group_id} placeholder="Group name" />
+              </div>
+              <div class="col-md-6">
+                <input type="text" class="form-control" id={scopes_id} placeholder="Scopes, separated by spaces" />
+              </div>
+              <div class="col-md-2">
+                <button type="submit" class="btn btn-primary w-100">{ "Set Scopes" }</button>
+              </div>
+            </form>
+          </>
+        }
+    }
+
+    /// Submit a change to a scope map, and reload the resource server once it's done.
+    fn change(
+        &mut self,
+        ctx: &Context<Self>,
+        kind: ScopeMapKind,
+        group: String,
+        method: RequestMethod,
+        body: Option<serde_json::Value>,
+    ) {
+        let rs_name = ctx.props().rs_name.clone();
+        self.change_error = None;
+        ctx.link().send_future(async move {
+            let uri = format!("/v1/oauth2/{}/{}/{}", rs_name, kind.path(), group);
+            match submit_change(&uri, method, body).await {
+                Ok(_) => match get_oauth2_rp(&rs_name).await {
+                    Ok(v) => v,
+                    Err(v) => v.into(),
+                },
+                Err(e) => AdminViewOAuth2Msg::ChangeFailed(e),
+            }
+        });
+    }
+}
+
 pub async fn get_oauth2_rp(rs_name: &str) -> Result<AdminViewOAuth2Msg, GetError> {
     let endpoint = format!("/v1/oauth2/{}", rs_name);
     let (_, _, value, _) = match do_request(&endpoint, RequestMethod::GET, None).await {
@@ -0,0 +1,296 @@
+use gloo::console;
+use wasm_bindgen::JsCast;
+use web_sys::HtmlTextAreaElement;
+use yew::{html, Component, Context, Html, Properties};
+use yew_router::prelude::Link;
+
+use crate::components::admin_menu::{submit_change, ChangeError, Entity, GetError};
+use crate::components::alpha_warning_banner;
+use crate::constants::{CSS_BREADCRUMB_ITEM, CSS_BREADCRUMB_ITEM_ACTIVE};
+use crate::utils::{do_alert_error, do_page_header, document, get_value_from_element_id};
+use crate::views::AdminRoute;
+use crate::{do_request, RequestMethod};
+
+const ID_DOMAIN_DISPLAY_NAME: &str = "domain_display_name";
+const ID_BADLIST_ADD: &str = "badlist_add";
+const ID_BADLIST_REMOVE: &str = "badlist_remove";
+
+impl From<GetError> for AdminSystemMsg {
+    fn from(ge: GetError) -> Self {
+        AdminSystemMsg::Failed {
+            emsg: ge.err,
+            kopid: None,
+        }
+    }
+}
+
+pub enum AdminSystemMsg {
+    /// When the server responds and we need to update the page
+    Responded {
+        domain_display_name: String,
+        badlist: Vec<String>,
+    },
+    Failed {
+        emsg: String,
+        kopid: Option<String>,
+    },
+    SetDomainDisplayName,
+    AddBadlist,
+    RemoveBadlist,
+    ChangeFailed(ChangeError),
+}
+
+enum ViewState {
+    /// waiting for the page to load
+    Loading,
+    /// server has responded
+    Responded {
+        domain_display_name: String,
+        badlist: Vec<String>,
+    },
+    /// failed to pull the details
+    Failed { emsg: String, kopid: Option<String> },
+}
+
+#[derive(PartialEq, Properties, Eq)]
+pub struct AdminSystemProps {}
+
+pub struct AdminSystem {
+    state: ViewState,
+    change_error: Option<ChangeError>,
+}
+
+impl Component for AdminSystem {
+    type Message = AdminSystemMsg;
+    type Properties = AdminSystemProps;
+
+    fn create(ctx: &Context<Self>) -> Self {
+        Self::reload(ctx);
+        AdminSystem {
+            state: ViewState::Loading,
+            change_error: None,
+        }
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let body = match &self.state {
+            ViewState::Loading => html! {"Waiting on the system settings to load..."},
+            ViewState::Responded {
+                domain_display_name,
+                badlist,
+            } => html! {
+              <>
+                <h4>{ "Domain" }</h4>
+                <form class="mb-4" onsubmit={ ctx.link().callback(|e: yew::SubmitEvent| {
+                    e.prevent_default();
+                    AdminSystemMsg::SetDomainDisplayName
+                }) }>
+                  <label for={ID_DOMAIN_DISPLAY_NAME} class="form-label">{ "Display Name" }</label>
+                  <div class="input-group">
+                    <input type="text" class="form-control"
+                      id={ID_DOMAIN_DISPLAY_NAME} value={ domain_display_name.clone() } />
+                    <button type="submit" class="btn btn-primary">{ "Save" }</button>
+                  </div>
+                  <div class="form-text">{ "Shown to users when they sign in and by authenticator applications." }</div>
+                </form>
+
+                <h4>{ "Password Badlist" }</h4>
+                <p>{ format!("There are {} passwords that may not be used.", badlist.len()) }</p>
+                <form class="mb-3" onsubmit={ ctx.link().callback(|e: yew::SubmitEvent| {
+                    e.prevent_default();
+                    AdminSystemMsg::AddBadlist
+                }) }>
+                  <label for={ID_BADLIST_ADD} class="form-label">{ "Add passwords, one per line" }</label>
+                  <textarea class="form-control mb-2" rows="4" id={ID_BADLIST_ADD}></textarea>
+                  <button type="submit" class="btn btn-primary">{ "Add to Badlist" }</button>
+                </form>
+                <form onsubmit={ ctx.link().callback(|e: yew::SubmitEvent| {
+                    e.prevent_default();
+                    AdminSystemMsg::RemoveBadlist
+                }) }>
+                  <label for={ID_BADLIST_REMOVE} class="form-label">{ "Remove passwords, one per line" }</label>
+                  <textarea class="form-control mb-2" rows="4" id={ID_BADLIST_REMOVE}></textarea>
+                  <button type="submit" class="btn btn-danger">{ "Remove from Badlist" }</button>
+                </form>
+              </>
+            },
+            ViewState::Failed { emsg, kopid } => {
+                console::error!("Failed to pull details", format!("{:?}", kopid));
+                do_alert_error("Failed to Query System Settings", Some(emsg))
+            }
+        };
+
+        html! {
+          <>
+            <ol class="breadcrumb">
+            <li class={CSS_BREADCRUMB_ITEM}><Link<AdminRoute> to={AdminRoute::AdminMenu}>{"Admin"}</Link<AdminRoute>></li>
+            <li class={CSS_BREADCRUMB_ITEM_ACTIVE} aria-current="page">{"System Settings"}</li>
+            </ol>
+            {do_page_header("System Settings")}
+            { alpha_warning_banner() }
+            { self.change_error.as_ref().map(ChangeError::view).unwrap_or_default() }
+            { body }
+          </>
+        }
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            AdminSystemMsg::Responded {
+                domain_display_name,
+                badlist,
+            } => {
+                self.state = ViewState::Responded {
+                    domain_display_name,
+                    badlist,
+                };
+            }
+            AdminSystemMsg::Failed { emsg, kopid } => {
+                self.state = ViewState::Failed { emsg, kopid };
+            }
+            AdminSystemMsg::SetDomainDisplayName => {
+                let value = get_value_from_element_id(ID_DOMAIN_DISPLAY_NAME).unwrap_or_default();
+                self.change(
+                    ctx,
+                    "/v1/domain/_attr/domain_display_name",
+                    RequestMethod::PUT,
+                    serde_json::json!([value.trim()]),
+                );
+            }
+            AdminSystemMsg::AddBadlist => {
+                let values = textarea_lines(ID_BADLIST_ADD);
+                if values.is_empty() {
+                    return false;
+                }
+                self.change(
+                    ctx,
+                    "/v1/system/_attr/badlist_password",
+                    RequestMethod::POST,
+                    serde_json::json!(values),
+                );
+            }
+            AdminSystemMsg::RemoveBadlist => {
+                let values = textarea_lines(ID_BADLIST_REMOVE);
+                if values.is_empty() {
+                    return false;
+                }
+                self.change(
+                    ctx,
+                    "/v1/system/_attr/badlist_password",
+                    RequestMethod::DELETE,
+                    serde_json::json!(values),
+                );
+            }
+            AdminSystemMsg::ChangeFailed(change_error) => {
+                // Show what the server currently holds alongside the error.
+                self.change_error = Some(change_error);
+                Self::reload(ctx);
+            }
+        }
+        true
+    }
+}
+
+impl AdminSystem {
+    fn reload(ctx: &Context<Self>) {
+        ctx.link().send_future(async move {
+            match get_system_settings().await {
+                Ok(v) => v,
+                Err(v) => v.into(),
+            }
+        });
+    }
+
+    /// Submit a change to the server, and reload the settings once it's done.
+    fn change(
+        &mut self,
+        ctx: &Context<Self>,
+        uri: &'static str,
+        method: RequestMethod,
+        body: serde_json::Value,
+    ) {
+        self.change_error = None;
+        self.state = ViewState::Loading;
+        ctx.link().send_future(async move {
+            match submit_change(uri, method, Some(body)).await {
+                Ok(_) => match get_system_settings().await {
+                    Ok(v) => v,
+                    Err(v) => v.into(),
+                },
+                Err(e) => AdminSystemMsg::ChangeFailed(e),
+            }
+        });
+    }
+}
+
+fn textarea_lines(id: &str) -> Vec<String> {
+    document()
+        .get_element_by_id(id)
+        .and_then(|element| element.dyn_into::<HtmlTextAreaElement>().ok())
+        .map(|element| {
+            element
+                .value()
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// pull the domain and system configuration
+pub async fn get_system_settings() -> Result<AdminSystemMsg, GetError> {
+    let (_, _, value, _) = match do_request("/v1/domain", RequestMethod::GET, None).await {
+        Ok(val) => val,
+        Err(error) => {
+            return Err(GetError {
+                err: format!("{:?}", error),
+            })
+        }
+    };
+
+    let domain: Vec<Entity> = match serde_wasm_bindgen::from_value(value) {
+        Ok(value) => value,
+        Err(error) => {
+            return Err(GetError {
+                err: format!("Failed to grab the domain data into JSON: {:?}", error),
+            });
+        }
+    };
+
+    let domain_display_name = domain
+        .first()
+        .and_then(|entity| entity.attrs.domain_display_name.first())
+        .cloned()
+        .unwrap_or_default();
+
+    let (_, _, value, _) = match do_request(
+        "/v1/system/_attr/badlist_password",
+        RequestMethod::GET,
+        None,
+    )
+    .await
+    {
+        Ok(val) => val,
+        Err(error) => {
+            return Err(GetError {
+                err: format!("{:?}", error),
+            })
+        }
+    };
+
+    let badlist: Option<Vec<String>> = match serde_wasm_bindgen::from_value(value) {
+        Ok(value) => value,
+        Err(error) => {
+            return Err(GetError {
+                err: format!("Failed to grab the badlist data into JSON: {:?}", error),
+            });
+        }
+    };
+
+    Ok(AdminSystemMsg::Responded {
+        domain_display_name,
+        badlist: badlist.unwrap_or_default(),
+    })
+}
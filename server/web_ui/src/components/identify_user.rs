@@ -0,0 +1,162 @@
+use kanidm_proto::internal::{IdentifyUserRequest, IdentifyUserResponse};
+use wasm_bindgen::UnwrapThrowExt;
+use yew::prelude::*;
+
+use crate::components::admin_menu::{submit_change, ChangeError};
+use crate::utils::get_value_from_element_id;
+use crate::RequestMethod;
+
+const ID_IDENTIFY_USER: &str = "identify_user";
+const ID_IDENTIFY_CODE: &str = "identify_code";
+
+pub enum Msg {
+    Submit,
+    Success {
+        account_id: String,
+        step: u32,
+        totp: u32,
+    },
+    CodeFailure {
+        account_id: String,
+    },
+    Error(ChangeError),
+}
+
+enum State {
+    Init,
+    Waiting,
+    /// The other person is who they say they are, and we can read our code back to them.
+    Success {
+        account_id: String,
+        step: u32,
+        totp: u32,
+    },
+    CodeFailure {
+        account_id: String,
+    },
+    Error(ChangeError),
+}
+
+#[derive(PartialEq, Eq, Properties)]
+pub struct Props {}
+
+/// Allows a person to confirm they are talking to the helpdesk, by entering the code the
+/// helpdesk read to them. In return they are given a code to read back to the helpdesk.
+pub struct IdentifyUser {
+    state: State,
+}
+
+impl Component for IdentifyUser {
+    type Message = Msg;
+    type Properties = Props;
+
+    fn create(_ctx: &Context<Self>) -> Self {
+        IdentifyUser { state: State::Init }
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            Msg::Submit => {
+                let account_id = get_value_from_element_id(ID_IDENTIFY_USER)
+                    .unwrap_or_default()
+                    .trim()
+                    .to_string();
+                let other_totp = get_value_from_element_id(ID_IDENTIFY_CODE)
+                    .and_then(|code| code.trim().parse::<u32>().ok());
+                let (false, Some(other_totp)) = (account_id.is_empty(), other_totp) else {
+                    self.state = State::Error(ChangeError {
+                        emsg: "A username and a numeric code are required".to_string(),
+                        kopid: None,
+                    });
+                    return true;
+                };
+
+                self.state = State::Waiting;
+                ctx.link().send_future(async move {
+                    let uri = format!("/v1/person/{}/_identify_user", account_id);
+                    let body = serde_json::to_value(IdentifyUserRequest::SubmitCode { other_totp })
+                        .expect_throw("Failed to serialise the request");
+                    match submit_change(&uri, RequestMethod::POST, Some(body)).await {
+                        Ok(value) => match serde_wasm_bindgen::from_value(value)
+                            .expect_throw("Invalid response type - IdentifyUserResponse")
+                        {
+                            IdentifyUserResponse::Success { step, totp } => Msg::Success {
+                                account_id,
+                                step,
+                                totp,
+                            },
+                            _ => Msg::CodeFailure { account_id },
+                        },
+                        Err(e) => Msg::Error(e),
+                    }
+                });
+            }
+            Msg::Success {
+                account_id,
+                step,
+                totp,
+            } => {
+                self.state = State::Success {
+                    account_id,
+                    step,
+                    totp,
+                };
+            }
+            Msg::CodeFailure { account_id } => {
+                self.state = State::CodeFailure { account_id };
+            }
+            Msg::Error(e) => {
+                self.state = State::Error(e);
+            }
+        }
+        true
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let result = match &self.state {
+            State::Init => html! {},
+            State::Waiting => html! {
+              <div class="spinner-border text-dark" role="status">
+                <span class="visually-hidden">{ "Loading..." }</span>
+              </div>
+            },
+            State::Success {
+                account_id,
+                step,
+                totp,
+            } => html! {
+              <div class="alert alert-success" role="alert">
+                <p>{ format!("The code is correct, you are talking to {}.", account_id) }</p>
+                <p>{ format!("Read this code back to them. It is valid for {} seconds.", step) }</p>
+                <p class="fs-2 font-monospace mb-0">{ format!("{:06}", totp) }</p>
+              </div>
+            },
+            State::CodeFailure { account_id } => html! {
+              <div class="alert alert-warning" role="alert">
+                { format!("The code is incorrect or has expired. You may not be talking to {}, do not share any codes with them.", account_id) }
+              </div>
+            },
+            State::Error(e) => e.view(),
+        };
+
+        html! {
+          <>
+            <h4>{ "Verify the Helpdesk" }</h4>
+            <p>{ "If the helpdesk has read you a code, enter it here to confirm who you are talking to." }</p>
+            <form class="mb-3" onsubmit={ ctx.link().callback(|e: SubmitEvent| {
+                e.prevent_default();
+                Msg::Submit
+            }) }>
+              <div class="input-group">
+                <input type="text" class="form-control" id={ID_IDENTIFY_USER}
+                  placeholder="Their username" required=true />
+                <input type="text" class="form-control" id={ID_IDENTIFY_CODE}
+                  placeholder="Their code" inputmode="numeric" autocomplete="off" required=true />
+                <button type="submit" class="btn btn-primary">{ "Verify" }</button>
+              </div>
+            </form>
+            { result }
+          </>
+        }
+    }
+}
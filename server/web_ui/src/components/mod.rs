@@ -3,10 +3,13 @@ use yew::Html;
 
 pub mod admin_accounts;
 pub mod admin_groups;
+pub mod admin_helpdesk;
 pub mod admin_menu;
 pub mod admin_oauth2;
+pub mod admin_system;
 pub mod change_unix_password;
 pub mod create_reset_code;
+pub mod identify_user;
 
 /// creates the "Kanidm is alpha" banner
 pub fn alpha_warning_banner() -> Html {
@@ -30,6 +30,7 @@ mod login;
 mod manager;
 mod models;
 mod oauth2;
+mod oauth2_logout;
 mod utils;
 mod views;
 
@@ -49,6 +50,7 @@ pub enum RequestMethod {
     GET,
     POST,
     PUT,
+    DELETE,
 }
 
 impl ToString for RequestMethod {
@@ -57,6 +59,7 @@ impl ToString for RequestMethod {
             RequestMethod::PUT => "PUT".to_string(),
             RequestMethod::POST => "POST".to_string(),
             RequestMethod::GET => "GET".to_string(),
+            RequestMethod::DELETE => "DELETE".to_string(),
         }
     }
 }
@@ -14,6 +14,7 @@ use yew_router::prelude::*;
 use crate::credential::reset::CredentialResetApp;
 use crate::login::{LoginApp, LoginWorkflow};
 use crate::oauth2::Oauth2App;
+use crate::oauth2_logout::Oauth2LogoutApp;
 use crate::views::{ViewRoute, ViewsApp};
 
 // router to decide on state.
@@ -31,6 +32,9 @@ pub enum Route {
     #[at("/ui/oauth2")]
     Oauth2,
 
+    #[at("/ui/oauth2/logout")]
+    Oauth2Logout,
+
     #[at("/ui/reset")]
     CredentialReset,
 
@@ -68,6 +72,8 @@ fn switch(route: Route) -> Html {
         #[allow(clippy::let_unit_value)]
         Route::Oauth2 => html! { <Oauth2App /> },
         #[allow(clippy::let_unit_value)]
+        Route::Oauth2Logout => html! { <Oauth2LogoutApp /> },
+        #[allow(clippy::let_unit_value)]
         Route::Views => html! { <ViewsApp /> },
         #[allow(clippy::let_unit_value)]
         Route::CredentialReset => html! { <CredentialResetApp /> },
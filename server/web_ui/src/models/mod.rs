@@ -3,7 +3,7 @@ use gloo::console;
 use gloo::storage::{
     LocalStorage as PersistentStorage, SessionStorage as TemporaryStorage, Storage,
 };
-use kanidm_proto::oauth2::AuthorisationRequest;
+use kanidm_proto::oauth2::AuthorisationRequestRef;
 use kanidm_proto::v1::{CUSessionToken, CUStatus};
 use serde::{Deserialize, Serialize};
 use wasm_bindgen::UnwrapThrowExt;
@@ -72,13 +72,14 @@ pub fn pop_return_location() -> Location {
     l.unwrap_or(Location::Manager(Route::Landing))
 }
 
-pub fn push_oauth2_authorisation_request(r: AuthorisationRequest) {
+pub fn push_oauth2_authorisation_request(r: AuthorisationRequestRef) {
     TemporaryStorage::set("oauth2_authorisation_request", r)
         .expect_throw("failed to set oauth2_authorisation_request in temporary storage");
 }
 
-pub fn pop_oauth2_authorisation_request() -> Option<AuthorisationRequest> {
-    let l: Result<AuthorisationRequest, _> = TemporaryStorage::get("oauth2_authorisation_request");
+pub fn pop_oauth2_authorisation_request() -> Option<AuthorisationRequestRef> {
+    let l: Result<AuthorisationRequestRef, _> =
+        TemporaryStorage::get("oauth2_authorisation_request");
     #[cfg(debug_assertions)]
     console::debug!(format!("oauth2_authorisation_request -> {:?}", l).as_str());
     TemporaryStorage::delete("oauth2_authorisation_request");
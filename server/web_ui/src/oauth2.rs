@@ -1,7 +1,7 @@
 use gloo::console;
 use kanidm_proto::constants::APPLICATION_JSON;
 pub use kanidm_proto::oauth2::{
-    AccessTokenRequest, AccessTokenResponse, AuthorisationRequest, AuthorisationResponse,
+    AccessTokenRequest, AccessTokenResponse, AuthorisationRequestRef, AuthorisationResponse,
     CodeChallengeMethod, ErrorResponse,
 };
 use wasm_bindgen::{JsCast, JsValue, UnwrapThrowExt};
@@ -84,7 +84,7 @@ impl Oauth2App {
         }
     }
 
-    async fn fetch_authreq(authreq: AuthorisationRequest) -> Result<Oauth2Msg, FetchError> {
+    async fn fetch_authreq(authreq: AuthorisationRequestRef) -> Result<Oauth2Msg, FetchError> {
         let authreq_jsvalue = serde_json::to_string(&authreq)
             .map(|s| JsValue::from(&s))
             .expect_throw("Failed to serialise authreq");
@@ -199,7 +199,7 @@ impl Component for Oauth2App {
             .location()
             .expect_throw("Can't access browser current location");
 
-        let query: Option<AuthorisationRequest> = location
+        let query: Option<AuthorisationRequestRef> = location
             .query()
             .map_err(|e| {
                 let e_msg = format!(
@@ -231,8 +231,12 @@ impl Component for Oauth2App {
         // as to the users name.
         // See: https://openid.net/specs/openid-connect-basic-1_0.html#RequestParameters
         // specifically, login_hint
-        if let Some(login_hint) = query.oidc_ext.login_hint.clone() {
-            models::push_login_hint(login_hint)
+        // This is only available when the request parameters were sent directly, as pushed
+        // and signed requests are opaque to us here.
+        if let AuthorisationRequestRef::Direct(auth_req) = &query {
+            if let Some(login_hint) = auth_req.oidc_ext.login_hint.clone() {
+                models::push_login_hint(login_hint)
+            }
         }
         // Push the request down. This covers if we move to LoginRequired so we can restore where
         // we were / what we were doing.
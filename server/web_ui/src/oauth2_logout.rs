@@ -0,0 +1,249 @@
+//! OpenID Connect RP-initiated logout. Relying parties send the user agent here (our
+//! advertised end_session_endpoint) so that we can end the users session and then return
+//! them to the relying party if it asked us to.
+
+use gloo::console;
+pub use kanidm_proto::oauth2::{EndSessionRequest, EndSessionResponse};
+use wasm_bindgen::{JsValue, UnwrapThrowExt};
+use yew::prelude::*;
+use yew_router::prelude::*;
+
+use crate::{do_request, error::*, RequestMethod};
+use crate::{models, utils};
+
+enum State {
+    // Submitting the end session request to the server.
+    Processing,
+    // The server could not confirm the request came from this user, so ask them.
+    Confirm(EndSessionResponse),
+    LoggedOut,
+    ErrInvalidRequest,
+}
+
+pub struct Oauth2LogoutApp {
+    state: State,
+}
+
+pub enum Oauth2LogoutMsg {
+    Response(EndSessionResponse),
+    Confirmed,
+    LogoutComplete(Option<String>),
+    Error { emsg: String, kopid: Option<String> },
+}
+
+impl From<FetchError> for Oauth2LogoutMsg {
+    fn from(fe: FetchError) -> Self {
+        Oauth2LogoutMsg::Error {
+            emsg: fe.as_string(),
+            kopid: None,
+        }
+    }
+}
+
+impl Oauth2LogoutApp {
+    async fn fetch_end_session(req: EndSessionRequest) -> Result<Oauth2LogoutMsg, FetchError> {
+        let req_jsvalue = serde_json::to_string(&req)
+            .map(|s| JsValue::from(&s))
+            .expect_throw("Failed to serialise end session request");
+
+        let (kopid, status, value, _) = do_request(
+            "/oauth2/end_session",
+            RequestMethod::POST,
+            Some(req_jsvalue),
+        )
+        .await?;
+
+        if status == 200 {
+            let resp: EndSessionResponse = serde_wasm_bindgen::from_value(value)
+                .map_err(|e| {
+                    let e_msg = format!("serde error -> {:?}", e);
+                    console::error!(e_msg.as_str());
+                })
+                .expect_throw("Invalid response type");
+            Ok(Oauth2LogoutMsg::Response(resp))
+        } else {
+            let emsg = value.as_string().unwrap_or_default();
+            Ok(Oauth2LogoutMsg::Error { emsg, kopid })
+        }
+    }
+
+    async fn fetch_logout(redirect_uri: Option<String>) -> Result<Oauth2LogoutMsg, FetchError> {
+        let (kopid, status, value, _) = do_request("/v1/logout", RequestMethod::GET, None).await?;
+
+        // As with the main logout, always clear the local token.
+        models::clear_bearer_token();
+
+        if status == 200 {
+            Ok(Oauth2LogoutMsg::LogoutComplete(redirect_uri))
+        } else {
+            let emsg = value.as_string().unwrap_or_default();
+            Ok(Oauth2LogoutMsg::Error { emsg, kopid })
+        }
+    }
+
+    fn redirect(&mut self, redirect_uri: Option<String>) -> bool {
+        let Some(loc) = redirect_uri else {
+            self.state = State::LoggedOut;
+            return true;
+        };
+
+        #[cfg(debug_assertions)]
+        console::debug!(format!("Redirecting to {}", loc).as_str());
+
+        match utils::window().location().replace(loc.as_str()) {
+            // No need to redraw, we are leaving.
+            Ok(_) => false,
+            Err(e) => {
+                console::error!(format!("{:?}", e).as_str());
+                self.state = State::ErrInvalidRequest;
+                true
+            }
+        }
+    }
+}
+
+impl Component for Oauth2LogoutApp {
+    type Message = Oauth2LogoutMsg;
+    type Properties = ();
+
+    fn create(ctx: &Context<Self>) -> Self {
+        #[cfg(debug_assertions)]
+        console::debug!("oauth2_logout::create");
+
+        let location = ctx
+            .link()
+            .location()
+            .expect_throw("Can't access browser current location");
+
+        // All of the parameters are optional, so an empty query is still a valid request.
+        let query: EndSessionRequest = location
+            .query()
+            .map_err(|e| {
+                let e_msg = format!("failed to decode end session url parameters -> {:?}", e);
+                console::error!(e_msg.as_str());
+            })
+            .unwrap_or_default();
+
+        add_body_form_classes!();
+
+        ctx.link().send_future(async {
+            match Self::fetch_end_session(query).await {
+                Ok(v) => v,
+                Err(v) => v.into(),
+            }
+        });
+
+        Oauth2LogoutApp {
+            state: State::Processing,
+        }
+    }
+
+    fn changed(&mut self, _ctx: &Context<Self>, _props: &Self::Properties) -> bool {
+        false
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        #[cfg(debug_assertions)]
+        console::debug!("oauth2_logout::update");
+
+        match msg {
+            Oauth2LogoutMsg::Response(resp) => {
+                if resp.logged_out {
+                    models::clear_bearer_token();
+                    self.redirect(resp.redirect_uri.map(|u| u.to_string()))
+                } else if models::get_bearer_token().is_some() {
+                    self.state = State::Confirm(resp);
+                    true
+                } else {
+                    // There is no session here to end.
+                    self.redirect(resp.redirect_uri.map(|u| u.to_string()))
+                }
+            }
+            Oauth2LogoutMsg::Confirmed => {
+                let redirect_uri = match &self.state {
+                    State::Confirm(resp) => resp.redirect_uri.as_ref().map(|u| u.to_string()),
+                    _ => None,
+                };
+                ctx.link().send_future(async {
+                    match Self::fetch_logout(redirect_uri).await {
+                        Ok(v) => v,
+                        Err(v) => v.into(),
+                    }
+                });
+                self.state = State::Processing;
+                true
+            }
+            Oauth2LogoutMsg::LogoutComplete(redirect_uri) => self.redirect(redirect_uri),
+            Oauth2LogoutMsg::Error { emsg, kopid } => {
+                self.state = State::ErrInvalidRequest;
+                console::error!(format!("{:?}", kopid).as_str());
+                console::error!(emsg.as_str());
+                true
+            }
+        }
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let body_content = match &self.state {
+            State::Processing => {
+                html! {
+                    <div class="alert alert-light" role="alert">
+                        <h2 class="text-center">{ "Processing ... " }</h2>
+                    </div>
+                }
+            }
+            State::Confirm(_) => {
+                html! {
+                    <form
+                      onsubmit={ ctx.link().callback(|e: SubmitEvent| {
+                          e.prevent_default();
+                          Oauth2LogoutMsg::Confirmed
+                      } ) }
+                      action="javascript:void(0);"
+                    >
+                      <h2 class="h3 mb-3 fw-normal">{ "Sign out?" }</h2>
+                      <p>{ "An application has asked to sign you out of Kanidm." }</p>
+                      <div class="text-center">
+                        <button autofocus=true class="w-100 btn btn-lg btn-primary" type="submit">{ "Sign out" }</button>
+                      </div>
+                    </form>
+                }
+            }
+            State::LoggedOut => {
+                html! {
+                    <div class="alert alert-success" role="alert">
+                        <h2 class="text-center">{ "You have been signed out" }</h2>
+                    </div>
+                }
+            }
+            State::ErrInvalidRequest => {
+                html! {
+                    <div class="alert alert-danger" role="alert">
+                        <h1>{ "Invalid request" } </h1>
+                        <p>
+                        { "Please close this window and try again again from the beginning." }
+                        </p>
+                    </div>
+                }
+            }
+        };
+        html! {
+        <>
+            <main class="form-signin">
+            <center>
+                <img src="/pkg/img/logo-square.svg" alt="Kanidm" class="kanidm_logo"/>
+            </center>
+            <div class="container">
+            { body_content }
+            </div>
+            </main>
+            { crate::utils::do_footer() }
+        </>
+        }
+    }
+
+    fn destroy(&mut self, _ctx: &Context<Self>) {
+        console::debug!("oauth2_logout::destroy");
+        remove_body_form_classes!();
+    }
+}
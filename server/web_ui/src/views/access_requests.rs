@@ -0,0 +1,266 @@
+#[cfg(debug_assertions)]
+use gloo::console;
+use kanidm_proto::v1::{AccessRequest, AccessRequestState};
+use time::format_description::well_known::Rfc3339;
+use wasm_bindgen::UnwrapThrowExt;
+use yew::prelude::*;
+use yew_router::prelude::*;
+
+use crate::constants::CSS_PAGE_HEADER;
+use crate::error::FetchError;
+use crate::manager::Route;
+use crate::models;
+use crate::views::{ViewProps, ViewRoute};
+use crate::{do_request, RequestMethod};
+
+pub enum Msg {
+    Ready { requests: Vec<AccessRequest> },
+    Approve(String),
+    Deny(String),
+    Withdraw(String),
+    RequestReauth,
+    Error { emsg: String, kopid: Option<String> },
+}
+
+impl From<FetchError> for Msg {
+    fn from(fe: FetchError) -> Self {
+        Msg::Error {
+            emsg: fe.as_string(),
+            kopid: None,
+        }
+    }
+}
+
+pub enum State {
+    Waiting,
+    Ready { requests: Vec<AccessRequest> },
+    Error { emsg: String, kopid: Option<String> },
+}
+
+pub struct AccessRequestsApp {
+    state: State,
+}
+
+impl Component for AccessRequestsApp {
+    type Message = Msg;
+    type Properties = ViewProps;
+
+    fn create(ctx: &Context<Self>) -> Self {
+        #[cfg(debug_assertions)]
+        console::debug!("views::access_requests::create");
+
+        Self::fetch(ctx);
+
+        AccessRequestsApp {
+            state: State::Waiting,
+        }
+    }
+
+    fn changed(&mut self, _ctx: &Context<Self>, _props: &Self::Properties) -> bool {
+        #[cfg(debug_assertions)]
+        console::debug!("views::access_requests::changed");
+        true
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        #[cfg(debug_assertions)]
+        console::debug!("views::access_requests::update");
+        match msg {
+            Msg::Ready { requests } => {
+                self.state = State::Ready { requests };
+                true
+            }
+            Msg::Approve(id) => {
+                Self::submit(ctx, format!("/v1/access_request/{}/_approve", id));
+                self.state = State::Waiting;
+                true
+            }
+            Msg::Deny(id) => {
+                Self::submit(ctx, format!("/v1/access_request/{}/_deny", id));
+                self.state = State::Waiting;
+                true
+            }
+            Msg::Withdraw(id) => {
+                Self::submit(ctx, format!("/v1/access_request/{}/_withdraw", id));
+                self.state = State::Waiting;
+                true
+            }
+            Msg::RequestReauth => {
+                models::push_return_location(models::Location::Views(ViewRoute::AccessRequests));
+
+                let uat = &ctx.props().current_user_uat;
+                models::push_login_hint(uat.spn.to_string());
+
+                ctx.link()
+                    .navigator()
+                    .expect_throw("failed to read history")
+                    .push(&Route::Reauth);
+
+                // No need to redraw, since this redirect will destroy the state.
+                false
+            }
+            Msg::Error { emsg, kopid } => {
+                self.state = State::Error { emsg, kopid };
+                true
+            }
+        }
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let body = match &self.state {
+            State::Waiting => html! {
+              <div class="vert-center">
+                <div class="spinner-border text-dark" role="status">
+                  <span class="visually-hidden">{ "Loading..." }</span>
+                </div>
+              </div>
+            },
+            State::Ready { requests } => self.view_ready(ctx, requests),
+            State::Error { emsg, kopid } => {
+                let message = match kopid {
+                    Some(k) => format!("An error occurred - {} - {}", emsg, k),
+                    None => format!("An error occurred - {} - No Operation ID", emsg),
+                };
+                html! {
+                  <div class="alert alert-danger" role="alert">{ message }</div>
+                }
+            }
+        };
+
+        html! {
+            <>
+              <div class={CSS_PAGE_HEADER}>
+                <h2>{ "Access Requests" }</h2>
+              </div>
+              { body }
+            </>
+        }
+    }
+}
+
+impl AccessRequestsApp {
+    fn view_ready(&self, ctx: &Context<Self>, requests: &[AccessRequest]) -> Html {
+        let uat = &ctx.props().current_user_uat;
+
+        let jsdate = js_sys::Date::new_0();
+        let isotime: String = jsdate.to_iso_string().into();
+        let time = time::OffsetDateTime::parse(&isotime, &Rfc3339)
+            .map(|odt| odt + time::Duration::new(60, 0))
+            .expect_throw("Unable to process time stamp");
+        // Approving or denying requests needs a privileged session.
+        let is_priv_able = uat.purpose_readwrite_active(time);
+
+        if requests.is_empty() {
+            return html! {
+              <div>
+                <h5>{ "No access requests" }</h5>
+              </div>
+            };
+        }
+
+        html! {
+          <table class="table table-striped">
+            <thead>
+              <tr>
+                <th scope="col">{ "Requester" }</th>
+                <th scope="col">{ "Group" }</th>
+                <th scope="col">{ "Reason" }</th>
+                <th scope="col">{ "Duration" }</th>
+                <th scope="col">{ "State" }</th>
+                <th scope="col"></th>
+              </tr>
+            </thead>
+            <tbody>
+            {
+              requests.iter().map(|ar| {
+                let id = ar.uuid.to_string();
+                let is_own = ar.requester.as_deref() == Some(uat.spn.as_str());
+
+                let actions = match ar.state {
+                    AccessRequestState::Pending if is_own => {
+                        html! {
+                          <button type="button" class="btn btn-secondary"
+                            onclick={ ctx.link().callback(move |_| Msg::Withdraw(id.clone())) }
+                          >{ "Withdraw" }</button>
+                        }
+                    }
+                    AccessRequestState::Pending if is_priv_able => {
+                        let approve_id = id.clone();
+                        let deny_id = id;
+                        html! {
+                          <>
+                            <button type="button" class="btn btn-success me-2"
+                              onclick={ ctx.link().callback(move |_| Msg::Approve(approve_id.clone())) }
+                            >{ "Approve" }</button>
+                            <button type="button" class="btn btn-danger"
+                              onclick={ ctx.link().callback(move |_| Msg::Deny(deny_id.clone())) }
+                            >{ "Deny" }</button>
+                          </>
+                        }
+                    }
+                    AccessRequestState::Pending => html! {
+                      <button type="button" class="btn btn-primary"
+                        onclick={ ctx.link().callback(|_| Msg::RequestReauth) }
+                      >{ "Unlock to decide 🔒" }</button>
+                    },
+                    _ => html! { <>{ ar.expiry.as_deref().map(|e| format!("Ends {}", e)).unwrap_or_default() }</> },
+                };
+
+                html! {
+                  <tr>
+                    <td>{ ar.requester.as_deref().unwrap_or("-") }</td>
+                    <td>{ ar.group.as_deref().unwrap_or("-") }</td>
+                    <td>{ ar.reason.as_deref().unwrap_or("") }</td>
+                    <td>{ format!("{}s", ar.duration) }</td>
+                    <td>{ ar.state.to_string() }</td>
+                    <td>{ actions }</td>
+                  </tr>
+                }
+              }).collect::<Html>()
+            }
+            </tbody>
+          </table>
+        }
+    }
+
+    fn fetch(ctx: &Context<Self>) {
+        ctx.link().send_future(async {
+            match Self::fetch_access_requests().await {
+                Ok(v) => v,
+                Err(v) => v.into(),
+            }
+        });
+    }
+
+    /// Send a state change for a request, and then reload the list of requests.
+    fn submit(ctx: &Context<Self>, uri: String) {
+        ctx.link().send_future(async move {
+            let res = match do_request(&uri, RequestMethod::POST, None).await {
+                Ok((_, 200, _, _)) => Self::fetch_access_requests().await,
+                Ok((kopid, _, value, _)) => Ok(Msg::Error {
+                    emsg: value.as_string().unwrap_or_default(),
+                    kopid,
+                }),
+                Err(e) => Err(e),
+            };
+            match res {
+                Ok(v) => v,
+                Err(v) => v.into(),
+            }
+        });
+    }
+
+    async fn fetch_access_requests() -> Result<Msg, FetchError> {
+        let (kopid, status, value, _) =
+            do_request("/v1/access_request", RequestMethod::GET, None).await?;
+
+        if status == 200 {
+            let requests: Vec<AccessRequest> = serde_wasm_bindgen::from_value(value)
+                .expect_throw("Invalid response type - Vec<AccessRequest>");
+            Ok(Msg::Ready { requests })
+        } else {
+            let emsg = value.as_string().unwrap_or_default();
+            Ok(Msg::Error { emsg, kopid })
+        }
+    }
+}
@@ -5,16 +5,22 @@ use wasm_bindgen::UnwrapThrowExt;
 use yew::prelude::*;
 use yew_router::prelude::*;
 
-use crate::components::{admin_accounts, admin_groups, admin_menu, admin_oauth2};
+use crate::components::{
+    admin_accounts, admin_groups, admin_helpdesk, admin_menu, admin_oauth2, admin_system,
+};
 use crate::manager::Route;
 use crate::models;
 use crate::{do_request, error::*, RequestMethod};
 
+mod access_requests;
 mod apps;
 mod profile;
+mod sessions;
 
+use access_requests::AccessRequestsApp;
 use apps::AppsApp;
 use profile::ProfileApp;
+use sessions::SessionsApp;
 
 #[derive(Routable, PartialEq, Eq, Clone, Debug, Serialize, Deserialize)]
 pub enum ViewRoute {
@@ -27,6 +33,12 @@ pub enum ViewRoute {
     #[at("/ui/profile")]
     Profile,
 
+    #[at("/ui/sessions")]
+    Sessions,
+
+    #[at("/ui/access_requests")]
+    AccessRequests,
+
     #[not_found]
     #[at("/ui/404")]
     NotFound,
@@ -43,6 +55,10 @@ pub enum AdminRoute {
     AdminListAccounts,
     #[at("/ui/admin/oauth2")]
     AdminListOAuth2,
+    #[at("/ui/admin/system")]
+    AdminSystem,
+    #[at("/ui/admin/helpdesk")]
+    AdminHelpdesk,
 
     #[at("/ui/admin/group/:uuid")]
     ViewGroup { uuid: String },
@@ -255,6 +271,20 @@ impl ViewsApp {
                         </Link<ViewRoute>>
                     </li>
 
+                    <li class="mb-1">
+                        <Link<ViewRoute> classes="nav-link" to={ViewRoute::Sessions}>
+                          <span data-feather="file"></span>
+                          { "Sessions & Devices" }
+                        </Link<ViewRoute>>
+                    </li>
+
+                    <li class="mb-1">
+                        <Link<ViewRoute> classes="nav-link" to={ViewRoute::AccessRequests}>
+                          <span data-feather="file"></span>
+                          { "Access Requests" }
+                        </Link<ViewRoute>>
+                    </li>
+
                     if ui_hint_experimental {
                       <li class="mb-1">
                         <Link<AdminRoute> classes="nav-link" to={AdminRoute::AdminMenu}>
@@ -311,6 +341,8 @@ impl ViewsApp {
                         #[allow(clippy::let_unit_value)]
                         ViewRoute::Apps => html! { <AppsApp /> },
                         ViewRoute::Profile => html! { <ProfileApp current_user_uat={ current_user_uat.clone() } /> },
+                        ViewRoute::Sessions => html! { <SessionsApp current_user_uat={ current_user_uat.clone() } /> },
+                        ViewRoute::AccessRequests => html! { <AccessRequestsApp current_user_uat={ current_user_uat.clone() } /> },
                         ViewRoute::NotFound => html! {
                             <Redirect<Route> to={Route::NotFound}/>
                         },
@@ -386,6 +418,12 @@ fn admin_routes(route: AdminRoute) -> Html {
         AdminRoute::AdminListOAuth2 => html!(
           <admin_oauth2::AdminListOAuth2 />
         ),
+        AdminRoute::AdminSystem => html!(
+          <admin_system::AdminSystem />
+        ),
+        AdminRoute::AdminHelpdesk => html!(
+          <admin_helpdesk::AdminHelpdesk />
+        ),
         AdminRoute::NotFound => html! (
           <Redirect<Route> to={Route::NotFound}/>
         ),
@@ -8,6 +8,7 @@ use yew_router::prelude::*;
 
 use crate::components::change_unix_password::ChangeUnixPassword;
 use crate::components::create_reset_code::CreateResetCode;
+use crate::components::identify_user::IdentifyUser;
 use crate::constants::CSS_PAGE_HEADER;
 use crate::error::*;
 use crate::manager::Route;
@@ -188,6 +189,8 @@ impl Component for ProfileApp {
               </div>
               { flash }
               { main }
+              <hr/>
+              <IdentifyUser />
             </>
         }
     }
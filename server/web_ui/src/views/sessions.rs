@@ -0,0 +1,359 @@
+#[cfg(debug_assertions)]
+use gloo::console;
+use kanidm_proto::internal::Oauth2Grant;
+use kanidm_proto::v1::{UatPurposeStatus, UatStatus};
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+use wasm_bindgen::UnwrapThrowExt;
+use yew::prelude::*;
+use yew_router::prelude::*;
+
+use crate::constants::{CSS_PAGE_HEADER, CSS_TABLE};
+use crate::error::FetchError;
+use crate::manager::Route;
+use crate::models;
+use crate::views::ViewProps;
+use crate::{do_request, RequestMethod};
+
+pub enum Msg {
+    Ready {
+        sessions: Vec<UatStatus>,
+        grants: Vec<Oauth2Grant>,
+    },
+    EndSession(String),
+    RevokeGrant(String),
+    LogoutEverywhere,
+    LogoutComplete,
+    Error {
+        emsg: String,
+        kopid: Option<String>,
+    },
+}
+
+impl From<FetchError> for Msg {
+    fn from(fe: FetchError) -> Self {
+        Msg::Error {
+            emsg: fe.as_string(),
+            kopid: None,
+        }
+    }
+}
+
+pub enum State {
+    Waiting,
+    Ready {
+        sessions: Vec<UatStatus>,
+        grants: Vec<Oauth2Grant>,
+    },
+    Error {
+        emsg: String,
+        kopid: Option<String>,
+    },
+}
+
+pub struct SessionsApp {
+    state: State,
+}
+
+impl Component for SessionsApp {
+    type Message = Msg;
+    type Properties = ViewProps;
+
+    fn create(ctx: &Context<Self>) -> Self {
+        #[cfg(debug_assertions)]
+        console::debug!("views::sessions::create");
+
+        Self::fetch(ctx);
+
+        SessionsApp {
+            state: State::Waiting,
+        }
+    }
+
+    fn changed(&mut self, _ctx: &Context<Self>, _props: &Self::Properties) -> bool {
+        #[cfg(debug_assertions)]
+        console::debug!("views::sessions::changed");
+        true
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        #[cfg(debug_assertions)]
+        console::debug!("views::sessions::update");
+        let id = ctx.props().current_user_uat.uuid.to_string();
+        match msg {
+            Msg::Ready { sessions, grants } => {
+                self.state = State::Ready { sessions, grants };
+                true
+            }
+            Msg::EndSession(session_id) => {
+                Self::submit(
+                    ctx,
+                    format!("/v1/account/{}/_user_auth_token/{}", id, session_id),
+                );
+                self.state = State::Waiting;
+                true
+            }
+            Msg::RevokeGrant(rs_name) => {
+                Self::submit(ctx, format!("/v1/account/{}/_oauth2_grant/{}", id, rs_name));
+                self.state = State::Waiting;
+                true
+            }
+            Msg::LogoutEverywhere => {
+                ctx.link().send_future(async move {
+                    match Self::logout_everywhere(id).await {
+                        Ok(v) => v,
+                        Err(v) => v.into(),
+                    }
+                });
+                self.state = State::Waiting;
+                true
+            }
+            Msg::LogoutComplete => {
+                // Our own session is gone too, so we need to login again.
+                ctx.link()
+                    .navigator()
+                    .expect_throw("failed to read history")
+                    .push(&Route::Login);
+                false
+            }
+            Msg::Error { emsg, kopid } => {
+                self.state = State::Error { emsg, kopid };
+                true
+            }
+        }
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let body = match &self.state {
+            State::Waiting => html! {
+              <div class="vert-center">
+                <div class="spinner-border text-dark" role="status">
+                  <span class="visually-hidden">{ "Loading..." }</span>
+                </div>
+              </div>
+            },
+            State::Ready { sessions, grants } => html! {
+              <>
+                { self.view_sessions(ctx, sessions) }
+                { self.view_grants(ctx, grants) }
+              </>
+            },
+            State::Error { emsg, kopid } => {
+                let message = match kopid {
+                    Some(k) => format!("An error occurred - {} - {}", emsg, k),
+                    None => format!("An error occurred - {} - No Operation ID", emsg),
+                };
+                html! {
+                  <div class="alert alert-danger" role="alert">{ message }</div>
+                }
+            }
+        };
+
+        html! {
+            <>
+              <div class={CSS_PAGE_HEADER}>
+                <h2>{ "Sessions & Devices" }</h2>
+                <button type="button" class="btn btn-danger"
+                  onclick={ ctx.link().callback(|_| Msg::LogoutEverywhere) }
+                >{ "Log out everywhere" }</button>
+              </div>
+              { body }
+            </>
+        }
+    }
+}
+
+impl SessionsApp {
+    fn view_sessions(&self, ctx: &Context<Self>, sessions: &[UatStatus]) -> Html {
+        let current_session_id = ctx.props().current_user_uat.session_id;
+
+        let mut sessions: Vec<_> = sessions.iter().collect();
+        sessions.sort_unstable_by_key(|session| std::cmp::Reverse(session.issued_at));
+
+        html! {
+          <>
+            <h4>{ "Sessions" }</h4>
+            <table class={CSS_TABLE}>
+              <thead>
+                <tr>
+                  <th scope="col">{ "Issued" }</th>
+                  <th scope="col">{ "Expires" }</th>
+                  <th scope="col">{ "Source" }</th>
+                  <th scope="col">{ "Credential" }</th>
+                  <th scope="col">{ "Access" }</th>
+                  <th scope="col"></th>
+                </tr>
+              </thead>
+              <tbody>
+              {
+                sessions.into_iter().map(|session| {
+                  let session_id = session.session_id.to_string();
+
+                  let action = if session.session_id == current_session_id {
+                      html! { <span class="badge bg-success">{ "This session" }</span> }
+                  } else {
+                      html! {
+                        <button type="button" class="btn btn-secondary"
+                          onclick={ ctx.link().callback(move |_| Msg::EndSession(session_id.clone())) }
+                        >{ "End session" }</button>
+                      }
+                  };
+
+                  let access = match session.purpose {
+                      UatPurposeStatus::ReadOnly => "Read only",
+                      UatPurposeStatus::ReadWrite => "Read write",
+                      UatPurposeStatus::PrivilegeCapable => "Privilege capable",
+                  };
+
+                  html! {
+                    <tr>
+                      <td>{ format_time(&session.issued_at) }</td>
+                      <td>{ session.expiry.as_ref().map(format_time).unwrap_or_else(|| "Never".to_string()) }</td>
+                      <td>{ session.label.as_str() }</td>
+                      <td>{ session.credential.as_deref().unwrap_or("Removed credential") }</td>
+                      <td>{ access }</td>
+                      <td>{ action }</td>
+                    </tr>
+                  }
+                }).collect::<Html>()
+              }
+              </tbody>
+            </table>
+          </>
+        }
+    }
+
+    fn view_grants(&self, ctx: &Context<Self>, grants: &[Oauth2Grant]) -> Html {
+        let body = if grants.is_empty() {
+            html! {
+              <p>{ "You have not granted any applications access to your account." }</p>
+            }
+        } else {
+            html! {
+              <table class={CSS_TABLE}>
+                <thead>
+                  <tr>
+                    <th scope="col">{ "Application" }</th>
+                    <th scope="col">{ "Consented Scopes" }</th>
+                    <th scope="col">{ "Active Sessions" }</th>
+                    <th scope="col">{ "Last Signed In" }</th>
+                    <th scope="col"></th>
+                  </tr>
+                </thead>
+                <tbody>
+                {
+                  grants.iter().map(|grant| {
+                    let rs_name = grant.name.clone();
+                    let scopes = grant.scopes.iter().cloned().collect::<Vec<_>>().join(" ");
+
+                    html! {
+                      <tr>
+                        <td>{ grant.display_name.as_str() }</td>
+                        <td>{ scopes }</td>
+                        <td>{ grant.session_count }</td>
+                        <td>{ grant.last_issued_at.as_ref().map(format_time).unwrap_or_else(|| "-".to_string()) }</td>
+                        <td>
+                          <button type="button" class="btn btn-danger"
+                            onclick={ ctx.link().callback(move |_| Msg::RevokeGrant(rs_name.clone())) }
+                          >{ "Revoke" }</button>
+                        </td>
+                      </tr>
+                    }
+                  }).collect::<Html>()
+                }
+                </tbody>
+              </table>
+            }
+        };
+
+        html! {
+          <>
+            <h4>{ "Applications" }</h4>
+            { body }
+          </>
+        }
+    }
+
+    fn fetch(ctx: &Context<Self>) {
+        let id = ctx.props().current_user_uat.uuid.to_string();
+        ctx.link().send_future(async move {
+            match Self::fetch_sessions(id).await {
+                Ok(v) => v,
+                Err(v) => v.into(),
+            }
+        });
+    }
+
+    /// Send a removal, and then reload the sessions and grants.
+    fn submit(ctx: &Context<Self>, uri: String) {
+        let id = ctx.props().current_user_uat.uuid.to_string();
+        ctx.link().send_future(async move {
+            let res = match do_request(&uri, RequestMethod::DELETE, None).await {
+                Ok((_, 200, _, _)) => Self::fetch_sessions(id).await,
+                Ok((kopid, _, value, _)) => Ok(Msg::Error {
+                    emsg: value.as_string().unwrap_or_default(),
+                    kopid,
+                }),
+                Err(e) => Err(e),
+            };
+            match res {
+                Ok(v) => v,
+                Err(v) => v.into(),
+            }
+        });
+    }
+
+    async fn fetch_sessions(id: String) -> Result<Msg, FetchError> {
+        let (kopid, status, value, _) = do_request(
+            &format!("/v1/account/{}/_user_auth_token", id),
+            RequestMethod::GET,
+            None,
+        )
+        .await?;
+
+        if status != 200 {
+            let emsg = value.as_string().unwrap_or_default();
+            return Ok(Msg::Error { emsg, kopid });
+        }
+
+        let sessions: Vec<UatStatus> = serde_wasm_bindgen::from_value(value)
+            .expect_throw("Invalid response type - Vec<UatStatus>");
+
+        let (kopid, status, value, _) = do_request(
+            &format!("/v1/account/{}/_oauth2_grant", id),
+            RequestMethod::GET,
+            None,
+        )
+        .await?;
+
+        if status == 200 {
+            let grants: Vec<Oauth2Grant> = serde_wasm_bindgen::from_value(value)
+                .expect_throw("Invalid response type - Vec<Oauth2Grant>");
+            Ok(Msg::Ready { sessions, grants })
+        } else {
+            let emsg = value.as_string().unwrap_or_default();
+            Ok(Msg::Error { emsg, kopid })
+        }
+    }
+
+    async fn logout_everywhere(id: String) -> Result<Msg, FetchError> {
+        let (kopid, status, value, _) = do_request(
+            &format!("/v1/account/{}/_user_auth_token", id),
+            RequestMethod::DELETE,
+            None,
+        )
+        .await?;
+
+        if status == 200 {
+            models::clear_bearer_token();
+            Ok(Msg::LogoutComplete)
+        } else {
+            let emsg = value.as_string().unwrap_or_default();
+            Ok(Msg::Error { emsg, kopid })
+        }
+    }
+}
+
+fn format_time(odt: &OffsetDateTime) -> String {
+    odt.format(&Rfc3339).unwrap_or_else(|_| odt.to_string())
+}
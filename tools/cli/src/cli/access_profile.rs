@@ -0,0 +1,121 @@
+use kanidm_proto::v1::{AccessProfile, Filter};
+
+use crate::common::OpType;
+use crate::AccessProfileOpt;
+
+fn parse_targetscope(targetscope: &str) -> Option<Filter> {
+    match serde_json::from_str(targetscope) {
+        Ok(f) => Some(f),
+        Err(e) => {
+            error!("Invalid targetscope filter -> {:?}", e);
+            None
+        }
+    }
+}
+
+impl AccessProfileOpt {
+    pub fn debug(&self) -> bool {
+        match self {
+            AccessProfileOpt::List(copt) => copt.debug,
+            AccessProfileOpt::Get(nopt) | AccessProfileOpt::Delete(nopt) => nopt.copt.debug,
+            AccessProfileOpt::Create(copt) => copt.copt.debug,
+            AccessProfileOpt::Update(uopt) => uopt.copt.debug,
+            AccessProfileOpt::Check(copt) => copt.copt.debug,
+        }
+    }
+
+    pub async fn exec(&self) {
+        match self {
+            AccessProfileOpt::List(copt) => {
+                let client = copt.to_client(OpType::Read).await;
+                match client.idm_access_profile_list().await {
+                    Ok(r) => r.iter().for_each(|e| println!("{}", e)),
+                    Err(e) => error!("Error -> {:?}", e),
+                }
+            }
+            AccessProfileOpt::Get(nopt) => {
+                let client = nopt.copt.to_client(OpType::Read).await;
+                match client.idm_access_profile_get(nopt.name.as_str()).await {
+                    Ok(Some(e)) => println!("{}", e),
+                    Ok(None) => println!("No matching entries"),
+                    Err(e) => error!("Error -> {:?}", e),
+                }
+            }
+            AccessProfileOpt::Create(copt) => {
+                let Some(targetscope) = parse_targetscope(copt.targetscope.as_str()) else {
+                    return;
+                };
+                let some_if_any = |v: &Vec<String>| (!v.is_empty()).then(|| v.clone());
+
+                let profile = AccessProfile {
+                    name: Some(copt.name.clone()),
+                    description: copt.description.clone(),
+                    enabled: copt.disabled.then_some(false),
+                    receiver_group: Some(copt.receiver_group.clone()),
+                    targetscope: Some(targetscope),
+                    search_attrs: some_if_any(&copt.search_attr),
+                    modify_present_attrs: some_if_any(&copt.modify_present_attr),
+                    modify_removed_attrs: some_if_any(&copt.modify_removed_attr),
+                    modify_classes: some_if_any(&copt.modify_class),
+                    create_attrs: some_if_any(&copt.create_attr),
+                    create_classes: some_if_any(&copt.create_class),
+                    delete: Some(copt.delete),
+                };
+
+                let client = copt.copt.to_client(OpType::Write).await;
+                match client.idm_access_profile_create(profile).await {
+                    Ok(_) => println!("Successfully created access profile {}", copt.name),
+                    Err(e) => error!("Error -> {:?}", e),
+                }
+            }
+            AccessProfileOpt::Update(uopt) => {
+                let targetscope = match uopt.targetscope.as_deref().map(parse_targetscope) {
+                    Some(None) => return,
+                    Some(f) => f,
+                    None => None,
+                };
+
+                let profile = AccessProfile {
+                    name: None,
+                    description: uopt.description.clone(),
+                    enabled: uopt.enabled,
+                    receiver_group: uopt.receiver_group.clone(),
+                    targetscope,
+                    search_attrs: uopt.search_attr.clone(),
+                    modify_present_attrs: uopt.modify_present_attr.clone(),
+                    modify_removed_attrs: uopt.modify_removed_attr.clone(),
+                    modify_classes: uopt.modify_class.clone(),
+                    create_attrs: uopt.create_attr.clone(),
+                    create_classes: uopt.create_class.clone(),
+                    delete: uopt.delete,
+                };
+
+                let client = uopt.copt.to_client(OpType::Write).await;
+                match client
+                    .idm_access_profile_update(uopt.name.as_str(), profile)
+                    .await
+                {
+                    Ok(_) => println!("Successfully updated access profile {}", uopt.name),
+                    Err(e) => error!("Error -> {:?}", e),
+                }
+            }
+            AccessProfileOpt::Delete(nopt) => {
+                let client = nopt.copt.to_client(OpType::Write).await;
+                match client.idm_access_profile_delete(nopt.name.as_str()).await {
+                    Ok(_) => println!("Successfully deleted access profile {}", nopt.name),
+                    Err(e) => error!("Error -> {:?}", e),
+                }
+            }
+            AccessProfileOpt::Check(copt) => {
+                let client = copt.copt.to_client(OpType::Read).await;
+                match client
+                    .idm_access_profile_check(copt.subject.as_str(), copt.target.as_str())
+                    .await
+                {
+                    Ok(r) => println!("{}", r),
+                    Err(e) => error!("Error -> {:?}", e),
+                }
+            }
+        }
+    }
+}
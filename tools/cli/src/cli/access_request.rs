@@ -0,0 +1,70 @@
+use crate::common::OpType;
+use crate::AccessRequestOpt;
+
+impl AccessRequestOpt {
+    pub fn debug(&self) -> bool {
+        match self {
+            AccessRequestOpt::List(copt) => copt.debug,
+            AccessRequestOpt::Get(iopt)
+            | AccessRequestOpt::Approve(iopt)
+            | AccessRequestOpt::Deny(iopt)
+            | AccessRequestOpt::Withdraw(iopt) => iopt.copt.debug,
+            AccessRequestOpt::Create(copt) => copt.copt.debug,
+        }
+    }
+
+    pub async fn exec(&self) {
+        match self {
+            AccessRequestOpt::List(copt) => {
+                let client = copt.to_client(OpType::Read).await;
+                match client.idm_access_request_list().await {
+                    Ok(r) => r.iter().for_each(|ar| println!("{}", ar)),
+                    Err(e) => error!("Error -> {:?}", e),
+                }
+            }
+            AccessRequestOpt::Get(iopt) => {
+                let client = iopt.copt.to_client(OpType::Read).await;
+                match client.idm_access_request_get(iopt.id.as_str()).await {
+                    Ok(ar) => println!("{}", ar),
+                    Err(e) => error!("Error -> {:?}", e),
+                }
+            }
+            AccessRequestOpt::Create(copt) => {
+                // Requesting access doesn't require privileges, only approving it does.
+                let client = copt.copt.to_client(OpType::Read).await;
+                match client
+                    .idm_access_request_create(
+                        copt.group.as_str(),
+                        copt.reason.as_deref(),
+                        copt.duration,
+                    )
+                    .await
+                {
+                    Ok(uuid) => println!("Successfully created access request {}", uuid),
+                    Err(e) => error!("Error -> {:?}", e),
+                }
+            }
+            AccessRequestOpt::Approve(iopt) => {
+                let client = iopt.copt.to_client(OpType::Write).await;
+                match client.idm_access_request_approve(iopt.id.as_str()).await {
+                    Ok(_) => println!("Successfully approved access request {}", iopt.id),
+                    Err(e) => error!("Error -> {:?}", e),
+                }
+            }
+            AccessRequestOpt::Deny(iopt) => {
+                let client = iopt.copt.to_client(OpType::Write).await;
+                match client.idm_access_request_deny(iopt.id.as_str()).await {
+                    Ok(_) => println!("Successfully denied access request {}", iopt.id),
+                    Err(e) => error!("Error -> {:?}", e),
+                }
+            }
+            AccessRequestOpt::Withdraw(iopt) => {
+                let client = iopt.copt.to_client(OpType::Read).await;
+                match client.idm_access_request_withdraw(iopt.id.as_str()).await {
+                    Ok(_) => println!("Successfully withdrew access request {}", iopt.id),
+                    Err(e) => error!("Error -> {:?}", e),
+                }
+            }
+        }
+    }
+}
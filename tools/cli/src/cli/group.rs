@@ -1,3 +1,5 @@
+use kanidm_proto::v1::Filter;
+
 use crate::common::OpType;
 use crate::{GroupOpt, GroupPosix, OutputMode};
 
@@ -7,12 +9,19 @@ impl GroupOpt {
             GroupOpt::List(copt) => copt.debug,
             GroupOpt::Get(gcopt) => gcopt.copt.debug,
             GroupOpt::Create(gcopt) => gcopt.copt.debug,
+            GroupOpt::CreateDynamic(gcopt) => gcopt.copt.debug,
+            GroupOpt::PreviewDynamic(gcopt) => gcopt.copt.debug,
             GroupOpt::Delete(gcopt) => gcopt.copt.debug,
             GroupOpt::ListMembers(gcopt) => gcopt.copt.debug,
             GroupOpt::AddMembers(gcopt) => gcopt.copt.debug,
             GroupOpt::RemoveMembers(gcopt) => gcopt.copt.debug,
             GroupOpt::SetMembers(gcopt) => gcopt.copt.debug,
             GroupOpt::PurgeMembers(gcopt) => gcopt.copt.debug,
+            GroupOpt::SetEntryManager(gcopt) => gcopt.copt.debug,
+            GroupOpt::PurgeEntryManager(gcopt) => gcopt.copt.debug,
+            GroupOpt::SetAccessRequestApprovers(gcopt) => gcopt.copt.debug,
+            GroupOpt::PurgeAccessRequestApprovers(gcopt) => gcopt.copt.debug,
+            GroupOpt::SetAccessRequestMaxDuration(gcopt) => gcopt.copt.debug,
             GroupOpt::Posix { commands } => match commands {
                 GroupPosix::Show(gcopt) => gcopt.copt.debug,
                 GroupPosix::Set(gcopt) => gcopt.copt.debug,
@@ -64,6 +73,42 @@ impl GroupOpt {
                     Ok(_) => println!("Successfully created group '{}'", gcopt.name.as_str()),
                 }
             }
+            GroupOpt::CreateDynamic(gcopt) => {
+                let filter: Filter = match serde_json::from_str(gcopt.filter.as_str()) {
+                    Ok(f) => f,
+                    Err(e) => {
+                        error!("Invalid filter -> {:?}", e);
+                        return;
+                    }
+                };
+                let client = gcopt.copt.to_client(OpType::Write).await;
+                match client
+                    .idm_group_create_dynamic(gcopt.name.as_str(), &filter)
+                    .await
+                {
+                    Err(err) => {
+                        error!("Error -> {:?}", err)
+                    }
+                    Ok(_) => println!(
+                        "Successfully created dynamic group '{}'",
+                        gcopt.name.as_str()
+                    ),
+                }
+            }
+            GroupOpt::PreviewDynamic(gcopt) => {
+                let filter: Filter = match serde_json::from_str(gcopt.filter.as_str()) {
+                    Ok(f) => f,
+                    Err(e) => {
+                        error!("Invalid filter -> {:?}", e);
+                        return;
+                    }
+                };
+                let client = gcopt.copt.to_client(OpType::Read).await;
+                match client.idm_group_dynamic_preview(&filter).await {
+                    Ok(members) => members.iter().for_each(|m| println!("{}", m)),
+                    Err(e) => error!("Error -> {:?}", e),
+                }
+            }
             GroupOpt::Delete(gcopt) => {
                 let client = gcopt.copt.to_client(OpType::Write).await;
                 match client.idm_group_delete(gcopt.name.as_str()).await {
@@ -81,6 +126,77 @@ impl GroupOpt {
                     ),
                 }
             }
+            GroupOpt::SetEntryManager(gcopt) => {
+                let client = gcopt.copt.to_client(OpType::Write).await;
+                match client
+                    .idm_group_set_entry_managed_by(
+                        gcopt.name.as_str(),
+                        gcopt.entry_managed_by.as_str(),
+                    )
+                    .await
+                {
+                    Err(e) => error!("Error -> {:?}", e),
+                    Ok(_) => println!(
+                        "Successfully set entry manager of group {} to {}",
+                        gcopt.name.as_str(),
+                        gcopt.entry_managed_by.as_str()
+                    ),
+                }
+            }
+            GroupOpt::PurgeEntryManager(gcopt) => {
+                let client = gcopt.copt.to_client(OpType::Write).await;
+                match client
+                    .idm_group_purge_entry_managed_by(gcopt.name.as_str())
+                    .await
+                {
+                    Err(e) => error!("Error -> {:?}", e),
+                    Ok(_) => println!(
+                        "Successfully removed entry manager of group {}",
+                        gcopt.name.as_str()
+                    ),
+                }
+            }
+            GroupOpt::SetAccessRequestApprovers(gcopt) => {
+                let client = gcopt.copt.to_client(OpType::Write).await;
+                let approvers: Vec<&str> = gcopt.approvers.iter().map(|s| s.as_str()).collect();
+                match client
+                    .idm_group_set_access_request_approvers(gcopt.name.as_str(), &approvers)
+                    .await
+                {
+                    Err(e) => error!("Error -> {:?}", e),
+                    Ok(_) => println!(
+                        "Successfully set access request approvers of group {}",
+                        gcopt.name.as_str()
+                    ),
+                }
+            }
+            GroupOpt::PurgeAccessRequestApprovers(gcopt) => {
+                let client = gcopt.copt.to_client(OpType::Write).await;
+                match client
+                    .idm_group_purge_access_request_approvers(gcopt.name.as_str())
+                    .await
+                {
+                    Err(e) => error!("Error -> {:?}", e),
+                    Ok(_) => println!(
+                        "Successfully removed access request approvers of group {}",
+                        gcopt.name.as_str()
+                    ),
+                }
+            }
+            GroupOpt::SetAccessRequestMaxDuration(gcopt) => {
+                let client = gcopt.copt.to_client(OpType::Write).await;
+                match client
+                    .idm_group_set_access_request_max_duration(gcopt.name.as_str(), gcopt.seconds)
+                    .await
+                {
+                    Err(e) => error!("Error -> {:?}", e),
+                    Ok(_) => println!(
+                        "Successfully set access request max duration of group {} to {}s",
+                        gcopt.name.as_str(),
+                        gcopt.seconds
+                    ),
+                }
+            }
             GroupOpt::ListMembers(gcopt) => {
                 let client = gcopt.copt.to_client(OpType::Read).await;
                 match client.idm_group_get_members(gcopt.name.as_str()).await {
@@ -88,11 +204,39 @@ impl GroupOpt {
                     Ok(None) => warn!("No members in group {}", gcopt.name.as_str()),
                     Err(e) => error!("Error -> {:?}", e),
                 }
+                match client
+                    .idm_group_get_member_validity(gcopt.name.as_str())
+                    .await
+                {
+                    Ok(Some(validity)) => {
+                        println!("Time bounded members (member,not before,not after):");
+                        validity.iter().for_each(|v| println!("{:?}", v))
+                    }
+                    Ok(None) => {}
+                    Err(e) => error!("Error -> {:?}", e),
+                }
             }
             GroupOpt::AddMembers(gcopt) => {
                 let client = gcopt.copt.to_client(OpType::Write).await;
                 let new_members: Vec<&str> = gcopt.members.iter().map(String::as_str).collect();
 
+                if gcopt.not_before.is_some() || gcopt.not_after.is_some() {
+                    for member in new_members.iter() {
+                        if let Err(e) = client
+                            .idm_group_set_member_validity(
+                                gcopt.name.as_str(),
+                                member,
+                                gcopt.not_before.as_deref(),
+                                gcopt.not_after.as_deref(),
+                            )
+                            .await
+                        {
+                            error!("Failed to set validity of {} -> {:?}", member, e);
+                            return;
+                        }
+                    }
+                }
+
                 match client
                     .idm_group_add_members(gcopt.name.as_str(), &new_members)
                     .await
@@ -21,18 +21,23 @@ use uuid::Uuid;
 
 include!("../opt/kanidm.rs");
 
+pub mod access_profile;
+pub mod access_request;
 pub mod badlist;
 pub mod common;
 pub mod domain;
 pub mod group;
 pub mod oauth2;
 pub mod person;
+pub mod pwhistory;
 pub mod raw;
 pub mod recycle;
+pub mod schema;
 pub mod serviceaccount;
 pub mod session;
 pub mod synch;
 mod webauthn;
+pub mod webhook;
 
 impl SelfOpt {
     pub fn debug(&self) -> bool {
@@ -69,6 +74,9 @@ impl SystemOpt {
     pub fn debug(&self) -> bool {
         match self {
             SystemOpt::PwBadlist { commands } => commands.debug(),
+            SystemOpt::PwHistory { commands } => commands.debug(),
+            SystemOpt::RecycleBinRetention { commands } => commands.debug(),
+            SystemOpt::Webhook { commands } => commands.debug(),
             SystemOpt::Oauth2 { commands } => commands.debug(),
             SystemOpt::Domain { commands } => commands.debug(),
             SystemOpt::Synch { commands } => commands.debug(),
@@ -78,6 +86,9 @@ impl SystemOpt {
     pub async fn exec(&self) {
         match self {
             SystemOpt::PwBadlist { commands } => commands.exec().await,
+            SystemOpt::PwHistory { commands } => commands.exec().await,
+            SystemOpt::RecycleBinRetention { commands } => commands.exec().await,
+            SystemOpt::Webhook { commands } => commands.exec().await,
             SystemOpt::Oauth2 { commands } => commands.exec().await,
             SystemOpt::Domain { commands } => commands.exec().await,
             SystemOpt::Synch { commands } => commands.exec().await,
@@ -98,6 +109,9 @@ impl KanidmClientOpt {
             KanidmClientOpt::Person { commands } => commands.debug(),
             KanidmClientOpt::ServiceAccount { commands } => commands.debug(),
             KanidmClientOpt::System { commands } => commands.debug(),
+            KanidmClientOpt::Schema { commands } => commands.debug(),
+            KanidmClientOpt::AccessProfile { commands } => commands.debug(),
+            KanidmClientOpt::AccessRequest { commands } => commands.debug(),
             KanidmClientOpt::Recycle { commands } => commands.debug(),
             KanidmClientOpt::Version {} => {
                 println!("kanidm {}", env!("KANIDM_PKG_VERSION"));
@@ -118,6 +132,9 @@ impl KanidmClientOpt {
             KanidmClientOpt::ServiceAccount { commands } => commands.exec().await,
             KanidmClientOpt::Group { commands } => commands.exec().await,
             KanidmClientOpt::System { commands } => commands.exec().await,
+            KanidmClientOpt::Schema { commands } => commands.exec().await,
+            KanidmClientOpt::AccessProfile { commands } => commands.exec().await,
+            KanidmClientOpt::AccessRequest { commands } => commands.exec().await,
             KanidmClientOpt::Recycle { commands } => commands.exec().await,
             KanidmClientOpt::Version {} => (),
         }
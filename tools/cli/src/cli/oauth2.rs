@@ -1,5 +1,10 @@
 use crate::common::OpType;
 use crate::{Oauth2Opt, OutputMode};
+use base64::{engine::general_purpose, Engine as _};
+use compact_jwt::JwkKeySet;
+use openssl::hash::MessageDigest;
+use openssl::x509::X509;
+use std::fs;
 
 impl Oauth2Opt {
     pub fn debug(&self) -> bool {
@@ -10,6 +15,8 @@ impl Oauth2Opt {
             Oauth2Opt::DeleteScopeMap(cbopt) => cbopt.nopt.copt.debug,
             Oauth2Opt::UpdateSupScopeMap(cbopt) => cbopt.nopt.copt.debug,
             Oauth2Opt::DeleteSupScopeMap(cbopt) => cbopt.nopt.copt.debug,
+            Oauth2Opt::AddTokenExchangeSource(cbopt) => cbopt.nopt.copt.debug,
+            Oauth2Opt::DeleteTokenExchangeSource(cbopt) => cbopt.nopt.copt.debug,
             Oauth2Opt::ResetSecrets(cbopt) => cbopt.copt.debug,
             // Should this be renamed to show client id? client secrets?
             Oauth2Opt::ShowBasicSecret(nopt) => nopt.copt.debug,
@@ -17,12 +24,22 @@ impl Oauth2Opt {
             Oauth2Opt::SetDisplayname(cbopt) => cbopt.nopt.copt.debug,
             Oauth2Opt::SetName { nopt, .. } => nopt.copt.debug,
             Oauth2Opt::SetLandingUrl { nopt, .. } => nopt.copt.debug,
+            Oauth2Opt::SetBackchannelLogoutUrl { nopt, .. } => nopt.copt.debug,
+            Oauth2Opt::ResetBackchannelLogoutUrl(nopt) => nopt.copt.debug,
+            Oauth2Opt::SetJwks { nopt, .. } => nopt.copt.debug,
+            Oauth2Opt::ResetJwks(nopt) => nopt.copt.debug,
+            Oauth2Opt::SetTlsClientCerts { nopt, .. } => nopt.copt.debug,
+            Oauth2Opt::ResetTlsClientCerts(nopt) => nopt.copt.debug,
             Oauth2Opt::EnablePkce(nopt) => nopt.copt.debug,
             Oauth2Opt::DisablePkce(nopt) => nopt.copt.debug,
             Oauth2Opt::EnableLegacyCrypto(nopt) => nopt.copt.debug,
             Oauth2Opt::DisableLegacyCrypto(nopt) => nopt.copt.debug,
             Oauth2Opt::PreferShortUsername(nopt) => nopt.copt.debug,
             Oauth2Opt::PreferSPNUsername(nopt) => nopt.copt.debug,
+            Oauth2Opt::RequirePushedAuthorisation(nopt) => nopt.copt.debug,
+            Oauth2Opt::DisableConsentPrompt(nopt) => nopt.copt.debug,
+            Oauth2Opt::EnableConsentPrompt(nopt) => nopt.copt.debug,
+            Oauth2Opt::AllowDirectAuthorisation(nopt) => nopt.copt.debug,
             Oauth2Opt::CreateBasic { copt, .. } | Oauth2Opt::CreatePublic { copt, .. } => {
                 copt.debug
             }
@@ -135,6 +152,32 @@ impl Oauth2Opt {
                     Err(e) => error!("Error -> {:?}", e),
                 }
             }
+            Oauth2Opt::AddTokenExchangeSource(cbopt) => {
+                let client = cbopt.nopt.copt.to_client(OpType::Write).await;
+                match client
+                    .idm_oauth2_rs_add_token_exchange_source(
+                        cbopt.nopt.name.as_str(),
+                        cbopt.source.as_str(),
+                    )
+                    .await
+                {
+                    Ok(_) => println!("Success"),
+                    Err(e) => error!("Error -> {:?}", e),
+                }
+            }
+            Oauth2Opt::DeleteTokenExchangeSource(cbopt) => {
+                let client = cbopt.nopt.copt.to_client(OpType::Write).await;
+                match client
+                    .idm_oauth2_rs_delete_token_exchange_source(
+                        cbopt.nopt.name.as_str(),
+                        cbopt.source.as_str(),
+                    )
+                    .await
+                {
+                    Ok(_) => println!("Success"),
+                    Err(e) => error!("Error -> {:?}", e),
+                }
+            }
             Oauth2Opt::ResetSecrets(cbopt) => {
                 let client = cbopt.copt.to_client(OpType::Write).await;
                 match client
@@ -237,6 +280,103 @@ impl Oauth2Opt {
                     Err(e) => error!("Error -> {:?}", e),
                 }
             }
+            Oauth2Opt::SetBackchannelLogoutUrl { nopt, url } => {
+                let client = nopt.copt.to_client(OpType::Write).await;
+                match client
+                    .idm_oauth2_rs_set_backchannel_logout_uri(nopt.name.as_str(), url)
+                    .await
+                {
+                    Ok(_) => println!("Success"),
+                    Err(e) => error!("Error -> {:?}", e),
+                }
+            }
+            Oauth2Opt::ResetBackchannelLogoutUrl(nopt) => {
+                let client = nopt.copt.to_client(OpType::Write).await;
+                match client
+                    .idm_oauth2_rs_clear_backchannel_logout_uri(nopt.name.as_str())
+                    .await
+                {
+                    Ok(_) => println!("Success"),
+                    Err(e) => error!("Error -> {:?}", e),
+                }
+            }
+            Oauth2Opt::SetJwks { nopt, path } => {
+                let jwks = match fs::read_to_string(path) {
+                    Ok(c) => c,
+                    Err(e) => {
+                        error!("Unable to read {:?} -> {:?}", path, e);
+                        return;
+                    }
+                };
+                // Check this is a valid key set before we send it, and remove any
+                // formatting so the stored value is compact.
+                let jwks = match serde_json::from_str::<JwkKeySet>(&jwks)
+                    .and_then(|jwks| serde_json::to_string(&jwks))
+                {
+                    Ok(j) => j,
+                    Err(e) => {
+                        error!("Invalid json web key set in {:?} -> {:?}", path, e);
+                        return;
+                    }
+                };
+                let client = nopt.copt.to_client(OpType::Write).await;
+                match client
+                    .idm_oauth2_rs_set_jwks(nopt.name.as_str(), &jwks)
+                    .await
+                {
+                    Ok(_) => println!("Success"),
+                    Err(e) => error!("Error -> {:?}", e),
+                }
+            }
+            Oauth2Opt::ResetJwks(nopt) => {
+                let client = nopt.copt.to_client(OpType::Write).await;
+                match client.idm_oauth2_rs_clear_jwks(nopt.name.as_str()).await {
+                    Ok(_) => println!("Success"),
+                    Err(e) => error!("Error -> {:?}", e),
+                }
+            }
+            Oauth2Opt::SetTlsClientCerts { nopt, paths } => {
+                let mut thumbprints = Vec::with_capacity(paths.len());
+                for path in paths {
+                    let pem = match fs::read(path) {
+                        Ok(c) => c,
+                        Err(e) => {
+                            error!("Unable to read {:?} -> {:?}", path, e);
+                            return;
+                        }
+                    };
+                    // We only store the x5t#S256 thumbprint of the certificate, which is
+                    // what the server compares the presented client certificate with.
+                    let digest = match X509::from_pem(&pem)
+                        .and_then(|cert| cert.digest(MessageDigest::sha256()))
+                    {
+                        Ok(d) => d,
+                        Err(e) => {
+                            error!("Invalid certificate in {:?} -> {:?}", path, e);
+                            return;
+                        }
+                    };
+                    thumbprints.push(general_purpose::URL_SAFE_NO_PAD.encode(digest));
+                }
+                let client = nopt.copt.to_client(OpType::Write).await;
+                match client
+                    .idm_oauth2_rs_set_tls_client_certs(nopt.name.as_str(), thumbprints)
+                    .await
+                {
+                    Ok(_) => println!("Success"),
+                    Err(e) => error!("Error -> {:?}", e),
+                }
+            }
+            Oauth2Opt::ResetTlsClientCerts(nopt) => {
+                let client = nopt.copt.to_client(OpType::Write).await;
+                match client
+                    .idm_oauth2_rs_set_tls_client_certs(nopt.name.as_str(), Vec::new())
+                    .await
+                {
+                    Ok(_) => println!("Success"),
+                    Err(e) => error!("Error -> {:?}", e),
+                }
+            }
             Oauth2Opt::EnablePkce(nopt) => {
                 let client = nopt.copt.to_client(OpType::Write).await;
                 match client.idm_oauth2_rs_enable_pkce(nopt.name.as_str()).await {
@@ -291,6 +431,46 @@ impl Oauth2Opt {
                     Err(e) => error!("Error -> {:?}", e),
                 }
             }
+            Oauth2Opt::RequirePushedAuthorisation(nopt) => {
+                let client = nopt.copt.to_client(OpType::Write).await;
+                match client
+                    .idm_oauth2_rs_require_pushed_authorisation(nopt.name.as_str())
+                    .await
+                {
+                    Ok(_) => println!("Success"),
+                    Err(e) => error!("Error -> {:?}", e),
+                }
+            }
+            Oauth2Opt::AllowDirectAuthorisation(nopt) => {
+                let client = nopt.copt.to_client(OpType::Write).await;
+                match client
+                    .idm_oauth2_rs_allow_direct_authorisation(nopt.name.as_str())
+                    .await
+                {
+                    Ok(_) => println!("Success"),
+                    Err(e) => error!("Error -> {:?}", e),
+                }
+            }
+            Oauth2Opt::DisableConsentPrompt(nopt) => {
+                let client = nopt.copt.to_client(OpType::Write).await;
+                match client
+                    .idm_oauth2_rs_disable_consent_prompt(nopt.name.as_str())
+                    .await
+                {
+                    Ok(_) => println!("Success"),
+                    Err(e) => error!("Error -> {:?}", e),
+                }
+            }
+            Oauth2Opt::EnableConsentPrompt(nopt) => {
+                let client = nopt.copt.to_client(OpType::Write).await;
+                match client
+                    .idm_oauth2_rs_enable_consent_prompt(nopt.name.as_str())
+                    .await
+                {
+                    Ok(_) => println!("Success"),
+                    Err(e) => error!("Error -> {:?}", e),
+                }
+            }
         }
     }
 }
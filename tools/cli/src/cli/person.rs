@@ -6,6 +6,7 @@ use dialoguer::theme::ColorfulTheme;
 use dialoguer::{Confirm, Input, Password, Select};
 use kanidm_client::ClientError::Http as ClientErrorHttp;
 use kanidm_client::KanidmClient;
+use kanidm_proto::internal::{IdentifyUserRequest, IdentifyUserResponse};
 use kanidm_proto::messages::{AccountChangeMessage, ConsoleOutputMode, MessageStatus};
 use kanidm_proto::v1::OperationError::PasswordQuality;
 use kanidm_proto::v1::{
@@ -22,7 +23,7 @@ use uuid::Uuid;
 use crate::webauthn::get_authenticator;
 use crate::{
     password_prompt, AccountCredential, AccountRadius, AccountSsh, AccountUserAuthToken,
-    AccountValidity, PersonOpt, PersonPosix,
+    AccountValidity, PersonOauth2Consent, PersonOpt, PersonPosix,
 };
 
 impl PersonOpt {
@@ -43,11 +44,16 @@ impl PersonOpt {
                 AccountUserAuthToken::Status(apo) => apo.copt.debug,
                 AccountUserAuthToken::Destroy { copt, .. } => copt.debug,
             },
+            PersonOpt::Consent { commands } => match commands {
+                PersonOauth2Consent::List(apo) => apo.copt.debug,
+                PersonOauth2Consent::Revoke { copt, .. } => copt.debug,
+            },
             PersonOpt::Ssh { commands } => match commands {
                 AccountSsh::List(ano) => ano.copt.debug,
                 AccountSsh::Add(ano) => ano.copt.debug,
                 AccountSsh::Delete(ano) => ano.copt.debug,
             },
+            PersonOpt::IdentifyUser(aopt) => aopt.copt.debug,
             PersonOpt::List(copt) => copt.debug,
             PersonOpt::Get(aopt) => aopt.copt.debug,
             PersonOpt::Update(aopt) => aopt.copt.debug,
@@ -214,6 +220,46 @@ impl PersonOpt {
                     }
                 }
             }, // End PersonOpt::Session
+            PersonOpt::Consent { commands } => match commands {
+                PersonOauth2Consent::List(apo) => {
+                    let client = apo.copt.to_client(OpType::Read).await;
+                    match client
+                        .idm_account_list_oauth2_grant(apo.aopts.account_id.as_str())
+                        .await
+                    {
+                        Ok(grants) => {
+                            if grants.is_empty() {
+                                println!("No oauth2 applications have been granted access");
+                            } else {
+                                for grant in grants {
+                                    println!("{}", grant);
+                                }
+                            }
+                        }
+                        Err(e) => {
+                            error!("Error listing oauth2 consents -> {:?}", e);
+                        }
+                    }
+                }
+                PersonOauth2Consent::Revoke {
+                    aopts,
+                    copt,
+                    rs_name,
+                } => {
+                    let client = copt.to_client(OpType::Write).await;
+                    match client
+                        .idm_account_revoke_oauth2_grant(aopts.account_id.as_str(), rs_name)
+                        .await
+                    {
+                        Ok(()) => {
+                            println!("Success");
+                        }
+                        Err(e) => {
+                            error!("Error revoking oauth2 consent -> {:?}", e);
+                        }
+                    }
+                }
+            }, // End PersonOpt::Consent
             PersonOpt::Ssh { commands } => match commands {
                 AccountSsh::List(aopt) => {
                     let client = aopt.copt.to_client(OpType::Read).await;
@@ -254,6 +300,10 @@ impl PersonOpt {
                     }
                 }
             }, // end PersonOpt::Ssh
+            PersonOpt::IdentifyUser(aopt) => {
+                let client = aopt.copt.to_client(OpType::Read).await;
+                identify_user_exec(&client, aopt.aopts.account_id.as_str()).await
+            }
             PersonOpt::List(copt) => {
                 let client = copt.to_client(OpType::Read).await;
                 match client.idm_person_account_list().await {
@@ -532,6 +582,7 @@ impl AccountCredential {
         match self {
             AccountCredential::Status(aopt) => aopt.copt.debug,
             AccountCredential::CreateResetToken { copt, .. } => copt.debug,
+            AccountCredential::HelpdeskReset { copt, .. } => copt.debug,
             AccountCredential::UseResetToken(aopt) => aopt.copt.debug,
             AccountCredential::Update(aopt) => aopt.copt.debug,
         }
@@ -594,7 +645,35 @@ impl AccountCredential {
                     }
                 }
             }
-            AccountCredential::CreateResetToken { aopts, copt, ttl } => {
+            AccountCredential::CreateResetToken {
+                aopts,
+                copt,
+                ttl,
+                email: true,
+            } => {
+                let client = copt.to_client(OpType::Write).await;
+                match client
+                    .idm_person_credential_reset_email(
+                        aopts.account_id.as_str(),
+                        ttl.map(u64::from),
+                    )
+                    .await
+                {
+                    Ok(()) => println!(
+                        "A credential reset link will be mailed to {}",
+                        aopts.account_id
+                    ),
+                    Err(e) => {
+                        error!("Error mailing credential reset -> {:?}", e);
+                    }
+                }
+            }
+            AccountCredential::CreateResetToken {
+                aopts,
+                copt,
+                ttl,
+                email: false,
+            } => {
                 let client = copt.to_client(OpType::Write).await;
 
                 // What's the client url?
@@ -603,52 +682,140 @@ impl AccountCredential {
                     .await
                 {
                     Ok(cuintent_token) => {
-                        let mut url = match Url::parse(client.get_url()) {
-                            Ok(u) => u,
-                            Err(e) => {
-                                error!("Unable to parse url - {:?}", e);
-                                return;
-                            }
-                        };
-                        url.set_path("/ui/reset");
-                        url.query_pairs_mut()
-                            .append_pair("token", cuintent_token.token.as_str());
-
-                        debug!(
-                            "Successfully created credential reset token for {}: {}",
-                            aopts.account_id, cuintent_token.token
-                        );
-                        println!(
-                            "The person can use one of the following to allow the credential reset"
-                        );
-                        println!("\nScan this QR Code:\n");
-                        let code = match QrCode::new(url.as_str()) {
-                            Ok(c) => c,
-                            Err(e) => {
-                                error!("Failed to generate QR code -> {:?}", e);
-                                return;
-                            }
-                        };
-                        let image = code
-                            .render::<unicode::Dense1x2>()
-                            .dark_color(unicode::Dense1x2::Light)
-                            .light_color(unicode::Dense1x2::Dark)
-                            .build();
-                        println!("{}", image);
-
-                        println!();
-                        println!("This link: {}", url.as_str());
-                        println!(
-                            "Or run this command: kanidm person credential use-reset-token {}",
-                            cuintent_token.token
-                        );
-                        println!();
+                        print_reset_token(&client, aopts.account_id.as_str(), &cuintent_token)
                     }
                     Err(e) => {
                         error!("Error starting credential reset -> {:?}", e);
                     }
                 }
             }
+            AccountCredential::HelpdeskReset {
+                aopts,
+                copt,
+                code,
+                ttl,
+            } => {
+                let client = copt.to_client(OpType::Write).await;
+                match client
+                    .idm_person_credential_helpdesk_reset(aopts.account_id.as_str(), *code, *ttl)
+                    .await
+                {
+                    Ok(cuintent_token) => {
+                        print_reset_token(&client, aopts.account_id.as_str(), &cuintent_token)
+                    }
+                    Err(e) => {
+                        error!("Error starting helpdesk credential reset -> {:?}", e);
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn print_reset_token(client: &KanidmClient, account_id: &str, cuintent_token: &CUIntentToken) {
+    let mut url = match Url::parse(client.get_url()) {
+        Ok(u) => u,
+        Err(e) => {
+            error!("Unable to parse url - {:?}", e);
+            return;
+        }
+    };
+    url.set_path("/ui/reset");
+    url.query_pairs_mut()
+        .append_pair("token", cuintent_token.token.as_str());
+
+    debug!(
+        "Successfully created credential reset token for {}: {}",
+        account_id, cuintent_token.token
+    );
+    println!("The person can use one of the following to allow the credential reset");
+    println!("\nScan this QR Code:\n");
+    let code = match QrCode::new(url.as_str()) {
+        Ok(c) => c,
+        Err(e) => {
+            error!("Failed to generate QR code -> {:?}", e);
+            return;
+        }
+    };
+    let image = code
+        .render::<unicode::Dense1x2>()
+        .dark_color(unicode::Dense1x2::Light)
+        .light_color(unicode::Dense1x2::Dark)
+        .build();
+    println!("{}", image);
+
+    println!();
+    println!("This link: {}", url.as_str());
+    println!(
+        "Or run this command: kanidm person credential use-reset-token {}",
+        cuintent_token.token
+    );
+    println!();
+}
+
+fn prompt_identity_code(account_id: &str) -> u32 {
+    Input::new()
+        .with_prompt(format!("Code read to you by {}", account_id))
+        .interact_text()
+        .expect("Failed to interact with interactive session")
+}
+
+async fn identify_user_exec(client: &KanidmClient, account_id: &str) {
+    // Whoever starts the exchange reads their code first, so that the other person
+    // can confirm who they are talking to before revealing their own code.
+    let has_code = Confirm::new()
+        .with_prompt(format!("Has {} already read you their code?", account_id))
+        .default(false)
+        .interact()
+        .expect("Failed to interact with interactive session");
+
+    if !has_code {
+        match client
+            .idm_person_identify_user(account_id, IdentifyUserRequest::Start)
+            .await
+        {
+            Ok(IdentifyUserResponse::ProvideCode { step, totp }) => {
+                println!(
+                    "Read this code to {}: {:06} (valid for {} seconds)",
+                    account_id, totp, step
+                );
+            }
+            Ok(res) => {
+                error!("Unexpected response -> {:?}", res);
+                return;
+            }
+            Err(e) => {
+                error!("Error starting identity verification -> {:?}", e);
+                return;
+            }
+        }
+    }
+
+    let other_totp = prompt_identity_code(account_id);
+    match client
+        .idm_person_identify_user(account_id, IdentifyUserRequest::SubmitCode { other_totp })
+        .await
+    {
+        Ok(IdentifyUserResponse::Success { step, totp }) => {
+            println!("✅ The code is correct, you are talking to {}", account_id);
+            if has_code {
+                println!(
+                    "Read this code to {}: {:06} (valid for {} seconds)",
+                    account_id, totp, step
+                );
+            }
+        }
+        Ok(IdentifyUserResponse::CodeFailure) => {
+            println!(
+                "❌ The code is incorrect or has expired. You may not be talking to {}",
+                account_id
+            );
+        }
+        Ok(res) => {
+            error!("Unexpected response -> {:?}", res);
+        }
+        Err(e) => {
+            error!("Error verifying identity -> {:?}", e);
         }
     }
 }
@@ -0,0 +1,31 @@
+use crate::common::OpType;
+use crate::PwHistoryOpt;
+
+impl PwHistoryOpt {
+    pub fn debug(&self) -> bool {
+        match self {
+            PwHistoryOpt::Show(copt) => copt.debug,
+            PwHistoryOpt::SetLength { copt, .. } => copt.debug,
+        }
+    }
+
+    pub async fn exec(&self) {
+        match self {
+            PwHistoryOpt::Show(copt) => {
+                let client = copt.to_client(OpType::Read).await;
+                match client.system_password_history_length_get().await {
+                    Ok(Some(length)) => println!("{}", length),
+                    Ok(None) => println!("Not set, using the default"),
+                    Err(e) => eprintln!("{:?}", e),
+                }
+            }
+            PwHistoryOpt::SetLength { copt, length } => {
+                let client = copt.to_client(OpType::Write).await;
+                match client.system_password_history_length_set(*length).await {
+                    Ok(_) => println!("Success"),
+                    Err(e) => eprintln!("{:?}", e),
+                }
+            }
+        }
+    }
+}
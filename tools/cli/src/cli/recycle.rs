@@ -1,12 +1,13 @@
 use crate::common::OpType;
-use crate::RecycleOpt;
+use crate::{RecycleOpt, RecycleRetentionOpt};
 
 impl RecycleOpt {
     pub fn debug(&self) -> bool {
         match self {
             RecycleOpt::List(copt) => copt.debug,
             RecycleOpt::Get(nopt) => nopt.copt.debug,
-            RecycleOpt::Revive(nopt) => nopt.copt.debug,
+            RecycleOpt::Revive(ropt) => ropt.copt.debug,
+            RecycleOpt::Purge(nopt) => nopt.copt.debug,
         }
     }
 
@@ -31,12 +32,72 @@ impl RecycleOpt {
                     }
                 }
             }
-            RecycleOpt::Revive(nopt) => {
+            RecycleOpt::Revive(ropt) => {
+                let client = ropt.copt.to_client(OpType::Write).await;
+                let res = if ropt.without_members {
+                    client
+                        .recycle_bin_revive_without_members(ropt.name.as_str())
+                        .await
+                } else {
+                    client.recycle_bin_revive(ropt.name.as_str()).await
+                };
+                if let Err(e) = res {
+                    error!("Error -> {:?}", e);
+                }
+            }
+            RecycleOpt::Purge(nopt) => {
                 let client = nopt.copt.to_client(OpType::Write).await;
-                if let Err(e) = client.recycle_bin_revive(nopt.name.as_str()).await {
+                if let Err(e) = client.recycle_bin_purge(nopt.name.as_str()).await {
                     error!("Error -> {:?}", e);
                 }
             }
         }
     }
 }
+
+impl RecycleRetentionOpt {
+    pub fn debug(&self) -> bool {
+        match self {
+            RecycleRetentionOpt::Show(copt) => copt.debug,
+            RecycleRetentionOpt::Set { copt, .. } => copt.debug,
+            RecycleRetentionOpt::Remove { copt, .. } => copt.debug,
+        }
+    }
+
+    pub async fn exec(&self) {
+        match self {
+            RecycleRetentionOpt::Show(copt) => {
+                let client = copt.to_client(OpType::Read).await;
+                match client.system_recycle_bin_retention_get().await {
+                    Ok(list) if list.is_empty() => println!("Not set, using the default"),
+                    Ok(list) => list.iter().for_each(|v| println!("{}", v)),
+                    Err(e) => eprintln!("{:?}", e),
+                }
+            }
+            RecycleRetentionOpt::Set {
+                copt,
+                class,
+                seconds,
+            } => {
+                let client = copt.to_client(OpType::Write).await;
+                match client
+                    .system_recycle_bin_retention_set(class.as_str(), *seconds)
+                    .await
+                {
+                    Ok(_) => println!("Success"),
+                    Err(e) => eprintln!("{:?}", e),
+                }
+            }
+            RecycleRetentionOpt::Remove { copt, class } => {
+                let client = copt.to_client(OpType::Write).await;
+                match client
+                    .system_recycle_bin_retention_remove(class.as_str())
+                    .await
+                {
+                    Ok(_) => println!("Success"),
+                    Err(e) => eprintln!("{:?}", e),
+                }
+            }
+        }
+    }
+}
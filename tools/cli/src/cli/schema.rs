@@ -0,0 +1,222 @@
+use kanidm_client::KanidmClient;
+
+use crate::common::OpType;
+use crate::{SchemaAttrOpt, SchemaClassOpt, SchemaOpt};
+
+impl SchemaOpt {
+    pub fn debug(&self) -> bool {
+        match self {
+            SchemaOpt::Attribute { commands } => commands.debug(),
+            SchemaOpt::Class { commands } => commands.debug(),
+        }
+    }
+
+    pub async fn exec(&self) {
+        match self {
+            SchemaOpt::Attribute { commands } => commands.exec().await,
+            SchemaOpt::Class { commands } => commands.exec().await,
+        }
+    }
+}
+
+impl SchemaAttrOpt {
+    pub fn debug(&self) -> bool {
+        match self {
+            SchemaAttrOpt::List(copt) => copt.debug,
+            SchemaAttrOpt::Get(nopt)
+            | SchemaAttrOpt::Deprecate(nopt)
+            | SchemaAttrOpt::Undeprecate(nopt) => nopt.copt.debug,
+            SchemaAttrOpt::Create(copt) => copt.copt.debug,
+            SchemaAttrOpt::Update(uopt) => uopt.copt.debug,
+        }
+    }
+
+    pub async fn exec(&self) {
+        match self {
+            SchemaAttrOpt::List(copt) => {
+                let client = copt.to_client(OpType::Read).await;
+                match client.idm_schema_attributetype_list().await {
+                    Ok(r) => r.iter().for_each(|e| println!("{}", e)),
+                    Err(e) => error!("Error -> {:?}", e),
+                }
+            }
+            SchemaAttrOpt::Get(nopt) => {
+                let client = nopt.copt.to_client(OpType::Read).await;
+                match client
+                    .idm_schema_attributetype_get(nopt.name.as_str())
+                    .await
+                {
+                    Ok(Some(e)) => println!("{}", e),
+                    Ok(None) => println!("No matching entries"),
+                    Err(e) => error!("Error -> {:?}", e),
+                }
+            }
+            SchemaAttrOpt::Create(copt) => {
+                let client = copt.copt.to_client(OpType::Write).await;
+                match client
+                    .idm_schema_attributetype_create(
+                        copt.name.as_str(),
+                        copt.description.as_str(),
+                        copt.syntax.as_str(),
+                        copt.multivalue,
+                        copt.unique,
+                        copt.unique_scope.as_deref(),
+                        copt.index.clone(),
+                    )
+                    .await
+                {
+                    Ok(_) => println!("Successfully created attribute {}", copt.name),
+                    Err(e) => error!("Error -> {:?}", e),
+                }
+            }
+            SchemaAttrOpt::Update(uopt) => {
+                let client = uopt.copt.to_client(OpType::Write).await;
+                match client
+                    .idm_schema_attributetype_update(
+                        uopt.name.as_str(),
+                        uopt.description.as_deref(),
+                        uopt.multivalue,
+                        uopt.unique,
+                        uopt.unique_scope.as_deref(),
+                        uopt.index.clone(),
+                    )
+                    .await
+                {
+                    Ok(_) => println!("Successfully updated attribute {}", uopt.name),
+                    Err(e) => error!("Error -> {:?}", e),
+                }
+            }
+            SchemaAttrOpt::Deprecate(nopt) | SchemaAttrOpt::Undeprecate(nopt) => {
+                let deprecated = matches!(self, SchemaAttrOpt::Deprecate(_));
+                let client = nopt.copt.to_client(OpType::Write).await;
+                match client
+                    .idm_schema_attributetype_deprecate(nopt.name.as_str(), deprecated)
+                    .await
+                {
+                    Ok(_) => println!("Success"),
+                    Err(e) => error!("Error -> {:?}", e),
+                }
+            }
+        }
+    }
+}
+
+// Alter the may or must list of a class, then write back the complete list.
+async fn update_class_attrs(
+    client: &KanidmClient,
+    name: &str,
+    attr: &str,
+    add: &[String],
+    remove: &[String],
+) {
+    let mut values = match client.idm_schema_classtype_get(name).await {
+        Ok(Some(e)) => e.attrs.get(attr).cloned().unwrap_or_default(),
+        Ok(None) => {
+            error!("No class named {} exists", name);
+            return;
+        }
+        Err(e) => {
+            error!("Error -> {:?}", e);
+            return;
+        }
+    };
+
+    values.retain(|v| !remove.contains(v));
+    for a in add {
+        if !values.contains(a) {
+            values.push(a.clone());
+        }
+    }
+
+    let (may, must) = if attr == "may" {
+        (Some(values), None)
+    } else {
+        (None, Some(values))
+    };
+
+    match client
+        .idm_schema_classtype_update(name, None, may, must)
+        .await
+    {
+        Ok(_) => println!("Successfully updated class {}", name),
+        Err(e) => error!("Error -> {:?}", e),
+    }
+}
+
+impl SchemaClassOpt {
+    pub fn debug(&self) -> bool {
+        match self {
+            SchemaClassOpt::List(copt) => copt.debug,
+            SchemaClassOpt::Get(nopt)
+            | SchemaClassOpt::Deprecate(nopt)
+            | SchemaClassOpt::Undeprecate(nopt) => nopt.copt.debug,
+            SchemaClassOpt::Create(copt) => copt.copt.debug,
+            SchemaClassOpt::AddMay(aopt)
+            | SchemaClassOpt::RemoveMay(aopt)
+            | SchemaClassOpt::AddMust(aopt)
+            | SchemaClassOpt::RemoveMust(aopt) => aopt.copt.debug,
+        }
+    }
+
+    pub async fn exec(&self) {
+        match self {
+            SchemaClassOpt::List(copt) => {
+                let client = copt.to_client(OpType::Read).await;
+                match client.idm_schema_classtype_list().await {
+                    Ok(r) => r.iter().for_each(|e| println!("{}", e)),
+                    Err(e) => error!("Error -> {:?}", e),
+                }
+            }
+            SchemaClassOpt::Get(nopt) => {
+                let client = nopt.copt.to_client(OpType::Read).await;
+                match client.idm_schema_classtype_get(nopt.name.as_str()).await {
+                    Ok(Some(e)) => println!("{}", e),
+                    Ok(None) => println!("No matching entries"),
+                    Err(e) => error!("Error -> {:?}", e),
+                }
+            }
+            SchemaClassOpt::Create(copt) => {
+                let client = copt.copt.to_client(OpType::Write).await;
+                match client
+                    .idm_schema_classtype_create(
+                        copt.name.as_str(),
+                        copt.description.as_str(),
+                        copt.may.clone(),
+                        copt.must.clone(),
+                    )
+                    .await
+                {
+                    Ok(_) => println!("Successfully created class {}", copt.name),
+                    Err(e) => error!("Error -> {:?}", e),
+                }
+            }
+            SchemaClassOpt::AddMay(aopt) => {
+                let client = aopt.copt.to_client(OpType::Write).await;
+                update_class_attrs(&client, aopt.name.as_str(), "may", &aopt.attrs, &[]).await
+            }
+            SchemaClassOpt::RemoveMay(aopt) => {
+                let client = aopt.copt.to_client(OpType::Write).await;
+                update_class_attrs(&client, aopt.name.as_str(), "may", &[], &aopt.attrs).await
+            }
+            SchemaClassOpt::AddMust(aopt) => {
+                let client = aopt.copt.to_client(OpType::Write).await;
+                update_class_attrs(&client, aopt.name.as_str(), "must", &aopt.attrs, &[]).await
+            }
+            SchemaClassOpt::RemoveMust(aopt) => {
+                let client = aopt.copt.to_client(OpType::Write).await;
+                update_class_attrs(&client, aopt.name.as_str(), "must", &[], &aopt.attrs).await
+            }
+            SchemaClassOpt::Deprecate(nopt) | SchemaClassOpt::Undeprecate(nopt) => {
+                let deprecated = matches!(self, SchemaClassOpt::Deprecate(_));
+                let client = nopt.copt.to_client(OpType::Write).await;
+                match client
+                    .idm_schema_classtype_deprecate(nopt.name.as_str(), deprecated)
+                    .await
+                {
+                    Ok(_) => println!("Success"),
+                    Err(e) => error!("Error -> {:?}", e),
+                }
+            }
+        }
+    }
+}
@@ -4,7 +4,8 @@ use time::OffsetDateTime;
 
 use crate::{
     AccountSsh, AccountUserAuthToken, AccountValidity, OutputMode, ServiceAccountApiToken,
-    ServiceAccountCredential, ServiceAccountOpt, ServiceAccountPosix,
+    ServiceAccountCredential, ServiceAccountOauth2Registrar, ServiceAccountOpt,
+    ServiceAccountPosix,
 };
 use time::format_description::well_known::Rfc3339;
 
@@ -24,6 +25,12 @@ impl ServiceAccountOpt {
                 ServiceAccountPosix::Show(apo) => apo.copt.debug,
                 ServiceAccountPosix::Set(apo) => apo.copt.debug,
             },
+            ServiceAccountOpt::Oauth2Registrar { commands } => match commands {
+                ServiceAccountOauth2Registrar::AddDomain { copt, .. }
+                | ServiceAccountOauth2Registrar::RemoveDomain { copt, .. }
+                | ServiceAccountOauth2Registrar::UpdateScopeMap { copt, .. }
+                | ServiceAccountOauth2Registrar::DeleteScopeMap { copt, .. } => copt.debug,
+            },
             ServiceAccountOpt::Session { commands } => match commands {
                 AccountUserAuthToken::Status(apo) => apo.copt.debug,
                 AccountUserAuthToken::Destroy { copt, .. } => copt.debug,
@@ -37,6 +44,8 @@ impl ServiceAccountOpt {
             ServiceAccountOpt::Get(aopt) => aopt.copt.debug,
             ServiceAccountOpt::Update(aopt) => aopt.copt.debug,
             ServiceAccountOpt::Delete(aopt) => aopt.copt.debug,
+            ServiceAccountOpt::SetEntryManager(aopt) => aopt.copt.debug,
+            ServiceAccountOpt::PurgeEntryManager(aopt) => aopt.copt.debug,
             ServiceAccountOpt::Create(aopt) => aopt.copt.debug,
             ServiceAccountOpt::Validity { commands } => match commands {
                 AccountValidity::Show(ano) => ano.copt.debug,
@@ -183,6 +192,74 @@ impl ServiceAccountOpt {
                     }
                 }
             }, // End ServiceAccountOpt::ApiToken
+            ServiceAccountOpt::Oauth2Registrar { commands } => match commands {
+                ServiceAccountOauth2Registrar::AddDomain {
+                    aopts,
+                    copt,
+                    domain,
+                } => {
+                    let client = copt.to_client(OpType::Write).await;
+                    match client
+                        .idm_service_account_oauth2_registrar_add_domain(
+                            aopts.account_id.as_str(),
+                            domain,
+                        )
+                        .await
+                    {
+                        Ok(()) => println!("Success"),
+                        Err(e) => error!("Error -> {:?}", e),
+                    }
+                }
+                ServiceAccountOauth2Registrar::RemoveDomain {
+                    aopts,
+                    copt,
+                    domain,
+                } => {
+                    let client = copt.to_client(OpType::Write).await;
+                    match client
+                        .idm_service_account_oauth2_registrar_remove_domain(
+                            aopts.account_id.as_str(),
+                            domain,
+                        )
+                        .await
+                    {
+                        Ok(()) => println!("Success"),
+                        Err(e) => error!("Error -> {:?}", e),
+                    }
+                }
+                ServiceAccountOauth2Registrar::UpdateScopeMap {
+                    aopts,
+                    copt,
+                    group,
+                    scopes,
+                } => {
+                    let client = copt.to_client(OpType::Write).await;
+                    match client
+                        .idm_service_account_oauth2_registrar_update_scope_map(
+                            aopts.account_id.as_str(),
+                            group,
+                            scopes.iter().map(String::as_str).collect(),
+                        )
+                        .await
+                    {
+                        Ok(()) => println!("Success"),
+                        Err(e) => error!("Error -> {:?}", e),
+                    }
+                }
+                ServiceAccountOauth2Registrar::DeleteScopeMap { aopts, copt, group } => {
+                    let client = copt.to_client(OpType::Write).await;
+                    match client
+                        .idm_service_account_oauth2_registrar_delete_scope_map(
+                            aopts.account_id.as_str(),
+                            group,
+                        )
+                        .await
+                    {
+                        Ok(()) => println!("Success"),
+                        Err(e) => error!("Error -> {:?}", e),
+                    }
+                }
+            }, // End ServiceAccountOpt::Oauth2Registrar
             ServiceAccountOpt::Posix { commands } => match commands {
                 ServiceAccountPosix::Show(aopt) => {
                     let client = aopt.copt.to_client(OpType::Read).await;
@@ -499,6 +576,29 @@ impl ServiceAccountOpt {
                     }
                 }
             }, // end ServiceAccountOpt::Validity
+            ServiceAccountOpt::SetEntryManager(aopt) => {
+                let client = aopt.copt.to_client(OpType::Write).await;
+                match client
+                    .idm_service_account_set_entry_managed_by(
+                        aopt.aopts.account_id.as_str(),
+                        aopt.entry_managed_by.as_str(),
+                    )
+                    .await
+                {
+                    Ok(()) => println!("Success"),
+                    Err(e) => error!("Error -> {:?}", e),
+                }
+            }
+            ServiceAccountOpt::PurgeEntryManager(aopt) => {
+                let client = aopt.copt.to_client(OpType::Write).await;
+                match client
+                    .idm_service_account_purge_entry_managed_by(aopt.aopts.account_id.as_str())
+                    .await
+                {
+                    Ok(()) => println!("Success"),
+                    Err(e) => error!("Error -> {:?}", e),
+                }
+            }
             ServiceAccountOpt::IntoPerson(aopt) => {
                 let client = aopt.copt.to_client(OpType::Write).await;
                 match client
@@ -0,0 +1,122 @@
+use kanidm_proto::v1::WebhookEventType;
+
+use crate::common::OpType;
+use crate::WebhookOpt;
+
+impl WebhookOpt {
+    pub fn debug(&self) -> bool {
+        match self {
+            WebhookOpt::List(copt) => copt.debug,
+            WebhookOpt::Create(wopt) => wopt.copt.debug,
+            WebhookOpt::SetUrl { nopt, .. } => nopt.copt.debug,
+            WebhookOpt::Get(nopt)
+            | WebhookOpt::Delete(nopt)
+            | WebhookOpt::ShowSecret(nopt)
+            | WebhookOpt::ResetSecret(nopt)
+            | WebhookOpt::DeadLetters(nopt)
+            | WebhookOpt::ClearDeadLetters(nopt)
+            | WebhookOpt::Retry(nopt) => nopt.copt.debug,
+        }
+    }
+
+    pub async fn exec(&self) {
+        match self {
+            WebhookOpt::List(copt) => {
+                let client = copt.to_client(OpType::Read).await;
+                match client.idm_webhook_list().await {
+                    Ok(r) => r.iter().for_each(|e| println!("{}", e)),
+                    Err(e) => error!("Error -> {:?}", e),
+                }
+            }
+            WebhookOpt::Get(nopt) => {
+                let client = nopt.copt.to_client(OpType::Read).await;
+                match client.idm_webhook_get(nopt.name.as_str()).await {
+                    Ok(Some(e)) => println!("{}", e),
+                    Ok(None) => println!("No matching entries"),
+                    Err(e) => error!("Error -> {:?}", e),
+                }
+            }
+            WebhookOpt::Create(wopt) => {
+                let events: Result<Vec<WebhookEventType>, _> =
+                    wopt.events.iter().map(|ev| ev.parse()).collect();
+                let Ok(events) = events else {
+                    error!("Invalid event, must be one of account_created, account_disabled, account_deleted or group_membership");
+                    return;
+                };
+                let client = wopt.copt.to_client(OpType::Write).await;
+                match client
+                    .idm_webhook_create(
+                        wopt.name.as_str(),
+                        wopt.url.as_str(),
+                        &events,
+                        wopt.filter.as_deref(),
+                    )
+                    .await
+                {
+                    Ok(_) => println!("Success"),
+                    Err(e) => error!("Error -> {:?}", e),
+                }
+            }
+            WebhookOpt::SetUrl { nopt, url } => {
+                let client = nopt.copt.to_client(OpType::Write).await;
+                match client
+                    .idm_webhook_set_url(nopt.name.as_str(), url.as_str())
+                    .await
+                {
+                    Ok(_) => println!("Success"),
+                    Err(e) => error!("Error -> {:?}", e),
+                }
+            }
+            WebhookOpt::Delete(nopt) => {
+                let client = nopt.copt.to_client(OpType::Write).await;
+                match client.idm_webhook_delete(nopt.name.as_str()).await {
+                    Ok(_) => println!("Success"),
+                    Err(e) => error!("Error -> {:?}", e),
+                }
+            }
+            WebhookOpt::ShowSecret(nopt) => {
+                let client = nopt.copt.to_client(OpType::Read).await;
+                match client.idm_webhook_get_secret(nopt.name.as_str()).await {
+                    Ok(Some(secret)) => println!("{}", secret),
+                    Ok(None) => eprintln!("No secret configured"),
+                    Err(e) => error!("Error -> {:?}", e),
+                }
+            }
+            WebhookOpt::ResetSecret(nopt) => {
+                let client = nopt.copt.to_client(OpType::Write).await;
+                match client.idm_webhook_reset_secret(nopt.name.as_str()).await {
+                    Ok(_) => println!("Success"),
+                    Err(e) => error!("Error -> {:?}", e),
+                }
+            }
+            WebhookOpt::DeadLetters(nopt) => {
+                let client = nopt.copt.to_client(OpType::Read).await;
+                match client.idm_webhook_dead_letters(nopt.name.as_str()).await {
+                    Ok(list) if list.is_empty() => println!("No undelivered events"),
+                    Ok(list) => list.iter().for_each(|dl| println!("{}", dl)),
+                    Err(e) => error!("Error -> {:?}", e),
+                }
+            }
+            WebhookOpt::ClearDeadLetters(nopt) => {
+                let client = nopt.copt.to_client(OpType::Write).await;
+                match client
+                    .idm_webhook_dead_letters_clear(nopt.name.as_str())
+                    .await
+                {
+                    Ok(_) => println!("Success"),
+                    Err(e) => error!("Error -> {:?}", e),
+                }
+            }
+            WebhookOpt::Retry(nopt) => {
+                let client = nopt.copt.to_client(OpType::Write).await;
+                match client
+                    .idm_webhook_dead_letters_retry(nopt.name.as_str())
+                    .await
+                {
+                    Ok(queued) => println!("Queued {} events for delivery", queued),
+                    Err(e) => error!("Error -> {:?}", e),
+                }
+            }
+        }
+    }
+}
@@ -60,6 +60,69 @@ pub struct GroupNamedMembers {
     copt: CommonOpt,
 }
 
+#[derive(Debug, Args)]
+pub struct GroupNamedAddMembers {
+    name: String,
+    #[clap(required = true, num_args(1..))]
+    members: Vec<String>,
+    /// The members are not members of the group until this time, in RFC3339 format
+    /// "YYYY-MM-DDTHH:MM:SS+TZ".
+    #[clap(long)]
+    not_before: Option<String>,
+    /// The members are no longer members of the group after this time, in RFC3339 format
+    /// "YYYY-MM-DDTHH:MM:SS+TZ". The members are removed from the group once it has passed.
+    #[clap(long)]
+    not_after: Option<String>,
+    #[clap(flatten)]
+    copt: CommonOpt,
+}
+
+#[derive(Debug, Args)]
+pub struct GroupNamedEntryManager {
+    name: String,
+    /// The person or group that will manage this group
+    entry_managed_by: String,
+    #[clap(flatten)]
+    copt: CommonOpt,
+}
+
+#[derive(Debug, Args)]
+pub struct GroupNamedApprovers {
+    name: String,
+    /// The groups whose members may approve requests for membership of this group
+    #[clap(required = true, num_args(1..))]
+    approvers: Vec<String>,
+    #[clap(flatten)]
+    copt: CommonOpt,
+}
+
+#[derive(Debug, Args)]
+pub struct GroupNamedFilter {
+    name: String,
+    /// The entries that are members of this group, as a JSON filter such as
+    /// '{"and":[{"eq":["class","person"]},{"sub":["mail","@example.com"]}]}'
+    filter: String,
+    #[clap(flatten)]
+    copt: CommonOpt,
+}
+
+#[derive(Debug, Args)]
+pub struct GroupDynamicPreview {
+    /// The JSON filter to preview the membership of
+    filter: String,
+    #[clap(flatten)]
+    copt: CommonOpt,
+}
+
+#[derive(Debug, Args)]
+pub struct GroupNamedMaxDuration {
+    name: String,
+    /// The maximum number of seconds that requested membership may last
+    seconds: u32,
+    #[clap(flatten)]
+    copt: CommonOpt,
+}
+
 #[derive(Debug, Args)]
 pub struct GroupPosixOpt {
     name: String,
@@ -90,6 +153,12 @@ pub enum GroupOpt {
     /// Create a new group
     #[clap(name = "create")]
     Create(Named),
+    /// Create a new dynamic group, whose members are the entries that match a filter
+    #[clap(name = "create-dynamic")]
+    CreateDynamic(GroupNamedFilter),
+    /// Show the entries that would be members of a dynamic group using this filter
+    #[clap(name = "preview-dynamic")]
+    PreviewDynamic(GroupDynamicPreview),
     /// Delete a group
     #[clap(name = "delete")]
     Delete(Named),
@@ -103,12 +172,27 @@ pub enum GroupOpt {
     /// Delete all members of a group.
     #[clap(name = "purge-members")]
     PurgeMembers(Named),
-    /// Add new members to a group
+    /// Add new members to a group, optionally only for a limited period of time
     #[clap(name = "add-members")]
-    AddMembers(GroupNamedMembers),
+    AddMembers(GroupNamedAddMembers),
     /// Remove the named members from this group
     #[clap(name = "remove-members")]
     RemoveMembers(GroupNamedMembers),
+    /// Set the person or group that is delegated the management of this group's members
+    #[clap(name = "set-entry-manager")]
+    SetEntryManager(GroupNamedEntryManager),
+    /// Remove the entry manager of this group
+    #[clap(name = "purge-entry-manager")]
+    PurgeEntryManager(Named),
+    /// Set the groups whose members may approve requests for membership of this group
+    #[clap(name = "set-access-request-approvers")]
+    SetAccessRequestApprovers(GroupNamedApprovers),
+    /// Remove the access request approvers of this group, preventing new requests
+    #[clap(name = "purge-access-request-approvers")]
+    PurgeAccessRequestApprovers(Named),
+    /// Set the maximum duration of requested membership of this group
+    #[clap(name = "set-access-request-max-duration")]
+    SetAccessRequestMaxDuration(GroupNamedMaxDuration),
     /// Manage posix extensions for this group allowing groups to be used on unix/linux systems
     #[clap(name = "posix")]
     Posix {
@@ -158,6 +242,16 @@ pub struct AccountNamedValidDateTimeOpt {
     datetime: String,
 }
 
+#[derive(Debug, Args)]
+pub struct AccountNamedEntryManagerOpt {
+    #[clap(flatten)]
+    aopts: AccountCommonOpt,
+    /// The person or group that will manage this account
+    entry_managed_by: String,
+    #[clap(flatten)]
+    copt: CommonOpt,
+}
+
 #[derive(Debug, Args)]
 pub struct AccountNamedTagOpt {
     #[clap(flatten)]
@@ -221,6 +315,25 @@ pub enum AccountCredential {
         /// Optionally set how many seconds the reset token should be valid for.
         #[clap(long = "ttl")]
         ttl: Option<u32>,
+        /// Mail the reset link to the primary mail address of the person, rather
+        /// than displaying it. The server must have a mail relay configured.
+        #[clap(long = "email")]
+        email: bool,
+    },
+    /// As a member of the helpdesk, create a reset token for a person once they
+    /// have read their identity verification code to you. See `kanidm person identify-user`.
+    #[clap(name = "helpdesk-reset")]
+    HelpdeskReset {
+        #[clap(flatten)]
+        aopts: AccountCommonOpt,
+        #[clap(flatten)]
+        copt: CommonOpt,
+        /// The identity verification code the person read to you.
+        #[clap(name = "code")]
+        code: u32,
+        /// Optionally set how many seconds the reset token should be valid for.
+        #[clap(long = "ttl")]
+        ttl: Option<u64>,
     },
 }
 
@@ -330,6 +443,26 @@ pub enum AccountUserAuthToken {
     },
 }
 
+#[derive(Debug, Subcommand)]
+pub enum PersonOauth2Consent {
+    /// Show the oauth2 applications this person has consented to, or that hold
+    /// sessions for this person.
+    #[clap(name = "list")]
+    List(AccountNamedOpt),
+    /// Withdraw the consent this person gave to an oauth2 application, ending the
+    /// sessions that the application holds for them.
+    #[clap(name = "revoke")]
+    Revoke {
+        #[clap(flatten)]
+        aopts: AccountCommonOpt,
+        #[clap(flatten)]
+        copt: CommonOpt,
+        /// The name of the oauth2 resource server.
+        #[clap(name = "rs-name")]
+        rs_name: String,
+    },
+}
+
 #[derive(Debug, Subcommand)]
 pub enum PersonOpt {
     /// Manage the credentials this person uses for authentication
@@ -356,12 +489,22 @@ pub enum PersonOpt {
         #[clap(subcommand)]
         commands: AccountUserAuthToken,
     },
+    /// Manage the consent this person has given to oauth2 applications.
+    #[clap(name = "consent")]
+    Consent {
+        #[clap(subcommand)]
+        commands: PersonOauth2Consent,
+    },
     /// Manage ssh public key's associated to this person
     #[clap(name = "ssh")]
     Ssh {
         #[clap(subcommand)]
         commands: AccountSsh,
     },
+    /// Verify the identity of another person by exchanging codes with them, such
+    /// as when they contact the helpdesk.
+    #[clap(name = "identify-user")]
+    IdentifyUser(AccountNamedOpt),
     /// List all persons
     #[clap(name = "list")]
     List(CommonOpt),
@@ -433,6 +576,54 @@ pub enum ServiceAccountApiToken {
     },
 }
 
+#[derive(Debug, Subcommand)]
+pub enum ServiceAccountOauth2Registrar {
+    /// Allow this service account to register oauth2 clients that redirect to this domain,
+    /// or any of its subdomains. This makes the service account an oauth2 registrar.
+    #[clap(name = "add-domain")]
+    AddDomain {
+        #[clap(flatten)]
+        aopts: AccountCommonOpt,
+        #[clap(flatten)]
+        copt: CommonOpt,
+        #[clap(name = "domain")]
+        domain: String,
+    },
+    /// Remove a domain that this service account may register oauth2 clients for.
+    #[clap(name = "remove-domain")]
+    RemoveDomain {
+        #[clap(flatten)]
+        aopts: AccountCommonOpt,
+        #[clap(flatten)]
+        copt: CommonOpt,
+        #[clap(name = "domain")]
+        domain: String,
+    },
+    /// Set the scopes that members of a group may be granted by oauth2 clients this
+    /// service account registers.
+    #[clap(name = "update-scope-map", visible_aliases=&["create-scope-map"])]
+    UpdateScopeMap {
+        #[clap(flatten)]
+        aopts: AccountCommonOpt,
+        #[clap(flatten)]
+        copt: CommonOpt,
+        #[clap(name = "group")]
+        group: String,
+        #[clap(name = "scopes")]
+        scopes: Vec<String>,
+    },
+    /// Remove the scopes of a group from oauth2 clients this service account registers.
+    #[clap(name = "delete-scope-map")]
+    DeleteScopeMap {
+        #[clap(flatten)]
+        aopts: AccountCommonOpt,
+        #[clap(flatten)]
+        copt: CommonOpt,
+        #[clap(name = "group")]
+        group: String,
+    },
+}
+
 #[derive(Debug, Args)]
 pub struct ServiceAccountUpdateOpt {
     #[clap(flatten)]
@@ -475,6 +666,12 @@ pub enum ServiceAccountOpt {
         #[clap(subcommand)]
         commands: ServiceAccountPosix,
     },
+    /// Manage the oauth2 clients this service account may dynamically register.
+    #[clap(name = "oauth2-registrar")]
+    Oauth2Registrar {
+        #[clap(subcommand)]
+        commands: ServiceAccountOauth2Registrar,
+    },
     /// Manage sessions (user auth tokens) associated to this service account.
     #[clap(name = "session")]
     Session {
@@ -502,6 +699,12 @@ pub enum ServiceAccountOpt {
     /// Delete a service account
     #[clap(name = "delete")]
     Delete(AccountNamedOpt),
+    /// Set the person or group that is delegated the management of this service account
+    #[clap(name = "set-entry-manager")]
+    SetEntryManager(AccountNamedEntryManagerOpt),
+    /// Remove the entry manager of this service account
+    #[clap(name = "purge-entry-manager")]
+    PurgeEntryManager(AccountNamedOpt),
     /// Manage a service account validity, such as expiry time (account lock/unlock)
     #[clap(name = "validity")]
     Validity {
@@ -525,7 +728,381 @@ pub enum RecycleOpt {
     Get(Named),
     #[clap(name = "revive")]
     /// Revive a recycled object into a live (accessible) state - this is the opposite of "delete"
-    Revive(Named),
+    Revive(RecycleReviveOpt),
+    #[clap(name = "purge")]
+    /// Immediately and permanently remove an object from the recycle bin. It can not
+    /// be revived after this.
+    Purge(Named),
+}
+
+#[derive(Debug, Args)]
+pub struct RecycleReviveOpt {
+    name: String,
+    #[clap(long)]
+    /// Do not restore the group memberships the object had when it was deleted
+    without_members: bool,
+    #[clap(flatten)]
+    copt: CommonOpt,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum RecycleRetentionOpt {
+    #[clap[name = "show"]]
+    /// Show the recycle bin retention of each class that has one configured
+    Show(CommonOpt),
+    #[clap[name = "set"]]
+    /// Set how many seconds recycled objects of a class are kept before they are
+    /// purged. If an object has multiple classes with a retention, the longest applies.
+    Set {
+        #[clap(flatten)]
+        copt: CommonOpt,
+        #[clap(name = "class")]
+        class: String,
+        #[clap(name = "seconds")]
+        seconds: u64,
+    },
+    #[clap[name = "remove"]]
+    /// Remove the retention of a class, so that the default is used
+    Remove {
+        #[clap(flatten)]
+        copt: CommonOpt,
+        #[clap(name = "class")]
+        class: String,
+    },
+}
+
+#[derive(Debug, Args)]
+pub struct WebhookCreateOpt {
+    name: String,
+    /// The url that events are posted to
+    url: String,
+    #[clap(long = "event", required = true)]
+    /// An event to send. One of account_created, account_disabled, account_deleted
+    /// or group_membership. May be repeated.
+    events: Vec<String>,
+    #[clap(long)]
+    /// Only send events about entries that match this json filter
+    filter: Option<String>,
+    #[clap(flatten)]
+    copt: CommonOpt,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum WebhookOpt {
+    #[clap(name = "list")]
+    /// List the configured webhooks
+    List(CommonOpt),
+    #[clap(name = "get")]
+    /// Display a webhook
+    Get(Named),
+    #[clap(name = "create")]
+    /// Create a webhook that is sent events about changes to accounts and groups
+    Create(WebhookCreateOpt),
+    #[clap(name = "set-url")]
+    /// Change the url that events are posted to
+    SetUrl {
+        #[clap(flatten)]
+        nopt: Named,
+        url: String,
+    },
+    #[clap(name = "delete")]
+    /// Delete a webhook
+    Delete(Named),
+    #[clap(name = "show-secret")]
+    /// Show the secret used to sign the events sent to a webhook
+    ShowSecret(Named),
+    #[clap(name = "reset-secret")]
+    /// Replace the secret of a webhook with a newly generated one
+    ResetSecret(Named),
+    #[clap(name = "dead-letters")]
+    /// Show the events that could not be delivered to a webhook
+    DeadLetters(Named),
+    #[clap(name = "clear-dead-letters")]
+    /// Discard the events that could not be delivered to a webhook
+    ClearDeadLetters(Named),
+    #[clap(name = "retry")]
+    /// Attempt to deliver the events that could not be delivered to a webhook again
+    Retry(Named),
+}
+
+#[derive(Debug, Args)]
+pub struct SchemaAttrCreateOpt {
+    name: String,
+    description: String,
+    #[clap(long)]
+    /// The syntax of the attribute, such as utf8string, utf8string_insensitive,
+    /// boolean, uint32, datetime, email_address, url or reference_uuid
+    syntax: String,
+    #[clap(long)]
+    /// Allow the attribute to hold multiple values
+    multivalue: bool,
+    #[clap(long)]
+    /// Values of this attribute must be unique across all entries
+    unique: bool,
+    #[clap(long, requires = "unique")]
+    /// Only require values to be unique amongst entries of this class
+    unique_scope: Option<String>,
+    #[clap(long)]
+    /// Index types for this attribute, such as equality, presence or substring
+    index: Vec<String>,
+    #[clap(flatten)]
+    copt: CommonOpt,
+}
+
+#[derive(Debug, Args)]
+pub struct SchemaAttrUpdateOpt {
+    name: String,
+    #[clap(long)]
+    description: Option<String>,
+    #[clap(long)]
+    multivalue: Option<bool>,
+    #[clap(long)]
+    unique: Option<bool>,
+    #[clap(long)]
+    /// Only require values to be unique amongst entries of this class. Provide an empty
+    /// value to require values to be unique across all entries.
+    unique_scope: Option<String>,
+    #[clap(long, num_args(0..))]
+    /// Replace the index types of this attribute. Provide no values to remove all indexes.
+    index: Option<Vec<String>>,
+    #[clap(flatten)]
+    copt: CommonOpt,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum SchemaAttrOpt {
+    #[clap(name = "list")]
+    /// List all schema attributes
+    List(CommonOpt),
+    #[clap(name = "get")]
+    /// View a single schema attribute
+    Get(Named),
+    #[clap(name = "create")]
+    /// Create a new schema attribute
+    Create(SchemaAttrCreateOpt),
+    #[clap(name = "update")]
+    /// Update a schema attribute. Changes that would invalidate existing entries are rejected.
+    Update(SchemaAttrUpdateOpt),
+    #[clap(name = "deprecate")]
+    /// Deprecate a schema attribute. Existing values are retained, but new values can not be added.
+    Deprecate(Named),
+    #[clap(name = "undeprecate")]
+    /// Allow values to be added to a deprecated schema attribute again
+    Undeprecate(Named),
+}
+
+#[derive(Debug, Args)]
+pub struct SchemaClassCreateOpt {
+    name: String,
+    description: String,
+    #[clap(long)]
+    /// Attributes that entries of this class may have
+    may: Vec<String>,
+    #[clap(long)]
+    /// Attributes that entries of this class must have
+    must: Vec<String>,
+    #[clap(flatten)]
+    copt: CommonOpt,
+}
+
+#[derive(Debug, Args)]
+pub struct SchemaClassAttrsOpt {
+    name: String,
+    #[clap(required = true, num_args(1..))]
+    attrs: Vec<String>,
+    #[clap(flatten)]
+    copt: CommonOpt,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum SchemaClassOpt {
+    #[clap(name = "list")]
+    /// List all schema classes
+    List(CommonOpt),
+    #[clap(name = "get")]
+    /// View a single schema class
+    Get(Named),
+    #[clap(name = "create")]
+    /// Create a new schema class
+    Create(SchemaClassCreateOpt),
+    #[clap(name = "add-may")]
+    /// Add attributes that entries of this class may have
+    AddMay(SchemaClassAttrsOpt),
+    #[clap(name = "remove-may")]
+    /// Remove attributes that entries of this class may have
+    RemoveMay(SchemaClassAttrsOpt),
+    #[clap(name = "add-must")]
+    /// Add attributes that entries of this class must have
+    AddMust(SchemaClassAttrsOpt),
+    #[clap(name = "remove-must")]
+    /// Remove attributes that entries of this class must have
+    RemoveMust(SchemaClassAttrsOpt),
+    #[clap(name = "deprecate")]
+    /// Deprecate a schema class. Existing entries retain the class, but it can not be added to entries.
+    Deprecate(Named),
+    #[clap(name = "undeprecate")]
+    /// Allow a deprecated schema class to be added to entries again
+    Undeprecate(Named),
+}
+
+#[derive(Debug, Subcommand)]
+pub enum SchemaOpt {
+    #[clap(name = "attribute")]
+    /// Manage schema attributes
+    Attribute {
+        #[clap(subcommand)]
+        commands: SchemaAttrOpt,
+    },
+    #[clap(name = "class")]
+    /// Manage schema classes
+    Class {
+        #[clap(subcommand)]
+        commands: SchemaClassOpt,
+    },
+}
+
+#[derive(Debug, Args)]
+pub struct AccessProfileCreateOpt {
+    name: String,
+    /// The name or uuid of the group that is granted this access
+    receiver_group: String,
+    /// The entries this access applies to, as a JSON filter such as '{"eq":["class","person"]}'
+    targetscope: String,
+    #[clap(long)]
+    description: Option<String>,
+    #[clap(long)]
+    /// Create the profile in a disabled state
+    disabled: bool,
+    #[clap(long)]
+    /// Attributes that may be searched and read
+    search_attr: Vec<String>,
+    #[clap(long)]
+    /// Attributes that may have values added
+    modify_present_attr: Vec<String>,
+    #[clap(long)]
+    /// Attributes that may have values removed
+    modify_removed_attr: Vec<String>,
+    #[clap(long)]
+    /// Classes that may be added to or removed from entries
+    modify_class: Vec<String>,
+    #[clap(long)]
+    /// Attributes that may be set on created entries
+    create_attr: Vec<String>,
+    #[clap(long)]
+    /// Classes that created entries may have
+    create_class: Vec<String>,
+    #[clap(long)]
+    /// Allow entries to be deleted
+    delete: bool,
+    #[clap(flatten)]
+    copt: CommonOpt,
+}
+
+#[derive(Debug, Args)]
+pub struct AccessProfileUpdateOpt {
+    name: String,
+    #[clap(long)]
+    description: Option<String>,
+    #[clap(long)]
+    enabled: Option<bool>,
+    #[clap(long)]
+    receiver_group: Option<String>,
+    #[clap(long)]
+    /// The entries this access applies to, as a JSON filter
+    targetscope: Option<String>,
+    #[clap(long, num_args(0..))]
+    /// Replace the attributes that may be searched. Provide no values to remove search access.
+    search_attr: Option<Vec<String>>,
+    #[clap(long, num_args(0..))]
+    modify_present_attr: Option<Vec<String>>,
+    #[clap(long, num_args(0..))]
+    modify_removed_attr: Option<Vec<String>>,
+    #[clap(long, num_args(0..))]
+    modify_class: Option<Vec<String>>,
+    #[clap(long, num_args(0..))]
+    create_attr: Option<Vec<String>>,
+    #[clap(long, num_args(0..))]
+    create_class: Option<Vec<String>>,
+    #[clap(long)]
+    delete: Option<bool>,
+    #[clap(flatten)]
+    copt: CommonOpt,
+}
+
+#[derive(Debug, Args)]
+pub struct AccessProfileCheckOpt {
+    /// The name or uuid of the account whose access is checked
+    subject: String,
+    /// The name or uuid of the entry being accessed
+    target: String,
+    #[clap(flatten)]
+    copt: CommonOpt,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum AccessProfileOpt {
+    #[clap(name = "list")]
+    /// List all access control profiles
+    List(CommonOpt),
+    #[clap(name = "get")]
+    /// View a single access control profile
+    Get(Named),
+    #[clap(name = "create")]
+    /// Create a new access control profile
+    Create(AccessProfileCreateOpt),
+    #[clap(name = "update")]
+    /// Update an access control profile. Only the provided options are changed.
+    Update(AccessProfileUpdateOpt),
+    #[clap(name = "delete")]
+    /// Delete an access control profile
+    Delete(Named),
+    #[clap(name = "check")]
+    /// Show what access an account is granted to an entry
+    Check(AccessProfileCheckOpt),
+}
+
+#[derive(Debug, Args)]
+pub struct AccessRequestIdOpt {
+    /// The uuid of the access request
+    id: String,
+    #[clap(flatten)]
+    copt: CommonOpt,
+}
+
+#[derive(Debug, Args)]
+pub struct AccessRequestCreateOpt {
+    /// The name or uuid of the group that membership is requested of
+    group: String,
+    #[clap(long)]
+    /// Why the membership is needed
+    reason: Option<String>,
+    #[clap(long)]
+    /// How many seconds the membership is needed for. Defaults to the maximum the group allows.
+    duration: Option<u32>,
+    #[clap(flatten)]
+    copt: CommonOpt,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum AccessRequestOpt {
+    #[clap(name = "list")]
+    /// List your access requests, and the requests that you may approve
+    List(CommonOpt),
+    #[clap(name = "get")]
+    /// View a single access request
+    Get(AccessRequestIdOpt),
+    #[clap(name = "create")]
+    /// Request time limited membership of a group
+    Create(AccessRequestCreateOpt),
+    #[clap(name = "approve")]
+    /// Approve an access request, granting the requested membership
+    Approve(AccessRequestIdOpt),
+    #[clap(name = "deny")]
+    /// Deny an access request
+    Deny(AccessRequestIdOpt),
+    #[clap(name = "withdraw")]
+    /// Withdraw one of your pending access requests
+    Withdraw(AccessRequestIdOpt),
 }
 
 #[derive(Debug, Args)]
@@ -640,6 +1217,14 @@ pub struct Oauth2DeleteScopeMapOpt {
     group: String,
 }
 
+#[derive(Debug, Args)]
+pub struct Oauth2TokenExchangeSourceOpt {
+    #[clap(flatten)]
+    nopt: Named,
+    #[clap(name = "source")]
+    source: String,
+}
+
 #[derive(Debug, Subcommand)]
 pub enum Oauth2Opt {
     #[clap(name = "list")]
@@ -692,6 +1277,12 @@ pub enum Oauth2Opt {
     #[clap(name = "delete-sup-scope-map")]
     /// Remove a mapping from groups to scopes
     DeleteSupScopeMap(Oauth2DeleteScopeMapOpt),
+    #[clap(name = "add-token-exchange-source")]
+    /// Allow another resource server to exchange its access tokens for tokens of this one
+    AddTokenExchangeSource(Oauth2TokenExchangeSourceOpt),
+    #[clap(name = "delete-token-exchange-source")]
+    /// Stop another resource server from exchanging its access tokens for tokens of this one
+    DeleteTokenExchangeSource(Oauth2TokenExchangeSourceOpt),
 
     #[clap(name = "reset-secrets")]
     /// Reset the secrets associated to this resource server
@@ -724,6 +1315,42 @@ pub enum Oauth2Opt {
         #[clap(name = "landing-url")]
         url: String,
     },
+    /// Set the url that back-channel logout tokens are posted to when a user's session
+    /// with this resource server ends.
+    #[clap(name = "set-backchannel-logout-url")]
+    SetBackchannelLogoutUrl {
+        #[clap(flatten)]
+        nopt: Named,
+        #[clap(name = "logout-url")]
+        url: String,
+    },
+    /// Stop sending back-channel logout tokens to this resource server.
+    #[clap(name = "reset-backchannel-logout-url")]
+    ResetBackchannelLogoutUrl(Named),
+    /// Set the json web key set (JWKS) this resource server signs its client assertions
+    /// with. Once set, the resource server can no longer authenticate with its basic secret.
+    #[clap(name = "set-jwks")]
+    SetJwks {
+        #[clap(flatten)]
+        nopt: Named,
+        #[clap(value_parser)]
+        path: PathBuf,
+    },
+    /// Remove the json web key set from this resource server.
+    #[clap(name = "reset-jwks")]
+    ResetJwks(Named),
+    /// Set the PEM encoded tls client certificates this resource server may authenticate
+    /// with. Once set, the resource server can no longer authenticate with its basic secret.
+    #[clap(name = "set-tls-client-certs")]
+    SetTlsClientCerts {
+        #[clap(flatten)]
+        nopt: Named,
+        #[clap(value_parser, required = true, num_args(1..))]
+        paths: Vec<PathBuf>,
+    },
+    /// Remove all tls client certificates from this resource server.
+    #[clap(name = "reset-tls-client-certs")]
+    ResetTlsClientCerts(Named),
     #[clap(name = "enable-pkce")]
     /// Enable PKCE on this oauth2 resource server. This defaults to being enabled.
     EnablePkce(Named),
@@ -745,6 +1372,22 @@ pub enum Oauth2Opt {
     #[clap(name = "prefer-spn-username")]
     /// Use the 'spn' attribute instead of 'name' for the preferred_username
     PreferSPNUsername(Named),
+    #[clap(name = "require-pushed-authorisation")]
+    /// Require this oauth2 resource server to push its authorisation requests to the
+    /// server before redirecting the user, so that they can't be tampered with.
+    RequirePushedAuthorisation(Named),
+    #[clap(name = "allow-direct-authorisation")]
+    /// Allow this oauth2 resource server to send authorisation requests directly through
+    /// the user's browser. This is the default.
+    AllowDirectAuthorisation(Named),
+    #[clap(name = "disable-consent-prompt")]
+    /// Treat this oauth2 resource server as a first party application that users have
+    /// already consented to, so that they are not asked for consent.
+    DisableConsentPrompt(Named),
+    #[clap(name = "enable-consent-prompt")]
+    /// Ask users to consent before this oauth2 resource server can access their
+    /// account. This is the default.
+    EnableConsentPrompt(Named),
 }
 
 #[derive(Args, Debug)]
@@ -784,6 +1427,23 @@ pub enum PwBadlistOpt {
     },
 }
 
+#[derive(Debug, Subcommand)]
+pub enum PwHistoryOpt {
+    #[clap[name = "show"]]
+    /// Show how many previous passwords are remembered per account
+    Show(CommonOpt),
+    #[clap[name = "set-length"]]
+    /// Set how many previous passwords are remembered per account. Accounts
+    /// will be unable to reuse any remembered password. A value of 0
+    /// disables password history.
+    SetLength {
+        #[clap(flatten)]
+        copt: CommonOpt,
+        #[clap(name = "length")]
+        length: u32,
+    },
+}
+
 #[derive(Debug, Subcommand)]
 pub enum DomainOpt {
     #[clap[name = "set-display-name"]]
@@ -912,6 +1572,24 @@ pub enum SystemOpt {
         #[clap(subcommand)]
         commands: PwBadlistOpt,
     },
+    #[clap(name = "pw-history")]
+    /// Configure how many previous passwords are remembered to prevent reuse
+    PwHistory {
+        #[clap(subcommand)]
+        commands: PwHistoryOpt,
+    },
+    #[clap(name = "recycle-bin-retention")]
+    /// Configure how long recycled objects of each class are kept
+    RecycleBinRetention {
+        #[clap(subcommand)]
+        commands: RecycleRetentionOpt,
+    },
+    #[clap(name = "webhook")]
+    /// Configure webhooks that are notified of changes to accounts and groups
+    Webhook {
+        #[clap(subcommand)]
+        commands: WebhookOpt,
+    },
     #[clap(name = "oauth2")]
     /// Configure and display oauth2/oidc resource server configuration
     Oauth2 {
@@ -973,6 +1651,23 @@ pub enum KanidmClientOpt {
         #[clap(subcommand)]
         commands: SystemOpt,
     },
+    /// Manage the schema of attributes and classes
+    Schema {
+        #[clap(subcommand)]
+        commands: SchemaOpt,
+    },
+    #[clap(name = "access-profile")]
+    /// Manage access control profiles
+    AccessProfile {
+        #[clap(subcommand)]
+        commands: AccessProfileOpt,
+    },
+    #[clap(name = "access-request")]
+    /// Request, approve and deny time limited group memberships
+    AccessRequest {
+        #[clap(subcommand)]
+        commands: AccessRequestOpt,
+    },
     #[clap(name = "recycle-bin")]
     /// Recycle Bin operations
     Recycle {